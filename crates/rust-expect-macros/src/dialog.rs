@@ -6,19 +6,32 @@
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::parse::{Parse, ParseStream};
-use syn::punctuated::Punctuated;
-use syn::{Expr, Ident, LitStr, Result, Token, braced};
+use syn::{Expr, Ident, LitChar, LitInt, LitStr, Result, Token, braced, bracketed};
+
+use crate::timeout::{self, TimeoutInput};
 
 /// A single step in a dialog.
 pub enum DialogStep {
     /// Send data to the terminal.
     Send(SendStep),
+    /// Send a control character, such as Ctrl+C.
+    SendControl(SendControlStep),
+    /// Send raw bytes.
+    SendBytes(SendBytesStep),
     /// Expect output from the terminal.
     Expect(ExpectStep),
     /// Wait for a duration.
     Wait(WaitStep),
     /// Set a timeout for subsequent operations.
     Timeout(TimeoutStep),
+    /// Forbid a pattern during subsequent expects.
+    Forbid(ForbidStep),
+    /// Run the sub-script of whichever pattern appears first.
+    IfMatch(IfMatchStep),
+    /// Expect a regex and store its first capture group in a variable.
+    Capture(CaptureStep),
+    /// Retry a sub-script until a pattern appears.
+    Repeat(RepeatStep),
 }
 
 /// A send operation.
@@ -29,6 +42,60 @@ pub struct SendStep {
     pub newline: bool,
 }
 
+/// A control character send, such as `ctrl 'c'`.
+pub struct SendControlStep {
+    /// The `ControlChar` variant to send.
+    pub variant: Ident,
+}
+
+impl Parse for SendControlStep {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ch: LitChar = input.parse()?;
+        let variant = match ch.value().to_ascii_lowercase() {
+            c @ 'a'..='z' => format!("Ctrl{}", c.to_ascii_uppercase()),
+            '[' => "Escape".to_string(),
+            '\\' => "CtrlBackslash".to_string(),
+            ']' => "CtrlBracket".to_string(),
+            '^' => "CtrlCaret".to_string(),
+            '_' => "CtrlUnderscore".to_string(),
+            other => {
+                return Err(syn::Error::new(
+                    ch.span(),
+                    format!("no control character for {other:?}"),
+                ));
+            }
+        };
+        Ok(Self {
+            variant: Ident::new(&variant, ch.span()),
+        })
+    }
+}
+
+/// A raw bytes send, such as `sendbytes [0x1b, 0x5b]`.
+pub struct SendBytesStep {
+    /// The bytes to send.
+    pub bytes: Vec<u8>,
+}
+
+impl Parse for SendBytesStep {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        let brackets = bracketed!(content in input);
+        let literals = content.parse_terminated(LitInt::parse, Token![,])?;
+        if literals.is_empty() {
+            return Err(syn::Error::new(
+                brackets.span.join(),
+                "sendbytes needs at least one byte",
+            ));
+        }
+        let bytes = literals
+            .iter()
+            .map(LitInt::base10_parse::<u8>)
+            .collect::<Result<_>>()?;
+        Ok(Self { bytes })
+    }
+}
+
 /// An expect operation.
 pub struct ExpectStep {
     /// The pattern to match.
@@ -37,6 +104,44 @@ pub struct ExpectStep {
     pub is_regex: bool,
     /// Optional timeout override.
     pub timeout: Option<Expr>,
+    /// Step to run if the pattern does not appear in time.
+    pub on_timeout: Option<Box<DialogStep>>,
+}
+
+impl ExpectStep {
+    /// Parse what follows the pattern: `, timeout`, or `within 5s`,
+    /// optionally followed by `else step`.
+    fn parse_tail(pattern: LitStr, is_regex: bool, input: ParseStream) -> Result<Self> {
+        let timeout = if input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+            Some(input.parse()?)
+        } else if input.peek(Ident) && input.fork().parse::<Ident>()? == "within" {
+            let _: Ident = input.parse()?;
+            let duration: TimeoutInput = input.parse()?;
+            Some(syn::parse2(timeout::expand(duration))?)
+        } else {
+            None
+        };
+        let on_timeout = if input.peek(Token![else]) {
+            let keyword: Token![else] = input.parse()?;
+            let step: DialogStep = input.parse()?;
+            if matches!(step, DialogStep::Timeout(_)) {
+                return Err(syn::Error::new(
+                    keyword.span,
+                    "else needs a step to run, not a timeout",
+                ));
+            }
+            Some(Box::new(step))
+        } else {
+            None
+        };
+        Ok(Self {
+            pattern,
+            is_regex,
+            timeout,
+            on_timeout,
+        })
+    }
 }
 
 /// A wait operation.
@@ -51,6 +156,140 @@ pub struct TimeoutStep {
     pub duration: Expr,
 }
 
+/// A forbidden pattern registration.
+pub struct ForbidStep {
+    /// The pattern that must not appear.
+    pub pattern: LitStr,
+}
+
+/// A regex capture into a named variable.
+pub struct CaptureStep {
+    /// The variable to store the capture in.
+    pub var: Ident,
+    /// The regex to match; group 1 is stored.
+    pub pattern: LitStr,
+    /// Optional timeout override.
+    pub timeout: Option<Expr>,
+}
+
+impl Parse for CaptureStep {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let var: Ident = input.parse()?;
+        let from: Ident = input.parse()?;
+        if from != "from_re" {
+            return Err(syn::Error::new(from.span(), "expected `from_re`"));
+        }
+        let pattern: LitStr = input.parse()?;
+        // Validate regex at compile time
+        match regex::Regex::new(&pattern.value()) {
+            Ok(re) if re.captures_len() > 1 => {}
+            Ok(_) => {
+                return Err(syn::Error::new(
+                    pattern.span(),
+                    "capture regex needs a capture group",
+                ));
+            }
+            Err(e) => {
+                return Err(syn::Error::new(
+                    pattern.span(),
+                    format!("invalid regex: {e}"),
+                ));
+            }
+        }
+        let timeout = if input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self {
+            var,
+            pattern,
+            timeout,
+        })
+    }
+}
+
+/// A sub-script retried until a pattern appears.
+pub struct RepeatStep {
+    /// The pattern that ends the repeat.
+    pub until: LitStr,
+    /// The most attempts to make.
+    pub max_attempts: LitInt,
+    /// The steps to run on each attempt.
+    pub body: DialogInput,
+}
+
+impl Parse for RepeatStep {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let until: Ident = input.parse()?;
+        if until != "until" {
+            return Err(syn::Error::new(until.span(), "expected `until`"));
+        }
+        let pattern: LitStr = input.parse()?;
+        let max: Ident = input.parse()?;
+        if max != "max" {
+            return Err(syn::Error::new(max.span(), "expected `max`"));
+        }
+        let max_attempts: LitInt = input.parse()?;
+        if max_attempts.base10_parse::<u32>()? == 0 {
+            return Err(syn::Error::new(
+                max_attempts.span(),
+                "repeat needs at least one attempt",
+            ));
+        }
+        let body;
+        braced!(body in input);
+        Ok(Self {
+            until: pattern,
+            max_attempts,
+            body: body.parse()?,
+        })
+    }
+}
+
+/// A branch on which pattern appears first.
+pub struct IfMatchStep {
+    /// The arms, in the order they are tried.
+    pub arms: Vec<IfMatchArm>,
+}
+
+/// One arm of an `if_match` block.
+pub struct IfMatchArm {
+    /// The pattern that selects this arm.
+    pub pattern: LitStr,
+    /// The steps to run when it matches.
+    pub body: DialogInput,
+}
+
+impl Parse for IfMatchStep {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        let brace = braced!(content in input);
+        let mut arms = Vec::new();
+        while !content.is_empty() {
+            let pattern: LitStr = content.parse()?;
+            let _: Token![=>] = content.parse()?;
+            let body;
+            braced!(body in content);
+            arms.push(IfMatchArm {
+                pattern,
+                body: body.parse()?,
+            });
+            if content.peek(Token![,]) {
+                let _: Token![,] = content.parse()?;
+            }
+        }
+        if arms.is_empty() {
+            return Err(syn::Error::new(
+                brace.span.join(),
+                "if_match needs at least one arm",
+            ));
+        }
+        Ok(Self { arms })
+    }
+}
+
 impl Parse for DialogStep {
     fn parse(input: ParseStream) -> Result<Self> {
         let keyword: Ident = input.parse()?;
@@ -70,19 +309,11 @@ impl Parse for DialogStep {
                     newline: true,
                 }))
             }
+            "ctrl" => Ok(Self::SendControl(input.parse()?)),
+            "sendbytes" | "send_bytes" => Ok(Self::SendBytes(input.parse()?)),
             "expect" => {
                 let pattern: LitStr = input.parse()?;
-                let timeout = if input.peek(Token![,]) {
-                    let _: Token![,] = input.parse()?;
-                    Some(input.parse()?)
-                } else {
-                    None
-                };
-                Ok(Self::Expect(ExpectStep {
-                    pattern,
-                    is_regex: false,
-                    timeout,
-                }))
+                Ok(Self::Expect(ExpectStep::parse_tail(pattern, false, input)?))
             }
             "expect_re" | "expect_regex" => {
                 let pattern: LitStr = input.parse()?;
@@ -94,17 +325,7 @@ impl Parse for DialogStep {
                         format!("invalid regex: {e}"),
                     ));
                 }
-                let timeout = if input.peek(Token![,]) {
-                    let _: Token![,] = input.parse()?;
-                    Some(input.parse()?)
-                } else {
-                    None
-                };
-                Ok(Self::Expect(ExpectStep {
-                    pattern,
-                    is_regex: true,
-                    timeout,
-                }))
+                Ok(Self::Expect(ExpectStep::parse_tail(pattern, true, input)?))
             }
             "wait" | "sleep" => {
                 let duration: Expr = input.parse()?;
@@ -114,6 +335,13 @@ impl Parse for DialogStep {
                 let duration: Expr = input.parse()?;
                 Ok(Self::Timeout(TimeoutStep { duration }))
             }
+            "forbid" => {
+                let pattern: LitStr = input.parse()?;
+                Ok(Self::Forbid(ForbidStep { pattern }))
+            }
+            "if_match" => Ok(Self::IfMatch(input.parse()?)),
+            "capture" => Ok(Self::Capture(input.parse()?)),
+            "repeat" => Ok(Self::Repeat(input.parse()?)),
             other => Err(syn::Error::new(
                 keyword.span(),
                 format!("unknown dialog command: {other}"),
@@ -125,82 +353,157 @@ impl Parse for DialogStep {
 /// The dialog! macro input.
 pub struct DialogInput {
     /// The steps in the dialog.
-    pub steps: Punctuated<DialogStep, Token![;]>,
+    pub steps: Vec<DialogStep>,
 }
 
 impl Parse for DialogInput {
     fn parse(input: ParseStream) -> Result<Self> {
         // Handle braced or unbraced syntax
-        let steps = if input.peek(syn::token::Brace) {
+        if input.peek(syn::token::Brace) {
             let content;
             braced!(content in input);
-            Punctuated::parse_terminated(&content)?
-        } else {
-            Punctuated::parse_terminated(input)?
-        };
+            return Self::parse_steps(&content);
+        }
+        Self::parse_steps(input)
+    }
+}
 
+impl DialogInput {
+    /// Parse `;`-separated steps; the `;` after an `if_match` or `repeat`
+    /// block is optional.
+    fn parse_steps(input: ParseStream) -> Result<Self> {
+        let mut steps = Vec::new();
+        while !input.is_empty() {
+            let step: DialogStep = input.parse()?;
+            let block = matches!(step, DialogStep::IfMatch(_) | DialogStep::Repeat(_));
+            steps.push(step);
+            if input.peek(Token![;]) {
+                let _: Token![;] = input.parse()?;
+            } else if !block && !input.is_empty() {
+                return Err(input.error("expected `;` between dialog steps"));
+            }
+        }
         Ok(Self { steps })
     }
 }
 
 /// Generate code for the dialog! macro.
+///
+/// Each command becomes an unnamed `DialogStep`, executed in order. A
+/// `timeout` command emits no step; it applies to every following expect
+/// that does not override it, including those inside `if_match` arms and
+/// `repeat` bodies. A `timeout` inside a block only applies within it.
 pub fn expand(input: DialogInput) -> TokenStream {
-    let steps: Vec<_> = input
-        .steps
-        .into_iter()
-        .map(|step| match step {
+    let steps = expand_steps(input.steps, None);
+    quote! {
+        rust_expect::dialog::Dialog::new()#(.step(#steps))*
+    }
+}
+
+/// Generate one `DialogStep` expression per step.
+fn expand_steps(input: Vec<DialogStep>, mut current_timeout: Option<Expr>) -> Vec<TokenStream> {
+    let mut steps = Vec::new();
+
+    for step in input {
+        match step {
             DialogStep::Send(send) => {
                 let data = &send.data;
-                if send.newline {
-                    quote! {
-                        rust_expect::dialog::DialogStep::SendLine(#data.to_string())
-                    }
+                let constructor = if send.newline {
+                    quote! { send_line }
                 } else {
-                    quote! {
-                        rust_expect::dialog::DialogStep::Send(#data.to_string())
-                    }
-                }
+                    quote! { send }
+                };
+                steps.push(quote! {
+                    rust_expect::dialog::DialogStep::#constructor(#data)
+                });
+            }
+            DialogStep::SendControl(ctrl) => {
+                let variant = &ctrl.variant;
+                steps.push(quote! {
+                    rust_expect::dialog::DialogStep::control(rust_expect::ControlChar::#variant)
+                });
+            }
+            DialogStep::SendBytes(send) => {
+                let bytes = &send.bytes;
+                steps.push(quote! {
+                    rust_expect::dialog::DialogStep::send_bytes([#(#bytes),*])
+                });
             }
             DialogStep::Expect(expect) => {
                 let pattern = &expect.pattern;
-                let timeout = expect
-                    .timeout
-                    .map_or_else(|| quote! { None }, |t| quote! { Some(#t) });
-
-                if expect.is_regex {
-                    quote! {
-                        rust_expect::dialog::DialogStep::ExpectRegex {
-                            pattern: #pattern.to_string(),
-                            timeout: #timeout,
-                        }
-                    }
+                let step = if expect.is_regex {
+                    quote! { rust_expect::dialog::DialogStep::expect_regex(#pattern) }
                 } else {
-                    quote! {
-                        rust_expect::dialog::DialogStep::Expect {
-                            pattern: #pattern.to_string(),
-                            timeout: #timeout,
-                        }
+                    quote! { rust_expect::dialog::DialogStep::expect(#pattern) }
+                };
+                let step = match expect.timeout.as_ref().or(current_timeout.as_ref()) {
+                    Some(t) => quote! { #step.timeout(#t) },
+                    None => step,
+                };
+                match expect.on_timeout {
+                    Some(fallback) => {
+                        let fallback = expand_steps(vec![*fallback], current_timeout.clone());
+                        steps.push(quote! { #step #(.on_timeout(#fallback))* });
                     }
+                    None => steps.push(step),
+                }
+            }
+            DialogStep::Capture(capture) => {
+                let var = capture.var.to_string();
+                let pattern = &capture.pattern;
+                let step = quote! { rust_expect::dialog::DialogStep::capture(#var, #pattern) };
+                match capture.timeout.as_ref().or(current_timeout.as_ref()) {
+                    Some(t) => steps.push(quote! { #step.timeout(#t) }),
+                    None => steps.push(step),
+                }
+            }
+            DialogStep::Repeat(repeat) => {
+                let until = &repeat.until;
+                let max_attempts = &repeat.max_attempts;
+                let body = expand_steps(repeat.body.steps, current_timeout.clone());
+                let step = quote! {
+                    rust_expect::dialog::DialogStep::repeat(#until, #max_attempts, [#(#body),*])
+                };
+                match current_timeout.as_ref() {
+                    Some(t) => steps.push(quote! { #step.timeout(#t) }),
+                    None => steps.push(step),
                 }
             }
             DialogStep::Wait(wait) => {
                 let duration = &wait.duration;
-                quote! {
-                    rust_expect::dialog::DialogStep::Wait(#duration)
-                }
+                steps.push(quote! {
+                    rust_expect::dialog::DialogStep::wait(#duration)
+                });
             }
             DialogStep::Timeout(timeout) => {
-                let duration = &timeout.duration;
-                quote! {
-                    rust_expect::dialog::DialogStep::SetTimeout(#duration)
+                current_timeout = Some(timeout.duration);
+            }
+            DialogStep::Forbid(forbid) => {
+                let pattern = &forbid.pattern;
+                steps.push(quote! {
+                    rust_expect::dialog::DialogStep::forbid(#pattern)
+                });
+            }
+            DialogStep::IfMatch(if_match) => {
+                let arms = if_match.arms.into_iter().map(|arm| {
+                    let pattern = arm.pattern;
+                    let body = expand_steps(arm.body.steps, current_timeout.clone());
+                    quote! {
+                        rust_expect::dialog::DialogBranch::new(#pattern, [#(#body),*])
+                    }
+                });
+                let step = quote! {
+                    rust_expect::dialog::DialogStep::if_match([#(#arms),*])
+                };
+                match current_timeout.as_ref() {
+                    Some(t) => steps.push(quote! { #step.timeout(#t) }),
+                    None => steps.push(step),
                 }
             }
-        })
-        .collect();
-
-    quote! {
-        rust_expect::dialog::Dialog::new(vec![#(#steps),*])
+        }
     }
+
+    steps
 }
 
 #[cfg(test)]
@@ -217,4 +520,159 @@ mod tests {
         };
         assert_eq!(input.steps.len(), 2);
     }
+
+    #[test]
+    fn parse_forbid_step() {
+        let input: DialogInput = parse_quote! {
+            forbid "Permission denied";
+            expect "$ "
+        };
+        assert_eq!(input.steps.len(), 2);
+        assert!(matches!(input.steps[0], DialogStep::Forbid(_)));
+    }
+
+    #[test]
+    fn parse_if_match_block() {
+        let input: DialogInput = parse_quote! {
+            if_match {
+                "(yes/no)?" => { sendln "yes"; expect "password:" },
+                "password:" => {}
+            }
+            sendln "secret"
+        };
+        assert_eq!(input.steps.len(), 2);
+        let DialogStep::IfMatch(if_match) = &input.steps[0] else {
+            panic!("expected an if_match step");
+        };
+        assert_eq!(if_match.arms.len(), 2);
+        assert_eq!(if_match.arms[0].body.steps.len(), 2);
+        assert!(if_match.arms[1].body.steps.is_empty());
+    }
+
+    #[test]
+    fn parse_repeat_block() {
+        let input: DialogInput = parse_quote! {
+            repeat until "prompt>" max 3 {
+                expect "password:";
+                sendln "secret"
+            }
+            sendln "show version"
+        };
+        assert_eq!(input.steps.len(), 2);
+        let DialogStep::Repeat(repeat) = &input.steps[0] else {
+            panic!("expected a repeat step");
+        };
+        assert_eq!(repeat.until.value(), "prompt>");
+        assert_eq!(repeat.max_attempts.base10_parse::<u32>().unwrap(), 3);
+        assert_eq!(repeat.body.steps.len(), 2);
+    }
+
+    #[test]
+    fn parse_rejects_zero_repeat_attempts() {
+        let result: Result<DialogInput> =
+            syn::parse2(quote! { repeat until "prompt>" max 0 { sendln "x" } });
+        assert!(result.is_err());
+        let result: Result<DialogInput> = syn::parse2(quote! { repeat "prompt>" max 3 {} });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_expect_within_else() {
+        let input: DialogInput = parse_quote! {
+            expect "login:" within 5s else sendln "";
+            expect_re r"\$ " within 1 m + 30 s
+        };
+        assert_eq!(input.steps.len(), 2);
+        let DialogStep::Expect(expect) = &input.steps[0] else {
+            panic!("expected an expect step");
+        };
+        assert!(expect.timeout.is_some());
+        assert!(matches!(
+            expect.on_timeout.as_deref(),
+            Some(DialogStep::Send(_))
+        ));
+        let DialogStep::Expect(expect) = &input.steps[1] else {
+            panic!("expected an expect step");
+        };
+        assert!(expect.timeout.is_some());
+        assert!(expect.on_timeout.is_none());
+    }
+
+    #[test]
+    fn parse_rejects_timeout_as_fallback() {
+        let result: Result<DialogInput> =
+            syn::parse2(quote! { expect "x" within 5s else timeout std::time::Duration::ZERO });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_rejects_empty_if_match() {
+        let result: Result<DialogInput> = syn::parse2(quote! { if_match {} });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_capture_step() {
+        let input: DialogInput = parse_quote! {
+            capture token from_re r"token: (\w+)";
+            sendln "use ${token}"
+        };
+        let DialogStep::Capture(capture) = &input.steps[0] else {
+            panic!("expected a capture step");
+        };
+        assert_eq!(capture.var, "token");
+        assert!(capture.timeout.is_none());
+    }
+
+    #[test]
+    fn parse_rejects_capture_without_group() {
+        let result: Result<DialogInput> = syn::parse2(quote! { capture token from_re r"\w+" });
+        assert!(result.is_err());
+        let result: Result<DialogInput> = syn::parse2(quote! { capture token from r"(\w+)" });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_requires_semicolons_between_steps() {
+        let result: Result<DialogInput> = syn::parse2(quote! { expect "a" expect "b" });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn expand_applies_timeout_to_later_expects() {
+        let input: DialogInput = parse_quote! {
+            expect "login:";
+            timeout std::time::Duration::from_secs(5);
+            expect "password:"
+        };
+        let output = expand(input).to_string();
+        assert_eq!(output.matches(". timeout").count(), 1);
+    }
+
+    #[test]
+    fn expand_ctrl_and_sendbytes() {
+        let input: DialogInput = parse_quote! {
+            ctrl 'c';
+            ctrl 'D';
+            sendbytes [0x1b, 0x5b]
+        };
+        let output = expand(input).to_string();
+        assert!(output.contains("ControlChar :: CtrlC"));
+        assert!(output.contains("ControlChar :: CtrlD"));
+        assert!(output.contains("send_bytes ([27u8 , 91u8])"));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_ctrl_char() {
+        let result: Result<DialogInput> = syn::parse2(quote! { ctrl '1' });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_rejects_out_of_range_byte() {
+        let result: Result<DialogInput> = syn::parse2(quote! { sendbytes [0x1b, 256] });
+        assert!(result.is_err());
+        let result: Result<DialogInput> = syn::parse2(quote! { sendbytes [] });
+        assert!(result.is_err());
+    }
 }
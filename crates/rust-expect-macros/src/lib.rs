@@ -4,6 +4,7 @@
 //!
 //! - [`patterns!`] - Define pattern sets for expect operations
 //! - [`regex!`] - Compile-time validated regex patterns
+//! - [`bytes_regex!`] - Compile-time validated regex patterns over raw bytes
 //! - [`dialog!`] - Define interactive dialog scripts
 //! - [`timeout!`] - Parse timeout duration specifications
 //!
@@ -74,10 +75,19 @@ mod timeout;
 ///     name: "named pattern",
 ///     regex(r"regex\s+pattern"),
 ///     glob("glob*pattern"),
-///     "pattern" => action_expression,
+///     _,
 /// }
 /// ```
 ///
+/// The `_` arm is a catch-all that matches on timeout or EOF; it must come
+/// last. Arms keep their order: when several match, the one starting
+/// earliest in the output wins, and among those starting at the same place
+/// the first arm wins.
+///
+/// If the arms have `=> action` values, every arm needs one and the macro
+/// yields `(PatternSet, [actions])`, with the actions indexed by the
+/// matched pattern's index.
+///
 /// # Examples
 ///
 /// ```ignore
@@ -87,8 +97,16 @@ mod timeout;
 ///     prompt: regex(r"\$\s*$"),
 /// };
 ///
-/// // Use with session.expect()
-/// let matched = session.expect(&login_patterns).await?;
+/// // Use with session.expect_any()
+/// let matched = session.expect_any(&login_patterns).await?;
+///
+/// // Map each outcome to a value, with a fallback for timeout or EOF
+/// let (patterns, outcomes) = patterns! {
+///     "ok" => Outcome::Ok,
+///     "err" => Outcome::Failed,
+///     _ => Outcome::NoAnswer,
+/// };
+/// let outcome = &outcomes[session.expect_any(&patterns).await?.pattern_index];
 /// ```
 #[proc_macro]
 pub fn patterns(input: TokenStream) -> TokenStream {
@@ -101,6 +119,15 @@ pub fn patterns(input: TokenStream) -> TokenStream {
 /// Creates a lazily-initialized `regex::Regex` that is validated at compile time.
 /// Invalid regex patterns will cause a compilation error.
 ///
+/// Flags can follow the pattern, as in `regex!(r"foo", i, m)`:
+///
+/// - `i` - Case-insensitive
+/// - `m` - `^` and `$` match at line boundaries
+/// - `s` - `.` matches newlines
+/// - `x` - Ignore whitespace and allow `#` comments
+/// - `U` - Swap the meaning of greedy and lazy repetition
+/// - `R` - Treat `\r\n` as a line ending, for `m` and `.`
+///
 /// # Examples
 ///
 /// ```ignore
@@ -109,6 +136,9 @@ pub fn patterns(input: TokenStream) -> TokenStream {
 /// // Valid regex - compiles successfully
 /// let prompt = regex!(r"^\w+@\w+:\S+\$\s*$");
 ///
+/// // Case-insensitive, matching each line of a PTY transcript
+/// let error = regex!(r"^error:", i, m, R);
+///
 /// // Invalid regex - compilation error
 /// // let bad = regex!(r"[invalid");
 /// ```
@@ -118,6 +148,29 @@ pub fn regex(input: TokenStream) -> TokenStream {
     regex::expand(input).into()
 }
 
+/// Compile-time validated regex pattern over raw bytes.
+///
+/// Like [`regex!`], but creates a `regex::bytes::Regex`, which matches
+/// byte buffers that may not be valid UTF-8, such as PTY output before it
+/// is decoded. It accepts the same flags.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rust_expect_macros::bytes_regex;
+///
+/// let prompt = bytes_regex!(r"\$ $");
+/// assert!(prompt.is_match(b"\xff\xfe user@host $ "));
+///
+/// // Match arbitrary bytes with Unicode mode turned off inline
+/// let escape = bytes_regex!(r"(?-u)\x1b\[[0-9;]*m");
+/// ```
+#[proc_macro]
+pub fn bytes_regex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as regex::RegexInput);
+    regex::expand_bytes(input).into()
+}
+
 /// Define an interactive dialog script.
 ///
 /// Creates a `Dialog` that can be executed against a session, automating
@@ -126,11 +179,24 @@ pub fn regex(input: TokenStream) -> TokenStream {
 /// # Commands
 ///
 /// - `send "text"` - Send text without newline
-/// - `sendln "text"` - Send text with newline
+/// - `sendln "text"` - Send text followed by the session's line ending
+/// - `ctrl 'c'` - Send a control character (`'a'`..`'z'`, `'['`, `'\\'`,
+///   `']'`, `'^'` or `'_'`)
+/// - `sendbytes [0x1b, 0x5b]` - Send raw bytes
 /// - `expect "pattern"` - Wait for literal pattern
 /// - `expect_re "regex"` - Wait for regex pattern (validated at compile time)
+/// - `expect "pattern" within 5s else command` - Wait up to a timeout
+///   written like `timeout!`, running the command instead of failing if
+///   the pattern does not appear; either half works on its own
 /// - `wait duration` - Wait for a duration
 /// - `timeout duration` - Set timeout for subsequent operations
+/// - `forbid "text"` - Fail the dialog if text appears during any later expect
+/// - `if_match { "a" => { ... }, "b" => { ... } }` - Wait for whichever
+///   pattern appears first and run that arm's commands
+/// - `capture name from_re "regex"` - Wait for a regex and store its first
+///   capture group as `${name}` for later commands
+/// - `repeat until "pattern" max n { ... }` - Run the commands until the
+///   pattern appears, at most `n` times
 ///
 /// # Examples
 ///
@@ -140,15 +206,46 @@ pub fn regex(input: TokenStream) -> TokenStream {
 ///
 /// let login_script = dialog! {
 ///     timeout Duration::from_secs(30);
+///     forbid "Permission denied";
 ///     expect "login:";
 ///     sendln "admin";
 ///     expect "password:";
 ///     sendln "secret123";
-///     expect_re r"\$\s*$"
+///     // Nudge a prompt that sometimes needs an extra Enter
+///     expect_re r"\$\s*$" within 5s else sendln ""
 /// };
 ///
 /// // Execute the dialog
 /// session.run_dialog(&login_script).await?;
+///
+/// // Answer the host key prompt only when it appears
+/// let ssh_login = dialog! {
+///     if_match {
+///         "(yes/no)?" => { sendln "yes"; expect "password:" },
+///         "password:" => {}
+///     }
+///     sendln "secret123"
+/// };
+///
+/// // Send back a value printed earlier
+/// let reuse_token = dialog! {
+///     capture token from_re r"token: (\w+)";
+///     sendln "use ${token}"
+/// };
+///
+/// // Leave a REPL with Ctrl+D
+/// let quit_repl = dialog! {
+///     expect ">>> ";
+///     ctrl 'd'
+/// };
+///
+/// // Retry the password on flaky devices
+/// let retry_login = dialog! {
+///     repeat until "prompt>" max 3 {
+///         expect "password:";
+///         sendln "secret123"
+///     }
+/// };
 /// ```
 #[proc_macro]
 pub fn dialog(input: TokenStream) -> TokenStream {
@@ -169,6 +266,11 @@ pub fn dialog(input: TokenStream) -> TokenStream {
 /// - `m`, `min`, `mins`, `minutes` - Minutes
 /// - `h`, `hr`, `hrs`, `hours` - Hours
 ///
+/// Values may be fractional, such as `1.5 s`, as long as the result is a
+/// whole number of nanoseconds. Components can be added with `+` or
+/// subtracted with `-`; they apply left to right and the total never drops
+/// below zero.
+///
 /// # Examples
 ///
 /// ```ignore
@@ -178,6 +280,8 @@ pub fn dialog(input: TokenStream) -> TokenStream {
 /// let medium = timeout!(5 s);
 /// let long = timeout!(2 m);
 /// let compound = timeout!(1 m + 30 s + 500 ms);
+/// let fractional = timeout!(1.5 s);
+/// let almost = timeout!(5 m - 10 s);
 /// ```
 #[proc_macro]
 pub fn timeout(input: TokenStream) -> TokenStream {
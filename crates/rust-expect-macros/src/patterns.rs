@@ -28,6 +28,8 @@ pub enum PatternKind {
     Regex(LitStr),
     /// Glob pattern.
     Glob(LitStr),
+    /// The catch-all `_` arm, matched on timeout or EOF.
+    Fallback(Token![_]),
 }
 
 impl Parse for Pattern {
@@ -42,7 +44,9 @@ impl Parse for Pattern {
         };
 
         // Parse pattern kind
-        let pattern = if input.peek(Ident) {
+        let pattern = if input.peek(Token![_]) {
+            PatternKind::Fallback(input.parse()?)
+        } else if input.peek(Ident) {
             let kind: Ident = input.parse()?;
             match kind.to_string().as_str() {
                 "regex" | "re" => {
@@ -96,7 +100,7 @@ pub struct PatternsInput {
 impl Parse for PatternsInput {
     fn parse(input: ParseStream) -> Result<Self> {
         // Handle braced or unbraced syntax
-        let patterns = if input.peek(syn::token::Brace) {
+        let patterns: Punctuated<Pattern, Token![,]> = if input.peek(syn::token::Brace) {
             let content;
             braced!(content in input);
             Punctuated::parse_terminated(&content)?
@@ -104,57 +108,105 @@ impl Parse for PatternsInput {
             Punctuated::parse_terminated(input)?
         };
 
+        let last = patterns.len().saturating_sub(1);
+        for (index, pattern) in patterns.iter().enumerate() {
+            if let PatternKind::Fallback(underscore) = pattern.kind {
+                if index != last {
+                    return Err(syn::Error::new(
+                        underscore.span,
+                        "the `_` arm must come last",
+                    ));
+                }
+                if let Some(ref name) = pattern.name {
+                    return Err(syn::Error::new(name.span(), "the `_` arm cannot be named"));
+                }
+            }
+        }
+        if patterns.iter().any(|p| p.action.is_some())
+            && let Some(pattern) = patterns.iter().find(|p| p.action.is_none())
+        {
+            return Err(syn::Error::new(
+                pattern.span(),
+                "every arm needs an action when any arm has one",
+            ));
+        }
+
         Ok(Self { patterns })
     }
 }
 
+impl Pattern {
+    /// Span of the pattern, for error messages.
+    fn span(&self) -> proc_macro2::Span {
+        match self.kind {
+            PatternKind::Literal(ref lit)
+            | PatternKind::Regex(ref lit)
+            | PatternKind::Glob(ref lit) => lit.span(),
+            PatternKind::Fallback(underscore) => underscore.span,
+        }
+    }
+}
+
 /// Generate code for the patterns! macro.
+///
+/// Patterns are added in order, so among matches starting at the same
+/// position the earlier arm wins. The `_` arm becomes the set's
+/// fallback, tried last. When the arms have actions, the macro yields
+/// the set and an array of the actions, indexed by the matched
+/// pattern's index.
 pub fn expand(input: PatternsInput) -> TokenStream {
-    let patterns: Vec<_> = input
-        .patterns
-        .into_iter()
-        .enumerate()
-        .map(|(idx, pattern)| {
-            let name = pattern
-                .name
-                .map_or_else(|| quote! { None }, |n| quote! { Some(#n.to_string()) });
-
-            let pattern_expr = match pattern.kind {
-                PatternKind::Literal(lit) => {
-                    quote! { rust_expect::pattern::PatternType::Literal(#lit.to_string()) }
-                }
-                PatternKind::Regex(lit) => {
-                    let pattern_str = lit.value();
-                    // Validate regex at compile time
-                    if let Err(e) = regex::Regex::new(&pattern_str) {
-                        return syn::Error::new(lit.span(), format!("invalid regex: {e}"))
-                            .to_compile_error();
-                    }
-                    quote! { rust_expect::pattern::PatternType::Regex(#lit.to_string()) }
-                }
-                PatternKind::Glob(lit) => {
-                    quote! { rust_expect::pattern::PatternType::Glob(#lit.to_string()) }
+    let mut adds = Vec::new();
+    let mut actions = Vec::new();
+
+    for pattern in input.patterns {
+        if let Some(action) = pattern.action {
+            actions.push(action);
+        }
+
+        let pattern_expr = match pattern.kind {
+            PatternKind::Literal(lit) => {
+                quote! { rust_expect::expect::Pattern::literal(#lit) }
+            }
+            PatternKind::Regex(lit) => {
+                let pattern_str = lit.value();
+                // Validate regex at compile time
+                if let Err(e) = regex::Regex::new(&pattern_str) {
+                    return syn::Error::new(lit.span(), format!("invalid regex: {e}"))
+                        .to_compile_error();
                 }
-            };
-
-            let action_expr = pattern.action.map_or_else(
-                || quote! { None::<Box<dyn Fn(&str)>> },
-                |a| quote! { Some(Box::new(move |_| { #a })) },
-            );
-
-            quote! {
-                rust_expect::pattern::Pattern {
-                    name: #name,
-                    index: #idx,
-                    pattern: #pattern_expr,
-                    action: #action_expr,
+                quote! {
+                    rust_expect::expect::Pattern::regex(#lit)
+                        .expect("regex validated at compile time")
                 }
             }
-        })
-        .collect();
+            PatternKind::Glob(lit) => {
+                quote! { rust_expect::expect::Pattern::glob(#lit) }
+            }
+            PatternKind::Fallback(_) => {
+                adds.push(quote! { set.add_fallback(); });
+                continue;
+            }
+        };
 
+        adds.push(if let Some(name) = pattern.name {
+            let name = name.to_string();
+            quote! { set.add_named(#name, #pattern_expr); }
+        } else {
+            quote! { set.add(#pattern_expr); }
+        });
+    }
+
+    let result = if actions.is_empty() {
+        quote! { set }
+    } else {
+        quote! { (set, [#(#actions),*]) }
+    };
     quote! {
-        rust_expect::pattern::PatternSet::new(vec![#(#patterns),*])
+        {
+            let mut set = rust_expect::expect::PatternSet::new();
+            #(#adds)*
+            #result
+        }
     }
 }
 
@@ -180,4 +232,42 @@ mod tests {
         };
         assert_eq!(input.patterns.len(), 2);
     }
+
+    #[test]
+    fn expand_keeps_order_and_tries_fallback_last() {
+        let input: PatternsInput = parse_quote! {
+            "ok" => Outcome::Ok,
+            failed: regex(r"err(or)?") => Outcome::Err,
+            _ => Outcome::Fallback,
+        };
+        let output = expand(input).to_string();
+        let ok = output.find("\"ok\"").unwrap();
+        let err = output.find("\"failed\"").unwrap();
+        let fallback = output.find("add_fallback").unwrap();
+        assert!(ok < err && err < fallback, "{output}");
+        assert!(
+            output.contains("[Outcome :: Ok , Outcome :: Err , Outcome :: Fallback]"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn expand_without_actions_yields_set() {
+        let input: PatternsInput = parse_quote! { "login:", _ };
+        let output = expand(input).to_string();
+        assert!(output.contains("add_fallback"));
+        assert!(output.trim_end().ends_with("set }"), "{output}");
+    }
+
+    #[test]
+    fn parse_rejects_misplaced_fallback() {
+        let result: Result<PatternsInput> = syn::parse2(quote! { _ => 0, "ok" => 1 });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_rejects_missing_action() {
+        let result: Result<PatternsInput> = syn::parse2(quote! { "ok" => 1, _ });
+        assert!(result.is_err());
+    }
 }
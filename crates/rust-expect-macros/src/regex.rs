@@ -1,39 +1,69 @@
 //! Compile-time validated regex macro implementation.
 //!
-//! This module implements the `regex!` macro for creating regex patterns
-//! that are validated at compile time.
+//! This module implements the `regex!` and `bytes_regex!` macros for
+//! creating regex patterns that are validated at compile time.
 
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::parse::{Parse, ParseStream};
-use syn::{LitStr, Result};
+use syn::{Ident, LitStr, Result, Token};
+
+/// Flags accepted after the pattern; each is an inline regex flag.
+const FLAGS: &str = "imsxUR";
 
 /// The regex! macro input.
 pub struct RegexInput {
     /// The regex pattern string.
     pub pattern: LitStr,
+    /// Flags to compile the pattern with, such as `i` and `m`.
+    pub flags: Vec<Ident>,
 }
 
 impl Parse for RegexInput {
     fn parse(input: ParseStream) -> Result<Self> {
-        Ok(Self {
-            pattern: input.parse()?,
-        })
+        let pattern = input.parse()?;
+        let mut flags = Vec::new();
+        while input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+            if input.is_empty() {
+                break;
+            }
+            let flag: Ident = input.parse()?;
+            let name = flag.to_string();
+            if name.len() != 1 || !FLAGS.contains(name.as_str()) {
+                return Err(syn::Error::new(
+                    flag.span(),
+                    format!("unknown regex flag `{name}`; expected one of i, m, s, x, U, R"),
+                ));
+            }
+            flags.push(flag);
+        }
+        Ok(Self { pattern, flags })
+    }
+}
+
+impl RegexInput {
+    /// The pattern with its flags applied as an inline `(?flags)` group.
+    fn flagged_pattern(&self) -> LitStr {
+        let pattern = self.pattern.value();
+        if self.flags.is_empty() {
+            return self.pattern.clone();
+        }
+        let flags: String = self.flags.iter().map(ToString::to_string).collect();
+        LitStr::new(&format!("(?{flags}){pattern}"), self.pattern.span())
     }
 }
 
 /// Generate code for the regex! macro.
 pub fn expand(input: RegexInput) -> TokenStream {
-    let pattern_str = input.pattern.value();
+    let lit = input.flagged_pattern();
 
     // Validate the regex at compile time
-    if let Err(e) = regex::Regex::new(&pattern_str) {
+    if let Err(e) = regex::Regex::new(&lit.value()) {
         return syn::Error::new(input.pattern.span(), format!("invalid regex: {e}"))
             .to_compile_error();
     }
 
-    let lit = &input.pattern;
-
     quote! {
         {
             // Use once_cell for lazy static initialization without unsafe
@@ -46,6 +76,26 @@ pub fn expand(input: RegexInput) -> TokenStream {
     }
 }
 
+/// Generate code for the `bytes_regex!` macro.
+pub fn expand_bytes(input: RegexInput) -> TokenStream {
+    let lit = input.flagged_pattern();
+
+    // Validate the regex at compile time
+    if let Err(e) = regex::bytes::Regex::new(&lit.value()) {
+        return syn::Error::new(input.pattern.span(), format!("invalid regex: {e}"))
+            .to_compile_error();
+    }
+
+    quote! {
+        {
+            static REGEX: std::sync::OnceLock<regex::bytes::Regex> = std::sync::OnceLock::new();
+            REGEX.get_or_init(|| {
+                regex::bytes::Regex::new(#lit).expect("regex was validated at compile time")
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use syn::parse_quote;
@@ -59,4 +109,21 @@ mod tests {
         };
         assert_eq!(input.pattern.value(), r"hello\s+world");
     }
+
+    #[test]
+    fn parse_regex_flags() {
+        let input: RegexInput = parse_quote! {
+            r"^foo$", i, m,
+        };
+        assert_eq!(input.flags.len(), 2);
+        assert_eq!(input.flagged_pattern().value(), "(?im)^foo$");
+    }
+
+    #[test]
+    fn parse_rejects_unknown_flag() {
+        let result: Result<RegexInput> = syn::parse2(quote! { "foo", q });
+        assert!(result.is_err());
+        let result: Result<RegexInput> = syn::parse2(quote! { "foo", im });
+        assert!(result.is_err());
+    }
 }
@@ -3,10 +3,11 @@
 //! This module implements the `timeout!` macro for parsing human-readable
 //! timeout specifications at compile time.
 
+use proc_macro2::Span;
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::parse::{Parse, ParseStream};
-use syn::{Ident, LitInt, Result, Token};
+use syn::{Ident, LitFloat, LitInt, Result, Token};
 
 /// A timeout specification.
 pub struct TimeoutInput {
@@ -14,12 +15,12 @@ pub struct TimeoutInput {
     pub components: Vec<TimeoutComponent>,
 }
 
-/// A single timeout component (e.g., "5s" or "100ms").
+/// A single timeout component (e.g., "5s", "100ms" or "- 1.5 s").
 pub struct TimeoutComponent {
-    /// The numeric value.
-    pub value: u64,
-    /// The unit (s, ms, us, m, h).
-    pub unit: TimeoutUnit,
+    /// The component's length in nanoseconds.
+    pub nanos: u64,
+    /// Whether the component is subtracted from the ones before it.
+    pub subtract: bool,
 }
 
 /// Time units supported by the timeout macro.
@@ -51,47 +52,116 @@ impl TimeoutUnit {
             Self::Hours => 60 * 60 * 1_000_000_000,
         }
     }
+
+    /// Parse a unit name.
+    fn parse(unit: &str, span: Span) -> Result<Self> {
+        Ok(match unit {
+            "ns" | "nanos" | "nanoseconds" => Self::Nanoseconds,
+            "us" | "micros" | "microseconds" => Self::Microseconds,
+            "ms" | "millis" | "milliseconds" => Self::Milliseconds,
+            "s" | "sec" | "secs" | "seconds" => Self::Seconds,
+            "m" | "min" | "mins" | "minutes" => Self::Minutes,
+            "h" | "hr" | "hrs" | "hours" => Self::Hours,
+            other => {
+                return Err(syn::Error::new(span, format!("unknown time unit: {other}")));
+            }
+        })
+    }
 }
 
 impl Parse for TimeoutComponent {
     fn parse(input: ParseStream) -> Result<Self> {
-        let value: LitInt = input.parse()?;
-        let value_u64: u64 = value.base10_parse()?;
-
-        let unit: Ident = input.parse()?;
-        let unit = match unit.to_string().as_str() {
-            "ns" | "nanos" | "nanoseconds" => TimeoutUnit::Nanoseconds,
-            "us" | "micros" | "microseconds" => TimeoutUnit::Microseconds,
-            "ms" | "millis" | "milliseconds" => TimeoutUnit::Milliseconds,
-            "s" | "sec" | "secs" | "seconds" => TimeoutUnit::Seconds,
-            "m" | "min" | "mins" | "minutes" => TimeoutUnit::Minutes,
-            "h" | "hr" | "hrs" | "hours" => TimeoutUnit::Hours,
-            other => {
-                return Err(syn::Error::new(
-                    unit.span(),
-                    format!("unknown time unit: {other}"),
-                ));
-            }
+        // The number, its suffix and its span; `5s` lexes as one literal
+        // with a suffix, `5 s` as two tokens
+        let (digits, suffix, span) = if input.peek(LitFloat) {
+            let value: LitFloat = input.parse()?;
+            (
+                value.base10_digits().to_string(),
+                value.suffix().to_string(),
+                value.span(),
+            )
+        } else {
+            let value: LitInt = input.parse()?;
+            (
+                value.base10_digits().to_string(),
+                value.suffix().to_string(),
+                value.span(),
+            )
+        };
+
+        let unit = if suffix.is_empty() {
+            let unit: Ident = input.parse()?;
+            TimeoutUnit::parse(&unit.to_string(), unit.span())?
+        } else {
+            TimeoutUnit::parse(&suffix, span)?
         };
 
         Ok(Self {
-            value: value_u64,
-            unit,
+            nanos: to_nanos(&digits, unit, span)?,
+            subtract: false,
         })
     }
 }
 
+/// Convert a decimal number of `unit`s to nanoseconds.
+///
+/// Fractions are exact: `1.5 s` is 1,500,000,000 ns. A fraction finer than
+/// a nanosecond is an error rather than being rounded.
+fn to_nanos(digits: &str, unit: TimeoutUnit, span: Span) -> Result<u64> {
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let invalid = || syn::Error::new(span, format!("invalid timeout value: {digits}"));
+    if !whole.bytes().all(|b| b.is_ascii_digit()) || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let multiplier = u128::from(unit.to_nanos());
+    let whole: u128 = whole.parse().map_err(|_| invalid())?;
+    let mut nanos = whole.saturating_mul(multiplier);
+    if !fraction.is_empty() {
+        let scale = u32::try_from(fraction.len())
+            .ok()
+            .and_then(|len| 10u128.checked_pow(len))
+            .ok_or_else(invalid)?;
+        let fraction: u128 = fraction.parse().map_err(|_| invalid())?;
+        let scaled = fraction.checked_mul(multiplier).ok_or_else(invalid)?;
+        if !scaled.is_multiple_of(scale) {
+            return Err(syn::Error::new(
+                span,
+                format!("{digits} is not a whole number of nanoseconds"),
+            ));
+        }
+        nanos = nanos.saturating_add(scaled / scale);
+    }
+    Ok(u64::try_from(nanos).unwrap_or(u64::MAX))
+}
+
 impl Parse for TimeoutInput {
     fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![-]) {
+            return Err(input.error("a timeout cannot start with `-`"));
+        }
+
         let mut components = Vec::new();
 
         // Parse first component
         components.push(input.parse()?);
 
-        // Parse additional components separated by +
-        while input.peek(Token![+]) {
-            let _: Token![+] = input.parse()?;
-            components.push(input.parse()?);
+        // Parse additional components separated by + or -
+        loop {
+            let subtract = if input.peek(Token![+]) {
+                let _: Token![+] = input.parse()?;
+                false
+            } else if input.peek(Token![-]) {
+                let _: Token![-] = input.parse()?;
+                true
+            } else {
+                break;
+            };
+            let component: TimeoutComponent = input.parse()?;
+            components.push(TimeoutComponent {
+                subtract,
+                ..component
+            });
         }
 
         Ok(Self { components })
@@ -99,13 +169,17 @@ impl Parse for TimeoutInput {
 }
 
 /// Generate code for the timeout! macro.
+///
+/// Components are applied left to right, and subtraction stops at zero.
 pub fn expand(input: TimeoutInput) -> TokenStream {
     // Calculate total duration in nanoseconds at compile time
-    let total_nanos: u64 = input
-        .components
-        .iter()
-        .map(|c| c.value.saturating_mul(c.unit.to_nanos()))
-        .fold(0u64, u64::saturating_add);
+    let total_nanos: u64 = input.components.iter().fold(0u64, |total, c| {
+        if c.subtract {
+            total.saturating_sub(c.nanos)
+        } else {
+            total.saturating_add(c.nanos)
+        }
+    });
 
     let secs = total_nanos / 1_000_000_000;
     let nanos = (total_nanos % 1_000_000_000) as u32;
@@ -127,7 +201,16 @@ mod tests {
             5 s
         };
         assert_eq!(input.components.len(), 1);
-        assert_eq!(input.components[0].value, 5);
+        assert_eq!(input.components[0].nanos, 5_000_000_000);
+    }
+
+    #[test]
+    fn parse_suffixed_timeout() {
+        let input: TimeoutInput = parse_quote! {
+            5s + 500ms
+        };
+        assert_eq!(input.components.len(), 2);
+        assert_eq!(input.components[1].nanos, 500_000_000);
     }
 
     #[test]
@@ -137,4 +220,43 @@ mod tests {
         };
         assert_eq!(input.components.len(), 2);
     }
+
+    #[test]
+    fn parse_fractional_timeout() {
+        let input: TimeoutInput = parse_quote! {
+            1.5 s + 0.25ms
+        };
+        assert_eq!(input.components[0].nanos, 1_500_000_000);
+        assert_eq!(input.components[1].nanos, 250_000);
+    }
+
+    #[test]
+    fn parse_subtracted_timeout() {
+        let input: TimeoutInput = parse_quote! {
+            5 m - 10 s
+        };
+        assert!(!input.components[0].subtract);
+        assert!(input.components[1].subtract);
+        assert_eq!(
+            expand(input).to_string(),
+            quote! { std::time::Duration::new(290u64, 0u32) }.to_string()
+        );
+    }
+
+    #[test]
+    fn subtraction_saturates_at_zero() {
+        let input: TimeoutInput = parse_quote! {
+            1 s - 5 s + 2 s
+        };
+        assert_eq!(
+            expand(input).to_string(),
+            quote! { std::time::Duration::new(2u64, 0u32) }.to_string()
+        );
+    }
+
+    #[test]
+    fn parse_rejects_sub_nanosecond_fraction() {
+        let result: Result<TimeoutInput> = syn::parse2(quote! { 1.5 ns });
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,10 @@
+//! Compile tests for the `regex!` and `bytes_regex!` macros.
+
+#[test]
+fn regex_macros() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/regex_flags.rs");
+    t.pass("tests/ui/bytes_regex.rs");
+    t.compile_fail("tests/ui/regex_unknown_flag.rs");
+    t.compile_fail("tests/ui/bytes_regex_invalid.rs");
+}
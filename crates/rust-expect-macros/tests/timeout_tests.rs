@@ -0,0 +1,9 @@
+//! Compile tests for the `timeout!` macro.
+
+#[test]
+fn timeout_macro() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/timeout_fractional.rs");
+    t.pass("tests/ui/timeout_subtract.rs");
+    t.compile_fail("tests/ui/timeout_leading_minus.rs");
+}
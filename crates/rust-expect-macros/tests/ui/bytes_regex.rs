@@ -0,0 +1,8 @@
+use rust_expect_macros::bytes_regex;
+
+fn main() {
+    // Matches around bytes that are not valid UTF-8
+    assert!(bytes_regex!(r"login: $").is_match(b"\xff\xfe login: "));
+    assert!(bytes_regex!(r"(?-u)\xff+", i).is_match(b"ok \xff\xff"));
+    assert!(bytes_regex!(r"LOGIN", i).is_match(b"login:"));
+}
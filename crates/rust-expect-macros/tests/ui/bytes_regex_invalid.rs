@@ -0,0 +1,5 @@
+use rust_expect_macros::bytes_regex;
+
+fn main() {
+    let _ = bytes_regex!(r"[unclosed", i);
+}
@@ -0,0 +1,12 @@
+use rust_expect_macros::regex;
+
+fn main() {
+    assert!(regex!(r"^error:", i).is_match("ERROR: disk full"));
+    assert!(!regex!(r"^error:").is_match("ERROR: disk full"));
+
+    let lines = regex!(r"^\$ $", m, R);
+    assert!(lines.is_match("output\r\n$ \r\n"));
+
+    let spaced = regex!(r"a . b", x, s);
+    assert!(spaced.is_match("a\nb"));
+}
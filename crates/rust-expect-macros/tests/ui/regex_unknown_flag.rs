@@ -0,0 +1,5 @@
+use rust_expect_macros::regex;
+
+fn main() {
+    let _ = regex!(r"foo", i, q);
+}
@@ -0,0 +1,9 @@
+use std::time::Duration;
+
+use rust_expect_macros::timeout;
+
+fn main() {
+    assert_eq!(timeout!(1.5 s), Duration::from_millis(1500));
+    assert_eq!(timeout!(0.5 m + 2.25 s), Duration::from_millis(32_250));
+    assert_eq!(timeout!(1.5ms), Duration::from_micros(1500));
+}
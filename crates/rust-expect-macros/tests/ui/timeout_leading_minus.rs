@@ -0,0 +1,5 @@
+use rust_expect_macros::timeout;
+
+fn main() {
+    let _ = timeout!(- 10 s);
+}
@@ -0,0 +1,10 @@
+use std::time::Duration;
+
+use rust_expect_macros::timeout;
+
+fn main() {
+    assert_eq!(timeout!(5 m - 10 s), Duration::from_secs(290));
+    assert_eq!(timeout!(1 h - 30 m + 5 s), Duration::from_secs(1805));
+    // Subtraction saturates at zero
+    assert_eq!(timeout!(10 s - 1 m), Duration::ZERO);
+}
@@ -4,7 +4,7 @@
 use std::hint::black_box;
 
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
-use rust_expect::expect::{Pattern, PatternSet, RingBuffer};
+use rust_expect::expect::{Matcher, Pattern, PatternSet, RingBuffer};
 
 fn bench_literal_pattern(c: &mut Criterion) {
     let pattern = Pattern::literal("needle");
@@ -97,6 +97,39 @@ fn bench_complex_regex(c: &mut Criterion) {
     });
 }
 
+/// Stream 10 MB into a matcher in 64 KB chunks, matching after each chunk.
+///
+/// `full_rescan` resets the scan cursor before every attempt, which is how
+/// matching behaved before the cursor existed.
+fn bench_incremental_scan(c: &mut Criterion) {
+    const TOTAL: usize = 10 * 1024 * 1024;
+    const CHUNK: usize = 64 * 1024;
+
+    let chunk = vec![b'x'; CHUNK];
+    let patterns = PatternSet::from_patterns(vec![Pattern::literal("DONE")]);
+    let mut group = c.benchmark_group("incremental_scan_10mb");
+    group.sample_size(10);
+
+    for (name, rescan) in [("watermark", false), ("full_rescan", true)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut matcher = Matcher::new(TOTAL + CHUNK);
+                for _ in 0..TOTAL / CHUNK {
+                    matcher.append(&chunk);
+                    if rescan {
+                        matcher.reset_scan();
+                    }
+                    assert!(matcher.try_match_any(&patterns).is_none());
+                }
+                matcher.append(b"DONE");
+                black_box(matcher.try_match_any(&patterns))
+            });
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_literal_pattern,
@@ -105,5 +138,6 @@ criterion_group!(
     bench_pattern_set_sizes,
     bench_ring_buffer,
     bench_complex_regex,
+    bench_incremental_scan,
 );
 criterion_main!(benches);
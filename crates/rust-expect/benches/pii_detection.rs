@@ -0,0 +1,83 @@
+//! PII detection benchmarks.
+#![allow(missing_docs)]
+
+#[cfg(not(feature = "pii-redaction"))]
+use criterion::Criterion;
+use criterion::{criterion_group, criterion_main};
+
+#[cfg(feature = "pii-redaction")]
+mod pii_benches {
+    use std::hint::black_box;
+
+    use criterion::{BenchmarkId, Criterion};
+    use rust_expect::pii::{PiiDetector, PiiRedactor};
+
+    /// Build a log blob containing `count` email addresses.
+    fn email_log(count: usize) -> String {
+        use std::fmt::Write;
+
+        let mut log = String::new();
+        for i in 0..count {
+            let _ = writeln!(log, "[INFO] request {i} from user{i}@example.com ok");
+        }
+        log
+    }
+
+    pub fn bench_detect_many_emails(c: &mut Criterion) {
+        let mut group = c.benchmark_group("pii_detect_emails");
+        let detector = PiiDetector::new();
+
+        for count in &[10, 100, 1000] {
+            let text = email_log(*count);
+            group.bench_with_input(BenchmarkId::from_parameter(count), &text, |b, text| {
+                b.iter(|| detector.detect(black_box(text)));
+            });
+        }
+
+        group.finish();
+    }
+
+    pub fn bench_detect_overlapping(c: &mut Criterion) {
+        let mut group = c.benchmark_group("pii_detect_overlapping");
+        let detector = PiiDetector::custom_only()
+            .add_pattern("pair", r"\d\d", "[PAIR]", 0.6)
+            .add_pattern("triple", r"\d{3}", "[TRIPLE]", 0.8);
+
+        for repeats in &[100, 1000] {
+            let text = "123456789".repeat(*repeats);
+            group.bench_with_input(BenchmarkId::from_parameter(repeats), &text, |b, text| {
+                b.iter(|| detector.detect(black_box(text)));
+            });
+        }
+
+        group.finish();
+    }
+
+    pub fn bench_redact(c: &mut Criterion) {
+        let redactor = PiiRedactor::new();
+        let text = email_log(100);
+
+        c.bench_function("pii_redact_100_emails", |b| {
+            b.iter(|| redactor.redact(black_box(&text)));
+        });
+    }
+
+    pub fn register_benches(c: &mut Criterion) {
+        bench_detect_many_emails(c);
+        bench_detect_overlapping(c);
+        bench_redact(c);
+    }
+}
+
+#[cfg(feature = "pii-redaction")]
+criterion_group!(benches, pii_benches::register_benches);
+
+#[cfg(not(feature = "pii-redaction"))]
+const fn dummy_bench(_c: &mut Criterion) {
+    // No-op when pii-redaction feature is disabled
+}
+
+#[cfg(not(feature = "pii-redaction"))]
+criterion_group!(benches, dummy_bench);
+
+criterion_main!(benches);
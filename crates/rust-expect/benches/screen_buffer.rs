@@ -1,16 +1,16 @@
 //! Screen buffer benchmarks.
 #![allow(missing_docs)]
 
-use std::hint::black_box;
-
-#[allow(unused_imports)]
-use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+#[cfg(not(feature = "screen"))]
+use criterion::Criterion;
+use criterion::{criterion_group, criterion_main};
 
 #[cfg(feature = "screen")]
 mod screen_benches {
-    use rust_expect::screen::{AnsiParser, ScreenBuffer, ScreenQueryExt};
+    use std::hint::black_box;
 
-    use super::{BenchmarkId, Criterion, black_box};
+    use criterion::{BenchmarkId, Criterion};
+    use rust_expect::screen::{AnsiParser, ScreenBuffer, ScreenQueryExt};
 
     /// Helper to write a string to the buffer character by character.
     fn write_str(buffer: &mut ScreenBuffer, s: &str) {
@@ -134,7 +134,7 @@ mod screen_benches {
 criterion_group!(benches, screen_benches::register_benches);
 
 #[cfg(not(feature = "screen"))]
-fn dummy_bench(_c: &mut Criterion) {
+const fn dummy_bench(_c: &mut Criterion) {
     // No-op when screen feature is disabled
 }
 
@@ -58,11 +58,9 @@ async fn main() -> Result<()> {
     println!("\n3. Custom buffer configuration...");
 
     let config = SessionConfig {
-        buffer: BufferConfig {
-            max_size: 1024 * 1024, // 1 MB buffer
-            search_window: Some(8192),
-            ring_buffer: true,
-        },
+        buffer: BufferConfig::new(1024 * 1024) // 1 MB buffer
+            .search_window(8192)
+            .ring_buffer(true),
         ..Default::default()
     };
 
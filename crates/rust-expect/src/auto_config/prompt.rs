@@ -3,6 +3,12 @@
 use std::sync::LazyLock;
 
 use regex::Regex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use super::shell::ShellType;
+use crate::error::Result;
+use crate::expect::{CompiledRegex, Pattern};
+use crate::session::Session;
 
 /// Common prompt patterns.
 /// Order matters: more specific patterns must come before generic ones.
@@ -204,6 +210,39 @@ pub fn set_prompt_command(marker: &str) -> String {
     format!("PS1='{marker} '")
 }
 
+/// Give a running shell a unique prompt and return a pattern for it.
+///
+/// Sends [`ShellType::prompt_command`] with a fresh marker, then waits,
+/// using the session's default timeout, for the first prompt drawn with
+/// it. The returned regex matches only that prompt: it cannot be confused
+/// with command output, and the echoed setup command does not match it.
+/// For [`ShellType::Cmd`] it also matches the `$P$G` path that follows the
+/// marker.
+///
+/// # Errors
+///
+/// Returns an error if sending the command fails or the new prompt does
+/// not appear before the timeout.
+pub async fn configure_prompt<T>(
+    session: &mut Session<T>,
+    shell: ShellType,
+) -> Result<CompiledRegex>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin + Send,
+{
+    let marker = generate_prompt_marker();
+    let source = match shell {
+        ShellType::Cmd => format!(r"{} [^\r\n]*>", regex::escape(&marker)),
+        _ => format!("{} ", regex::escape(&marker)),
+    };
+    let regex = Regex::new(&source).expect("escaped prompt marker is a valid regex");
+    let prompt = CompiledRegex::new(source, regex);
+
+    session.send_line(&shell.prompt_command(&marker)).await?;
+    session.expect(Pattern::Regex(prompt.clone())).await?;
+    Ok(prompt)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
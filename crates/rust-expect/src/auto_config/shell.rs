@@ -2,6 +2,11 @@
 
 use std::path::PathBuf;
 
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::error::Result;
+use crate::session::Session;
+
 /// Known shell types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ShellType {
@@ -65,6 +70,88 @@ impl ShellType {
         }
     }
 
+    /// Get a command that sets the prompt to `marker` followed by a space.
+    ///
+    /// The marker is split by the shell's quoting so that the echoed
+    /// command line does not contain it verbatim; only the prompt the
+    /// shell draws does. Cmd has no such quoting, so its prompt is
+    /// `marker`, a space and the `$P$G` path, while the echo keeps the
+    /// literal `$S`.
+    #[must_use]
+    pub fn prompt_command(&self, marker: &str) -> String {
+        let (head, tail) = marker.split_at(marker.len() / 2);
+        match self {
+            Self::Bash | Self::Sh | Self::Dash | Self::Ksh | Self::Unknown => {
+                format!("PS1='{head}''{tail} '; PS2=''")
+            }
+            Self::Zsh => format!("PROMPT='{head}''{tail} '; RPROMPT=''"),
+            Self::Fish => format!("function fish_prompt; echo -n '{head}''{tail} '; end"),
+            Self::Tcsh => format!("set prompt='{head}''{tail} '"),
+            Self::PowerShell => format!("function prompt {{ '{head}' + '{tail} ' }}"),
+            Self::Cmd => format!("prompt {marker}$S$P$G"),
+        }
+    }
+
+    /// Get the commands that make the shell predictable to automate.
+    ///
+    /// They turn off terminal echo and line-editor features that redraw
+    /// input, and point pagers at `cat` so commands like `git log` or
+    /// `man` print their output instead of waiting in `less`.
+    #[must_use]
+    pub const fn setup_commands(&self) -> &'static [&'static str] {
+        match self {
+            Self::Bash => &[
+                "stty -echo",
+                "set +o vi",
+                "bind 'set enable-bracketed-paste off'",
+                "export PAGER=cat GIT_PAGER=cat MANPAGER=cat SYSTEMD_PAGER=",
+            ],
+            Self::Zsh => &[
+                "stty -echo",
+                "unsetopt zle",
+                "export PAGER=cat GIT_PAGER=cat MANPAGER=cat SYSTEMD_PAGER=",
+            ],
+            Self::Ksh => &[
+                "stty -echo",
+                "set +o vi",
+                "export PAGER=cat GIT_PAGER=cat MANPAGER=cat SYSTEMD_PAGER=",
+            ],
+            Self::Sh | Self::Dash | Self::Unknown => &[
+                "stty -echo",
+                "export PAGER=cat GIT_PAGER=cat MANPAGER=cat SYSTEMD_PAGER=",
+            ],
+            Self::Fish => &[
+                "stty -echo",
+                "set -g fish_autosuggestion_enabled 0",
+                "set -gx PAGER cat",
+                "set -gx GIT_PAGER cat",
+                "set -gx MANPAGER cat",
+            ],
+            Self::Tcsh => &[
+                "stty -echo",
+                "setenv PAGER cat",
+                "setenv GIT_PAGER cat",
+                "setenv MANPAGER cat",
+            ],
+            Self::PowerShell => &[
+                "Remove-Module PSReadLine -ErrorAction SilentlyContinue",
+                "$env:PAGER = 'cat'",
+                "$env:GIT_PAGER = 'cat'",
+            ],
+            // Quoted so the space before a following `&` stays out of the value
+            Self::Cmd => &[r#"set "PAGER=cat""#, r#"set "GIT_PAGER=cat""#],
+        }
+    }
+
+    /// Get the separator for running several commands on one line.
+    #[must_use]
+    pub const fn command_separator(&self) -> &'static str {
+        match self {
+            Self::Cmd => " & ",
+            _ => "; ",
+        }
+    }
+
     /// Get exit command.
     #[must_use]
     pub const fn exit_command(&self) -> &'static str {
@@ -139,6 +226,24 @@ pub fn default_shell() -> String {
     })
 }
 
+/// Send the shell's [`setup_commands`](ShellType::setup_commands).
+///
+/// The commands go out as one line, so the shell echoes it once and draws
+/// a single prompt afterwards. Nothing is awaited; follow up with an
+/// expect for the prompt, or with
+/// [`configure_prompt`](super::configure_prompt).
+///
+/// # Errors
+///
+/// Returns an error if sending fails.
+pub async fn prepare_shell<T>(session: &mut Session<T>, shell: ShellType) -> Result<()>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin + Send,
+{
+    let line = shell.setup_commands().join(shell.command_separator());
+    session.send_line(&line).await
+}
+
 /// Shell configuration options.
 #[derive(Debug, Clone)]
 pub struct ShellConfig {
@@ -232,6 +337,22 @@ mod tests {
         assert_eq!(ShellType::Zsh.name(), "zsh");
     }
 
+    #[test]
+    fn prompt_command_hides_marker() {
+        let marker = "__EXPECT_PROMPT_42__";
+        for shell in [
+            ShellType::Bash,
+            ShellType::Zsh,
+            ShellType::Fish,
+            ShellType::Tcsh,
+            ShellType::PowerShell,
+            ShellType::Cmd,
+        ] {
+            let command = shell.prompt_command(marker);
+            assert!(!command.contains(&format!("{marker} ")), "{command}");
+        }
+    }
+
     #[test]
     fn shell_config_default() {
         let config = ShellConfig::new();
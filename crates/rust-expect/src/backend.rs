@@ -13,6 +13,13 @@ pub use pty::{EnvMode, PtyConfig, PtySpawner, PtyTransport};
 #[cfg(windows)]
 pub use pty::{WindowsAsyncPty, WindowsPtyHandle};
 
+/// The async PTY transport for the current platform.
+#[cfg(unix)]
+pub(crate) type PlatformPty = AsyncPty;
+/// The async PTY transport for the current platform.
+#[cfg(windows)]
+pub(crate) type PlatformPty = WindowsAsyncPty;
+
 // SSH backend is conditionally compiled
 #[cfg(feature = "ssh")]
 pub mod ssh;
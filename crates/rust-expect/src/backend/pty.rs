@@ -4,6 +4,7 @@
 //! to spawn local processes with pseudo-terminal support.
 
 use std::io;
+use std::path::PathBuf;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
@@ -85,6 +86,8 @@ pub struct PtyConfig {
     pub login_shell: bool,
     /// Environment variable handling.
     pub env_mode: EnvMode,
+    /// Working directory for the child process.
+    pub working_dir: Option<PathBuf>,
 }
 
 impl Default for PtyConfig {
@@ -93,6 +96,7 @@ impl Default for PtyConfig {
             dimensions: (80, 24),
             login_shell: false,
             env_mode: EnvMode::Inherit,
+            working_dir: None,
         }
     }
 }
@@ -107,6 +111,7 @@ impl From<&SessionConfig> for PtyConfig {
             } else {
                 EnvMode::Extend
             },
+            working_dir: config.working_dir.clone(),
         }
     }
 }
@@ -155,12 +160,17 @@ impl PtySpawner {
     /// - The command or arguments contain null bytes
     /// - PTY allocation fails
     /// - Fork fails
-    /// - Exec fails (child exits with code 1)
+    /// - The working directory does not exist ([`SpawnError::WorkingDirMissing`])
+    /// - The command does not exist ([`SpawnError::CommandNotFound`])
+    /// - The command is not executable ([`SpawnError::PermissionDenied`])
+    /// - Exec fails for any other reason ([`SpawnError::ExecFailed`])
     #[cfg(unix)]
     #[allow(unsafe_code)]
     #[allow(clippy::unused_async)]
+    #[allow(clippy::too_many_lines)] // fork/exec sequence reads best kept in one place
     pub async fn spawn(&self, command: &str, args: &[String]) -> Result<PtyHandle> {
         use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
 
         // Validate and create CStrings BEFORE forking so we can return proper errors
         let cmd_cstring = CString::new(command).map_err(|_| {
@@ -185,6 +195,24 @@ impl PtySpawner {
             argv_cstrings.push(arg_cstring);
         }
 
+        let working_dir_cstring = match &self.config.working_dir {
+            Some(dir) => Some(CString::new(dir.as_os_str().as_bytes()).map_err(|_| {
+                ExpectError::Spawn(SpawnError::InvalidArgument {
+                    kind: "working directory".to_string(),
+                    value: dir.display().to_string(),
+                    reason: "path contains null byte".to_string(),
+                })
+            })?),
+            None => None,
+        };
+
+        // Build argv before forking so the child doesn't allocate
+        let argv_ptrs: Vec<*const libc::c_char> = argv_cstrings
+            .iter()
+            .map(|s| s.as_ptr())
+            .chain(std::iter::once(std::ptr::null()))
+            .collect();
+
         // Create PTY pair
         // SAFETY: openpty() is called with valid pointers to stack-allocated integers.
         // The null pointers for name, termp, and winp are explicitly allowed per POSIX.
@@ -212,6 +240,20 @@ impl PtySpawner {
 
         let (master_fd, slave_fd) = pty_result;
 
+        // The child reports chdir/exec failures through this pipe. The write end
+        // is close-on-exec, so a successful exec closes it without writing.
+        let (error_read, error_write) = match exec_error_pipe() {
+            Ok(fds) => fds,
+            Err(e) => {
+                // SAFETY: Both fds were just returned by openpty() and are unused.
+                unsafe {
+                    libc::close(master_fd);
+                    libc::close(slave_fd);
+                }
+                return Err(ExpectError::Spawn(SpawnError::Io(e)));
+            }
+        };
+
         // Fork the process
         // SAFETY: fork() is safe to call at this point as we have no threads running
         // that could hold locks. The child process will immediately set up its
@@ -219,9 +261,17 @@ impl PtySpawner {
         let pid = unsafe { libc::fork() };
 
         match pid {
-            -1 => Err(ExpectError::Spawn(SpawnError::Io(
-                io::Error::last_os_error(),
-            ))),
+            -1 => {
+                let err = io::Error::last_os_error();
+                // SAFETY: All four fds were created above and are not shared.
+                unsafe {
+                    libc::close(master_fd);
+                    libc::close(slave_fd);
+                    libc::close(error_read);
+                    libc::close(error_write);
+                }
+                Err(ExpectError::Spawn(SpawnError::Io(err)))
+            }
             0 => {
                 // Child process
                 // SAFETY: This runs in the forked child process only. We:
@@ -230,11 +280,14 @@ impl PtySpawner {
                 // - Set the slave as the controlling terminal via TIOCSCTTY
                 // - Redirect stdin/stdout/stderr to the slave pty
                 // - Close the original slave fd if it's not 0, 1, or 2
+                // - Change to the working directory, if any
                 // - Execute the target command (never returns on success)
-                // - Exit with code 1 if exec fails
+                // - Report the failing stage and errno over the error pipe and
+                //   exit with code 127 if chdir or exec fails
                 // All file descriptors are valid and owned by this process.
                 unsafe {
                     libc::close(master_fd);
+                    libc::close(error_read);
                     libc::setsid();
                     // Cast TIOCSCTTY to c_ulong for macOS compatibility (u32 -> u64)
                     libc::ioctl(slave_fd, libc::TIOCSCTTY as libc::c_ulong, 0);
@@ -247,15 +300,15 @@ impl PtySpawner {
                         libc::close(slave_fd);
                     }
 
-                    // Use pre-validated CStrings (validated before fork)
-                    let argv_ptrs: Vec<*const libc::c_char> = argv_cstrings
-                        .iter()
-                        .map(|s| s.as_ptr())
-                        .chain(std::iter::once(std::ptr::null()))
-                        .collect();
+                    if let Some(dir) = &working_dir_cstring
+                        && libc::chdir(dir.as_ptr()) == -1
+                    {
+                        report_exec_error(error_write, EXEC_STAGE_CHDIR);
+                    }
 
+                    // Use pre-validated CStrings (validated before fork)
                     libc::execvp(cmd_cstring.as_ptr(), argv_ptrs.as_ptr());
-                    libc::_exit(1);
+                    report_exec_error(error_write, EXEC_STAGE_EXEC);
                 }
             }
             child_pid => {
@@ -264,6 +317,18 @@ impl PtySpawner {
                 // The parent doesn't need the slave end; only the child uses it.
                 unsafe {
                     libc::close(slave_fd);
+                    libc::close(error_write);
+                }
+
+                if let Some((stage, errno)) = read_exec_error(error_read) {
+                    // SAFETY: The child has exited (or is about to) without
+                    // exec'ing; reap it so it doesn't linger as a zombie and
+                    // release the master fd, which is no longer needed.
+                    unsafe {
+                        libc::waitpid(child_pid, std::ptr::null_mut(), 0);
+                        libc::close(master_fd);
+                    }
+                    return Err(ExpectError::Spawn(self.exec_error(command, stage, errno)));
                 }
 
                 // Set non-blocking
@@ -284,6 +349,29 @@ impl PtySpawner {
         }
     }
 
+    /// Classify a failure the child reported over the exec error pipe.
+    #[cfg(unix)]
+    fn exec_error(&self, command: &str, stage: u8, errno: i32) -> SpawnError {
+        let error = io::Error::from_raw_os_error(errno);
+        if stage != EXEC_STAGE_CHDIR {
+            return SpawnError::from_exec_error(command, &error);
+        }
+
+        let path = self
+            .config
+            .working_dir
+            .as_ref()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        match error.kind() {
+            io::ErrorKind::NotFound | io::ErrorKind::NotADirectory => {
+                SpawnError::working_dir_missing(path)
+            }
+            io::ErrorKind::PermissionDenied => SpawnError::permission_denied(path),
+            _ => SpawnError::exec_failed(command, errno),
+        }
+    }
+
     /// Spawn a command on Windows using ConPTY.
     ///
     /// # Errors
@@ -299,11 +387,8 @@ impl PtySpawner {
         // Create configuration for rust-pty
         let pty_config = rust_pty::PtyConfig {
             window_size: self.config.dimensions,
-            // If env_mode is Clear, use empty env; otherwise inherit (env: None)
-            env: match self.config.env_mode {
-                EnvMode::Clear => Some(std::collections::HashMap::new()),
-                _ => None,
-            },
+            clear_env: matches!(self.config.env_mode, EnvMode::Clear),
+            working_directory: self.config.working_dir.clone(),
             ..Default::default()
         };
 
@@ -312,8 +397,21 @@ impl PtySpawner {
             WindowsPtySystem::spawn(command, args.iter().map(|s| s.as_str()), &pty_config)
                 .await
                 .map_err(|e| {
-                    ExpectError::Spawn(SpawnError::PtyAllocation {
-                        reason: format!("Windows ConPTY spawn failed: {e}"),
+                    ExpectError::Spawn(match e {
+                        // CreateProcessW failed: classify from GetLastError
+                        rust_pty::PtyError::Spawn(err) => {
+                            // ERROR_DIRECTORY: the working directory is invalid
+                            if err.raw_os_error() == Some(267)
+                                && let Some(dir) = &self.config.working_dir
+                            {
+                                SpawnError::working_dir_missing(dir.display().to_string())
+                            } else {
+                                SpawnError::from_exec_error(command, &err)
+                            }
+                        }
+                        e => SpawnError::PtyAllocation {
+                            reason: format!("Windows ConPTY spawn failed: {e}"),
+                        },
                     })
                 })?;
 
@@ -325,6 +423,118 @@ impl PtySpawner {
     }
 }
 
+/// Exec error pipe stage: changing to the working directory failed.
+#[cfg(unix)]
+const EXEC_STAGE_CHDIR: u8 = 1;
+
+/// Exec error pipe stage: executing the command failed.
+#[cfg(unix)]
+const EXEC_STAGE_EXEC: u8 = 2;
+
+/// Create the close-on-exec pipe used to report spawn failures from the child.
+///
+/// The flag is set atomically, so a child forked by another thread can never
+/// inherit the pipe.
+#[cfg(all(unix, not(target_vendor = "apple")))]
+#[allow(unsafe_code)]
+fn exec_error_pipe() -> io::Result<(libc::c_int, libc::c_int)> {
+    let mut fds = [0 as libc::c_int; 2];
+
+    // SAFETY: fds is a valid array of two c_ints for pipe2() to fill in.
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(fds.into())
+}
+
+/// Create the close-on-exec pipe used to report spawn failures from the child.
+///
+/// Apple platforms have no `pipe2`, so the flag is set after creating the
+/// pipe.
+#[cfg(target_vendor = "apple")]
+#[allow(unsafe_code)]
+fn exec_error_pipe() -> io::Result<(libc::c_int, libc::c_int)> {
+    let mut fds = [0 as libc::c_int; 2];
+
+    // SAFETY: fds is a valid array of two c_ints for pipe() to fill in.
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    for fd in fds {
+        // SAFETY: fd was just returned by pipe() and is owned by us.
+        if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+            let err = io::Error::last_os_error();
+            // SAFETY: Both fds are valid and not used anywhere else.
+            unsafe {
+                libc::close(fds[0]);
+                libc::close(fds[1]);
+            }
+            return Err(err);
+        }
+    }
+
+    Ok(fds.into())
+}
+
+/// Report the current errno for `stage` to the parent and exit the child.
+///
+/// Only async-signal-safe calls are made, as this runs between fork and exec.
+#[cfg(unix)]
+#[allow(unsafe_code)]
+fn report_exec_error(fd: libc::c_int, stage: u8) -> ! {
+    let errno = io::Error::last_os_error().raw_os_error().unwrap_or(0);
+    let mut message = [0u8; 5];
+    message[0] = stage;
+    message[1..].copy_from_slice(&errno.to_ne_bytes());
+
+    // SAFETY: fd is the write end of the exec error pipe and message is a valid
+    // buffer. _exit() never returns and skips atexit handlers, as required
+    // after fork().
+    unsafe {
+        libc::write(fd, message.as_ptr().cast(), message.len());
+        libc::_exit(127);
+    }
+}
+
+/// Read the stage and errno reported by the child, closing the pipe.
+///
+/// Returns `None` when the pipe closes without data, meaning exec succeeded.
+#[cfg(unix)]
+#[allow(unsafe_code)]
+fn read_exec_error(fd: libc::c_int) -> Option<(u8, i32)> {
+    let mut message = [0u8; 5];
+    let mut filled = 0;
+
+    while filled < message.len() {
+        // SAFETY: The buffer pointer and length stay within `message`.
+        let n = unsafe {
+            libc::read(
+                fd,
+                message[filled..].as_mut_ptr().cast(),
+                message.len() - filled,
+            )
+        };
+        match n {
+            0 => break,
+            n if n > 0 => filled += n as usize,
+            _ if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => {}
+            _ => break,
+        }
+    }
+
+    // SAFETY: fd is the read end of the exec error pipe, owned by the parent.
+    unsafe {
+        libc::close(fd);
+    }
+
+    (filled == message.len()).then(|| {
+        let errno = i32::from_ne_bytes([message[1], message[2], message[3], message[4]]);
+        (message[0], errno)
+    })
+}
+
 impl Default for PtySpawner {
     fn default() -> Self {
         Self::new()
@@ -586,6 +796,36 @@ impl AsyncPty {
         }
     }
 
+    /// Get the terminal's foreground process group.
+    ///
+    /// This is the process group of the job currently in control of the
+    /// terminal, which may differ from the spawned process (e.g. a command
+    /// started from a shell).
+    #[allow(unsafe_code)]
+    pub fn foreground_pgrp(&self) -> Result<i32> {
+        // SAFETY: The fd is a valid PTY master descriptor owned by us.
+        let pgrp = unsafe { libc::tcgetpgrp(*self.inner.get_ref()) };
+        if pgrp <= 0 {
+            Err(ExpectError::Io(io::Error::last_os_error()))
+        } else {
+            Ok(pgrp)
+        }
+    }
+
+    /// Send a signal to the terminal's foreground process group.
+    #[allow(unsafe_code)]
+    pub fn signal_foreground(&self, signal: i32) -> Result<()> {
+        let pgrp = self.foreground_pgrp()?;
+        // SAFETY: pgrp is a process group ID reported by the kernel for this PTY.
+        // killpg() returns an error if the group no longer exists.
+        let result = unsafe { libc::killpg(pgrp, signal) };
+        if result != 0 {
+            Err(ExpectError::Io(io::Error::last_os_error()))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Kill the child process.
     pub fn kill(&self) -> Result<()> {
         self.signal(libc::SIGKILL)
@@ -761,6 +1001,13 @@ impl WindowsAsyncPty {
         self.child.is_running()
     }
 
+    /// Generate a Ctrl+C console event for the child process.
+    pub fn interrupt(&self) -> Result<()> {
+        self.child
+            .signal(rust_pty::PtySignal::Interrupt)
+            .map_err(|e| ExpectError::Io(io::Error::other(format!("interrupt failed: {e}"))))
+    }
+
     /// Kill the child process.
     pub fn kill(&mut self) -> Result<()> {
         self.child
@@ -6,24 +6,43 @@
 //! - Retry policies with exponential backoff
 //! - Keepalive management
 //! - Resilient sessions with auto-reconnect
+//! - Local and remote port forwarding
+//! - Agent forwarding
+//! - SFTP file transfer
 
+#[cfg(feature = "ssh")]
+mod agent;
 pub mod auth;
 pub mod builder;
 pub mod channel;
+#[cfg(feature = "ssh")]
+pub mod forward;
 pub mod keepalive;
+#[cfg(feature = "ssh")]
+pub mod known_hosts;
 pub mod pool;
 pub mod resilient;
 pub mod retry;
 pub mod session;
+#[cfg(feature = "ssh")]
+pub mod sftp;
 
 // Re-export commonly used types
-pub use auth::{AuthMethod, HostKeyVerification, SshCredentials};
+#[cfg(feature = "ssh")]
+pub use auth::HostKeyCallback;
+pub use auth::{AuthMethod, HostKeyDecision, HostKeyVerification, SshCredentials};
 pub use builder::{SshSessionBuilder, parse_ssh_target};
-pub use channel::{ChannelConfig, ChannelRequest, ChannelType, SshChannel};
+pub use channel::{ChannelConfig, ChannelRequest, ChannelType, SshChannel, SshChannelStream};
+#[cfg(feature = "ssh")]
+pub use forward::{LocalForward, RemoteForward};
 pub use keepalive::{
     KeepaliveAction, KeepaliveConfig, KeepaliveManager, KeepaliveState, KeepaliveStats,
 };
+#[cfg(feature = "ssh")]
+pub use known_hosts::{HostKeyStatus, KnownHosts};
 pub use pool::{ConnectionPool, PoolConfig, PoolStats, PooledConnection};
 pub use resilient::{ResilientConfig, ResilientSession, ResilientState};
 pub use retry::{RetryPolicy, RetryState, RetryStrategy};
 pub use session::{SshConfig, SshSession, SshSessionState};
+#[cfg(feature = "ssh")]
+pub use sftp::{SftpClient, SftpFile, SftpMetadata};
@@ -0,0 +1,49 @@
+//! SSH agent forwarding.
+//!
+//! When a channel is opened with
+//! [`ChannelConfig::forward_agent`](super::ChannelConfig::forward_agent),
+//! the server may open `auth-agent@openssh.com` channels back to the
+//! client. Each one is proxied to the local agent.
+
+use std::path::{Path, PathBuf};
+
+use russh::Channel;
+use russh::client::Msg;
+
+/// Proxy an agent channel opened by the server to the local agent.
+///
+/// Connects to `identity_agent`, or to the agent named by `SSH_AUTH_SOCK`.
+pub(crate) fn accept(channel: Channel<Msg>, identity_agent: Option<PathBuf>) {
+    tokio::spawn(async move {
+        let mut agent = match connect_local(identity_agent.as_deref()).await {
+            Ok(agent) => agent,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to connect to local SSH agent");
+                let _ = channel.close().await;
+                return;
+            }
+        };
+        let mut stream = channel.into_stream();
+        let _ = tokio::io::copy_bidirectional(&mut agent, &mut stream).await;
+    });
+}
+
+/// Connect to `path`, or to the agent named by `SSH_AUTH_SOCK`.
+#[cfg(unix)]
+async fn connect_local(path: Option<&Path>) -> std::io::Result<tokio::net::UnixStream> {
+    if let Some(path) = path {
+        return tokio::net::UnixStream::connect(path).await;
+    }
+    let path = std::env::var_os("SSH_AUTH_SOCK").ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "SSH_AUTH_SOCK is not set")
+    })?;
+    tokio::net::UnixStream::connect(path).await
+}
+
+/// The Windows OpenSSH agent; custom agent paths are Unix-only.
+#[cfg(windows)]
+async fn connect_local(
+    _path: Option<&Path>,
+) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    tokio::net::windows::named_pipe::ClientOptions::new().open(r"\\.\pipe\openssh-ssh-agent")
+}
@@ -1,6 +1,8 @@
 //! SSH authentication methods.
 
 use std::path::PathBuf;
+#[cfg(feature = "ssh")]
+use std::sync::Arc;
 
 /// SSH authentication method.
 #[derive(Debug, Clone)]
@@ -252,7 +254,7 @@ impl Default for SshCredentials {
 ///
 /// The `AcceptAll` variant is only available when the `insecure-skip-verify` feature
 /// is enabled. Using it in production environments enables MITM attacks.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Clone)]
 #[non_exhaustive]
 #[derive(Default)]
 pub enum HostKeyVerification {
@@ -274,8 +276,73 @@ pub enum HostKeyVerification {
     KnownHosts,
     /// Accept on first use, then verify (Trust On First Use).
     Tofu,
+    /// Let a callback decide, e.g. by prompting the user.
+    ///
+    /// The callback is asked about every key the server presents; use
+    /// [`KnownHosts`](super::KnownHosts) inside it to only prompt for
+    /// unknown hosts. It runs on the connection task, so a blocking prompt
+    /// holds up the handshake until it returns.
+    #[cfg(feature = "ssh")]
+    Callback(HostKeyCallback),
+}
+
+/// Callback for [`HostKeyVerification::Callback`].
+///
+/// Called with the host, the port, and the key the server presented.
+#[cfg(feature = "ssh")]
+pub type HostKeyCallback =
+    Arc<dyn Fn(&str, u16, &russh::keys::PublicKey) -> HostKeyDecision + Send + Sync>;
+
+/// What a [`HostKeyVerification::Callback`] decided about a host key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostKeyDecision {
+    /// Trust the key for this connection only.
+    Accept,
+    /// Trust the key and append it to `known_hosts`.
+    AcceptAndSave,
+    /// Refuse the key and abort the connection.
+    Reject,
+}
+
+impl HostKeyVerification {
+    /// Verify host keys with a callback.
+    #[cfg(feature = "ssh")]
+    #[must_use]
+    pub fn callback<F>(callback: F) -> Self
+    where
+        F: Fn(&str, u16, &russh::keys::PublicKey) -> HostKeyDecision + Send + Sync + 'static,
+    {
+        Self::Callback(Arc::new(callback))
+    }
 }
 
+impl std::fmt::Debug for HostKeyVerification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "insecure-skip-verify")]
+            Self::AcceptAll => f.write_str("AcceptAll"),
+            Self::RejectUnknown => f.write_str("RejectUnknown"),
+            Self::KnownHosts => f.write_str("KnownHosts"),
+            Self::Tofu => f.write_str("Tofu"),
+            #[cfg(feature = "ssh")]
+            Self::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+impl PartialEq for HostKeyVerification {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            // Callbacks are only equal to themselves
+            #[cfg(feature = "ssh")]
+            (Self::Callback(a), Self::Callback(b)) => Arc::ptr_eq(a, b),
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for HostKeyVerification {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -369,4 +436,16 @@ mod tests {
         assert!(creds.auth_methods[1].is_keyboard_interactive());
         assert!(creds.auth_methods[2].is_password());
     }
+
+    #[cfg(feature = "ssh")]
+    #[test]
+    fn host_key_callback_equality() {
+        let callback = HostKeyVerification::callback(|_, _, _| HostKeyDecision::Reject);
+        let other = HostKeyVerification::callback(|_, _, _| HostKeyDecision::Reject);
+
+        assert_eq!(callback, callback.clone());
+        assert_ne!(callback, other);
+        assert_ne!(callback, HostKeyVerification::KnownHosts);
+        assert_eq!(format!("{callback:?}"), "Callback(..)");
+    }
 }
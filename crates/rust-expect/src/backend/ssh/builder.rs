@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use super::auth::{AuthMethod, HostKeyVerification, SshCredentials};
+use super::keepalive::KeepaliveConfig;
 use super::session::{SshConfig, SshSession};
 
 /// Builder for SSH sessions.
@@ -17,6 +18,9 @@ pub struct SshSessionBuilder {
     host_key_verification: HostKeyVerification,
     compression: bool,
     tcp_keepalive: Option<Duration>,
+    keepalive: KeepaliveConfig,
+    proxy_jump: Vec<SshConfig>,
+    identity_agent: Option<PathBuf>,
 }
 
 impl SshSessionBuilder {
@@ -101,7 +105,7 @@ impl SshSessionBuilder {
 
     /// Set host key verification policy.
     #[must_use]
-    pub const fn host_key_verification(mut self, policy: HostKeyVerification) -> Self {
+    pub fn host_key_verification(mut self, policy: HostKeyVerification) -> Self {
         self.host_key_verification = policy;
         self
     }
@@ -116,7 +120,7 @@ impl SshSessionBuilder {
     /// This method is only available when the `insecure-skip-verify` feature is enabled.
     #[cfg(feature = "insecure-skip-verify")]
     #[must_use]
-    pub const fn accept_all_keys(mut self) -> Self {
+    pub fn accept_all_keys(mut self) -> Self {
         self.host_key_verification = HostKeyVerification::AcceptAll;
         self
     }
@@ -135,6 +139,33 @@ impl SshSessionBuilder {
         self
     }
 
+    /// Set SSH-level keepalives.
+    ///
+    /// See [`SshConfig::keepalive`].
+    #[must_use]
+    pub const fn keepalive(mut self, keepalive: KeepaliveConfig) -> Self {
+        self.keepalive = keepalive;
+        self
+    }
+
+    /// Reach the host through jump hosts, nearest first.
+    ///
+    /// See [`SshConfig::proxy_jump`].
+    #[must_use]
+    pub fn proxy_jump(mut self, hops: Vec<SshConfig>) -> Self {
+        self.proxy_jump = hops;
+        self
+    }
+
+    /// Use the agent listening on `path`.
+    ///
+    /// See [`SshConfig::identity_agent`].
+    #[must_use]
+    pub fn identity_agent(mut self, path: impl Into<PathBuf>) -> Self {
+        self.identity_agent = Some(path.into());
+        self
+    }
+
     /// Build the session.
     pub fn build(self) -> crate::error::Result<SshSession> {
         let host = self
@@ -165,6 +196,9 @@ impl SshSessionBuilder {
             host_key_verification: self.host_key_verification,
             compression: self.compression,
             tcp_keepalive: self.tcp_keepalive,
+            keepalive: self.keepalive,
+            proxy_jump: self.proxy_jump,
+            identity_agent: self.identity_agent,
         };
 
         Ok(SshSession::new(config))
@@ -106,6 +106,8 @@ pub struct ChannelConfig {
     pub term: String,
     /// Terminal dimensions.
     pub dimensions: Dimensions,
+    /// Forward the local SSH agent.
+    pub forward_agent: bool,
 }
 
 impl Default for ChannelConfig {
@@ -118,6 +120,7 @@ impl Default for ChannelConfig {
             pty: true,
             term: "xterm-256color".to_string(),
             dimensions: Dimensions::default(),
+            forward_agent: false,
         }
     }
 }
@@ -156,6 +159,29 @@ impl ChannelConfig {
         self.buffer_size = size;
         self
     }
+
+    /// Forward the local SSH agent, like `ssh -A`.
+    ///
+    /// The channel sends `auth-agent-req@openssh.com` and agent channels
+    /// the server opens back are proxied to the local agent, the same one
+    /// [`AuthMethod::Agent`](super::AuthMethod::Agent) uses. Once any
+    /// channel asks for it, forwarding stays on for the rest of the
+    /// connection.
+    ///
+    /// # Security
+    ///
+    /// Anyone who controls the server, including its root user, can use
+    /// the forwarded agent to authenticate as you to other hosts for as
+    /// long as the connection is open. They cannot read the keys
+    /// themselves. Only forward the agent to hosts you trust; to reach a
+    /// host behind a bastion, prefer
+    /// [`SshConfig::proxy_jump`](super::SshConfig::proxy_jump), which
+    /// keeps the keys local.
+    #[must_use]
+    pub const fn forward_agent(mut self, enabled: bool) -> Self {
+        self.forward_agent = enabled;
+        self
+    }
 }
 
 // ============================================================================
@@ -180,6 +206,11 @@ pub struct SshChannelStream {
     exit_status: Option<u32>,
     /// Whether EOF has been received.
     eof_received: bool,
+    /// The connection the channel runs on, when the stream owns it.
+    ///
+    /// Declared last so the channel is dropped before the connection is
+    /// closed.
+    connection: Option<super::session::SshSession>,
 }
 
 #[cfg(feature = "ssh")]
@@ -191,6 +222,7 @@ impl std::fmt::Debug for SshChannelStream {
             .field("read_buffer_len", &self.read_buffer.len())
             .field("exit_status", &self.exit_status)
             .field("eof_received", &self.eof_received)
+            .field("owns_connection", &self.connection.is_some())
             .finish()
     }
 }
@@ -207,9 +239,17 @@ impl SshChannelStream {
             read_buffer: VecDeque::with_capacity(32768),
             exit_status: None,
             eof_received: false,
+            connection: None,
         }
     }
 
+    /// Keep `connection` open for as long as this stream lives.
+    #[must_use]
+    pub(crate) fn owning(mut self, connection: super::session::SshSession) -> Self {
+        self.connection = Some(connection);
+        self
+    }
+
     /// Get the configuration.
     #[must_use]
     pub const fn config(&self) -> &ChannelConfig {
@@ -235,6 +275,9 @@ impl SshChannelStream {
     }
 
     /// Get the exit status if the command has completed.
+    ///
+    /// The status usually arrives after EOF; use
+    /// [`wait_exit_status`](Self::wait_exit_status) to wait for it.
     #[must_use]
     pub const fn exit_status(&self) -> Option<u32> {
         self.exit_status
@@ -264,6 +307,19 @@ impl SshChannelStream {
             })
     }
 
+    /// Ask the server to forward agent connections over this connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request cannot be sent.
+    pub async fn request_agent_forward(&mut self) -> crate::error::Result<()> {
+        self.channel.agent_forward(false).await.map_err(|e| {
+            crate::error::ExpectError::Ssh(SshError::Channel {
+                reason: format!("Agent forwarding request failed: {e}"),
+            })
+        })
+    }
+
     /// Request a shell on this channel.
     ///
     /// # Errors
@@ -376,6 +432,30 @@ impl SshChannelStream {
         Some(msg)
     }
 
+    /// Wait for the remote command to finish and return its exit status.
+    ///
+    /// Servers usually send the exit status after EOF, so call this once
+    /// the output has been read. Any output that arrives while waiting is
+    /// kept in the read buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the channel closes without an exit status, for
+    /// example when the remote command was killed by a signal.
+    pub async fn wait_exit_status(&mut self) -> crate::error::Result<u32> {
+        while self.exit_status.is_none() {
+            match self.wait().await {
+                None | Some(russh::ChannelMsg::Close) => break,
+                Some(_) => {}
+            }
+        }
+        self.exit_status.ok_or_else(|| {
+            crate::error::ExpectError::Ssh(SshError::Channel {
+                reason: "channel closed without an exit status".to_string(),
+            })
+        })
+    }
+
     /// Read available data from the internal buffer.
     ///
     /// Returns the number of bytes read.
@@ -629,6 +709,8 @@ mod tests {
         assert_eq!(config.term, "vt100");
         assert_eq!(config.dimensions.cols, 120);
         assert_eq!(config.buffer_size, 65536);
+        assert!(!config.forward_agent);
+        assert!(config.forward_agent(true).forward_agent);
     }
 
     #[cfg(not(feature = "ssh"))]
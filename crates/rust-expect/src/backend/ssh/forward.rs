@@ -0,0 +1,254 @@
+//! SSH port forwarding.
+//!
+//! [`SshSession::forward_local`](super::SshSession::forward_local) works like
+//! `ssh -L`: it listens locally and tunnels each accepted connection through
+//! a `direct-tcpip` channel to a host reachable from the server.
+//! [`SshSession::forward_remote`](super::SshSession::forward_remote) works
+//! like `ssh -R`: the server listens and each connection it forwards back is
+//! connected to a local target.
+//!
+//! Both return a handle that tears the forward down when dropped.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use russh::Channel;
+use russh::client::Msg;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::{JoinHandle, JoinSet};
+
+use super::session::SharedHandle;
+
+/// Remote forwards registered on a connection, keyed by the address and
+/// port the server listens on.
+pub(crate) type RemoteForwards = Arc<Mutex<HashMap<(String, u32), RemoteTarget>>>;
+
+/// Where connections for a remote forward are sent.
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteTarget {
+    host: String,
+    port: u16,
+    /// Closed when the owning [`RemoteForward`] is dropped.
+    shutdown: watch::Receiver<()>,
+}
+
+/// A local port forward, like `ssh -L`.
+///
+/// Dropping the handle stops listening and closes the tunnelled
+/// connections.
+#[derive(Debug)]
+pub struct LocalForward {
+    local_addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl LocalForward {
+    /// Bind `listener` and tunnel each connection to `host:port`.
+    pub(crate) fn start(
+        listener: TcpListener,
+        handle: SharedHandle,
+        host: String,
+        port: u16,
+    ) -> std::io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let task = tokio::spawn(accept_local(listener, handle, host, port));
+        Ok(Self { local_addr, task })
+    }
+
+    /// The local address being listened on.
+    ///
+    /// Useful when the forward was bound to port 0.
+    #[must_use]
+    pub const fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for LocalForward {
+    fn drop(&mut self) {
+        // Aborting the accept loop drops its JoinSet, which aborts the
+        // per-connection tasks as well
+        self.task.abort();
+    }
+}
+
+async fn accept_local(listener: TcpListener, handle: SharedHandle, host: String, port: u16) {
+    let mut connections = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((mut socket, peer)) = accepted else {
+                    break;
+                };
+                let handle = Arc::clone(&handle);
+                let host = host.clone();
+                connections.spawn(async move {
+                    let channel = handle
+                        .read()
+                        .await
+                        .channel_open_direct_tcpip(
+                            host.as_str(),
+                            u32::from(port),
+                            peer.ip().to_string(),
+                            u32::from(peer.port()),
+                        )
+                        .await;
+                    match channel {
+                        Ok(channel) => {
+                            let mut stream = channel.into_stream();
+                            let _ = tokio::io::copy_bidirectional(&mut socket, &mut stream).await;
+                        }
+                        Err(e) => {
+                            tracing::warn!(%host, port, error = %e, "direct-tcpip channel refused");
+                        }
+                    }
+                });
+            }
+            Some(_) = connections.join_next() => {}
+        }
+    }
+}
+
+/// A remote port forward, like `ssh -R`.
+///
+/// Dropping the handle cancels the forward on the server and closes the
+/// connections it carried.
+pub struct RemoteForward {
+    address: String,
+    port: u32,
+    handle: SharedHandle,
+    forwards: RemoteForwards,
+    _shutdown: watch::Sender<()>,
+}
+
+impl std::fmt::Debug for RemoteForward {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteForward")
+            .field("address", &self.address)
+            .field("port", &self.port)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RemoteForward {
+    /// Register a forward the server has accepted.
+    pub(crate) fn register(
+        handle: SharedHandle,
+        forwards: RemoteForwards,
+        address: String,
+        port: u32,
+        local_host: String,
+        local_port: u16,
+    ) -> Self {
+        let (shutdown, receiver) = watch::channel(());
+        let target = RemoteTarget {
+            host: local_host,
+            port: local_port,
+            shutdown: receiver,
+        };
+        lock(&forwards).insert((address.clone(), port), target);
+        Self {
+            address,
+            port,
+            handle,
+            forwards,
+            _shutdown: shutdown,
+        }
+    }
+
+    /// The address the server listens on.
+    #[must_use]
+    pub fn remote_address(&self) -> &str {
+        &self.address
+    }
+
+    /// The port the server listens on.
+    ///
+    /// When the forward was requested on port 0, this is the port the
+    /// server picked.
+    #[must_use]
+    pub fn remote_port(&self) -> u16 {
+        u16::try_from(self.port).unwrap_or_default()
+    }
+}
+
+impl Drop for RemoteForward {
+    fn drop(&mut self) {
+        lock(&self.forwards).remove(&(self.address.clone(), self.port));
+
+        // Cancelling needs a round trip, so it can only happen on a runtime
+        if let Ok(rt) = tokio::runtime::Handle::try_current() {
+            let handle = Arc::clone(&self.handle);
+            let address = std::mem::take(&mut self.address);
+            let port = self.port;
+            rt.spawn(async move {
+                let _ = handle
+                    .read()
+                    .await
+                    .cancel_tcpip_forward(address, port)
+                    .await;
+            });
+        }
+    }
+}
+
+/// Connect a channel the server opened for a remote forward to its target.
+pub(crate) fn accept_remote(
+    forwards: &RemoteForwards,
+    channel: Channel<Msg>,
+    connected_address: &str,
+    connected_port: u32,
+) {
+    let target = {
+        let forwards = lock(forwards);
+        // Servers may report the bound address differently from how it was
+        // requested, so fall back to matching the port alone
+        forwards
+            .get(&(connected_address.to_string(), connected_port))
+            .or_else(|| {
+                forwards
+                    .iter()
+                    .find(|((_, port), _)| *port == connected_port)
+                    .map(|(_, target)| target)
+            })
+            .cloned()
+    };
+    let Some(mut target) = target else {
+        tracing::warn!(
+            address = %connected_address,
+            port = connected_port,
+            "Ignoring forwarded connection for unknown remote forward"
+        );
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut socket = match TcpStream::connect((target.host.as_str(), target.port)).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                tracing::warn!(
+                    host = %target.host,
+                    port = target.port,
+                    error = %e,
+                    "Failed to connect remote forward target"
+                );
+                return;
+            }
+        };
+        let mut stream = channel.into_stream();
+        tokio::select! {
+            _ = tokio::io::copy_bidirectional(&mut socket, &mut stream) => {}
+            _ = target.shutdown.changed() => {}
+        }
+    });
+}
+
+fn lock(
+    forwards: &RemoteForwards,
+) -> std::sync::MutexGuard<'_, HashMap<(String, u32), RemoteTarget>> {
+    forwards
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
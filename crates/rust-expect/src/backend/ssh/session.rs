@@ -3,10 +3,12 @@
 //! This module provides SSH session handling with actual russh integration
 //! when the `ssh` feature is enabled.
 
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 use super::auth::{AuthMethod, HostKeyVerification, SshCredentials};
+use super::keepalive::KeepaliveConfig;
 #[cfg(feature = "ssh")]
 use crate::error::SshError;
 
@@ -27,6 +29,12 @@ pub struct SshConfig {
     pub compression: bool,
     /// TCP keepalive interval.
     pub tcp_keepalive: Option<Duration>,
+    /// SSH-level keepalives.
+    pub keepalive: KeepaliveConfig,
+    /// Jump hosts to tunnel through, nearest first.
+    pub proxy_jump: Vec<Self>,
+    /// Agent socket, instead of `SSH_AUTH_SOCK`.
+    pub identity_agent: Option<PathBuf>,
 }
 
 impl Default for SshConfig {
@@ -39,6 +47,9 @@ impl Default for SshConfig {
             host_key_verification: HostKeyVerification::default(),
             compression: false,
             tcp_keepalive: Some(Duration::from_secs(60)),
+            keepalive: KeepaliveConfig::default(),
+            proxy_jump: Vec::new(),
+            identity_agent: None,
         }
     }
 }
@@ -83,7 +94,7 @@ impl SshConfig {
 
     /// Set host key verification.
     #[must_use]
-    pub const fn host_key_verification(mut self, policy: HostKeyVerification) -> Self {
+    pub fn host_key_verification(mut self, policy: HostKeyVerification) -> Self {
         self.host_key_verification = policy;
         self
     }
@@ -95,6 +106,43 @@ impl SshConfig {
         self
     }
 
+    /// Set SSH-level keepalives.
+    ///
+    /// While connected, a `keepalive@openssh.com` request is sent whenever
+    /// nothing has been received for `interval`. After `max_missed`
+    /// unanswered keepalives the connection is closed and the session
+    /// reports [`SshSessionState::Error`]. The keepalive is off when
+    /// `enabled` or `use_ssh_keepalive` is false.
+    #[must_use]
+    pub const fn keepalive(mut self, keepalive: KeepaliveConfig) -> Self {
+        self.keepalive = keepalive;
+        self
+    }
+
+    /// Reach the host through jump hosts, like OpenSSH's `ProxyJump`.
+    ///
+    /// The first host is connected to directly. Each later hop, and finally
+    /// this host, is reached over a `direct-tcpip` channel opened on the hop
+    /// before it. Every hop authenticates and verifies its host key with its
+    /// own config; any `proxy_jump` set on a hop is ignored.
+    #[must_use]
+    pub fn proxy_jump(mut self, hops: Vec<Self>) -> Self {
+        self.proxy_jump = hops;
+        self
+    }
+
+    /// Use the agent listening on `path`, like OpenSSH's `IdentityAgent`.
+    ///
+    /// The agent is used for [`AuthMethod::Agent`] and for
+    /// [agent forwarding](super::ChannelConfig::forward_agent). Without
+    /// it, the agent named by `SSH_AUTH_SOCK` is used. Only Unix sockets
+    /// are supported; on Windows the system agent is always used.
+    #[must_use]
+    pub fn identity_agent(mut self, path: impl Into<PathBuf>) -> Self {
+        self.identity_agent = Some(path.into());
+        self
+    }
+
     /// Get the address string.
     #[must_use]
     pub fn address(&self) -> String {
@@ -123,11 +171,16 @@ pub enum SshSessionState {
 
 #[cfg(feature = "ssh")]
 mod russh_impl {
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
 
     use russh::client;
     use russh::keys::{PrivateKey, PrivateKeyWithHashAlg, PublicKey};
 
+    use super::super::agent;
+    use super::super::auth::HostKeyDecision;
+    use super::super::forward::{self, RemoteForwards};
+    use super::super::known_hosts::{HostKeyStatus, KnownHosts};
     use super::{Arc, AuthMethod, HostKeyVerification, SshCredentials, SshError};
 
     /// Client handler for russh that manages host key verification.
@@ -138,6 +191,16 @@ mod russh_impl {
         pub host: String,
         /// The port we're connecting to.
         pub port: u16,
+        /// Remote forwards to route server-opened channels to.
+        pub(crate) remote_forwards: RemoteForwards,
+        /// Set once a channel has asked for agent forwarding; agent
+        /// channels are refused until then.
+        pub(crate) agent_forwarding: Arc<AtomicBool>,
+        /// Agent socket forwarded agent channels connect to.
+        pub(crate) identity_agent: Option<PathBuf>,
+        /// Dropped with the handler when the connection ends, which tells
+        /// the session's receiver that it is gone.
+        pub(crate) _alive: tokio::sync::watch::Sender<()>,
     }
 
     impl client::Handler for SshClientHandler {
@@ -148,7 +211,7 @@ mod russh_impl {
             &mut self,
             server_public_key: &PublicKey,
         ) -> Result<bool, Self::Error> {
-            match self.host_key_verification {
+            match &self.host_key_verification {
                 #[cfg(feature = "insecure-skip-verify")]
                 HostKeyVerification::AcceptAll => {
                     tracing::warn!(
@@ -171,10 +234,84 @@ mod russh_impl {
                 }
                 HostKeyVerification::Tofu => {
                     // Trust on first use - accept and save to known_hosts
-                    handle_tofu(&self.host, self.port, server_public_key)
+                    accept_and_save(&self.host, self.port, server_public_key)
+                }
+                HostKeyVerification::Callback(callback) => {
+                    match callback(&self.host, self.port, server_public_key) {
+                        HostKeyDecision::Accept => Ok(true),
+                        HostKeyDecision::AcceptAndSave => {
+                            accept_and_save(&self.host, self.port, server_public_key)
+                        }
+                        HostKeyDecision::Reject => {
+                            tracing::debug!(
+                                host = %self.host,
+                                "Host key rejected by verification callback"
+                            );
+                            Ok(false)
+                        }
+                    }
                 }
             }
         }
+
+        /// Log why the connection ended.
+        async fn disconnected(
+            &mut self,
+            reason: client::DisconnectReason<Self::Error>,
+        ) -> Result<(), Self::Error> {
+            match reason {
+                client::DisconnectReason::ReceivedDisconnect(info) => {
+                    tracing::info!(
+                        host = %self.host,
+                        message = %info.message,
+                        "SSH server closed the connection"
+                    );
+                    Ok(())
+                }
+                client::DisconnectReason::Error(e) => {
+                    tracing::warn!(host = %self.host, error = %e, "SSH connection lost");
+                    Err(e)
+                }
+            }
+        }
+
+        /// Route a connection from a remote forward to its local target.
+        async fn server_channel_open_forwarded_tcpip(
+            &mut self,
+            channel: russh::Channel<client::Msg>,
+            connected_address: &str,
+            connected_port: u32,
+            _originator_address: &str,
+            _originator_port: u32,
+            _session: &mut client::Session,
+        ) -> Result<(), Self::Error> {
+            forward::accept_remote(
+                &self.remote_forwards,
+                channel,
+                connected_address,
+                connected_port,
+            );
+            Ok(())
+        }
+
+        /// Proxy an agent channel to the local agent, if forwarding was
+        /// requested.
+        async fn server_channel_open_agent_forward(
+            &mut self,
+            channel: russh::Channel<client::Msg>,
+            _session: &mut client::Session,
+        ) -> Result<(), Self::Error> {
+            if self.agent_forwarding.load(Ordering::SeqCst) {
+                agent::accept(channel, self.identity_agent.clone());
+            } else {
+                tracing::warn!(
+                    host = %self.host,
+                    "Refusing agent channel; agent forwarding was not requested"
+                );
+                let _ = channel.close().await;
+            }
+            Ok(())
+        }
     }
 
     /// Check a server key against the `known_hosts` file.
@@ -208,104 +345,45 @@ mod russh_impl {
             }
         };
 
-        // Build the host pattern to search for
-        // Standard SSH uses "host" for port 22, "[host]:port" for non-standard ports
-        let host_pattern = if port == 22 {
-            host.to_string()
-        } else {
-            format!("[{host}]:{port}")
-        };
-
-        // Parse each line looking for matching host entries
-        for line in contents.lines() {
-            let line = line.trim();
-
-            // Skip empty lines and comments
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-
-            // Skip markers like @cert-authority and @revoked for now
-            if line.starts_with('@') {
-                continue;
-            }
-
-            // Parse: hostnames keytype base64key [comment]
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() < 3 {
-                continue;
+        match KnownHosts::parse(&contents).check_key(host, port, server_public_key) {
+            HostKeyStatus::Trusted => {
+                tracing::debug!(
+                    host = %host,
+                    "Host key verified against known_hosts"
+                );
+                Ok(true)
             }
-
-            let hostnames = parts[0];
-            let key_type = parts[1];
-            let key_data = parts[2];
-
-            // Check if this line matches our host
-            let host_matches = hostnames.split(',').any(|h| {
-                let h = h.trim();
-                h == host || h == host_pattern || h == format!("{host},*") || h == "*"
-            });
-
-            if !host_matches {
-                continue;
+            HostKeyStatus::Revoked => {
+                tracing::error!(
+                    host = %host,
+                    "Host key is marked @revoked in known_hosts"
+                );
+                Ok(false)
             }
-
-            // Try to parse and compare the key
-            if let Some(stored_key) = parse_known_hosts_key(key_type, key_data) {
-                if keys_match(&stored_key, server_public_key) {
-                    tracing::debug!(
-                        host = %host,
-                        "Host key verified against known_hosts"
-                    );
-                    return Ok(true);
-                }
+            HostKeyStatus::Changed => {
                 // Key mismatch - potential MITM attack!
                 tracing::error!(
                     host = %host,
                     "HOST KEY MISMATCH! Possible man-in-the-middle attack!"
                 );
-                return Ok(false);
-            }
-        }
-
-        // Host not found in known_hosts
-        tracing::warn!(
-            host = %host,
-            "Host not found in known_hosts file"
-        );
-        Ok(false)
-    }
-
-    /// Parse a public key from `known_hosts` format.
-    fn parse_known_hosts_key(key_type: &str, key_data: &str) -> Option<PublicKey> {
-        // The key_type should match what's in the decoded data
-        // Common types: ssh-rsa, ssh-ed25519, ecdsa-sha2-nistp256, etc.
-        match key_type {
-            "ssh-ed25519"
-            | "ssh-rsa"
-            | "ecdsa-sha2-nistp256"
-            | "ecdsa-sha2-nistp384"
-            | "ecdsa-sha2-nistp521" => {
-                // Try to parse using russh-keys (takes base64 directly)
-                russh::keys::parse_public_key_base64(key_data).ok()
+                Ok(false)
             }
-            _ => {
-                tracing::debug!(key_type = %key_type, "Unknown key type in known_hosts");
-                None
+            HostKeyStatus::Unknown => {
+                tracing::warn!(
+                    host = %host,
+                    "Host not found in known_hosts file"
+                );
+                Ok(false)
             }
         }
     }
 
-    /// Compare two public keys for equality.
-    fn keys_match(stored: &PublicKey, server: &PublicKey) -> bool {
-        // Compare the key fingerprints using SHA-256 (standard for OpenSSH)
-        use russh::keys::HashAlg;
-        stored.fingerprint(HashAlg::Sha256) == server.fingerprint(HashAlg::Sha256)
-    }
-
-    /// Handle Trust On First Use - accept and save the key.
+    /// Accept the key and save it to `known_hosts`.
+    ///
+    /// Used for Trust On First Use and callback decisions. Failing to save
+    /// still accepts the key.
     #[allow(clippy::unnecessary_wraps)]
-    fn handle_tofu(
+    fn accept_and_save(
         host: &str,
         port: u16,
         server_public_key: &PublicKey,
@@ -360,7 +438,7 @@ mod russh_impl {
                     tracing::info!(
                         host = %host,
                         path = %known_hosts_path.display(),
-                        "Added host key to known_hosts (TOFU)"
+                        "Added host key to known_hosts"
                     );
 
                     // Set proper permissions on Unix
@@ -480,6 +558,7 @@ mod russh_impl {
     pub async fn authenticate(
         handle: &mut client::Handle<SshClientHandler>,
         credentials: &SshCredentials,
+        identity_agent: Option<&Path>,
     ) -> crate::error::Result<bool> {
         let username = &credentials.username;
 
@@ -563,8 +642,17 @@ mod russh_impl {
                     tracing::debug!(user = %username, "Attempting SSH agent authentication");
 
                     // Connect to the SSH agent
+                    #[cfg(not(unix))]
+                    let _ = identity_agent;
+                    #[cfg(unix)]
+                    let agent = match identity_agent {
+                        Some(path) => {
+                            russh::keys::agent::client::AgentClient::connect_uds(path).await
+                        }
+                        None => russh::keys::agent::client::AgentClient::connect_env().await,
+                    };
                     #[cfg(unix)]
-                    match russh::keys::agent::client::AgentClient::connect_env().await {
+                    match agent {
                         Ok(mut agent) => {
                             // Get list of keys from agent
                             match agent.request_identities().await {
@@ -950,9 +1038,31 @@ pub struct SshSession {
     /// Current state.
     state: SshSessionState,
     /// The russh client handle (when connected).
-    handle: Option<russh::client::Handle<russh_impl::SshClientHandler>>,
+    handle: Option<SharedHandle>,
+    /// Remote forwards registered with the connection's handler.
+    remote_forwards: Option<super::forward::RemoteForwards>,
+    /// Whether the connection's handler accepts agent channels.
+    agent_forwarding: Option<Arc<std::sync::atomic::AtomicBool>>,
+    /// Connections to the jump hosts, nearest first.
+    jumps: Vec<russh::client::Handle<russh_impl::SshClientHandler>>,
+    /// Closed when the connection ends.
+    alive: Option<tokio::sync::watch::Receiver<()>>,
 }
 
+/// A freshly opened connection, before authentication.
+#[cfg(feature = "ssh")]
+struct Transport {
+    handle: russh::client::Handle<russh_impl::SshClientHandler>,
+    remote_forwards: super::forward::RemoteForwards,
+    agent_forwarding: Arc<std::sync::atomic::AtomicBool>,
+    alive: tokio::sync::watch::Receiver<()>,
+}
+
+/// A russh client handle shared with port forwarding tasks.
+#[cfg(feature = "ssh")]
+pub type SharedHandle =
+    Arc<tokio::sync::RwLock<russh::client::Handle<russh_impl::SshClientHandler>>>;
+
 #[cfg(feature = "ssh")]
 impl std::fmt::Debug for SshSession {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -960,6 +1070,7 @@ impl std::fmt::Debug for SshSession {
             .field("config", &self.config)
             .field("state", &self.state)
             .field("connected", &self.handle.is_some())
+            .field("jumps", &self.jumps.len())
             .finish()
     }
 }
@@ -973,6 +1084,10 @@ impl SshSession {
             config,
             state: SshSessionState::Disconnected,
             handle: None,
+            remote_forwards: None,
+            agent_forwarding: None,
+            jumps: Vec::new(),
+            alive: None,
         }
     }
 
@@ -983,68 +1098,85 @@ impl SshSession {
     }
 
     /// Get current state.
+    ///
+    /// A connected session moves to [`SshSessionState::Error`] once the
+    /// connection drops; see [`is_alive`](Self::is_alive).
     #[must_use]
-    pub const fn state(&self) -> SshSessionState {
-        self.state
+    pub fn state(&self) -> SshSessionState {
+        if self.state == SshSessionState::Connected && !self.is_alive() {
+            SshSessionState::Error
+        } else {
+            self.state
+        }
     }
 
     /// Check if connected.
     #[must_use]
     pub fn is_connected(&self) -> bool {
-        self.state == SshSessionState::Connected && self.handle.is_some()
+        self.state() == SshSessionState::Connected && self.handle.is_some()
+    }
+
+    /// Check whether the connection to the server is still up.
+    ///
+    /// Turns false when the server closes the connection, the link fails,
+    /// or keepalives go unanswered (see [`SshConfig::keepalive`]).
+    #[must_use]
+    pub fn is_alive(&self) -> bool {
+        self.alive
+            .as_ref()
+            .is_some_and(|alive| alive.has_changed().is_ok())
+    }
+
+    /// Wait until the connection to the server ends.
+    ///
+    /// Returns immediately when not connected. Useful for long-lived
+    /// automations that need to reconnect when the link drops.
+    pub async fn closed(&self) {
+        if let Some(alive) = &self.alive {
+            let mut alive = alive.clone();
+            // Nothing is ever sent, so this only returns once the handler
+            // holding the sender is dropped
+            while alive.changed().await.is_ok() {}
+        }
     }
 
     /// Connect to the SSH server asynchronously.
     ///
     /// This establishes a TCP connection, performs the SSH handshake,
-    /// and authenticates using the configured credentials.
+    /// and authenticates using the configured credentials. With
+    /// [`SshConfig::proxy_jump`], each jump host is connected and
+    /// authenticated in turn and the connection is tunnelled through them.
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - The TCP connection fails
+    /// - A jump host fails to connect, authenticate or open the tunnel
     /// - Host key verification fails
     /// - All authentication methods are exhausted
     pub async fn connect_async(&mut self) -> crate::error::Result<()> {
         self.state = SshSessionState::Connecting;
 
-        // Create the russh client config
-        let ssh_config = Arc::new(russh::client::Config::default());
-
-        // Create the handler
-        let handler = russh_impl::SshClientHandler {
-            host_key_verification: self.config.host_key_verification,
-            host: self.config.host.clone(),
-            port: self.config.port,
-        };
+        let (jumps, tunnel) = Self::connect_jumps(&self.config)
+            .await
+            .inspect_err(|_| self.state = SshSessionState::Error)?;
 
         // Connect to the server
-        let addr = (self.config.host.as_str(), self.config.port);
         tracing::info!(
             host = %self.config.host,
             port = %self.config.port,
+            jumps = jumps.len(),
             "Connecting to SSH server"
         );
 
-        let mut handle = tokio::time::timeout(
-            self.config.connect_timeout,
-            russh::client::connect(ssh_config, addr, handler),
-        )
-        .await
-        .map_err(|_| {
-            self.state = SshSessionState::Error;
-            crate::error::ExpectError::Ssh(SshError::Timeout {
-                duration: self.config.connect_timeout,
-            })
-        })?
-        .map_err(|e| {
-            self.state = SshSessionState::Error;
-            crate::error::ExpectError::Ssh(SshError::Connection {
-                host: self.config.host.clone(),
-                port: self.config.port,
-                reason: e.to_string(),
-            })
-        })?;
+        let Transport {
+            mut handle,
+            remote_forwards,
+            agent_forwarding,
+            alive,
+        } = Self::open_transport(&self.config, tunnel)
+            .await
+            .inspect_err(|_| self.state = SshSessionState::Error)?;
 
         // Authenticate
         self.state = SshSessionState::Authenticating;
@@ -1053,11 +1185,20 @@ impl SshSession {
             "Authenticating with SSH server"
         );
 
-        russh_impl::authenticate(&mut handle, &self.config.credentials).await?;
+        russh_impl::authenticate(
+            &mut handle,
+            &self.config.credentials,
+            self.config.identity_agent.as_deref(),
+        )
+        .await?;
 
         // Success!
         self.state = SshSessionState::Connected;
-        self.handle = Some(handle);
+        self.handle = Some(Arc::new(tokio::sync::RwLock::new(handle)));
+        self.remote_forwards = Some(remote_forwards);
+        self.agent_forwarding = Some(agent_forwarding);
+        self.jumps = jumps;
+        self.alive = Some(alive);
 
         tracing::info!(
             host = %self.config.host,
@@ -1068,6 +1209,114 @@ impl SshSession {
         Ok(())
     }
 
+    /// Connect and authenticate to each jump host in turn.
+    ///
+    /// Returns the jump host handles, nearest first, and a tunnel from the
+    /// last of them to `config`'s host.
+    async fn connect_jumps(
+        config: &SshConfig,
+    ) -> crate::error::Result<(
+        Vec<russh::client::Handle<russh_impl::SshClientHandler>>,
+        Option<russh::ChannelStream<russh::client::Msg>>,
+    )> {
+        let mut jumps = Vec::with_capacity(config.proxy_jump.len());
+        let mut tunnel = None;
+
+        let next_hops = config
+            .proxy_jump
+            .iter()
+            .skip(1)
+            .chain(std::iter::once(config));
+        for (hop, next) in config.proxy_jump.iter().zip(next_hops) {
+            tracing::info!(
+                host = %hop.host,
+                port = %hop.port,
+                "Connecting to jump host"
+            );
+            let mut handle = Self::open_transport(hop, tunnel.take()).await?.handle;
+            russh_impl::authenticate(&mut handle, &hop.credentials, hop.identity_agent.as_deref())
+                .await?;
+
+            let channel = handle
+                .channel_open_direct_tcpip(next.host.as_str(), u32::from(next.port), "127.0.0.1", 0)
+                .await
+                .map_err(|e| {
+                    crate::error::ExpectError::Ssh(SshError::Connection {
+                        host: next.host.clone(),
+                        port: next.port,
+                        reason: format!("jump host {} refused the tunnel: {e}", hop.address()),
+                    })
+                })?;
+            tunnel = Some(channel.into_stream());
+            jumps.push(handle);
+        }
+
+        Ok((jumps, tunnel))
+    }
+
+    /// Perform the SSH handshake with `config`'s host.
+    ///
+    /// Connects over TCP, or over `tunnel` when the host is behind a jump
+    /// host.
+    async fn open_transport(
+        config: &SshConfig,
+        tunnel: Option<russh::ChannelStream<russh::client::Msg>>,
+    ) -> crate::error::Result<Transport> {
+        // Create the russh client config; russh sends the keepalives
+        let keepalive = &config.keepalive;
+        let ssh_keepalive = keepalive.enabled && keepalive.use_ssh_keepalive;
+        let ssh_config = Arc::new(russh::client::Config {
+            keepalive_interval: ssh_keepalive.then_some(keepalive.interval),
+            keepalive_max: usize::try_from(keepalive.max_missed).unwrap_or(usize::MAX),
+            ..Default::default()
+        });
+
+        // Create the handler
+        let remote_forwards = super::forward::RemoteForwards::default();
+        let agent_forwarding = Arc::default();
+        let (alive_tx, alive) = tokio::sync::watch::channel(());
+        let handler = russh_impl::SshClientHandler {
+            host_key_verification: config.host_key_verification.clone(),
+            host: config.host.clone(),
+            port: config.port,
+            remote_forwards: Arc::clone(&remote_forwards),
+            agent_forwarding: Arc::clone(&agent_forwarding),
+            identity_agent: config.identity_agent.clone(),
+            _alive: alive_tx,
+        };
+
+        let connect = async {
+            if let Some(stream) = tunnel {
+                russh::client::connect_stream(ssh_config, stream, handler).await
+            } else {
+                let addr = (config.host.as_str(), config.port);
+                russh::client::connect(ssh_config, addr, handler).await
+            }
+        };
+
+        let handle = tokio::time::timeout(config.connect_timeout, connect)
+            .await
+            .map_err(|_| {
+                crate::error::ExpectError::Ssh(SshError::Timeout {
+                    duration: config.connect_timeout,
+                })
+            })?
+            .map_err(|e| {
+                crate::error::ExpectError::Ssh(SshError::Connection {
+                    host: config.host.clone(),
+                    port: config.port,
+                    reason: e.to_string(),
+                })
+            })?;
+
+        Ok(Transport {
+            handle,
+            remote_forwards,
+            agent_forwarding,
+            alive,
+        })
+    }
+
     /// Connect synchronously by blocking on the async connection.
     ///
     /// This is a convenience method that uses the current tokio runtime
@@ -1096,36 +1345,78 @@ impl SshSession {
         }
     }
 
-    /// Disconnect from the SSH server.
+    /// Disconnect from the SSH server without waiting.
+    ///
+    /// The session is marked disconnected at once. The disconnect message
+    /// is sent from a task spawned on the current tokio runtime, so this
+    /// never blocks and is safe to call from `Drop` on any runtime flavor;
+    /// outside a runtime the connection is simply dropped. Nothing reports
+    /// whether the message was sent; use [`disconnect_async`] to wait for
+    /// the server to be told.
+    ///
+    /// [`disconnect_async`]: Self::disconnect_async
     pub fn disconnect(&mut self) {
-        if let Some(handle) = self.handle.take() {
-            // Attempt graceful disconnect
-            let _ = tokio::runtime::Handle::try_current().map(|rt| {
-                tokio::task::block_in_place(|| {
-                    rt.block_on(async {
-                        let _ = handle
-                            .disconnect(russh::Disconnect::ByApplication, "", "en")
-                            .await;
-                    });
-                });
-            });
+        if let Some((handle, jumps)) = self.take_connection()
+            && let Ok(rt) = tokio::runtime::Handle::try_current()
+        {
+            rt.spawn(send_disconnect(handle, jumps));
         }
+    }
+
+    /// Disconnect from the SSH server, waiting until the disconnect message
+    /// has been sent over this connection and any jump hosts.
+    ///
+    /// The session is marked disconnected before the message is sent, and
+    /// send errors are ignored since the connection is closed either way.
+    pub async fn disconnect_async(&mut self) {
+        if let Some((handle, jumps)) = self.take_connection() {
+            send_disconnect(handle, jumps).await;
+        }
+    }
+
+    /// Reset the session to disconnected, returning the connection to close.
+    fn take_connection(
+        &mut self,
+    ) -> Option<(
+        SharedHandle,
+        Vec<russh::client::Handle<russh_impl::SshClientHandler>>,
+    )> {
+        let jumps = std::mem::take(&mut self.jumps);
+        let handle = self.handle.take();
+        self.remote_forwards = None;
+        self.agent_forwarding = None;
+        self.alive = None;
         self.state = SshSessionState::Disconnected;
+        handle.map(|handle| (handle, jumps))
     }
 
-    /// Get a reference to the russh handle.
+    /// Get the shared russh handle.
     ///
     /// This is useful for advanced operations like opening channels.
+    /// Port forwards hold clones of it, so it stays usable while they run.
     #[must_use]
-    pub const fn handle(&self) -> Option<&russh::client::Handle<russh_impl::SshClientHandler>> {
+    pub const fn handle(&self) -> Option<&SharedHandle> {
         self.handle.as_ref()
     }
 
     /// Get a mutable reference to the russh handle.
-    pub const fn handle_mut(
+    ///
+    /// Returns `None` while port forwards share the handle.
+    pub fn handle_mut(
         &mut self,
     ) -> Option<&mut russh::client::Handle<russh_impl::SshClientHandler>> {
-        self.handle.as_mut()
+        self.handle
+            .as_mut()
+            .and_then(Arc::get_mut)
+            .map(tokio::sync::RwLock::get_mut)
+    }
+
+    fn shared_handle(&self) -> crate::error::Result<&SharedHandle> {
+        self.handle.as_ref().ok_or_else(|| {
+            crate::error::ExpectError::Ssh(SshError::Session {
+                reason: "Not connected".to_string(),
+            })
+        })
     }
 
     /// Open a session channel.
@@ -1139,17 +1430,18 @@ impl SshSession {
     pub async fn open_channel(
         &mut self,
     ) -> crate::error::Result<russh::Channel<russh::client::Msg>> {
-        let handle = self.handle.as_mut().ok_or_else(|| {
-            crate::error::ExpectError::Ssh(SshError::Session {
-                reason: "Not connected".to_string(),
-            })
-        })?;
+        let handle = self.shared_handle()?;
 
-        let channel = handle.channel_open_session().await.map_err(|e| {
-            crate::error::ExpectError::Ssh(SshError::Channel {
-                reason: e.to_string(),
-            })
-        })?;
+        let channel = handle
+            .read()
+            .await
+            .channel_open_session()
+            .await
+            .map_err(|e| {
+                crate::error::ExpectError::Ssh(SshError::Channel {
+                    reason: e.to_string(),
+                })
+            })?;
 
         Ok(channel)
     }
@@ -1197,6 +1489,7 @@ impl SshSession {
     ) -> crate::error::Result<super::channel::SshChannelStream> {
         let channel = self.open_channel().await?;
         let mut stream = super::channel::SshChannelStream::new(channel, config);
+        self.request_agent_forward(&mut stream).await?;
 
         // Request PTY if configured
         if stream.config().pty {
@@ -1246,6 +1539,7 @@ impl SshSession {
     ) -> crate::error::Result<super::channel::SshChannelStream> {
         let channel = self.open_channel().await?;
         let mut stream = super::channel::SshChannelStream::new(channel, config);
+        self.request_agent_forward(&mut stream).await?;
 
         // Request PTY if configured
         if stream.config().pty {
@@ -1257,6 +1551,188 @@ impl SshSession {
 
         Ok(stream)
     }
+
+    /// Ask for agent forwarding if the stream's config wants it.
+    async fn request_agent_forward(
+        &self,
+        stream: &mut super::channel::SshChannelStream,
+    ) -> crate::error::Result<()> {
+        if !stream.config().forward_agent {
+            return Ok(());
+        }
+        // Accept agent channels before asking, since the server may open
+        // one as soon as it sees the request
+        if let Some(enabled) = &self.agent_forwarding {
+            enabled.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+        stream.request_agent_forward().await
+    }
+
+    /// Forward a local port through the server, like `ssh -L`.
+    ///
+    /// Listens on `local_addr` and tunnels each accepted connection to
+    /// `remote_host:remote_port` as seen from the server. Bind to port 0
+    /// and read [`LocalForward::local_addr`](super::forward::LocalForward::local_addr)
+    /// to let the OS pick a port. The forward runs until the returned
+    /// handle is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let tunnel = session.forward_local("127.0.0.1:0", "db.internal", 5432).await?;
+    /// let db = tokio::net::TcpStream::connect(tunnel.local_addr()).await?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not connected or the local
+    /// address cannot be bound.
+    pub async fn forward_local(
+        &self,
+        local_addr: impl tokio::net::ToSocketAddrs,
+        remote_host: impl Into<String>,
+        remote_port: u16,
+    ) -> crate::error::Result<super::forward::LocalForward> {
+        let handle = Arc::clone(self.shared_handle()?);
+        let listener = tokio::net::TcpListener::bind(local_addr).await?;
+        Ok(super::forward::LocalForward::start(
+            listener,
+            handle,
+            remote_host.into(),
+            remote_port,
+        )?)
+    }
+
+    /// Ask the server to forward a port back to this side, like `ssh -R`.
+    ///
+    /// The server listens on `remote_addr:remote_port` and each connection
+    /// it accepts is connected to `local_host:local_port`. Pass port 0 to
+    /// let the server pick one, then read
+    /// [`RemoteForward::remote_port`](super::forward::RemoteForward::remote_port).
+    /// Dropping the returned handle cancels the forward.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not connected or the server
+    /// refuses the forward.
+    pub async fn forward_remote(
+        &self,
+        remote_addr: impl Into<String>,
+        remote_port: u16,
+        local_host: impl Into<String>,
+        local_port: u16,
+    ) -> crate::error::Result<super::forward::RemoteForward> {
+        let handle = Arc::clone(self.shared_handle()?);
+        let forwards = self.remote_forwards.clone().unwrap_or_default();
+        let remote_addr = remote_addr.into();
+
+        let requested = u32::from(remote_port);
+        let bound = handle
+            .write()
+            .await
+            .tcpip_forward(remote_addr.as_str(), requested)
+            .await
+            .map_err(|e| {
+                crate::error::ExpectError::Ssh(SshError::Channel {
+                    reason: format!("remote forward {remote_addr}:{remote_port} refused: {e}"),
+                })
+            })?;
+        // The server only reports a port when it picked one
+        let port = if requested == 0 { bound } else { requested };
+
+        Ok(super::forward::RemoteForward::register(
+            handle,
+            forwards,
+            remote_addr,
+            port,
+            local_host.into(),
+            local_port,
+        ))
+    }
+
+    /// Start an SFTP client on a new channel.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let sftp = session.sftp().await?;
+    /// sftp.write("notes.txt", b"hello").await?;
+    /// assert_eq!(sftp.stat("notes.txt").await?.size, Some(5));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not connected or the server does
+    /// not offer the `sftp` subsystem.
+    pub async fn sftp(&self) -> crate::error::Result<super::sftp::SftpClient> {
+        let channel = self
+            .shared_handle()?
+            .read()
+            .await
+            .channel_open_session()
+            .await
+            .map_err(|e| {
+                crate::error::ExpectError::Ssh(SshError::Channel {
+                    reason: e.to_string(),
+                })
+            })?;
+        super::sftp::SftpClient::start(channel).await
+    }
+
+    /// Copy a remote file to `local` over SFTP.
+    ///
+    /// Returns the number of bytes copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if SFTP cannot be started or the transfer fails.
+    pub async fn download(
+        &self,
+        remote: &str,
+        local: impl AsRef<std::path::Path>,
+    ) -> crate::error::Result<u64> {
+        let sftp = self.sftp().await?;
+        let copied = sftp.download(remote, local).await?;
+        sftp.close().await?;
+        Ok(copied)
+    }
+
+    /// Copy `local` to a remote file over SFTP.
+    ///
+    /// Returns the number of bytes copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if SFTP cannot be started or the transfer fails.
+    pub async fn upload(
+        &self,
+        local: impl AsRef<std::path::Path>,
+        remote: &str,
+    ) -> crate::error::Result<u64> {
+        let sftp = self.sftp().await?;
+        let copied = sftp.upload(local, remote).await?;
+        sftp.close().await?;
+        Ok(copied)
+    }
+}
+
+/// Send a graceful disconnect over `handle`, then close the tunnels from
+/// the inside out.
+#[cfg(feature = "ssh")]
+async fn send_disconnect(
+    handle: SharedHandle,
+    jumps: Vec<russh::client::Handle<russh_impl::SshClientHandler>>,
+) {
+    let _ = handle
+        .read()
+        .await
+        .disconnect(russh::Disconnect::ByApplication, "", "en")
+        .await;
+    for jump in jumps.iter().rev() {
+        let _ = jump
+            .disconnect(russh::Disconnect::ByApplication, "", "en")
+            .await;
+    }
 }
 
 #[cfg(feature = "ssh")]
@@ -1368,4 +1844,52 @@ mod tests {
             HostKeyVerification::KnownHosts
         );
     }
+
+    #[cfg(feature = "ssh")]
+    #[tokio::test]
+    async fn host_key_callback_decides() {
+        use std::sync::Mutex;
+
+        use russh::client::Handler;
+        use russh::keys::PrivateKey;
+        use russh::keys::PublicKey;
+        use russh::keys::ssh_key::private::Ed25519Keypair;
+
+        use super::super::auth::HostKeyDecision;
+
+        let key = PrivateKey::from(Ed25519Keypair::from_seed(&[3; 32]))
+            .public_key()
+            .clone();
+        let seen: Arc<Mutex<Vec<(String, u16, PublicKey)>>> = Arc::default();
+        let handler = |decision| {
+            let seen = Arc::clone(&seen);
+            russh_impl::SshClientHandler {
+                host_key_verification: HostKeyVerification::callback(move |host, port, key| {
+                    seen.lock()
+                        .unwrap()
+                        .push((host.to_string(), port, key.clone()));
+                    decision
+                }),
+                host: "bastion.example.com".to_string(),
+                port: 2222,
+                remote_forwards: Arc::default(),
+                agent_forwarding: Arc::default(),
+                identity_agent: None,
+                _alive: tokio::sync::watch::channel(()).0,
+            }
+        };
+
+        let accepted = handler(HostKeyDecision::Accept)
+            .check_server_key(&key)
+            .await;
+        assert!(accepted.unwrap());
+        let rejected = handler(HostKeyDecision::Reject)
+            .check_server_key(&key)
+            .await;
+        assert!(!rejected.unwrap());
+
+        let seen = std::mem::take(&mut *seen.lock().unwrap());
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0], ("bastion.example.com".to_string(), 2222, key));
+    }
 }
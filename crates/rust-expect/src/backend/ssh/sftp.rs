@@ -0,0 +1,157 @@
+//! SFTP file transfer.
+//!
+//! [`SshSession::sftp`](super::SshSession::sftp) starts the `sftp`
+//! subsystem on a new channel and returns an [`SftpClient`]. For one-off
+//! transfers, [`SshSession::download`](super::SshSession::download) and
+//! [`SshSession::upload`](super::SshSession::upload) copy a whole file
+//! without keeping a client around.
+
+use std::path::Path;
+
+use russh::Channel;
+use russh::client::Msg;
+use russh_sftp::client::SftpSession;
+pub use russh_sftp::client::fs::{File as SftpFile, Metadata as SftpMetadata};
+use tokio::io::AsyncWriteExt;
+
+use crate::error::{ExpectError, Result, SshError};
+
+/// A minimal SFTP client running on one SSH channel.
+///
+/// Remote paths are passed to the server as-is, so relative paths resolve
+/// against the login directory.
+pub struct SftpClient {
+    session: SftpSession,
+}
+
+impl std::fmt::Debug for SftpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SftpClient").finish_non_exhaustive()
+    }
+}
+
+impl SftpClient {
+    /// Start the `sftp` subsystem on `channel`.
+    pub(crate) async fn start(channel: Channel<Msg>) -> Result<Self> {
+        channel
+            .request_subsystem(true, "sftp")
+            .await
+            .map_err(|e| ExpectError::Ssh(SshError::channel(format!("sftp subsystem: {e}"))))?;
+        let session = SftpSession::new(channel.into_stream())
+            .await
+            .map_err(|e| ExpectError::Ssh(SshError::channel(format!("sftp subsystem: {e}"))))?;
+        Ok(Self { session })
+    }
+
+    /// Open a remote file for reading.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened.
+    pub async fn open(&self, path: &str) -> Result<SftpFile> {
+        self.session
+            .open(path)
+            .await
+            .map_err(|e| sftp_error(path, &e))
+    }
+
+    /// Create or truncate a remote file for writing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub async fn create(&self, path: &str) -> Result<SftpFile> {
+        self.session
+            .create(path)
+            .await
+            .map_err(|e| sftp_error(path, &e))
+    }
+
+    /// Read a whole remote file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or read.
+    pub async fn read(&self, path: &str) -> Result<Vec<u8>> {
+        self.session
+            .read(path)
+            .await
+            .map_err(|e| sftp_error(path, &e))
+    }
+
+    /// Write `data` to a remote file, creating it or replacing its contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written.
+    pub async fn write(&self, path: &str, data: &[u8]) -> Result<()> {
+        let mut file = self.create(path).await?;
+        file.write_all(data)
+            .await
+            .map_err(|e| sftp_error(path, &e))?;
+        file.shutdown().await.map_err(|e| sftp_error(path, &e))
+    }
+
+    /// Query a remote file's metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path does not exist.
+    pub async fn stat(&self, path: &str) -> Result<SftpMetadata> {
+        self.session
+            .metadata(path)
+            .await
+            .map_err(|e| sftp_error(path, &e))
+    }
+
+    /// Copy a remote file to `local`, returning the number of bytes copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either file cannot be opened or the copy fails.
+    pub async fn download(&self, remote: &str, local: impl AsRef<Path>) -> Result<u64> {
+        let mut source = self.open(remote).await?;
+        let mut target = tokio::fs::File::create(local).await?;
+        let copied = tokio::io::copy(&mut source, &mut target)
+            .await
+            .map_err(|e| sftp_error(remote, &e))?;
+        target.flush().await?;
+        Ok(copied)
+    }
+
+    /// Copy `local` to a remote file, returning the number of bytes copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either file cannot be opened or the copy fails.
+    pub async fn upload(&self, local: impl AsRef<Path>, remote: &str) -> Result<u64> {
+        let mut source = tokio::fs::File::open(local).await?;
+        let mut target = self.create(remote).await?;
+        let copied = tokio::io::copy(&mut source, &mut target)
+            .await
+            .map_err(|e| sftp_error(remote, &e))?;
+        // Shutting down closes the remote handle, so the server has the
+        // whole file once this returns
+        target
+            .shutdown()
+            .await
+            .map_err(|e| sftp_error(remote, &e))?;
+        Ok(copied)
+    }
+
+    /// End the SFTP session and close its channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server does not acknowledge the close.
+    pub async fn close(self) -> Result<()> {
+        self.session
+            .close()
+            .await
+            .map_err(|e| ExpectError::Ssh(SshError::channel(format!("sftp close: {e}"))))
+    }
+}
+
+fn sftp_error(path: &str, e: &impl std::fmt::Display) -> ExpectError {
+    ExpectError::Ssh(SshError::sftp(path, e.to_string()))
+}
@@ -5,6 +5,7 @@
 
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Default timeout duration (30 seconds).
@@ -206,8 +207,17 @@ impl TimeoutConfig {
     }
 }
 
+/// Callback invoked when output is discarded from a full buffer.
+///
+/// Receives the number of bytes discarded.
+pub(crate) type OverflowCallback = Arc<dyn Fn(usize) + Send + Sync>;
+
 /// Configuration for the output buffer.
-#[derive(Debug, Clone)]
+///
+/// Build it with [`new`](Self::new) or [`Default`] and the setters; the
+/// overflow callback can only be set through
+/// [`on_overflow`](Self::on_overflow).
+#[derive(Clone)]
 pub struct BufferConfig {
     /// Maximum buffer size in bytes.
     pub max_size: usize,
@@ -216,7 +226,25 @@ pub struct BufferConfig {
     pub search_window: Option<usize>,
 
     /// Whether to use a ring buffer (discard oldest data when full).
+    ///
+    /// When disabled, an expect fails with
+    /// [`ExpectError::BufferOverflow`](crate::ExpectError::BufferOverflow)
+    /// once unmatched output exceeds `max_size`.
     pub ring_buffer: bool,
+
+    /// Called whenever output is discarded because the buffer is full.
+    pub(crate) on_overflow: Option<OverflowCallback>,
+}
+
+impl std::fmt::Debug for BufferConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufferConfig")
+            .field("max_size", &self.max_size)
+            .field("search_window", &self.search_window)
+            .field("ring_buffer", &self.ring_buffer)
+            .field("on_overflow", &self.on_overflow.is_some())
+            .finish()
+    }
 }
 
 impl Default for BufferConfig {
@@ -225,6 +253,7 @@ impl Default for BufferConfig {
             max_size: DEFAULT_BUFFER_SIZE,
             search_window: None,
             ring_buffer: true,
+            on_overflow: None,
         }
     }
 }
@@ -252,6 +281,13 @@ impl BufferConfig {
         self.ring_buffer = enabled;
         self
     }
+
+    /// Set a callback for output discarded from a full buffer.
+    #[must_use]
+    pub fn on_overflow(mut self, callback: impl Fn(usize) + Send + Sync + 'static) -> Self {
+        self.on_overflow = Some(Arc::new(callback));
+        self
+    }
 }
 
 /// Configuration for logging.
@@ -375,6 +411,10 @@ pub struct EncodingConfig {
     pub encoding: Encoding,
 
     /// How to handle invalid sequences.
+    ///
+    /// Sessions do not apply this yet: output in a legacy charset is
+    /// decoded by a [`StreamingDecoder`](crate::encoding::StreamingDecoder),
+    /// which always replaces malformed input with U+FFFD.
     pub error_handling: EncodingErrorHandling,
 
     /// Whether to normalize line endings.
@@ -414,6 +454,25 @@ impl EncodingConfig {
         self.normalize_line_endings = normalize;
         self
     }
+
+    /// Create a streaming decoder for the configured charset.
+    ///
+    /// Returns `None` for UTF-8 and raw output, which are matched as-is.
+    /// The decoder always replaces malformed input, whatever
+    /// [`error_handling`](Self::error_handling) is set to.
+    #[cfg(feature = "legacy-encoding")]
+    #[must_use]
+    pub fn decoder(&self) -> Option<crate::encoding::StreamingDecoder> {
+        use crate::encoding::{DetectedEncoding, StreamingDecoder};
+
+        let detected = match self.encoding {
+            Encoding::Utf8 | Encoding::Raw => return None,
+            Encoding::Latin1 => DetectedEncoding::Latin1,
+            Encoding::Windows1252 => DetectedEncoding::Windows1252,
+            Encoding::ShiftJis => DetectedEncoding::ShiftJis,
+        };
+        StreamingDecoder::new(&detected)
+    }
 }
 
 /// Supported text encodings.
@@ -433,6 +492,10 @@ pub enum Encoding {
     /// Windows-1252.
     #[cfg(feature = "legacy-encoding")]
     Windows1252,
+
+    /// Shift-JIS.
+    #[cfg(feature = "legacy-encoding")]
+    ShiftJis,
 }
 
 /// How to handle encoding errors.
@@ -4,7 +4,10 @@
 //! interactive terminal sessions using dialog definitions.
 //!
 //! Dialogs define a sequence of expect/send steps that can be executed
-//! against a session. They support variable substitution and branching.
+//! against a session. They support variable substitution, capturing
+//! output into variables for later steps, and branching, either by
+//! jumping to named steps or by running a sub-script for whichever of
+//! several prompts appears.
 //!
 //! # Examples
 //!
@@ -45,6 +48,39 @@
 //! assert_eq!(dialog.substitute("${USER}"), "admin");
 //! ```
 //!
+//! ## Branching on the Prompt
+//!
+//! ```
+//! use rust_expect::{Dialog, DialogBranch, DialogStep};
+//!
+//! // First-time connections ask to accept the host key before the password
+//! let dialog = Dialog::named("ssh-login")
+//!     .step(DialogStep::if_match([
+//!         DialogBranch::new("(yes/no)?", [
+//!             DialogStep::send_line("yes"),
+//!             DialogStep::expect("password:"),
+//!         ]),
+//!         DialogBranch::new("password:", []),
+//!     ]))
+//!     .step(DialogStep::send_line("secret"));
+//!
+//! assert_eq!(dialog.steps[0].if_match.len(), 2);
+//! ```
+//!
+//! ## Capturing Values
+//!
+//! ```
+//! use rust_expect::DialogBuilder;
+//!
+//! // The token printed by the first command is sent back by the second
+//! let dialog = DialogBuilder::named("token")
+//!     .capture("token", r"token: (\w+)")
+//!     .step(rust_expect::DialogStep::send_line("use ${token}"))
+//!     .build();
+//!
+//! assert_eq!(dialog.len(), 2);
+//! ```
+//!
 //! ## Using the Builder
 //!
 //! ```
@@ -84,5 +120,7 @@ pub mod definition;
 pub mod executor;
 
 pub use common::*;
-pub use definition::{Dialog, DialogBuilder, DialogStep};
+pub use definition::{
+    Dialog, DialogBranch, DialogBuilder, DialogCapture, DialogRepeat, DialogStep,
+};
 pub use executor::{DialogExecutor, DialogResult, StepResult};
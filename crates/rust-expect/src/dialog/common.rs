@@ -8,8 +8,8 @@ use super::definition::{Dialog, DialogBuilder, DialogStep};
 #[must_use]
 pub fn login_dialog(username: &str, password: &str) -> Dialog {
     DialogBuilder::named("login")
-        .expect_send("username", "login:", format!("{username}\n"))
-        .expect_send("password", "assword:", format!("{password}\n"))
+        .expect_send_line("username", "login:", username)
+        .expect_send_line("password", "assword:", password)
         .build()
 }
 
@@ -23,13 +23,13 @@ pub fn ssh_dialog(host: &str, username: &str, password: &str) -> Dialog {
         .step(
             DialogStep::new("hostkey")
                 .with_expect("(yes/no")
-                .with_send("yes\n")
+                .with_send_line("yes")
                 .then("password"),
         )
         .step(
             DialogStep::new("password")
                 .with_expect("assword:")
-                .with_send("${PASS}\n"),
+                .with_send_line("${PASS}"),
         )
 }
 
@@ -37,16 +37,16 @@ pub fn ssh_dialog(host: &str, username: &str, password: &str) -> Dialog {
 #[must_use]
 pub fn sudo_dialog(password: &str) -> Dialog {
     DialogBuilder::named("sudo")
-        .expect_send("password", "[sudo] password", format!("{password}\n"))
+        .expect_send_line("password", "[sudo] password", password)
         .build()
 }
 
 /// Create a yes/no confirmation dialog.
 #[must_use]
 pub fn confirm_dialog(answer: bool) -> Dialog {
-    let response = if answer { "yes\n" } else { "no\n" };
+    let response = if answer { "yes" } else { "no" };
     DialogBuilder::named("confirm")
-        .expect_send("confirm", "[y/n]", response)
+        .expect_send_line("confirm", "[y/n]", response)
         .build()
 }
 
@@ -54,7 +54,7 @@ pub fn confirm_dialog(answer: bool) -> Dialog {
 #[must_use]
 pub fn menu_dialog(selection: &str) -> Dialog {
     DialogBuilder::named("menu")
-        .expect_send("select", "choice:", format!("{selection}\n"))
+        .expect_send_line("select", "choice:", selection)
         .build()
 }
 
@@ -65,12 +65,12 @@ pub fn ftp_dialog(username: &str, password: &str) -> Dialog {
         .step(
             DialogStep::new("user")
                 .with_expect("Name")
-                .with_send(format!("{username}\n")),
+                .with_send_line(username),
         )
         .step(
             DialogStep::new("pass")
                 .with_expect("Password")
-                .with_send(format!("{password}\n")),
+                .with_send_line(password),
         )
 }
 
@@ -81,12 +81,12 @@ pub fn telnet_dialog(username: &str, password: &str) -> Dialog {
         .step(
             DialogStep::new("login")
                 .with_expect("login:")
-                .with_send(format!("{username}\n")),
+                .with_send_line(username),
         )
         .step(
             DialogStep::new("password")
                 .with_expect("Password:")
-                .with_send(format!("{password}\n")),
+                .with_send_line(password),
         )
 }
 
@@ -97,12 +97,12 @@ pub fn git_credential_dialog(username: &str, password: &str) -> Dialog {
         .step(
             DialogStep::new("user")
                 .with_expect("Username")
-                .with_send(format!("{username}\n")),
+                .with_send_line(username),
         )
         .step(
             DialogStep::new("pass")
                 .with_expect("Password")
-                .with_send(format!("{password}\n")),
+                .with_send_line(password),
         )
 }
 
@@ -12,18 +12,47 @@ pub struct DialogStep {
     pub name: String,
     /// Pattern to expect.
     pub expect: Option<String>,
+    /// Whether the expect pattern is a regular expression.
+    pub regex: bool,
     /// Response to send.
     pub send: Option<String>,
+    /// Whether to append the session's line ending to the response.
+    pub send_line: bool,
     /// Control character to send (alternative to text).
     pub send_control: Option<ControlChar>,
+    /// Raw bytes to send (alternative to text), e.g. an escape sequence.
+    pub send_bytes: Option<Vec<u8>>,
     /// Timeout for this step.
     pub timeout: Option<Duration>,
     /// Whether to continue on timeout.
     pub continue_on_timeout: bool,
+    /// Step to run instead of failing if the expect times out.
+    ///
+    /// It replaces this step's own send, and the dialog carries on after it.
+    pub on_timeout: Option<Box<Self>>,
     /// Next step name (for branching).
     pub next: Option<String>,
     /// Conditional branches.
     pub branches: HashMap<String, String>,
+    /// Sub-scripts to choose between by which pattern appears first.
+    ///
+    /// When non-empty, the step waits for any branch pattern instead of
+    /// [`expect`](Self::expect), then runs the matching branch's steps
+    /// before the dialog moves on.
+    pub if_match: Vec<DialogBranch>,
+    /// Sub-script to retry until a pattern appears.
+    ///
+    /// It runs after the step's own expect and send.
+    pub repeat: Option<DialogRepeat>,
+    /// Variable to store a regex capture group of the match in.
+    pub capture: Option<DialogCapture>,
+    /// Patterns that abort the dialog if they appear while expecting.
+    ///
+    /// Forbidden patterns registered by a step stay active for that step's
+    /// expect and every expect after it.
+    pub forbid: Vec<String>,
+    /// Delay before executing this step.
+    pub delay: Option<Duration>,
 }
 
 impl DialogStep {
@@ -45,6 +74,16 @@ impl DialogStep {
         }
     }
 
+    /// Create a step that expects a regex pattern (simple unnamed step).
+    #[must_use]
+    pub fn expect_regex(pattern: impl Into<String>) -> Self {
+        Self {
+            expect: Some(pattern.into()),
+            regex: true,
+            ..Default::default()
+        }
+    }
+
     /// Create a step that sends text (simple unnamed step).
     #[must_use]
     pub fn send(text: impl Into<String>) -> Self {
@@ -54,6 +93,107 @@ impl DialogStep {
         }
     }
 
+    /// Create a step that sends a line (simple unnamed step).
+    ///
+    /// The session's configured [`LineEnding`](crate::config::LineEnding)
+    /// is appended when the step runs.
+    #[must_use]
+    pub fn send_line(text: impl Into<String>) -> Self {
+        Self {
+            send: Some(text.into()),
+            send_line: true,
+            ..Default::default()
+        }
+    }
+
+    /// Create a step that sends a control character (simple unnamed step).
+    #[must_use]
+    pub fn control(ctrl: ControlChar) -> Self {
+        Self {
+            send_control: Some(ctrl),
+            ..Default::default()
+        }
+    }
+
+    /// Create a step that sends raw bytes (simple unnamed step).
+    ///
+    /// The bytes are written as-is, without variable substitution or a
+    /// line ending.
+    #[must_use]
+    pub fn send_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            send_bytes: Some(bytes.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Create a step that expects a pattern within `timeout` and runs
+    /// `on_timeout` if it does not appear (simple unnamed step).
+    #[must_use]
+    pub fn expect_or(pattern: impl Into<String>, timeout: Duration, on_timeout: Self) -> Self {
+        Self::expect(pattern)
+            .timeout(timeout)
+            .on_timeout(on_timeout)
+    }
+
+    /// Create a step that forbids a pattern (simple unnamed step).
+    ///
+    /// If the pattern appears while any later step is expecting, the
+    /// dialog fails instead of waiting for the expected text.
+    #[must_use]
+    pub fn forbid(pattern: impl Into<String>) -> Self {
+        Self {
+            forbid: vec![pattern.into()],
+            ..Default::default()
+        }
+    }
+
+    /// Create a step that runs the branch whose pattern appears first
+    /// (simple unnamed step).
+    ///
+    /// When several patterns are already in the buffer, the one starting
+    /// earliest in the output wins; branch order only breaks ties between
+    /// matches at the same position.
+    #[must_use]
+    pub fn if_match(branches: impl IntoIterator<Item = DialogBranch>) -> Self {
+        Self {
+            if_match: branches.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Create a step that runs `steps` until `until` appears, at most
+    /// `max_attempts` times (simple unnamed step).
+    ///
+    /// After each attempt the step waits up to its timeout for `until`;
+    /// if it does not appear, the steps run again.
+    #[must_use]
+    pub fn repeat(
+        until: impl Into<String>,
+        max_attempts: u32,
+        steps: impl IntoIterator<Item = Self>,
+    ) -> Self {
+        Self::default().with_repeat(DialogRepeat::new(until, max_attempts, steps))
+    }
+
+    /// Create a step that expects a regex and stores its first capture
+    /// group in `var` (simple unnamed step).
+    ///
+    /// Later steps can use the captured text as `${var}`.
+    #[must_use]
+    pub fn capture(var: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self::expect_regex(pattern).with_capture(var, 1)
+    }
+
+    /// Create a step that only waits for a duration (simple unnamed step).
+    #[must_use]
+    pub fn wait(duration: Duration) -> Self {
+        Self {
+            delay: Some(duration),
+            ..Default::default()
+        }
+    }
+
     /// Chain: set the pattern to expect (builder pattern).
     #[must_use]
     pub fn with_expect(mut self, pattern: impl Into<String>) -> Self {
@@ -61,10 +201,27 @@ impl DialogStep {
         self
     }
 
+    /// Chain: set a regex pattern to expect (builder pattern).
+    #[must_use]
+    pub fn with_expect_regex(mut self, pattern: impl Into<String>) -> Self {
+        self.expect = Some(pattern.into());
+        self.regex = true;
+        self
+    }
+
     /// Chain: set the text to send (builder pattern).
     #[must_use]
     pub fn with_send(mut self, text: impl Into<String>) -> Self {
         self.send = Some(text.into());
+        self.send_line = false;
+        self
+    }
+
+    /// Chain: set a line to send, followed by the session's line ending.
+    #[must_use]
+    pub fn with_send_line(mut self, text: impl Into<String>) -> Self {
+        self.send = Some(text.into());
+        self.send_line = true;
         self
     }
 
@@ -75,12 +232,25 @@ impl DialogStep {
         self
     }
 
+    /// Chain: set raw bytes to send (e.g., an escape sequence).
+    #[must_use]
+    pub fn with_send_bytes(mut self, bytes: impl Into<Vec<u8>>) -> Self {
+        self.send_bytes = Some(bytes.into());
+        self
+    }
+
     /// Chain: set the text to send after expecting.
     /// Alias for `with_send`, for fluent API.
     #[must_use]
-    pub fn then_send(mut self, text: impl Into<String>) -> Self {
-        self.send = Some(text.into());
-        self
+    pub fn then_send(self, text: impl Into<String>) -> Self {
+        self.with_send(text)
+    }
+
+    /// Chain: set a line to send after expecting.
+    /// Alias for `with_send_line`, for fluent API.
+    #[must_use]
+    pub fn then_send_line(self, text: impl Into<String>) -> Self {
+        self.with_send_line(text)
     }
 
     /// Chain: set a control character to send after expecting.
@@ -98,6 +268,33 @@ impl DialogStep {
         self
     }
 
+    /// Chain: forbid a pattern for this and all following expects.
+    #[must_use]
+    pub fn with_forbid(mut self, pattern: impl Into<String>) -> Self {
+        self.forbid.push(pattern.into());
+        self
+    }
+
+    /// Chain: wait for a duration before executing this step.
+    #[must_use]
+    pub const fn with_delay(mut self, duration: Duration) -> Self {
+        self.delay = Some(duration);
+        self
+    }
+
+    /// Chain: store capture group `group` of the match in `var`.
+    ///
+    /// Group 0 is the whole match. The step fails if the group did not
+    /// take part in the match.
+    #[must_use]
+    pub fn with_capture(mut self, var: impl Into<String>, group: usize) -> Self {
+        self.capture = Some(DialogCapture {
+            var: var.into(),
+            group,
+        });
+        self
+    }
+
     /// Set the next step name.
     #[must_use]
     pub fn then(mut self, next: impl Into<String>) -> Self {
@@ -112,6 +309,20 @@ impl DialogStep {
         self
     }
 
+    /// Chain: add a sub-script to run if `branch`'s pattern appears first.
+    #[must_use]
+    pub fn with_branch(mut self, branch: DialogBranch) -> Self {
+        self.if_match.push(branch);
+        self
+    }
+
+    /// Chain: retry a sub-script after this step's expect and send.
+    #[must_use]
+    pub fn with_repeat(mut self, repeat: DialogRepeat) -> Self {
+        self.repeat = Some(repeat);
+        self
+    }
+
     /// Set whether to continue on timeout.
     #[must_use]
     pub const fn continue_on_timeout(mut self, cont: bool) -> Self {
@@ -119,18 +330,43 @@ impl DialogStep {
         self
     }
 
+    /// Chain: run `fallback` instead of failing if the expect times out.
+    #[must_use]
+    pub fn on_timeout(mut self, fallback: Self) -> Self {
+        self.on_timeout = Some(Box::new(fallback));
+        self
+    }
+
     /// Get the expect pattern.
     #[must_use]
     pub fn expect_pattern(&self) -> Option<&str> {
         self.expect.as_deref()
     }
 
+    /// Check if the expect pattern is a regex.
+    #[must_use]
+    pub const fn is_regex(&self) -> bool {
+        self.regex
+    }
+
+    /// Get the patterns forbidden by this step.
+    #[must_use]
+    pub fn forbidden_patterns(&self) -> &[String] {
+        &self.forbid
+    }
+
     /// Get the send text.
     #[must_use]
     pub fn send_text(&self) -> Option<&str> {
         self.send.as_deref()
     }
 
+    /// Check if the session's line ending is appended to the send text.
+    #[must_use]
+    pub const fn appends_line_ending(&self) -> bool {
+        self.send_line
+    }
+
     /// Get the control character to send.
     #[must_use]
     pub const fn send_control(&self) -> Option<ControlChar> {
@@ -150,6 +386,102 @@ impl DialogStep {
     }
 }
 
+/// A sub-script retried by a [`DialogStep::repeat`] step.
+///
+/// Like a [`DialogBranch`], the steps run in order and `next` is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct DialogRepeat {
+    /// Steps to run on each attempt.
+    pub steps: Vec<DialogStep>,
+    /// Pattern that ends the repeat once it appears.
+    pub until: String,
+    /// Whether `until` is a regular expression.
+    pub regex: bool,
+    /// Most attempts before the step fails; the steps always run once.
+    pub max_attempts: u32,
+}
+
+impl DialogRepeat {
+    /// Create a repeat that ends when a literal pattern appears.
+    #[must_use]
+    pub fn new(
+        until: impl Into<String>,
+        max_attempts: u32,
+        steps: impl IntoIterator<Item = DialogStep>,
+    ) -> Self {
+        Self {
+            steps: steps.into_iter().collect(),
+            until: until.into(),
+            regex: false,
+            max_attempts,
+        }
+    }
+
+    /// Create a repeat that ends when a regex pattern appears.
+    #[must_use]
+    pub fn regex(
+        until: impl Into<String>,
+        max_attempts: u32,
+        steps: impl IntoIterator<Item = DialogStep>,
+    ) -> Self {
+        Self {
+            regex: true,
+            ..Self::new(until, max_attempts, steps)
+        }
+    }
+}
+
+/// Where a [`DialogStep::capture`] step stores what it matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogCapture {
+    /// Name of the variable to set.
+    pub var: String,
+    /// Regex capture group to store; 0 is the whole match.
+    pub group: usize,
+}
+
+/// One arm of a [`DialogStep::if_match`] step.
+///
+/// The steps run in order, like a small dialog of their own; `next` and
+/// name-based branches inside them are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct DialogBranch {
+    /// Pattern that selects this branch.
+    pub pattern: String,
+    /// Whether the pattern is a regular expression.
+    pub regex: bool,
+    /// Steps to run when the pattern matches.
+    pub steps: Vec<DialogStep>,
+}
+
+impl DialogBranch {
+    /// Create a branch selected by a literal pattern.
+    #[must_use]
+    pub fn new(pattern: impl Into<String>, steps: impl IntoIterator<Item = DialogStep>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            regex: false,
+            steps: steps.into_iter().collect(),
+        }
+    }
+
+    /// Create a branch selected by a regex pattern.
+    #[must_use]
+    pub fn regex(pattern: impl Into<String>, steps: impl IntoIterator<Item = DialogStep>) -> Self {
+        Self {
+            regex: true,
+            ..Self::new(pattern, steps)
+        }
+    }
+
+    /// Chain: add a step to the branch.
+    #[must_use]
+    pub fn step(mut self, step: DialogStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+}
+
 /// A complete dialog definition.
 #[derive(Debug, Clone, Default)]
 pub struct Dialog {
@@ -245,13 +577,45 @@ impl Dialog {
     /// Substitute variables in a string.
     #[must_use]
     pub fn substitute(&self, s: &str) -> String {
-        let mut result = s.to_string();
-        for (name, value) in &self.variables {
-            result = result.replace(&format!("${{{name}}}"), value);
-            result = result.replace(&format!("${name}"), value);
+        substitute(s, &self.variables)
+    }
+}
+
+/// Substitute `${name}` and `$name` references to `variables` in a string.
+///
+/// The string is scanned once from left to right, so text inserted for one
+/// reference is never itself expanded. A bare `$name` takes the longest
+/// variable name that follows the `$`. Unknown references are left as is.
+pub(crate) fn substitute(s: &str, variables: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(dollar) = rest.find('$') {
+        result.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+
+        let braced = after.strip_prefix('{').and_then(|inner| {
+            let end = inner.find('}')?;
+            let value = variables.get(&inner[..end])?;
+            Some((value, end + 2))
+        });
+        let reference = braced.or_else(|| {
+            variables
+                .iter()
+                .filter(|(name, _)| !name.is_empty() && after.starts_with(name.as_str()))
+                .max_by_key(|(name, _)| name.len())
+                .map(|(name, value)| (value, name.len()))
+        });
+
+        if let Some((value, len)) = reference {
+            result.push_str(value);
+            rest = &after[len..];
+        } else {
+            result.push('$');
+            rest = after;
         }
-        result
     }
+    result.push_str(rest);
+    result
 }
 
 /// A builder for creating dialogs.
@@ -310,6 +674,46 @@ impl DialogBuilder {
         self
     }
 
+    /// Add a step that expects a pattern and sends a line.
+    ///
+    /// The session's line ending is appended to `send` when the step runs.
+    #[must_use]
+    pub fn expect_send_line(
+        mut self,
+        name: impl Into<String>,
+        expect: impl Into<String>,
+        send: impl Into<String>,
+    ) -> Self {
+        self.dialog = self.dialog.step(
+            DialogStep::new(name)
+                .with_expect(expect)
+                .with_send_line(send),
+        );
+        self
+    }
+
+    /// Add a step that stores the first capture group of `pattern` in `var`.
+    #[must_use]
+    pub fn capture(mut self, var: impl Into<String>, pattern: impl Into<String>) -> Self {
+        self.dialog = self.dialog.step(DialogStep::capture(var, pattern));
+        self
+    }
+
+    /// Add a step that runs `steps` until `until` appears, at most
+    /// `max_attempts` times.
+    #[must_use]
+    pub fn repeat(
+        mut self,
+        until: impl Into<String>,
+        max_attempts: u32,
+        steps: impl IntoIterator<Item = DialogStep>,
+    ) -> Self {
+        self.dialog = self
+            .dialog
+            .step(DialogStep::repeat(until, max_attempts, steps));
+        self
+    }
+
     /// Build the dialog.
     #[must_use]
     pub fn build(self) -> Dialog {
@@ -371,6 +775,32 @@ mod tests {
         assert_eq!(step.get_timeout(), Some(Duration::from_secs(10)));
     }
 
+    #[test]
+    fn dialog_step_forbid() {
+        let step = DialogStep::expect("$ ")
+            .with_forbid("Permission denied")
+            .with_forbid("Login incorrect");
+        assert_eq!(
+            step.forbidden_patterns(),
+            ["Permission denied", "Login incorrect"]
+        );
+
+        let step = DialogStep::forbid("denied");
+        assert!(step.expect_pattern().is_none());
+        assert_eq!(step.forbidden_patterns(), ["denied"]);
+    }
+
+    #[test]
+    fn dialog_step_control_and_bytes() {
+        let step = DialogStep::control(ControlChar::CtrlC);
+        assert_eq!(step.send_control(), Some(ControlChar::CtrlC));
+        assert!(step.expect_pattern().is_none());
+
+        let step = DialogStep::send_bytes([0x1b, 0x5b]);
+        assert_eq!(step.send_bytes.as_deref(), Some(&[0x1b, 0x5b][..]));
+        assert!(step.send_text().is_none());
+    }
+
     #[test]
     fn dialog_variable_substitution() {
         let dialog = DialogBuilder::new()
@@ -381,6 +811,32 @@ mod tests {
         assert_eq!(dialog.substitute("${greeting}, ${name}!"), "Hello, Alice!");
     }
 
+    #[test]
+    fn substitute_does_not_expand_inserted_values() {
+        let dialog = DialogBuilder::new()
+            .var("captured", "${password}")
+            .var("password", "hunter2")
+            .build();
+
+        assert_eq!(
+            dialog.substitute("got ${captured} and $captured"),
+            "got ${password} and ${password}"
+        );
+    }
+
+    #[test]
+    fn substitute_bare_name_takes_longest_variable() {
+        let dialog = DialogBuilder::new()
+            .var("USER", "admin")
+            .var("USERNAME", "alice")
+            .build();
+
+        assert_eq!(
+            dialog.substitute("$USERNAME/$USER/$HOME"),
+            "alice/admin/$HOME"
+        );
+    }
+
     #[test]
     fn dialog_builder_named() {
         let dialog = DialogBuilder::named("test")
@@ -1,14 +1,15 @@
 //! Dialog execution engine.
 
+use std::collections::HashMap;
 use std::time::Duration;
 
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-use super::definition::{Dialog, DialogStep};
-use crate::Pattern;
+use super::definition::{Dialog, DialogRepeat, DialogStep, substitute};
 use crate::error::{ExpectError, Result};
 use crate::expect::PatternSet;
 use crate::session::Session;
+use crate::{Match, Pattern};
 
 /// Result of executing a dialog step.
 #[derive(Debug, Clone)]
@@ -42,6 +43,8 @@ pub struct DialogResult {
     pub output: String,
     /// Error message if failed.
     pub error: Option<String>,
+    /// Variables when the dialog stopped, including captured values.
+    pub variables: HashMap<String, String>,
 }
 
 impl DialogResult {
@@ -62,6 +65,12 @@ impl DialogResult {
     pub fn get_step(&self, name: &str) -> Option<&StepResult> {
         self.steps.iter().find(|s| s.step_name == name)
     }
+
+    /// Get a variable, such as one set by a capture step.
+    #[must_use]
+    pub fn variable(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(String::as_str)
+    }
 }
 
 /// Dialog execution state.
@@ -140,11 +149,16 @@ impl DialogExecutor {
     }
 
     /// Get the pattern for a step.
+    ///
+    /// Returns `None` if the step has no expect pattern or its regex is invalid.
     #[must_use]
     pub fn step_pattern(&self, step: &DialogStep, dialog: &Dialog) -> Option<Pattern> {
-        step.expect
-            .as_ref()
-            .map(|e| Pattern::literal(dialog.substitute(e)))
+        let text = dialog.substitute(step.expect.as_ref()?);
+        if step.regex {
+            Pattern::regex(&text).ok()
+        } else {
+            Some(Pattern::literal(text))
+        }
     }
 
     /// Execute a dialog on a session.
@@ -193,23 +207,19 @@ impl DialogExecutor {
                 steps: Vec::new(),
                 output: String::new(),
                 error: None,
+                variables: dialog.variables.clone(),
             });
         }
 
+        let mut variables = dialog.variables.clone();
         let mut step_results = Vec::new();
         let mut total_output = String::new();
         let mut step_count = 0;
+        let mut forbidden: Vec<String> = Vec::new();
+        // Sub-scripts being run, innermost last
+        let mut frames: Vec<Frame<'_>> = Vec::new();
 
-        // Determine starting step
-        let mut current_step_idx = if let Some(ref entry) = dialog.entry {
-            dialog
-                .steps
-                .iter()
-                .position(|s| &s.name == entry)
-                .unwrap_or(0)
-        } else {
-            0
-        };
+        let mut current_step_idx = Some(entry_index(dialog));
 
         loop {
             // Prevent infinite loops
@@ -221,17 +231,50 @@ impl DialogExecutor {
                     steps: step_results,
                     output: total_output,
                     error: Some(format!("Exceeded maximum steps ({})", self.max_steps)),
+                    variables,
                 });
             }
 
-            // Get current step
-            let Some(step) = dialog.steps.get(current_step_idx) else {
-                break; // No more steps
-            };
+            // A sub-script runs to the end before the dialog moves on
+            let (step_result, sub_scripts, nested) = match next_nested(&mut frames) {
+                Some(Nested::Until(repeating)) => {
+                    let until = self
+                        .check_until(session, repeating, dialog, &mut variables, &forbidden)
+                        .await?;
+                    let Some(result) = until else {
+                        if let Some(frame) = frames.last_mut() {
+                            frame.retry();
+                        }
+                        continue;
+                    };
+                    frames.pop();
+                    (result, Vec::new(), true)
+                }
+                next => {
+                    let (step, nested) = if let Some(Nested::Step(step)) = next {
+                        (step, true)
+                    } else if let Some(step) = current_step_idx.and_then(|i| dialog.steps.get(i)) {
+                        (step, false)
+                    } else {
+                        break; // No more steps
+                    };
+
+                    // Forbidden patterns stay active for the rest of the dialog
+                    for pattern in step.forbid.iter().map(|f| substitute(f, &variables)) {
+                        if !forbidden.contains(&pattern) {
+                            forbidden.push(pattern);
+                        }
+                    }
 
-            // Execute the step
-            let step_result = self.execute_step(session, step, dialog).await?;
+                    // Execute the step
+                    let (step_result, sub_scripts) = self
+                        .run_step(session, step, dialog, &mut variables, &forbidden)
+                        .await?;
+                    (step_result, sub_scripts, nested)
+                }
+            };
             let success = step_result.success;
+            let step_name = step_result.step_name.clone();
             total_output.push_str(&step_result.output);
 
             // Determine next step
@@ -244,25 +287,27 @@ impl DialogExecutor {
                     success: false,
                     steps: step_results,
                     output: total_output,
-                    error: Some(format!("Step '{}' failed", step.name)),
+                    error: Some(format!("Step '{step_name}' failed")),
+                    variables,
                 });
             }
 
-            // Move to next step
-            if let Some(next_name) = next_step {
-                if let Some(idx) = dialog.steps.iter().position(|s| s.name == next_name) {
-                    current_step_idx = idx;
-                } else {
-                    // Next step not found, end dialog
-                    break;
-                }
+            frames.extend(sub_scripts);
+            // Steps inside a sub-script run in order
+            if nested {
+                continue;
+            }
+
+            // Move to next step (unnamed steps always advance sequentially)
+            current_step_idx = if let Some(next_name) = next_step.filter(|name| !name.is_empty()) {
+                // A missing next step ends the dialog
+                dialog.steps.iter().position(|s| s.name == next_name)
             } else {
                 // No explicit next, try sequential
-                current_step_idx += 1;
-                if current_step_idx >= dialog.steps.len() {
-                    break;
-                }
-            }
+                current_step_idx
+                    .map(|i| i + 1)
+                    .filter(|&i| i < dialog.steps.len())
+            };
         }
 
         Ok(DialogResult {
@@ -271,11 +316,18 @@ impl DialogExecutor {
             steps: step_results,
             output: total_output,
             error: None,
+            variables,
         })
     }
 
     /// Execute a single dialog step on a session.
     ///
+    /// For an [`if_match`](DialogStep::if_match) step this only waits for
+    /// one of the branch patterns; the branch itself runs as part of
+    /// [`execute`](Self::execute). Likewise, only `execute` runs
+    /// [`on_timeout`](DialogStep::on_timeout) fallbacks and keeps captured
+    /// values for later steps.
+    ///
     /// # Errors
     ///
     /// Returns an error if an I/O error occurs (timeouts are handled per-step).
@@ -285,39 +337,81 @@ impl DialogExecutor {
         step: &DialogStep,
         dialog: &Dialog,
     ) -> Result<StepResult>
+    where
+        T: AsyncReadExt + AsyncWriteExt + Unpin + Send,
+    {
+        let mut variables = dialog.variables.clone();
+        let forbidden: Vec<String> = step
+            .forbid
+            .iter()
+            .map(|f| substitute(f, &variables))
+            .collect();
+        let (result, _) = self
+            .run_step(session, step, dialog, &mut variables, &forbidden)
+            .await?;
+        Ok(result)
+    }
+
+    /// Execute a step, failing it if any forbidden pattern appears first.
+    ///
+    /// Stores the step's capture in `variables`, and also returns the
+    /// sub-scripts to run next: the step's repeat, then the branch taken.
+    async fn run_step<'s, T>(
+        &self,
+        session: &mut Session<T>,
+        step: &'s DialogStep,
+        dialog: &Dialog,
+        variables: &mut HashMap<String, String>,
+        forbidden: &[String],
+    ) -> Result<(StepResult, Vec<Frame<'s>>)>
     where
         T: AsyncReadExt + AsyncWriteExt + Unpin + Send,
     {
         let timeout = step.timeout.unwrap_or(self.default_timeout);
         let mut output = String::new();
         let mut matched_text = None;
+        let mut taken = None;
+        let mut fallback = None;
 
-        // Handle expect pattern if present
-        if let Some(ref expect_pattern) = step.expect {
-            let pattern = Pattern::literal(dialog.substitute(expect_pattern));
-            let mut patterns = PatternSet::new();
-            patterns.add(pattern).add(Pattern::timeout(timeout));
+        if let Some(delay) = step.delay {
+            tokio::time::sleep(delay).await;
+        }
 
+        if let Some((patterns, first_forbidden, waiting_for)) =
+            wait_patterns(step, variables, forbidden, timeout)?
+        {
             match session.expect_any(&patterns).await {
+                Ok(m)
+                    if (first_forbidden..first_forbidden + forbidden.len())
+                        .contains(&m.pattern_index) =>
+                {
+                    let error = format!(
+                        "Forbidden pattern '{}' appeared while waiting for {waiting_for}",
+                        forbidden[m.pattern_index - first_forbidden]
+                    );
+                    return Ok((failed(step, m.before, Some(m.matched), error), Vec::new()));
+                }
                 Ok(m) => {
+                    if let Err(error) = store_capture(step, &m, variables) {
+                        return Ok((failed(step, m.before, Some(m.matched), error), Vec::new()));
+                    }
                     output.clone_from(&m.before);
                     matched_text = Some(m.matched);
+                    taken = step
+                        .if_match
+                        .get(m.pattern_index)
+                        .map(|b| b.steps.as_slice());
                 }
                 Err(ExpectError::Timeout { buffer, .. }) => {
-                    if step.continue_on_timeout {
+                    if let Some(ref on_timeout) = step.on_timeout {
+                        output = buffer;
+                        fallback = Some(on_timeout.as_ref());
+                    } else if step.continue_on_timeout {
                         output = buffer;
                     } else {
-                        return Ok(StepResult {
-                            step_name: step.name.clone(),
-                            success: false,
-                            output: buffer,
-                            matched: None,
-                            send: None,
-                            error: Some(format!(
-                                "Timeout waiting for pattern '{expect_pattern}' after {timeout:?}"
-                            )),
-                            next_step: None,
-                        });
+                        let error =
+                            format!("Timeout waiting for pattern {waiting_for} after {timeout:?}");
+                        return Ok((failed(step, buffer, None, error), Vec::new()));
                     }
                 }
                 Err(e) => return Err(e),
@@ -335,20 +429,15 @@ impl DialogExecutor {
             }
         }
 
-        // Handle send if present (text or control character)
-        let substituted_send = if let Some(ref send_text) = step.send {
-            let substituted = dialog.substitute(send_text);
-            session.send_str(&substituted).await?;
-            Some(substituted)
-        } else if let Some(ctrl) = step.send_control {
-            session.send_control(ctrl).await?;
-            Some(format!("<{ctrl:?}>"))
-        } else {
+        // The fallback replaces the step's own response
+        let substituted_send = if fallback.is_some() {
             None
+        } else {
+            send_response(session, step, variables).await?
         };
 
         // Determine next step if not set
-        if next_step.is_none() {
+        if next_step.is_none() && !step.name.is_empty() {
             next_step = dialog
                 .steps
                 .iter()
@@ -357,7 +446,7 @@ impl DialogExecutor {
                 .map(|s| s.name.clone());
         }
 
-        Ok(StepResult {
+        let result = StepResult {
             step_name: step.name.clone(),
             success: true,
             output,
@@ -365,8 +454,256 @@ impl DialogExecutor {
             send: substituted_send,
             error: None,
             next_step,
-        })
+        };
+        let mut sub_scripts = Vec::new();
+        if let Some(ref repeat) = step.repeat {
+            sub_scripts.push(Frame::repeat(step, repeat));
+        }
+        if let Some(taken) = taken {
+            sub_scripts.push(Frame::branch(taken));
+        }
+        if let Some(fallback) = fallback {
+            sub_scripts.push(Frame::branch(std::slice::from_ref(fallback)));
+        }
+        Ok((result, sub_scripts))
+    }
+
+    /// Wait for a repeat's `until` pattern after an attempt.
+    ///
+    /// Returns `None` if it did not appear within the repeat step's timeout
+    /// and attempts are left.
+    async fn check_until<T>(
+        &self,
+        session: &mut Session<T>,
+        repeating: Repeating<'_>,
+        dialog: &Dialog,
+        variables: &mut HashMap<String, String>,
+        forbidden: &[String],
+    ) -> Result<Option<StepResult>>
+    where
+        T: AsyncReadExt + AsyncWriteExt + Unpin + Send,
+    {
+        let Repeating {
+            owner,
+            repeat,
+            attempts,
+        } = repeating;
+        let until = DialogStep {
+            name: owner.name.clone(),
+            expect: Some(repeat.until.clone()),
+            regex: repeat.regex,
+            timeout: owner.timeout,
+            continue_on_timeout: true,
+            ..Default::default()
+        };
+        let (mut result, _) = self
+            .run_step(session, &until, dialog, variables, forbidden)
+            .await?;
+        if result.success && result.matched.is_none() {
+            if attempts < repeat.max_attempts {
+                return Ok(None);
+            }
+            result.success = false;
+            result.error = Some(format!(
+                "Pattern '{}' did not appear after {attempts} attempts",
+                repeat.until
+            ));
+        }
+        Ok(Some(result))
+    }
+}
+
+/// A sub-script being run by [`DialogExecutor::execute`].
+struct Frame<'a> {
+    steps: std::slice::Iter<'a, DialogStep>,
+    /// Set when the steps belong to a repeat.
+    repeating: Option<Repeating<'a>>,
+}
+
+impl<'a> Frame<'a> {
+    fn branch(steps: &'a [DialogStep]) -> Self {
+        Self {
+            steps: steps.iter(),
+            repeating: None,
+        }
+    }
+
+    fn repeat(owner: &'a DialogStep, repeat: &'a DialogRepeat) -> Self {
+        Self {
+            steps: repeat.steps.iter(),
+            repeating: Some(Repeating {
+                owner,
+                repeat,
+                attempts: 1,
+            }),
+        }
+    }
+
+    /// Start the next attempt of a repeat.
+    fn retry(&mut self) {
+        if let Some(ref mut repeating) = self.repeating {
+            repeating.attempts += 1;
+            self.steps = repeating.repeat.steps.iter();
+        }
+    }
+}
+
+/// A repeat in progress.
+#[derive(Clone, Copy)]
+struct Repeating<'a> {
+    /// The step the repeat belongs to.
+    owner: &'a DialogStep,
+    repeat: &'a DialogRepeat,
+    /// Attempts started so far.
+    attempts: u32,
+}
+
+/// What the innermost sub-script does next.
+enum Nested<'a> {
+    /// Run one of its steps.
+    Step(&'a DialogStep),
+    /// Check whether a finished repeat attempt succeeded.
+    Until(Repeating<'a>),
+}
+
+/// Determine the starting step.
+fn entry_index(dialog: &Dialog) -> usize {
+    dialog
+        .entry
+        .as_ref()
+        .and_then(|entry| dialog.steps.iter().position(|s| &s.name == entry))
+        .unwrap_or(0)
+}
+
+/// Send a step's text, control character or raw bytes, if any, returning what was
+/// sent after variable substitution.
+async fn send_response<T>(
+    session: &mut Session<T>,
+    step: &DialogStep,
+    variables: &HashMap<String, String>,
+) -> Result<Option<String>>
+where
+    T: AsyncReadExt + AsyncWriteExt + Unpin + Send,
+{
+    if let Some(ref send_text) = step.send {
+        let substituted = substitute(send_text, variables);
+        if step.send_line {
+            session.send_line(&substituted).await?;
+        } else {
+            session.send_str(&substituted).await?;
+        }
+        Ok(Some(substituted))
+    } else if let Some(ctrl) = step.send_control {
+        session.send_control(ctrl).await?;
+        Ok(Some(format!("<{ctrl:?}>")))
+    } else if let Some(ref bytes) = step.send_bytes {
+        session.send(bytes).await?;
+        let hex: Vec<String> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        Ok(Some(format!("<{}>", hex.join(" "))))
+    } else {
+        Ok(None)
+    }
+}
+
+/// The result of a step that failed before sending anything.
+fn failed(step: &DialogStep, output: String, matched: Option<String>, error: String) -> StepResult {
+    StepResult {
+        step_name: step.name.clone(),
+        success: false,
+        output,
+        matched,
+        send: None,
+        error: Some(error),
+        next_step: None,
+    }
+}
+
+/// Store the step's capture group of `m`, if it has one.
+fn store_capture(
+    step: &DialogStep,
+    m: &Match,
+    variables: &mut HashMap<String, String>,
+) -> std::result::Result<(), String> {
+    let Some(ref capture) = step.capture else {
+        return Ok(());
+    };
+    // Literal matches carry no groups, but group 0 is still the matched text
+    let value = if capture.group == 0 {
+        Some(m.matched.as_str())
+    } else {
+        m.group(capture.group)
+    };
+    let Some(value) = value else {
+        return Err(format!(
+            "Capture group {} did not match in '{}'",
+            capture.group, m.matched
+        ));
+    };
+    variables.insert(capture.var.clone(), value.to_string());
+    Ok(())
+}
+
+/// Build the pattern set a step waits on.
+///
+/// The step's own patterns (the branch patterns, or else the expect
+/// pattern) come first, then the forbidden patterns, then the timeout.
+/// Returns the set, the index of the first forbidden pattern and a
+/// description of what is awaited, or `None` if the step expects nothing.
+fn wait_patterns(
+    step: &DialogStep,
+    variables: &HashMap<String, String>,
+    forbidden: &[String],
+    timeout: Duration,
+) -> Result<Option<(PatternSet, usize, String)>> {
+    // Branch patterns take the place of the expect pattern
+    let wanted: Vec<(&str, bool)> = if step.if_match.is_empty() {
+        step.expect
+            .iter()
+            .map(|p| (p.as_str(), step.regex))
+            .collect()
+    } else {
+        step.if_match
+            .iter()
+            .map(|b| (b.pattern.as_str(), b.regex))
+            .collect()
+    };
+    if wanted.is_empty() {
+        return Ok(None);
+    }
+
+    let waiting_for = wanted
+        .iter()
+        .map(|(p, _)| format!("'{p}'"))
+        .collect::<Vec<_>>()
+        .join(" or ");
+    let mut patterns = PatternSet::new();
+    for &(pattern, regex) in &wanted {
+        let text = substitute(pattern, variables);
+        patterns.add(if regex {
+            Pattern::regex(&text)?
+        } else {
+            Pattern::literal(text)
+        });
+    }
+    for f in forbidden {
+        patterns.add(Pattern::literal(f.clone()));
+    }
+    patterns.add(Pattern::timeout(timeout));
+    Ok(Some((patterns, wanted.len(), waiting_for)))
+}
+
+/// Take the next action of the innermost unfinished sub-script.
+fn next_nested<'a>(frames: &mut Vec<Frame<'a>>) -> Option<Nested<'a>> {
+    while let Some(frame) = frames.last_mut() {
+        if let Some(step) = frame.steps.next() {
+            return Some(Nested::Step(step));
+        }
+        if let Some(repeating) = frame.repeating {
+            return Some(Nested::Until(repeating));
+        }
+        frames.pop();
     }
+    None
 }
 
 #[cfg(test)]
@@ -152,47 +152,86 @@ pub fn decode_utf8_skip(bytes: &[u8]) -> EncodedText {
 
 /// Normalize line endings in text.
 ///
-/// Converts all line endings (CRLF, CR, LF) to the specified style.
+/// Converts all line endings (CRLF, CR, LF) to the specified style. A bare
+/// CR is treated as a line ending; use [`normalize_line_endings_with`] to
+/// treat it as a line overwrite instead.
 #[must_use]
 pub fn normalize_line_endings(text: &str, ending: LineEndingStyle) -> Cow<'_, str> {
-    let target = ending.as_str();
-
-    // Check if normalization is needed
-    let needs_crlf = text.contains("\r\n");
-    let needs_cr = text.contains('\r') && !needs_crlf;
-    let needs_lf = text.contains('\n') && !needs_crlf;
-
-    // If already normalized, return as-is
-    match ending {
-        LineEndingStyle::Lf if !needs_crlf && !needs_cr => return Cow::Borrowed(text),
-        LineEndingStyle::CrLf if needs_crlf && !needs_cr && !needs_lf => {
-            return Cow::Borrowed(text);
-        }
-        LineEndingStyle::Cr if needs_cr && !needs_crlf && !needs_lf => return Cow::Borrowed(text),
-        _ => {}
+    normalize_line_endings_with(text, ending, BareCr::Newline)
+}
+
+/// Normalize line endings in text, choosing how a bare CR is handled.
+///
+/// Every CRLF, LF and (with [`BareCr::Newline`]) bare CR becomes the line
+/// ending of `ending`. [`LineEndingStyle::Auto`] picks the predominant style
+/// of `text` so mixed endings come out consistent. With
+/// [`BareCr::Overwrite`], a bare CR returns to the start of the line and
+/// the text after it overwrites what was there, so a progress bar collapses
+/// to its last state.
+#[must_use]
+pub fn normalize_line_endings_with(
+    text: &str,
+    ending: LineEndingStyle,
+    bare_cr: BareCr,
+) -> Cow<'_, str> {
+    let target = ending.resolve(text).as_str();
+
+    // Without a CR, only LF endings can differ from the target
+    if !text.contains('\r') && (target == "\n" || !text.contains('\n')) {
+        return Cow::Borrowed(text);
     }
 
-    // First normalize all endings to LF
-    let normalized = if needs_crlf {
-        text.replace("\r\n", "\n")
-    } else {
-        text.to_string()
-    };
+    let mut result = String::with_capacity(text.len());
+    // The current line and the column a bare CR rewinds
+    let mut line: Vec<char> = Vec::new();
+    let mut col = 0;
+    let mut chars = text.chars().peekable();
 
-    let normalized = if normalized.contains('\r') {
-        normalized.replace('\r', "\n")
-    } else {
-        normalized
-    };
+    while let Some(c) = chars.next() {
+        let line_end = match c {
+            '\r' if chars.peek() == Some(&'\n') => {
+                chars.next();
+                true
+            }
+            '\n' => true,
+            '\r' => bare_cr == BareCr::Newline,
+            _ => {
+                if col < line.len() {
+                    line[col] = c;
+                } else {
+                    line.push(c);
+                }
+                col += 1;
+                continue;
+            }
+        };
+        if line_end {
+            result.extend(line.iter());
+            result.push_str(target);
+            line.clear();
+        }
+        col = 0;
+    }
+    result.extend(line);
 
-    // Then convert to target if not LF
-    let result = if target == "\n" {
-        normalized
+    if result == text {
+        Cow::Borrowed(text)
     } else {
-        normalized.replace('\n', target)
-    };
+        Cow::Owned(result)
+    }
+}
 
-    Cow::Owned(result)
+/// How [`normalize_line_endings_with`] treats a CR not followed by LF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BareCr {
+    /// A bare CR ends the line, as on classic Mac OS.
+    #[default]
+    Newline,
+    /// A bare CR returns to the start of the line, as on a terminal.
+    ///
+    /// Suits progress output from tools like `curl` that redraw a line in
+    /// place.
+    Overwrite,
 }
 
 /// Line ending styles.
@@ -205,26 +244,40 @@ pub enum LineEndingStyle {
     CrLf,
     /// Classic Mac (CR)
     Cr,
+    /// The predominant style of the text being normalized.
+    ///
+    /// CRLF when it occurs more often than bare LF, LF when the text has
+    /// any other line ending, and the platform style otherwise. A bare CR
+    /// is never chosen, since it usually comes from progress output.
+    Auto,
 }
 
 impl LineEndingStyle {
     /// Get the line ending as a string.
+    ///
+    /// [`Auto`](Self::Auto) has no fixed ending and returns the platform
+    /// style.
     #[must_use]
     pub const fn as_str(self) -> &'static str {
         match self {
             Self::Lf => "\n",
             Self::CrLf => "\r\n",
             Self::Cr => "\r",
+            Self::Auto => Self::from_env().as_str(),
         }
     }
 
     /// Get the line ending as bytes.
+    ///
+    /// [`Auto`](Self::Auto) has no fixed ending and returns the platform
+    /// style.
     #[must_use]
     pub const fn as_bytes(self) -> &'static [u8] {
         match self {
             Self::Lf => b"\n",
             Self::CrLf => b"\r\n",
             Self::Cr => b"\r",
+            Self::Auto => Self::from_env().as_bytes(),
         }
     }
 
@@ -233,6 +286,22 @@ impl LineEndingStyle {
     pub const fn from_env() -> Self {
         if cfg!(windows) { Self::CrLf } else { Self::Lf }
     }
+
+    /// The concrete style to normalize `text` to.
+    fn resolve(self, text: &str) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        if crlf > lf {
+            Self::CrLf
+        } else if lf > 0 || text.contains('\r') {
+            Self::Lf
+        } else {
+            Self::from_env()
+        }
+    }
 }
 
 /// Detect the predominant line ending in text.
@@ -261,30 +330,53 @@ pub fn detect_line_ending(text: &str) -> Option<LineEndingStyle> {
 
 /// Detect encoding from environment variables.
 ///
-/// Checks `LC_ALL`, `LC_CTYPE`, and `LANG` in order.
+/// Follows POSIX precedence for the character type category: the first of
+/// `LC_ALL`, `LC_CTYPE` and `LANG` that is set and non-empty wins. Its
+/// codeset is parsed with [`detect_encoding_from_locale`]. When none is set,
+/// UTF-8 is assumed.
 #[must_use]
 pub fn detect_encoding_from_env() -> DetectedEncoding {
-    let locale = std::env::var("LC_ALL")
-        .or_else(|_| std::env::var("LC_CTYPE"))
-        .or_else(|_| std::env::var("LANG"))
-        .unwrap_or_default();
-
-    let locale_lower = locale.to_lowercase();
-
-    if locale_lower.contains("utf-8") || locale_lower.contains("utf8") {
-        DetectedEncoding::Utf8
-    } else if locale_lower.contains("iso-8859-1") || locale_lower.contains("iso8859-1") {
-        DetectedEncoding::Latin1
-    } else if locale_lower.contains("1252") {
-        DetectedEncoding::Windows1252
-    } else if locale.is_empty() {
-        // Default to UTF-8 for modern systems
-        DetectedEncoding::Utf8
-    } else {
-        DetectedEncoding::Unknown(locale)
+    ctype_locale(|name| std::env::var(name).ok()).map_or(DetectedEncoding::Utf8, |locale| {
+        detect_encoding_from_locale(&locale)
+    })
+}
+
+/// Detect encoding from a locale name such as `en_US.UTF-8@euro`.
+///
+/// The name has the form `language[_territory][.codeset][@modifier]`; only
+/// the codeset is significant. Codesets are compared case-insensitively
+/// and ignoring `-` and `_`, so `UTF-8`, `utf8` and `Utf_8` are all UTF-8.
+/// An unrecognized codeset is returned as [`DetectedEncoding::Unknown`],
+/// as is a locale without one (such as `C`).
+#[must_use]
+pub fn detect_encoding_from_locale(locale: &str) -> DetectedEncoding {
+    let name = locale.split_once('@').map_or(locale, |(name, _)| name);
+    let Some((_, codeset)) = name.split_once('.') else {
+        return DetectedEncoding::Unknown(locale.to_string());
+    };
+
+    let normalized: String = codeset
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_'))
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    match normalized.as_str() {
+        "utf8" => DetectedEncoding::Utf8,
+        "iso88591" | "latin1" => DetectedEncoding::Latin1,
+        "cp1252" | "windows1252" => DetectedEncoding::Windows1252,
+        "sjis" | "shiftjis" | "cp932" | "windows31j" | "pck" => DetectedEncoding::ShiftJis,
+        _ => DetectedEncoding::Unknown(codeset.to_string()),
     }
 }
 
+/// The locale governing character type, looked up through `var`.
+fn ctype_locale(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .filter_map(var)
+        .find(|value| !value.is_empty())
+}
+
 /// Detected encoding from environment.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DetectedEncoding {
@@ -294,7 +386,10 @@ pub enum DetectedEncoding {
     Latin1,
     /// Windows-1252.
     Windows1252,
-    /// Unknown encoding (contains the locale string).
+    /// Shift-JIS.
+    ShiftJis,
+    /// Unknown encoding (contains the codeset, or the locale name when it
+    /// has none).
     Unknown(String),
 }
 
@@ -306,9 +401,110 @@ impl DetectedEncoding {
     }
 }
 
+/// Incremental decoder for PTY output in a non-UTF-8 charset.
+///
+/// Output arrives in arbitrary chunks, so a multibyte character may be split
+/// across two reads. The decoder holds back an incomplete trailing sequence
+/// until the next chunk completes it. Malformed input is replaced with
+/// U+FFFD.
+#[cfg(feature = "legacy-encoding")]
+pub struct StreamingDecoder {
+    inner: DecoderKind,
+}
+
+#[cfg(feature = "legacy-encoding")]
+enum DecoderKind {
+    /// ISO-8859-1 maps each byte to the code point of the same value.
+    ///
+    /// `encoding_rs` follows the WHATWG tables, which treat the Latin-1
+    /// labels as Windows-1252, so true Latin-1 is decoded here.
+    Latin1,
+    Rs(encoding_rs::Decoder),
+}
+
+#[cfg(feature = "legacy-encoding")]
+impl std::fmt::Debug for StreamingDecoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamingDecoder")
+            .field("encoding", &self.encoding_name())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "legacy-encoding")]
+impl StreamingDecoder {
+    /// Create a decoder for `encoding`.
+    ///
+    /// For [`DetectedEncoding::Unknown`] the string is looked up as a
+    /// WHATWG encoding label (e.g. `"euc-jp"`). Returns `None` if the label
+    /// is not recognized.
+    #[must_use]
+    pub fn new(encoding: &DetectedEncoding) -> Option<Self> {
+        let rs = match encoding {
+            DetectedEncoding::Latin1 => {
+                return Some(Self {
+                    inner: DecoderKind::Latin1,
+                });
+            }
+            DetectedEncoding::Utf8 => encoding_rs::UTF_8,
+            DetectedEncoding::Windows1252 => encoding_rs::WINDOWS_1252,
+            DetectedEncoding::ShiftJis => encoding_rs::SHIFT_JIS,
+            DetectedEncoding::Unknown(label) => encoding_rs::Encoding::for_label(label.as_bytes())?,
+        };
+        Some(Self {
+            inner: DecoderKind::Rs(rs.new_decoder_without_bom_handling()),
+        })
+    }
+
+    /// Name of the charset being decoded.
+    #[must_use]
+    pub fn encoding_name(&self) -> &'static str {
+        match &self.inner {
+            DecoderKind::Latin1 => "ISO-8859-1",
+            DecoderKind::Rs(decoder) => decoder.encoding().name(),
+        }
+    }
+
+    /// Decode the next chunk of output.
+    ///
+    /// Bytes of an incomplete trailing sequence are kept and prepended to
+    /// the next chunk.
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        self.decode_inner(bytes, false)
+    }
+
+    /// Flush any held-back bytes at end of stream.
+    ///
+    /// An incomplete trailing sequence decodes to U+FFFD. The decoder is
+    /// reset and can be reused.
+    pub fn finish(&mut self) -> String {
+        self.decode_inner(&[], true)
+    }
+
+    fn decode_inner(&mut self, bytes: &[u8], last: bool) -> String {
+        match &mut self.inner {
+            DecoderKind::Latin1 => bytes.iter().copied().map(char::from).collect(),
+            DecoderKind::Rs(decoder) => {
+                let capacity = decoder
+                    .max_utf8_buffer_length(bytes.len())
+                    .unwrap_or(bytes.len() * 3 + 4);
+                let mut out = String::with_capacity(capacity);
+                let _ = decoder.decode_to_string(bytes, &mut out, last);
+                if last {
+                    *decoder = decoder.encoding().new_decoder_without_bom_handling();
+                }
+                out
+            }
+        }
+    }
+}
+
 /// Strip ANSI escape sequences from text.
 ///
-/// Removes all ANSI control sequences (CSI, OSC, etc.) from the input.
+/// Removes CSI sequences with any final byte, OSC strings terminated by BEL
+/// or ST, DCS, SOS, PM and APC strings, character-set designations, and
+/// single-character escapes such as cursor save/restore. Only the printable
+/// text and ordinary control characters survive.
 #[must_use]
 pub fn strip_ansi(text: &str) -> Cow<'_, str> {
     // Quick check: if no escape character, return as-is
@@ -316,56 +512,17 @@ pub fn strip_ansi(text: &str) -> Cow<'_, str> {
         return Cow::Borrowed(text);
     }
 
+    let bytes = text.as_bytes();
     let mut result = String::with_capacity(text.len());
-    let mut chars = text.chars().peekable();
+    let mut start = 0;
 
-    while let Some(c) = chars.next() {
-        if c == '\x1b' {
-            // Start of escape sequence
-            if let Some(&next) = chars.peek() {
-                match next {
-                    '[' => {
-                        // CSI sequence: ESC [ ... final byte
-                        chars.next(); // consume '['
-                        while let Some(&param) = chars.peek() {
-                            if param.is_ascii_alphabetic() || param == '@' || param == '`' {
-                                chars.next(); // consume final byte
-                                break;
-                            }
-                            chars.next();
-                        }
-                    }
-                    ']' => {
-                        // OSC sequence: ESC ] ... ST or BEL
-                        chars.next(); // consume ']'
-                        while let Some(osc_char) = chars.next() {
-                            if osc_char == '\x07' || osc_char == '\x1b' {
-                                // BEL or possible ST
-                                if osc_char == '\x1b' && chars.peek() == Some(&'\\') {
-                                    chars.next(); // consume '\\'
-                                }
-                                break;
-                            }
-                        }
-                    }
-                    '(' | ')' | '*' | '+' => {
-                        // Designate character set: ESC ( X
-                        chars.next();
-                        chars.next();
-                    }
-                    _ if next.is_ascii_uppercase() || next == '=' || next == '>' => {
-                        // Simple escape sequence: ESC X
-                        chars.next();
-                    }
-                    _ => {
-                        // Unknown, just skip the ESC
-                    }
-                }
-            }
-        } else {
-            result.push(c);
-        }
+    while let Some(offset) = text[start..].find('\x1b') {
+        let esc = start + offset;
+        result.push_str(&text[start..esc]);
+        // Sequences end on ASCII bytes, so this is a char boundary
+        start = esc + crate::util::bytes::escape_sequence_len(&bytes[esc..]);
     }
+    result.push_str(&text[start..]);
 
     Cow::Owned(result)
 }
@@ -418,6 +575,70 @@ mod tests {
         assert_eq!(result, "line1\r\nline2\r\nline3");
     }
 
+    #[test]
+    fn normalize_lone_cr_as_newline() {
+        let text = "10%\r50%\r100%\ndone";
+        let result = normalize_line_endings(text, LineEndingStyle::Lf);
+        assert_eq!(result, "10%\n50%\n100%\ndone");
+    }
+
+    #[test]
+    fn normalize_lone_cr_as_overwrite() {
+        let text = "  0%\r 50%\r100%\ndone\r\n";
+        let result = normalize_line_endings_with(text, LineEndingStyle::Lf, BareCr::Overwrite);
+        assert_eq!(result, "100%\ndone\n");
+    }
+
+    #[test]
+    fn normalize_overwrite_keeps_longer_tail() {
+        // A shorter redraw leaves the end of the previous line visible
+        let text = "downloading\rfetch";
+        let result = normalize_line_endings_with(text, LineEndingStyle::Lf, BareCr::Overwrite);
+        assert_eq!(result, "fetchoading");
+    }
+
+    #[test]
+    fn normalize_mixed_to_crlf() {
+        let text = "a\nb\r\nc\rd";
+        let result = normalize_line_endings(text, LineEndingStyle::CrLf);
+        assert_eq!(result, "a\r\nb\r\nc\r\nd");
+    }
+
+    #[test]
+    fn normalize_auto_picks_predominant_style() {
+        let text = "a\r\nb\r\nc\nd";
+        let result = normalize_line_endings(text, LineEndingStyle::Auto);
+        assert_eq!(result, "a\r\nb\r\nc\r\nd");
+
+        let text = "a\nb\nc\r\nd\re";
+        let result = normalize_line_endings(text, LineEndingStyle::Auto);
+        assert_eq!(result, "a\nb\nc\nd\ne");
+    }
+
+    #[test]
+    fn normalize_auto_never_picks_bare_cr() {
+        let text = "1\r2\r3";
+        let result = normalize_line_endings(text, LineEndingStyle::Auto);
+        assert_eq!(result, "1\n2\n3");
+        let result = normalize_line_endings_with(text, LineEndingStyle::Auto, BareCr::Overwrite);
+        assert_eq!(result, "3");
+    }
+
+    #[test]
+    fn normalize_unchanged_is_borrowed() {
+        for (text, style) in [
+            ("a\nb", LineEndingStyle::Lf),
+            ("a\r\nb", LineEndingStyle::CrLf),
+            ("a\rb", LineEndingStyle::Cr),
+            ("a\r\nb\r\n", LineEndingStyle::Auto),
+        ] {
+            assert!(matches!(
+                normalize_line_endings(text, style),
+                Cow::Borrowed(_)
+            ));
+        }
+    }
+
     #[test]
     fn detect_line_ending_lf() {
         assert_eq!(
@@ -434,6 +655,88 @@ mod tests {
         );
     }
 
+    fn lookup<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| (*value).to_string())
+        }
+    }
+
+    #[test]
+    fn ctype_locale_precedence() {
+        let all = [
+            ("LANG", "fr_FR.ISO-8859-1"),
+            ("LC_CTYPE", "ja_JP.SJIS"),
+            ("LC_ALL", "en_US.UTF-8"),
+        ];
+        assert_eq!(ctype_locale(lookup(&all)).as_deref(), Some("en_US.UTF-8"));
+        assert_eq!(
+            ctype_locale(lookup(&all[..2])).as_deref(),
+            Some("ja_JP.SJIS")
+        );
+        assert_eq!(
+            ctype_locale(lookup(&all[..1])).as_deref(),
+            Some("fr_FR.ISO-8859-1")
+        );
+        assert_eq!(ctype_locale(lookup(&[])), None);
+    }
+
+    #[test]
+    fn ctype_locale_skips_empty_values() {
+        let vars = [("LC_ALL", ""), ("LC_CTYPE", ""), ("LANG", "de_DE.CP1252")];
+        assert_eq!(ctype_locale(lookup(&vars)).as_deref(), Some("de_DE.CP1252"));
+    }
+
+    #[test]
+    fn locale_codesets() {
+        assert_eq!(
+            detect_encoding_from_locale("en_US.UTF-8"),
+            DetectedEncoding::Utf8
+        );
+        assert_eq!(
+            detect_encoding_from_locale("C.utf8"),
+            DetectedEncoding::Utf8
+        );
+        assert_eq!(
+            detect_encoding_from_locale("de_DE.iso88591"),
+            DetectedEncoding::Latin1
+        );
+        assert_eq!(
+            detect_encoding_from_locale("en_GB.CP1252"),
+            DetectedEncoding::Windows1252
+        );
+        assert_eq!(
+            detect_encoding_from_locale("ja_JP.Shift_JIS"),
+            DetectedEncoding::ShiftJis
+        );
+        assert_eq!(
+            detect_encoding_from_locale("ja_JP.eucJP"),
+            DetectedEncoding::Unknown("eucJP".into())
+        );
+        assert_eq!(
+            detect_encoding_from_locale("C"),
+            DetectedEncoding::Unknown("C".into())
+        );
+    }
+
+    #[test]
+    fn locale_modifier_is_ignored() {
+        assert_eq!(
+            detect_encoding_from_locale("en_US.UTF-8@euro"),
+            DetectedEncoding::Utf8
+        );
+        assert_eq!(
+            detect_encoding_from_locale("de_DE.ISO-8859-1@euro"),
+            DetectedEncoding::Latin1
+        );
+        // A modifier without a codeset leaves the encoding unknown
+        assert_eq!(
+            detect_encoding_from_locale("sr_RS@latin"),
+            DetectedEncoding::Unknown("sr_RS@latin".into())
+        );
+    }
+
     #[test]
     fn strip_ansi_csi() {
         let text = "\x1b[32mgreen\x1b[0m text";
@@ -449,10 +752,108 @@ mod tests {
         assert_eq!(result, "plain text");
     }
 
+    #[test]
+    fn strip_ansi_csi_any_final_byte() {
+        // Cursor visibility, scrolling and bracketed keys end in non-letters
+        let text = "\x1b[?25la\x1b[2~b\x1b[1 qc\x1b[>4;1md";
+        assert_eq!(strip_ansi(text), "abcd");
+    }
+
+    #[test]
+    fn strip_ansi_osc_st_terminated() {
+        let text = "\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\ text";
+        assert_eq!(strip_ansi(text), "link text");
+    }
+
+    #[test]
+    fn strip_ansi_dcs_and_string_sequences() {
+        let text = "a\x1bP1$r0m\x1b\\b\x1b_apc\x1b\\c\x1b^pm\x1b\\d\x1bXsos\x1b\\e";
+        assert_eq!(strip_ansi(text), "abcde");
+    }
+
+    #[test]
+    fn strip_ansi_single_char_escapes() {
+        // Cursor save/restore, keypad modes, charset designation, reset
+        let text = "\x1b7one\x1b8 \x1b=two\x1b> \x1b(0\x1b)Bthree\x1b#8\x1bc";
+        assert_eq!(strip_ansi(text), "one two three");
+    }
+
+    #[test]
+    fn strip_ansi_ls_color_output() {
+        let text = "\x1b]0;user@host: ~\x07\x1b[0m\x1b[01;34mdir\x1b[0m  \x1b[01;32mrun.sh\x1b[0m\r\n\x1b[K";
+        assert_eq!(strip_ansi(text), "dir  run.sh\r\n");
+    }
+
+    #[test]
+    fn strip_ansi_keeps_unicode() {
+        let text = "\x1b]2;タイトル\x07\x1b[1mこんにちは\x1b[0m é";
+        assert_eq!(strip_ansi(text), "こんにちは é");
+    }
+
     #[test]
     fn strip_ansi_osc() {
         let text = "\x1b]0;Window Title\x07normal text";
         let result = strip_ansi(text);
         assert_eq!(result, "normal text");
     }
+
+    /// "こんにちは" in Shift-JIS: two bytes per character.
+    #[cfg(feature = "legacy-encoding")]
+    const SJIS_HELLO: &[u8] = b"\x82\xb1\x82\xf1\x82\xc9\x82\xbf\x82\xcd";
+
+    #[cfg(feature = "legacy-encoding")]
+    #[test]
+    fn streaming_decoder_shift_jis_byte_at_a_time() {
+        let mut decoder = StreamingDecoder::new(&DetectedEncoding::ShiftJis).unwrap();
+        let mut text = String::new();
+        for (i, byte) in SJIS_HELLO.iter().enumerate() {
+            let chunk = decoder.decode(std::slice::from_ref(byte));
+            // Lead bytes are held back until their trail byte arrives
+            assert_eq!(chunk.is_empty(), i % 2 == 0);
+            text.push_str(&chunk);
+        }
+        assert_eq!(text, "こんにちは");
+        assert!(decoder.finish().is_empty());
+    }
+
+    #[cfg(feature = "legacy-encoding")]
+    #[test]
+    fn streaming_decoder_shift_jis_odd_boundaries() {
+        let mut decoder = StreamingDecoder::new(&DetectedEncoding::ShiftJis).unwrap();
+        let (first, rest) = SJIS_HELLO.split_at(3);
+        let (second, third) = rest.split_at(4);
+        assert_eq!(decoder.decode(first), "こ");
+        assert_eq!(decoder.decode(second), "んに");
+        assert_eq!(decoder.decode(third), "ちは");
+    }
+
+    #[cfg(feature = "legacy-encoding")]
+    #[test]
+    fn streaming_decoder_finish_flushes_partial_sequence() {
+        let mut decoder = StreamingDecoder::new(&DetectedEncoding::ShiftJis).unwrap();
+        assert_eq!(decoder.decode(b"ok \x82"), "ok ");
+        assert_eq!(decoder.finish(), "\u{FFFD}");
+        // The decoder is reset and usable again
+        assert_eq!(decoder.decode(b"\x82\xb1"), "こ");
+    }
+
+    #[cfg(feature = "legacy-encoding")]
+    #[test]
+    fn streaming_decoder_single_byte_charsets() {
+        let mut latin1 = StreamingDecoder::new(&DetectedEncoding::Latin1).unwrap();
+        // 0x80 is a C1 control in Latin-1 but the euro sign in Windows-1252
+        assert_eq!(latin1.decode(b"caf\xe9 \x80"), "café \u{80}");
+        assert_eq!(latin1.encoding_name(), "ISO-8859-1");
+
+        let mut cp1252 = StreamingDecoder::new(&DetectedEncoding::Windows1252).unwrap();
+        assert_eq!(cp1252.decode(b"caf\xe9 \x80"), "café €");
+    }
+
+    #[cfg(feature = "legacy-encoding")]
+    #[test]
+    fn streaming_decoder_from_label() {
+        let decoder = StreamingDecoder::new(&DetectedEncoding::Unknown("euc-jp".into())).unwrap();
+        assert_eq!(decoder.encoding_name(), "EUC-JP");
+        assert!(StreamingDecoder::new(&DetectedEncoding::Unknown("C".into())).is_none());
+    }
 }
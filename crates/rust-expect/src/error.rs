@@ -9,6 +9,8 @@ use std::time::Duration;
 
 use thiserror::Error;
 
+use crate::validation::ValidationError;
+
 /// Maximum length of buffer content to display in error messages.
 const MAX_BUFFER_DISPLAY: usize = 500;
 
@@ -115,6 +117,26 @@ fn format_eof_error(buffer: &str) -> String {
     )
 }
 
+/// Format validation errors one per line.
+fn format_validation_errors(errors: &[ValidationError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("  - {e}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Format an unexpected data error message.
+fn format_unexpected_data_error(duration: Duration, buffer: &str) -> String {
+    let buffer_snippet = format_buffer_snippet(buffer);
+
+    format!(
+        "output kept arriving for {duration:?} without end of file\n\
+         \n\
+         {buffer_snippet}"
+    )
+}
+
 /// The main error type for rust-expect operations.
 #[derive(Debug, Error)]
 pub enum ExpectError {
@@ -172,6 +194,24 @@ pub enum ExpectError {
         buffer: String,
     },
 
+    /// Output kept arriving while waiting for end of file.
+    #[error("{}", format_unexpected_data_error(*duration, buffer))]
+    UnexpectedData {
+        /// How long the wait lasted.
+        duration: Duration,
+        /// Output received while waiting.
+        buffer: String,
+    },
+
+    /// A forbidden pattern matched before the expected one.
+    #[error("forbidden pattern '{pattern}' matched: {matched:?}")]
+    ForbiddenMatch {
+        /// The forbidden pattern that matched.
+        pattern: String,
+        /// The text it matched.
+        matched: String,
+    },
+
     /// Invalid pattern specification.
     #[error("invalid pattern: {message}")]
     InvalidPattern {
@@ -291,6 +331,22 @@ pub enum SpawnError {
         path: String,
     },
 
+    /// The working directory does not exist.
+    #[error("working directory does not exist: {path}")]
+    WorkingDirMissing {
+        /// The missing working directory path.
+        path: String,
+    },
+
+    /// The command was found but could not be executed.
+    #[error("failed to execute {command}: {}", std::io::Error::from_raw_os_error(*errno))]
+    ExecFailed {
+        /// The command that failed to execute.
+        command: String,
+        /// The OS error code reported by the failed exec.
+        errno: i32,
+    },
+
     /// General I/O error during spawn.
     #[error("I/O error during spawn: {0}")]
     Io(#[from] std::io::Error),
@@ -305,6 +361,13 @@ pub enum SpawnError {
         /// The reason it's invalid.
         reason: String,
     },
+
+    /// The session configuration failed validation.
+    #[error("invalid session configuration:\n{}", format_validation_errors(errors))]
+    InvalidConfig {
+        /// Every problem found, in field order.
+        errors: Vec<ValidationError>,
+    },
 }
 
 /// Errors related to SSH connections.
@@ -354,6 +417,15 @@ pub enum SshError {
         reason: String,
     },
 
+    /// SFTP operation failed.
+    #[error("SFTP error on '{path}': {reason}")]
+    Sftp {
+        /// The remote path involved.
+        path: String,
+        /// The reason for the failure.
+        reason: String,
+    },
+
     /// Timeout during SSH operation.
     #[error("SSH operation timed out after {duration:?}")]
     Timeout {
@@ -402,6 +474,22 @@ impl ExpectError {
         }
     }
 
+    /// Create an unexpected data error.
+    pub fn unexpected_data(duration: Duration, buffer: impl Into<String>) -> Self {
+        Self::UnexpectedData {
+            duration,
+            buffer: buffer.into(),
+        }
+    }
+
+    /// Create a forbidden match error.
+    pub fn forbidden_match(pattern: impl Into<String>, matched: impl Into<String>) -> Self {
+        Self::ForbiddenMatch {
+            pattern: pattern.into(),
+            matched: matched.into(),
+        }
+    }
+
     /// Create an invalid pattern error.
     pub fn invalid_pattern(message: impl Into<String>) -> Self {
         Self::InvalidPattern {
@@ -461,7 +549,8 @@ impl ExpectError {
             Self::Timeout { buffer, .. }
             | Self::PatternNotFound { buffer, .. }
             | Self::ProcessExited { buffer, .. }
-            | Self::Eof { buffer, .. } => Some(buffer),
+            | Self::Eof { buffer, .. }
+            | Self::UnexpectedData { buffer, .. } => Some(buffer),
             _ => None,
         }
     }
@@ -503,6 +592,42 @@ impl SpawnError {
     pub fn invalid_working_dir(path: impl Into<String>) -> Self {
         Self::InvalidWorkingDir { path: path.into() }
     }
+
+    /// Create a missing working directory error.
+    pub fn working_dir_missing(path: impl Into<String>) -> Self {
+        Self::WorkingDirMissing { path: path.into() }
+    }
+
+    /// Create an invalid configuration error.
+    #[must_use]
+    pub const fn invalid_config(errors: Vec<ValidationError>) -> Self {
+        Self::InvalidConfig { errors }
+    }
+
+    /// Create an exec failure error.
+    pub fn exec_failed(command: impl Into<String>, errno: i32) -> Self {
+        Self::ExecFailed {
+            command: command.into(),
+            errno,
+        }
+    }
+
+    /// Classify the OS error reported when executing `command`.
+    ///
+    /// Missing programs become [`SpawnError::CommandNotFound`], access
+    /// failures become [`SpawnError::PermissionDenied`], and anything else
+    /// is reported as [`SpawnError::ExecFailed`] with the raw error code.
+    pub fn from_exec_error(command: impl Into<String>, error: &std::io::Error) -> Self {
+        let command = command.into();
+        match error.kind() {
+            std::io::ErrorKind::NotFound => Self::CommandNotFound { command },
+            std::io::ErrorKind::PermissionDenied => Self::PermissionDenied { path: command },
+            _ => Self::ExecFailed {
+                command,
+                errno: error.raw_os_error().unwrap_or(0),
+            },
+        }
+    }
 }
 
 #[cfg(feature = "ssh")]
@@ -546,6 +671,14 @@ impl SshError {
         }
     }
 
+    /// Create an SFTP error.
+    pub fn sftp(path: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Sftp {
+            path: path.into(),
+            reason: reason.into(),
+        }
+    }
+
     /// Create a timeout error.
     #[must_use]
     pub const fn timeout(duration: Duration) -> Self {
@@ -630,6 +763,26 @@ mod tests {
         assert!(err.to_string().contains("nonexistent"));
     }
 
+    #[test]
+    fn spawn_error_from_exec_error() {
+        let not_found = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(matches!(
+            SpawnError::from_exec_error("missing", &not_found),
+            SpawnError::CommandNotFound { command } if command == "missing"
+        ));
+
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(matches!(
+            SpawnError::from_exec_error("/etc/passwd", &denied),
+            SpawnError::PermissionDenied { path } if path == "/etc/passwd"
+        ));
+
+        let other = std::io::Error::from_raw_os_error(8);
+        let err = SpawnError::from_exec_error("binary", &other);
+        assert!(matches!(err, SpawnError::ExecFailed { errno: 8, .. }));
+        assert!(err.to_string().contains("binary"));
+    }
+
     #[test]
     fn format_buffer_snippet_empty() {
         let result = format_buffer_snippet("");
@@ -17,4 +17,6 @@ pub use buffer::{DEFAULT_CAPACITY, RingBuffer};
 pub use cache::{CacheStats, DEFAULT_CACHE_SIZE, GLOBAL_CACHE, RegexCache, get_regex};
 pub use large_buffer::{AdaptiveBuffer, LargeBuffer, MMAP_THRESHOLD};
 pub use matcher::{ExpectState, MatchResult, Matcher};
-pub use pattern::{CompiledRegex, NamedPattern, Pattern, PatternMatch, PatternSet};
+pub use pattern::{
+    CompiledGlob, CompiledRegex, MatchAnchor, NamedPattern, Pattern, PatternMatch, PatternSet,
+};
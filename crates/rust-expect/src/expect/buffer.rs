@@ -6,6 +6,8 @@
 use std::collections::VecDeque;
 use std::fmt;
 
+use super::pattern::{Pattern, PatternMatch};
+
 /// Default buffer capacity (1 MB).
 pub const DEFAULT_CAPACITY: usize = 1024 * 1024;
 
@@ -23,6 +25,8 @@ pub struct RingBuffer {
     total_written: usize,
     /// Bytes discarded due to overflow.
     bytes_discarded: usize,
+    /// Length of the prefix already searched without a match.
+    last_scanned: usize,
 }
 
 impl RingBuffer {
@@ -34,6 +38,7 @@ impl RingBuffer {
             max_size,
             total_written: 0,
             bytes_discarded: 0,
+            last_scanned: 0,
         }
     }
 
@@ -51,10 +56,11 @@ impl RingBuffer {
 
         // If new data alone exceeds max_size, only keep the tail
         if data.len() >= self.max_size {
+            self.bytes_discarded += self.data.len() + data.len() - self.max_size;
             self.data.clear();
             let start = data.len() - self.max_size;
             self.data.extend(&data[start..]);
-            self.bytes_discarded += data.len() - self.max_size;
+            self.last_scanned = 0;
             return;
         }
 
@@ -62,6 +68,7 @@ impl RingBuffer {
         let needed_space = (self.data.len() + data.len()).saturating_sub(self.max_size);
         if needed_space > 0 {
             self.bytes_discarded += needed_space;
+            self.last_scanned = self.last_scanned.saturating_sub(needed_space);
             for _ in 0..needed_space {
                 self.data.pop_front();
             }
@@ -117,6 +124,69 @@ impl RingBuffer {
     /// Clear the buffer.
     pub fn clear(&mut self) {
         self.data.clear();
+        self.last_scanned = 0;
+    }
+
+    /// Get the length of the prefix already searched without a match.
+    ///
+    /// The cursor follows the data as it is consumed or discarded, so
+    /// only bytes appended since [`mark_scanned`](Self::mark_scanned) lie
+    /// beyond it.
+    #[must_use]
+    pub const fn last_scanned(&self) -> usize {
+        self.last_scanned
+    }
+
+    /// Record that the whole buffer has been searched without a match.
+    pub fn mark_scanned(&mut self) {
+        self.last_scanned = self.data.len();
+    }
+
+    /// Forget the scan cursor so the next search covers the whole buffer.
+    pub const fn reset_scan(&mut self) {
+        self.last_scanned = 0;
+    }
+
+    /// Get the start of the line containing `offset`: just after the last
+    /// `\n` before it, or the start of the buffer.
+    pub fn line_start(&mut self, offset: usize) -> usize {
+        let data = self.as_slice();
+        let offset = offset.min(data.len());
+        data[..offset]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1)
+    }
+
+    /// Get the buffer contents from `offset` onwards as a string.
+    ///
+    /// The offset is moved back to the nearest UTF-8 character boundary so
+    /// the text decodes cleanly; the adjusted offset is returned with it.
+    pub fn text_from(&mut self, offset: usize) -> (usize, String) {
+        let data = self.as_slice();
+        let mut offset = offset.min(data.len());
+        // Step back over UTF-8 continuation bytes
+        while offset > 0 && offset < data.len() && data[offset] & 0xC0 == 0x80 {
+            offset -= 1;
+        }
+        (
+            offset,
+            String::from_utf8_lossy(&data[offset..]).into_owned(),
+        )
+    }
+
+    /// Search for a pattern in the buffer contents from `offset` onwards.
+    ///
+    /// Positions in the returned match are relative to the start of the
+    /// buffer. Patterns that inspect the text before a match, such as
+    /// anchored ones, treat `offset` as the start of the text.
+    pub fn search_from(&mut self, offset: usize, pattern: &Pattern) -> Option<PatternMatch> {
+        let (offset, text) = self.text_from(offset);
+        pattern.matches(&text).map(|m| PatternMatch {
+            start: offset + m.start,
+            end: offset + m.end,
+            ..m
+        })
     }
 
     /// Find a byte sequence in the buffer.
@@ -147,6 +217,7 @@ impl RingBuffer {
     /// Returns the consumed data.
     pub fn consume(&mut self, end: usize) -> Vec<u8> {
         let end = end.min(self.data.len());
+        self.last_scanned = self.last_scanned.saturating_sub(end);
         self.data.drain(..end).collect()
     }
 
@@ -225,6 +296,7 @@ impl fmt::Debug for RingBuffer {
             .field("max_size", &self.max_size)
             .field("total_written", &self.total_written)
             .field("bytes_discarded", &self.bytes_discarded)
+            .field("last_scanned", &self.last_scanned)
             .finish()
     }
 }
@@ -264,6 +336,52 @@ mod tests {
         assert_eq!(buf.as_str_lossy(), "4567890abc");
     }
 
+    #[test]
+    fn oversized_append_counts_replaced_data_as_discarded() {
+        let mut buf = RingBuffer::new(4);
+        buf.append(b"abc");
+        buf.append(b"123456");
+
+        assert_eq!(buf.as_str_lossy(), "3456");
+        assert_eq!(buf.bytes_discarded(), 5);
+    }
+
+    #[test]
+    fn search_from_reports_buffer_positions() {
+        let mut buf = RingBuffer::new(100);
+        buf.append(b"ok ok done");
+        let m = buf.search_from(1, &Pattern::literal("ok")).unwrap();
+        assert_eq!((m.start, m.end), (3, 5));
+        assert!(buf.search_from(4, &Pattern::literal("ok")).is_none());
+    }
+
+    #[test]
+    fn search_from_aligns_to_char_boundary() {
+        let mut buf = RingBuffer::new(100);
+        buf.append("précis".as_bytes());
+        // Offset 3 is inside 'é'
+        let m = buf.search_from(3, &Pattern::literal("écis")).unwrap();
+        assert_eq!(m.start, 2);
+    }
+
+    #[test]
+    fn scan_cursor_follows_consumed_and_discarded_data() {
+        let mut buf = RingBuffer::new(10);
+        buf.append(b"12345678");
+        buf.mark_scanned();
+        assert_eq!(buf.last_scanned(), 8);
+
+        buf.consume(3);
+        assert_eq!(buf.last_scanned(), 5);
+
+        // Overflow discards 3 bytes from the front
+        buf.append(b"abcdefgh");
+        assert_eq!(buf.last_scanned(), 2);
+
+        buf.clear();
+        assert_eq!(buf.last_scanned(), 0);
+    }
+
     #[test]
     fn find_pattern() {
         let mut buf = RingBuffer::new(100);
@@ -3,53 +3,118 @@
 //! This module provides a cache for compiled regular expressions,
 //! avoiding the overhead of recompiling patterns on each use.
 
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
 
 /// Default maximum cache size.
-pub const DEFAULT_CACHE_SIZE: usize = 100;
+pub const DEFAULT_CACHE_SIZE: usize = 1024;
 
 /// A cache for compiled regular expressions.
 ///
-/// The cache uses LRU (Least Recently Used) eviction when full.
+/// The cache uses LRU (Least Recently Used) eviction when full. Every hit
+/// refreshes an entry's recency, so patterns in regular use stay cached
+/// while one-off patterns built from dynamic input are evicted.
 pub struct RegexCache {
     cache: RwLock<LruCache>,
-    max_size: usize,
+    max_size: AtomicUsize,
+    /// Logical clock used to stamp entries on access.
+    clock: AtomicU64,
     /// Total cache hits (for statistics).
     total_hits: AtomicUsize,
     /// Total cache misses (for statistics).
     total_misses: AtomicUsize,
+    /// Total entries evicted to stay within the capacity (for statistics).
+    total_evictions: AtomicUsize,
 }
 
 struct LruCache {
-    entries: HashMap<String, CacheEntry>,
-    order: Vec<String>,
+    entries: HashMap<Arc<str>, CacheEntry>,
+    /// Accesses in clock order. A record whose stamp is older than its
+    /// entry's `last_used` was superseded by a later access and is skipped.
+    order: Mutex<VecDeque<(u64, Arc<str>)>>,
+}
+
+impl LruCache {
+    /// Stamp `entry` as used now and record the access.
+    ///
+    /// Hits happen under the read lock, so the record is appended under
+    /// `order`'s own lock, which also keeps the queue in stamp order.
+    fn touch(&self, key: &Arc<str>, entry: &CacheEntry, clock: &AtomicU64) {
+        let mut order = self
+            .order
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let stamp = clock.fetch_add(1, Ordering::Relaxed) + 1;
+        entry.last_used.store(stamp, Ordering::Relaxed);
+        order.push_back((stamp, Arc::clone(key)));
+
+        // Drop superseded records once they outnumber the live ones
+        if order.len() > 2 * self.entries.len() + 16 {
+            order.retain(|(stamp, key)| Self::is_current(&self.entries, key, *stamp));
+        }
+    }
+
+    /// Whether `stamp` is the latest access to `key`.
+    fn is_current(entries: &HashMap<Arc<str>, CacheEntry>, key: &str, stamp: u64) -> bool {
+        entries
+            .get(key)
+            .is_some_and(|entry| entry.last_used.load(Ordering::Relaxed) == stamp)
+    }
+
+    /// Remove the least recently used entry, returning whether one existed.
+    fn evict_oldest(&mut self) -> bool {
+        let order = self
+            .order
+            .get_mut()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        while let Some((stamp, key)) = order.pop_front() {
+            if Self::is_current(&self.entries, &key, stamp) {
+                self.entries.remove(&key);
+                return true;
+            }
+        }
+        false
+    }
 }
 
 struct CacheEntry {
     regex: Arc<Regex>,
     /// Number of times this pattern has been accessed.
     hits: AtomicUsize,
+    /// Clock value at the most recent access.
+    last_used: AtomicU64,
 }
 
 impl RegexCache {
     /// Create a new regex cache with the specified maximum size.
+    ///
+    /// A maximum size of zero disables caching: every lookup compiles.
     #[must_use]
     pub fn new(max_size: usize) -> Self {
         Self {
             cache: RwLock::new(LruCache {
                 entries: HashMap::with_capacity(max_size),
-                order: Vec::with_capacity(max_size),
+                order: Mutex::new(VecDeque::with_capacity(max_size)),
             }),
-            max_size,
+            max_size: AtomicUsize::new(max_size),
+            clock: AtomicU64::new(0),
             total_hits: AtomicUsize::new(0),
             total_misses: AtomicUsize::new(0),
+            total_evictions: AtomicUsize::new(0),
         }
     }
 
+    /// Create a new regex cache holding at most `capacity` patterns.
+    ///
+    /// This is equivalent to [`RegexCache::new`].
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::new(capacity)
+    }
+
     /// Create a new regex cache with default size.
     #[must_use]
     pub fn with_default_size() -> Self {
@@ -64,6 +129,32 @@ impl RegexCache {
     ///
     /// Returns an error if the pattern is invalid.
     pub fn get_or_compile(&self, pattern: &str) -> Result<Arc<Regex>, regex::Error> {
+        self.get_or_insert(pattern, || Regex::new(pattern))
+    }
+
+    /// Get or compile a regex pattern that ignores case.
+    ///
+    /// Case-insensitive compilations are cached separately from
+    /// case-sensitive ones, under the key `(?i)` followed by `pattern`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is invalid.
+    pub fn get_or_compile_case_insensitive(
+        &self,
+        pattern: &str,
+    ) -> Result<Arc<Regex>, regex::Error> {
+        self.get_or_insert(&format!("(?i){pattern}"), || {
+            RegexBuilder::new(pattern).case_insensitive(true).build()
+        })
+    }
+
+    /// Look up `pattern`, compiling and caching the regex on a miss.
+    fn get_or_insert(
+        &self,
+        pattern: &str,
+        compile: impl FnOnce() -> Result<Regex, regex::Error>,
+    ) -> Result<Arc<Regex>, regex::Error> {
         // Try read path first
         // Note: We recover from lock poisoning since the cache is just an optimization
         {
@@ -71,11 +162,10 @@ impl RegexCache {
                 .cache
                 .read()
                 .unwrap_or_else(std::sync::PoisonError::into_inner);
-            if let Some(entry) = cache.entries.get(pattern) {
+            if let Some(regex) = self.hit(&cache, pattern) {
                 // Track cache hit
-                entry.hits.fetch_add(1, Ordering::Relaxed);
                 self.total_hits.fetch_add(1, Ordering::Relaxed);
-                return Ok(Arc::clone(&entry.regex));
+                return Ok(regex);
             }
         }
 
@@ -83,8 +173,7 @@ impl RegexCache {
         self.total_misses.fetch_add(1, Ordering::Relaxed);
 
         // Compile the regex
-        let regex = Regex::new(pattern)?;
-        let regex = Arc::new(regex);
+        let regex = Arc::new(compile()?);
 
         // Update cache
         {
@@ -94,34 +183,43 @@ impl RegexCache {
                 .unwrap_or_else(std::sync::PoisonError::into_inner);
 
             // Double-check after acquiring write lock (another thread may have inserted)
-            if let Some(entry) = cache.entries.get(pattern) {
-                // Count as hit since we're returning a cached entry
-                entry.hits.fetch_add(1, Ordering::Relaxed);
-                return Ok(Arc::clone(&entry.regex));
+            // Count as hit since we're returning a cached entry
+            if let Some(regex) = self.hit(&cache, pattern) {
+                return Ok(regex);
+            }
+
+            let max_size = self.max_size();
+            if max_size == 0 {
+                return Ok(regex);
             }
 
             // Evict if necessary
-            if cache.entries.len() >= self.max_size
-                && let Some(oldest) = cache.order.first().cloned()
-            {
-                cache.entries.remove(&oldest);
-                cache.order.remove(0);
+            while cache.entries.len() >= max_size && cache.evict_oldest() {
+                self.total_evictions.fetch_add(1, Ordering::Relaxed);
             }
 
             // Insert new entry
-            cache.entries.insert(
-                pattern.to_string(),
-                CacheEntry {
-                    regex: Arc::clone(&regex),
-                    hits: AtomicUsize::new(1), // First access
-                },
-            );
-            cache.order.push(pattern.to_string());
+            let key: Arc<str> = Arc::from(pattern);
+            let entry = CacheEntry {
+                regex: Arc::clone(&regex),
+                hits: AtomicUsize::new(1), // First access
+                last_used: AtomicU64::new(0),
+            };
+            cache.entries.insert(Arc::clone(&key), entry);
+            cache.touch(&key, &cache.entries[&key], &self.clock);
         }
 
         Ok(regex)
     }
 
+    /// Return the cached regex for `pattern`, recording the access.
+    fn hit(&self, cache: &LruCache, pattern: &str) -> Option<Arc<Regex>> {
+        let (key, entry) = cache.entries.get_key_value(pattern)?;
+        entry.hits.fetch_add(1, Ordering::Relaxed);
+        cache.touch(key, entry, &self.clock);
+        Some(Arc::clone(&entry.regex))
+    }
+
     /// Check if a pattern is cached.
     #[must_use]
     pub fn contains(&self, pattern: &str) -> bool {
@@ -155,13 +253,32 @@ impl RegexCache {
             .write()
             .unwrap_or_else(std::sync::PoisonError::into_inner);
         cache.entries.clear();
-        cache.order.clear();
+        cache
+            .order
+            .get_mut()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clear();
     }
 
     /// Get the maximum cache size.
     #[must_use]
-    pub const fn max_size(&self) -> usize {
-        self.max_size
+    pub fn max_size(&self) -> usize {
+        self.max_size.load(Ordering::Relaxed)
+    }
+
+    /// Change the maximum cache size.
+    ///
+    /// Shrinking the cache evicts least recently used patterns until it
+    /// fits. This is how the capacity of [`GLOBAL_CACHE`] is configured.
+    pub fn set_max_size(&self, max_size: usize) {
+        let mut cache = self
+            .cache
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        self.max_size.store(max_size, Ordering::Relaxed);
+        while cache.entries.len() > max_size && cache.evict_oldest() {
+            self.total_evictions.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
     /// Get cache statistics.
@@ -174,9 +291,10 @@ impl RegexCache {
 
         CacheStats {
             size: cache.entries.len(),
-            max_size: self.max_size,
+            max_size: self.max_size(),
             total_hits: self.total_hits.load(Ordering::Relaxed),
             total_misses: self.total_misses.load(Ordering::Relaxed),
+            total_evictions: self.total_evictions.load(Ordering::Relaxed),
         }
     }
 
@@ -192,6 +310,12 @@ impl RegexCache {
         self.total_misses.load(Ordering::Relaxed)
     }
 
+    /// Get the total number of evicted patterns.
+    #[must_use]
+    pub fn total_evictions(&self) -> usize {
+        self.total_evictions.load(Ordering::Relaxed)
+    }
+
     /// Get the cache hit rate as a ratio (0.0 to 1.0).
     ///
     /// Returns 1.0 if no accesses have been made.
@@ -219,6 +343,8 @@ pub struct CacheStats {
     pub total_hits: usize,
     /// Total cache misses.
     pub total_misses: usize,
+    /// Total patterns evicted to stay within the maximum size.
+    pub total_evictions: usize,
 }
 
 impl CacheStats {
@@ -259,6 +385,21 @@ pub fn get_regex(pattern: &str) -> Result<Arc<Regex>, regex::Error> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn cache_keeps_case_insensitive_apart() {
+        let cache = RegexCache::new(10);
+        let sensitive = cache.get_or_compile("login").unwrap();
+        let insensitive = cache.get_or_compile_case_insensitive("login").unwrap();
+
+        assert!(!sensitive.is_match("LOGIN"));
+        assert!(insensitive.is_match("LOGIN"));
+        assert_eq!(cache.len(), 2);
+
+        cache.get_or_compile_case_insensitive("login").unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.total_hits(), 1);
+    }
+
     #[test]
     fn cache_basic() {
         let cache = RegexCache::new(10);
@@ -286,6 +427,82 @@ mod tests {
         assert!(cache.contains(r"c+"));
     }
 
+    #[test]
+    fn cache_evicts_least_recently_used() {
+        let cache = RegexCache::with_capacity(3);
+        cache.get_or_compile(r"a+").unwrap();
+        cache.get_or_compile(r"b+").unwrap();
+        cache.get_or_compile(r"c+").unwrap();
+
+        // Touching "a+" makes "b+" the least recently used
+        cache.get_or_compile(r"a+").unwrap();
+        cache.get_or_compile(r"d+").unwrap();
+        assert!(cache.contains(r"a+"));
+        assert!(!cache.contains(r"b+"));
+
+        cache.get_or_compile(r"e+").unwrap();
+        assert!(!cache.contains(r"c+"));
+        assert!(cache.contains(r"a+"));
+        assert!(cache.contains(r"d+"));
+        assert!(cache.contains(r"e+"));
+
+        let stats = cache.stats();
+        assert_eq!(stats.size, 3);
+        assert_eq!(stats.max_size, 3);
+        assert_eq!(stats.total_hits, 1);
+        assert_eq!(stats.total_misses, 5);
+        assert_eq!(stats.total_evictions, 2);
+    }
+
+    #[test]
+    fn cache_access_log_stays_bounded() {
+        let cache = RegexCache::with_capacity(4);
+        for pattern in [r"a+", r"b+", r"c+", r"d+"] {
+            cache.get_or_compile(pattern).unwrap();
+        }
+        for _ in 0..1000 {
+            cache.get_or_compile(r"b+").unwrap();
+            cache.get_or_compile(r"a+").unwrap();
+        }
+        {
+            let inner = cache.cache.read().unwrap();
+            assert!(inner.order.lock().unwrap().len() <= 2 * 4 + 16);
+        }
+
+        // "c+" and "d+" were never touched again, so they go first
+        cache.get_or_compile(r"e+").unwrap();
+        cache.get_or_compile(r"f+").unwrap();
+        assert!(!cache.contains(r"c+"));
+        assert!(!cache.contains(r"d+"));
+        assert!(cache.contains(r"a+"));
+        assert!(cache.contains(r"b+"));
+    }
+
+    #[test]
+    fn cache_shrinks_to_new_max_size() {
+        let cache = RegexCache::with_capacity(4);
+        for pattern in [r"a+", r"b+", r"c+", r"d+"] {
+            cache.get_or_compile(pattern).unwrap();
+        }
+        cache.get_or_compile(r"a+").unwrap();
+
+        cache.set_max_size(2);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(r"a+"));
+        assert!(cache.contains(r"d+"));
+        assert_eq!(cache.total_evictions(), 2);
+
+        cache.set_max_size(0);
+        assert!(cache.is_empty());
+        cache.get_or_compile(r"a+").unwrap();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn default_cache_size() {
+        assert_eq!(RegexCache::default().max_size(), DEFAULT_CACHE_SIZE);
+    }
+
     #[test]
     fn cache_invalid_pattern() {
         let cache = RegexCache::new(10);
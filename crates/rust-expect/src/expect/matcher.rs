@@ -9,7 +9,7 @@ use std::time::{Duration, Instant};
 use super::buffer::RingBuffer;
 use super::cache::RegexCache;
 use super::pattern::{Pattern, PatternSet};
-use crate::types::Match;
+use crate::types::{CaptureGroups, Match};
 
 /// The pattern matching engine.
 pub struct Matcher {
@@ -81,6 +81,22 @@ impl Matcher {
         self.buffer.clear();
     }
 
+    /// Remove and return up to `max` bytes from the front of the buffer.
+    pub fn take(&mut self, max: usize) -> Vec<u8> {
+        self.buffer.consume(max)
+    }
+
+    /// Remove and return the first complete line from the buffer.
+    ///
+    /// The line is returned without its `ending`. Returns `None` if the
+    /// buffer does not yet hold a complete line.
+    pub fn take_line(&mut self, ending: &[u8]) -> Option<String> {
+        let pos = self.buffer.find(ending)?;
+        let line = self.buffer.consume(pos);
+        self.buffer.consume(ending.len());
+        Some(String::from_utf8_lossy(&line).into_owned())
+    }
+
     /// Try to match a single pattern against the buffer.
     #[must_use]
     pub fn try_match(&mut self, pattern: &Pattern) -> Option<MatchResult> {
@@ -92,42 +108,48 @@ impl Matcher {
                 start: self.adjust_position(pos),
                 end: self.adjust_position(pos + s.len()),
                 captures: Vec::new(),
+                groups: CaptureGroups::default(),
             }),
-            Pattern::Regex(compiled) => compiled.find(&text).map(|m| {
-                let captures = compiled.captures(&text);
-                MatchResult {
-                    pattern_index: 0,
-                    start: self.adjust_position(m.start()),
-                    end: self.adjust_position(m.end()),
-                    captures,
-                }
+            Pattern::LiteralCi(_)
+            | Pattern::Regex(_)
+            | Pattern::Glob(_)
+            | Pattern::Fuzzy { .. }
+            | Pattern::Anchored { .. } => pattern.matches(&text).map(|m| MatchResult {
+                pattern_index: 0,
+                start: self.adjust_position(m.start),
+                end: self.adjust_position(m.end),
+                captures: m.captures,
+                groups: m.groups,
             }),
-            Pattern::Glob(glob) => {
-                self.try_glob_match(glob, &text)
-                    .map(|(start, end)| MatchResult {
-                        pattern_index: 0,
-                        start: self.adjust_position(start),
-                        end: self.adjust_position(end),
-                        captures: Vec::new(),
-                    })
-            }
             Pattern::Eof | Pattern::Timeout(_) | Pattern::Bytes(_) => None,
         }
     }
 
     /// Try to match any pattern from a set against the buffer.
+    ///
+    /// When every pattern has a bounded length (see
+    /// [`PatternSet::max_match_len`]), only the bytes appended since the
+    /// last unsuccessful call are searched, plus enough earlier bytes to
+    /// catch a match spanning the boundary. The search starts at the
+    /// beginning of a line, so line anchors and word boundaries see the
+    /// same text before a match as a full search would. Call
+    /// [`reset_scan`](Self::reset_scan) before matching a different set.
     #[must_use]
     pub fn try_match_any(&mut self, patterns: &PatternSet) -> Option<MatchResult> {
-        let text = self.get_search_text();
+        let (offset, text) = match self.scan_start(patterns) {
+            Some(offset) => self.buffer.text_from(offset),
+            None => (self.adjust_position(0), self.get_search_text()),
+        };
         let mut best: Option<MatchResult> = None;
 
         for (idx, named) in patterns.iter().enumerate() {
             if let Some(pm) = named.pattern.matches(&text) {
                 let result = MatchResult {
                     pattern_index: idx,
-                    start: self.adjust_position(pm.start),
-                    end: self.adjust_position(pm.end),
+                    start: offset + pm.start,
+                    end: offset + pm.end,
                     captures: pm.captures,
+                    groups: pm.groups,
                 };
 
                 match &best {
@@ -138,9 +160,31 @@ impl Matcher {
             }
         }
 
+        if best.is_none() {
+            self.buffer.mark_scanned();
+        }
         best
     }
 
+    /// Get where an incremental search for `patterns` can start, or `None`
+    /// if the whole buffer must be searched.
+    fn scan_start(&mut self, patterns: &PatternSet) -> Option<usize> {
+        if self.search_window.is_some() {
+            return None;
+        }
+        let max_len = patterns.max_match_len()?;
+        let offset = self.buffer.last_scanned().saturating_sub(max_len);
+        Some(self.buffer.line_start(offset))
+    }
+
+    /// Forget which part of the buffer has already been searched.
+    ///
+    /// The next [`try_match_any`](Self::try_match_any) searches the whole
+    /// buffer again.
+    pub const fn reset_scan(&mut self) {
+        self.buffer.reset_scan();
+    }
+
     /// Consume matched content from the buffer and return a Match.
     pub fn consume_match(&mut self, result: &MatchResult) -> Match {
         let before = self.buffer.consume_before(result.start);
@@ -150,6 +194,7 @@ impl Matcher {
 
         Match::new(result.pattern_index, matched, before, after)
             .with_captures(result.captures.clone())
+            .with_groups(result.groups.clone())
     }
 
     /// Get the timeout for a pattern set.
@@ -186,37 +231,6 @@ impl Matcher {
             None => pos,
         }
     }
-
-    /// Simple glob matching.
-    #[allow(clippy::unused_self)]
-    fn try_glob_match(&self, pattern: &str, text: &str) -> Option<(usize, usize)> {
-        // Convert glob to a simple search
-        // For now, just handle * as prefix/suffix
-        if let Some(rest) = pattern.strip_prefix('*') {
-            if let Some(inner) = rest.strip_suffix('*') {
-                // Pattern like *inner*
-                text.find(inner).map(|pos| (pos, pos + inner.len()))
-            } else {
-                // Pattern like *suffix
-                let suffix = rest;
-                if text.ends_with(suffix) {
-                    let start = text.len() - suffix.len();
-                    Some((start, text.len()))
-                } else {
-                    None
-                }
-            }
-        } else if let Some(prefix) = pattern.strip_suffix('*') {
-            // Pattern like prefix*
-            if text.starts_with(prefix) {
-                Some((0, prefix.len()))
-            } else {
-                None
-            }
-        } else {
-            text.find(pattern).map(|pos| (pos, pos + pattern.len()))
-        }
-    }
 }
 
 impl Default for Matcher {
@@ -236,6 +250,8 @@ pub struct MatchResult {
     pub end: usize,
     /// Capture groups.
     pub captures: Vec<String>,
+    /// Numbered and named capture groups.
+    pub groups: CaptureGroups,
 }
 
 impl MatchResult {
@@ -316,6 +332,77 @@ impl ExpectState {
 mod tests {
     use super::*;
 
+    #[test]
+    fn incremental_match_spans_chunk_boundary() {
+        let mut matcher = Matcher::new(1024);
+        let patterns = PatternSet::from_patterns(vec![Pattern::literal("password:")]);
+
+        matcher.append(b"Enter pass");
+        assert!(matcher.try_match_any(&patterns).is_none());
+        assert_eq!(matcher.buffer().last_scanned(), 10);
+
+        matcher.append(b"word: ");
+        let result = matcher.try_match_any(&patterns).unwrap();
+        assert_eq!((result.start, result.end), (6, 15));
+    }
+
+    #[test]
+    fn bounded_regex_scans_only_new_lines() {
+        let mut matcher = Matcher::new(1 << 20);
+        let patterns = PatternSet::from_patterns(vec![
+            Pattern::regex(r"(?:password|passphrase):").unwrap(),
+            Pattern::at_line_start(Pattern::glob("Login?")),
+        ]);
+
+        let noise = "x".repeat(99) + "\n";
+        matcher.append(noise.repeat(1000).as_bytes());
+        assert!(matcher.try_match_any(&patterns).is_none());
+        // Only the last line is searched again
+        assert_eq!(matcher.scan_start(&patterns), Some(99_900));
+
+        matcher.append(b"Enter pass");
+        assert!(matcher.try_match_any(&patterns).is_none());
+        matcher.append(b"word: ");
+        let result = matcher.try_match_any(&patterns).unwrap();
+        assert_eq!((result.start, result.end), (100_006, 100_015));
+
+        // Unbounded patterns still search everything
+        let unbounded = PatternSet::from_patterns(vec![Pattern::regex(r"\d+").unwrap()]);
+        assert_eq!(matcher.scan_start(&unbounded), None);
+    }
+
+    #[test]
+    fn incremental_scan_keeps_text_before_match() {
+        let mut matcher = Matcher::new(1024);
+        let word = PatternSet::from_patterns(vec![Pattern::regex(r"\bbar\b").unwrap()]);
+        matcher.append(b"foobar");
+        assert!(matcher.try_match_any(&word).is_none());
+        // A scan starting mid-word would see a boundary before "bar"
+        matcher.append(b" baz");
+        assert!(matcher.try_match_any(&word).is_none());
+
+        let prompt = PatternSet::from_patterns(vec![Pattern::at_line_start("$ ")]);
+        matcher.clear();
+        matcher.append(b"cost: ");
+        assert!(matcher.try_match_any(&prompt).is_none());
+        matcher.append(b"$ 5\n$ ");
+        let result = matcher.try_match_any(&prompt).unwrap();
+        assert_eq!(result.start, 10);
+    }
+
+    #[test]
+    fn reset_scan_rescans_for_new_patterns() {
+        let mut matcher = Matcher::new(1024);
+        matcher.append(b"login: ");
+        let password = PatternSet::from_patterns(vec![Pattern::literal("password:")]);
+        assert!(matcher.try_match_any(&password).is_none());
+
+        // The cursor only applies to the set that was searched
+        let login = PatternSet::from_patterns(vec![Pattern::literal("login:")]);
+        matcher.reset_scan();
+        assert!(matcher.try_match_any(&login).is_some());
+    }
+
     #[test]
     fn matcher_literal() {
         let mut matcher = Matcher::new(1024);
@@ -358,6 +445,17 @@ mod tests {
         assert_eq!(m.after, "|suffix");
     }
 
+    #[test]
+    fn matcher_take_line() {
+        let mut matcher = Matcher::new(1024);
+        matcher.append(b"one\r\ntwo\r\npart");
+
+        assert_eq!(matcher.take_line(b"\r\n").as_deref(), Some("one"));
+        assert_eq!(matcher.take_line(b"\r\n").as_deref(), Some("two"));
+        assert_eq!(matcher.take_line(b"\r\n"), None);
+        assert_eq!(matcher.buffer_str(), "part");
+    }
+
     #[test]
     fn matcher_pattern_set() {
         let mut matcher = Matcher::new(1024);
@@ -1,7 +1,8 @@
 //! Pattern types for expect operations.
 //!
 //! This module defines the pattern types that can be used with expect operations,
-//! including literal strings, regular expressions, globs, EOF, and timeout.
+//! including literal strings, regular expressions, globs, approximate text,
+//! EOF, and timeout.
 //!
 //! # Examples
 //!
@@ -21,6 +22,15 @@
 //! let log = Pattern::glob("Error:*");
 //! assert!(log.matches("Error: connection failed").is_some());
 //!
+//! // Fuzzy pattern - tolerates small typos
+//! let login = Pattern::fuzzy("password:", 1);
+//! assert!(login.matches("pasword:").is_some());
+//!
+//! // Anchored pattern - ignores hits in the middle of a line
+//! let prompt = Pattern::at_line_start("$ ");
+//! assert!(prompt.matches("cost: $ 5").is_none());
+//! assert!(prompt.matches("cost: $ 5\n$ ").is_some());
+//!
 //! // Timeout pattern - used with expect_any for timeouts
 //! let timeout = Pattern::timeout(Duration::from_secs(5));
 //! assert!(timeout.is_timeout());
@@ -30,10 +40,16 @@
 //! assert!(eof.is_eof());
 //! ```
 
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
 use regex::Regex;
+use regex_syntax::hir::Look;
+
+use super::cache::GLOBAL_CACHE;
+use crate::types::CaptureGroups;
 
 /// A pattern that can be matched against terminal output.
 #[derive(Clone)]
@@ -41,11 +57,30 @@ pub enum Pattern {
     /// Match an exact string.
     Literal(String),
 
+    /// Match an exact string, ignoring ASCII case.
+    LiteralCi(String),
+
     /// Match a regular expression.
     Regex(CompiledRegex),
 
     /// Match a glob pattern.
-    Glob(String),
+    Glob(CompiledGlob),
+
+    /// Match text approximately, allowing up to `max_distance` edits.
+    Fuzzy {
+        /// The text to look for.
+        text: String,
+        /// Maximum Levenshtein distance between `text` and the match.
+        max_distance: usize,
+    },
+
+    /// Match another pattern only at certain positions.
+    Anchored {
+        /// The pattern to match.
+        pattern: Box<Self>,
+        /// Where a match may occur.
+        anchor: MatchAnchor,
+    },
 
     /// Match end of file (process terminated).
     Eof,
@@ -64,6 +99,24 @@ impl Pattern {
         Self::Literal(s.into())
     }
 
+    /// Create a literal pattern that ignores ASCII case.
+    ///
+    /// Only ASCII letters are folded; other characters must match exactly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_expect::Pattern;
+    ///
+    /// let prompt = Pattern::literal_ci("password:");
+    /// assert!(prompt.matches("Password: ").is_some());
+    /// assert!(prompt.matches("PASSWORD: ").is_some());
+    /// ```
+    #[must_use]
+    pub fn literal_ci(s: impl Into<String>) -> Self {
+        Self::LiteralCi(s.into())
+    }
+
     /// Create a regex pattern.
     ///
     /// # Errors
@@ -74,10 +127,147 @@ impl Pattern {
         Ok(Self::Regex(CompiledRegex::new(pattern.to_string(), regex)))
     }
 
+    /// Make this pattern ignore case.
+    ///
+    /// Regexes are recompiled with the `i` flag, literals become
+    /// [`LiteralCi`](Self::LiteralCi), and anchored patterns apply this to
+    /// the pattern they wrap. Other patterns are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_expect::Pattern;
+    ///
+    /// let prompt = Pattern::regex(r"login:\s*$").unwrap().case_insensitive();
+    /// assert!(prompt.matches("LOGIN: ").is_some());
+    /// ```
+    #[must_use]
+    pub fn case_insensitive(self) -> Self {
+        match self {
+            Self::Literal(s) => Self::LiteralCi(s),
+            Self::Regex(r) => r.case_insensitive().map_or(Self::Regex(r), Self::Regex),
+            Self::Anchored { pattern, anchor } => Self::Anchored {
+                pattern: Box::new(pattern.case_insensitive()),
+                anchor,
+            },
+            other => other,
+        }
+    }
+
     /// Create a glob pattern.
+    ///
+    /// Globs use shell-style wildcards. Unlike a shell glob, the pattern is
+    /// not anchored to the whole text: it matches at the first place in the
+    /// output where it fits, and a trailing `*` runs to the end of that
+    /// line.
+    ///
+    /// - `*` matches any run of characters within a line
+    /// - `?` matches any single character except a newline
+    /// - `[abc]`, `[a-z]` match one character from the set, and `[!abc]` or
+    ///   `[^abc]` one character outside it
+    /// - `\` makes the next character literal, so `\*` matches a `*`
+    ///
+    /// A `]` right after the opening `[` (or `[!`) is part of the set, and a
+    /// `[` without a closing `]` is literal. Every other character matches
+    /// itself. The glob is translated to a regex here, once, and the regex
+    /// is kept in [`GLOBAL_CACHE`]. A glob whose translation is not a valid
+    /// regex, such as one with the range `[z-a]`, never matches; use
+    /// [`try_glob`](Self::try_glob) to reject it instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_expect::Pattern;
+    ///
+    /// let log = Pattern::glob("log[0-9]*");
+    /// assert!(log.matches("wrote log7.txt").is_some());
+    /// assert!(log.matches("wrote logs.txt").is_none());
+    /// ```
     #[must_use]
     pub fn glob(pattern: impl Into<String>) -> Self {
-        Self::Glob(pattern.into())
+        Self::Glob(CompiledGlob::new(pattern.into()))
+    }
+
+    /// Create a glob pattern, rejecting a glob that cannot match.
+    ///
+    /// Same as [`glob`](Self::glob), but fails instead of building a
+    /// pattern that never matches.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the glob translates to an invalid regex, such as
+    /// one with the range `[z-a]`.
+    pub fn try_glob(pattern: impl Into<String>) -> Result<Self, regex::Error> {
+        CompiledGlob::try_new(pattern.into()).map(Self::Glob)
+    }
+
+    /// Create a fuzzy pattern.
+    ///
+    /// The pattern matches any part of the output within `max_distance`
+    /// single-character insertions, deletions or substitutions of `text`.
+    /// When several windows qualify, the one with the smallest distance is
+    /// returned, preferring the earliest and then the longest on ties.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_expect::Pattern;
+    ///
+    /// let prompt = Pattern::fuzzy("password:", 1);
+    /// let m = prompt.matches("Enter pasword: ").unwrap();
+    /// assert_eq!(&"Enter pasword: "[m.start..m.end], "pasword:");
+    /// ```
+    #[must_use]
+    pub fn fuzzy(text: impl Into<String>, max_distance: usize) -> Self {
+        Self::Fuzzy {
+            text: text.into(),
+            max_distance,
+        }
+    }
+
+    /// Create a pattern that only matches at the start of a line.
+    ///
+    /// A hit counts only if it begins at the start of the buffer or right
+    /// after a `\n`, so a prompt character echoed inside earlier output is
+    /// not mistaken for the prompt itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_expect::Pattern;
+    ///
+    /// let prompt = Pattern::at_line_start("# ");
+    /// assert!(prompt.matches("see # comment").is_none());
+    /// assert_eq!(prompt.matches("see # comment\n# ").unwrap().start, 14);
+    /// ```
+    #[must_use]
+    pub fn at_line_start(pattern: impl Into<Self>) -> Self {
+        Self::Anchored {
+            pattern: Box::new(pattern.into()),
+            anchor: MatchAnchor::LineStart,
+        }
+    }
+
+    /// Create a pattern that only matches within the last line of output.
+    ///
+    /// Everything up to and including the final `\n` is ignored, which suits
+    /// prompts that are printed without a trailing newline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_expect::Pattern;
+    ///
+    /// let prompt = Pattern::on_last_line("$ ");
+    /// assert!(prompt.matches("price: $ 5\n").is_none());
+    /// assert!(prompt.matches("price: $ 5\nuser@host:~$ ").is_some());
+    /// ```
+    #[must_use]
+    pub fn on_last_line(pattern: impl Into<Self>) -> Self {
+        Self::Anchored {
+            pattern: Box::new(pattern.into()),
+            anchor: MatchAnchor::LastLine,
+        }
     }
 
     /// Create an EOF pattern.
@@ -102,9 +292,11 @@ impl Pattern {
     #[must_use]
     pub fn as_str(&self) -> &str {
         match self {
-            Self::Literal(s) => s,
+            Self::Literal(s) | Self::LiteralCi(s) => s,
             Self::Regex(r) => r.pattern(),
-            Self::Glob(s) => s,
+            Self::Glob(g) => g.pattern(),
+            Self::Fuzzy { text: s, .. } => s,
+            Self::Anchored { pattern, .. } => pattern.as_str(),
             Self::Eof => "<EOF>",
             Self::Timeout(_) => "<TIMEOUT>",
             Self::Bytes(_) => "<BYTES>",
@@ -117,25 +309,54 @@ impl Pattern {
     #[must_use]
     pub fn matches(&self, text: &str) -> Option<PatternMatch> {
         match self {
-            Self::Literal(s) => text.find(s).map(|pos| PatternMatch {
-                start: pos,
-                end: pos + s.len(),
-                captures: Vec::new(),
-            }),
-            Self::Regex(r) => r.find(text).map(|m| PatternMatch {
-                start: m.start(),
-                end: m.end(),
-                captures: r.captures(text),
-            }),
-            Self::Glob(pattern) => glob_match(pattern, text).map(|pos| PatternMatch {
-                start: pos,
-                end: text.len(),
-                captures: Vec::new(),
-            }),
+            Self::Literal(s) => text
+                .find(s)
+                .map(|pos| PatternMatch::new(pos, pos + s.len())),
+            Self::LiteralCi(s) => {
+                find_ignore_ascii_case(text, s).map(|pos| PatternMatch::new(pos, pos + s.len()))
+            }
+            Self::Regex(r) => r.find_match(text),
+            Self::Glob(g) => g.find(text).map(|m| PatternMatch::new(m.start(), m.end())),
+            Self::Fuzzy {
+                text: needle,
+                max_distance,
+            } => fuzzy_match(needle, text, *max_distance)
+                .map(|(start, end)| PatternMatch::new(start, end)),
+            Self::Anchored { pattern, anchor } => anchor.find(pattern, text),
             Self::Eof | Self::Timeout(_) | Self::Bytes(_) => None,
         }
     }
 
+    /// Get the longest text, in bytes, this pattern can match.
+    ///
+    /// Returns `None` when the length is unbounded, as with a regex using
+    /// `+` or a glob using `*`, or when a regex uses `\A` or `^` outside
+    /// multi-line mode, which tie the match to the start of the whole text.
+    /// Anchored patterns have the bound of the pattern they wrap.
+    #[must_use]
+    pub fn max_match_len(&self) -> Option<usize> {
+        match self {
+            Self::Literal(s) | Self::LiteralCi(s) => Some(s.len()),
+            // Each insertion adds at most one UTF-8 character
+            Self::Fuzzy { text, max_distance } => Some(text.len() + max_distance * 4),
+            Self::Regex(r) => r.max_match_len(),
+            Self::Glob(g) => g.max_len,
+            Self::Anchored { pattern, .. } => pattern.max_match_len(),
+            Self::Eof | Self::Timeout(_) | Self::Bytes(_) => Some(0),
+        }
+    }
+
+    /// Whether [`matches`](Self::matches) returns the leftmost match.
+    ///
+    /// A fuzzy pattern returns its closest window instead, and an anchored
+    /// one only considers part of the text.
+    const fn is_leftmost(&self) -> bool {
+        matches!(
+            self,
+            Self::Literal(_) | Self::LiteralCi(_) | Self::Regex(_) | Self::Glob(_)
+        )
+    }
+
     /// Check if this is a timeout pattern.
     #[must_use]
     pub const fn is_timeout(&self) -> bool {
@@ -185,10 +406,10 @@ impl Pattern {
     /// Create a pattern that matches any common prompt character.
     ///
     /// A simpler alternative to `shell_prompt()` that uses glob matching.
-    /// Less precise but faster for simple cases.
+    /// Less precise: it matches any line containing a `$`.
     #[must_use]
     pub fn any_prompt() -> Self {
-        Self::Glob("*$*".to_string())
+        Self::glob("*$*")
     }
 
     /// Create a pattern that matches IPv4 addresses.
@@ -256,8 +477,7 @@ impl Pattern {
     /// ```
     #[must_use]
     pub fn error_indicator() -> Self {
-        Self::regex(r"(?i)\b(?:error|failed|fatal)\b")
-            .unwrap_or_else(|_| Self::Glob("*[Ee]rror*".to_string()))
+        Self::regex(r"(?i)\b(?:error|failed|fatal)\b").unwrap_or_else(|_| Self::glob("*[Ee]rror*"))
     }
 
     /// Create a pattern that matches common success indicators.
@@ -266,7 +486,7 @@ impl Pattern {
     #[must_use]
     pub fn success_indicator() -> Self {
         Self::regex(r"(?i)\b(?:success|successful|passed|complete|ok)\b")
-            .unwrap_or_else(|_| Self::Glob("*[Ss]uccess*".to_string()))
+            .unwrap_or_else(|_| Self::glob("*[Ss]uccess*"))
     }
 
     /// Create a pattern that matches common password prompts.
@@ -303,7 +523,7 @@ impl Pattern {
     #[must_use]
     pub fn confirmation_prompt() -> Self {
         Self::regex(r"\[([yYnN])/([yYnN])\]|\(([yY]es)/([nN]o)\)")
-            .unwrap_or_else(|_| Self::Glob("*[y/n]*".to_string()))
+            .unwrap_or_else(|_| Self::glob("*[y/n]*"))
     }
 
     /// Create a pattern that matches common "continue?" prompts.
@@ -312,7 +532,7 @@ impl Pattern {
     #[must_use]
     pub fn continue_prompt() -> Self {
         Self::regex(r"(?i)(?:continue\s*\?|press any key|hit enter)")
-            .unwrap_or_else(|_| Self::Glob("*continue*".to_string()))
+            .unwrap_or_else(|_| Self::glob("*continue*"))
     }
 }
 
@@ -320,8 +540,11 @@ impl fmt::Debug for Pattern {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Literal(s) => write!(f, "Literal({s:?})"),
+            Self::LiteralCi(s) => write!(f, "LiteralCi({s:?})"),
             Self::Regex(r) => write!(f, "Regex({:?})", r.pattern()),
-            Self::Glob(s) => write!(f, "Glob({s:?})"),
+            Self::Glob(g) => write!(f, "Glob({:?})", g.pattern()),
+            Self::Fuzzy { text, max_distance } => write!(f, "Fuzzy({text:?}, {max_distance})"),
+            Self::Anchored { pattern, anchor } => write!(f, "Anchored({anchor:?}, {pattern:?})"),
             Self::Eof => write!(f, "Eof"),
             Self::Timeout(d) => write!(f, "Timeout({d:?})"),
             Self::Bytes(n) => write!(f, "Bytes({n})"),
@@ -341,18 +564,102 @@ impl From<String> for Pattern {
     }
 }
 
+/// Where an anchored pattern may match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchAnchor {
+    /// The match must begin at the start of the text or right after `\n`.
+    LineStart,
+    /// The match must lie within the text after the last `\n`.
+    LastLine,
+}
+
+impl MatchAnchor {
+    /// Find the first match of `pattern` in `text` that satisfies the anchor.
+    fn find(self, pattern: &Pattern, text: &str) -> Option<PatternMatch> {
+        let offset = |m: PatternMatch, base: usize| PatternMatch {
+            start: m.start + base,
+            end: m.end + base,
+            ..m
+        };
+
+        match self {
+            Self::LineStart if pattern.is_leftmost() => {
+                let mut base = 0;
+                loop {
+                    // Nothing further on can match either
+                    let m = pattern.matches(&text[base..])?;
+                    if m.start == 0 {
+                        return Some(offset(m, base));
+                    }
+                    // The match found is also the one any line starting
+                    // before it would find, so resume at the first line
+                    // start at or after it rather than rescanning each
+                    let from = base + m.start - 1;
+                    let newline = text.as_bytes()[from..].iter().position(|&b| b == b'\n')?;
+                    base = from + newline + 1;
+                }
+            }
+            Self::LineStart => {
+                // The best match is not necessarily the leftmost, so each
+                // line start is tried on its own
+                let mut line_starts =
+                    std::iter::once(0).chain(text.match_indices('\n').map(|(i, _)| i + 1));
+                line_starts.find_map(|base| {
+                    match_at_start(pattern, &text[base..]).map(|m| offset(m, base))
+                })
+            }
+            Self::LastLine => {
+                let base = text.rfind('\n').map_or(0, |i| i + 1);
+                pattern.matches(&text[base..]).map(|m| offset(m, base))
+            }
+        }
+    }
+}
+
 /// A compiled regular expression with its source pattern.
 #[derive(Clone)]
 pub struct CompiledRegex {
     pattern: String,
     regex: Regex,
+    case_insensitive: bool,
+    /// Longest possible match, worked out on first use.
+    max_len: OnceLock<Option<usize>>,
 }
 
 impl CompiledRegex {
     /// Create a new compiled regex.
     #[must_use]
     pub const fn new(pattern: String, regex: Regex) -> Self {
-        Self { pattern, regex }
+        Self {
+            pattern,
+            regex,
+            case_insensitive: false,
+            max_len: OnceLock::new(),
+        }
+    }
+
+    /// Recompile the source pattern so that it ignores case.
+    ///
+    /// The compiled regex comes from [`GLOBAL_CACHE`], which keeps it apart
+    /// from the case-sensitive compilation of the same source.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern cannot be compiled.
+    pub fn case_insensitive(&self) -> Result<Self, regex::Error> {
+        let regex = GLOBAL_CACHE.get_or_compile_case_insensitive(&self.pattern)?;
+        Ok(Self {
+            pattern: self.pattern.clone(),
+            regex: Regex::clone(&regex),
+            case_insensitive: true,
+            max_len: OnceLock::new(),
+        })
+    }
+
+    /// Check whether the regex ignores case.
+    #[must_use]
+    pub const fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
     }
 
     /// Get the source pattern.
@@ -372,14 +679,127 @@ impl CompiledRegex {
     pub fn captures(&self, text: &str) -> Vec<String> {
         self.regex
             .captures(text)
-            .map(|caps| {
-                caps.iter()
-                    .skip(1) // Skip the full match
-                    .filter_map(|m| m.map(|m| m.as_str().to_string()))
-                    .collect()
-            })
+            .map(|caps| capture_list(&caps))
             .unwrap_or_default()
     }
+
+    /// Get numbered and named capture groups from the first match.
+    #[must_use]
+    pub fn capture_groups(&self, text: &str) -> CaptureGroups {
+        self.regex
+            .captures(text)
+            .map(|caps| self.groups(&caps))
+            .unwrap_or_default()
+    }
+
+    /// Get the longest text, in bytes, the regex can match.
+    ///
+    /// See [`Pattern::max_match_len`].
+    #[must_use]
+    pub fn max_match_len(&self) -> Option<usize> {
+        *self
+            .max_len
+            .get_or_init(|| regex_max_len(&self.pattern, self.case_insensitive))
+    }
+
+    /// Find the first match with its capture groups, running the regex
+    /// once.
+    fn find_match(&self, text: &str) -> Option<PatternMatch> {
+        // Without groups only the whole match is captured, and find is faster
+        if self.regex.captures_len() == 1 {
+            return self.regex.find(text).map(|m| PatternMatch {
+                groups: CaptureGroups::new(vec![Some(m.as_str().to_string())], HashMap::new()),
+                ..PatternMatch::new(m.start(), m.end())
+            });
+        }
+        let caps = self.regex.captures(text)?;
+        let whole = caps.get(0)?;
+        Some(PatternMatch {
+            start: whole.start(),
+            end: whole.end(),
+            captures: capture_list(&caps),
+            groups: self.groups(&caps),
+        })
+    }
+
+    /// Collect numbered and named groups from `caps`.
+    fn groups(&self, caps: &regex::Captures<'_>) -> CaptureGroups {
+        let groups = caps
+            .iter()
+            .map(|m| m.map(|m| m.as_str().to_string()))
+            .collect();
+        let names = self
+            .regex
+            .capture_names()
+            .enumerate()
+            .filter_map(|(index, name)| Some((name?.to_string(), index)))
+            .collect();
+        CaptureGroups::new(groups, names)
+    }
+}
+
+/// Collect the groups that took part in a match, skipping the full match.
+fn capture_list(caps: &regex::Captures<'_>) -> Vec<String> {
+    caps.iter()
+        .skip(1)
+        .filter_map(|m| m.map(|m| m.as_str().to_string()))
+        .collect()
+}
+
+/// A glob with the regex it was translated to.
+///
+/// Built by [`Pattern::glob`], which documents the syntax.
+#[derive(Clone)]
+pub struct CompiledGlob {
+    pattern: String,
+    regex: Option<Arc<Regex>>,
+    /// Longest possible match; `None` once the glob uses `*`.
+    max_len: Option<usize>,
+}
+
+impl CompiledGlob {
+    /// Translate and compile a glob.
+    ///
+    /// A glob that does not translate to a valid regex never matches.
+    #[must_use]
+    pub fn new(pattern: String) -> Self {
+        let source = glob_to_regex(&pattern);
+        let regex = GLOBAL_CACHE.get_or_compile(&source).ok();
+        let max_len = regex_max_len(&source, false);
+        Self {
+            pattern,
+            regex,
+            max_len,
+        }
+    }
+
+    /// Translate and compile a glob, failing if it is invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the translated regex does not compile.
+    pub fn try_new(pattern: String) -> Result<Self, regex::Error> {
+        let source = glob_to_regex(&pattern);
+        let regex = GLOBAL_CACHE.get_or_compile(&source)?;
+        let max_len = regex_max_len(&source, false);
+        Ok(Self {
+            pattern,
+            regex: Some(regex),
+            max_len,
+        })
+    }
+
+    /// Get the source glob.
+    #[must_use]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Find the first match in the text.
+    #[must_use]
+    pub fn find<'a>(&self, text: &'a str) -> Option<regex::Match<'a>> {
+        self.regex.as_ref()?.find(text)
+    }
 }
 
 /// Result of a successful pattern match.
@@ -391,9 +811,22 @@ pub struct PatternMatch {
     pub end: usize,
     /// Capture groups (for regex patterns).
     pub captures: Vec<String>,
+    /// Numbered and named capture groups (for regex patterns).
+    pub groups: CaptureGroups,
 }
 
 impl PatternMatch {
+    /// Create a match without capture groups.
+    #[must_use]
+    pub fn new(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end,
+            captures: Vec::new(),
+            groups: CaptureGroups::default(),
+        }
+    }
+
     /// Get the matched text from the original input.
     #[must_use]
     pub fn as_str<'a>(&self, text: &'a str) -> &'a str {
@@ -414,9 +847,15 @@ impl PatternMatch {
 }
 
 /// A set of patterns for multi-pattern matching.
+///
+/// When several patterns match, the one that starts earliest in the text
+/// wins. Among patterns that start at the same place, the one added first
+/// wins, so earlier patterns take priority on overlapping matches.
 #[derive(Debug, Clone, Default)]
 pub struct PatternSet {
     patterns: Vec<NamedPattern>,
+    /// Whether a catch-all fallback follows the patterns.
+    fallback: bool,
 }
 
 /// A pattern with an optional name.
@@ -459,7 +898,10 @@ impl PatternSet {
                 index,
             })
             .collect();
-        Self { patterns }
+        Self {
+            patterns,
+            fallback: false,
+        }
     }
 
     /// Add a pattern to the set.
@@ -484,7 +926,33 @@ impl PatternSet {
         self
     }
 
+    /// Add a catch-all fallback, tried after every pattern.
+    ///
+    /// Like `default` in Tcl expect, the fallback matches when an expect
+    /// would otherwise time out or hit EOF. A timeout fallback leaves the
+    /// buffer in place; its [`Match::before`](crate::Match::before) is a
+    /// copy of the unmatched output.
+    pub const fn add_fallback(&mut self) -> &mut Self {
+        self.fallback = true;
+        self
+    }
+
+    /// Get the index reported when the fallback matches.
+    ///
+    /// This is always one past the last pattern, so it stays last when
+    /// more patterns are added.
+    #[must_use]
+    pub const fn fallback_index(&self) -> Option<usize> {
+        if self.fallback {
+            Some(self.patterns.len())
+        } else {
+            None
+        }
+    }
+
     /// Get the number of patterns in the set.
+    ///
+    /// The fallback is not counted.
     #[must_use]
     pub const fn len(&self) -> usize {
         self.patterns.len()
@@ -498,7 +966,8 @@ impl PatternSet {
 
     /// Find the first matching pattern in the text.
     ///
-    /// Returns the pattern index and match details.
+    /// Returns the pattern index and match details. The fallback is never
+    /// returned here, since it only matches on timeout or EOF.
     #[must_use]
     pub fn find_match(&self, text: &str) -> Option<(usize, PatternMatch)> {
         let mut best_match: Option<(usize, PatternMatch)> = None;
@@ -533,63 +1002,327 @@ impl PatternSet {
             .min()
     }
 
+    /// Get the longest text, in bytes, any pattern in the set can match.
+    ///
+    /// Returns `None` if any pattern is unbounded; see
+    /// [`Pattern::max_match_len`].
+    #[must_use]
+    pub fn max_match_len(&self) -> Option<usize> {
+        self.patterns.iter().try_fold(0, |max, p| {
+            p.pattern.max_match_len().map(|len| max.max(len))
+        })
+    }
+
     /// Check if any pattern is an EOF pattern.
     #[must_use]
     pub fn has_eof(&self) -> bool {
         self.patterns.iter().any(|p| p.pattern.is_eof())
     }
 
+    /// Get the index of the first EOF pattern, if any.
+    #[must_use]
+    pub fn eof_index(&self) -> Option<usize> {
+        self.patterns.iter().position(|p| p.pattern.is_eof())
+    }
+
     /// Get iterator over patterns.
     pub fn iter(&self) -> impl Iterator<Item = &NamedPattern> {
         self.patterns.iter()
     }
 }
 
-/// Simple glob pattern matching.
+/// Find `needle` in `text`, ignoring ASCII case.
 ///
-/// Supports `*` (any characters) and `?` (single character).
-fn glob_match(pattern: &str, text: &str) -> Option<usize> {
-    let pattern_chars: Vec<char> = pattern.chars().collect();
-    let text_chars: Vec<char> = text.chars().collect();
+/// Compares bytes in place instead of lowercasing a copy of the text. Bytes
+/// outside ASCII must match exactly, so a hit always starts and ends on a
+/// character boundary.
+fn find_ignore_ascii_case(text: &str, needle: &str) -> Option<usize> {
+    let needle = needle.as_bytes();
+    if needle.is_empty() {
+        return Some(0);
+    }
+    text.as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+}
 
-    (0..=text_chars.len()).find(|&start| glob_match_from(&pattern_chars, &text_chars[start..]))
+/// Work out the longest match of a regex, in bytes.
+///
+/// Returns `None` if the length is unbounded, or if the regex can assert
+/// the start of the whole text, which an incremental scan starting at a
+/// later line cannot reproduce. Other look-around, such as `(?m)^` and
+/// `\b`, only sees the neighbouring character and is safe there.
+fn regex_max_len(pattern: &str, case_insensitive: bool) -> Option<usize> {
+    let hir = regex_syntax::ParserBuilder::new()
+        .case_insensitive(case_insensitive)
+        .build()
+        .parse(pattern)
+        .ok()?;
+    let properties = hir.properties();
+    if properties.look_set().contains(Look::Start) {
+        return None;
+    }
+    properties.maximum_len()
 }
 
-const fn glob_match_from(pattern: &[char], text: &[char]) -> bool {
-    let mut p = 0;
-    let mut t = 0;
-    let mut star_p = None;
-    let mut star_t = 0;
-
-    while p < pattern.len() {
-        if pattern[p] == '*' {
-            star_p = Some(p);
-            star_t = t;
-            p += 1;
-        } else if t < text.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
-            p += 1;
-            t += 1;
-        } else if let Some(sp) = star_p {
-            p = sp + 1;
-            star_t += 1;
-            if star_t > text.len() {
-                return false;
+/// Translate a glob into an equivalent regex.
+///
+/// See [`Pattern::glob`] for the supported syntax.
+#[must_use]
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::with_capacity(glob.len() * 2);
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' => regex.push_str("[^\\n]*"),
+            '?' => regex.push_str("[^\\n]"),
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                push_escaped(&mut regex, chars[i]);
             }
-            t = star_t;
-        } else {
-            return false;
+            '[' => match glob_class(&chars[i + 1..]) {
+                Some((class, len)) => {
+                    regex.push_str(&class);
+                    i += len;
+                }
+                None => regex.push_str("\\["),
+            },
+            c => push_escaped(&mut regex, c),
+        }
+        i += 1;
+    }
+
+    regex
+}
+
+/// Translate the body of a `[...]` set, starting after the `[`.
+///
+/// Returns the regex class and the number of glob characters used,
+/// including the closing `]`, or `None` if the set is not closed.
+fn glob_class(chars: &[char]) -> Option<(String, usize)> {
+    let negated = matches!(chars.first(), Some('!' | '^'));
+    let body_start = usize::from(negated);
+    // A leading `]` is a member, not the end of the set
+    let close = chars
+        .iter()
+        .skip(body_start + 1)
+        .position(|&c| c == ']')
+        .map(|pos| pos + body_start + 1)?;
+
+    let mut class = String::from(if negated { "[^\\n" } else { "[" });
+    for &c in &chars[body_start..close] {
+        if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~') {
+            class.push('\\');
+        }
+        class.push(c);
+    }
+    class.push(']');
+    Some((class, close + 1))
+}
+
+/// Append `c` to `regex` as a literal character.
+fn push_escaped(regex: &mut String, c: char) {
+    let mut buf = [0; 4];
+    regex.push_str(&regex::escape(c.encode_utf8(&mut buf)));
+}
+
+/// Find a match of `pattern` that starts at the beginning of `text`.
+fn match_at_start(pattern: &Pattern, text: &str) -> Option<PatternMatch> {
+    match pattern {
+        Pattern::Fuzzy {
+            text: needle,
+            max_distance,
+        } => fuzzy_match_prefix(needle, text, *max_distance).map(|end| PatternMatch::new(0, end)),
+        _ => pattern.matches(text).filter(|m| m.start == 0),
+    }
+}
+
+/// Find the closest prefix of `text` within `max_distance` edits of `needle`.
+///
+/// Returns the byte length of the prefix, preferring the shortest on ties.
+fn fuzzy_match_prefix(needle: &str, text: &str, max_distance: usize) -> Option<usize> {
+    let needle: Vec<char> = needle.chars().collect();
+    // Distance from each needle prefix to the text read so far
+    let mut cost: Vec<usize> = (0..=needle.len()).collect();
+    let mut best = (needle.len() <= max_distance).then_some((needle.len(), 0));
+
+    // A longer prefix needs more insertions than allowed
+    for (j, (at, c)) in text
+        .char_indices()
+        .take(needle.len() + max_distance)
+        .enumerate()
+    {
+        let mut diag = cost[0];
+        cost[0] = j + 1;
+        for (i, &n) in needle.iter().enumerate() {
+            let next = (diag + usize::from(n != c))
+                .min(cost[i + 1] + 1)
+                .min(cost[i] + 1);
+            diag = cost[i + 1];
+            cost[i + 1] = next;
+        }
+
+        let distance = cost[needle.len()];
+        if distance <= max_distance && best.is_none_or(|(d, _)| distance < d) {
+            best = Some((distance, at + c.len_utf8()));
+        }
+    }
+    best.map(|(_, end)| end)
+}
+
+/// Find the part of `text` closest to `needle` in Levenshtein distance.
+///
+/// This is Sellers' approximate substring search: a column of edit
+/// distances is carried across the text, with a free start at every
+/// position, so each text character is visited once. Returns the byte range
+/// of the best window within `max_distance`.
+fn fuzzy_match(needle: &str, text: &str, max_distance: usize) -> Option<(usize, usize)> {
+    let needle: Vec<char> = needle.chars().collect();
+    if needle.is_empty() {
+        return Some((0, 0));
+    }
+
+    // Distance and start (in chars) of the best alignment of each needle prefix
+    let mut cost: Vec<usize> = (0..=needle.len()).collect();
+    let mut start = vec![0; needle.len() + 1];
+    // The needle can also be matched entirely by deletions
+    let mut best = (needle.len() <= max_distance).then_some((needle.len(), 0, 0));
+
+    let chars: Vec<char> = text.chars().collect();
+    for (j, &c) in chars.iter().enumerate() {
+        let (mut diag_cost, mut diag_start) = (cost[0], start[0]);
+        cost[0] = 0;
+        start[0] = j + 1;
+
+        for (i, &n) in needle.iter().enumerate() {
+            let (above_cost, above_start) = (cost[i + 1], start[i + 1]);
+
+            // Substitute or keep c, skip c in the text, or skip n in the needle
+            let mut next = (diag_cost + usize::from(n != c), diag_start);
+            if above_cost + 1 < next.0 {
+                next = (above_cost + 1, above_start);
+            }
+            if cost[i] + 1 < next.0 {
+                next = (cost[i] + 1, start[i]);
+            }
+
+            (diag_cost, diag_start) = (above_cost, above_start);
+            (cost[i + 1], start[i + 1]) = next;
+        }
+
+        // Keep the closest window, extending it while the distance holds
+        let (distance, from) = (cost[needle.len()], start[needle.len()]);
+        if distance <= max_distance
+            && best.is_none_or(|(d, s, _)| distance < d || (distance == d && from == s))
+        {
+            best = Some((distance, from, j + 1));
         }
     }
 
-    // Pattern matched - we don't require text to be fully consumed
-    // (we're looking for the pattern within the text)
-    true
+    // Convert char positions back to byte offsets
+    let byte_offset = |pos: usize| text.char_indices().nth(pos).map_or(text.len(), |(b, _)| b);
+    best.map(|(_, start, end)| (byte_offset(start), byte_offset(end)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn fuzzy_pattern_tolerates_typo() {
+        let pattern = Pattern::fuzzy("password:", 1);
+        let text = "Enter pasword: ";
+        let m = pattern.matches(text).unwrap();
+        assert_eq!(m.as_str(text), "pasword:");
+
+        assert!(Pattern::fuzzy("password:", 0).matches(text).is_none());
+        assert!(pattern.matches("Enter pswrd: ").is_none());
+    }
+
+    #[test]
+    fn fuzzy_pattern_prefers_closest_window() {
+        let pattern = Pattern::fuzzy("login:", 2);
+        let text = "lgin: then login: ";
+        let m = pattern.matches(text).unwrap();
+        assert_eq!(m.start, 11);
+        assert_eq!(m.as_str(text), "login:");
+    }
+
+    #[test]
+    fn fuzzy_pattern_reports_byte_offsets() {
+        let pattern = Pattern::fuzzy("café", 1);
+        let text = "ünïcode cafe";
+        let m = pattern.matches(text).unwrap();
+        assert_eq!(m.as_str(text), "cafe");
+    }
+
+    #[test]
+    fn pattern_set_max_match_len() {
+        let set = PatternSet::from_patterns(vec![
+            Pattern::literal("login:"),
+            Pattern::fuzzy("password:", 1),
+            Pattern::Eof,
+        ]);
+        assert_eq!(set.max_match_len(), Some(13));
+
+        let mut set = set;
+        set.add(Pattern::regex(r"\$\s*").unwrap());
+        assert_eq!(set.max_match_len(), None);
+    }
+
+    #[test]
+    fn line_start_pattern_skips_mid_line_hits() {
+        let pattern = Pattern::at_line_start("$ ");
+        let text = "echo $ HOME\nvalue $ x\n$ ";
+        let m = pattern.matches(text).unwrap();
+        assert_eq!(m.start, text.len() - 2);
+        assert_eq!(m.as_str(text), "$ ");
+
+        assert!(pattern.matches("a $ b\nc $ d").is_none());
+        assert_eq!(pattern.matches("$ first\n$ second").unwrap().start, 0);
+    }
+
+    #[test]
+    fn line_start_pattern_resumes_after_mid_line_hits() {
+        let pattern = Pattern::at_line_start("$ ");
+        let text = "caf\u{e9}$ 1\n\n\u{e9}$ 2\n$ ";
+        assert_eq!(pattern.matches(text).unwrap().start, text.len() - 2);
+    }
+
+    #[test]
+    fn line_start_fuzzy_pattern_tries_every_line() {
+        // The closest window is mid-line, but line 0 is within reach too
+        let pattern = Pattern::at_line_start(Pattern::fuzzy("login:", 1));
+        let text = "logn: \nxlogin:";
+        let m = pattern.matches(text).unwrap();
+        assert_eq!((m.start, m.as_str(text)), (0, "logn:"));
+
+        let text = "a login:\npasswd\nlogin:";
+        assert_eq!(pattern.matches(text).unwrap().start, 16);
+        assert!(pattern.matches("a login:\nb login:").is_none());
+    }
+
+    #[test]
+    fn line_start_regex_keeps_captures() {
+        let pattern = Pattern::at_line_start(Pattern::regex(r"(\w+)@host").unwrap());
+        let text = "mail to bob@host\nalice@host:~$ ";
+        let m = pattern.matches(text).unwrap();
+        assert_eq!(m.as_str(text), "alice@host");
+        assert_eq!(m.captures, vec!["alice".to_string()]);
+    }
+
+    #[test]
+    fn last_line_pattern_ignores_earlier_lines() {
+        let pattern = Pattern::on_last_line("$ ");
+        assert!(pattern.matches("total $ 5\nmore output").is_none());
+        assert!(pattern.matches("prompt$ \n").is_none());
+
+        let text = "total $ 5\nuser@host:~$ ";
+        assert_eq!(pattern.matches(text).unwrap().start, text.len() - 2);
+    }
+
     #[test]
     fn literal_pattern_matches() {
         let pattern = Pattern::literal("hello");
@@ -618,6 +1351,55 @@ mod tests {
         assert_eq!(m.captures, vec!["user", "domain"]);
     }
 
+    #[test]
+    fn regex_match_groups_come_from_the_same_match() {
+        let pattern = Pattern::regex(r"(?P<user>\w+)(@(\w+))?:").unwrap();
+        let m = pattern.matches("bob: alice@host:").unwrap();
+        assert_eq!((m.start, m.end), (0, 4));
+        assert_eq!(m.captures, vec!["bob"]);
+        assert_eq!(m.groups.name("user"), Some("bob"));
+        assert_eq!(m.groups.get(3), None);
+
+        let m = Pattern::regex(r"\d+").unwrap().matches("id 42").unwrap();
+        assert!(m.captures.is_empty());
+        assert_eq!(m.groups.get(0), Some("42"));
+    }
+
+    #[test]
+    fn literal_ci_ignores_ascii_case() {
+        let pattern = Pattern::literal_ci("Password:");
+        let text = "Enter PASSWORD: ";
+        let m = pattern.matches(text).unwrap();
+        assert_eq!(m.as_str(text), "PASSWORD:");
+        assert!(pattern.matches("passwd:").is_none());
+
+        // Non-ASCII characters must match exactly
+        let pattern = Pattern::literal_ci("Café");
+        assert!(pattern.matches("CAFé").is_some());
+        assert!(pattern.matches("CAFÉ").is_none());
+    }
+
+    #[test]
+    fn case_insensitive_builder() {
+        let pattern = Pattern::regex(r"(\w+) login:").unwrap().case_insensitive();
+        let m = pattern.matches("HOST LOGIN: ").unwrap();
+        assert_eq!(m.captures, vec!["HOST"]);
+        assert!(matches!(&pattern, Pattern::Regex(r) if r.is_case_insensitive()));
+        assert!(
+            Pattern::regex("login:")
+                .unwrap()
+                .matches("LOGIN:")
+                .is_none()
+        );
+
+        assert!(matches!(
+            Pattern::literal("ok").case_insensitive(),
+            Pattern::LiteralCi(_)
+        ));
+        let anchored = Pattern::at_line_start("ok").case_insensitive();
+        assert!(anchored.matches("x\nOK").is_some());
+    }
+
     #[test]
     fn glob_pattern_matches() {
         let pattern = Pattern::glob("hello*world");
@@ -625,6 +1407,85 @@ mod tests {
         assert!(result.is_some());
     }
 
+    #[test]
+    fn glob_question_mark_matches_one_char() {
+        let pattern = Pattern::glob("file?.txt");
+        let text = "ls\nfile1.txt  file22.txt";
+        let m = pattern.matches(text).unwrap();
+        assert_eq!(m.as_str(text), "file1.txt");
+
+        assert!(pattern.matches("file.txt").is_none());
+        assert!(pattern.matches("file\n.txt").is_none());
+    }
+
+    #[test]
+    fn glob_character_class() {
+        let pattern = Pattern::glob("log[0-9]*");
+        let text = "logs: log42.gz\nnext";
+        let m = pattern.matches(text).unwrap();
+        assert_eq!(m.as_str(text), "log42.gz");
+
+        assert!(Pattern::glob("log[!0-9]").matches("log5").is_none());
+        assert!(Pattern::glob("log[!0-9]").matches("logx").is_some());
+        assert!(Pattern::glob("[]]").matches("a]b").is_some());
+    }
+
+    #[test]
+    fn glob_star_stays_within_line() {
+        let pattern = Pattern::glob("start*end");
+        assert!(pattern.matches("start\nend").is_none());
+        assert!(pattern.matches("start middle end").is_some());
+    }
+
+    #[test]
+    fn max_match_len_bounds() {
+        let len = |p: Pattern| p.max_match_len();
+        assert_eq!(len(Pattern::regex("password:|login:").unwrap()), Some(9));
+        assert_eq!(len(Pattern::regex("[0-9]{1,3}").unwrap()), Some(3));
+        assert_eq!(len(Pattern::regex(r"(?m)^\w{2}$").unwrap()), Some(8));
+        assert_eq!(len(Pattern::regex(r"\d+").unwrap()), None);
+        assert_eq!(len(Pattern::regex("^login:").unwrap()), None);
+        assert_eq!(len(Pattern::glob("file?.txt")), Some(12));
+        assert_eq!(len(Pattern::glob("log*")), None);
+        assert_eq!(len(Pattern::at_line_start("$ ")), Some(2));
+        assert_eq!(len(Pattern::on_last_line(Pattern::glob("*"))), None);
+    }
+
+    #[test]
+    fn glob_is_unanchored() {
+        let pattern = Pattern::glob("err?r:*");
+        let text = "make: error: no rule\nnext line";
+        let m = pattern.matches(text).unwrap();
+        assert_eq!(m.as_str(text), "error: no rule");
+        assert_eq!(pattern.as_str(), "err?r:*");
+    }
+
+    #[test]
+    fn glob_with_invalid_range_never_matches() {
+        let pattern = Pattern::glob("[z-a]");
+        assert!(pattern.matches("z-a").is_none());
+        assert!(pattern.matches("m").is_none());
+    }
+
+    #[test]
+    fn try_glob_rejects_invalid_range() {
+        assert!(Pattern::try_glob("[z-a]").is_err());
+
+        let pattern = Pattern::try_glob("log[0-9]*").unwrap();
+        assert_eq!(pattern.as_str(), "log[0-9]*");
+        assert!(pattern.matches("wrote log7.txt").is_some());
+    }
+
+    #[test]
+    fn glob_escapes_and_literals() {
+        assert_eq!(glob_to_regex("a.b"), r"a\.b");
+        assert_eq!(glob_to_regex(r"\*"), r"\*");
+        assert_eq!(glob_to_regex("[abc"), r"\[abc");
+        assert!(Pattern::glob(r"cost: \$5?").matches("cost: $50").is_some());
+        assert!(Pattern::glob(r"2 \* 3").matches("2 * 3").is_some());
+        assert!(Pattern::glob(r"2 \* 3").matches("2 x 3").is_none());
+    }
+
     #[test]
     fn pattern_set_finds_first() {
         let mut set = PatternSet::new();
@@ -638,6 +1499,26 @@ mod tests {
         assert_eq!(idx, 1);
     }
 
+    #[test]
+    fn pattern_set_prefers_earlier_pattern_at_same_start() {
+        let mut set = PatternSet::new();
+        set.add(Pattern::literal("error"))
+            .add(Pattern::literal("err"));
+        assert_eq!(set.find_match("error: disk").unwrap().0, 0);
+    }
+
+    #[test]
+    fn pattern_set_fallback_is_last() {
+        let mut set = PatternSet::new();
+        assert_eq!(set.fallback_index(), None);
+        set.add(Pattern::literal("ok")).add_fallback();
+        assert_eq!(set.fallback_index(), Some(1));
+        set.add(Pattern::literal("err"));
+        assert_eq!(set.fallback_index(), Some(2));
+        assert_eq!(set.len(), 2);
+        assert!(set.find_match("nothing here").is_none());
+    }
+
     #[test]
     fn pattern_set_min_timeout() {
         let mut set = PatternSet::new();
@@ -5,6 +5,20 @@
 
 use std::time::{Duration, Instant};
 
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[cfg(feature = "ssh")]
+use crate::backend::ssh::{SshChannelStream, SshConfig};
+#[cfg(feature = "ssh")]
+use crate::dialog::Dialog;
+#[cfg(feature = "ssh")]
+use crate::error::{ExpectError, Result, SshError};
+use crate::expect::Pattern;
+use crate::session::Session;
+use crate::types::Match;
+#[cfg(feature = "ssh")]
+use crate::util::{RetryConfig, RetryError, retry};
+
 /// Health status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HealthStatus {
@@ -98,6 +112,8 @@ pub struct HealthCheckConfig {
     pub failure_threshold: u32,
     /// Number of successes before healthy.
     pub success_threshold: u32,
+    /// Line sent by [`HealthChecker::probe`]; empty sends just a newline.
+    pub probe_command: String,
 }
 
 impl Default for HealthCheckConfig {
@@ -107,6 +123,7 @@ impl Default for HealthCheckConfig {
             timeout: Duration::from_secs(5),
             failure_threshold: 3,
             success_threshold: 1,
+            probe_command: String::new(),
         }
     }
 }
@@ -145,6 +162,37 @@ impl HealthCheckConfig {
         self.success_threshold = threshold;
         self
     }
+
+    /// Set the line a probe sends, e.g. a no-op like `true`.
+    #[must_use]
+    pub fn with_probe_command(mut self, command: impl Into<String>) -> Self {
+        self.probe_command = command.into();
+        self
+    }
+}
+
+/// How a [`HealthChecker`] re-establishes a dropped SSH session.
+#[cfg(feature = "ssh")]
+#[derive(Debug, Clone)]
+struct Reconnect {
+    /// Where to connect.
+    ssh: SshConfig,
+    /// Dialog run on every new connection, e.g. a login.
+    login: Dialog,
+    /// Delays between attempts, and how many to make.
+    retry: RetryConfig,
+}
+
+/// One attempt made by [`HealthChecker::reconnect`].
+#[cfg(feature = "ssh")]
+#[derive(Debug, Clone)]
+pub struct ReconnectAttempt {
+    /// Attempt number, starting at 1.
+    pub attempt: u32,
+    /// How long the attempt took.
+    pub duration: Duration,
+    /// Why the attempt failed, or `None` if it succeeded.
+    pub error: Option<String>,
 }
 
 /// Health checker state.
@@ -162,6 +210,12 @@ pub struct HealthChecker {
     last_check: Option<Instant>,
     /// Last result.
     last_result: Option<HealthCheckResult>,
+    /// Reconnect policy for SSH sessions.
+    #[cfg(feature = "ssh")]
+    reconnect: Option<Reconnect>,
+    /// Attempts made by the latest reconnect.
+    #[cfg(feature = "ssh")]
+    reconnect_log: Vec<ReconnectAttempt>,
 }
 
 impl HealthChecker {
@@ -175,9 +229,42 @@ impl HealthChecker {
             successes: 0,
             last_check: None,
             last_result: None,
+            #[cfg(feature = "ssh")]
+            reconnect: None,
+            #[cfg(feature = "ssh")]
+            reconnect_log: Vec::new(),
         }
     }
 
+    /// Reconnect dropped SSH sessions in [`check_ssh`](Self::check_ssh).
+    ///
+    /// Each attempt connects with `ssh`, starts a shell and runs `login`
+    /// on it. `retry` gives the number of attempts, the backoff between
+    /// them and an optional deadline for the whole reconnect.
+    #[cfg(feature = "ssh")]
+    #[must_use]
+    pub fn with_reconnect(mut self, ssh: SshConfig, login: Dialog, retry: RetryConfig) -> Self {
+        self.reconnect = Some(Reconnect { ssh, login, retry });
+        self
+    }
+
+    /// Get the number of attempts made by the latest reconnect.
+    #[cfg(feature = "ssh")]
+    #[must_use]
+    pub fn reconnect_attempts(&self) -> u32 {
+        u32::try_from(self.reconnect_log.len()).unwrap_or(u32::MAX)
+    }
+
+    /// Get each attempt made by the latest reconnect, in order.
+    ///
+    /// This shows how a reconnect went after the fact: which attempts
+    /// failed, why, and how long each took.
+    #[cfg(feature = "ssh")]
+    #[must_use]
+    pub fn reconnect_log(&self) -> &[ReconnectAttempt] {
+        &self.reconnect_log
+    }
+
     /// Get current status.
     #[must_use]
     pub const fn status(&self) -> HealthStatus {
@@ -227,6 +314,155 @@ impl HealthChecker {
         self.last_result = Some(HealthCheckResult::unhealthy(message));
     }
 
+    /// Actively check that a session responds.
+    ///
+    /// Sends the configured [probe command](HealthCheckConfig::probe_command)
+    /// and waits up to `timeout` for `marker`, such as the shell prompt.
+    /// This catches a wedged session that is still connected but no longer
+    /// answers. Unread output, including output the transport already has
+    /// ready, is discarded first, so a stale marker cannot pass the probe.
+    /// A marker that also appears in the probe command, as in `echo alive`
+    /// with marker `alive`, is not matched in the terminal's echo of the
+    /// command, only in what it prints.
+    ///
+    /// The outcome is recorded like [`record_success`](Self::record_success)
+    /// or [`record_failure`](Self::record_failure), and returned as
+    /// [`HealthStatus::Healthy`] or [`HealthStatus::Unhealthy`]; the
+    /// checker's own [`status`](Self::status) still follows the thresholds.
+    pub async fn probe<T>(
+        &mut self,
+        session: &mut Session<T>,
+        marker: impl Into<Pattern>,
+        timeout: Duration,
+    ) -> HealthStatus
+    where
+        T: AsyncReadExt + AsyncWriteExt + Unpin + Send,
+    {
+        let start = Instant::now();
+        let marker = marker.into();
+        let command = &self.config.probe_command;
+        let result: crate::Result<()> = async {
+            session.discard_pending().await?;
+            session.send_line(command).await?;
+            loop {
+                let remaining = timeout.saturating_sub(start.elapsed());
+                let m = session.expect_timeout(marker.clone(), remaining).await?;
+                if !is_echo(command, &m) {
+                    return Ok(());
+                }
+            }
+        }
+        .await;
+
+        let (status, result) = match result {
+            Ok(()) => {
+                self.record_success();
+                (HealthStatus::Healthy, HealthCheckResult::healthy())
+            }
+            Err(e) => {
+                let message = format!("probe failed: {e}");
+                self.record_failure(message.clone());
+                (
+                    HealthStatus::Unhealthy,
+                    HealthCheckResult::unhealthy(message),
+                )
+            }
+        };
+        self.last_result = Some(result.with_duration(start.elapsed()));
+        status
+    }
+
+    /// Probe an SSH session, reconnecting it if it is closed or wedged.
+    ///
+    /// A session that has reached EOF, or fails the [`probe`](Self::probe),
+    /// is replaced through the policy set with
+    /// [`with_reconnect`](Self::with_reconnect). Afterwards the checker is
+    /// [`Healthy`](HealthStatus::Healthy) with the new session in place, or
+    /// [`Unhealthy`](HealthStatus::Unhealthy) once every attempt has
+    /// failed; [`reconnect_log`](Self::reconnect_log) records each attempt.
+    /// Without a policy this is just a probe.
+    #[cfg(feature = "ssh")]
+    pub async fn check_ssh(
+        &mut self,
+        session: &mut Session<SshChannelStream>,
+        marker: impl Into<Pattern>,
+        timeout: Duration,
+    ) -> HealthStatus {
+        if session.is_eof() {
+            self.record_failure("session closed");
+        } else if self.probe(session, marker, timeout).await.is_healthy() {
+            return HealthStatus::Healthy;
+        }
+        if self.reconnect.is_none() {
+            return HealthStatus::Unhealthy;
+        }
+
+        match self.reconnect().await {
+            Ok(new) => {
+                *session = new;
+                self.record_success();
+                self.status = HealthStatus::Healthy;
+                HealthStatus::Healthy
+            }
+            Err(e) => {
+                self.record_failure(format!("reconnect failed: {e}"));
+                self.status = HealthStatus::Unhealthy;
+                HealthStatus::Unhealthy
+            }
+        }
+    }
+
+    /// Open a new session with the reconnect policy, retrying with backoff.
+    ///
+    /// # Errors
+    ///
+    /// Returns the last attempt's error once every attempt has failed or
+    /// the deadline has passed, or an error if no policy was set.
+    #[cfg(feature = "ssh")]
+    pub async fn reconnect(&mut self) -> Result<Session<SshChannelStream>> {
+        let Some(policy) = self.reconnect.clone() else {
+            return Err(ExpectError::config("no reconnect policy set"));
+        };
+        let log = std::sync::Mutex::new(Vec::new());
+        let mut attempts = 0;
+        let result = retry(
+            || {
+                attempts += 1;
+                let attempt = attempts;
+                let (policy, log) = (&policy, &log);
+                async move {
+                    let start = Instant::now();
+                    let result = connect_and_login(policy).await;
+                    if let Err(e) = &result {
+                        tracing::debug!(attempt, error = %e, "SSH reconnect failed");
+                    }
+                    log.lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner)
+                        .push(ReconnectAttempt {
+                            attempt,
+                            duration: start.elapsed(),
+                            error: result.as_ref().err().map(ToString::to_string),
+                        });
+                    result
+                }
+            },
+            &policy.retry,
+        )
+        .await;
+        self.reconnect_log = log
+            .into_inner()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        result.map_err(|e| match e {
+            RetryError::Exhausted { last, .. }
+            | RetryError::DeadlineExceeded {
+                last: Some(last), ..
+            } => last,
+            RetryError::DeadlineExceeded { last: None, .. } => {
+                ExpectError::Ssh(SshError::session("reconnect deadline passed"))
+            }
+        })
+    }
+
     /// Reset the checker.
     pub fn reset(&mut self) {
         self.status = HealthStatus::Unknown;
@@ -237,6 +473,30 @@ impl HealthChecker {
     }
 }
 
+/// Whether `m` matched inside the terminal's echo of `command` rather than
+/// in its output, i.e. the matched text follows the start of the command.
+fn is_echo(command: &str, m: &Match) -> bool {
+    let line = m.before.rsplit('\n').next().unwrap_or_default();
+    command
+        .match_indices(m.matched.as_str())
+        .any(|(start, _)| start > 0 && line.ends_with(&command[..start]))
+}
+
+/// Connect, start a shell and run the login dialog on it.
+#[cfg(feature = "ssh")]
+async fn connect_and_login(policy: &Reconnect) -> Result<Session<SshChannelStream>> {
+    let mut session = Session::spawn_ssh(policy.ssh.clone()).await?;
+    let result = session.run_dialog(&policy.login).await?;
+    if result.success {
+        Ok(session)
+    } else {
+        Err(ExpectError::Ssh(SshError::session(format!(
+            "login dialog failed: {}",
+            result.error.unwrap_or_default()
+        ))))
+    }
+}
+
 /// Simple liveness check.
 #[must_use]
 pub fn liveness_check() -> HealthCheckResult {
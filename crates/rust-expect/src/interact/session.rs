@@ -36,8 +36,15 @@ use tokio::sync::Mutex;
 use super::hooks::{HookManager, InteractionEvent};
 use super::mode::InteractionMode;
 use super::terminal::TerminalSize;
+use crate::config::LineEnding;
 use crate::error::{ExpectError, Result};
 use crate::expect::Pattern;
+use crate::transcript::Recorder;
+
+/// Pause in session output after which text held back by the redactor is
+/// shown.
+#[cfg(feature = "pii-redaction")]
+const REDACTOR_LULL: Duration = Duration::from_millis(200);
 
 /// Action to take after a pattern match in interactive mode.
 #[derive(Debug, Clone)]
@@ -76,6 +83,8 @@ pub struct InteractContext<'a> {
     pub buffer: &'a str,
     /// The pattern index that matched.
     pub pattern_index: usize,
+    /// The session's line ending, used by [`send_line`](Self::send_line).
+    pub line_ending: LineEnding,
 }
 
 impl InteractContext<'_> {
@@ -84,10 +93,10 @@ impl InteractContext<'_> {
         InteractAction::send(data)
     }
 
-    /// Create a send action with line ending.
+    /// Create a send action with the session's line ending appended.
     pub fn send_line(&self, data: impl Into<String>) -> InteractAction {
         let mut s = data.into();
-        s.push('\n');
+        s.push_str(self.line_ending.as_str());
         InteractAction::send(s)
     }
 }
@@ -140,8 +149,22 @@ where
     buffer_size: usize,
     /// Escape string to exit interact mode.
     escape_sequence: Option<Vec<u8>>,
+    /// Maximum gap between the bytes of the escape sequence.
+    escape_timeout: Option<Duration>,
+    /// Line ending appended by [`InteractContext::send_line`].
+    line_ending: LineEnding,
     /// Default timeout for the interaction.
     timeout: Option<Duration>,
+    /// Maximum time without input or output.
+    idle_timeout: Option<Duration>,
+    /// Recorder capturing the interaction.
+    recorder: Option<Recorder>,
+    /// Redactor applied to output mirrored to stdout.
+    #[cfg(feature = "pii-redaction")]
+    redactor: Option<crate::pii::StreamingRedactor>,
+    /// Decoder for a non-UTF-8 charset, applied before hooks and matching.
+    #[cfg(feature = "legacy-encoding")]
+    decoder: Option<crate::encoding::StreamingDecoder>,
 }
 
 impl<'a, T> InteractBuilder<'a, T>
@@ -159,7 +182,15 @@ where
             mode: InteractionMode::default(),
             buffer_size: 8192,
             escape_sequence: Some(vec![0x1d]), // Ctrl+] by default
+            escape_timeout: None,
+            line_ending: LineEnding::default(),
             timeout: None,
+            idle_timeout: None,
+            recorder: None,
+            #[cfg(feature = "pii-redaction")]
+            redactor: None,
+            #[cfg(feature = "legacy-encoding")]
+            decoder: None,
         }
     }
 
@@ -224,8 +255,8 @@ where
     /// # Platform Support
     ///
     /// - **Unix**: Resize events are detected via SIGWINCH signal handling.
-    /// - **Windows**: Resize detection is not currently supported; the callback
-    ///   will not be invoked.
+    /// - **Windows**: Resize events are detected from console input records
+    ///   (`WINDOW_BUFFER_SIZE_EVENT`) and by polling the console window size.
     #[must_use]
     pub fn on_resize<F>(mut self, callback: F) -> Self
     where
@@ -244,13 +275,26 @@ where
 
     /// Set the escape sequence to exit interact mode.
     ///
-    /// Default is Ctrl+] (0x1d).
+    /// Default is Ctrl+] (0x1d). The sequence is matched across reads, so
+    /// a multi-byte escape may arrive in separate chunks.
     #[must_use]
     pub fn with_escape(mut self, escape: impl Into<Vec<u8>>) -> Self {
         self.escape_sequence = Some(escape.into());
         self
     }
 
+    /// Require the bytes of the escape sequence to arrive close together.
+    ///
+    /// A partial escape is forgotten if the next byte takes longer than
+    /// `timeout`, like vim's `timeoutlen`. This keeps a lone Ctrl+]
+    /// followed by unrelated input later from completing a multi-byte
+    /// escape. Without it, the bytes may be any time apart.
+    #[must_use]
+    pub const fn with_escape_timeout(mut self, timeout: Duration) -> Self {
+        self.escape_timeout = Some(timeout);
+        self
+    }
+
     /// Disable the escape sequence (interact runs until pattern stops it).
     #[must_use]
     pub fn no_escape(mut self) -> Self {
@@ -265,6 +309,28 @@ where
         self
     }
 
+    /// Set the line ending appended by [`InteractContext::send_line`].
+    ///
+    /// [`Session::interact`](crate::Session::interact) sets this from the
+    /// session's configuration.
+    #[must_use]
+    pub const fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// End the interaction after a period with no activity.
+    ///
+    /// Any output from the session or input from the user resets the idle
+    /// timer. Unlike [`with_timeout`](Self::with_timeout), which caps the
+    /// total duration, this detects a session that has gone quiet, and ends
+    /// with [`InteractEndReason::Idle`].
+    #[must_use]
+    pub const fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
     /// Set the output buffer size.
     #[must_use]
     pub const fn with_buffer_size(mut self, size: usize) -> Self {
@@ -292,6 +358,52 @@ where
         self
     }
 
+    /// Record the interaction to a transcript.
+    ///
+    /// Output shown on stdout, user input sent to the session, and terminal
+    /// resizes are recorded as they happen. Keep a handle from
+    /// [`Recorder::transcript`] to read the transcript afterwards; recording
+    /// stops when the interaction ends.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let recorder = Recorder::new(80, 24);
+    /// let transcript = recorder.transcript();
+    ///
+    /// session.interact().record_to(recorder).start().await?;
+    /// ```
+    #[must_use]
+    pub fn record_to(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Redact PII from the output mirrored to stdout.
+    ///
+    /// Output is passed through a [`StreamingRedactor`](crate::pii::StreamingRedactor),
+    /// so a value split across reads is still redacted. Text that cannot
+    /// start a match is shown at once; a tail that might be the start of a
+    /// secret, such as a prompt like `login: `, is held until more output
+    /// arrives, output pauses for 200 ms, or the interaction ends. A secret split across a longer pause is shown unredacted.
+    /// Pattern hooks and output hooks still see the unredacted output.
+    #[cfg(feature = "pii-redaction")]
+    #[must_use]
+    pub fn with_redactor(mut self, redactor: crate::pii::PiiRedactor) -> Self {
+        self.redactor = Some(crate::pii::StreamingRedactor::new(redactor));
+        self
+    }
+
+    /// Decode output from a legacy charset before hooks and matching.
+    #[cfg(feature = "legacy-encoding")]
+    pub(crate) const fn with_decoder(
+        mut self,
+        decoder: Option<crate::encoding::StreamingDecoder>,
+    ) -> Self {
+        self.decoder = decoder;
+        self
+    }
+
     /// Start the interactive session.
     ///
     /// This runs the interaction loop, reading from stdin and the session,
@@ -307,7 +419,12 @@ where
     ///
     /// Returns an error if I/O fails or a pattern callback returns an error.
     pub async fn start(self) -> Result<InteractResult> {
-        let mut runner = InteractRunner::new(
+        self.into_runner().run().await
+    }
+
+    /// Build the runner for the interaction loop.
+    fn into_runner(self) -> InteractRunner<T> {
+        let runner = InteractRunner::new(
             Arc::clone(self.transport),
             self.output_hooks,
             self.input_hooks,
@@ -315,10 +432,20 @@ where
             self.hook_manager,
             self.mode,
             self.buffer_size,
-            self.escape_sequence,
+            self.escape_sequence
+                .map(|sequence| EscapeMatcher::new(sequence, self.escape_timeout)),
             self.timeout,
         );
-        runner.run().await
+        InteractRunner {
+            line_ending: self.line_ending,
+            idle_timeout: self.idle_timeout,
+            recorder: self.recorder,
+            #[cfg(feature = "pii-redaction")]
+            redactor: self.redactor,
+            #[cfg(feature = "legacy-encoding")]
+            decoder: self.decoder,
+            ..runner
+        }
     }
 }
 
@@ -343,12 +470,88 @@ pub enum InteractEndReason {
     Escape,
     /// Timeout occurred.
     Timeout,
+    /// No input or output for the idle timeout.
+    Idle,
     /// EOF was reached on the session.
     Eof,
     /// An error occurred in a pattern callback.
     Error(String),
 }
 
+/// Incremental matcher for the interact escape sequence.
+///
+/// Tracks how much of the sequence has been typed so far, so the sequence
+/// is found even when it is split across reads or starts mid-chunk.
+struct EscapeMatcher {
+    /// The escape sequence.
+    sequence: Vec<u8>,
+    /// Maximum gap between bytes of the sequence.
+    timeout: Option<Duration>,
+    /// Number of leading sequence bytes matched so far.
+    matched: usize,
+    /// When the last matched byte arrived.
+    last_byte: Option<std::time::Instant>,
+}
+
+impl EscapeMatcher {
+    const fn new(sequence: Vec<u8>, timeout: Option<Duration>) -> Self {
+        Self {
+            sequence,
+            timeout,
+            matched: 0,
+            last_byte: None,
+        }
+    }
+
+    /// Feed input bytes, returning `true` once the full sequence is seen.
+    fn feed(&mut self, data: &[u8]) -> bool {
+        self.feed_at(data, std::time::Instant::now())
+    }
+
+    /// Feed input bytes that arrived at `now`.
+    fn feed_at(&mut self, data: &[u8], now: std::time::Instant) -> bool {
+        if self.sequence.is_empty() {
+            return false;
+        }
+
+        // A partial match expires if the next byte comes too late
+        if let (Some(timeout), Some(last)) = (self.timeout, self.last_byte)
+            && self.matched > 0
+            && now.duration_since(last) > timeout
+        {
+            self.matched = 0;
+        }
+
+        for &byte in data {
+            self.matched = self.advance(byte);
+            if self.matched == self.sequence.len() {
+                self.matched = 0;
+                self.last_byte = None;
+                return true;
+            }
+        }
+        self.last_byte = Some(now);
+        false
+    }
+
+    /// Length of the longest sequence prefix ending in `byte`.
+    fn advance(&self, byte: u8) -> usize {
+        let mut len = self.matched + 1;
+        while len > 0 {
+            let start = self.matched + 1 - len;
+            let candidate = self.sequence[start..self.matched]
+                .iter()
+                .copied()
+                .chain(std::iter::once(byte));
+            if candidate.eq(self.sequence[..len].iter().copied()) {
+                return len;
+            }
+            len -= 1;
+        }
+        0
+    }
+}
+
 /// Internal runner for the interaction loop.
 struct InteractRunner<T>
 where
@@ -357,20 +560,34 @@ where
     transport: Arc<Mutex<T>>,
     output_hooks: Vec<OutputPatternHook>,
     input_hooks: Vec<InputPatternHook>,
-    /// Resize hook - used on Unix via SIGWINCH signal handling.
-    /// On Windows, terminal resize events aren't currently supported.
-    #[cfg_attr(windows, allow(dead_code))]
+    /// Resize hook - used on Unix via SIGWINCH signal handling and on
+    /// Windows via console input events.
     resize_hook: Option<ResizeHook>,
     hook_manager: HookManager,
     mode: InteractionMode,
     buffer: String,
     buffer_size: usize,
-    escape_sequence: Option<Vec<u8>>,
+    escape: Option<EscapeMatcher>,
+    /// Line ending passed to pattern hooks.
+    line_ending: LineEnding,
     timeout: Option<Duration>,
-    /// Current terminal size - tracked for resize delta detection on Unix.
-    /// On Windows, terminal resize events aren't currently supported.
-    #[cfg_attr(windows, allow(dead_code))]
+    /// Maximum time without input or output.
+    idle_timeout: Option<Duration>,
+    /// Time of the last input or output.
+    last_activity: std::time::Instant,
+    /// Current terminal size - tracked for resize delta detection.
     current_size: Option<TerminalSize>,
+    /// Recorder capturing the interaction.
+    recorder: Option<Recorder>,
+    /// Redactor applied to output mirrored to stdout.
+    #[cfg(feature = "pii-redaction")]
+    redactor: Option<crate::pii::StreamingRedactor>,
+    /// Time of the last session output.
+    #[cfg(feature = "pii-redaction")]
+    last_output: std::time::Instant,
+    /// Decoder for a non-UTF-8 charset, applied before hooks and matching.
+    #[cfg(feature = "legacy-encoding")]
+    decoder: Option<crate::encoding::StreamingDecoder>,
 }
 
 impl<T> InteractRunner<T>
@@ -386,7 +603,7 @@ where
         hook_manager: HookManager,
         mode: InteractionMode,
         buffer_size: usize,
-        escape_sequence: Option<Vec<u8>>,
+        escape: Option<EscapeMatcher>,
         timeout: Option<Duration>,
     ) -> Self {
         // Get initial terminal size
@@ -401,35 +618,194 @@ where
             mode,
             buffer: String::with_capacity(buffer_size),
             buffer_size,
-            escape_sequence,
+            escape,
+            line_ending: LineEnding::default(),
             timeout,
+            idle_timeout: None,
+            last_activity: std::time::Instant::now(),
             current_size,
+            recorder: None,
+            #[cfg(feature = "pii-redaction")]
+            redactor: None,
+            #[cfg(feature = "pii-redaction")]
+            last_output: std::time::Instant::now(),
+            #[cfg(feature = "legacy-encoding")]
+            decoder: None,
         }
     }
 
     async fn run(&mut self) -> Result<InteractResult> {
+        self.last_activity = std::time::Instant::now();
         #[cfg(unix)]
+        let result = self.run_with_signals().await;
+        #[cfg(not(unix))]
+        let result = self.run_without_signals().await;
+
+        #[cfg(feature = "pii-redaction")]
+        self.flush_mirror().await;
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.stop();
+        }
+        result
+    }
+
+    /// End the interaction if the overall or idle timeout has passed.
+    fn check_timeouts(&self, deadline: Option<std::time::Instant>) -> Option<InteractResult> {
+        let now = std::time::Instant::now();
+        let reason = if deadline.is_some_and(|d| now >= d) {
+            InteractEndReason::Timeout
+        } else if self
+            .idle_timeout
+            .is_some_and(|idle| now.duration_since(self.last_activity) >= idle)
         {
-            self.run_with_signals().await
+            InteractEndReason::Idle
+        } else {
+            return None;
+        };
+
+        self.hook_manager.notify(&InteractionEvent::Ended);
+        Some(InteractResult {
+            reason,
+            buffer: self.buffer.clone(),
+        })
+    }
+
+    /// Decode session output from the configured legacy charset, if any.
+    #[cfg_attr(
+        not(feature = "legacy-encoding"),
+        allow(clippy::needless_pass_by_ref_mut, clippy::unused_self)
+    )]
+    fn decode_output(&mut self, data: &[u8]) -> Vec<u8> {
+        #[cfg(feature = "legacy-encoding")]
+        if let Some(decoder) = self.decoder.as_mut() {
+            return decoder.decode(data).into_bytes();
         }
-        #[cfg(not(unix))]
+        data.to_vec()
+    }
+
+    /// Handle any partial character the decoder held back at EOF.
+    #[cfg(feature = "legacy-encoding")]
+    async fn finish_output(&mut self) -> Result<Option<InteractResult>> {
+        let Some(decoder) = self.decoder.as_mut() else {
+            return Ok(None);
+        };
+        let text = decoder.finish();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        self.handle_output(text.into_bytes()).await
+    }
+
+    /// Run session output through the hooks, mirror it to stdout and check
+    /// the output patterns against it.
+    async fn handle_output(&mut self, data: Vec<u8>) -> Result<Option<InteractResult>> {
+        let processed = self.hook_manager.process_output(data);
+
+        self.hook_manager
+            .notify(&InteractionEvent::Output(processed.clone()));
+
+        // Write to stdout
+        self.write_mirror(&processed).await;
+
+        // Append to buffer for pattern matching
+        if let Ok(s) = std::str::from_utf8(&processed) {
+            self.buffer.push_str(s);
+            // Trim buffer if too large
+            if self.buffer.len() > self.buffer_size {
+                let start = self.buffer.len() - self.buffer_size;
+                self.buffer = self.buffer[start..].to_string();
+            }
+        }
+
+        self.check_output_patterns().await
+    }
+
+    /// Write session output to stdout.
+    async fn write_mirror(&mut self, data: &[u8]) {
+        let shown = self.display_output(data);
+        write_stdout(&shown).await;
+    }
+
+    /// Prepare session output for stdout, redacting and recording it.
+    #[cfg_attr(
+        not(feature = "pii-redaction"),
+        allow(clippy::needless_pass_by_ref_mut)
+    )]
+    fn display_output<'d>(&mut self, data: &'d [u8]) -> std::borrow::Cow<'d, [u8]> {
+        #[cfg(feature = "pii-redaction")]
+        let shown = self.redact_output(data);
+        #[cfg(not(feature = "pii-redaction"))]
+        let shown = std::borrow::Cow::Borrowed(data);
+
+        if let Some(ref recorder) = self.recorder
+            && !shown.is_empty()
         {
-            self.run_without_signals().await
+            recorder.record_output(&shown);
+        }
+        shown
+    }
+
+    /// Send user input to the session, recording it.
+    async fn send_input(&self, data: &[u8]) -> Result<()> {
+        if let Some(ref recorder) = self.recorder {
+            recorder.record_input(data);
+        }
+        let mut transport = self.transport.lock().await;
+        transport.write_all(data).await.map_err(ExpectError::Io)?;
+        transport.flush().await.map_err(ExpectError::Io)
+    }
+
+    /// Pass session output through the redactor, if any.
+    #[cfg(feature = "pii-redaction")]
+    fn redact_output<'d>(&mut self, data: &'d [u8]) -> std::borrow::Cow<'d, [u8]> {
+        self.last_output = std::time::Instant::now();
+        match self.redactor.as_mut() {
+            Some(redactor) => std::borrow::Cow::Owned(redactor.feed(data)),
+            None => std::borrow::Cow::Borrowed(data),
+        }
+    }
+
+    /// Show output held back by the redactor once output has paused.
+    ///
+    /// A prompt without a trailing newline would otherwise stay hidden
+    /// until the user answers it.
+    #[cfg(feature = "pii-redaction")]
+    async fn release_held_output(&mut self) {
+        if self.last_output.elapsed() >= REDACTOR_LULL {
+            self.flush_mirror().await;
+        }
+    }
+
+    /// Write any output held back by the redactor to stdout.
+    #[cfg(feature = "pii-redaction")]
+    async fn flush_mirror(&mut self) {
+        let Some(remaining) = self
+            .redactor
+            .as_mut()
+            .map(crate::pii::StreamingRedactor::flush)
+        else {
+            return;
+        };
+        if remaining.is_empty() {
+            return;
+        }
+        if let Some(ref recorder) = self.recorder {
+            recorder.record_output(remaining.as_bytes());
         }
+        write_stdout(remaining.as_bytes()).await;
     }
 
     /// Run the interaction loop with Unix signal handling (SIGWINCH).
     #[cfg(unix)]
     #[allow(clippy::significant_drop_tightening)]
     async fn run_with_signals(&mut self) -> Result<InteractResult> {
-        use tokio::io::{BufReader, stdin, stdout};
+        use tokio::io::{BufReader, stdin};
 
         self.hook_manager.notify(&InteractionEvent::Started);
 
         let mut stdin = BufReader::new(stdin());
         let mut input_buf = [0u8; 1024];
         let mut output_buf = [0u8; 4096];
-        let mut escape_buf: Vec<u8> = Vec::new();
 
         let deadline = self.timeout.map(|t| std::time::Instant::now() + t);
 
@@ -439,16 +815,12 @@ where
                 .map_err(ExpectError::Io)?;
 
         loop {
-            // Check timeout
-            if let Some(deadline) = deadline
-                && std::time::Instant::now() >= deadline
-            {
-                self.hook_manager.notify(&InteractionEvent::Ended);
-                return Ok(InteractResult {
-                    reason: InteractEndReason::Timeout,
-                    buffer: self.buffer.clone(),
-                });
+            // Check overall and idle timeouts
+            if let Some(result) = self.check_timeouts(deadline) {
+                return Ok(result);
             }
+            #[cfg(feature = "pii-redaction")]
+            self.release_held_output().await;
 
             let read_timeout = self.mode.read_timeout;
             let mut transport = self.transport.lock().await;
@@ -468,6 +840,10 @@ where
                     drop(transport); // Release lock before processing
                     match result {
                         Ok(0) => {
+                            #[cfg(feature = "legacy-encoding")]
+                            if let Some(result) = self.finish_output().await? {
+                                return Ok(result);
+                            }
                             self.hook_manager.notify(&InteractionEvent::Ended);
                             return Ok(InteractResult {
                                 reason: InteractEndReason::Eof,
@@ -475,28 +851,9 @@ where
                             });
                         }
                         Ok(n) => {
-                            let data = &output_buf[..n];
-                            let processed = self.hook_manager.process_output(data.to_vec());
-
-                            self.hook_manager.notify(&InteractionEvent::Output(processed.clone()));
-
-                            // Write to stdout
-                            let mut stdout = stdout();
-                            let _ = stdout.write_all(&processed).await;
-                            let _ = stdout.flush().await;
-
-                            // Append to buffer for pattern matching
-                            if let Ok(s) = std::str::from_utf8(&processed) {
-                                self.buffer.push_str(s);
-                                // Trim buffer if too large
-                                if self.buffer.len() > self.buffer_size {
-                                    let start = self.buffer.len() - self.buffer_size;
-                                    self.buffer = self.buffer[start..].to_string();
-                                }
-                            }
-
-                            // Check output patterns
-                            if let Some(result) = self.check_output_patterns().await? {
+                            self.last_activity = std::time::Instant::now();
+                            let data = self.decode_output(&output_buf[..n]);
+                            if let Some(result) = self.handle_output(data).await? {
                                 return Ok(result);
                             }
                         }
@@ -515,24 +872,18 @@ where
                         if n == 0 {
                             continue;
                         }
+                        self.last_activity = std::time::Instant::now();
 
                         let data = &input_buf[..n];
 
                         // Check for escape sequence
-                        if let Some(ref esc) = self.escape_sequence {
-                            escape_buf.extend_from_slice(data);
-                            if escape_buf.ends_with(esc) {
-                                self.hook_manager.notify(&InteractionEvent::ExitRequested);
-                                self.hook_manager.notify(&InteractionEvent::Ended);
-                                return Ok(InteractResult {
-                                    reason: InteractEndReason::Escape,
-                                    buffer: self.buffer.clone(),
-                                });
-                            }
-                            // Keep only last N bytes where N is escape length
-                            if escape_buf.len() > esc.len() {
-                                escape_buf = escape_buf[escape_buf.len() - esc.len()..].to_vec();
-                            }
+                        if self.escape.as_mut().is_some_and(|esc| esc.feed(data)) {
+                            self.hook_manager.notify(&InteractionEvent::ExitRequested);
+                            self.hook_manager.notify(&InteractionEvent::Ended);
+                            return Ok(InteractResult {
+                                reason: InteractEndReason::Escape,
+                                buffer: self.buffer.clone(),
+                            });
                         }
 
                         // Process through input hooks
@@ -546,9 +897,7 @@ where
                         }
 
                         // Send to session
-                        let mut transport = self.transport.lock().await;
-                        transport.write_all(&processed).await.map_err(ExpectError::Io)?;
-                        transport.flush().await.map_err(ExpectError::Io)?;
+                        self.send_input(&processed).await?;
                     }
                 }
             }
@@ -559,28 +908,31 @@ where
     #[cfg(not(unix))]
     #[allow(clippy::significant_drop_tightening)]
     async fn run_without_signals(&mut self) -> Result<InteractResult> {
-        use tokio::io::{BufReader, stdin, stdout};
+        use tokio::io::{BufReader, stdin};
 
         self.hook_manager.notify(&InteractionEvent::Started);
 
         let mut stdin = BufReader::new(stdin());
         let mut input_buf = [0u8; 1024];
         let mut output_buf = [0u8; 4096];
-        let mut escape_buf: Vec<u8> = Vec::new();
 
         let deadline = self.timeout.map(|t| std::time::Instant::now() + t);
 
+        #[cfg(windows)]
+        let _window_input = self.watch_console_resize();
+
         loop {
-            // Check timeout
-            if let Some(deadline) = deadline {
-                if std::time::Instant::now() >= deadline {
-                    self.hook_manager.notify(&InteractionEvent::Ended);
-                    return Ok(InteractResult {
-                        reason: InteractEndReason::Timeout,
-                        buffer: self.buffer.clone(),
-                    });
-                }
+            #[cfg(windows)]
+            if let Some(result) = self.poll_console_resize().await? {
+                return Ok(result);
+            }
+
+            // Check overall and idle timeouts
+            if let Some(result) = self.check_timeouts(deadline) {
+                return Ok(result);
             }
+            #[cfg(feature = "pii-redaction")]
+            self.release_held_output().await;
 
             let read_timeout = self.mode.read_timeout;
             let mut transport = self.transport.lock().await;
@@ -591,6 +943,10 @@ where
                     drop(transport); // Release lock before processing
                     match result {
                         Ok(0) => {
+                            #[cfg(feature = "legacy-encoding")]
+                            if let Some(result) = self.finish_output().await? {
+                                return Ok(result);
+                            }
                             self.hook_manager.notify(&InteractionEvent::Ended);
                             return Ok(InteractResult {
                                 reason: InteractEndReason::Eof,
@@ -598,28 +954,9 @@ where
                             });
                         }
                         Ok(n) => {
-                            let data = &output_buf[..n];
-                            let processed = self.hook_manager.process_output(data.to_vec());
-
-                            self.hook_manager.notify(&InteractionEvent::Output(processed.clone()));
-
-                            // Write to stdout
-                            let mut stdout = stdout();
-                            let _ = stdout.write_all(&processed).await;
-                            let _ = stdout.flush().await;
-
-                            // Append to buffer for pattern matching
-                            if let Ok(s) = std::str::from_utf8(&processed) {
-                                self.buffer.push_str(s);
-                                // Trim buffer if too large
-                                if self.buffer.len() > self.buffer_size {
-                                    let start = self.buffer.len() - self.buffer_size;
-                                    self.buffer = self.buffer[start..].to_string();
-                                }
-                            }
-
-                            // Check output patterns
-                            if let Some(result) = self.check_output_patterns().await? {
+                            self.last_activity = std::time::Instant::now();
+                            let data = self.decode_output(&output_buf[..n]);
+                            if let Some(result) = self.handle_output(data).await? {
                                 return Ok(result);
                             }
                         }
@@ -638,24 +975,18 @@ where
                         if n == 0 {
                             continue;
                         }
+                        self.last_activity = std::time::Instant::now();
 
                         let data = &input_buf[..n];
 
                         // Check for escape sequence
-                        if let Some(ref esc) = self.escape_sequence {
-                            escape_buf.extend_from_slice(data);
-                            if escape_buf.ends_with(esc) {
-                                self.hook_manager.notify(&InteractionEvent::ExitRequested);
-                                self.hook_manager.notify(&InteractionEvent::Ended);
-                                return Ok(InteractResult {
-                                    reason: InteractEndReason::Escape,
-                                    buffer: self.buffer.clone(),
-                                });
-                            }
-                            // Keep only last N bytes where N is escape length
-                            if escape_buf.len() > esc.len() {
-                                escape_buf = escape_buf[escape_buf.len() - esc.len()..].to_vec();
-                            }
+                        if self.escape.as_mut().is_some_and(|esc| esc.feed(data)) {
+                            self.hook_manager.notify(&InteractionEvent::ExitRequested);
+                            self.hook_manager.notify(&InteractionEvent::Ended);
+                            return Ok(InteractResult {
+                                reason: InteractEndReason::Escape,
+                                buffer: self.buffer.clone(),
+                            });
                         }
 
                         // Process through input hooks
@@ -669,9 +1000,7 @@ where
                         }
 
                         // Send to session
-                        let mut transport = self.transport.lock().await;
-                        transport.write_all(&processed).await.map_err(ExpectError::Io)?;
-                        transport.flush().await.map_err(ExpectError::Io)?;
+                        self.send_input(&processed).await?;
                     }
                 }
             }
@@ -692,6 +1021,7 @@ where
                     after,
                     buffer: &self.buffer,
                     pattern_index: index,
+                    line_ending: self.line_ending,
                 };
 
                 match (hook.callback)(&ctx) {
@@ -744,6 +1074,7 @@ where
                     after,
                     buffer: &input_str,
                     pattern_index: index,
+                    line_ending: self.line_ending,
                 };
 
                 match (hook.callback)(&ctx) {
@@ -775,16 +1106,42 @@ where
 
     /// Handle a window resize event.
     ///
-    /// This is called on Unix when SIGWINCH is received. On Windows, terminal
-    /// resize events aren't currently supported via signals.
-    #[cfg_attr(windows, allow(dead_code))]
-    #[allow(clippy::significant_drop_tightening)]
+    /// This is called on Unix when SIGWINCH is received.
+    #[cfg(unix)]
     async fn handle_resize(&mut self) -> Result<Option<InteractResult>> {
         // Get the new terminal size
         let Ok(new_size) = super::terminal::Terminal::size() else {
             return Ok(None); // Ignore if we can't get size
         };
+        self.apply_resize(new_size).await
+    }
+
+    /// Ask the console to report window resizes as input records.
+    ///
+    /// The returned guard restores the console mode when dropped.
+    #[cfg(windows)]
+    fn watch_console_resize(&mut self) -> Option<super::terminal::console::WindowInput> {
+        if let Some(size) = super::terminal::console::window_size() {
+            self.current_size = Some(size);
+        }
+        super::terminal::console::WindowInput::enable()
+    }
+
+    /// Handle a console resize on Windows, if one is pending.
+    #[cfg(windows)]
+    async fn poll_console_resize(&mut self) -> Result<Option<InteractResult>> {
+        match super::terminal::console::poll_resize(self.current_size) {
+            Some(size) => self.apply_resize(size).await,
+            None => Ok(None),
+        }
+    }
 
+    /// Notify hooks and the recorder of a new terminal size.
+    ///
+    /// Shared by the SIGWINCH handler on Unix and console event polling on
+    /// Windows.
+    #[allow(clippy::significant_drop_tightening)]
+    async fn apply_resize(&mut self, new_size: TerminalSize) -> Result<Option<InteractResult>> {
         // Build the context with previous size
         let ctx = ResizeContext {
             size: new_size,
@@ -797,6 +1154,10 @@ where
             rows: new_size.rows,
         });
 
+        if let Some(ref recorder) = self.recorder {
+            recorder.record_resize(new_size.cols, new_size.rows);
+        }
+
         // Update our tracked size
         self.current_size = Some(new_size);
 
@@ -829,3 +1190,302 @@ where
         Ok(None)
     }
 }
+
+/// Write data to stdout, ignoring errors.
+async fn write_stdout(data: &[u8]) {
+    if data.is_empty() {
+        return;
+    }
+    let mut stdout = tokio::io::stdout();
+    let _ = stdout.write_all(data).await;
+    let _ = stdout.flush().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn records_output_and_input_in_order() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let transport = Arc::new(Mutex::new(client));
+        let recorder = Recorder::new(80, 24);
+        let transcript = recorder.transcript();
+        let mut runner = InteractBuilder::new(&transport)
+            .record_to(recorder)
+            .into_runner();
+
+        runner.display_output(b"$ ");
+        runner.send_input(b"ls\n").await.unwrap();
+        runner.display_output(b"file1\r\n$ ");
+        runner.recorder.as_mut().unwrap().stop();
+
+        let mut sent = [0u8; 3];
+        server.read_exact(&mut sent).await.unwrap();
+        assert_eq!(&sent, b"ls\n");
+
+        let transcript = transcript.lock().unwrap().clone();
+        let kinds: Vec<_> = transcript.events.iter().map(|e| e.event_type).collect();
+        assert_eq!(
+            kinds,
+            [
+                crate::transcript::EventType::Output,
+                crate::transcript::EventType::Input,
+                crate::transcript::EventType::Output,
+            ]
+        );
+        assert_eq!(transcript.events[1].data, b"ls\n");
+        assert!(
+            transcript
+                .events
+                .windows(2)
+                .all(|w| w[0].timestamp <= w[1].timestamp)
+        );
+        assert!(transcript.metadata.duration.is_some());
+    }
+
+    #[cfg(feature = "legacy-encoding")]
+    #[tokio::test]
+    async fn output_is_decoded_before_matching() {
+        let (client, _server) = tokio::io::duplex(1024);
+        let transport = Arc::new(Mutex::new(client));
+        let decoder =
+            crate::config::EncodingConfig::new(crate::config::Encoding::ShiftJis).decoder();
+        let mut runner = InteractBuilder::new(&transport)
+            .with_decoder(decoder)
+            .into_runner();
+
+        // "テスト" in Shift-JIS, split inside the second character
+        let bytes = b"\x83\x65\x83\x58\x83\x67";
+        let mut text = runner.decode_output(&bytes[..3]);
+        text.extend(runner.decode_output(&bytes[3..]));
+        assert_eq!(String::from_utf8(text).unwrap(), "テスト");
+    }
+
+    #[test]
+    fn context_send_line_uses_line_ending() {
+        let ctx = |line_ending| InteractContext {
+            matched: "",
+            before: "",
+            after: "",
+            buffer: "",
+            pattern_index: 0,
+            line_ending,
+        };
+        let sent = |action| match action {
+            InteractAction::Send(data) => data,
+            other => panic!("expected Send, got {other:?}"),
+        };
+
+        assert_eq!(sent(ctx(LineEnding::CrLf).send_line("dir")), b"dir\r\n");
+        assert_eq!(sent(ctx(LineEnding::Lf).send_line("ls")), b"ls\n");
+    }
+
+    #[tokio::test]
+    async fn idle_timeout_ends_quiet_session() {
+        // start() is not driven here: its stdin reader cannot be cancelled
+        // and would keep the test runtime from shutting down.
+        let (client, _server) = tokio::io::duplex(1024);
+        let transport = Arc::new(Mutex::new(client));
+        let mut runner = InteractBuilder::new(&transport)
+            .with_timeout(Duration::from_secs(10))
+            .with_idle_timeout(Duration::from_millis(50))
+            .into_runner();
+        runner.last_activity = std::time::Instant::now();
+        let deadline = runner.timeout.map(|t| std::time::Instant::now() + t);
+
+        assert!(runner.check_timeouts(deadline).is_none());
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        let result = runner.check_timeouts(deadline).unwrap();
+        assert!(matches!(result.reason, InteractEndReason::Idle));
+    }
+
+    #[tokio::test]
+    async fn activity_resets_idle_timer() {
+        let (client, _server) = tokio::io::duplex(1024);
+        let transport = Arc::new(Mutex::new(client));
+        let mut runner = InteractBuilder::new(&transport)
+            .with_idle_timeout(Duration::from_secs(1))
+            .into_runner();
+
+        runner.last_activity = std::time::Instant::now()
+            .checked_sub(Duration::from_secs(2))
+            .unwrap();
+        let result = runner.check_timeouts(None).unwrap();
+        assert!(matches!(result.reason, InteractEndReason::Idle));
+
+        runner.last_activity = std::time::Instant::now();
+        assert!(runner.check_timeouts(None).is_none());
+
+        // The overall deadline takes precedence
+        let deadline = Some(std::time::Instant::now());
+        let result = runner.check_timeouts(deadline).unwrap();
+        assert!(matches!(result.reason, InteractEndReason::Timeout));
+    }
+
+    #[test]
+    fn escape_split_across_reads() {
+        let mut esc = EscapeMatcher::new(b"~.".to_vec(), None);
+        assert!(!esc.feed(b"ls\r~"));
+        assert!(esc.feed(b"."));
+
+        let mut esc = EscapeMatcher::new(b"\x1d\x1dq".to_vec(), None);
+        assert!(!esc.feed(b"\x1d"));
+        assert!(!esc.feed(b"\x1d"));
+        assert!(esc.feed(b"q"));
+    }
+
+    #[test]
+    fn escape_mid_chunk_and_overlap() {
+        let mut esc = EscapeMatcher::new(b"~.".to_vec(), None);
+        assert!(esc.feed(b"abc~.def"));
+
+        // A repeated first byte keeps the partial match alive
+        let mut esc = EscapeMatcher::new(b"aab".to_vec(), None);
+        assert!(!esc.feed(b"a"));
+        assert!(!esc.feed(b"aa"));
+        assert!(esc.feed(b"b"));
+
+        let mut esc = EscapeMatcher::new(b"~.".to_vec(), None);
+        assert!(!esc.feed(b"~x."));
+    }
+
+    #[test]
+    fn escape_inter_byte_timeout() {
+        let start = std::time::Instant::now();
+        let timeout = Some(Duration::from_millis(500));
+
+        let mut esc = EscapeMatcher::new(b"\x1dq".to_vec(), timeout);
+        assert!(!esc.feed_at(b"\x1d", start));
+        assert!(esc.feed_at(b"q", start + Duration::from_millis(100)));
+
+        let mut esc = EscapeMatcher::new(b"\x1dq".to_vec(), timeout);
+        assert!(!esc.feed_at(b"\x1d", start));
+        assert!(!esc.feed_at(b"q", start + Duration::from_secs(2)));
+        assert!(!esc.feed_at(b"\x1d", start + Duration::from_secs(3)));
+        assert!(esc.feed_at(b"q", start + Duration::from_millis(3200)));
+    }
+
+    #[test]
+    fn escape_single_byte_default() {
+        let mut esc = EscapeMatcher::new(vec![0x1d], Some(Duration::ZERO));
+        assert!(!esc.feed(b"hello"));
+        assert!(esc.feed(b"x\x1d"));
+        assert!(!EscapeMatcher::new(Vec::new(), None).feed(b"anything"));
+    }
+
+    #[tokio::test]
+    async fn apply_resize_builds_context() {
+        let (client, _server) = tokio::io::duplex(1024);
+        let transport = Arc::new(Mutex::new(client));
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = Recorder::new(80, 24);
+        let transcript = recorder.transcript();
+        let mut runner = InteractBuilder::new(&transport)
+            .on_resize({
+                let seen = Arc::clone(&seen);
+                move |ctx| {
+                    seen.lock().unwrap().push(*ctx);
+                    InteractAction::Continue
+                }
+            })
+            .record_to(recorder)
+            .into_runner();
+        runner.current_size = Some(TerminalSize::new(80, 24));
+
+        assert!(
+            runner
+                .apply_resize(TerminalSize::new(120, 40))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        runner
+            .apply_resize(TerminalSize::new(100, 30))
+            .await
+            .unwrap();
+
+        let seen = seen.lock().unwrap().clone();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0].size, TerminalSize::new(120, 40));
+        assert_eq!(seen[0].previous, Some(TerminalSize::new(80, 24)));
+        assert_eq!(seen[1].previous, Some(TerminalSize::new(120, 40)));
+        assert_eq!(runner.current_size, Some(TerminalSize::new(100, 30)));
+
+        let transcript = transcript.lock().unwrap().clone();
+        assert_eq!(transcript.events.len(), 2);
+        assert_eq!(transcript.events[1].data, b"100x30");
+    }
+
+    #[cfg(feature = "pii-redaction")]
+    #[tokio::test]
+    async fn redactor_masks_mirrored_output() {
+        let (client, _server) = tokio::io::duplex(1024);
+        let transport = Arc::new(Mutex::new(client));
+        let mut runner = InteractBuilder::new(&transport)
+            .with_redactor(crate::pii::PiiRedactor::new())
+            .into_runner();
+
+        // The SSN arrives split across two reads
+        let mut shown = runner.redact_output(b"ssn: 123-45-").into_owned();
+        shown.extend_from_slice(&runner.redact_output(b"6789\r\n$ "));
+        shown.extend_from_slice(runner.redactor.as_mut().unwrap().flush().as_bytes());
+
+        let shown = String::from_utf8(shown).unwrap();
+        assert_eq!(shown, "ssn: [SSN REDACTED]\r\n$ ");
+    }
+
+    #[cfg(feature = "pii-redaction")]
+    #[tokio::test]
+    async fn redactor_holds_secret_split_across_quiet_period() {
+        let (client, _server) = tokio::io::duplex(1024);
+        let transport = Arc::new(Mutex::new(client));
+        let mut runner = InteractBuilder::new(&transport)
+            .with_redactor(crate::pii::PiiRedactor::new())
+            .into_runner();
+
+        let mut shown = runner.redact_output(b"ssn: 123-45-").into_owned();
+        // A pause shorter than the lull releases nothing of the partial SSN
+        runner.release_held_output().await;
+        assert!(!shown.contains(&b'1'));
+        shown.extend_from_slice(&runner.redact_output(b"6789\r\n"));
+
+        let shown = String::from_utf8(shown).unwrap();
+        assert_eq!(shown, "ssn: [SSN REDACTED]\r\n");
+    }
+
+    #[cfg(feature = "pii-redaction")]
+    #[tokio::test]
+    async fn redactor_shows_trailing_prompt_after_lull() {
+        let (client, _server) = tokio::io::duplex(1024);
+        let transport = Arc::new(Mutex::new(client));
+        let recorder = Recorder::new(80, 24);
+        let transcript = recorder.transcript();
+        let mut runner = InteractBuilder::new(&transport)
+            .with_redactor(crate::pii::PiiRedactor::new())
+            .record_to(recorder)
+            .into_runner();
+
+        runner.write_mirror(b"Last login: today\nPassword: ").await;
+        let shown = |transcript: &Arc<std::sync::Mutex<crate::transcript::Transcript>>| {
+            let events = transcript.lock().unwrap().events.clone();
+            let data: Vec<u8> = events.into_iter().flat_map(|e| e.data).collect();
+            String::from_utf8(data).unwrap()
+        };
+        assert_eq!(shown(&transcript), "Last login: today\n");
+
+        runner.last_output -= REDACTOR_LULL;
+        runner.release_held_output().await;
+        assert_eq!(shown(&transcript), "Last login: today\nPassword: ");
+    }
+
+    #[cfg(feature = "pii-redaction")]
+    #[tokio::test]
+    async fn output_unchanged_without_redactor() {
+        let (client, _server) = tokio::io::duplex(1024);
+        let transport = Arc::new(Mutex::new(client));
+        let mut runner = InteractBuilder::new(&transport).into_runner();
+
+        assert_eq!(&*runner.redact_output(b"123-45-6789"), b"123-45-6789");
+    }
+}
@@ -36,6 +36,16 @@ impl TerminalSize {
     pub const fn new(cols: u16, rows: u16) -> Self {
         Self { cols, rows }
     }
+
+    /// Convert a Windows console extent (`COORD` or window rectangle size).
+    ///
+    /// Returns `None` if either dimension is not positive.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn from_console_extent(width: i16, height: i16) -> Option<Self> {
+        let cols = u16::try_from(width).ok().filter(|&c| c > 0)?;
+        let rows = u16::try_from(height).ok().filter(|&r| r > 0)?;
+        Some(Self::new(cols, rows))
+    }
 }
 
 /// Terminal state for saving/restoring.
@@ -190,6 +200,171 @@ pub fn write_immediate(data: &[u8]) -> io::Result<()> {
     stdout.flush()
 }
 
+/// Windows console resize detection.
+///
+/// With `ENABLE_WINDOW_INPUT` set, the console queues a
+/// `WINDOW_BUFFER_SIZE_EVENT` input record when the window is resized.
+/// Records are peeked first so key events stay queued for stdin. A pending
+/// stdin read may discard resize records before they are seen, so the
+/// visible window size is compared on every poll as well.
+#[cfg(windows)]
+#[allow(unsafe_code)]
+pub(crate) mod console {
+    use windows_sys::Win32::Foundation::{HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Console::{
+        CONSOLE_MODE, CONSOLE_SCREEN_BUFFER_INFO, ENABLE_WINDOW_INPUT, GetConsoleMode,
+        GetConsoleScreenBufferInfo, GetStdHandle, INPUT_RECORD, KEY_EVENT, PeekConsoleInputW,
+        ReadConsoleInputW, STD_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE, SetConsoleMode,
+        WINDOW_BUFFER_SIZE_EVENT,
+    };
+
+    use super::TerminalSize;
+
+    /// Number of input records inspected per poll.
+    const PEEK_RECORDS: usize = 32;
+
+    /// Get a standard handle, if it is valid.
+    fn std_handle(which: STD_HANDLE) -> Option<HANDLE> {
+        // SAFETY: GetStdHandle has no preconditions.
+        let handle = unsafe { GetStdHandle(which) };
+        (!handle.is_null() && handle != INVALID_HANDLE_VALUE).then_some(handle)
+    }
+
+    /// Get the size of the visible console window.
+    pub(crate) fn window_size() -> Option<TerminalSize> {
+        let handle = std_handle(STD_OUTPUT_HANDLE)?;
+        let mut info = CONSOLE_SCREEN_BUFFER_INFO::default();
+        // SAFETY: handle is a valid console handle and info is a valid out pointer.
+        if unsafe { GetConsoleScreenBufferInfo(handle, &raw mut info) } == 0 {
+            return None;
+        }
+        let window = info.srWindow;
+        TerminalSize::from_console_extent(
+            window.Right - window.Left + 1,
+            window.Bottom - window.Top + 1,
+        )
+    }
+
+    /// Get the size carried by a resize input record.
+    pub(crate) fn resize_from_record(record: &INPUT_RECORD) -> Option<TerminalSize> {
+        if u32::from(record.EventType) != WINDOW_BUFFER_SIZE_EVENT {
+            return None;
+        }
+        // SAFETY: the event type says the union holds a WINDOW_BUFFER_SIZE_RECORD.
+        let size = unsafe { record.Event.WindowBufferSizeEvent.dwSize };
+        TerminalSize::from_console_extent(size.X, size.Y)
+    }
+
+    /// Consume queued non-key input records, returning the last resize.
+    fn take_resize_event() -> Option<TerminalSize> {
+        let handle = std_handle(STD_INPUT_HANDLE)?;
+        let mut records = [INPUT_RECORD::default(); PEEK_RECORDS];
+        let mut count = 0u32;
+        // SAFETY: records holds PEEK_RECORDS entries and count is a valid out pointer.
+        let peeked = unsafe {
+            PeekConsoleInputW(
+                handle,
+                records.as_mut_ptr(),
+                PEEK_RECORDS as u32,
+                &raw mut count,
+            )
+        };
+        if peeked == 0 {
+            return None;
+        }
+
+        // Leave key events (and everything after them) for stdin
+        let leading = records[..count as usize]
+            .iter()
+            .take_while(|r| u32::from(r.EventType) != KEY_EVENT)
+            .count();
+        if leading == 0 {
+            return None;
+        }
+
+        let mut read = 0u32;
+        // SAFETY: as above; no more records are read than were peeked.
+        let consumed = unsafe {
+            ReadConsoleInputW(handle, records.as_mut_ptr(), leading as u32, &raw mut read)
+        };
+        if consumed == 0 {
+            return None;
+        }
+        records[..read as usize]
+            .iter()
+            .rev()
+            .find_map(resize_from_record)
+    }
+
+    /// Check whether the console has been resized since `current`.
+    ///
+    /// Returns the new size if it differs from `current`.
+    pub(crate) fn poll_resize(current: Option<TerminalSize>) -> Option<TerminalSize> {
+        let event = take_resize_event();
+        let size = window_size().or(event)?;
+        (Some(size) != current).then_some(size)
+    }
+
+    /// Enables console window input events, restoring the mode on drop.
+    pub(crate) struct WindowInput {
+        /// Console mode before window input was enabled.
+        mode: CONSOLE_MODE,
+    }
+
+    impl WindowInput {
+        /// Enable `ENABLE_WINDOW_INPUT` on stdin, if it is a console.
+        pub(crate) fn enable() -> Option<Self> {
+            let handle = std_handle(STD_INPUT_HANDLE)?;
+            let mut mode: CONSOLE_MODE = 0;
+            // SAFETY: handle is valid and mode is a valid out pointer.
+            if unsafe { GetConsoleMode(handle, &raw mut mode) } == 0 {
+                return None;
+            }
+            // SAFETY: handle is a valid console input handle.
+            if unsafe { SetConsoleMode(handle, mode | ENABLE_WINDOW_INPUT) } == 0 {
+                return None;
+            }
+            Some(Self { mode })
+        }
+    }
+
+    impl Drop for WindowInput {
+        fn drop(&mut self) {
+            if let Some(handle) = std_handle(STD_INPUT_HANDLE) {
+                // SAFETY: handle is a valid console input handle.
+                unsafe {
+                    SetConsoleMode(handle, self.mode);
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use windows_sys::Win32::System::Console::{COORD, WINDOW_BUFFER_SIZE_RECORD};
+
+        use super::*;
+
+        #[test]
+        fn resize_record_conversion() {
+            let mut record = INPUT_RECORD {
+                EventType: WINDOW_BUFFER_SIZE_EVENT as u16,
+                ..Default::default()
+            };
+            record.Event.WindowBufferSizeEvent = WINDOW_BUFFER_SIZE_RECORD {
+                dwSize: COORD { X: 120, Y: 40 },
+            };
+            assert_eq!(
+                resize_from_record(&record),
+                Some(TerminalSize::new(120, 40))
+            );
+
+            record.EventType = KEY_EVENT as u16;
+            assert_eq!(resize_from_record(&record), None);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,6 +383,16 @@ mod tests {
         assert_eq!(size.rows, 24);
     }
 
+    #[test]
+    fn terminal_size_from_console_extent() {
+        assert_eq!(
+            TerminalSize::from_console_extent(120, 40),
+            Some(TerminalSize::new(120, 40))
+        );
+        assert_eq!(TerminalSize::from_console_extent(0, 40), None);
+        assert_eq!(TerminalSize::from_console_extent(120, -1), None);
+    }
+
     #[test]
     fn terminal_running_flag() {
         let term = Terminal::new();
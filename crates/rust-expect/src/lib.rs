@@ -29,7 +29,7 @@
 //! ```
 
 // Re-export macros
-pub use rust_expect_macros::{dialog, patterns, regex, timeout};
+pub use rust_expect_macros::{bytes_regex, dialog, patterns, regex, timeout};
 
 // Core types (Phase 4)
 pub mod config;
@@ -77,15 +77,18 @@ pub use config::{
     BufferConfig, EncodingConfig, HumanTypingConfig, InteractConfig, LineEnding, LogFormat,
     LoggingConfig, SessionConfig, TimeoutConfig,
 };
-pub use dialog::{Dialog, DialogBuilder, DialogStep};
+pub use dialog::{Dialog, DialogBranch, DialogBuilder, DialogCapture, DialogRepeat, DialogStep};
+#[cfg(feature = "legacy-encoding")]
+pub use encoding::StreamingDecoder;
 pub use encoding::{
-    DetectedEncoding, EncodedText, LineEndingStyle, decode_utf8_lossy, detect_encoding_from_env,
-    detect_line_ending, normalize_line_endings, strip_ansi,
+    BareCr, DetectedEncoding, EncodedText, LineEndingStyle, decode_utf8_lossy,
+    detect_encoding_from_env, detect_encoding_from_locale, detect_line_ending,
+    normalize_line_endings, normalize_line_endings_with, strip_ansi,
 };
 pub use error::{ExpectError, Result, SpawnError};
 pub use expect::{
-    CacheStats, CompiledRegex, GLOBAL_CACHE, Matcher, Pattern, PatternManager, PatternSet,
-    RegexCache, RingBuffer, get_regex,
+    CacheStats, CompiledGlob, CompiledRegex, GLOBAL_CACHE, MatchAnchor, Matcher, Pattern,
+    PatternManager, PatternSet, RegexCache, RingBuffer, get_regex,
 };
 pub use health::{HealthChecker, HealthStatus};
 pub use interact::{
@@ -97,19 +100,20 @@ pub use metrics::{Counter, Gauge, Histogram, MetricsRegistry, SessionMetrics};
 #[cfg(feature = "mock")]
 pub use mock::{MockBuilder, MockSession, MockTransport, Scenario};
 pub use multi::{
-    GroupBuilder, GroupManager, GroupResult, MultiSessionManager, PatternSelector, ReadyType,
-    SelectResult, SendResult, SessionGroup,
+    ExpectAllResult, GroupBuilder, GroupManager, GroupResult, ManagedSession, MultiSessionManager,
+    PatternSelector, ReadyType, SelectResult, SendResult, SessionGroup,
 };
 #[cfg(feature = "pii-redaction")]
 pub use pii::{PiiDetector, PiiRedactor, PiiType};
 #[cfg(feature = "screen")]
 pub use screen::{Attributes, Cell, ScreenBuffer};
-pub use send::{AnsiSend, BasicSend, HumanTyper, Sender};
+pub use send::{AnsiSend, BasicSend, HumanTyper, SendFileMode, Sender};
 pub use session::{QuickSession, Session, SessionBuilder};
 pub use sync::{SyncSession, block_on};
 pub use transcript::{Player, Recorder, Transcript, TranscriptEvent};
 pub use types::{
-    ControlChar, Dimensions, ExpectResult, Match, ProcessExitStatus, SessionId, SessionState,
+    CaptureGroups, ControlChar, Dimensions, ExpectResult, Match, ProcessExitStatus, SessionId,
+    SessionState,
 };
 pub use util::{Backpressure, Deadline, TimeoutExt};
 
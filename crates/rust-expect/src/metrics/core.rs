@@ -3,7 +3,8 @@
 //! This module provides metrics collection for monitoring session
 //! performance and behavior.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
@@ -41,6 +42,15 @@ impl Counter {
     pub fn reset(&self) {
         self.value.store(0, Ordering::Relaxed);
     }
+
+    /// Read the value and reset to zero in one atomic step.
+    ///
+    /// Increments racing with the call land either in the returned value
+    /// or in the next one, never in neither.
+    #[must_use]
+    pub fn take(&self) -> u64 {
+        self.value.swap(0, Ordering::Relaxed)
+    }
 }
 
 /// A gauge metric.
@@ -76,6 +86,15 @@ impl Gauge {
     pub fn get(&self) -> u64 {
         self.value.load(Ordering::Relaxed)
     }
+
+    /// Read the value for interval reporting.
+    ///
+    /// A gauge holds a level rather than a running total, so unlike
+    /// [`Counter::take`] this leaves the value in place.
+    #[must_use]
+    pub fn snapshot(&self) -> u64 {
+        self.get()
+    }
 }
 
 /// A histogram for measuring distributions.
@@ -85,10 +104,8 @@ pub struct Histogram {
     buckets: Vec<f64>,
     /// Counts per bucket.
     counts: Vec<AtomicU64>,
-    /// Sum of all values.
+    /// Sum of all values, as `f64` bits.
     sum: AtomicU64,
-    /// Total count.
-    count: AtomicU64,
 }
 
 impl Histogram {
@@ -108,7 +125,6 @@ impl Histogram {
             buckets,
             counts,
             sum: AtomicU64::new(0),
-            count: AtomicU64::new(0),
         }
     }
 
@@ -123,18 +139,38 @@ impl Histogram {
         self.counts[idx].fetch_add(1, Ordering::Relaxed);
 
         // Update sum (as bits for f64 storage)
-        let bits = value.to_bits();
-        self.sum.fetch_add(bits, Ordering::Relaxed);
-        self.count.fetch_add(1, Ordering::Relaxed);
+        let _ = self
+            .sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
     }
 
     /// Get the count.
+    ///
+    /// This is the total of the bucket counts, so it always agrees with
+    /// them, even while [`take`](Self::take) runs.
     #[must_use]
     pub fn count(&self) -> u64 {
-        self.count.load(Ordering::Relaxed)
+        self.counts.iter().map(|c| c.load(Ordering::Relaxed)).sum()
+    }
+
+    /// Get the sum of all observed values.
+    #[must_use]
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.sum.load(Ordering::Relaxed))
+    }
+
+    /// Get the bucket upper bounds.
+    #[must_use]
+    pub fn buckets(&self) -> &[f64] {
+        &self.buckets
     }
 
     /// Get bucket counts.
+    ///
+    /// There is one count per bucket, plus a last one for values above
+    /// every bound. Counts are per bucket, not cumulative.
     #[must_use]
     pub fn bucket_counts(&self) -> Vec<u64> {
         self.counts
@@ -142,6 +178,101 @@ impl Histogram {
             .map(|c| c.load(Ordering::Relaxed))
             .collect()
     }
+
+    /// Read the bucket counts and sum, resetting them to zero.
+    ///
+    /// Each bucket is swapped atomically, so no observation is lost, but
+    /// one racing with the call may have its count and its share of the
+    /// sum split across this snapshot and the next.
+    #[must_use]
+    pub fn take(&self) -> HistogramSnapshot {
+        let counts: Vec<u64> = self
+            .counts
+            .iter()
+            .map(|c| c.swap(0, Ordering::Relaxed))
+            .collect();
+        let sum = f64::from_bits(self.sum.swap(0, Ordering::Relaxed));
+        let count = counts.iter().sum();
+        HistogramSnapshot {
+            buckets: self.buckets.clone(),
+            counts,
+            sum,
+            count,
+        }
+    }
+
+    /// Estimate the `q`-quantile of the observed values, for `q` in `0..=1`.
+    ///
+    /// Only bucket counts are stored, so the estimate finds the bucket
+    /// holding the rank and interpolates linearly inside it, the way
+    /// Prometheus' `histogram_quantile` does. The error is bounded by the
+    /// width of that bucket: choose bounds that are dense where precision
+    /// matters. Ranks in the overflow bucket report the highest bound, and
+    /// the first bucket is assumed to start at zero.
+    ///
+    /// Returns `None` if nothing has been observed, there are no bucket
+    /// bounds, or `q` is outside `0..=1`.
+    #[must_use]
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        let last = *self.buckets.last()?;
+        let counts = self.bucket_counts();
+        let total: u64 = counts.iter().sum();
+        if total == 0 {
+            return None;
+        }
+
+        let rank = q * total as f64;
+        let mut below = 0;
+        for (i, &count) in counts.iter().enumerate() {
+            if count > 0 && (below + count) as f64 >= rank {
+                let Some(&upper) = self.buckets.get(i) else {
+                    return Some(last);
+                };
+                let lower = match i.checked_sub(1) {
+                    Some(prev) => self.buckets[prev],
+                    None => upper.min(0.0),
+                };
+                let fraction = (rank - below as f64) / count as f64;
+                return Some((upper - lower).mul_add(fraction.max(0.0), lower));
+            }
+            below += count;
+        }
+        Some(last)
+    }
+
+    /// Estimate the median; see [`quantile`](Self::quantile).
+    #[must_use]
+    pub fn p50(&self) -> Option<f64> {
+        self.quantile(0.5)
+    }
+
+    /// Estimate the 90th percentile; see [`quantile`](Self::quantile).
+    #[must_use]
+    pub fn p90(&self) -> Option<f64> {
+        self.quantile(0.9)
+    }
+
+    /// Estimate the 99th percentile; see [`quantile`](Self::quantile).
+    #[must_use]
+    pub fn p99(&self) -> Option<f64> {
+        self.quantile(0.99)
+    }
+}
+
+/// Bucket counts taken from a [`Histogram`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramSnapshot {
+    /// Bucket upper bounds.
+    pub buckets: Vec<f64>,
+    /// Per-bucket counts, with a last one for values above every bound.
+    pub counts: Vec<u64>,
+    /// Sum of the values counted.
+    pub sum: f64,
+    /// Number of values counted.
+    pub count: u64,
 }
 
 impl Default for Histogram {
@@ -213,6 +344,18 @@ impl SessionMetrics {
         Self::default()
     }
 
+    /// Get the histogram of expect durations, in seconds.
+    #[must_use]
+    pub const fn expect_latency(&self) -> &Histogram {
+        &self.expect_duration
+    }
+
+    /// Get the counter of expects that timed out.
+    #[must_use]
+    pub const fn timeouts(&self) -> &Counter {
+        &self.timeouts
+    }
+
     /// Report a snapshot.
     #[must_use]
     pub fn snapshot(&self) -> MetricsSnapshot {
@@ -253,6 +396,7 @@ pub struct MetricsRegistry {
     counters: Arc<Mutex<HashMap<String, Arc<Counter>>>>,
     gauges: Arc<Mutex<HashMap<String, Arc<Gauge>>>>,
     histograms: Arc<Mutex<HashMap<String, Arc<Histogram>>>>,
+    sessions: Arc<Mutex<HashMap<String, Arc<SessionMetrics>>>>,
 }
 
 impl MetricsRegistry {
@@ -300,6 +444,312 @@ impl MetricsRegistry {
             .or_insert_with(|| Arc::new(Histogram::new()))
             .clone()
     }
+
+    /// Get or create a histogram with the given bucket upper bounds.
+    ///
+    /// The bounds only apply when the histogram is created; an existing
+    /// histogram of that name is returned with its own buckets.
+    #[must_use]
+    pub fn histogram_with_buckets(&self, name: &str, buckets: Vec<f64>) -> Arc<Histogram> {
+        let mut histograms = self
+            .histograms
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        histograms
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(Histogram::with_buckets(buckets)))
+            .clone()
+    }
+
+    /// Get or create the metrics of a session.
+    ///
+    /// They are exported labelled with `session_id`.
+    #[must_use]
+    pub fn session(&self, session_id: &str) -> Arc<SessionMetrics> {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| Arc::new(SessionMetrics::new()))
+            .clone()
+    }
+
+    /// Take an interval snapshot of every named metric.
+    ///
+    /// Counters and histograms are reset as they are read, so the next
+    /// call reports only what happened since this one; gauges are read
+    /// as they are. Session metrics are not included.
+    #[must_use]
+    pub fn drain(&self) -> RegistrySnapshot {
+        RegistrySnapshot {
+            counters: sorted(&self.counters)
+                .into_iter()
+                .map(|(name, counter)| (name, counter.take()))
+                .collect(),
+            gauges: sorted(&self.gauges)
+                .into_iter()
+                .map(|(name, gauge)| (name, gauge.snapshot()))
+                .collect(),
+            histograms: sorted(&self.histograms)
+                .into_iter()
+                .map(|(name, histogram)| (name, histogram.take()))
+                .collect(),
+        }
+    }
+
+    /// Render every metric in the Prometheus text exposition format.
+    ///
+    /// Names are sanitized to `[a-zA-Z_:][a-zA-Z0-9_:]*`, label values
+    /// are escaped, and metrics are sorted by name. Session metrics are
+    /// exported as `expect_session_*` families with a `session_id` label.
+    ///
+    /// Each family is rendered once. When several metrics sanitize to the
+    /// same name, such as `queue-depth` and `queue_depth`, counters win
+    /// over gauges and gauges over histograms, whatever their original
+    /// names. Within one kind, a metric whose name was already valid wins,
+    /// otherwise the first in sort order. The others are left out. Names
+    /// used by the session families are reserved while any session is
+    /// registered.
+    #[must_use]
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let sessions = sorted(&self.sessions);
+        let mut seen: HashSet<String> = HashSet::new();
+        if !sessions.is_empty() {
+            seen.extend(session_families().map(str::to_string));
+        }
+
+        for (name, counter) in families(&self.counters) {
+            if seen.insert(name.clone()) {
+                family(&mut out, &name, None, "counter");
+                sample(&mut out, &name, &[], counter.get());
+            }
+        }
+        for (name, gauge) in families(&self.gauges) {
+            if seen.insert(name.clone()) {
+                family(&mut out, &name, None, "gauge");
+                sample(&mut out, &name, &[], gauge.get());
+            }
+        }
+        for (name, histogram) in families(&self.histograms) {
+            if seen.insert(name.clone()) {
+                family(&mut out, &name, None, "histogram");
+                histogram_samples(&mut out, &name, &[], &histogram);
+            }
+        }
+
+        if !sessions.is_empty() {
+            render_sessions(&mut out, &sessions);
+        }
+        out
+    }
+}
+
+/// Values drained from a [`MetricsRegistry`], keyed by metric name.
+#[derive(Debug, Clone, Default)]
+pub struct RegistrySnapshot {
+    /// Counter increments since the previous drain.
+    pub counters: HashMap<String, u64>,
+    /// Current gauge values.
+    pub gauges: HashMap<String, u64>,
+    /// Histogram observations since the previous drain.
+    pub histograms: HashMap<String, HistogramSnapshot>,
+}
+
+/// The exported families of [`SessionMetrics`]: name, help, and value.
+type SessionField = (&'static str, &'static str, fn(&SessionMetrics) -> u64);
+
+/// The exported histograms of [`SessionMetrics`]: name, help, and field.
+type SessionHistogram = (
+    &'static str,
+    &'static str,
+    fn(&SessionMetrics) -> &Histogram,
+);
+
+const SESSION_COUNTERS: [SessionField; 6] = [
+    ("expect_session_bytes_sent_total", "Bytes sent", |m| {
+        m.bytes_sent.get()
+    }),
+    (
+        "expect_session_bytes_received_total",
+        "Bytes received",
+        |m| m.bytes_received.get(),
+    ),
+    (
+        "expect_session_commands_executed_total",
+        "Commands executed",
+        |m| m.commands_executed.get(),
+    ),
+    (
+        "expect_session_pattern_matches_total",
+        "Pattern matches",
+        |m| m.pattern_matches.get(),
+    ),
+    ("expect_session_timeouts_total", "Timeouts", |m| {
+        m.timeouts.get()
+    }),
+    ("expect_session_errors_total", "Errors", |m| m.errors.get()),
+];
+
+const SESSION_HISTOGRAMS: [SessionHistogram; 2] = [
+    (
+        "expect_session_command_duration_seconds",
+        "Command duration in seconds",
+        |m| &m.command_duration,
+    ),
+    (
+        "expect_session_expect_duration_seconds",
+        "Expect duration in seconds",
+        |m| &m.expect_duration,
+    ),
+];
+
+/// The family names [`render_sessions`] emits.
+fn session_families() -> impl Iterator<Item = &'static str> {
+    SESSION_COUNTERS
+        .iter()
+        .map(|(name, ..)| *name)
+        .chain(["expect_session_active_sessions"])
+        .chain(SESSION_HISTOGRAMS.iter().map(|(name, ..)| *name))
+}
+
+fn render_sessions(out: &mut String, sessions: &[(String, Arc<SessionMetrics>)]) {
+    for (name, help, value) in SESSION_COUNTERS {
+        family(out, name, Some(help), "counter");
+        for (id, metrics) in sessions {
+            sample(out, name, &[("session_id", id)], value(metrics));
+        }
+    }
+
+    let name = "expect_session_active_sessions";
+    family(out, name, Some("Active sessions"), "gauge");
+    for (id, metrics) in sessions {
+        sample(
+            out,
+            name,
+            &[("session_id", id)],
+            metrics.active_sessions.get(),
+        );
+    }
+
+    for (name, help, histogram) in SESSION_HISTOGRAMS {
+        family(out, name, Some(help), "histogram");
+        for (id, metrics) in sessions {
+            histogram_samples(out, name, &[("session_id", id)], histogram(metrics));
+        }
+    }
+}
+
+/// Snapshot a map of metrics sorted by key.
+fn sorted<T>(map: &Mutex<HashMap<String, Arc<T>>>) -> Vec<(String, Arc<T>)> {
+    let mut entries: Vec<_> = map
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .iter()
+        .map(|(key, metric)| (key.clone(), Arc::clone(metric)))
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+/// Snapshot a map of metrics under their sanitized names, sorted so a
+/// name that needed no sanitizing comes before ones that collide with it.
+fn families<T>(map: &Mutex<HashMap<String, Arc<T>>>) -> Vec<(String, Arc<T>)> {
+    let mut entries: Vec<_> = sorted(map)
+        .into_iter()
+        .map(|(name, metric)| (sanitize(&name), name, metric))
+        .collect();
+    entries.sort_by(|a, b| (&a.0, a.0 != a.1).cmp(&(&b.0, b.0 != b.1)));
+    entries
+        .into_iter()
+        .map(|(sanitized, _, metric)| (sanitized, metric))
+        .collect()
+}
+
+/// Replace characters not allowed in a Prometheus metric name.
+fn sanitize(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !out.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == ':') {
+        out.insert(0, '_');
+    }
+    out
+}
+
+fn family(out: &mut String, name: &str, help: Option<&str>, kind: &str) {
+    if let Some(help) = help {
+        let _ = writeln!(out, "# HELP {name} {help}");
+    }
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: impl std::fmt::Display) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        out.push('{');
+        for (i, (key, val)) in labels.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "{key}=\"");
+            for c in val.chars() {
+                match c {
+                    '\\' => out.push_str("\\\\"),
+                    '"' => out.push_str("\\\""),
+                    '\n' => out.push_str("\\n"),
+                    c => out.push(c),
+                }
+            }
+            out.push('"');
+        }
+        out.push('}');
+    }
+    let _ = writeln!(out, " {value}");
+}
+
+/// Write the cumulative `_bucket` lines, then `_sum` and `_count`.
+fn histogram_samples(out: &mut String, name: &str, labels: &[(&str, &str)], histogram: &Histogram) {
+    let counts = histogram.bucket_counts();
+    let bucket = format!("{name}_bucket");
+    let mut cumulative = 0;
+    for (i, count) in counts.iter().enumerate() {
+        cumulative += count;
+        let le = histogram
+            .buckets
+            .get(i)
+            .map_or_else(|| "+Inf".to_string(), |b| format_float(*b));
+        let mut bucket_labels = labels.to_vec();
+        bucket_labels.push(("le", &le));
+        sample(out, &bucket, &bucket_labels, cumulative);
+    }
+    sample(
+        out,
+        &format!("{name}_sum"),
+        labels,
+        format_float(histogram.sum()),
+    );
+    sample(out, &format!("{name}_count"), labels, cumulative);
+}
+
+/// Format a float the way Prometheus clients do.
+fn format_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
 }
 
 #[cfg(test)]
@@ -362,4 +812,239 @@ mod tests {
         assert_eq!(snapshot.bytes_sent, 100);
         assert_eq!(snapshot.commands_executed, 1);
     }
+
+    #[test]
+    fn histogram_sum() {
+        let histogram = Histogram::new();
+        histogram.observe(0.25);
+        histogram.observe(1.5);
+
+        assert!((histogram.sum() - 1.75).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn render_prometheus_one_of_each() {
+        let registry = MetricsRegistry::new();
+        registry.counter("requests_total").add(3);
+        registry.gauge("queue-depth").set(7);
+        let latency = registry.histogram_with_buckets("latency_seconds", vec![0.1, 1.0]);
+        latency.observe(0.05);
+        latency.observe(0.5);
+        latency.observe(2.0);
+
+        assert_eq!(
+            registry.render_prometheus(),
+            "\
+# TYPE requests_total counter
+requests_total 3
+# TYPE queue_depth gauge
+queue_depth 7
+# TYPE latency_seconds histogram
+latency_seconds_bucket{le=\"0.1\"} 1
+latency_seconds_bucket{le=\"1\"} 2
+latency_seconds_bucket{le=\"+Inf\"} 3
+latency_seconds_sum 2.55
+latency_seconds_count 3
+"
+        );
+    }
+
+    #[test]
+    fn render_prometheus_session_metrics() {
+        let registry = MetricsRegistry::new();
+        let session = registry.session("sess-\"1\"");
+        session.bytes_sent.add(42);
+        session.expect_duration.observe(0.003);
+
+        let output = registry.render_prometheus();
+        assert!(output.contains(
+            "# HELP expect_session_bytes_sent_total Bytes sent\n\
+             # TYPE expect_session_bytes_sent_total counter\n\
+             expect_session_bytes_sent_total{session_id=\"sess-\\\"1\\\"\"} 42\n"
+        ));
+        assert!(output.contains(
+            "expect_session_expect_duration_seconds_bucket{session_id=\"sess-\\\"1\\\"\",le=\"0.005\"} 1\n"
+        ));
+        assert!(output.contains(
+            "expect_session_expect_duration_seconds_count{session_id=\"sess-\\\"1\\\"\"} 1\n"
+        ));
+        assert!(output.contains("# TYPE expect_session_active_sessions gauge\n"));
+    }
+
+    #[test]
+    fn render_prometheus_renders_colliding_names_once() {
+        let registry = MetricsRegistry::new();
+        registry.gauge("queue-depth").set(1);
+        registry.gauge("queue_depth").set(2);
+        registry.counter("queue.depth").add(3);
+        registry.counter("expect_session_errors_total").add(4);
+        let _ = registry.session("s1");
+
+        let output = registry.render_prometheus();
+        assert_eq!(output.matches("# TYPE queue_depth ").count(), 1);
+        assert!(output.contains("# TYPE queue_depth counter\nqueue_depth 3\n"));
+        assert_eq!(
+            output
+                .matches("# TYPE expect_session_errors_total ")
+                .count(),
+            1
+        );
+        assert!(!output.contains("expect_session_errors_total 4"));
+
+        let registry = MetricsRegistry::new();
+        registry.gauge("queue-depth").set(1);
+        registry.gauge("queue_depth").set(2);
+        assert_eq!(
+            registry.render_prometheus(),
+            "# TYPE queue_depth gauge\nqueue_depth 2\n"
+        );
+    }
+
+    #[test]
+    fn histogram_with_buckets_keeps_existing_histogram() {
+        let registry = MetricsRegistry::new();
+        let first = registry.histogram_with_buckets("latency", vec![1.0]);
+        let second = registry.histogram_with_buckets("latency", vec![2.0, 3.0]);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(second.buckets(), [1.0]);
+    }
+
+    #[test]
+    fn render_prometheus_empty() {
+        assert_eq!(MetricsRegistry::new().render_prometheus(), "");
+    }
+
+    #[test]
+    fn histogram_quantiles_of_uniform_values() {
+        let buckets = (1..=10).map(|i| f64::from(i) * 10.0).collect();
+        let histogram = Histogram::with_buckets(buckets);
+        for i in 0..1000 {
+            histogram.observe(f64::from(i) / 10.0);
+        }
+
+        let close = |actual: Option<f64>, expected: f64| {
+            let actual = actual.unwrap();
+            assert!((actual - expected).abs() < 1.0, "{actual} vs {expected}");
+        };
+        close(histogram.p50(), 50.0);
+        close(histogram.p90(), 90.0);
+        close(histogram.p99(), 99.0);
+        close(histogram.quantile(0.0), 0.0);
+        close(histogram.quantile(1.0), 100.0);
+    }
+
+    #[test]
+    fn histogram_quantile_edge_cases() {
+        let histogram = Histogram::with_buckets(vec![1.0, 2.0]);
+        assert_eq!(histogram.p50(), None);
+
+        histogram.observe(5.0);
+        // Values above every bound report the highest bound
+        assert_eq!(histogram.p99(), Some(2.0));
+        assert_eq!(histogram.quantile(1.5), None);
+        assert_eq!(histogram.quantile(f64::NAN), None);
+
+        let unbounded = Histogram::with_buckets(Vec::new());
+        unbounded.observe(1.0);
+        assert_eq!(unbounded.p50(), None);
+    }
+
+    #[test]
+    fn counter_take_resets() {
+        let counter = Counter::new();
+        counter.add(5);
+        assert_eq!(counter.take(), 5);
+        assert_eq!(counter.get(), 0);
+        assert_eq!(counter.take(), 0);
+    }
+
+    #[test]
+    fn registry_drain_resets_counters_and_histograms() {
+        let registry = MetricsRegistry::new();
+        registry.counter("sent").add(3);
+        registry.gauge("open").set(2);
+        registry.histogram("latency").observe(0.2);
+
+        let snapshot = registry.drain();
+        assert_eq!(snapshot.counters["sent"], 3);
+        assert_eq!(snapshot.gauges["open"], 2);
+        assert_eq!(snapshot.histograms["latency"].count, 1);
+        assert!((snapshot.histograms["latency"].sum - 0.2).abs() < f64::EPSILON);
+
+        let snapshot = registry.drain();
+        assert_eq!(snapshot.counters["sent"], 0);
+        assert_eq!(snapshot.gauges["open"], 2);
+        assert_eq!(snapshot.histograms["latency"].count, 0);
+        assert_eq!(registry.histogram("latency").count(), 0);
+    }
+
+    #[test]
+    fn registry_drain_loses_no_concurrent_increments() {
+        const THREADS: u64 = 8;
+        const PER_THREAD: u64 = 10_000;
+
+        let registry = Arc::new(MetricsRegistry::new());
+        let done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        let drainer = {
+            let registry = Arc::clone(&registry);
+            let done = Arc::clone(&done);
+            std::thread::spawn(move || {
+                let mut total = 0;
+                while !done.load(Ordering::Acquire) {
+                    total += registry.drain().counters.get("hits").copied().unwrap_or(0);
+                }
+                total + registry.drain().counters.get("hits").copied().unwrap_or(0)
+            })
+        };
+
+        let workers: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let counter = registry.counter("hits");
+                std::thread::spawn(move || {
+                    for _ in 0..PER_THREAD {
+                        counter.inc();
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        done.store(true, Ordering::Release);
+
+        assert_eq!(drainer.join().unwrap(), THREADS * PER_THREAD);
+    }
+
+    #[test]
+    fn histogram_take_races_observe_without_skew() {
+        const THREADS: u64 = 4;
+        const PER_THREAD: u64 = 10_000;
+
+        let histogram = Arc::new(Histogram::new());
+        let workers: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let histogram = Arc::clone(&histogram);
+                std::thread::spawn(move || {
+                    for _ in 0..PER_THREAD {
+                        histogram.observe(0.2);
+                    }
+                })
+            })
+            .collect();
+
+        let mut taken = 0;
+        while !workers.iter().all(std::thread::JoinHandle::is_finished) {
+            let snapshot = histogram.take();
+            assert_eq!(snapshot.count, snapshot.counts.iter().sum::<u64>());
+            assert!(histogram.count() <= THREADS * PER_THREAD);
+            taken += snapshot.count;
+        }
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert_eq!(taken + histogram.take().count, THREADS * PER_THREAD);
+        assert_eq!(histogram.count(), 0);
+    }
 }
@@ -4,6 +4,7 @@
 //! performance and behavior. It includes:
 //!
 //! - Core metrics (counters, gauges, histograms)
+//! - Prometheus text exposition of a [`MetricsRegistry`]
 //! - OpenTelemetry span integration (with `metrics` feature)
 //! - Prometheus export (with `metrics` feature)
 //!
@@ -25,6 +26,21 @@
 //! let elapsed = timer.stop();
 //! ```
 //!
+//! # Prometheus Text Exposition
+//!
+//! ```rust
+//! use rust_expect::metrics::MetricsRegistry;
+//!
+//! let registry = MetricsRegistry::new();
+//! registry.counter("logins_total").inc();
+//! registry.session("build-42").bytes_sent.add(128);
+//!
+//! // Serve this from a /metrics endpoint
+//! let text = registry.render_prometheus();
+//! assert!(text.contains("logins_total 1"));
+//! assert!(text.contains(r#"expect_session_bytes_sent_total{session_id="build-42"} 128"#));
+//! ```
+//!
 //! # OpenTelemetry Integration (with `metrics` feature)
 //!
 //! ```rust,ignore
@@ -29,7 +29,7 @@ pub mod session;
 
 pub use builtin::*;
 pub use event::{EventTimeline, MockEvent};
-pub use scenario::{Scenario, ScenarioBuilder, ScenarioStep};
+pub use scenario::{Scenario, ScenarioBuilder, ScenarioFault, ScenarioStep};
 pub use session::{MockSession, MockTransport};
 
 /// Create a simple mock transport with pre-queued output.
@@ -79,6 +79,17 @@ impl MockBuilder {
         self
     }
 
+    /// Expect the code under test to write `data`.
+    ///
+    /// Later output is held back until it does. Written input that does
+    /// not match fails the write and is reported by
+    /// [`MockTransport::verify`].
+    #[must_use]
+    pub fn expect_input(mut self, data: &str) -> Self {
+        self.events.push(MockEvent::input_str(data));
+        self
+    }
+
     /// Add a delay.
     #[must_use]
     pub fn delay_ms(mut self, ms: u64) -> Self {
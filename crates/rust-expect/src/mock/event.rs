@@ -11,6 +11,9 @@ pub enum MockEvent {
     /// Data received from the process.
     Output(Vec<u8>),
     /// Data sent to the process.
+    ///
+    /// A [`MockTransport`](super::MockTransport) holds back later events
+    /// until the client has written exactly this.
     Input(Vec<u8>),
     /// Delay before next event.
     Delay(Duration),
@@ -20,6 +23,10 @@ pub enum MockEvent {
     Eof,
     /// Error occurred.
     Error(String),
+    /// The next read fails with an I/O error of this kind.
+    IoError(std::io::ErrorKind),
+    /// The process stops responding; later events are never delivered.
+    Hang,
     /// Window size changed.
     Resize {
         /// Number of rows.
@@ -81,6 +88,18 @@ impl MockEvent {
         Self::Error(msg.into())
     }
 
+    /// Create an I/O error event.
+    #[must_use]
+    pub const fn io_error(kind: std::io::ErrorKind) -> Self {
+        Self::IoError(kind)
+    }
+
+    /// Create a hang event.
+    #[must_use]
+    pub const fn hang() -> Self {
+        Self::Hang
+    }
+
     /// Create a resize event.
     #[must_use]
     pub const fn resize(rows: u16, cols: u16) -> Self {
@@ -181,6 +200,12 @@ impl EventTimeline {
         self.events.len().saturating_sub(self.position)
     }
 
+    /// Get the events not yet reached.
+    #[must_use]
+    pub fn remaining_events(&self) -> &[MockEvent] {
+        self.events.get(self.position..).unwrap_or_default()
+    }
+
     /// Get all events.
     #[must_use]
     pub fn events(&self) -> &[MockEvent] {
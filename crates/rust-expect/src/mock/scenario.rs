@@ -3,6 +3,7 @@
 //! Scenarios define a sequence of expected patterns and responses
 //! for simulating interactive sessions.
 
+use std::io;
 use std::time::Duration;
 
 use super::event::{EventTimeline, MockEvent};
@@ -14,10 +15,30 @@ pub struct ScenarioStep {
     pub expect: Option<String>,
     /// Response to send.
     pub response: Option<String>,
+    /// Response sent in pieces, each after its delay.
+    ///
+    /// Sent after [`response`](Self::response), if both are set.
+    pub chunks: Vec<(Duration, String)>,
     /// Delay before response.
     pub delay: Duration,
     /// Optional error to inject.
     pub error: Option<String>,
+    /// Fault that ends the step, after its response.
+    pub fault: Option<ScenarioFault>,
+}
+
+/// A failure injected at a point in a scenario.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScenarioFault {
+    /// The next read fails with an I/O error of this kind.
+    ///
+    /// Sessions treat [`io::ErrorKind::BrokenPipe`] like EOF, as a closed
+    /// PTY reports it.
+    Error(io::ErrorKind),
+    /// The process closes its output.
+    Eof,
+    /// The process stops responding without closing its output.
+    Hang,
 }
 
 impl Default for ScenarioStep {
@@ -25,8 +46,10 @@ impl Default for ScenarioStep {
         Self {
             expect: None,
             response: None,
+            chunks: Vec::new(),
             delay: Duration::ZERO,
             error: None,
+            fault: None,
         }
     }
 }
@@ -52,6 +75,22 @@ impl ScenarioStep {
         self
     }
 
+    /// Send the response in pieces, as a PTY delivers output.
+    ///
+    /// `delays[i]` is waited out before `parts[i]` is sent; parts without
+    /// a matching delay follow immediately, but still arrive as a separate
+    /// read.
+    #[must_use]
+    pub fn respond_chunked<S: Into<String>>(
+        mut self,
+        parts: impl IntoIterator<Item = S>,
+        delays: impl IntoIterator<Item = Duration>,
+    ) -> Self {
+        let delays = delays.into_iter().chain(std::iter::repeat(Duration::ZERO));
+        self.chunks = delays.zip(parts).map(|(d, p)| (d, p.into())).collect();
+        self
+    }
+
     /// Set the delay before response.
     #[must_use]
     pub const fn delay(mut self, duration: Duration) -> Self {
@@ -72,6 +111,13 @@ impl ScenarioStep {
         self.error = Some(msg.into());
         self
     }
+
+    /// End the step with a fault.
+    #[must_use]
+    pub const fn fault(mut self, fault: ScenarioFault) -> Self {
+        self.fault = Some(fault);
+        self
+    }
 }
 
 /// A complete mock scenario.
@@ -126,6 +172,36 @@ impl Scenario {
         self.step(ScenarioStep::new().expect(pattern).respond(response))
     }
 
+    /// Add a step that sends its output in delayed pieces.
+    ///
+    /// See [`ScenarioStep::respond_chunked`].
+    #[must_use]
+    pub fn respond_chunked<S: Into<String>>(
+        self,
+        parts: impl IntoIterator<Item = S>,
+        delays: impl IntoIterator<Item = Duration>,
+    ) -> Self {
+        self.step(ScenarioStep::new().respond_chunked(parts, delays))
+    }
+
+    /// Fail the next read with an I/O error of `kind`.
+    #[must_use]
+    pub fn then_error(self, kind: io::ErrorKind) -> Self {
+        self.step(ScenarioStep::new().fault(ScenarioFault::Error(kind)))
+    }
+
+    /// Close the output here, before any later steps.
+    #[must_use]
+    pub fn then_eof(self) -> Self {
+        self.step(ScenarioStep::new().fault(ScenarioFault::Eof))
+    }
+
+    /// Stop responding here, so expects time out.
+    #[must_use]
+    pub fn then_hang(self) -> Self {
+        self.step(ScenarioStep::new().fault(ScenarioFault::Hang))
+    }
+
     /// Set the exit code.
     #[must_use]
     pub const fn exit_code(mut self, code: i32) -> Self {
@@ -177,6 +253,19 @@ impl Scenario {
             if let Some(response) = &step.response {
                 events.push(MockEvent::output_str(response));
             }
+            for (delay, part) in &step.chunks {
+                if !delay.is_zero() {
+                    events.push(MockEvent::delay(*delay));
+                }
+                events.push(MockEvent::output_str(part));
+            }
+
+            match step.fault {
+                Some(ScenarioFault::Error(kind)) => events.push(MockEvent::io_error(kind)),
+                Some(ScenarioFault::Eof) => events.push(MockEvent::eof()),
+                Some(ScenarioFault::Hang) => events.push(MockEvent::hang()),
+                None => {}
+            }
         }
 
         // Add exit
@@ -252,6 +341,17 @@ impl ScenarioBuilder {
         self
     }
 
+    /// Add a step that sends its output in delayed pieces.
+    #[must_use]
+    pub fn respond_chunked<S: Into<String>>(
+        mut self,
+        parts: impl IntoIterator<Item = S>,
+        delays: impl IntoIterator<Item = Duration>,
+    ) -> Self {
+        self.scenario = self.scenario.respond_chunked(parts, delays);
+        self
+    }
+
     /// Set the exit code.
     #[must_use]
     pub fn exit_code(mut self, code: i32) -> Self {
@@ -292,6 +392,21 @@ mod tests {
         assert!(timeline.events()[1].is_exit());
     }
 
+    #[test]
+    fn scenario_chunked_timeline() {
+        let scenario = Scenario::new("chunked").respond_chunked(
+            ["Pass", "word", ": "],
+            [Duration::ZERO, Duration::from_millis(20)],
+        );
+
+        let events = scenario.to_timeline().events().to_vec();
+        assert_eq!(events.len(), 4);
+        assert!(events[0].is_output());
+        assert!(events[1].is_delay());
+        assert!(events[2].is_output());
+        assert!(events[3].is_output());
+    }
+
     #[test]
     fn scenario_builder() {
         let scenario = ScenarioBuilder::new("login")
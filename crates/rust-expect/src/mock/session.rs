@@ -7,12 +7,15 @@ use std::collections::VecDeque;
 use std::io;
 use std::pin::Pin;
 use std::sync::{Arc, Mutex};
-use std::task::{Context, Poll};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::time::Sleep;
 
 use super::event::{EventTimeline, MockEvent};
 use super::scenario::Scenario;
+use crate::util::bytes::escape_bytes;
 
 /// Shared state for the mock transport.
 #[derive(Debug)]
@@ -26,9 +29,23 @@ struct MockState {
     /// Whether EOF has been signaled.
     eof: bool,
     /// Error to return on next read.
-    error: Option<String>,
+    error: Option<io::Error>,
+    /// Whether the process has stopped responding.
+    hung: bool,
     /// Exit code if exited.
     exit_code: Option<i32>,
+    /// Scripted delay to wait out before the next event.
+    delay: Option<Duration>,
+    /// Timer for the delay currently being waited out.
+    sleep: Option<Pin<Box<Sleep>>>,
+    /// Input the timeline is waiting for.
+    expected_input: Option<Vec<u8>>,
+    /// Bytes written since the last expected input was matched.
+    unmatched_input: Vec<u8>,
+    /// Mismatches between expected and written input.
+    failures: Vec<String>,
+    /// Reader blocked on an expected input.
+    read_waker: Option<Waker>,
 }
 
 impl MockState {
@@ -39,7 +56,14 @@ impl MockState {
             timeline,
             eof: false,
             error: None,
+            hung: false,
             exit_code: None,
+            delay: None,
+            sleep: None,
+            expected_input: None,
+            unmatched_input: Vec::new(),
+            failures: Vec::new(),
+            read_waker: None,
         }
     }
 
@@ -53,18 +77,107 @@ impl MockState {
                     self.eof = true;
                 }
                 MockEvent::Error(msg) => {
-                    self.error = Some(msg);
+                    self.error = Some(io::Error::other(msg));
+                }
+                MockEvent::IoError(kind) => {
+                    self.error = Some(io::Error::new(kind, "mock I/O error"));
+                }
+                MockEvent::Hang => {
+                    self.hung = true;
                 }
                 MockEvent::Exit(code) => {
                     self.exit_code = Some(code);
                     self.eof = true;
                 }
-                MockEvent::Input(_) | MockEvent::Delay(_) | MockEvent::Resize { .. } => {
+                MockEvent::Delay(duration) => {
+                    // The timer is started on the next read, which runs
+                    // on a runtime
+                    if !duration.is_zero() {
+                        self.delay = Some(self.delay.unwrap_or_default() + duration);
+                    }
+                }
+                MockEvent::Input(data) => {
+                    self.expected_input = Some(data);
+                }
+                MockEvent::Resize { .. } => {
                     // These are handled differently
                 }
             }
         }
     }
+
+    /// Compare written bytes with the expected input.
+    ///
+    /// Returns a description of the mismatch, which is also recorded for
+    /// [`MockTransport::verify`].
+    fn match_input(&mut self) -> Option<String> {
+        let expected = self.expected_input.as_ref()?;
+        let written = &self.unmatched_input;
+        let common = expected.len().min(written.len());
+        if expected[..common] != written[..common] {
+            let offset = expected
+                .iter()
+                .zip(written)
+                .take_while(|(e, w)| e == w)
+                .count();
+            let failure = format!(
+                "mock expected input \"{}\" but received \"{}\" (first difference at byte {offset})",
+                escape_bytes(expected),
+                escape_bytes(written),
+            );
+            self.expected_input = None;
+            self.unmatched_input.clear();
+            self.failures.push(failure.clone());
+            return Some(failure);
+        }
+        if common == expected.len() {
+            self.unmatched_input.drain(..common);
+            self.expected_input = None;
+            if let Some(waker) = self.read_waker.take() {
+                waker.wake();
+            }
+        }
+        None
+    }
+
+    /// Drop written bytes that no scripted input can still match.
+    ///
+    /// Only as many bytes as the current and upcoming expected inputs can
+    /// consume are kept, so writes with nothing left to match them do not
+    /// accumulate.
+    fn trim_unmatched_input(&mut self) {
+        let upcoming: usize = self
+            .timeline
+            .remaining_events()
+            .iter()
+            .map(|event| match event {
+                MockEvent::Input(data) => data.len(),
+                _ => 0,
+            })
+            .sum();
+        let wanted = self.expected_input.as_ref().map_or(0, Vec::len) + upcoming;
+        self.unmatched_input.truncate(wanted);
+    }
+
+    /// Describe every expectation that was not met.
+    fn unmet_expectations(&self) -> Vec<String> {
+        let mut unmet = self.failures.clone();
+        let upcoming = self
+            .timeline
+            .remaining_events()
+            .iter()
+            .filter_map(|event| match event {
+                MockEvent::Input(data) => Some(data),
+                _ => None,
+            });
+        for expected in self.expected_input.iter().chain(upcoming) {
+            unmet.push(format!(
+                "mock expected input \"{}\" that was never sent",
+                escape_bytes(expected)
+            ));
+        }
+        unmet
+    }
 }
 
 /// A mock transport for testing.
@@ -154,7 +267,7 @@ impl MockTransport {
             .state
             .lock()
             .unwrap_or_else(std::sync::PoisonError::into_inner);
-        state.error = Some(msg.into());
+        state.error = Some(io::Error::other(msg.into()));
     }
 
     /// Check if EOF has been signaled.
@@ -185,6 +298,24 @@ impl MockTransport {
             .unwrap_or_else(std::sync::PoisonError::into_inner);
         state.process_event();
     }
+
+    /// Check that every expected input was written, and written correctly.
+    ///
+    /// Call this at the end of a test. Clones of a transport share their
+    /// expectations, so keep a clone to verify after handing the transport
+    /// to a [`Session`](crate::Session).
+    ///
+    /// # Panics
+    ///
+    /// Panics with each mismatch and each input that was never sent.
+    pub fn verify(&self) {
+        let unmet = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .unmet_expectations();
+        assert!(unmet.is_empty(), "{}", unmet.join("\n"));
+    }
 }
 
 impl Default for MockTransport {
@@ -196,7 +327,7 @@ impl Default for MockTransport {
 impl AsyncRead for MockTransport {
     fn poll_read(
         self: Pin<&mut Self>,
-        _cx: &mut Context<'_>,
+        cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
         let mut state = self
@@ -204,41 +335,52 @@ impl AsyncRead for MockTransport {
             .lock()
             .unwrap_or_else(std::sync::PoisonError::into_inner);
 
-        // Check for error
-        if let Some(error) = state.error.take() {
-            return Poll::Ready(Err(io::Error::other(error)));
-        }
-
-        // Check for EOF
-        if state.output.is_empty() && state.eof {
-            return Poll::Ready(Ok(()));
-        }
+        loop {
+            // Check for error
+            if let Some(error) = state.error.take() {
+                return Poll::Ready(Err(error));
+            }
 
-        // Read available data
-        if state.output.is_empty() {
-            // Process next event and try again
-            state.process_event();
+            // Read available data; each output event arrives as its own read
             if !state.output.is_empty() {
                 let to_read = buf.remaining().min(state.output.len());
-                for _ in 0..to_read {
-                    if let Some(byte) = state.output.pop_front() {
-                        buf.put_slice(&[byte]);
-                    }
-                }
-                Poll::Ready(Ok(()))
-            } else if state.eof {
-                Poll::Ready(Ok(()))
-            } else {
-                Poll::Pending
+                let chunk: Vec<u8> = state.output.drain(..to_read).collect();
+                buf.put_slice(&chunk);
+                return Poll::Ready(Ok(()));
             }
-        } else {
-            let to_read = buf.remaining().min(state.output.len());
-            for _ in 0..to_read {
-                if let Some(byte) = state.output.pop_front() {
-                    buf.put_slice(&[byte]);
+
+            // Wait out a scripted delay before moving on
+            if let Some(duration) = state.delay.take() {
+                state.sleep = Some(Box::pin(tokio::time::sleep(duration)));
+            }
+            if let Some(sleep) = state.sleep.as_mut() {
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
                 }
+                state.sleep = None;
+            }
+
+            // Check for EOF
+            if state.eof {
+                return Poll::Ready(Ok(()));
             }
-            Poll::Ready(Ok(()))
+            if state.hung {
+                return Poll::Pending;
+            }
+            // Later events wait until the client sends the expected input.
+            // It may already have been written; a mismatch is recorded for
+            // verify() and the timeline moves on.
+            let _ = state.match_input();
+            if state.expected_input.is_some() {
+                state.read_waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+            if !state.timeline.has_more() {
+                return Poll::Pending;
+            }
+
+            // Process next event and try again
+            state.process_event();
         }
     }
 }
@@ -249,13 +391,26 @@ impl AsyncWrite for MockTransport {
         _cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        let len = buf.len();
-        self.state
+        let mut state = self
+            .state
             .lock()
-            .unwrap_or_else(std::sync::PoisonError::into_inner)
-            .input
-            .extend(buf);
-        Poll::Ready(Ok(len))
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        state.input.extend(buf);
+        state.unmatched_input.extend(buf);
+
+        loop {
+            if let Some(failure) = state.match_input() {
+                return Poll::Ready(Err(io::Error::other(failure)));
+            }
+            // Input may be written before the output preceding it is read
+            if state.expected_input.is_some()
+                || !matches!(state.timeline.peek(), Some(MockEvent::Input(_)))
+            {
+                state.trim_unmatched_input();
+                return Poll::Ready(Ok(buf.len()));
+            }
+            state.process_event();
+        }
     }
 
     fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -321,6 +476,16 @@ impl MockSession {
     pub fn take_input_str(&self) -> String {
         self.transport.take_input_str()
     }
+
+    /// Check that every expected input was written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an expected input was not sent or did not match; see
+    /// [`MockTransport::verify`].
+    pub fn verify(&self) {
+        self.transport.verify();
+    }
 }
 
 impl Default for MockSession {
@@ -358,6 +523,56 @@ mod tests {
         assert_eq!(n, 0);
     }
 
+    #[tokio::test]
+    async fn mock_transport_waits_out_delays() {
+        let timeline = EventTimeline::from_events(vec![
+            MockEvent::output_str("pass"),
+            MockEvent::delay_ms(30),
+            MockEvent::output_str("word: "),
+        ]);
+        let mut transport = MockTransport::from_timeline(timeline);
+
+        let mut buf = [0u8; 16];
+        let n = transport.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"pass");
+
+        let start = tokio::time::Instant::now();
+        let n = transport.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"word: ");
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn mock_transport_keeps_only_input_it_can_match() {
+        let timeline = EventTimeline::from_events(vec![
+            MockEvent::output_str("Continue"),
+            MockEvent::output_str("? "),
+            MockEvent::input_str("y\n"),
+            MockEvent::eof(),
+        ]);
+        let mut transport = MockTransport::from_timeline(timeline);
+
+        // Written before the prompt is read, then followed by input that
+        // nothing in the script expects
+        transport.write_all(b"y\n").await.unwrap();
+        transport.write_all(&[b'x'; 4096]).await.unwrap();
+        let unmatched = |t: &MockTransport| t.state.lock().unwrap().unmatched_input.len();
+        assert_eq!(unmatched(&transport), 2);
+
+        let mut buf = [0u8; 16];
+        let mut output = Vec::new();
+        loop {
+            let n = transport.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            output.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(output, b"Continue? ");
+        assert_eq!(unmatched(&transport), 0);
+        transport.verify();
+    }
+
     #[tokio::test]
     async fn mock_transport_from_timeline() {
         let timeline = EventTimeline::from_events(vec![
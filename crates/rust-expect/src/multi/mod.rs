@@ -35,4 +35,7 @@ pub use group::{GroupBuilder, GroupManager, GroupResult, SessionGroup};
 /// Session identifier type for multi-session operations.
 /// This is distinct from `types::SessionId` which is a UUID-based identifier.
 pub use select::SessionId as MultiSessionId;
-pub use select::{MultiSessionManager, PatternSelector, ReadyType, SelectResult, SendResult};
+pub use select::{
+    ExpectAllResult, ManagedSession, MultiSessionManager, PatternSelector, ReadyType, SelectResult,
+    SendResult,
+};
@@ -14,7 +14,7 @@
 //! async fn main() -> Result<(), rust_expect::ExpectError> {
 //!     let mut manager = MultiSessionManager::new();
 //!
-//!     // Add sessions
+//!     // Spawn sessions
 //!     let id1 = manager.spawn("bash", &["-c", "echo server1"]).await?;
 //!     let id2 = manager.spawn("bash", &["-c", "echo server2"]).await?;
 //!
@@ -35,7 +35,7 @@ use std::time::Duration;
 
 use futures::stream::{FuturesUnordered, StreamExt};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, watch};
 
 use crate::config::SessionConfig;
 use crate::error::{ExpectError, Result};
@@ -53,6 +53,9 @@ pub struct SelectResult {
     /// The match result.
     pub matched: Match,
     /// Index of the pattern that matched (if multiple patterns provided).
+    ///
+    /// For a [`PatternSelector`], this indexes the matching session's own
+    /// pattern list, or the default patterns if it has none.
     pub pattern_index: usize,
 }
 
@@ -67,6 +70,59 @@ pub struct SendResult {
     pub error: Option<String>,
 }
 
+/// Outcome of [`MultiSessionManager::expect_all_within`], per session.
+#[derive(Debug, Default)]
+pub struct ExpectAllResult {
+    /// Sessions that matched, ordered by session ID.
+    pub matched: Vec<SelectResult>,
+    /// Sessions that did not match before the deadline.
+    pub timed_out: Vec<SessionId>,
+    /// Sessions that failed for another reason, such as EOF.
+    pub errored: Vec<(SessionId, ExpectError)>,
+}
+
+impl ExpectAllResult {
+    /// Check whether every waited-on session matched.
+    #[must_use]
+    pub const fn all_matched(&self) -> bool {
+        self.timed_out.is_empty() && self.errored.is_empty()
+    }
+
+    /// Get the IDs of the sessions that matched.
+    #[must_use]
+    pub fn matched_ids(&self) -> Vec<SessionId> {
+        self.matched.iter().map(|r| r.session_id).collect()
+    }
+}
+
+/// A session shared between the manager and its pending operations.
+type SharedSession<T> = Arc<Mutex<ManagedSession<T>>>;
+
+/// A session in the manager and the signal that cancels operations on it.
+struct Slot<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> {
+    /// The shared session.
+    session: SharedSession<T>,
+    /// Set to `true` when the session is closed.
+    closed: watch::Sender<bool>,
+}
+
+/// Run `op`, giving up with [`ExpectError::SessionClosed`] if the session
+/// is closed first.
+async fn until_closed<R>(
+    mut closed: watch::Receiver<bool>,
+    op: impl Future<Output = Result<R>>,
+) -> Result<R> {
+    tokio::select! {
+        result = op => result,
+        // A dropped sender also means the session left the manager.
+        _ = closed.wait_for(|&closed| closed) => Err(ExpectError::SessionClosed),
+    }
+}
+
+/// Pending per-session expect operations.
+type ExpectFutures =
+    FuturesUnordered<Pin<Box<dyn Future<Output = (SessionId, Result<(Match, usize)>)> + Send>>>;
+
 /// Type of readiness event.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ReadyType {
@@ -83,7 +139,9 @@ pub enum ReadyType {
 }
 
 /// A managed session with its metadata.
-struct ManagedSession<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> {
+///
+/// Returned behind a shared lock by [`MultiSessionManager::remove_arc`].
+pub struct ManagedSession<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> {
     /// The underlying session.
     session: crate::session::Session<T>,
     /// Session label for identification.
@@ -92,6 +150,37 @@ struct ManagedSession<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> {
     active: bool,
 }
 
+impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> ManagedSession<T> {
+    /// Get the session.
+    #[must_use]
+    pub const fn session(&self) -> &crate::session::Session<T> {
+        &self.session
+    }
+
+    /// Get the session mutably.
+    pub const fn session_mut(&mut self) -> &mut crate::session::Session<T> {
+        &mut self.session
+    }
+
+    /// Get the session label.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Check if the session was active in the manager.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Take the session out of its wrapper.
+    #[must_use]
+    pub fn into_session(self) -> crate::session::Session<T> {
+        self.session
+    }
+}
+
 impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> fmt::Debug for ManagedSession<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ManagedSession")
@@ -109,9 +198,26 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> fmt::Debug for ManagedSessi
 /// - Wait for all sessions to match patterns (`expect_all`)
 /// - Send to multiple sessions in parallel
 /// - Select on multiple sessions with different patterns per session
+///
+/// # Ownership
+///
+/// Each session is stored behind an `Arc<Mutex<_>>`. Manager operations
+/// clone the `Arc` into their futures and lock it for the duration of the
+/// operation, so a session is only shared while such a call is in flight.
+/// Because [`remove`](Self::remove) and [`remove_arc`](Self::remove_arc)
+/// take `&mut self`, they cannot race with the manager's own operations;
+/// `remove` gives back the plain session only when nothing else holds it,
+/// while `remove_arc` always detaches the entry and returns the shared
+/// handle.
+///
+/// [`close`](Self::close) takes `&self`, so it can be called while other
+/// operations are in flight, for example from another task sharing the
+/// manager. It cancels any pending manager operation on the session, which
+/// then fails with [`ExpectError::SessionClosed`], before shutting the
+/// session down.
 pub struct MultiSessionManager<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> {
     /// Sessions indexed by ID.
-    sessions: HashMap<SessionId, Arc<Mutex<ManagedSession<T>>>>,
+    sessions: std::sync::Mutex<HashMap<SessionId, Slot<T>>>,
     /// Next session ID to assign.
     next_id: SessionId,
     /// Default timeout for operations.
@@ -125,7 +231,7 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> fmt::Debug
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("MultiSessionManager")
-            .field("session_count", &self.sessions.len())
+            .field("session_count", &self.len())
             .field("next_id", &self.next_id)
             .field("default_timeout", &self.default_timeout)
             .finish()
@@ -143,7 +249,7 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
     #[must_use]
     pub fn new() -> Self {
         Self {
-            sessions: HashMap::new(),
+            sessions: std::sync::Mutex::new(HashMap::new()),
             next_id: 0,
             default_timeout: Duration::from_secs(30),
             default_config: SessionConfig::default(),
@@ -181,7 +287,11 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
             active: true,
         };
 
-        self.sessions.insert(id, Arc::new(Mutex::new(managed)));
+        let slot = Slot {
+            session: Arc::new(Mutex::new(managed)),
+            closed: watch::Sender::new(false),
+        };
+        self.slots_mut().insert(id, slot);
         id
     }
 
@@ -190,62 +300,89 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
     /// Returns the session if it existed.
     #[allow(clippy::unused_async)]
     pub async fn remove(&mut self, id: SessionId) -> Option<crate::session::Session<T>> {
-        if let Some(arc) = self.sessions.remove(&id) {
-            // Try to unwrap the Arc - this will only succeed if we have the only reference
-            match Arc::try_unwrap(arc) {
-                Ok(mutex) => Some(mutex.into_inner().session),
-                Err(arc) => {
-                    // Put it back and return None - someone else has a reference
-                    self.sessions.insert(id, arc);
-                    None
-                }
+        let slot = self.slots_mut().remove(&id)?;
+        // Try to unwrap the Arc - this will only succeed if we have the only reference
+        match Arc::try_unwrap(slot.session) {
+            Ok(mutex) => Some(mutex.into_inner().session),
+            Err(session) => {
+                // Put it back and return None - someone else has a reference
+                let slot = Slot {
+                    session,
+                    closed: slot.closed,
+                };
+                self.slots_mut().insert(id, slot);
+                None
             }
-        } else {
-            None
         }
     }
 
+    /// Detach a session from the manager, even if it is shared.
+    ///
+    /// Unlike [`remove`](Self::remove), this always takes the entry out of
+    /// the manager and returns the shared handle. Once no other clones
+    /// remain, `Arc::try_unwrap` yields the session.
+    pub fn remove_arc(&mut self, id: SessionId) -> Option<Arc<Mutex<ManagedSession<T>>>> {
+        self.slots_mut().remove(&id).map(|slot| slot.session)
+    }
+
+    /// Lock the session table.
+    fn slots(&self) -> std::sync::MutexGuard<'_, HashMap<SessionId, Slot<T>>> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Get the session table through exclusive access.
+    fn slots_mut(&mut self) -> &mut HashMap<SessionId, Slot<T>> {
+        self.sessions
+            .get_mut()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Get a session and a receiver that reports when it is closed.
+    fn entry(&self, id: SessionId) -> Result<(SharedSession<T>, watch::Receiver<bool>)> {
+        self.slots()
+            .get(&id)
+            .map(|slot| (Arc::clone(&slot.session), slot.closed.subscribe()))
+            .ok_or(ExpectError::SessionNotFound { id })
+    }
+
     /// Get the number of sessions.
     #[must_use]
     pub fn len(&self) -> usize {
-        self.sessions.len()
+        self.slots().len()
     }
 
     /// Check if there are no sessions.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.sessions.is_empty()
+        self.slots().is_empty()
     }
 
     /// Get all session IDs.
     #[must_use]
     pub fn session_ids(&self) -> Vec<SessionId> {
-        self.sessions.keys().copied().collect()
+        self.slots().keys().copied().collect()
     }
 
     /// Get the label for a session.
     pub async fn label(&self, id: SessionId) -> Option<String> {
-        if let Some(arc) = self.sessions.get(&id) {
-            let guard = arc.lock().await;
-            Some(guard.label.clone())
-        } else {
-            None
-        }
+        let (arc, _) = self.entry(id).ok()?;
+        let guard = arc.lock().await;
+        Some(guard.label.clone())
     }
 
     /// Check if a session is active.
     pub async fn is_active(&self, id: SessionId) -> bool {
-        if let Some(arc) = self.sessions.get(&id) {
-            let guard = arc.lock().await;
-            guard.active
-        } else {
-            false
+        match self.entry(id) {
+            Ok((arc, _)) => arc.lock().await.active,
+            Err(_) => false,
         }
     }
 
     /// Set a session's active state.
     pub async fn set_active(&self, id: SessionId, active: bool) {
-        if let Some(arc) = self.sessions.get(&id) {
+        if let Ok((arc, _)) = self.entry(id) {
             let mut guard = arc.lock().await;
             guard.active = active;
         }
@@ -254,7 +391,7 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
     /// Get active session IDs.
     pub async fn active_ids(&self) -> Vec<SessionId> {
         let mut active = Vec::new();
-        for &id in self.sessions.keys() {
+        for id in self.session_ids() {
             if self.is_active(id).await {
                 active.push(id);
             }
@@ -268,13 +405,12 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
     ///
     /// Returns an error if the session doesn't exist or the send fails.
     pub async fn send(&self, id: SessionId, data: &[u8]) -> Result<()> {
-        let arc = self
-            .sessions
-            .get(&id)
-            .ok_or(ExpectError::SessionNotFound { id })?;
-
-        let mut guard = arc.lock().await;
-        guard.session.send(data).await
+        let (arc, closed) = self.entry(id)?;
+        until_closed(closed, async {
+            let mut guard = arc.lock().await;
+            guard.session.send(data).await
+        })
+        .await
     }
 
     /// Send a line to a specific session.
@@ -283,41 +419,66 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
     ///
     /// Returns an error if the session doesn't exist or the send fails.
     pub async fn send_line(&self, id: SessionId, line: &str) -> Result<()> {
-        let arc = self
-            .sessions
-            .get(&id)
-            .ok_or(ExpectError::SessionNotFound { id })?;
-
-        let mut guard = arc.lock().await;
-        guard.session.send_line(line).await
+        let (arc, closed) = self.entry(id)?;
+        until_closed(closed, async {
+            let mut guard = arc.lock().await;
+            guard.session.send_line(line).await
+        })
+        .await
     }
 
     /// Send data to all active sessions in parallel.
     ///
     /// Returns results for each session.
     pub async fn send_all(&self, data: &[u8]) -> Vec<SendResult> {
-        let mut futures = FuturesUnordered::new();
+        let ids = self.session_ids();
+        self.send_to(&ids, data).await
+    }
 
-        for (&id, arc) in &self.sessions {
-            let arc = Arc::clone(arc);
-            let data = data.to_vec();
+    /// Send data to the given sessions in parallel.
+    ///
+    /// Inactive and unknown sessions get a failed result, as in
+    /// [`send_all`](Self::send_all). Returns one result per ID, in
+    /// completion order.
+    pub async fn send_to(&self, ids: &[SessionId], data: &[u8]) -> Vec<SendResult> {
+        let mut futures = FuturesUnordered::new();
+        let mut results = Vec::new();
 
-            futures.push(async move {
-                let mut guard = arc.lock().await;
-                if !guard.active {
-                    return SendResult {
+        for &id in ids {
+            let (arc, closed) = match self.entry(id) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    results.push(SendResult {
                         session_id: id,
                         success: false,
-                        error: Some("Session not active".to_string()),
-                    };
+                        error: Some(e.to_string()),
+                    });
+                    continue;
                 }
+            };
+            let data = data.to_vec();
+
+            futures.push(async move {
+                let sent = until_closed(closed, async {
+                    let mut guard = arc.lock().await;
+                    if !guard.active {
+                        return Ok(false);
+                    }
+                    guard.session.send(&data).await.map(|()| true)
+                })
+                .await;
 
-                match guard.session.send(&data).await {
-                    Ok(()) => SendResult {
+                match sent {
+                    Ok(true) => SendResult {
                         session_id: id,
                         success: true,
                         error: None,
                     },
+                    Ok(false) => SendResult {
+                        session_id: id,
+                        success: false,
+                        error: Some("Session not active".to_string()),
+                    },
                     Err(e) => SendResult {
                         session_id: id,
                         success: false,
@@ -327,7 +488,6 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
             });
         }
 
-        let mut results = Vec::new();
         while let Some(result) = futures.next().await {
             results.push(result);
         }
@@ -340,13 +500,12 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
     ///
     /// Returns an error if the session doesn't exist or expect fails.
     pub async fn expect(&self, id: SessionId, pattern: impl Into<Pattern>) -> Result<Match> {
-        let arc = self
-            .sessions
-            .get(&id)
-            .ok_or(ExpectError::SessionNotFound { id })?;
-
-        let mut guard = arc.lock().await;
-        guard.session.expect(pattern).await
+        let (arc, closed) = self.entry(id)?;
+        until_closed(closed, async {
+            let mut guard = arc.lock().await;
+            guard.session.expect(pattern).await
+        })
+        .await
     }
 
     /// Wait for any session to match the given pattern.
@@ -368,38 +527,12 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
     /// # Errors
     ///
     /// Returns an error if all sessions timeout or encounter errors.
-    #[allow(clippy::type_complexity)]
     pub async fn expect_any_of(&self, patterns: &[Pattern]) -> Result<SelectResult> {
-        if self.sessions.is_empty() {
+        if self.is_empty() {
             return Err(ExpectError::NoSessions);
         }
 
-        let pattern_set = PatternSet::from_patterns(patterns.to_vec());
-
-        // Create futures for all active sessions
-        let mut futures: FuturesUnordered<
-            Pin<Box<dyn Future<Output = (SessionId, Result<(Match, usize)>)> + Send>>,
-        > = FuturesUnordered::new();
-
-        for (&id, arc) in &self.sessions {
-            let arc = Arc::clone(arc);
-            let patterns = pattern_set.clone();
-
-            let future: Pin<Box<dyn Future<Output = (SessionId, Result<(Match, usize)>)> + Send>> =
-                Box::pin(async move {
-                    let mut guard = arc.lock().await;
-                    if !guard.active {
-                        return (id, Err(ExpectError::SessionClosed));
-                    }
-
-                    match guard.session.expect_any(&patterns).await {
-                        Ok(m) => (id, Ok((m, 0))), // pattern_index 0 for now
-                        Err(e) => (id, Err(e)),
-                    }
-                });
-
-            futures.push(future);
-        }
+        let mut futures = self.expect_futures(patterns, None);
 
         // Wait for the first successful match
         let mut last_error: Option<ExpectError> = None;
@@ -448,38 +581,12 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
     /// # Errors
     ///
     /// Returns an error if any session fails.
-    #[allow(clippy::type_complexity)]
     pub async fn expect_all_of(&self, patterns: &[Pattern]) -> Result<Vec<SelectResult>> {
-        if self.sessions.is_empty() {
+        if self.is_empty() {
             return Err(ExpectError::NoSessions);
         }
 
-        let pattern_set = PatternSet::from_patterns(patterns.to_vec());
-
-        // Create futures for all active sessions
-        let mut futures: FuturesUnordered<
-            Pin<Box<dyn Future<Output = (SessionId, Result<(Match, usize)>)> + Send>>,
-        > = FuturesUnordered::new();
-
-        for (&id, arc) in &self.sessions {
-            let arc = Arc::clone(arc);
-            let patterns = pattern_set.clone();
-
-            let future: Pin<Box<dyn Future<Output = (SessionId, Result<(Match, usize)>)> + Send>> =
-                Box::pin(async move {
-                    let mut guard = arc.lock().await;
-                    if !guard.active {
-                        return (id, Err(ExpectError::SessionClosed));
-                    }
-
-                    match guard.session.expect_any(&patterns).await {
-                        Ok(m) => (id, Ok((m, 0))),
-                        Err(e) => (id, Err(e)),
-                    }
-                });
-
-            futures.push(future);
-        }
+        let mut futures = self.expect_futures(patterns, None);
 
         // Collect all results
         let mut results = Vec::new();
@@ -511,6 +618,96 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
         Ok(results)
     }
 
+    /// Wait for all active sessions to match, giving up after `timeout`.
+    ///
+    /// Unlike [`expect_all_of`](Self::expect_all_of), this does not stop at
+    /// the first failure. Every active session is waited on until it
+    /// matches, fails, or the deadline passes, and the outcome for each is
+    /// reported. A session's own expect timeout also counts as timed out.
+    /// Inactive sessions are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error only if the manager has no sessions.
+    pub async fn expect_all_within(
+        &self,
+        patterns: &[Pattern],
+        timeout: Duration,
+    ) -> Result<ExpectAllResult> {
+        if self.is_empty() {
+            return Err(ExpectError::NoSessions);
+        }
+
+        let mut futures = self.expect_futures(patterns, Some(timeout));
+        let mut outcome = ExpectAllResult::default();
+
+        while let Some((session_id, result)) = futures.next().await {
+            match result {
+                Ok((matched, pattern_index)) => outcome.matched.push(SelectResult {
+                    session_id,
+                    matched,
+                    pattern_index,
+                }),
+                Err(ExpectError::Timeout { .. }) => outcome.timed_out.push(session_id),
+                Err(ExpectError::SessionClosed) if !self.is_active(session_id).await => {}
+                Err(e) => outcome.errored.push((session_id, e)),
+            }
+        }
+
+        outcome.matched.sort_by_key(|r| r.session_id);
+        outcome.timed_out.sort_unstable();
+        outcome.errored.sort_by_key(|(id, _)| *id);
+        Ok(outcome)
+    }
+
+    /// Build one expect future per session.
+    ///
+    /// Inactive sessions resolve to [`ExpectError::SessionClosed`]. With a
+    /// deadline, sessions still waiting when it passes resolve to a timeout.
+    fn expect_futures(&self, patterns: &[Pattern], deadline: Option<Duration>) -> ExpectFutures {
+        let pattern_set = PatternSet::from_patterns(patterns.to_vec());
+        let futures: ExpectFutures = FuturesUnordered::new();
+
+        let entries: Vec<_> = self
+            .slots()
+            .iter()
+            .map(|(&id, slot)| (id, Arc::clone(&slot.session), slot.closed.subscribe()))
+            .collect();
+
+        for (id, arc, closed) in entries {
+            let patterns = pattern_set.clone();
+
+            futures.push(Box::pin(async move {
+                let expect = until_closed(closed, async {
+                    let mut guard = arc.lock().await;
+                    if !guard.active {
+                        return Err(ExpectError::SessionClosed);
+                    }
+                    guard.session.expect_any(&patterns).await.map(|m| {
+                        let pattern_index = m.pattern_index;
+                        (m, pattern_index)
+                    })
+                });
+
+                let result = match deadline {
+                    Some(limit) => tokio::time::timeout(limit, expect)
+                        .await
+                        .unwrap_or_else(|_| {
+                            Err(ExpectError::Timeout {
+                                duration: limit,
+                                pattern: "multi-session expect".to_string(),
+                                buffer: String::new(),
+                            })
+                        }),
+                    None => expect.await,
+                };
+                (id, result)
+            }));
+        }
+
+        futures
+    }
+
     /// Execute a closure on a specific session.
     ///
     /// This provides direct access to the session for operations not covered
@@ -523,13 +720,12 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
     where
         F: FnOnce(&mut crate::session::Session<T>) -> R,
     {
-        let arc = self
-            .sessions
-            .get(&id)
-            .ok_or(ExpectError::SessionNotFound { id })?;
-
-        let mut guard = arc.lock().await;
-        Ok(f(&mut guard.session))
+        let (arc, closed) = self.entry(id)?;
+        until_closed(closed, async {
+            let mut guard = arc.lock().await;
+            Ok(f(&mut guard.session))
+        })
+        .await
     }
 
     /// Execute an async closure on a specific session.
@@ -542,13 +738,69 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static> MultiSessionManag
         F: FnOnce(&mut crate::session::Session<T>) -> Fut,
         Fut: Future<Output = R>,
     {
-        let arc = self
-            .sessions
-            .get(&id)
+        let (arc, closed) = self.entry(id)?;
+        until_closed(closed, async {
+            let mut guard = arc.lock().await;
+            Ok(f(&mut guard.session).await)
+        })
+        .await
+    }
+}
+
+#[cfg(any(unix, windows))]
+impl MultiSessionManager<crate::backend::PlatformPty> {
+    /// Spawn a process in a new PTY session and add it to the manager.
+    ///
+    /// The session uses the manager's default configuration (see
+    /// [`with_config`](Self::with_config)) and is labelled
+    /// `"<command>#<id>"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if spawning fails.
+    pub async fn spawn(&mut self, command: &str, args: &[&str]) -> Result<SessionId> {
+        let config = self.default_config.clone();
+        self.spawn_with_config(command, args, config).await
+    }
+
+    /// Spawn a process with a custom configuration and add it to the manager.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if spawning fails.
+    pub async fn spawn_with_config(
+        &mut self,
+        command: &str,
+        args: &[&str],
+        config: SessionConfig,
+    ) -> Result<SessionId> {
+        let session = crate::session::Session::spawn_with_config(command, args, config).await?;
+        let label = format!("{command}#{}", self.next_id);
+        Ok(self.add(session, label))
+    }
+
+    /// Detach a session, kill its process and wait for it to exit.
+    ///
+    /// The session is removed from the manager and any manager operation
+    /// still pending on it is cancelled with [`ExpectError::SessionClosed`],
+    /// so this does not wait for a long `expect` to finish. Waiting for the
+    /// exit is bounded by the manager's default timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session doesn't exist, killing fails, or the
+    /// process does not exit in time.
+    pub async fn close(&self, id: SessionId) -> Result<crate::types::ProcessExitStatus> {
+        let slot = self
+            .slots()
+            .remove(&id)
             .ok_or(ExpectError::SessionNotFound { id })?;
+        slot.closed.send_replace(true);
 
-        let mut guard = arc.lock().await;
-        Ok(f(&mut guard.session).await)
+        let mut guard = slot.session.lock().await;
+        guard.active = false;
+        guard.session.kill()?;
+        guard.session.wait_timeout(self.default_timeout).await
     }
 }
 
@@ -636,24 +888,26 @@ impl PatternSelector {
                 continue;
             }
 
-            let arc = match manager.sessions.get(&id) {
-                Some(arc) => Arc::clone(arc),
-                None => continue,
+            let Ok((arc, closed)) = manager.entry(id) else {
+                continue;
             };
 
             let pattern_set = PatternSet::from_patterns(patterns.to_vec());
 
             let future: Pin<Box<dyn Future<Output = (SessionId, Result<(Match, usize)>)> + Send>> =
                 Box::pin(async move {
-                    let mut guard = arc.lock().await;
-                    if !guard.active {
-                        return (id, Err(ExpectError::SessionClosed));
-                    }
-
-                    match guard.session.expect_any(&pattern_set).await {
-                        Ok(m) => (id, Ok((m, 0))),
-                        Err(e) => (id, Err(e)),
-                    }
+                    let result = until_closed(closed, async {
+                        let mut guard = arc.lock().await;
+                        if !guard.active {
+                            return Err(ExpectError::SessionClosed);
+                        }
+                        guard.session.expect_any(&pattern_set).await.map(|m| {
+                            let pattern_index = m.pattern_index;
+                            (m, pattern_index)
+                        })
+                    })
+                    .await;
+                    (id, result)
                 });
 
             futures.push(future);
@@ -744,6 +998,176 @@ mod tests {
         assert_eq!(selector.patterns_for(99).len(), 1); // Falls back to default
     }
 
+    #[tokio::test]
+    async fn pattern_selector_reports_session_pattern_index() {
+        use tokio::io::AsyncWriteExt;
+
+        let mut manager: MultiSessionManager<DuplexStream> = MultiSessionManager::new();
+        let (client0, _server0) = create_mock_transport();
+        let (client1, mut server1) = create_mock_transport();
+        let id0 = manager.add(
+            crate::session::Session::new(client0, SessionConfig::default()),
+            "quiet",
+        );
+        let id1 = manager.add(
+            crate::session::Session::new(client1, SessionConfig::default()),
+            "worker",
+        );
+
+        let selector = PatternSelector::new()
+            .session(id0, "ok")
+            .session(id1, "ok")
+            .session(id1, "err")
+            .timeout(Duration::from_secs(1));
+
+        server1.write_all(b"job err: disk full\n").await.unwrap();
+        let result = selector.select(&manager).await.unwrap();
+        assert_eq!(result.session_id, id1);
+        assert_eq!(result.pattern_index, 1);
+        assert_eq!(result.matched.matched, "err");
+
+        server1.write_all(b"retry ok\n").await.unwrap();
+        let result = selector.select(&manager).await.unwrap();
+        assert_eq!(result.session_id, id1);
+        assert_eq!(result.pattern_index, 0);
+    }
+
+    #[tokio::test]
+    async fn pattern_selector_default_pattern_index() {
+        use tokio::io::AsyncWriteExt;
+
+        let mut manager: MultiSessionManager<DuplexStream> = MultiSessionManager::new();
+        let (client, mut server) = create_mock_transport();
+        let id = manager.add(
+            crate::session::Session::new(client, SessionConfig::default()),
+            "shell",
+        );
+
+        let selector = PatternSelector::new()
+            .default_pattern("$ ")
+            .default_pattern("# ")
+            .timeout(Duration::from_secs(1));
+
+        server.write_all(b"root# ").await.unwrap();
+        let result = selector.select(&manager).await.unwrap();
+        assert_eq!(result.session_id, id);
+        assert_eq!(result.pattern_index, 1);
+    }
+
+    #[tokio::test]
+    async fn send_to_subset() {
+        use tokio::io::AsyncReadExt;
+
+        let mut manager: MultiSessionManager<DuplexStream> = MultiSessionManager::new();
+        let mut servers = Vec::new();
+        let mut ids = Vec::new();
+        for label in ["a", "b", "c"] {
+            let (client, server) = create_mock_transport();
+            ids.push(manager.add(
+                crate::session::Session::new(client, SessionConfig::default()),
+                label,
+            ));
+            servers.push(server);
+        }
+
+        let results = manager.send_to(&[ids[0], ids[2]], b"ping").await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.success));
+
+        for i in [0, 2] {
+            let mut buf = [0u8; 4];
+            servers[i].read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"ping");
+        }
+
+        // The third session received nothing.
+        let mut buf = [0u8; 4];
+        let read = tokio::time::timeout(Duration::from_millis(50), servers[1].read(&mut buf)).await;
+        assert!(read.is_err());
+    }
+
+    #[tokio::test]
+    async fn send_to_inactive_and_unknown() {
+        let mut manager: MultiSessionManager<DuplexStream> = MultiSessionManager::new();
+        let (client, _server) = create_mock_transport();
+        let id = manager.add(
+            crate::session::Session::new(client, SessionConfig::default()),
+            "idle",
+        );
+        manager.set_active(id, false).await;
+
+        let results = manager.send_to(&[id, 42], b"x").await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| !r.success && r.error.is_some()));
+    }
+
+    #[tokio::test]
+    async fn expect_all_within_reports_timeouts() {
+        use tokio::io::AsyncWriteExt;
+
+        let mut manager: MultiSessionManager<DuplexStream> = MultiSessionManager::new();
+        let mut servers = Vec::new();
+        let mut ids = Vec::new();
+        for label in ["fast", "hung", "closed", "idle"] {
+            let (client, server) = create_mock_transport();
+            ids.push(manager.add(
+                crate::session::Session::new(client, SessionConfig::default()),
+                label,
+            ));
+            servers.push(server);
+        }
+        manager.set_active(ids[3], false).await;
+
+        servers[0].write_all(b"deploy ok\n").await.unwrap();
+        // ids[1] never writes; ids[2] closes without matching
+        servers[2].write_all(b"crashed\n").await.unwrap();
+        drop(servers.remove(2));
+
+        let outcome = manager
+            .expect_all_within(&[Pattern::literal("ok")], Duration::from_millis(100))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.matched_ids(), vec![ids[0]]);
+        assert_eq!(outcome.timed_out, vec![ids[1]]);
+        assert_eq!(outcome.errored.len(), 1);
+        assert_eq!(outcome.errored[0].0, ids[2]);
+        assert!(!outcome.all_matched());
+    }
+
+    #[tokio::test]
+    async fn remove_arc_detaches_shared_session() {
+        use tokio::io::AsyncWriteExt;
+
+        let mut manager: MultiSessionManager<DuplexStream> = MultiSessionManager::new();
+        let (client, mut server) = create_mock_transport();
+        let id = manager.add(
+            crate::session::Session::new(client, SessionConfig::default()),
+            "shared",
+        );
+
+        let arc = manager.remove_arc(id).unwrap();
+        assert!(manager.is_empty());
+
+        // An expect is pending on the detached session while we hold it.
+        let pending = tokio::spawn({
+            let arc = Arc::clone(&arc);
+            async move {
+                let mut guard = arc.lock().await;
+                guard.session_mut().expect("ready").await.map(|m| m.matched)
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!pending.is_finished());
+
+        server.write_all(b"ready\n").await.unwrap();
+        assert_eq!(pending.await.unwrap().unwrap(), "ready");
+
+        let detached = Arc::try_unwrap(arc).unwrap().into_inner();
+        assert_eq!(detached.label(), "shared");
+        let _session = detached.into_session();
+    }
+
     #[tokio::test]
     async fn expect_any_no_sessions() {
         let manager: MultiSessionManager<DuplexStream> = MultiSessionManager::new();
@@ -8,6 +8,7 @@
 //! - Email addresses
 //! - Phone numbers
 //! - API keys and tokens
+//! - IBANs and national identifiers (UK NINO, Canadian SIN)
 //! - Custom patterns (user-defined)
 //!
 //! # Example
@@ -45,7 +46,7 @@ pub mod registry;
 pub mod ssn;
 
 pub use detector::{CustomPattern, PiiDetector, PiiMatch, PiiType};
-pub use redactor::{PiiRedactor, RedactionStyle, StreamingRedactor};
+pub use redactor::{DEFAULT_HOLDBACK, PiiRedactor, RedactionStyle, StreamingRedactor};
 pub use registry::{PatternEntry, PatternRegistry, PatternSet};
 
 /// Quick check if text contains any PII.
@@ -3,7 +3,9 @@
 //! This module provides functionality for redacting detected PII
 //! from text, replacing sensitive information with placeholders.
 
-use super::detector::{PiiDetector, PiiMatch, PiiType};
+use super::detector::{
+    PiiDetector, PiiMatch, PiiType, may_occur_in_builtin_match, may_start_builtin_match,
+};
 
 /// Redaction style.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,6 +31,8 @@ pub struct PiiRedactor {
     style: RedactionStyle,
     /// Custom placeholders per PII type.
     custom_placeholders: std::collections::HashMap<PiiType, String>,
+    /// Salt for consistent tokens, if enabled.
+    token_salt: Option<String>,
 }
 
 impl Default for PiiRedactor {
@@ -45,6 +49,7 @@ impl PiiRedactor {
             detector: PiiDetector::new(),
             style: RedactionStyle::Placeholder,
             custom_placeholders: std::collections::HashMap::new(),
+            token_salt: None,
         }
     }
 
@@ -72,11 +77,40 @@ impl PiiRedactor {
         self
     }
 
+    /// Tag each placeholder with a short hash of the redacted value.
+    ///
+    /// Identical values then redact to identical tokens (for example
+    /// `[EMAIL#3f2a9c0d5b7e1486]`), so occurrences can be correlated in a
+    /// transcript without revealing the value. The token is a 64-bit
+    /// HMAC-SHA256 of the value keyed with the salt, so it links the
+    /// occurrences of a value by design, and anyone holding the salt can
+    /// test guesses against it. Keep the salt secret and random: with a
+    /// known or guessable salt, short values such as phone numbers can be
+    /// recovered by trying them all. Use the same salt to get the same
+    /// tokens across runs. Only placeholder
+    /// replacements are tagged; the asterisk, X and partial-mask styles are
+    /// unaffected.
+    #[must_use]
+    pub fn with_consistent_tokens(mut self, salt: impl Into<String>) -> Self {
+        self.token_salt = Some(salt.into());
+        self
+    }
+
     /// Redact PII from the given text.
     #[must_use]
     pub fn redact(&self, text: &str) -> String {
         let matches = self.detector.detect(text);
+        self.redact_matches(text, &matches)
+    }
 
+    /// Create a [`StreamingRedactor`] that shares this redactor's settings.
+    #[must_use]
+    pub fn stream(&self) -> StreamingRedactor {
+        StreamingRedactor::new(self.clone())
+    }
+
+    /// Replace already-detected matches in `text`.
+    fn redact_matches(&self, text: &str, matches: &[PiiMatch]) -> String {
         if matches.is_empty() {
             return text.to_string();
         }
@@ -84,7 +118,7 @@ impl PiiRedactor {
         let mut result = String::with_capacity(text.len());
         let mut last_end = 0;
 
-        for m in &matches {
+        for m in matches {
             // Add text before this match
             result.push_str(&text[last_end..m.start]);
             // Add redaction
@@ -110,16 +144,39 @@ impl PiiRedactor {
         if !m.is_custom()
             && let Some(custom) = self.custom_placeholders.get(&m.pii_type)
         {
-            return custom.clone();
+            return self.tokenize(custom, &m.text);
         }
 
         match self.style {
             // Use PiiMatch::placeholder() which handles both built-in and custom patterns
-            RedactionStyle::Placeholder => m.placeholder().to_string(),
+            RedactionStyle::Placeholder | RedactionStyle::Custom => {
+                self.tokenize(m.placeholder(), &m.text)
+            }
             RedactionStyle::Asterisks => "*".repeat(m.len()),
             RedactionStyle::Xs => "X".repeat(m.len()),
             RedactionStyle::PartialMask => self.partial_mask(&m.text),
-            RedactionStyle::Custom => m.placeholder().to_string(),
+        }
+    }
+
+    /// Append the consistent token for `value` to a placeholder, if enabled.
+    ///
+    /// `[EMAIL REDACTED]` becomes `[EMAIL#3f2a9c0d5b7e1486]`; placeholders without
+    /// brackets get the suffix appended.
+    fn tokenize(&self, placeholder: &str, value: &str) -> String {
+        let Some(salt) = &self.token_salt else {
+            return placeholder.to_string();
+        };
+
+        let token = token_hash(salt, value);
+        match placeholder
+            .strip_prefix('[')
+            .and_then(|p| p.strip_suffix(']'))
+        {
+            Some(label) => {
+                let label = label.strip_suffix(" REDACTED").unwrap_or(label);
+                format!("[{label}#{token:016x}]")
+            }
+            None => format!("{placeholder}#{token:016x}"),
         }
     }
 
@@ -149,10 +206,49 @@ impl PiiRedactor {
     }
 }
 
+/// Hash a value to a 64-bit token with HMAC-SHA256 keyed by `salt`.
+fn token_hash(salt: &str, value: &str) -> u64 {
+    use hmac::{Hmac, Mac};
+
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(salt.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(value.as_bytes());
+    let digest = mac.finalize().into_bytes();
+    let mut token = [0; 8];
+    token.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(token)
+}
+
+/// Default limit on how many trailing bytes a [`StreamingRedactor`] holds
+/// back.
+///
+/// This is the longest PII match the stream can reassemble across chunk
+/// boundaries. All built-in bounded patterns fit comfortably within it.
+pub const DEFAULT_HOLDBACK: usize = 256;
+
 /// A streaming redactor for processing data in chunks.
+///
+/// PTY output arrives in arbitrary chunks, so a value such as
+/// `123-45-6789` may be split across two reads. The streaming redactor
+/// holds back the tail of its input that a match could still be growing
+/// into, and never emits a prefix that ends inside a detected match, so a
+/// split value is redacted once the rest of it arrives.
+///
+/// Text up to the last newline is always released, as matches are assumed
+/// not to span lines. After it, output is released up to the point where
+/// a built-in match could still start: a prompt such as `user@host:~$ `
+/// goes out at once, while `SSN 123-4` waits for the next chunk. With
+/// custom patterns, whose characters are unknown, the whole unterminated
+/// line is held. At most [`holdback`](Self::holdback) bytes are ever held,
+/// so a match longer than that may still be split.
+///
+/// Use [`PiiRedactor::stream`] to create one from an existing redactor.
 pub struct StreamingRedactor {
     redactor: PiiRedactor,
     buffer: String,
+    /// Trailing bytes of an incomplete UTF-8 sequence from [`feed`](Self::feed).
+    pending: Vec<u8>,
+    holdback: usize,
     max_buffer: usize,
 }
 
@@ -163,11 +259,26 @@ impl StreamingRedactor {
         Self {
             redactor,
             buffer: String::new(),
+            pending: Vec::new(),
+            holdback: DEFAULT_HOLDBACK,
             max_buffer: 4096,
         }
     }
 
+    /// Set the most trailing bytes held back between chunks.
+    ///
+    /// This should be at least the length of the longest value that must
+    /// be redacted when split across chunks.
+    #[must_use]
+    pub const fn holdback(mut self, bytes: usize) -> Self {
+        self.holdback = bytes;
+        self
+    }
+
     /// Set the maximum buffer size.
+    ///
+    /// When a single match keeps the buffer from draining and it grows past
+    /// this size, the holdback boundary is used even if it splits the match.
     #[must_use]
     pub const fn max_buffer(mut self, size: usize) -> Self {
         self.max_buffer = size;
@@ -180,45 +291,100 @@ impl StreamingRedactor {
     pub fn process(&mut self, data: &str) -> String {
         self.buffer.push_str(data);
 
-        // Find a safe point to redact (end of line or max buffer)
-        let safe_point = self.find_safe_point();
-
-        if safe_point > 0 {
-            let to_process = self.buffer[..safe_point].to_string();
-            self.buffer = self.buffer[safe_point..].to_string();
-            self.redactor.redact(&to_process)
-        } else {
-            String::new()
+        let matches = self.redactor.detector.detect(&self.buffer);
+        let safe_point = self.find_safe_point(&matches);
+        if safe_point == 0 {
+            return String::new();
         }
+
+        let settled: Vec<PiiMatch> = matches
+            .into_iter()
+            .take_while(|m| m.end <= safe_point)
+            .collect();
+        let output = self
+            .redactor
+            .redact_matches(&self.buffer[..safe_point], &settled);
+        self.buffer.drain(..safe_point);
+        output
+    }
+
+    /// Process a chunk of raw bytes.
+    ///
+    /// An incomplete UTF-8 sequence at the end of `data` is kept until the
+    /// next call; other invalid bytes are replaced with U+FFFD.
+    pub fn feed(&mut self, data: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(data);
+        let complete = match std::str::from_utf8(&self.pending) {
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            _ => self.pending.len(),
+        };
+        let rest = self.pending.split_off(complete);
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending = rest;
+        self.process(&text).into_bytes()
     }
 
     /// Flush any remaining data.
     pub fn flush(&mut self) -> String {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            self.buffer.push_str(&String::from_utf8_lossy(&pending));
+        }
         let remaining = std::mem::take(&mut self.buffer);
         self.redactor.redact(&remaining)
     }
 
     /// Find a safe point to split the buffer.
-    fn find_safe_point(&self) -> usize {
-        if self.buffer.len() >= self.max_buffer {
-            // Force a split at max buffer
-            return self.max_buffer;
+    ///
+    /// The point is where a match could still be starting, but no more
+    /// than `holdback` bytes before the end of the buffer, moved back to
+    /// the start of any match that straddles it.
+    fn find_safe_point(&self, matches: &[PiiMatch]) -> usize {
+        let boundary = floor_char_boundary(
+            &self.buffer,
+            self.buffer.len().saturating_sub(self.holdback),
+        )
+        .max(self.open_tail_start());
+
+        let straddling = matches
+            .iter()
+            .find(|m| m.start < boundary && m.end > boundary);
+        match straddling {
+            Some(m) if m.start > 0 || self.buffer.len() < self.max_buffer => m.start,
+            _ => boundary,
         }
+    }
 
-        // Try to find a newline
-        if let Some(pos) = self.buffer.rfind('\n') {
-            return pos + 1;
+    /// Where the tail begins that a match may still be growing into.
+    ///
+    /// That is after the last newline and, with only built-in patterns, at
+    /// the first character that can start a match after the last one that
+    /// cannot occur in any.
+    fn open_tail_start(&self) -> usize {
+        let line_start = self.buffer.rfind('\n').map_or(0, |i| i + 1);
+        if self.redactor.detector.custom_pattern_count() > 0 {
+            return line_start;
         }
 
-        // Try to find a space
-        if self.buffer.len() > 100
-            && let Some(pos) = self.buffer[..100].rfind(' ')
-        {
-            return pos + 1;
-        }
+        let line = &self.buffer[line_start..];
+        let after_break = line
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| !may_occur_in_builtin_match(c))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let start = line[after_break..]
+            .find(may_start_builtin_match)
+            .map_or(line.len(), |i| after_break + i);
+        line_start + start
+    }
+}
 
-        0
+/// Round `index` down to the nearest char boundary in `text`.
+const fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
     }
+    index
 }
 
 #[cfg(test)]
@@ -275,6 +441,117 @@ mod tests {
         assert!(!combined.contains("user@example.com"));
     }
 
+    #[test]
+    fn stream_redacts_ssn_split_across_feeds() {
+        let mut stream = PiiRedactor::new().stream();
+
+        let mut out = stream.feed(b"SSN 123-4");
+        out.extend(stream.feed(b"5-6789 end"));
+        out.extend(stream.flush().into_bytes());
+
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text, "SSN [SSN REDACTED] end");
+    }
+
+    #[test]
+    fn stream_emits_text_beyond_holdback() {
+        let mut stream = PiiRedactor::new().stream().holdback(16);
+
+        let out = stream.process("card 4111-1111-1111-1111 then plenty of trailing text");
+        assert!(out.starts_with("card [CARD REDACTED]"));
+        assert!(!out.contains("4111"));
+
+        let rest = stream.flush();
+        assert_eq!(rest.len(), 16);
+        assert!(format!("{out}{rest}").ends_with("trailing text"));
+    }
+
+    #[test]
+    fn stream_holds_back_match_straddling_boundary() {
+        let mut stream = PiiRedactor::new().stream().holdback(4);
+
+        let out = stream.process("id 123-45-6789");
+        assert_eq!(out, "id ");
+        assert_eq!(stream.flush(), "[SSN REDACTED]");
+    }
+
+    #[test]
+    fn stream_releases_complete_lines() {
+        let mut stream = PiiRedactor::new().stream();
+        assert_eq!(stream.process("done 123\nnext 4"), "done 123\n");
+        assert_eq!(stream.flush(), "next 4");
+    }
+
+    #[test]
+    fn stream_releases_text_no_match_can_continue() {
+        let mut stream = PiiRedactor::new().stream();
+        assert_eq!(stream.process("user@host:~$ "), "user@host:~$ ");
+
+        assert_eq!(stream.process("echo > 123-4"), "echo > ");
+        assert_eq!(stream.process("5-6789\n"), "[SSN REDACTED]\n");
+    }
+
+    #[test]
+    fn stream_holds_open_line_with_custom_patterns() {
+        let detector = PiiDetector::new().add_pattern("ticket", r"#T\d{6}", "[TICKET]", 0.9);
+        let mut stream = PiiRedactor::with_detector(detector).stream();
+
+        assert_eq!(stream.process("see #T12"), "");
+        let out = stream.process("3456 now\n");
+        assert_eq!(out, "see [TICKET] now\n");
+    }
+
+    #[test]
+    fn stream_feed_keeps_split_utf8_sequence() {
+        let mut stream = PiiRedactor::new().stream().holdback(0);
+
+        let bytes = "caf\u{e9}".as_bytes();
+        let mut out = stream.feed(&bytes[..4]);
+        assert_eq!(out, b"caf");
+        out.extend(stream.feed(&bytes[4..]));
+        out.extend(stream.flush().into_bytes());
+        assert_eq!(String::from_utf8(out).unwrap(), "caf\u{e9}");
+    }
+
+    #[test]
+    fn consistent_tokens_are_deterministic() {
+        let redactor = PiiRedactor::new().with_consistent_tokens("salt");
+        let text = "From a@example.com to b@example.com, cc a@example.com";
+
+        let first = redactor.redact(text);
+        assert_eq!(first, redactor.redact(text));
+        assert!(!first.contains("example.com"));
+
+        let tokens: Vec<&str> = first
+            .split([' ', ','])
+            .filter(|w| w.starts_with("[EMAIL#"))
+            .collect();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].len(), "[EMAIL#]".len() + 16);
+        assert_eq!(tokens[0], tokens[2]);
+        assert_ne!(tokens[0], tokens[1]);
+    }
+
+    #[test]
+    fn token_hash_is_truncated_hmac_sha256() {
+        assert_eq!(
+            token_hash("key", "The quick brown fox jumps over the lazy dog"),
+            0xf7bc_83f4_3053_8424
+        );
+    }
+
+    #[test]
+    fn consistent_tokens_depend_on_salt() {
+        let a = PiiRedactor::new().with_consistent_tokens("one");
+        let b = PiiRedactor::new().with_consistent_tokens("two");
+        assert_ne!(a.redact("user@example.com"), b.redact("user@example.com"));
+
+        let custom = PiiRedactor::new()
+            .custom_placeholder(PiiType::Email, "<email>")
+            .with_consistent_tokens("one");
+        assert!(custom.redact("user@example.com").starts_with("<email>#"));
+    }
+
     #[test]
     fn redact_custom_pattern() {
         let detector = PiiDetector::new().add_pattern(
@@ -27,15 +27,16 @@ pub use crate::config::{
 };
 // Encoding utilities
 pub use crate::encoding::{
-    DetectedEncoding, EncodedText, LineEndingStyle, decode_utf8_lossy, detect_encoding_from_env,
-    detect_line_ending, normalize_line_endings, strip_ansi,
+    BareCr, DetectedEncoding, EncodedText, LineEndingStyle, decode_utf8_lossy,
+    detect_encoding_from_env, detect_encoding_from_locale, detect_line_ending,
+    normalize_line_endings, normalize_line_endings_with, strip_ansi,
 };
 // Error handling
 pub use crate::error::{ExpectError, Result, SpawnError};
 // Pattern types
 pub use crate::expect::{Matcher, Pattern, PatternManager, PatternSet, RingBuffer};
 // Send traits
-pub use crate::send::{AnsiSend, BasicSend, HumanTyper, Sender};
+pub use crate::send::{AnsiSend, BasicSend, HumanTyper, SendFileMode, Sender};
 // Session types
 pub use crate::session::{QuickSession, Session, SessionBuilder};
 // Sync wrapper
@@ -45,4 +46,4 @@ pub use crate::types::{
     ControlChar, Dimensions, ExpectResult, Match, ProcessExitStatus, SessionId, SessionState,
 };
 // Macros (re-exported from rust-expect-macros)
-pub use crate::{dialog, patterns, regex, timeout};
+pub use crate::{bytes_regex, dialog, patterns, regex, timeout};
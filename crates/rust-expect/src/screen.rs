@@ -21,9 +21,9 @@ pub mod parser;
 pub mod query;
 
 pub use buffer::{
-    Attributes, Cell, CellChange, ChangeType, Color, Cursor, ScreenBuffer, ScreenDiff,
+    Attributes, Cell, CellChange, CellStyle, ChangeType, Color, Cursor, Hyperlink, MAX_COMBINING,
+    ScreenBuffer, ScreenDiff, StyledSpan,
 };
-use parser::apply_sgr;
 pub use parser::{AnsiParser, AnsiSequence, EraseMode, ParseResult};
 pub use query::{Region, ScreenQuery, ScreenQueryExt};
 
@@ -32,14 +32,6 @@ pub use query::{Region, ScreenQuery, ScreenQueryExt};
 pub struct Screen {
     /// The screen buffer.
     buffer: ScreenBuffer,
-    /// The ANSI parser.
-    parser: AnsiParser,
-    /// Current foreground color.
-    fg: Color,
-    /// Current background color.
-    bg: Color,
-    /// Current text attributes.
-    attrs: Attributes,
 }
 
 impl Screen {
@@ -48,10 +40,6 @@ impl Screen {
     pub fn new(rows: usize, cols: usize) -> Self {
         Self {
             buffer: ScreenBuffer::new(rows, cols),
-            parser: AnsiParser::new(),
-            fg: Color::Default,
-            bg: Color::Default,
-            attrs: Attributes::empty(),
         }
     }
 
@@ -91,12 +79,10 @@ impl Screen {
     }
 
     /// Process input bytes.
+    ///
+    /// See [`ScreenBuffer::process_bytes`].
     pub fn process(&mut self, data: &[u8]) {
-        for byte in data {
-            if let Some(result) = self.parser.parse(*byte) {
-                self.apply_result(result);
-            }
-        }
+        self.buffer.process_bytes(data);
     }
 
     /// Process a string.
@@ -104,235 +90,6 @@ impl Screen {
         self.process(s.as_bytes());
     }
 
-    /// Apply a parse result to the screen.
-    fn apply_result(&mut self, result: ParseResult) {
-        match result {
-            ParseResult::Print(c) => {
-                self.buffer.set_style(self.fg, self.bg, self.attrs);
-                self.buffer.write_char(c);
-            }
-            ParseResult::Control(c) => self.apply_control(c),
-            ParseResult::Sequence(seq) => self.apply_sequence(seq),
-        }
-    }
-
-    /// Apply a control character.
-    fn apply_control(&mut self, c: u8) {
-        match c {
-            0x07 => {
-                // Bell - ignored
-            }
-            0x08 => {
-                // Backspace
-                let cursor = self.buffer.cursor_mut();
-                if cursor.col > 0 {
-                    cursor.col -= 1;
-                }
-            }
-            0x09 => {
-                // Tab - move to next tab stop (every 8 columns)
-                let cols = self.buffer.cols();
-                let cursor = self.buffer.cursor_mut();
-                cursor.col = ((cursor.col / 8) + 1) * 8;
-                if cursor.col >= cols {
-                    cursor.col = cols - 1;
-                }
-            }
-            0x0a..=0x0c => {
-                // Line feed (LF), Vertical Tab (VT), Form Feed (FF)
-                // All behave the same in VT100: move down one line, scroll if needed
-                // Also reset column (newline mode behavior)
-                let rows = self.buffer.rows();
-                let cursor_row = self.buffer.cursor().row + 1;
-                if cursor_row >= rows {
-                    self.buffer.scroll_up(1);
-                    self.buffer.cursor_mut().row = rows - 1;
-                } else {
-                    self.buffer.cursor_mut().row = cursor_row;
-                }
-                self.buffer.cursor_mut().col = 0;
-            }
-            0x0d => {
-                // Carriage return
-                self.buffer.cursor_mut().col = 0;
-            }
-            _ => {}
-        }
-    }
-
-    /// Apply an ANSI sequence.
-    #[allow(clippy::too_many_lines)] // Large match over AnsiSequence variants - structure is clear
-    fn apply_sequence(&mut self, seq: AnsiSequence) {
-        match seq {
-            AnsiSequence::CursorUp(n) => {
-                let cursor = self.buffer.cursor_mut();
-                cursor.row = cursor.row.saturating_sub(n as usize);
-            }
-            AnsiSequence::CursorDown(n) => {
-                let rows = self.buffer.rows();
-                let cursor = self.buffer.cursor_mut();
-                cursor.row = (cursor.row + n as usize).min(rows.saturating_sub(1));
-            }
-            AnsiSequence::CursorForward(n) => {
-                let cols = self.buffer.cols();
-                let cursor = self.buffer.cursor_mut();
-                cursor.col = (cursor.col + n as usize).min(cols.saturating_sub(1));
-            }
-            AnsiSequence::CursorBackward(n) => {
-                let cursor = self.buffer.cursor_mut();
-                cursor.col = cursor.col.saturating_sub(n as usize);
-            }
-            AnsiSequence::CursorNextLine(n) => {
-                // Move to beginning of line n lines down
-                let rows = self.buffer.rows();
-                let cursor = self.buffer.cursor_mut();
-                cursor.row = (cursor.row + n as usize).min(rows.saturating_sub(1));
-                cursor.col = 0;
-            }
-            AnsiSequence::CursorPrevLine(n) => {
-                // Move to beginning of line n lines up
-                let cursor = self.buffer.cursor_mut();
-                cursor.row = cursor.row.saturating_sub(n as usize);
-                cursor.col = 0;
-            }
-            AnsiSequence::CursorColumn(n) => {
-                // Move cursor to column n (1-based)
-                let cols = self.buffer.cols();
-                let cursor = self.buffer.cursor_mut();
-                cursor.col = (n.saturating_sub(1) as usize).min(cols.saturating_sub(1));
-            }
-            AnsiSequence::CursorRow(n) => {
-                // Move cursor to row n (1-based)
-                let rows = self.buffer.rows();
-                let cursor = self.buffer.cursor_mut();
-                cursor.row = (n.saturating_sub(1) as usize).min(rows.saturating_sub(1));
-            }
-            AnsiSequence::CursorPosition { row, col } => {
-                self.buffer.goto(
-                    (row.saturating_sub(1)) as usize,
-                    (col.saturating_sub(1)) as usize,
-                );
-            }
-            AnsiSequence::EraseDisplay(mode) => match mode {
-                EraseMode::ToEnd => self.buffer.clear_to_end(),
-                EraseMode::ToStart => self.buffer.clear_to_start(),
-                EraseMode::All => self.buffer.clear(),
-            },
-            AnsiSequence::EraseLine(mode) => match mode {
-                EraseMode::ToEnd => self.buffer.clear_line_to_end(),
-                EraseMode::ToStart => {
-                    // Clear from start of line to cursor
-                    let row = self.buffer.cursor().row;
-                    let col = self.buffer.cursor().col;
-                    for c in 0..=col {
-                        self.buffer.set(row, c, Cell::default());
-                    }
-                }
-                EraseMode::All => self.buffer.clear_line(),
-            },
-            AnsiSequence::EraseChars(n) => {
-                // Erase n characters from cursor position (replace with spaces)
-                let row = self.buffer.cursor().row;
-                let col = self.buffer.cursor().col;
-                let cols = self.buffer.cols();
-                let end = (col + n as usize).min(cols);
-                for c in col..end {
-                    self.buffer.set(row, c, Cell::default());
-                }
-            }
-            AnsiSequence::SetGraphics(params) => {
-                apply_sgr(&params, &mut self.fg, &mut self.bg, &mut self.attrs);
-            }
-            AnsiSequence::ScrollUp(n) => {
-                self.buffer.scroll_up(n as usize);
-            }
-            AnsiSequence::ScrollDown(n) => {
-                self.buffer.scroll_down(n as usize);
-            }
-            AnsiSequence::ReverseIndex => {
-                // Move cursor up, scroll down if at top of scroll region
-                let cursor_row = self.buffer.cursor().row;
-                let (top, _) = (0, self.buffer.rows() - 1); // Use full screen for now
-                if cursor_row == top {
-                    self.buffer.scroll_down(1);
-                } else {
-                    self.buffer.cursor_mut().row = cursor_row.saturating_sub(1);
-                }
-            }
-            AnsiSequence::Index => {
-                // Move cursor down, scroll up if at bottom
-                let rows = self.buffer.rows();
-                let cursor_row = self.buffer.cursor().row;
-                if cursor_row >= rows - 1 {
-                    self.buffer.scroll_up(1);
-                } else {
-                    self.buffer.cursor_mut().row = cursor_row + 1;
-                }
-            }
-            AnsiSequence::NextLine => {
-                // Move to start of next line, scroll if needed
-                let rows = self.buffer.rows();
-                let cursor_row = self.buffer.cursor().row;
-                if cursor_row >= rows - 1 {
-                    self.buffer.scroll_up(1);
-                    self.buffer.cursor_mut().row = rows - 1;
-                } else {
-                    self.buffer.cursor_mut().row = cursor_row + 1;
-                }
-                self.buffer.cursor_mut().col = 0;
-            }
-            AnsiSequence::SaveCursor => {
-                self.buffer.save_cursor();
-            }
-            AnsiSequence::RestoreCursor => {
-                self.buffer.restore_cursor();
-            }
-            AnsiSequence::SetScrollRegion { top, bottom } => {
-                let top = (top.saturating_sub(1)) as usize;
-                let bottom = if bottom == 0 {
-                    self.buffer.rows() - 1
-                } else {
-                    (bottom.saturating_sub(1)) as usize
-                };
-                self.buffer.set_scroll_region(top, bottom);
-            }
-            AnsiSequence::ShowCursor => {
-                self.buffer.cursor_mut().visible = true;
-            }
-            AnsiSequence::HideCursor => {
-                self.buffer.cursor_mut().visible = false;
-            }
-            AnsiSequence::InsertLines(n) => {
-                self.buffer.insert_lines(n as usize);
-            }
-            AnsiSequence::DeleteLines(n) => {
-                self.buffer.delete_lines(n as usize);
-            }
-            AnsiSequence::InsertChars(n) => {
-                self.buffer.insert_chars(n as usize);
-            }
-            AnsiSequence::DeleteChars(n) => {
-                self.buffer.delete_chars(n as usize);
-            }
-            AnsiSequence::RepeatChar(n) => {
-                // Repeat the last printed character n times
-                // Note: We don't track last char, so this is a no-op for now
-                // A full implementation would track last_printed_char
-                let _ = n;
-            }
-            AnsiSequence::Reset => {
-                self.buffer.clear();
-                self.buffer.goto(0, 0);
-                self.fg = Color::Default;
-                self.bg = Color::Default;
-                self.attrs = Attributes::empty();
-            }
-            AnsiSequence::Unknown(_) => {
-                // Ignore unknown sequences
-            }
-        }
-    }
-
     /// Get the text content of the screen.
     #[must_use]
     pub fn text(&self) -> String {
@@ -404,6 +161,66 @@ mod tests {
         assert_eq!(cell.fg, Color::Red);
     }
 
+    #[test]
+    fn screen_erase_display_clears_every_row() {
+        let mut screen = Screen::new(3, 10);
+        screen.process_str("one\r\ntwo\r\nthree\x1b[2J");
+        assert_eq!(screen.text().trim(), "");
+        // ED does not move the cursor
+        assert_eq!((screen.cursor().row, screen.cursor().col), (2, 5));
+    }
+
+    #[test]
+    fn screen_cursor_position_is_one_based() {
+        let mut screen = Screen::new(24, 80);
+        screen.process_str("\x1b[10;5HX");
+        assert_eq!(screen.buffer().get(9, 4).unwrap().char, 'X');
+        assert_eq!((screen.cursor().row, screen.cursor().col), (9, 5));
+    }
+
+    #[test]
+    fn screen_sgr_color_runs() {
+        let mut screen = Screen::new(2, 20);
+        screen.process_str("\x1b[1;31mab\x1b[32;44mcd\x1b[0me");
+
+        let cells: Vec<_> = (0..5)
+            .map(|col| *screen.buffer().get(0, col).unwrap())
+            .collect();
+        assert_eq!((cells[0].fg, cells[1].fg), (Color::Red, Color::Red));
+        assert!(cells[1].attrs.contains(Attributes::BOLD));
+        assert_eq!((cells[2].fg, cells[2].bg), (Color::Green, Color::Blue));
+        assert!(cells[3].attrs.contains(Attributes::BOLD));
+        assert_eq!(cells[4].fg, Color::Default);
+        assert_eq!(cells[4].bg, Color::Default);
+        assert!(cells[4].attrs.is_empty());
+    }
+
+    #[test]
+    fn screen_sequences_split_across_calls() {
+        let mut screen = Screen::new(24, 80);
+        screen.process(b"\x1b[1");
+        screen.process(b"0;5");
+        screen.process(b"H\x1b[3");
+        screen.process(b"1m\xc3");
+        screen.process(b"\xa9");
+
+        let cell = screen.buffer().get(9, 4).unwrap();
+        assert_eq!(cell.char, '\u{e9}');
+        assert_eq!(cell.fg, Color::Red);
+    }
+
+    #[test]
+    fn screen_scroll_region_limits_scrolling() {
+        let mut screen = Screen::new(4, 10);
+        screen.process_str("header\x1b[2;4r");
+        assert_eq!((screen.cursor().row, screen.cursor().col), (0, 0));
+
+        screen.process_str("\x1b[4;1Ha\nb\nc");
+        assert_eq!(screen.buffer().row_text(0).trim_end(), "header");
+        assert_eq!(screen.buffer().row_text(3).trim_end(), "c");
+        assert_eq!(screen.buffer().row_text(2).trim_end(), "b");
+    }
+
     #[test]
     fn screen_scroll() {
         let mut screen = Screen::new(3, 10);
@@ -497,6 +314,19 @@ mod tests {
         assert!(screen.buffer().row_text(0).is_empty());
     }
 
+    #[test]
+    fn screen_reverse_index_in_scroll_region() {
+        let mut screen = Screen::new(4, 20);
+        screen.process_str("top\r\nA\r\nB\r\nstatus");
+        screen.process_str("\x1b[2;3r"); // Scroll region rows 2-3
+        screen.process_str("\x1b[2;1H\x1bMnew"); // Insert a line at the region top
+
+        assert_eq!(screen.buffer().row_text(0), "top");
+        assert_eq!(screen.buffer().row_text(1), "new");
+        assert_eq!(screen.buffer().row_text(2), "A");
+        assert_eq!(screen.buffer().row_text(3), "status");
+    }
+
     #[test]
     fn screen_index() {
         let mut screen = Screen::new(3, 20);
@@ -3,9 +3,26 @@
 //! This module provides a 2D screen buffer for terminal emulation,
 //! storing characters, attributes, and cursor position.
 
+use std::collections::{HashMap, VecDeque};
 use std::fmt::{self, Write as _};
+use std::ops::Range;
+use std::sync::Arc;
+
+use unicode_width::UnicodeWidthChar;
+
+use super::parser::{AnsiParser, AnsiSequence, EraseMode, ParseResult, apply_sgr};
+
+/// Live hyperlink count below which unreferenced ids are never swept.
+const MIN_LINK_SWEEP: usize = 64;
+
+/// Maximum number of combining marks kept per cell, as in xterm.
+pub const MAX_COMBINING: usize = 2;
 
 /// A single cell in the screen buffer.
+///
+/// A full-width character occupies two cells: the first holds the
+/// character with a width of 2, and the second is a continuation cell
+/// with a width of 0.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Cell {
     /// The character in this cell.
@@ -16,6 +33,16 @@ pub struct Cell {
     pub bg: Color,
     /// Text attributes.
     pub attrs: Attributes,
+    /// Display width in columns; 0 marks the continuation of a wide character.
+    pub width: u8,
+    /// Zero-width combining marks attached to the character.
+    pub combining: [Option<char>; MAX_COMBINING],
+    /// Id of the OSC 8 hyperlink covering this cell.
+    ///
+    /// Resolve it with [`ScreenBuffer::hyperlink`]. Cells store an id rather
+    /// than the URI so that they stay `Copy`. Once no cell in the buffer
+    /// uses an id, it may be reused for another URI.
+    pub hyperlink: Option<u32>,
 }
 
 impl Default for Cell {
@@ -25,6 +52,9 @@ impl Default for Cell {
             fg: Color::Default,
             bg: Color::Default,
             attrs: Attributes::empty(),
+            width: 1,
+            combining: [None; MAX_COMBINING],
+            hyperlink: None,
         }
     }
 }
@@ -63,7 +93,47 @@ impl Cell {
     /// Check if this cell is empty (space with default colors).
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.char == ' ' && self.fg == Color::Default && self.bg == Color::Default
+        self.char == ' '
+            && self.width == 1
+            && self.combining[0].is_none()
+            && self.hyperlink.is_none()
+            && self.fg == Color::Default
+            && self.bg == Color::Default
+    }
+
+    /// Get the colors and attributes of this cell.
+    #[must_use]
+    pub const fn style(&self) -> CellStyle {
+        CellStyle {
+            fg: self.fg,
+            bg: self.bg,
+            attrs: self.attrs,
+        }
+    }
+
+    /// Check if this cell is the second half of a wide character.
+    #[must_use]
+    pub const fn is_continuation(&self) -> bool {
+        self.width == 0
+    }
+
+    /// Append the text of this cell, including combining marks, to `out`.
+    ///
+    /// Continuation cells add nothing, since their character is stored in
+    /// the cell before them.
+    pub fn push_to(&self, out: &mut String) {
+        if self.is_continuation() {
+            return;
+        }
+        out.push(self.char);
+        out.extend(self.combining.iter().flatten());
+    }
+
+    /// Attach a combining mark, dropping it once the cell is full.
+    fn add_combining(&mut self, mark: char) {
+        if let Some(slot) = self.combining.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(mark);
+        }
     }
 }
 
@@ -196,6 +266,10 @@ impl Cursor {
 }
 
 /// A 2D screen buffer.
+///
+/// Raw terminal output is applied with
+/// [`process_bytes`](Self::process_bytes); the editing primitives can
+/// also be called directly.
 #[derive(Clone)]
 pub struct ScreenBuffer {
     /// Buffer dimensions.
@@ -211,6 +285,55 @@ pub struct ScreenBuffer {
     scroll_region: (usize, usize),
     /// Saved cursor position.
     saved_cursor: Option<Cursor>,
+    /// Rows scrolled off the top, oldest first.
+    scrollback: VecDeque<Vec<Cell>>,
+    /// Maximum number of rows kept in `scrollback`.
+    scrollback_limit: usize,
+    /// Hyperlink URIs indexed by id - 1; `None` marks a freed id.
+    links: Vec<Option<Arc<str>>>,
+    /// The id of each URI in `links`.
+    link_ids: HashMap<Arc<str>, u32>,
+    /// Freed ids, reused before `links` grows.
+    free_links: Vec<u32>,
+    /// Number of live links at which unreferenced ones are swept.
+    link_sweep_at: usize,
+    /// Parser state carried between `process_bytes` calls.
+    parser: AnsiParser,
+}
+
+/// The colors and attributes a cell is rendered with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CellStyle {
+    /// Foreground color.
+    pub fg: Color,
+    /// Background color.
+    pub bg: Color,
+    /// Text attributes.
+    pub attrs: Attributes,
+}
+
+/// A run of consecutive cells on a row that share a style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledSpan {
+    /// Columns covered by the span.
+    pub columns: Range<usize>,
+    /// Style of every cell in the span.
+    pub style: CellStyle,
+    /// Text of the span.
+    pub text: String,
+}
+
+/// A hyperlink found on the screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hyperlink {
+    /// Target URI.
+    pub uri: Arc<str>,
+    /// Text of the linked cells.
+    pub text: String,
+    /// Row of the link (0-indexed).
+    pub row: usize,
+    /// Column of the first linked cell (0-indexed).
+    pub col: usize,
 }
 
 impl ScreenBuffer {
@@ -226,7 +349,74 @@ impl ScreenBuffer {
             current_style: Cell::default(),
             scroll_region: (0, rows.saturating_sub(1)),
             saved_cursor: None,
+            scrollback: VecDeque::new(),
+            scrollback_limit: 0,
+            links: Vec::new(),
+            link_ids: HashMap::new(),
+            free_links: Vec::new(),
+            link_sweep_at: MIN_LINK_SWEEP,
+            parser: AnsiParser::new(),
+        }
+    }
+
+    /// Keep up to `limit` rows that scroll off the top of the screen.
+    ///
+    /// Scrollback is disabled by default.
+    #[must_use]
+    pub fn with_scrollback(mut self, limit: usize) -> Self {
+        self.set_scrollback_limit(limit);
+        self
+    }
+
+    /// Get the maximum number of scrollback rows.
+    #[must_use]
+    pub const fn scrollback_limit(&self) -> usize {
+        self.scrollback_limit
+    }
+
+    /// Change the maximum number of scrollback rows, dropping the oldest
+    /// rows if the history no longer fits.
+    pub fn set_scrollback_limit(&mut self, limit: usize) {
+        self.scrollback_limit = limit;
+        let excess = self.scrollback.len().saturating_sub(limit);
+        self.scrollback.drain(..excess);
+    }
+
+    /// Get the number of rows in the scrollback history.
+    #[must_use]
+    pub fn history_len(&self) -> usize {
+        self.scrollback.len()
+    }
+
+    /// Get the text of a scrollback row.
+    ///
+    /// Row 0 is the oldest row kept; row `history_len() - 1` is the one
+    /// that scrolled off most recently.
+    #[must_use]
+    pub fn scrollback_line(&self, n: usize) -> Option<String> {
+        self.scrollback.get(n).map(|cells| cells_text(cells))
+    }
+
+    /// Discard the scrollback history.
+    pub fn clear_scrollback(&mut self) {
+        self.scrollback.clear();
+    }
+
+    /// Move the top `n` screen rows into the scrollback history.
+    ///
+    /// Only rows above `height` are taken: the height of the scrolling
+    /// region, or of the screen.
+    fn push_scrollback(&mut self, n: usize, height: usize) {
+        if self.scrollback_limit == 0 {
+            return;
+        }
+        for row in 0..n.min(height) {
+            let start = row * self.cols;
+            self.scrollback
+                .push_back(self.cells[start..start + self.cols].to_vec());
         }
+        let excess = self.scrollback.len().saturating_sub(self.scrollback_limit);
+        self.scrollback.drain(..excess);
     }
 
     /// Get the number of rows.
@@ -268,24 +458,122 @@ impl ScreenBuffer {
     }
 
     /// Write a character at the current cursor position.
+    ///
+    /// The cursor advances by the display width of the character. A
+    /// full-width character takes two cells and wraps early if only one
+    /// column is left, while a zero-width combining mark is attached to the
+    /// previously written character without moving the cursor.
     pub fn write_char(&mut self, c: char) {
-        if self.cursor.row < self.rows && self.cursor.col < self.cols {
-            let idx = self.cursor.row * self.cols + self.cursor.col;
-            self.cells[idx] = Cell {
-                char: c,
-                fg: self.current_style.fg,
-                bg: self.current_style.bg,
-                attrs: self.current_style.attrs,
-            };
-            self.cursor.col += 1;
-            if self.cursor.col >= self.cols {
-                self.cursor.col = 0;
-                self.cursor.row += 1;
-                if self.cursor.row >= self.rows {
-                    self.scroll_up(1);
-                    self.cursor.row = self.rows - 1;
-                }
+        if self.cursor.row >= self.rows || self.cursor.col >= self.cols {
+            return;
+        }
+
+        let width = match c.width() {
+            Some(0) => {
+                self.attach_combining(c);
+                return;
             }
+            Some(2) if self.cols >= 2 => 2,
+            _ => 1,
+        };
+
+        if self.cursor.col + width > self.cols {
+            // Not enough room for a wide character on this line
+            self.split_wide_at(self.cursor.row, self.cursor.col);
+            self.set(self.cursor.row, self.cursor.col, Cell::default());
+            self.wrap_line();
+        }
+
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        let cell = Cell {
+            char: c,
+            fg: self.current_style.fg,
+            bg: self.current_style.bg,
+            attrs: self.current_style.attrs,
+            width: if width == 2 { 2 } else { 1 },
+            combining: [None; MAX_COMBINING],
+            hyperlink: self.current_style.hyperlink,
+        };
+        for offset in 0..width {
+            self.split_wide_at(row, col + offset);
+        }
+        self.set(row, col, cell);
+        if width == 2 {
+            self.set(
+                row,
+                col + 1,
+                Cell {
+                    char: ' ',
+                    width: 0,
+                    ..cell
+                },
+            );
+        }
+
+        self.cursor.col += width;
+        if self.cursor.col >= self.cols {
+            self.wrap_line();
+        }
+    }
+
+    /// Move the cursor to the start of the next line, scrolling at the bottom.
+    fn wrap_line(&mut self) {
+        self.cursor.col = 0;
+        self.cursor.row += 1;
+        if self.cursor.row >= self.rows {
+            self.scroll_up(1);
+            self.cursor.row = self.rows - 1;
+        }
+    }
+
+    /// Blank the other half of a wide character about to be overwritten at
+    /// `(row, col)`, so no half of it is left behind.
+    fn split_wide_at(&mut self, row: usize, col: usize) {
+        let Some(cell) = self.get(row, col).copied() else {
+            return;
+        };
+        if cell.is_continuation() && col > 0 {
+            self.set(row, col - 1, Cell::default());
+        } else if cell.width == 2 {
+            self.set(row, col + 1, Cell::default());
+        }
+    }
+
+    /// Blank a wide character that straddles the boundary left of `col`,
+    /// before the cells on one side of it are edited or moved.
+    fn split_wide_before(&mut self, row: usize, col: usize) {
+        if col > 0 && self.get(row, col).is_some_and(Cell::is_continuation) {
+            self.set(row, col - 1, Cell::default());
+            self.set(row, col, Cell::default());
+        }
+    }
+
+    /// Blank the first half of a wide character cut off by the end of `row`.
+    fn drop_cut_wide_at_end(&mut self, row: usize) {
+        let Some(last) = ((row + 1) * self.cols).checked_sub(1) else {
+            return;
+        };
+        if self.cells.get(last).is_some_and(|cell| cell.width == 2) {
+            self.cells[last] = Cell::default();
+        }
+    }
+
+    /// Attach a combining mark to the character left of the cursor.
+    fn attach_combining(&mut self, mark: char) {
+        let (row, mut col) = match (self.cursor.row, self.cursor.col) {
+            (0, 0) => return,
+            // The previous character wrapped onto this line
+            (row, 0) => (row - 1, self.cols - 1),
+            (row, col) => (row, col - 1),
+        };
+        if self
+            .get(row, col)
+            .is_some_and(|cell| cell.is_continuation() && col > 0)
+        {
+            col -= 1;
+        }
+        if let Some(cell) = self.get_mut(row, col) {
+            cell.add_combining(mark);
         }
     }
 
@@ -313,6 +601,7 @@ impl ScreenBuffer {
 
     /// Clear from cursor to end of screen.
     pub fn clear_to_end(&mut self) {
+        self.split_wide_before(self.cursor.row, self.cursor.col);
         let start = self.cursor.row * self.cols + self.cursor.col;
         for cell in &mut self.cells[start..] {
             *cell = Cell::default();
@@ -321,6 +610,7 @@ impl ScreenBuffer {
 
     /// Clear from start of screen to cursor.
     pub fn clear_to_start(&mut self) {
+        self.split_wide_before(self.cursor.row, self.cursor.col + 1);
         let end = self.cursor.row * self.cols + self.cursor.col + 1;
         for cell in &mut self.cells[..end] {
             *cell = Cell::default();
@@ -338,6 +628,7 @@ impl ScreenBuffer {
 
     /// Clear from cursor to end of line.
     pub fn clear_line_to_end(&mut self) {
+        self.split_wide_before(self.cursor.row, self.cursor.col);
         let start = self.cursor.row * self.cols + self.cursor.col;
         let end = self.cursor.row * self.cols + self.cols;
         for cell in &mut self.cells[start..end] {
@@ -355,6 +646,11 @@ impl ScreenBuffer {
             return;
         }
 
+        // Only rows leaving the top of the screen go to the history
+        if top == 0 {
+            self.push_scrollback(n, scroll_height);
+        }
+
         // Move lines up
         if n <= bottom.saturating_sub(top) {
             for row in top..=bottom.saturating_sub(n) {
@@ -415,6 +711,37 @@ impl ScreenBuffer {
         self.scroll_region = (0, self.rows.saturating_sub(1));
     }
 
+    /// Index (IND): move the cursor down one row.
+    ///
+    /// At the bottom of the scroll region the region scrolls up instead.
+    pub fn index(&mut self) {
+        if self.cursor.row == self.scroll_region.1 {
+            self.scroll_up(1);
+        } else if self.cursor.row + 1 < self.rows {
+            self.cursor.row += 1;
+        }
+    }
+
+    /// Reverse index (RI): move the cursor up one row.
+    ///
+    /// At the top of the scroll region the region scrolls down instead,
+    /// which is how editors insert lines at the top of the screen.
+    pub fn reverse_index(&mut self) {
+        if self.cursor.row == self.scroll_region.0 {
+            self.scroll_down(1);
+        } else {
+            self.cursor.row = self.cursor.row.saturating_sub(1);
+        }
+    }
+
+    /// Next line (NEL): move to the first column of the next row.
+    ///
+    /// Scrolls like [`index`](Self::index) at the bottom of the scroll region.
+    pub fn next_line(&mut self) {
+        self.index();
+        self.cursor.col = 0;
+    }
+
     /// Save the current cursor position.
     pub const fn save_cursor(&mut self) {
         self.saved_cursor = Some(self.cursor);
@@ -427,6 +754,226 @@ impl ScreenBuffer {
         }
     }
 
+    /// Process raw terminal output.
+    ///
+    /// Control characters and ANSI escape sequences (cursor movement, CUP,
+    /// ED, EL, SGR, DECSTBM, OSC 8 and the rest of [`AnsiSequence`]) drive
+    /// the cursor, style and scroll region, and text is written as UTF-8.
+    /// An escape sequence or UTF-8 character split across calls is held
+    /// and completed by the next call; invalid UTF-8 prints as U+FFFD.
+    pub fn process_bytes(&mut self, data: &[u8]) {
+        for &byte in data {
+            if let Some(result) = self.parser.parse(byte) {
+                self.apply_result(result);
+            }
+        }
+    }
+
+    /// Apply a parse result to the buffer.
+    fn apply_result(&mut self, result: ParseResult) {
+        match result {
+            ParseResult::Print(c) => {
+                self.write_char(c);
+            }
+            ParseResult::Control(c) => self.apply_control(c),
+            ParseResult::Sequence(seq) => self.apply_sequence(seq),
+        }
+    }
+
+    /// Apply a control character.
+    fn apply_control(&mut self, c: u8) {
+        match c {
+            0x07 => {
+                // Bell - ignored
+            }
+            0x08 => {
+                // Backspace
+                let cursor = self.cursor_mut();
+                if cursor.col > 0 {
+                    cursor.col -= 1;
+                }
+            }
+            0x09 => {
+                // Tab - move to next tab stop (every 8 columns)
+                let cols = self.cols();
+                let cursor = self.cursor_mut();
+                cursor.col = ((cursor.col / 8) + 1) * 8;
+                if cursor.col >= cols {
+                    cursor.col = cols - 1;
+                }
+            }
+            0x0a..=0x0c => {
+                // Line feed (LF), Vertical Tab (VT), Form Feed (FF)
+                // All behave the same in VT100: move down one line, scrolling
+                // at the bottom of the scroll region
+                // Also reset column (newline mode behavior)
+                self.next_line();
+            }
+            0x0d => {
+                // Carriage return
+                self.cursor_mut().col = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply an ANSI sequence.
+    #[allow(clippy::too_many_lines)] // Large match over AnsiSequence variants - structure is clear
+    fn apply_sequence(&mut self, seq: AnsiSequence) {
+        match seq {
+            AnsiSequence::CursorUp(n) => {
+                let cursor = self.cursor_mut();
+                cursor.row = cursor.row.saturating_sub(n as usize);
+            }
+            AnsiSequence::CursorDown(n) => {
+                let rows = self.rows();
+                let cursor = self.cursor_mut();
+                cursor.row = (cursor.row + n as usize).min(rows.saturating_sub(1));
+            }
+            AnsiSequence::CursorForward(n) => {
+                let cols = self.cols();
+                let cursor = self.cursor_mut();
+                cursor.col = (cursor.col + n as usize).min(cols.saturating_sub(1));
+            }
+            AnsiSequence::CursorBackward(n) => {
+                let cursor = self.cursor_mut();
+                cursor.col = cursor.col.saturating_sub(n as usize);
+            }
+            AnsiSequence::CursorNextLine(n) => {
+                // Move to beginning of line n lines down
+                let rows = self.rows();
+                let cursor = self.cursor_mut();
+                cursor.row = (cursor.row + n as usize).min(rows.saturating_sub(1));
+                cursor.col = 0;
+            }
+            AnsiSequence::CursorPrevLine(n) => {
+                // Move to beginning of line n lines up
+                let cursor = self.cursor_mut();
+                cursor.row = cursor.row.saturating_sub(n as usize);
+                cursor.col = 0;
+            }
+            AnsiSequence::CursorColumn(n) => {
+                // Move cursor to column n (1-based)
+                let cols = self.cols();
+                let cursor = self.cursor_mut();
+                cursor.col = (n.saturating_sub(1) as usize).min(cols.saturating_sub(1));
+            }
+            AnsiSequence::CursorRow(n) => {
+                // Move cursor to row n (1-based)
+                let rows = self.rows();
+                let cursor = self.cursor_mut();
+                cursor.row = (n.saturating_sub(1) as usize).min(rows.saturating_sub(1));
+            }
+            AnsiSequence::CursorPosition { row, col } => {
+                self.goto(
+                    (row.saturating_sub(1)) as usize,
+                    (col.saturating_sub(1)) as usize,
+                );
+            }
+            AnsiSequence::EraseDisplay(mode) => match mode {
+                EraseMode::ToEnd => self.clear_to_end(),
+                EraseMode::ToStart => self.clear_to_start(),
+                EraseMode::All => self.clear(),
+            },
+            AnsiSequence::EraseLine(mode) => match mode {
+                EraseMode::ToEnd => self.clear_line_to_end(),
+                EraseMode::ToStart => {
+                    // Clear from start of line to cursor
+                    let row = self.cursor().row;
+                    let col = self.cursor().col;
+                    self.split_wide_before(row, col + 1);
+                    for c in 0..=col {
+                        self.set(row, c, Cell::default());
+                    }
+                }
+                EraseMode::All => self.clear_line(),
+            },
+            AnsiSequence::EraseChars(n) => {
+                // Erase n characters from cursor position (replace with spaces)
+                let row = self.cursor().row;
+                let col = self.cursor().col;
+                let cols = self.cols();
+                let end = (col + n as usize).min(cols);
+                self.split_wide_before(row, col);
+                self.split_wide_before(row, end);
+                for c in col..end {
+                    self.set(row, c, Cell::default());
+                }
+            }
+            AnsiSequence::SetGraphics(params) => {
+                let style = &mut self.current_style;
+                apply_sgr(&params, &mut style.fg, &mut style.bg, &mut style.attrs);
+            }
+            AnsiSequence::ScrollUp(n) => {
+                self.scroll_up(n as usize);
+            }
+            AnsiSequence::ScrollDown(n) => {
+                self.scroll_down(n as usize);
+            }
+            AnsiSequence::ReverseIndex => {
+                self.reverse_index();
+            }
+            AnsiSequence::Index => {
+                self.index();
+            }
+            AnsiSequence::NextLine => {
+                self.next_line();
+            }
+            AnsiSequence::SaveCursor => {
+                self.save_cursor();
+            }
+            AnsiSequence::RestoreCursor => {
+                self.restore_cursor();
+            }
+            AnsiSequence::SetScrollRegion { top, bottom } => {
+                let top = (top.saturating_sub(1)) as usize;
+                let bottom = if bottom == 0 {
+                    self.rows() - 1
+                } else {
+                    (bottom.saturating_sub(1)) as usize
+                };
+                self.set_scroll_region(top, bottom);
+                // DECSTBM also homes the cursor
+                self.goto(0, 0);
+            }
+            AnsiSequence::ShowCursor => {
+                self.cursor_mut().visible = true;
+            }
+            AnsiSequence::HideCursor => {
+                self.cursor_mut().visible = false;
+            }
+            AnsiSequence::InsertLines(n) => {
+                self.insert_lines(n as usize);
+            }
+            AnsiSequence::DeleteLines(n) => {
+                self.delete_lines(n as usize);
+            }
+            AnsiSequence::InsertChars(n) => {
+                self.insert_chars(n as usize);
+            }
+            AnsiSequence::DeleteChars(n) => {
+                self.delete_chars(n as usize);
+            }
+            AnsiSequence::RepeatChar(n) => {
+                // Repeat the last printed character n times
+                // Note: We don't track last char, so this is a no-op for now
+                // A full implementation would track last_printed_char
+                let _ = n;
+            }
+            AnsiSequence::Hyperlink(uri) => {
+                self.set_hyperlink(uri.as_deref());
+            }
+            AnsiSequence::Reset => {
+                self.clear();
+                self.goto(0, 0);
+                self.current_style = Cell::default();
+            }
+            AnsiSequence::Unknown(_) => {
+                // Ignore unknown sequences
+            }
+        }
+    }
+
     /// Set the current text style.
     pub const fn set_style(&mut self, fg: Color, bg: Color, attrs: Attributes) {
         self.current_style.fg = fg;
@@ -435,8 +982,111 @@ impl ScreenBuffer {
     }
 
     /// Reset the current text style to defaults.
+    ///
+    /// An open hyperlink stays open.
     pub fn reset_style(&mut self) {
-        self.current_style = Cell::default();
+        self.current_style = Cell {
+            hyperlink: self.current_style.hyperlink,
+            ..Cell::default()
+        };
+    }
+
+    /// Start a hyperlink to `uri` for the characters written next, or end
+    /// the current one with `None`.
+    ///
+    /// Each URI has one id for as long as any cell uses it. Ids no cell
+    /// refers to any more are freed and reused, so the link table stays
+    /// proportional to the links on screen and in the scrollback.
+    pub fn set_hyperlink(&mut self, uri: Option<&str>) {
+        self.current_style.hyperlink = uri.map(|uri| self.intern_link(uri));
+    }
+
+    /// Get the id of `uri`, allocating one if it has none.
+    fn intern_link(&mut self, uri: &str) -> u32 {
+        if let Some(&id) = self.link_ids.get(uri) {
+            return id;
+        }
+        if self.link_ids.len() >= self.link_sweep_at {
+            self.sweep_links();
+            self.link_sweep_at = (2 * self.link_ids.len()).max(MIN_LINK_SWEEP);
+        }
+
+        let uri: Arc<str> = Arc::from(uri);
+        let id = if let Some(id) = self.free_links.pop() {
+            self.links[id as usize - 1] = Some(Arc::clone(&uri));
+            id
+        } else {
+            self.links.push(Some(Arc::clone(&uri)));
+            u32::try_from(self.links.len()).unwrap_or(u32::MAX)
+        };
+        self.link_ids.insert(uri, id);
+        id
+    }
+
+    /// Free the ids no cell, scrollback row or the current style uses.
+    fn sweep_links(&mut self) {
+        let mut used = vec![false; self.links.len()];
+        let cells = self.cells.iter().chain(self.scrollback.iter().flatten());
+        for id in cells
+            .chain([&self.current_style])
+            .filter_map(|cell| cell.hyperlink)
+        {
+            if let Some(slot) = (id as usize).checked_sub(1).and_then(|i| used.get_mut(i)) {
+                *slot = true;
+            }
+        }
+
+        for (index, used) in used.into_iter().enumerate() {
+            if !used && let Some(uri) = self.links[index].take() {
+                self.link_ids.remove(&uri);
+                self.free_links
+                    .push(u32::try_from(index + 1).unwrap_or(u32::MAX));
+            }
+        }
+    }
+
+    /// Get the URI of a hyperlink id stored in [`Cell::hyperlink`].
+    #[must_use]
+    pub fn hyperlink(&self, id: u32) -> Option<&str> {
+        self.link(id).map(|uri| &**uri)
+    }
+
+    fn link(&self, id: u32) -> Option<&Arc<str>> {
+        let index = usize::try_from(id).ok()?.checked_sub(1)?;
+        self.links.get(index)?.as_ref()
+    }
+
+    /// Collect the hyperlinks on the screen.
+    ///
+    /// Each run of cells on a row with the same link is reported once,
+    /// top to bottom and left to right.
+    #[must_use]
+    pub fn hyperlinks(&self) -> Vec<Hyperlink> {
+        let mut found = Vec::new();
+        for row in 0..self.rows {
+            let cells = &self.cells[row * self.cols..(row + 1) * self.cols];
+            let mut col = 0;
+            while col < cells.len() {
+                let Some(id) = cells[col].hyperlink else {
+                    col += 1;
+                    continue;
+                };
+                let end = cells[col..]
+                    .iter()
+                    .position(|cell| cell.hyperlink != Some(id))
+                    .map_or(cells.len(), |len| col + len);
+                if let Some(uri) = self.link(id) {
+                    found.push(Hyperlink {
+                        uri: Arc::clone(uri),
+                        text: cells_text(&cells[col..end]),
+                        row,
+                        col,
+                    });
+                }
+                col = end;
+            }
+        }
+        found
     }
 
     /// Insert n blank characters at the cursor position.
@@ -451,6 +1101,7 @@ impl ScreenBuffer {
         let row = self.cursor.row;
         let col = self.cursor.col;
         let n = n.min(self.cols - col);
+        self.split_wide_before(row, col);
 
         // Shift characters to the right
         let row_start = row * self.cols;
@@ -462,6 +1113,7 @@ impl ScreenBuffer {
         for c in col..col + n {
             self.cells[row_start + c] = Cell::default();
         }
+        self.drop_cut_wide_at_end(row);
     }
 
     /// Delete n characters at the cursor position.
@@ -476,6 +1128,8 @@ impl ScreenBuffer {
         let row = self.cursor.row;
         let col = self.cursor.col;
         let n = n.min(self.cols - col);
+        self.split_wide_before(row, col);
+        self.split_wide_before(row, col + n);
 
         let row_start = row * self.cols;
 
@@ -554,6 +1208,81 @@ impl ScreenBuffer {
         }
     }
 
+    /// Split a row into runs of cells with identical colors and attributes.
+    ///
+    /// The spans cover the whole row, including trailing blank cells.
+    #[must_use]
+    pub fn styled_spans(&self, row: usize) -> Vec<StyledSpan> {
+        if row >= self.rows {
+            return Vec::new();
+        }
+
+        let cells = &self.cells[row * self.cols..(row + 1) * self.cols];
+        let mut spans: Vec<StyledSpan> = Vec::new();
+        for (col, cell) in cells.iter().enumerate() {
+            match spans.last_mut() {
+                Some(span) if span.style == cell.style() => {
+                    span.columns.end = col + 1;
+                    cell.push_to(&mut span.text);
+                }
+                _ => {
+                    let mut text = String::new();
+                    cell.push_to(&mut text);
+                    spans.push(StyledSpan {
+                        columns: col..col + 1,
+                        style: cell.style(),
+                        text,
+                    });
+                }
+            }
+        }
+        spans
+    }
+
+    /// Find `text` rendered with at least the attributes `attrs`.
+    ///
+    /// Every cell of the match must have all of `attrs` set; other
+    /// attributes and colors are ignored. Returns the `(row, col)` of the
+    /// first such match, searching top to bottom and left to right, and
+    /// considers overlapping occurrences. Text does not match across rows.
+    #[must_use]
+    pub fn find_styled(&self, text: &str, attrs: Attributes) -> Option<(usize, usize)> {
+        if text.is_empty() {
+            return None;
+        }
+
+        for row in 0..self.rows {
+            let cells = &self.cells[row * self.cols..(row + 1) * self.cols];
+            // Row text plus the byte offset where each cell's text starts
+            let mut line = String::with_capacity(self.cols);
+            let mut starts = Vec::with_capacity(self.cols);
+            for (col, cell) in cells.iter().enumerate() {
+                if !cell.is_continuation() {
+                    starts.push((line.len(), col));
+                    cell.push_to(&mut line);
+                }
+            }
+
+            let mut from = 0;
+            while let Some(found) = line[from..].find(text) {
+                let offset = from + found;
+                // Resume one character later so overlapping hits are seen
+                from = offset + line[offset..].chars().next().map_or(1, char::len_utf8);
+                let end = offset + text.len();
+                let mut matched = starts
+                    .iter()
+                    .filter(|&&(start, _)| start >= offset && start < end);
+                let first = matched.clone().next().map(|&(_, col)| col);
+                if matched.all(|&(_, col)| cells[col].attrs.contains(attrs))
+                    && let Some(col) = first
+                {
+                    return Some((row, col));
+                }
+            }
+        }
+        None
+    }
+
     /// Get a row as a string.
     #[must_use]
     pub fn row_text(&self, row: usize) -> String {
@@ -562,13 +1291,7 @@ impl ScreenBuffer {
         }
 
         let start = row * self.cols;
-        let end = start + self.cols;
-        self.cells[start..end]
-            .iter()
-            .map(|c| c.char)
-            .collect::<String>()
-            .trim_end()
-            .to_string()
+        cells_text(&self.cells[start..start + self.cols])
     }
 
     /// Get all content as a string.
@@ -580,25 +1303,146 @@ impl ScreenBuffer {
             .join("\n")
     }
 
+    /// Get the scrollback history followed by the screen content.
+    #[must_use]
+    pub fn full_text(&self) -> String {
+        self.scrollback
+            .iter()
+            .map(|cells| cells_text(cells))
+            .chain((0..self.rows).map(|r| self.row_text(r)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     /// Resize the buffer.
+    ///
+    /// When the screen gets shorter than the cursor row, the top rows move
+    /// into the scrollback history so the cursor line stays visible.
+    /// Scrollback rows keep the width they were written with.
     pub fn resize(&mut self, new_rows: usize, new_cols: usize) {
+        let shift = (self.cursor.row + 1).saturating_sub(new_rows);
+        self.push_scrollback(shift, self.rows);
+
+        let old_cols = self.cols;
         let mut new_cells = vec![Cell::default(); new_rows * new_cols];
 
-        for row in 0..new_rows.min(self.rows) {
+        for row in 0..new_rows.min(self.rows - shift) {
             for col in 0..new_cols.min(self.cols) {
-                new_cells[row * new_cols + col] = self.cells[row * self.cols + col];
+                new_cells[row * new_cols + col] = self.cells[(row + shift) * self.cols + col];
             }
         }
 
         self.rows = new_rows;
         self.cols = new_cols;
         self.cells = new_cells;
-        self.cursor.row = self.cursor.row.min(new_rows.saturating_sub(1));
+        if new_cols < old_cols {
+            for row in 0..new_rows {
+                self.drop_cut_wide_at_end(row);
+            }
+        }
+        self.cursor.row = self
+            .cursor
+            .row
+            .saturating_sub(shift)
+            .min(new_rows.saturating_sub(1));
         self.cursor.col = self.cursor.col.min(new_cols.saturating_sub(1));
         self.scroll_region = (0, new_rows.saturating_sub(1));
     }
 }
 
+/// Build the text of a row of cells, without trailing whitespace.
+fn cells_text(cells: &[Cell]) -> String {
+    let mut text = String::with_capacity(cells.len());
+    for cell in cells {
+        cell.push_to(&mut text);
+    }
+    text.truncate(text.trim_end().len());
+    text
+}
+
+/// Append a CUP sequence moving to the 0-based `row` and `col`.
+fn push_cursor_position(out: &mut Vec<u8>, row: usize, col: usize) {
+    out.extend_from_slice(format!("\x1b[{};{}H", row + 1, col + 1).as_bytes());
+}
+
+/// Append `cell`, preceded by an SGR sequence if `style` differs from it.
+///
+/// `text` is scratch space for the cell's characters.
+fn push_cell(
+    out: &mut Vec<u8>,
+    style: &mut Option<(Color, Color, Attributes)>,
+    text: &mut String,
+    cell: &Cell,
+) {
+    let cell_style = (cell.fg, cell.bg, cell.attrs);
+    if *style != Some(cell_style) {
+        push_sgr(out, cell);
+        *style = Some(cell_style);
+    }
+
+    text.clear();
+    cell.push_to(text);
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// Append an SGR sequence selecting the style of `cell` from scratch.
+fn push_sgr(out: &mut Vec<u8>, cell: &Cell) {
+    const ATTRIBUTE_CODES: [(Attributes, u8); 8] = [
+        (Attributes::BOLD, 1),
+        (Attributes::DIM, 2),
+        (Attributes::ITALIC, 3),
+        (Attributes::UNDERLINE, 4),
+        (Attributes::BLINK, 5),
+        (Attributes::INVERSE, 7),
+        (Attributes::HIDDEN, 8),
+        (Attributes::STRIKETHROUGH, 9),
+    ];
+
+    let mut sgr = String::from("\x1b[0");
+    for (attr, code) in ATTRIBUTE_CODES {
+        if cell.attrs.contains(attr) {
+            let _ = write!(sgr, ";{code}");
+        }
+    }
+    push_color(&mut sgr, cell.fg, 30);
+    push_color(&mut sgr, cell.bg, 40);
+    sgr.push('m');
+    out.extend_from_slice(sgr.as_bytes());
+}
+
+/// Append the SGR parameters for `color`, where `base` is 30 for the
+/// foreground and 40 for the background.
+fn push_color(sgr: &mut String, color: Color, base: u8) {
+    let code = match color {
+        Color::Default => return,
+        Color::Indexed(n) => {
+            let _ = write!(sgr, ";{};5;{n}", base + 8);
+            return;
+        }
+        Color::Rgb(r, g, b) => {
+            let _ = write!(sgr, ";{};2;{r};{g};{b}", base + 8);
+            return;
+        }
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::White => base + 7,
+        Color::BrightBlack => base + 60,
+        Color::BrightRed => base + 61,
+        Color::BrightGreen => base + 62,
+        Color::BrightYellow => base + 63,
+        Color::BrightBlue => base + 64,
+        Color::BrightMagenta => base + 65,
+        Color::BrightCyan => base + 66,
+        Color::BrightWhite => base + 67,
+    };
+    let _ = write!(sgr, ";{code}");
+}
+
 impl fmt::Debug for ScreenBuffer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ScreenBuffer")
@@ -710,6 +1554,9 @@ pub struct ScreenDiff {
     old_dims: (usize, usize),
     /// New dimensions (rows, cols).
     new_dims: (usize, usize),
+    /// Start column and content of the new cell left of the bottom-right
+    /// one, used to paint that corner without scrolling.
+    corner_left: Option<(usize, Cell)>,
 }
 
 impl ScreenDiff {
@@ -760,6 +1607,18 @@ impl ScreenDiff {
             }
         }
 
+        let corner_left = new.rows.checked_sub(1).and_then(|row| {
+            let mut corner = new.cols.checked_sub(1)?;
+            if new.get(row, corner)?.is_continuation() {
+                corner = corner.checked_sub(1)?;
+            }
+            let mut left = corner.checked_sub(1)?;
+            if new.get(row, left)?.is_continuation() && left > 0 {
+                left -= 1;
+            }
+            Some((left, *new.get(row, left)?))
+        });
+
         Self {
             changes,
             cursor_changed,
@@ -768,6 +1627,7 @@ impl ScreenDiff {
             dimensions_changed,
             old_dims: (old.rows, old.cols),
             new_dims: (new.rows, new.cols),
+            corner_left,
         }
     }
 
@@ -834,6 +1694,78 @@ impl ScreenDiff {
         self.changes.iter().filter(|c| c.row == row).collect()
     }
 
+    /// Encode the changes as ANSI escape sequences for a real terminal.
+    ///
+    /// Writing the result to a terminal that shows the old buffer repaints
+    /// only the changed cells. Contiguous changes on a row are written in
+    /// one run after a single cursor move, SGR sequences are only emitted
+    /// when the style changes, and the cursor ends at its new position and
+    /// visibility. Dimension changes cannot be expressed and are ignored.
+    #[must_use]
+    pub fn to_ansi(&self) -> Vec<u8> {
+        let mut changes: Vec<&CellChange> = self
+            .changes
+            .iter()
+            .filter(|c| !c.new.is_continuation())
+            .collect();
+        changes.sort_by_key(|c| (c.row, c.col));
+
+        let mut out = Vec::new();
+        // Where the terminal cursor is, if known
+        let mut position = None;
+        // The style the terminal will write with, if known
+        let mut style: Option<(Color, Color, Attributes)> = None;
+        let mut text = String::new();
+
+        for change in changes {
+            let cell = &change.new;
+            let next = change.col + usize::from(cell.width.max(1));
+
+            // Filling the bottom-right cell would scroll the screen, so the
+            // cell is written one place early, pushed into the corner by
+            // inserting blanks, and its left neighbour is written again
+            if let Some((left_col, left)) = &self.corner_left
+                && change.row + 1 == self.new_dims.0
+                && next == self.new_dims.1
+                && *left_col < change.col
+            {
+                push_cursor_position(&mut out, change.row, *left_col);
+                push_cell(&mut out, &mut style, &mut text, cell);
+                push_cursor_position(&mut out, change.row, *left_col);
+                out.extend_from_slice(format!("\x1b[{}@", change.col - left_col).as_bytes());
+                push_cell(&mut out, &mut style, &mut text, left);
+                position = Some((change.row, change.col));
+                continue;
+            }
+
+            if position != Some((change.row, change.col)) {
+                push_cursor_position(&mut out, change.row, change.col);
+            }
+            push_cell(&mut out, &mut style, &mut text, cell);
+
+            // Past the last column the terminal may wrap, so forget the position
+            position = (next < self.new_dims.1).then_some((change.row, next));
+        }
+
+        if style.is_some_and(|s| s != (Color::Default, Color::Default, Attributes::empty())) {
+            out.extend_from_slice(b"\x1b[0m");
+        }
+        let cursor = self.new_cursor;
+        let wrote = style.is_some();
+        if (wrote || self.cursor_changed) && position != Some((cursor.row, cursor.col)) {
+            push_cursor_position(&mut out, cursor.row, cursor.col);
+        }
+        if cursor.visible != self.old_cursor.visible {
+            out.extend_from_slice(if cursor.visible {
+                b"\x1b[?25h"
+            } else {
+                b"\x1b[?25l"
+            });
+        }
+
+        out
+    }
+
     /// Generate a human-readable diff report.
     #[must_use]
     pub fn report(&self) -> String {
@@ -975,6 +1907,294 @@ mod tests {
         assert_eq!(buf.row_text(0), "Hi");
     }
 
+    fn write_str(buf: &mut ScreenBuffer, s: &str) {
+        s.chars().for_each(|c| buf.write_char(c));
+    }
+
+    #[test]
+    fn wide_chars_take_two_columns() {
+        let mut buf = ScreenBuffer::new(2, 10);
+        write_str(&mut buf, "a\u{65e5}\u{672c}\u{8a9e}b");
+
+        assert_eq!(buf.cursor().col, 8);
+        assert_eq!(buf.get(0, 1).unwrap().width, 2);
+        assert!(buf.get(0, 2).unwrap().is_continuation());
+        assert_eq!(buf.get(0, 7).unwrap().char, 'b');
+        assert_eq!(buf.row_text(0), "a\u{65e5}\u{672c}\u{8a9e}b");
+    }
+
+    #[test]
+    fn wide_char_wraps_when_one_column_is_left() {
+        let mut buf = ScreenBuffer::new(2, 3);
+        write_str(&mut buf, "ab\u{65e5}");
+
+        assert_eq!(buf.row_text(0), "ab");
+        assert_eq!(buf.row_text(1), "\u{65e5}");
+        assert_eq!((buf.cursor().row, buf.cursor().col), (1, 2));
+    }
+
+    #[test]
+    fn overwriting_half_of_wide_char_blanks_the_other_half() {
+        let mut buf = ScreenBuffer::new(1, 10);
+        write_str(&mut buf, "\u{65e5}\u{672c}");
+
+        buf.goto(0, 1);
+        buf.write_char('x');
+        assert_eq!(buf.row_text(0), " x\u{672c}");
+
+        buf.goto(0, 2);
+        buf.write_char('y');
+        assert_eq!(buf.row_text(0), " xy");
+        assert!(!buf.get(0, 3).unwrap().is_continuation());
+    }
+
+    fn widths(buf: &ScreenBuffer, row: usize) -> Vec<u8> {
+        (0..buf.cols())
+            .map(|col| buf.get(row, col).unwrap().width)
+            .collect()
+    }
+
+    #[test]
+    fn inserting_inside_wide_char_blanks_it() {
+        let mut buf = ScreenBuffer::new(1, 6);
+        buf.process_bytes("\u{65e5}\u{672c}\x1b[1;2H\x1b[1@".as_bytes());
+        assert_eq!(widths(&buf, 0), [1, 1, 1, 2, 0, 1]);
+        assert_eq!(buf.row_text(0), "   \u{672c}");
+
+        // A wide character pushed into the last column loses its other half
+        let mut buf = ScreenBuffer::new(1, 4);
+        buf.process_bytes("ab\u{65e5}\x1b[1;1H\x1b[1@".as_bytes());
+        assert_eq!(widths(&buf, 0), [1; 4]);
+        assert_eq!(buf.row_text(0), " ab");
+    }
+
+    #[test]
+    fn deleting_half_of_wide_char_blanks_the_other_half() {
+        let mut buf = ScreenBuffer::new(1, 6);
+        buf.process_bytes("\u{65e5}\u{672c}\x1b[1;1H\x1b[1P".as_bytes());
+        assert_eq!(widths(&buf, 0), [1, 2, 0, 1, 1, 1]);
+        assert_eq!(buf.row_text(0), " \u{672c}");
+
+        let mut buf = ScreenBuffer::new(1, 6);
+        buf.process_bytes("\u{65e5}\u{672c}\x1b[1;2H\x1b[1P".as_bytes());
+        assert_eq!(widths(&buf, 0), [1, 2, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn erasing_half_of_wide_char_blanks_the_other_half() {
+        let mut buf = ScreenBuffer::new(1, 6);
+        buf.process_bytes("\u{65e5}\u{672c}x\x1b[1;2H\x1b[2X".as_bytes());
+        assert_eq!(widths(&buf, 0), [1; 6]);
+        assert_eq!(buf.row_text(0), "    x");
+
+        let mut buf = ScreenBuffer::new(1, 6);
+        buf.process_bytes("\u{65e5}\u{672c}\x1b[1;2H\x1b[K".as_bytes());
+        assert_eq!(widths(&buf, 0), [1; 6]);
+
+        let mut buf = ScreenBuffer::new(1, 6);
+        buf.process_bytes("\u{65e5}\u{672c}\x1b[1;3H\x1b[1K".as_bytes());
+        assert_eq!(widths(&buf, 0), [1; 6]);
+        assert!(buf.row_text(0).is_empty());
+
+        let mut buf = ScreenBuffer::new(2, 6);
+        buf.process_bytes("\u{65e5}\u{672c}\x1b[1;3H\x1b[1J".as_bytes());
+        assert_eq!(widths(&buf, 0), [1; 6]);
+    }
+
+    #[test]
+    fn resize_blanks_wide_char_cut_by_last_column() {
+        let mut buf = ScreenBuffer::new(1, 6);
+        write_str(&mut buf, "ab\u{65e5}");
+        buf.resize(1, 3);
+        assert_eq!(widths(&buf, 0), [1, 1, 1]);
+        assert_eq!(buf.row_text(0), "ab");
+    }
+
+    #[test]
+    fn combining_marks_attach_to_previous_cell() {
+        let mut buf = ScreenBuffer::new(2, 4);
+        write_str(&mut buf, "cafe\u{301}!");
+
+        // The accent does not take a column, even after a wrap
+        assert_eq!(buf.get(0, 3).unwrap().combining[0], Some('\u{301}'));
+        assert_eq!(buf.row_text(0), "cafe\u{301}");
+        assert_eq!(buf.row_text(1), "!");
+
+        let mut buf = ScreenBuffer::new(1, 10);
+        write_str(&mut buf, "\u{65e5}\u{302}a");
+        assert_eq!(buf.get(0, 0).unwrap().combining[0], Some('\u{302}'));
+        assert_eq!(buf.cursor().col, 3);
+        assert_eq!(buf.row_text(0), "\u{65e5}\u{302}a");
+    }
+
+    #[test]
+    fn scrollback_keeps_rows_scrolled_off_the_top() {
+        let mut buf = ScreenBuffer::new(3, 10).with_scrollback(4);
+        for i in 0..8 {
+            write_str(&mut buf, &format!("line {i}"));
+            if i < 7 {
+                buf.goto(buf.cursor().row, 0);
+                buf.index();
+            }
+        }
+
+        // Lines 0 to 4 left the screen, and the limit keeps only 1 to 4
+        assert_eq!(buf.history_len(), 4);
+        assert_eq!(buf.scrollback_line(0).as_deref(), Some("line 1"));
+        assert_eq!(buf.scrollback_line(3).as_deref(), Some("line 4"));
+        assert_eq!(buf.scrollback_line(4), None);
+        assert_eq!(buf.text(), "line 5\nline 6\nline 7");
+        assert_eq!(
+            buf.full_text(),
+            "line 1\nline 2\nline 3\nline 4\nline 5\nline 6\nline 7"
+        );
+
+        buf.set_scrollback_limit(2);
+        assert_eq!(buf.scrollback_line(0).as_deref(), Some("line 3"));
+        buf.clear_scrollback();
+        assert_eq!(buf.history_len(), 0);
+    }
+
+    #[test]
+    fn resize_to_zero_rows_or_cols() {
+        let mut buf = ScreenBuffer::new(3, 10).with_scrollback(10);
+        buf.goto(2, 4);
+        write_str(&mut buf, "x");
+
+        buf.resize(0, 10);
+        assert_eq!((buf.cursor().row, buf.cursor().col), (0, 5));
+        assert_eq!(buf.history_len(), 3);
+
+        buf.resize(2, 0);
+        buf.resize(2, 10);
+        assert_eq!(buf.cursor().col, 0);
+        assert!(buf.text().trim().is_empty());
+    }
+
+    #[test]
+    fn scrollback_is_off_by_default_and_skips_scroll_regions() {
+        let mut buf = ScreenBuffer::new(3, 10);
+        buf.write_char('a');
+        buf.scroll_up(1);
+        assert_eq!(buf.history_len(), 0);
+
+        let mut buf = ScreenBuffer::new(3, 10).with_scrollback(10);
+        buf.write_char('a');
+        buf.set_scroll_region(1, 2);
+        buf.scroll_up(1);
+        assert_eq!(buf.history_len(), 0);
+    }
+
+    #[test]
+    fn scrollback_takes_only_rows_leaving_a_top_region() {
+        let mut buf = ScreenBuffer::new(4, 10).with_scrollback(10);
+        for (row, text) in ["a", "b", "c", "d"].iter().enumerate() {
+            buf.goto(row, 0);
+            write_str(&mut buf, text);
+        }
+
+        buf.set_scroll_region(0, 1);
+        buf.scroll_up(3);
+        assert_eq!(buf.history_len(), 2);
+        assert_eq!(buf.scrollback_line(0).as_deref(), Some("a"));
+        assert_eq!(buf.scrollback_line(1).as_deref(), Some("b"));
+        assert_eq!(buf.row_text(2), "c");
+    }
+
+    #[test]
+    fn resize_moves_rows_above_cursor_into_scrollback() {
+        let mut buf = ScreenBuffer::new(4, 10).with_scrollback(10);
+        for (row, text) in ["a", "b", "c", "d"].iter().enumerate() {
+            buf.goto(row, 0);
+            write_str(&mut buf, text);
+        }
+
+        buf.resize(2, 5);
+        assert_eq!(buf.full_text(), "a\nb\nc\nd");
+        assert_eq!(buf.history_len(), 2);
+        assert_eq!(buf.text(), "c\nd");
+        assert_eq!(buf.cursor().row, 1);
+
+        // Growing keeps the history and the screen as they are
+        buf.resize(4, 20);
+        assert_eq!(buf.history_len(), 2);
+        assert_eq!(buf.row_text(1), "d");
+    }
+
+    #[test]
+    fn hyperlinks_cover_the_linked_text() {
+        let mut screen = crate::screen::Screen::new(3, 30);
+        screen.process_str(
+            "see \x1b]8;;file:///var/log/app.log\x1b\\/var/log/app.log\x1b]8;;\x1b\\ now\r\n",
+        );
+        screen.process_str("\x1b]8;id=2;https://example.com\x07docs\x1b]8;;\x07");
+
+        let buf = screen.buffer();
+        let links = buf.hyperlinks();
+        assert_eq!(links.len(), 2);
+        assert_eq!(&*links[0].uri, "file:///var/log/app.log");
+        assert_eq!(links[0].text, "/var/log/app.log");
+        assert_eq!((links[0].row, links[0].col), (0, 4));
+        assert_eq!(&*links[1].uri, "https://example.com");
+        assert_eq!((links[1].row, links[1].text.as_str()), (1, "docs"));
+
+        // Text around the link is not linked, and the text is unchanged
+        assert_eq!(buf.get(0, 3).unwrap().hyperlink, None);
+        let id = buf.get(0, 4).unwrap().hyperlink.unwrap();
+        assert_eq!(buf.hyperlink(id), Some("file:///var/log/app.log"));
+        assert_eq!(buf.row_text(0), "see /var/log/app.log now");
+    }
+
+    #[test]
+    fn styled_spans_group_cells_by_style() {
+        let mut screen = crate::screen::Screen::new(2, 16);
+        screen.process_str("ok \x1b[1;31merror\x1b[0m \x1b[1;31;44mx\x1b[0m");
+
+        let spans = screen.buffer().styled_spans(0);
+        let red_bold = CellStyle {
+            fg: Color::Red,
+            attrs: Attributes::BOLD,
+            ..CellStyle::default()
+        };
+        assert_eq!(spans.len(), 5);
+        assert_eq!(spans[0].text, "ok ");
+        assert_eq!(spans[1].columns, 3..8);
+        assert_eq!(spans[1].style, red_bold);
+        assert_eq!(spans[1].text, "error");
+        assert_eq!(spans[3].style.bg, Color::Blue);
+        assert_eq!(spans[4].columns, 10..16);
+        assert_eq!(spans[4].style, CellStyle::default());
+        assert!(screen.buffer().styled_spans(2).is_empty());
+    }
+
+    #[test]
+    fn find_styled_requires_attributes_on_every_cell() {
+        let mut screen = crate::screen::Screen::new(3, 30);
+        screen.process_str("error: plain\r\n");
+        screen.process_str("\x1b[1merr\x1b[0mor: half bold\r\n");
+        screen.process_str("\x1b[1;4;31merror\x1b[0m: styled");
+        let buf = screen.buffer();
+
+        assert_eq!(buf.find_styled("error", Attributes::BOLD), Some((2, 0)));
+        assert_eq!(
+            buf.find_styled("error", Attributes::BOLD | Attributes::UNDERLINE),
+            Some((2, 0))
+        );
+        assert_eq!(buf.find_styled("error", Attributes::ITALIC), None);
+        assert_eq!(buf.find_styled("plain", Attributes::empty()), Some((0, 7)));
+        assert_eq!(buf.get(2, 0).unwrap().style().fg, Color::Red);
+    }
+
+    #[test]
+    fn find_styled_sees_overlapping_matches() {
+        let mut screen = crate::screen::Screen::new(1, 10);
+        screen.process_str("a\x1b[1maa\x1b[0m");
+        let buf = screen.buffer();
+
+        assert_eq!(buf.find_styled("aa", Attributes::empty()), Some((0, 0)));
+        assert_eq!(buf.find_styled("aa", Attributes::BOLD), Some((0, 1)));
+    }
+
     #[test]
     fn screen_buffer_cursor() {
         let mut buf = ScreenBuffer::new(24, 80);
@@ -1013,6 +2233,63 @@ mod tests {
         assert!(buf.row_text(2).is_empty());
     }
 
+    #[test]
+    fn index_scrolls_at_region_bottom() {
+        let mut buf = ScreenBuffer::new(4, 10);
+        for (row, text) in ["A", "B", "C", "D"].iter().enumerate() {
+            buf.goto(row, 0);
+            buf.write_char(text.chars().next().unwrap());
+        }
+        buf.set_scroll_region(1, 2);
+
+        buf.goto(2, 5);
+        buf.index();
+        assert_eq!(buf.cursor().row, 2);
+        assert_eq!(buf.row_text(0), "A");
+        assert_eq!(buf.row_text(1), "C");
+        assert!(buf.row_text(2).is_empty());
+        assert_eq!(buf.row_text(3), "D");
+
+        // Below the region the cursor moves but never scrolls
+        buf.goto(3, 0);
+        buf.index();
+        assert_eq!(buf.cursor().row, 3);
+        assert_eq!(buf.row_text(3), "D");
+    }
+
+    #[test]
+    fn reverse_index_scrolls_at_region_top() {
+        let mut buf = ScreenBuffer::new(4, 10);
+        for (row, text) in ["A", "B", "C", "D"].iter().enumerate() {
+            buf.goto(row, 0);
+            buf.write_char(text.chars().next().unwrap());
+        }
+        buf.set_scroll_region(1, 2);
+
+        buf.goto(1, 0);
+        buf.reverse_index();
+        assert_eq!(buf.cursor().row, 1);
+        assert_eq!(buf.row_text(0), "A");
+        assert!(buf.row_text(1).is_empty());
+        assert_eq!(buf.row_text(2), "B");
+        assert_eq!(buf.row_text(3), "D");
+
+        buf.goto(3, 0);
+        buf.reverse_index();
+        assert_eq!(buf.cursor().row, 2);
+    }
+
+    #[test]
+    fn next_line_resets_column() {
+        let mut buf = ScreenBuffer::new(2, 10);
+        buf.goto(1, 4);
+        buf.write_char('X');
+        buf.next_line();
+        assert_eq!(buf.cursor().row, 1);
+        assert_eq!(buf.cursor().col, 0);
+        assert!(buf.row_text(1).is_empty());
+    }
+
     #[test]
     fn insert_chars_shifts_right() {
         let mut buf = ScreenBuffer::new(1, 10);
@@ -1181,6 +2458,86 @@ mod tests {
         assert_eq!(significant.len(), 2); // Only "Hi" are significant
     }
 
+    /// Replay the ANSI diff from `old` to `new` onto a copy of `old`.
+    fn replay_diff(old: &crate::screen::Screen, new: &crate::screen::Screen) -> Vec<u8> {
+        let ansi = ScreenDiff::compute(old.buffer(), new.buffer()).to_ansi();
+        let mut replayed = old.clone();
+        replayed.process(&ansi);
+        assert!(
+            replayed.buffer().equals(new.buffer()),
+            "replay differs:\n{}",
+            replayed.buffer().diff(new.buffer()).report()
+        );
+        ansi
+    }
+
+    #[test]
+    fn diff_to_ansi_groups_runs_and_styles() {
+        let mut old = crate::screen::Screen::new(3, 20);
+        old.process_str("hello world\r\nsecond");
+        let mut new = old.clone();
+        new.process_str("\x1b[1;7H\x1b[32mthere\x1b[0m\x1b[2;1HSECOND");
+
+        let ansi = replay_diff(&old, &new);
+        assert_eq!(
+            String::from_utf8(ansi).unwrap(),
+            "\x1b[1;7H\x1b[0;32mthere\x1b[2;1H\x1b[0mSECOND"
+        );
+    }
+
+    #[test]
+    fn diff_to_ansi_replays_wide_chars_and_cursor() {
+        let mut old = crate::screen::Screen::new(4, 10);
+        old.process_str("\x1b[1;31mab\u{65e5}\x1b[0m\r\n\x1b[44mline\x1b[0m");
+        let mut new = old.clone();
+        new.process_str("\x1b[1;1Hx\x1b[1;4H\u{672c}z\x1b[3;9H\x1b[38;5;200mqq\x1b[?25l\x1b[2;2H");
+
+        let ansi = replay_diff(&old, &new);
+        assert!(
+            String::from_utf8(ansi)
+                .unwrap()
+                .ends_with("\x1b[0m\x1b[2;2H\x1b[?25l")
+        );
+
+        // An empty diff emits nothing
+        assert!(
+            ScreenDiff::compute(new.buffer(), new.buffer())
+                .to_ansi()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn diff_to_ansi_fills_bottom_right_without_scrolling() {
+        let mut old = crate::screen::Screen::new(2, 3);
+        old.process_str("ab");
+        let mut new = old.clone();
+        new.buffer.set(1, 2, Cell::new('z'));
+
+        let ansi = replay_diff(&old, &new);
+        assert_eq!(
+            String::from_utf8(ansi).unwrap(),
+            "\x1b[2;2H\x1b[0mz\x1b[2;2H\x1b[1@ \x1b[1;3H"
+        );
+
+        // A wide corner character with a wide neighbour
+        let mut old = crate::screen::Screen::new(2, 6);
+        old.process_str("ab\r\n\u{65e5}");
+        let mut new = old.clone();
+        new.process_str("\x1b[2;3H\u{672c}");
+        new.buffer.set(1, 4, Cell::new('\u{8a9e}'));
+        new.buffer.set(
+            1,
+            5,
+            Cell {
+                width: 0,
+                ..Cell::new(' ')
+            },
+        );
+        new.buffer.cells[6 + 4].width = 2;
+        replay_diff(&old, &new);
+    }
+
     #[test]
     fn diff_report() {
         let buf1 = ScreenBuffer::new(3, 10);
@@ -1282,4 +2639,40 @@ mod tests {
         assert_eq!(row0_changes.len(), 4); // R, o, w, 0
         assert_eq!(row1_changes.len(), 4); // R, o, w, 1
     }
+
+    #[test]
+    fn line_feed_scrolls_at_bottom_of_scroll_region() {
+        let mut buf = ScreenBuffer::new(4, 10);
+        buf.process_bytes(b"top\r\none\r\ntwo\r\nfooter");
+        // Region rows 2-3 in 1-based terms, cursor on its last row
+        buf.process_bytes(b"\x1b[2;3r\x1b[3;1H\nnew");
+
+        assert_eq!(buf.row_text(0), "top");
+        assert_eq!(buf.row_text(1), "two");
+        assert_eq!(buf.row_text(2), "new");
+        assert_eq!(buf.row_text(3), "footer");
+        assert_eq!(buf.cursor().row, 2);
+    }
+
+    #[test]
+    fn hyperlink_ids_are_interned_and_reclaimed() {
+        let mut buf = ScreenBuffer::new(2, 20);
+        buf.process_bytes(b"\x1b]8;;https://a\x07a\x1b]8;;\x07 \x1b]8;;https://a\x07b\x1b]8;;\x07");
+        let (a, b) = (
+            buf.get(0, 0).unwrap().hyperlink,
+            buf.get(0, 2).unwrap().hyperlink,
+        );
+        assert!(a.is_some());
+        assert_eq!(a, b);
+
+        // Each link overwrites the last, so only one is ever on screen
+        for n in 0..10_000 {
+            let line = format!("\r\x1b]8;;https://example.com/{n}\x07link\x1b]8;;\x07");
+            buf.process_bytes(line.as_bytes());
+        }
+        assert!(buf.links.len() <= MIN_LINK_SWEEP + 1);
+        let links = buf.hyperlinks();
+        assert_eq!(links.len(), 1);
+        assert_eq!(&*links[0].uri, "https://example.com/9999");
+    }
 }
@@ -74,6 +74,8 @@ pub enum AnsiSequence {
     DeleteChars(u16),
     /// Repeat previous character (REP).
     RepeatChar(u16),
+    /// Start (`Some` URI) or end (`None`) an OSC 8 hyperlink.
+    Hyperlink(Option<String>),
     /// Reset terminal.
     Reset,
     /// Unknown or unsupported sequence.
@@ -108,16 +110,29 @@ pub struct AnsiParser {
     params: Vec<u16>,
     intermediate: String,
     current_param: Option<u16>,
+    /// Bytes of a partially received UTF-8 character.
+    utf8: [u8; 4],
+    /// Number of bytes collected in `utf8`.
+    utf8_len: usize,
+    /// Total length of the UTF-8 character being collected.
+    utf8_need: usize,
+    /// Payload of the OSC string being collected.
+    osc: Vec<u8>,
 }
 
+/// Longest OSC payload kept; longer strings are truncated.
+const MAX_OSC_LEN: usize = 4096;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ParserState {
     Ground,
+    Utf8,
     Escape,
     CsiEntry,
     CsiParam,
     CsiIntermediate,
     OscString,
+    OscEscape,
 }
 
 impl Default for AnsiParser {
@@ -135,6 +150,10 @@ impl AnsiParser {
             params: Vec::new(),
             intermediate: String::new(),
             current_param: None,
+            utf8: [0; 4],
+            utf8_len: 0,
+            utf8_need: 0,
+            osc: Vec::new(),
         }
     }
 
@@ -144,17 +163,26 @@ impl AnsiParser {
         self.params.clear();
         self.intermediate.clear();
         self.current_param = None;
+        self.utf8_len = 0;
+        self.utf8_need = 0;
+        self.osc.clear();
     }
 
     /// Parse a byte and return any completed sequences.
+    ///
+    /// Printable text is decoded as UTF-8, so a multi-byte character is
+    /// returned once its last byte arrives. Invalid bytes print as
+    /// `U+FFFD`.
     pub fn parse(&mut self, byte: u8) -> Option<ParseResult> {
         match self.state {
             ParserState::Ground => self.ground(byte),
+            ParserState::Utf8 => self.utf8(byte),
             ParserState::Escape => self.escape(byte),
             ParserState::CsiEntry => self.csi_entry(byte),
             ParserState::CsiParam => self.csi_param(byte),
             ParserState::CsiIntermediate => self.csi_intermediate(byte),
             ParserState::OscString => self.osc_string(byte),
+            ParserState::OscEscape => self.osc_escape(byte),
         }
     }
 
@@ -168,8 +196,41 @@ impl AnsiParser {
                 // Control characters
                 Some(ParseResult::Control(byte))
             }
-            _ => Some(ParseResult::Print(byte as char)),
+            0x20..=0x7f => Some(ParseResult::Print(byte as char)),
+            0xc2..=0xf4 => {
+                self.utf8[0] = byte;
+                self.utf8_len = 1;
+                self.utf8_need = match byte {
+                    0xc2..=0xdf => 2,
+                    0xe0..=0xef => 3,
+                    _ => 4,
+                };
+                self.state = ParserState::Utf8;
+                None
+            }
+            _ => Some(ParseResult::Print(char::REPLACEMENT_CHARACTER)),
+        }
+    }
+
+    fn utf8(&mut self, byte: u8) -> Option<ParseResult> {
+        if !matches!(byte, 0x80..=0xbf) {
+            // Drop the truncated character and handle the byte normally
+            self.reset();
+            return self.ground(byte);
         }
+
+        self.utf8[self.utf8_len] = byte;
+        self.utf8_len += 1;
+        if self.utf8_len < self.utf8_need {
+            return None;
+        }
+
+        let c = std::str::from_utf8(&self.utf8[..self.utf8_len])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        self.reset();
+        Some(ParseResult::Print(c))
     }
 
     fn escape(&mut self, byte: u8) -> Option<ParseResult> {
@@ -183,6 +244,7 @@ impl AnsiParser {
             }
             b']' => {
                 self.state = ParserState::OscString;
+                self.osc.clear();
                 None
             }
             b'7' => {
@@ -309,15 +371,43 @@ impl AnsiParser {
 
     fn osc_string(&mut self, byte: u8) -> Option<ParseResult> {
         match byte {
-            0x07 | 0x1b => {
-                // BEL or ESC terminates OSC
-                self.reset();
+            // BEL terminates OSC
+            0x07 => self.finalize_osc(),
+            // ESC starts the ST terminator, ESC \
+            0x1b => {
+                self.state = ParserState::OscEscape;
+                None
+            }
+            _ => {
+                if self.osc.len() < MAX_OSC_LEN {
+                    self.osc.push(byte);
+                }
                 None
             }
-            _ => None, // Ignore OSC content for now
         }
     }
 
+    fn osc_escape(&mut self, byte: u8) -> Option<ParseResult> {
+        if byte == b'\\' {
+            return self.finalize_osc();
+        }
+        // Not a terminator: drop the OSC string and start a new escape
+        self.reset();
+        self.state = ParserState::Escape;
+        self.escape(byte)
+    }
+
+    fn finalize_osc(&mut self) -> Option<ParseResult> {
+        let osc = std::mem::take(&mut self.osc);
+        self.reset();
+
+        // OSC 8 ; params ; URI, where an empty URI ends the link
+        let payload = std::str::from_utf8(&osc).ok()?;
+        let (_params, uri) = payload.strip_prefix("8;")?.split_once(';')?;
+        let uri = (!uri.is_empty()).then(|| uri.to_string());
+        Some(ParseResult::Sequence(AnsiSequence::Hyperlink(uri)))
+    }
+
     fn finalize_csi(&mut self, final_byte: u8) -> ParseResult {
         let params = std::mem::take(&mut self.params);
         let intermediate = std::mem::take(&mut self.intermediate);
@@ -501,6 +591,53 @@ mod tests {
         assert_eq!(result, Some(ParseResult::Print('A')));
     }
 
+    #[test]
+    fn parse_utf8_across_calls() {
+        let mut parser = AnsiParser::new();
+        let bytes = "é€".as_bytes();
+        let printed: Vec<_> = bytes.iter().filter_map(|&b| parser.parse(b)).collect();
+        assert_eq!(
+            printed,
+            vec![ParseResult::Print('é'), ParseResult::Print('€')]
+        );
+
+        // A truncated character is dropped, and the next byte still parses
+        assert_eq!(parser.parse(0xe2), None);
+        assert_eq!(parser.parse(b'A'), Some(ParseResult::Print('A')));
+        assert_eq!(
+            parser.parse(0xff),
+            Some(ParseResult::Print(char::REPLACEMENT_CHARACTER))
+        );
+    }
+
+    #[test]
+    fn parse_osc8_with_both_terminators() {
+        let mut parser = AnsiParser::new();
+        let parse = |parser: &mut AnsiParser, s: &str| -> Vec<ParseResult> {
+            s.bytes().filter_map(|b| parser.parse(b)).collect()
+        };
+
+        assert_eq!(
+            parse(&mut parser, "\x1b]8;;file:///tmp/a.txt\x1b\\"),
+            vec![ParseResult::Sequence(AnsiSequence::Hyperlink(Some(
+                "file:///tmp/a.txt".to_string()
+            )))]
+        );
+        assert_eq!(
+            parse(&mut parser, "\x1b]8;id=1;\x07x"),
+            vec![
+                ParseResult::Sequence(AnsiSequence::Hyperlink(None)),
+                ParseResult::Print('x')
+            ]
+        );
+
+        // Other OSC strings, such as window titles, are consumed silently
+        assert_eq!(
+            parse(&mut parser, "\x1b]0;title\x1b\\y"),
+            vec![ParseResult::Print('y')]
+        );
+    }
+
     #[test]
     fn apply_sgr_colors() {
         let mut fg = Color::Default;
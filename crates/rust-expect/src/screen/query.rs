@@ -126,7 +126,7 @@ impl<'a> ScreenQuery<'a> {
             let mut line = String::new();
             for col in region.left..=region.right {
                 if let Some(cell) = self.buffer.get(row, col) {
-                    line.push(cell.char);
+                    cell.push_to(&mut line);
                 }
             }
             lines.push(line.trim_end().to_string());
@@ -239,7 +239,7 @@ impl<'a> ScreenQuery<'a> {
         let mut line = String::new();
         for col in region.left..=region.right {
             if let Some(cell) = self.buffer.get(row, col) {
-                line.push(cell.char);
+                cell.push_to(&mut line);
             }
         }
         line
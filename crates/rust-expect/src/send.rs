@@ -1,10 +1,13 @@
 //! Send module for writing data to sessions.
 //!
 //! This module provides functionality for sending data to spawned processes,
-//! including basic send operations, ANSI escape sequences, and human-like typing.
+//! including basic send operations, ANSI escape sequences, human-like typing,
+//! and streaming files.
 
 mod basic;
+pub(crate) mod file;
 mod human;
 
-pub use basic::{AnsiSend, AnsiSequences, BasicSend, Sender};
+pub use basic::{AnsiSend, AnsiSequences, BasicSend, CursorKeyMode, Key, Sender};
+pub use file::SendFileMode;
 pub use human::{HumanSend, HumanTyper, TypeEvent, TypingSpeed};
@@ -46,6 +46,18 @@ pub trait BasicSend: Send {
         async move { self.send_bytes(&[ctrl.as_byte()]).await }
     }
 
+    /// Send a chord of control characters in a single write.
+    ///
+    /// Useful for prefix-key sequences such as tmux's Ctrl+B or screen's
+    /// Ctrl+A followed by a command key.
+    fn send_chord(
+        &mut self,
+        chord: &[ControlChar],
+    ) -> impl std::future::Future<Output = Result<()>> + Send {
+        let bytes: Vec<u8> = chord.iter().map(|c| c.as_byte()).collect();
+        async move { self.send_bytes(&bytes).await }
+    }
+
     /// Send Ctrl+C (interrupt).
     fn send_interrupt(&mut self) -> impl std::future::Future<Output = Result<()>> + Send {
         self.send_control(ControlChar::CtrlC)
@@ -83,6 +95,8 @@ pub struct Sender<W> {
     line_ending: LineEnding,
     /// Optional delay between characters.
     char_delay: Option<Duration>,
+    /// Optional maximum rate in bytes per second.
+    throttle: Option<u32>,
 }
 
 impl<W: AsyncWriteExt + Unpin + Send> Sender<W> {
@@ -92,9 +106,21 @@ impl<W: AsyncWriteExt + Unpin + Send> Sender<W> {
             writer,
             line_ending: LineEnding::Lf,
             char_delay: None,
+            throttle: None,
         }
     }
 
+    /// Limit writes to at most `bytes_per_sec` bytes per second.
+    ///
+    /// Data is written in small chunks with a pause after each one, so a
+    /// slow device such as a 9600-baud serial console is never sent more
+    /// than it can buffer. A rate of zero disables throttling.
+    #[must_use]
+    pub const fn with_throttle(mut self, bytes_per_sec: u32) -> Self {
+        self.set_throttle(Some(bytes_per_sec));
+        self
+    }
+
     /// Set the line ending.
     pub const fn set_line_ending(&mut self, ending: LineEnding) {
         self.line_ending = ending;
@@ -105,25 +131,56 @@ impl<W: AsyncWriteExt + Unpin + Send> Sender<W> {
         self.char_delay = delay;
     }
 
+    /// Set or clear the write throttle, in bytes per second.
+    ///
+    /// See [`with_throttle`](Self::with_throttle).
+    pub const fn set_throttle(&mut self, bytes_per_sec: Option<u32>) {
+        self.throttle = match bytes_per_sec {
+            Some(0) | None => None,
+            rate => rate,
+        };
+    }
+
+    /// Get the write throttle, in bytes per second.
+    #[must_use]
+    pub const fn throttle(&self) -> Option<u32> {
+        self.throttle
+    }
+
     /// Get the line ending.
     #[must_use]
     pub const fn line_ending(&self) -> LineEnding {
         self.line_ending
     }
 
-    /// Send bytes with optional character delay.
+    /// Send bytes with optional character delay and throttle.
     pub async fn send_with_delay(&mut self, data: &[u8]) -> Result<()> {
-        if let Some(delay) = self.char_delay {
-            for byte in data {
+        if self.char_delay.is_some() || self.throttle.is_some() {
+            // One byte at a time for the character delay, otherwise chunks
+            // of about 10ms worth of data at the throttled rate
+            let chunk_size = match (self.char_delay, self.throttle) {
+                (None, Some(rate)) => (rate as usize / 100).max(1),
+                _ => 1,
+            };
+            let start = tokio::time::Instant::now();
+            let mut sent = 0;
+            for chunk in data.chunks(chunk_size) {
                 self.writer
-                    .write_all(&[*byte])
+                    .write_all(chunk)
                     .await
                     .map_err(crate::error::ExpectError::Io)?;
                 self.writer
                     .flush()
                     .await
                     .map_err(crate::error::ExpectError::Io)?;
-                tokio::time::sleep(delay).await;
+                sent += chunk.len();
+                if let Some(delay) = self.char_delay {
+                    tokio::time::sleep(delay).await;
+                }
+                if let Some(rate) = self.throttle {
+                    // Pace against the start time so rounding does not drift
+                    tokio::time::sleep_until(start + throttle_delay(sent, rate)).await;
+                }
             }
         } else {
             self.writer
@@ -144,6 +201,12 @@ impl<W: AsyncWriteExt + Unpin + Send> Sender<W> {
     }
 }
 
+/// Time it takes to send `bytes` at `rate` bytes per second.
+fn throttle_delay(bytes: usize, rate: u32) -> Duration {
+    let nanos = bytes as u128 * 1_000_000_000 / u128::from(rate);
+    Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+}
+
 impl<W: AsyncWriteExt + Unpin + Send> BasicSend for Sender<W> {
     async fn send_bytes(&mut self, data: &[u8]) -> Result<()> {
         self.send_with_delay(data).await
@@ -230,6 +293,92 @@ impl AnsiSequences {
     }
 }
 
+/// Cursor key mode (DECCKM).
+///
+/// Full-screen programs such as `vim` and `less` usually switch the
+/// terminal into application mode (`ESC [ ? 1 h`), where the arrow keys,
+/// Home and End are sent with an `SS3` (`ESC O`) prefix instead of `CSI`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorKeyMode {
+    /// Normal mode: arrows send `ESC [ A` and so on.
+    #[default]
+    Normal,
+    /// Application mode: arrows send `ESC O A` and so on.
+    Application,
+}
+
+/// A named terminal key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// Up arrow.
+    Up,
+    /// Down arrow.
+    Down,
+    /// Right arrow.
+    Right,
+    /// Left arrow.
+    Left,
+    /// Home.
+    Home,
+    /// End.
+    End,
+    /// Page up.
+    PageUp,
+    /// Page down.
+    PageDown,
+    /// Insert.
+    Insert,
+    /// Delete.
+    Delete,
+    /// Function key F1 through F12.
+    F(u8),
+}
+
+impl Key {
+    /// Get the escape sequence for this key in the given cursor key mode.
+    ///
+    /// Only the arrows, Home and End depend on the mode. Returns `None` for
+    /// function keys outside F1–F12.
+    #[must_use]
+    pub const fn sequence(self, mode: CursorKeyMode) -> Option<&'static [u8]> {
+        let application = matches!(mode, CursorKeyMode::Application);
+        let seq: &'static [u8] = match self {
+            Self::Up if application => b"\x1bOA",
+            Self::Down if application => b"\x1bOB",
+            Self::Right if application => b"\x1bOC",
+            Self::Left if application => b"\x1bOD",
+            Self::Home if application => b"\x1bOH",
+            Self::End if application => b"\x1bOF",
+            Self::Up => AnsiSequences::CURSOR_UP,
+            Self::Down => AnsiSequences::CURSOR_DOWN,
+            Self::Right => AnsiSequences::CURSOR_RIGHT,
+            Self::Left => AnsiSequences::CURSOR_LEFT,
+            Self::Home => AnsiSequences::HOME,
+            Self::End => AnsiSequences::END,
+            Self::PageUp => AnsiSequences::PAGE_UP,
+            Self::PageDown => AnsiSequences::PAGE_DOWN,
+            Self::Insert => AnsiSequences::INSERT,
+            Self::Delete => AnsiSequences::DELETE,
+            Self::F(n) => match n {
+                1 => AnsiSequences::F1,
+                2 => AnsiSequences::F2,
+                3 => AnsiSequences::F3,
+                4 => AnsiSequences::F4,
+                5 => AnsiSequences::F5,
+                6 => AnsiSequences::F6,
+                7 => AnsiSequences::F7,
+                8 => AnsiSequences::F8,
+                9 => AnsiSequences::F9,
+                10 => AnsiSequences::F10,
+                11 => AnsiSequences::F11,
+                12 => AnsiSequences::F12,
+                _ => return None,
+            },
+        };
+        Some(seq)
+    }
+}
+
 /// Extension trait for sending ANSI sequences.
 pub trait AnsiSend: BasicSend {
     /// Send cursor up.
@@ -290,26 +439,39 @@ pub trait AnsiSend: BasicSend {
 
     /// Send a function key.
     fn send_function_key(&mut self, n: u8) -> impl std::future::Future<Output = Result<()>> + Send
+    where
+        Self: Send,
+    {
+        self.send_key(Key::F(n))
+    }
+
+    /// Send a named key using normal cursor key mode.
+    ///
+    /// Function keys outside F1–F12 are ignored.
+    fn send_key(&mut self, key: Key) -> impl std::future::Future<Output = Result<()>> + Send
+    where
+        Self: Send,
+    {
+        self.send_key_with_mode(key, CursorKeyMode::Normal)
+    }
+
+    /// Send a named key using the given cursor key mode.
+    ///
+    /// Use [`CursorKeyMode::Application`] when the program has enabled
+    /// DECCKM. Function keys outside F1–F12 are ignored.
+    fn send_key_with_mode(
+        &mut self,
+        key: Key,
+        mode: CursorKeyMode,
+    ) -> impl std::future::Future<Output = Result<()>> + Send
     where
         Self: Send,
     {
         async move {
-            let seq = match n {
-                1 => AnsiSequences::F1,
-                2 => AnsiSequences::F2,
-                3 => AnsiSequences::F3,
-                4 => AnsiSequences::F4,
-                5 => AnsiSequences::F5,
-                6 => AnsiSequences::F6,
-                7 => AnsiSequences::F7,
-                8 => AnsiSequences::F8,
-                9 => AnsiSequences::F9,
-                10 => AnsiSequences::F10,
-                11 => AnsiSequences::F11,
-                12 => AnsiSequences::F12,
-                _ => return Ok(()),
-            };
-            self.send_bytes(seq).await
+            match key.sequence(mode) {
+                Some(seq) => self.send_bytes(seq).await,
+                None => Ok(()),
+            }
         }
     }
 }
@@ -333,4 +495,103 @@ mod tests {
         assert_eq!(AnsiSequences::cursor_position(1, 1), b"\x1b[1;1H");
         assert_eq!(AnsiSequences::cursor_position(10, 20), b"\x1b[10;20H");
     }
+
+    #[test]
+    fn key_sequences_follow_cursor_key_mode() {
+        assert_eq!(
+            Key::Up.sequence(CursorKeyMode::Normal),
+            Some(&b"\x1b[A"[..])
+        );
+        assert_eq!(
+            Key::Up.sequence(CursorKeyMode::Application),
+            Some(&b"\x1bOA"[..])
+        );
+        assert_eq!(
+            Key::End.sequence(CursorKeyMode::Application),
+            Some(&b"\x1bOF"[..])
+        );
+        // Keys outside the cursor block are the same in both modes
+        assert_eq!(
+            Key::PageDown.sequence(CursorKeyMode::Application),
+            Some(AnsiSequences::PAGE_DOWN)
+        );
+        assert_eq!(
+            Key::F(5).sequence(CursorKeyMode::Application),
+            Some(AnsiSequences::F5)
+        );
+        assert_eq!(Key::F(13).sequence(CursorKeyMode::Normal), None);
+    }
+
+    #[tokio::test]
+    async fn send_key_writes_mode_sequence() {
+        let out = sent_bytes(|mut sender| async move {
+            sender.send_key(Key::Up).await.unwrap();
+            sender
+                .send_key_with_mode(Key::Up, CursorKeyMode::Application)
+                .await
+                .unwrap();
+            sender.send_key(Key::F(0)).await.unwrap();
+        })
+        .await;
+        assert_eq!(out, b"\x1b[A\x1bOA");
+    }
+
+    #[test]
+    fn throttle_zero_disables() {
+        let (client, _server) = tokio::io::duplex(64);
+        let mut sender = Sender::new(client).with_throttle(960);
+        assert_eq!(sender.throttle(), Some(960));
+        sender.set_throttle(Some(0));
+        assert_eq!(sender.throttle(), None);
+        assert_eq!(throttle_delay(960, 960), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn throttled_send_is_paced() {
+        let payload = vec![b'x'; 300];
+        let started = std::time::Instant::now();
+        let out = sent_bytes(|sender| async move {
+            let mut sender = sender.with_throttle(1000);
+            sender.send_bytes(&payload).await.unwrap();
+        })
+        .await;
+
+        assert_eq!(out.len(), 300);
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
+
+    async fn sent_bytes<F, Fut>(send: F) -> Vec<u8>
+    where
+        F: FnOnce(Sender<tokio::io::DuplexStream>) -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
+        use tokio::io::AsyncReadExt;
+
+        let (client, mut server) = tokio::io::duplex(1024);
+        send(Sender::new(client)).await;
+        let mut out = Vec::new();
+        server.read_to_end(&mut out).await.unwrap();
+        out
+    }
+
+    #[tokio::test]
+    async fn send_control_writes_control_byte() {
+        let out = sent_bytes(|mut sender| async move {
+            sender.send_control(ControlChar::CtrlC).await.unwrap();
+        })
+        .await;
+        assert_eq!(out, [0x03]);
+    }
+
+    #[tokio::test]
+    async fn send_chord_writes_sequence() {
+        let out = sent_bytes(|mut sender| async move {
+            sender
+                .send_chord(&[ControlChar::CtrlA, ControlChar::CtrlK])
+                .await
+                .unwrap();
+        })
+        .await;
+        assert_eq!(out, [0x01, 0x0B]);
+    }
 }
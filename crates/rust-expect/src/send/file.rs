@@ -0,0 +1,76 @@
+//! Streaming files as input.
+
+use std::time::Duration;
+
+use crate::expect::Pattern;
+
+/// How [`Session::send_file`](crate::Session::send_file) sends a file.
+#[derive(Debug, Clone)]
+pub enum SendFileMode {
+    /// Send the whole file at once, byte for byte.
+    Raw,
+    /// Send one line at a time, waiting for `prompt` after each.
+    ///
+    /// Lines are sent with the session's line ending in place of their
+    /// own, so `\r\n` files work too.
+    PromptPaced {
+        /// The prompt the program prints when it is ready for a line.
+        prompt: Pattern,
+        /// How long to wait for the prompt after each line.
+        timeout: Duration,
+    },
+}
+
+impl SendFileMode {
+    /// Send a line at a time, waiting up to `timeout` for `prompt` after each.
+    #[must_use]
+    pub fn prompt_paced(prompt: impl Into<Pattern>, timeout: Duration) -> Self {
+        Self::PromptPaced {
+            prompt: prompt.into(),
+            timeout,
+        }
+    }
+}
+
+/// Split file contents into lines without their `\n` or `\r\n` endings.
+///
+/// A final line without a newline is included; a trailing newline does not
+/// add an empty line.
+pub(crate) fn lines(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let body = data.strip_suffix(b"\n").unwrap_or(data);
+    (!data.is_empty())
+        .then(|| body.split(|&b| b == b'\n'))
+        .into_iter()
+        .flatten()
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(data: &[u8]) -> Vec<&[u8]> {
+        lines(data).collect()
+    }
+
+    #[test]
+    fn lines_strip_endings() {
+        assert_eq!(collect(b"a\nb\r\nc\n"), [&b"a"[..], b"b", b"c"]);
+    }
+
+    #[test]
+    fn lines_keep_unterminated_last_line() {
+        assert_eq!(collect(b"a\nb"), [&b"a"[..], b"b"]);
+    }
+
+    #[test]
+    fn lines_keep_blank_lines() {
+        assert_eq!(collect(b"a\n\nb\n"), [&b"a"[..], b"", b"b"]);
+    }
+
+    #[test]
+    fn lines_of_empty_file() {
+        assert!(collect(b"").is_empty());
+        assert_eq!(collect(b"\n"), [&b""[..]]);
+    }
+}
@@ -5,8 +5,10 @@
 
 use std::time::Duration;
 
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
+use super::basic::BasicSend;
 use crate::config::HumanTypingConfig;
 use crate::error::Result;
 
@@ -15,17 +17,14 @@ pub struct HumanTyper {
     /// Configuration for typing behavior.
     config: HumanTypingConfig,
     /// Random number generator.
-    rng: rand::rngs::ThreadRng,
+    rng: ChaCha8Rng,
 }
 
 impl HumanTyper {
     /// Create a new human typer with default configuration.
     #[must_use]
     pub fn new() -> Self {
-        Self {
-            config: HumanTypingConfig::default(),
-            rng: rand::rng(),
-        }
+        Self::with_config(HumanTypingConfig::default())
     }
 
     /// Create a new human typer with custom configuration.
@@ -33,7 +32,20 @@ impl HumanTyper {
     pub fn with_config(config: HumanTypingConfig) -> Self {
         Self {
             config,
-            rng: rand::rng(),
+            rng: ChaCha8Rng::from_os_rng(),
+        }
+    }
+
+    /// Create a human typer with a fixed random seed.
+    ///
+    /// The same seed, configuration, and text always produce the same
+    /// keystrokes, typos, and delays. The generator is `ChaCha8`, whose
+    /// output for a seed does not change between `rand` releases.
+    #[must_use]
+    pub fn with_seed(config: HumanTypingConfig, seed: u64) -> Self {
+        Self {
+            config,
+            rng: ChaCha8Rng::seed_from_u64(seed),
         }
     }
 
@@ -129,6 +141,28 @@ impl HumanTyper {
 
         events
     }
+
+    /// Type text into a sender, including typos and corrections.
+    ///
+    /// Each keystroke is sent separately, with the planned delays in
+    /// between.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if sending fails.
+    pub async fn type_text<S: BasicSend>(&mut self, sender: &mut S, text: &str) -> Result<()> {
+        for event in self.plan_typing(text) {
+            match event {
+                TypeEvent::Delay(delay) => tokio::time::sleep(delay).await,
+                other => {
+                    if let Some(bytes) = other.as_bytes() {
+                        sender.send_bytes(&bytes).await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for HumanTyper {
@@ -277,10 +311,25 @@ pub trait HumanSend {
     }
 }
 
+impl<T: BasicSend> HumanSend for T {
+    async fn send_human(&mut self, text: &str, config: HumanTypingConfig) -> Result<()> {
+        HumanTyper::with_config(config).type_text(self, text).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Concatenate the bytes a plan would send.
+    fn planned_bytes(events: &[TypeEvent]) -> Vec<u8> {
+        events
+            .iter()
+            .filter_map(TypeEvent::as_bytes)
+            .flatten()
+            .collect()
+    }
+
     #[test]
     fn human_typer_delay() {
         let mut typer = HumanTyper::new();
@@ -304,6 +353,56 @@ mod tests {
         assert!(matches!(events[2], TypeEvent::Char('i')));
     }
 
+    #[test]
+    fn seeded_typos_are_deterministic() {
+        let config = HumanTypingConfig::default()
+            .typo_chance(0.3)
+            .correction_chance(1.0);
+
+        let first =
+            planned_bytes(&HumanTyper::with_seed(config.clone(), 7).plan_typing("hello world"));
+        let second = planned_bytes(&HumanTyper::with_seed(config, 7).plan_typing("hello world"));
+        assert_eq!(first, second);
+        assert_eq!(first, b"y\x7fhek\x7fllo wp\x7forle\x7fd");
+    }
+
+    #[test]
+    fn corrected_typos_end_with_intended_text() {
+        let config = HumanTypingConfig::default()
+            .typo_chance(1.0)
+            .correction_chance(1.0);
+        let bytes = planned_bytes(&HumanTyper::with_seed(config, 1).plan_typing("ab"));
+
+        // Every letter is mistyped, erased, and retyped
+        assert_eq!(bytes.len(), 6);
+        assert_eq!(bytes[1], 0x7f);
+        assert_eq!(&bytes[2..3], b"a");
+        assert_eq!(bytes[4], 0x7f);
+        assert_eq!(&bytes[5..], b"b");
+    }
+
+    #[tokio::test]
+    async fn type_text_sends_planned_bytes() {
+        use tokio::io::AsyncReadExt;
+
+        let config = HumanTypingConfig::new(Duration::from_millis(1), Duration::ZERO)
+            .typo_chance(1.0)
+            .correction_chance(1.0);
+        let expected = planned_bytes(&HumanTyper::with_seed(config.clone(), 3).plan_typing("k"));
+
+        let (client, mut server) = tokio::io::duplex(64);
+        let mut sender = super::super::Sender::new(client);
+        HumanTyper::with_seed(config, 3)
+            .type_text(&mut sender, "k")
+            .await
+            .unwrap();
+        drop(sender);
+
+        let mut sent = Vec::new();
+        server.read_to_end(&mut sent).await.unwrap();
+        assert_eq!(sent, expected);
+    }
+
     #[test]
     fn nearby_keys() {
         let nearby = get_nearby_keys('f');
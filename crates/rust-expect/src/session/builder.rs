@@ -109,7 +109,7 @@ impl SessionBuilder {
 
     /// Set the buffer configuration.
     #[must_use]
-    pub const fn buffer_config(mut self, config: BufferConfig) -> Self {
+    pub fn buffer_config(mut self, config: BufferConfig) -> Self {
         self.config.buffer = config;
         self
     }
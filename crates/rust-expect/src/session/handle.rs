@@ -3,22 +3,33 @@
 //! This module provides the main `Session` type that users interact with
 //! to control spawned processes, send input, and expect output.
 
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::Mutex;
 
+#[cfg(feature = "ssh")]
+use crate::backend::ssh::{ChannelConfig, SshChannelStream, SshConfig, SshSession};
 #[cfg(unix)]
 use crate::backend::{AsyncPty, PtyConfig, PtySpawner};
 #[cfg(windows)]
 use crate::backend::{PtyConfig, PtySpawner, WindowsAsyncPty};
 use crate::config::SessionConfig;
 use crate::dialog::{Dialog, DialogExecutor, DialogResult};
-use crate::error::{ExpectError, Result};
+#[cfg(feature = "legacy-encoding")]
+use crate::encoding::StreamingDecoder;
+use crate::error::{ExpectError, Result, SpawnError};
 use crate::expect::{ExpectState, MatchResult, Matcher, Pattern, PatternManager, PatternSet};
 use crate::interact::InteractBuilder;
+#[cfg(feature = "metrics")]
+use crate::metrics::{SessionMetrics, Timer};
+#[cfg(feature = "screen")]
+use crate::screen::Screen;
+use crate::send::{SendFileMode, file};
 use crate::types::{ControlChar, Dimensions, Match, ProcessExitStatus, SessionId, SessionState};
+use crate::validation::{ValidationError, validate_session_config};
 
 /// A session handle for interacting with a spawned process.
 ///
@@ -39,6 +50,18 @@ pub struct Session<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> {
     id: SessionId,
     /// EOF flag.
     eof: bool,
+    /// Whether output was discarded from the full buffer during the
+    /// current expect.
+    overflowed: bool,
+    /// Rendered terminal screen, fed from every read.
+    #[cfg(feature = "screen")]
+    screen: Screen,
+    /// Metrics recorded by every expect.
+    #[cfg(feature = "metrics")]
+    metrics: Arc<SessionMetrics>,
+    /// Decoder for a non-UTF-8 charset, applied before matching.
+    #[cfg(feature = "legacy-encoding")]
+    decoder: Option<StreamingDecoder>,
 }
 
 impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
@@ -47,6 +70,13 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
         let buffer_size = config.buffer.max_size;
         let mut matcher = Matcher::new(buffer_size);
         matcher.set_default_timeout(config.timeout.default);
+        #[cfg(feature = "screen")]
+        let screen = {
+            let (cols, rows) = config.dimensions;
+            Screen::new(usize::from(rows), usize::from(cols))
+        };
+        #[cfg(feature = "legacy-encoding")]
+        let decoder = config.encoding.decoder();
         Self {
             transport: Arc::new(Mutex::new(transport)),
             config,
@@ -55,6 +85,13 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
             state: SessionState::Starting,
             id: SessionId::new(),
             eof: false,
+            overflowed: false,
+            #[cfg(feature = "screen")]
+            screen,
+            #[cfg(feature = "metrics")]
+            metrics: Arc::new(SessionMetrics::new()),
+            #[cfg(feature = "legacy-encoding")]
+            decoder,
         }
     }
 
@@ -93,6 +130,25 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
         self.matcher.clear();
     }
 
+    /// Clear the buffer along with any output the transport already has
+    /// ready, without waiting for more.
+    ///
+    /// At most one buffer's worth is drained, so a process that never
+    /// stops writing cannot hold this up.
+    pub(crate) async fn discard_pending(&mut self) -> Result<()> {
+        self.matcher.clear();
+        let mut drained = 0;
+        while !self.eof && drained < self.config.buffer.max_size {
+            let n = self.read_with_timeout(Duration::ZERO).await?;
+            self.matcher.clear();
+            if n == 0 {
+                break;
+            }
+            drained += n;
+        }
+        Ok(())
+    }
+
     /// Get the pattern manager for before/after patterns.
     #[must_use]
     pub const fn pattern_manager(&self) -> &PatternManager {
@@ -104,6 +160,25 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
         &mut self.pattern_manager
     }
 
+    /// Get the metrics this session records.
+    ///
+    /// Every expect, including [`expect_eof`](Self::expect_eof) and screen
+    /// expects, records its duration in
+    /// [`expect_latency`](SessionMetrics::expect_latency), and each one
+    /// that times out increments [`timeouts`](SessionMetrics::timeouts).
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub const fn metrics(&self) -> &Arc<SessionMetrics> {
+        &self.metrics
+    }
+
+    /// Record into shared metrics, e.g. from
+    /// [`MetricsRegistry::session`](crate::metrics::MetricsRegistry::session).
+    #[cfg(feature = "metrics")]
+    pub fn set_metrics(&mut self, metrics: Arc<SessionMetrics>) {
+        self.metrics = metrics;
+    }
+
     /// Set the session state.
     pub const fn set_state(&mut self, state: SessionState) {
         self.state = state;
@@ -161,13 +236,71 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
         self.send(&[ctrl.as_byte()]).await
     }
 
+    /// Send a chord of control characters, such as Ctrl+A Ctrl+K.
+    ///
+    /// The whole chord is written at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write fails.
+    pub async fn send_chord(&mut self, chord: &[ControlChar]) -> Result<()> {
+        let bytes: Vec<u8> = chord.iter().map(|c| c.as_byte()).collect();
+        self.send(&bytes).await
+    }
+
+    /// Send the contents of a file as input.
+    ///
+    /// In [`SendFileMode::Raw`] the file is sent as-is in one write. In
+    /// [`SendFileMode::PromptPaced`] each line is sent with the configured
+    /// line ending and its prompt is expected before the next line, so a
+    /// program like `psql` reads a script one statement at a time. Wait for
+    /// the first prompt before calling this.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, a write fails, or in
+    /// paced mode a prompt does not appear within its timeout.
+    pub async fn send_file(&mut self, path: impl AsRef<Path>, mode: SendFileMode) -> Result<()> {
+        let path = path.as_ref();
+        let data = tokio::fs::read(path)
+            .await
+            .map_err(|e| ExpectError::io_context(format!("reading {}", path.display()), e))?;
+
+        match mode {
+            SendFileMode::Raw => self.send(&data).await,
+            SendFileMode::PromptPaced { prompt, timeout } => {
+                let line_ending = self.config.line_ending.as_bytes();
+                for line in file::lines(&data) {
+                    self.send(&[line, line_ending].concat()).await?;
+                    self.expect_timeout(prompt.clone(), timeout).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
     /// Expect a pattern in the output.
     ///
     /// Blocks until the pattern is matched, EOF is detected, or timeout occurs.
     ///
+    /// On a match, the output up to and including the match is consumed from
+    /// the buffer and returned as [`Match::before`] and [`Match::matched`].
+    /// Output after the match stays buffered, so the next expect starts
+    /// searching right where this one stopped. An [`Pattern::Eof`] match
+    /// consumes the whole buffer into [`Match::before`]. On error nothing is
+    /// consumed.
+    ///
+    /// When unmatched output outgrows the configured
+    /// [`max_size`](crate::config::BufferConfig::max_size), the oldest output
+    /// is discarded and the
+    /// [`on_overflow`](crate::config::BufferConfig::on_overflow) callback is
+    /// called. With [`ring_buffer`](crate::config::BufferConfig::ring_buffer)
+    /// disabled, the expect fails with [`ExpectError::BufferOverflow`] instead.
+    ///
     /// # Errors
     ///
-    /// Returns an error on timeout, EOF (if not expected), or I/O error.
+    /// Returns an error on timeout, EOF (if not expected), buffer overflow
+    /// without a ring buffer, or I/O error.
     pub async fn expect(&mut self, pattern: impl Into<Pattern>) -> Result<Match> {
         let patterns = PatternSet::from_patterns(vec![pattern.into()]);
         self.expect_any(&patterns).await
@@ -175,19 +308,46 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
 
     /// Expect any of the given patterns.
     ///
+    /// If the set has a [fallback](PatternSet::add_fallback), it matches
+    /// instead of a timeout or EOF error.
+    ///
     /// # Errors
     ///
     /// Returns an error on timeout, EOF (if not expected), or I/O error.
     pub async fn expect_any(&mut self, patterns: &PatternSet) -> Result<Match> {
+        #[cfg(feature = "metrics")]
+        let timer = Timer::start();
+        let result = self.expect_any_unrecorded(patterns).await;
+        #[cfg(feature = "metrics")]
+        self.record_expect(timer, &result);
+        result
+    }
+
+    /// Record an expect's duration, and whether it timed out.
+    #[cfg(feature = "metrics")]
+    fn record_expect<R>(&self, timer: Timer, result: &Result<R>) {
+        timer.record_to(self.metrics.expect_latency());
+        if matches!(result, Err(ExpectError::Timeout { .. })) {
+            self.metrics.timeouts().inc();
+        }
+    }
+
+    /// The expect loop behind [`expect_any`](Self::expect_any).
+    async fn expect_any_unrecorded(&mut self, patterns: &PatternSet) -> Result<Match> {
         let timeout = self.matcher.get_timeout(patterns);
         let state = ExpectState::new(patterns.clone(), timeout);
+        self.matcher.reset_scan();
+        self.overflowed = false;
 
         loop {
             // Check before patterns first
-            if let Some((_, action)) = self
-                .pattern_manager
-                .check_before(&self.matcher.buffer_str())
-            {
+            let before = if self.pattern_manager.before_count() == 0 {
+                None
+            } else {
+                self.pattern_manager
+                    .check_before(&self.matcher.buffer_str())
+            };
+            if let Some((_, action)) = before {
                 match action {
                     crate::expect::HandlerAction::Continue => {}
                     crate::expect::HandlerAction::Return(s) => {
@@ -210,8 +370,17 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
                 return Ok(self.matcher.consume_match(&result));
             }
 
+            // Output that could have held the match was discarded
+            if self.overflowed && !self.config.buffer.ring_buffer {
+                return Err(ExpectError::buffer_overflow(self.config.buffer.max_size));
+            }
+
             // Check for timeout
             if state.is_timed_out() {
+                if let Some(index) = patterns.fallback_index() {
+                    let before = self.matcher.buffer_str();
+                    return Ok(Match::new(index, String::new(), before.clone(), before));
+                }
                 return Err(ExpectError::Timeout {
                     duration: timeout,
                     pattern: patterns
@@ -225,13 +394,10 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
 
             // Check for EOF
             if self.eof {
-                if state.expects_eof() {
-                    return Ok(Match::new(
-                        0,
-                        String::new(),
-                        self.matcher.buffer_str(),
-                        String::new(),
-                    ));
+                if let Some(index) = patterns.eof_index().or_else(|| patterns.fallback_index()) {
+                    let before = self.matcher.buffer_str();
+                    self.matcher.clear();
+                    return Ok(Match::new(index, String::new(), before, String::new()));
                 }
                 return Err(ExpectError::Eof {
                     buffer: self.matcher.buffer_str(),
@@ -259,18 +425,213 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
         self.expect_any(&patterns).await
     }
 
+    /// Expect a pattern, failing fast if a forbidden pattern appears first.
+    ///
+    /// All patterns are checked against the same buffer on every read, and
+    /// whichever matches earliest in the output wins. This lets a long wait
+    /// abort as soon as a known error banner shows up.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExpectError::ForbiddenMatch`] if a forbidden pattern
+    /// matches first, or an error on timeout, EOF, or I/O failure.
+    pub async fn expect_unless(
+        &mut self,
+        want: impl Into<Pattern>,
+        forbidden: &[Pattern],
+    ) -> Result<Match> {
+        let mut patterns = PatternSet::new();
+        patterns.add(want.into());
+        for pattern in forbidden {
+            patterns.add(pattern.clone());
+        }
+
+        let m = self.expect_any(&patterns).await?;
+        match m.pattern_index.checked_sub(1) {
+            Some(index) => Err(ExpectError::forbidden_match(
+                forbidden[index].as_str(),
+                m.matched,
+            )),
+            None => Ok(m),
+        }
+    }
+
+    /// Expect each pattern in turn, returning the matches in the order given.
+    ///
+    /// Each pattern is expected like [`expect`](Self::expect), with its own
+    /// timeout, and consumes the buffer up to its match before the next one
+    /// is searched for. Each returned [`Match::pattern_index`] is the
+    /// pattern's position in `patterns`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first pattern that does not match, on
+    /// timeout, EOF, or I/O error. Matches before it stay consumed.
+    pub async fn expect_sequence(&mut self, patterns: &[Pattern]) -> Result<Vec<Match>> {
+        let mut matches = Vec::with_capacity(patterns.len());
+        for (index, pattern) in patterns.iter().enumerate() {
+            let mut m = self.expect(pattern.clone()).await?;
+            m.pattern_index = index;
+            matches.push(m);
+        }
+        Ok(matches)
+    }
+
+    /// Read whatever output is available, up to `max` bytes.
+    ///
+    /// Returns the output buffered but not yet consumed by an expect, topped
+    /// up with a single non-blocking read from the transport. If nothing is
+    /// buffered, this waits until the process produces output. When more
+    /// than `max` bytes are available, the first `max` are returned and the
+    /// rest stay buffered for the next call or expect.
+    ///
+    /// An empty vector is returned if `max` is zero, or once EOF has been
+    /// reached and the buffer is drained.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the transport fails.
+    pub async fn read_available(&mut self, max: usize) -> Result<Vec<u8>> {
+        if max == 0 {
+            return Ok(Vec::new());
+        }
+
+        if self.matcher.buffer().is_empty() {
+            // Wait in default-timeout slices so the transport lock is released
+            while !self.eof && self.matcher.buffer().is_empty() {
+                self.read_with_timeout(self.config.timeout.default).await?;
+            }
+        } else if !self.eof && self.matcher.buffer().len() < max {
+            // A zero timeout polls the read exactly once
+            self.read_with_timeout(Duration::ZERO).await?;
+        }
+
+        Ok(self.matcher.take(max))
+    }
+
+    /// Get the rendered terminal screen.
+    ///
+    /// Every byte read from the process is also fed to this screen, so it
+    /// reflects cursor movement, erases and redraws.
+    #[cfg(feature = "screen")]
+    #[must_use]
+    pub const fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    /// Expect a pattern in the rendered screen instead of the raw output.
+    ///
+    /// This suits full-screen programs, whose raw output is full of cursor
+    /// movement and redraws. The pattern is matched against the screen
+    /// text, one line per row, every time new output arrives.
+    ///
+    /// The returned [`Match`] describes the screen text. Screen matches do
+    /// not consume the raw output buffer used by [`expect`](Self::expect),
+    /// and the screen keeps showing the matched text until the program
+    /// redraws it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on timeout, EOF, or I/O error. The error buffer
+    /// holds the screen text.
+    #[cfg(feature = "screen")]
+    pub async fn expect_on_screen(&mut self, pattern: impl Into<Pattern>) -> Result<Match> {
+        #[cfg(feature = "metrics")]
+        let timer = Timer::start();
+        let result = self.expect_on_screen_unrecorded(pattern.into()).await;
+        #[cfg(feature = "metrics")]
+        self.record_expect(timer, &result);
+        result
+    }
+
+    /// The wait behind [`expect_on_screen`](Self::expect_on_screen).
+    #[cfg(feature = "screen")]
+    async fn expect_on_screen_unrecorded(&mut self, pattern: Pattern) -> Result<Match> {
+        let timeout = self.config.timeout.default;
+        let deadline = deadline_after(timeout);
+
+        loop {
+            let text = self.screen.text();
+            if let Some(m) = pattern.matches(&text) {
+                return Ok(
+                    Match::new(0, &text[m.start..m.end], &text[..m.start], &text[m.end..])
+                        .with_captures(m.captures)
+                        .with_groups(m.groups),
+                );
+            }
+
+            if self.eof {
+                return Err(ExpectError::Eof { buffer: text });
+            }
+
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining.is_zero() {
+                return Err(ExpectError::timeout(timeout, pattern.as_str(), text));
+            }
+            self.read_with_timeout(remaining).await?;
+        }
+    }
+
+    /// Read the next line of output.
+    ///
+    /// Waits until the buffer holds a line terminated by the configured
+    /// [`LineEnding`](crate::config::LineEnding), then consumes it and
+    /// returns it without the terminator. Lines share the buffer with
+    /// [`expect`](Self::expect), so both can be mixed freely.
+    ///
+    /// Once EOF is reached, any unterminated trailing output is returned as
+    /// a final line, after which `Ok(None)` is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExpectError::Timeout`] if no complete line arrives within
+    /// `timeout`; a partial line stays buffered. Returns an error if reading
+    /// from the transport fails.
+    pub async fn next_line(&mut self, timeout: Duration) -> Result<Option<String>> {
+        let ending = self.config.line_ending.as_bytes();
+        let deadline = deadline_after(timeout);
+
+        loop {
+            if let Some(line) = self.matcher.take_line(ending) {
+                return Ok(Some(line));
+            }
+
+            if self.eof {
+                if self.matcher.buffer().is_empty() {
+                    return Ok(None);
+                }
+                let line = self.matcher.buffer_str();
+                self.matcher.clear();
+                return Ok(Some(line));
+            }
+
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining.is_zero() {
+                return Err(ExpectError::timeout(
+                    timeout,
+                    "<line>",
+                    self.matcher.buffer_str(),
+                ));
+            }
+            self.read_with_timeout(remaining).await?;
+        }
+    }
+
     /// Read data from the transport with timeout.
     async fn read_with_timeout(&mut self, timeout: Duration) -> Result<usize> {
         let mut buf = [0u8; 4096];
-        let mut transport = self.transport.lock().await;
+        let read = {
+            let mut transport = self.transport.lock().await;
+            tokio::time::timeout(timeout, transport.read(&mut buf)).await
+        };
 
-        match tokio::time::timeout(timeout, transport.read(&mut buf)).await {
+        match read {
             Ok(Ok(0)) => {
-                self.eof = true;
+                self.mark_eof();
                 Ok(0)
             }
             Ok(Ok(n)) => {
-                self.matcher.append(&buf[..n]);
+                self.ingest(&buf[..n]);
                 Ok(n)
             }
             Ok(Err(e)) => {
@@ -278,7 +639,7 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
                 // (i.e., the child process has terminated). Treat this as EOF.
                 // See: https://bugs.python.org/issue5380
                 if is_pty_eof_error(&e) {
-                    self.eof = true;
+                    self.mark_eof();
                     Ok(0)
                 } else {
                     Err(ExpectError::io_context("reading from process", e))
@@ -291,6 +652,52 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
         }
     }
 
+    /// Feed output to the matcher and screen, decoding it first when a
+    /// legacy charset is configured.
+    fn ingest(&mut self, data: &[u8]) {
+        let discarded = self.matcher.buffer().bytes_discarded();
+        #[cfg(feature = "legacy-encoding")]
+        if let Some(decoder) = &mut self.decoder {
+            let text = decoder.decode(data);
+            self.matcher.append(text.as_bytes());
+            #[cfg(feature = "screen")]
+            self.screen.process(text.as_bytes());
+            self.note_overflow(discarded);
+            return;
+        }
+        self.matcher.append(data);
+        #[cfg(feature = "screen")]
+        self.screen.process(data);
+        self.note_overflow(discarded);
+    }
+
+    /// Record EOF, flushing any partial character the decoder held back.
+    #[cfg_attr(not(feature = "legacy-encoding"), allow(clippy::missing_const_for_fn))]
+    fn mark_eof(&mut self) {
+        self.eof = true;
+        #[cfg(feature = "legacy-encoding")]
+        if let Some(decoder) = &mut self.decoder {
+            let discarded = self.matcher.buffer().bytes_discarded();
+            let text = decoder.finish();
+            self.matcher.append(text.as_bytes());
+            #[cfg(feature = "screen")]
+            self.screen.process(text.as_bytes());
+            self.note_overflow(discarded);
+        }
+    }
+
+    /// Flag an overflow and notify the callback if output was discarded
+    /// since the buffer had discarded `before` bytes.
+    fn note_overflow(&mut self, before: usize) {
+        let lost = self.matcher.buffer().bytes_discarded() - before;
+        if lost > 0 {
+            self.overflowed = true;
+            if let Some(callback) = &self.config.buffer.on_overflow {
+                callback(lost);
+            }
+        }
+    }
+
     /// Wait for the process to exit.
     ///
     /// This method blocks until EOF is detected on the session, which typically
@@ -328,7 +735,7 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
     /// - The timeout expires before the process exits
     /// - An I/O error occurs while waiting
     pub async fn wait_timeout(&mut self, timeout: Duration) -> Result<ProcessExitStatus> {
-        let deadline = tokio::time::Instant::now() + timeout;
+        let deadline = deadline_after(timeout);
 
         while !self.eof {
             let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
@@ -397,7 +804,11 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
     where
         T: 'static,
     {
-        InteractBuilder::new(&self.transport)
+        let builder =
+            InteractBuilder::new(&self.transport).with_line_ending(self.config.line_ending);
+        #[cfg(feature = "legacy-encoding")]
+        let builder = builder.with_decoder(self.config.encoding.decoder());
+        builder
     }
 
     /// Run a dialog on this session.
@@ -453,8 +864,9 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
 
     /// Expect end-of-file (process termination).
     ///
-    /// This is a convenience method for waiting until the process terminates
-    /// and closes its output stream.
+    /// Reads until the process closes its output stream and returns
+    /// everything it printed that no earlier expect consumed. The wait is
+    /// bounded by the session's default timeout.
     ///
     /// # Example
     ///
@@ -463,29 +875,70 @@ impl<T: AsyncReadExt + AsyncWriteExt + Unpin + Send> Session<T> {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), rust_expect::ExpectError> {
-    ///     let mut session = Session::spawn("echo", &["hello"]).await?;
-    ///     session.expect("hello").await?;
-    ///     session.expect_eof().await?;
+    ///     let mut session = Session::spawn("sh", &[]).await?;
+    ///     session.send_line("echo bye; exit").await?;
+    ///     let output = session.expect_eof().await?;
+    ///     assert!(output.contains("bye"));
     ///     Ok(())
     /// }
     /// ```
     ///
     /// # Errors
     ///
-    /// Returns an error if the session times out before EOF or an I/O error occurs.
-    pub async fn expect_eof(&mut self) -> Result<Match> {
-        self.expect(Pattern::eof()).await
+    /// Returns [`ExpectError::UnexpectedData`] if the process is still
+    /// producing output when the timeout expires, meaning some arrived in
+    /// the last quarter of the wait. Returns [`ExpectError::Timeout`] if it
+    /// went quiet before that without closing, or an error if reading fails.
+    pub async fn expect_eof(&mut self) -> Result<String> {
+        self.expect_eof_timeout(self.config.timeout.default).await
     }
 
     /// Expect end-of-file with a specific timeout.
     ///
+    /// See [`expect_eof`](Self::expect_eof).
+    ///
     /// # Errors
     ///
-    /// Returns an error if the session times out before EOF or an I/O error occurs.
-    pub async fn expect_eof_timeout(&mut self, timeout: Duration) -> Result<Match> {
-        let mut patterns = PatternSet::new();
-        patterns.add(Pattern::eof()).add(Pattern::timeout(timeout));
-        self.expect_any(&patterns).await
+    /// Returns [`ExpectError::UnexpectedData`] if the process is still
+    /// producing output when the timeout expires, meaning some arrived in
+    /// the last quarter of the wait. Returns [`ExpectError::Timeout`] if it
+    /// went quiet before that without closing, or an error if reading fails.
+    pub async fn expect_eof_timeout(&mut self, timeout: Duration) -> Result<String> {
+        #[cfg(feature = "metrics")]
+        let timer = Timer::start();
+        let result = self.expect_eof_unrecorded(timeout).await;
+        #[cfg(feature = "metrics")]
+        self.record_expect(timer, &result);
+        result
+    }
+
+    /// The wait behind [`expect_eof_timeout`](Self::expect_eof_timeout).
+    async fn expect_eof_unrecorded(&mut self, timeout: Duration) -> Result<String> {
+        let deadline = deadline_after(timeout);
+        // Output after this point means the process is still busy
+        let quiet_from =
+            deadline - deadline.saturating_duration_since(tokio::time::Instant::now()) / 4;
+        let mut last_output = None;
+
+        while !self.eof {
+            let now = tokio::time::Instant::now();
+            let remaining = deadline.saturating_duration_since(now);
+            if remaining.is_zero() {
+                let buffer = self.matcher.buffer_str();
+                return Err(if last_output.is_some_and(|at| at >= quiet_from) {
+                    ExpectError::unexpected_data(timeout, buffer)
+                } else {
+                    ExpectError::timeout(timeout, "<EOF>", buffer)
+                });
+            }
+            if self.read_with_timeout(remaining).await? > 0 {
+                last_output = Some(tokio::time::Instant::now());
+            }
+        }
+
+        let output = self.matcher.buffer_str();
+        self.matcher.clear();
+        Ok(output)
     }
 
     /// Run a batch of commands, waiting for the prompt after each.
@@ -665,7 +1118,7 @@ impl Session<AsyncPty> {
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The command contains null bytes
+    /// - The command is empty or contains null bytes
     /// - PTY allocation fails
     /// - Fork fails
     /// - The command cannot be executed
@@ -677,18 +1130,25 @@ impl Session<AsyncPty> {
     ///
     /// # Errors
     ///
-    /// Returns an error if spawning fails.
+    /// Returns [`SpawnError::WorkingDirMissing`] if the working directory
+    /// does not exist and nothing else is wrong, [`SpawnError::InvalidConfig`]
+    /// listing every problem if the configuration otherwise fails
+    /// [`validate_session_config`], or an error if spawning fails.
     pub async fn spawn_with_config(
         command: &str,
         args: &[&str],
-        config: SessionConfig,
+        mut config: SessionConfig,
     ) -> Result<Self> {
-        let pty_config = PtyConfig::from(&config);
-        let spawner = PtySpawner::with_config(pty_config);
-
         // Convert &[&str] to Vec<String> for the spawner
         let args_owned: Vec<String> = args.iter().map(|s| (*s).to_string()).collect();
 
+        config.command = command.to_string();
+        config.args.clone_from(&args_owned);
+        validate_session_config(&config).map_err(|errors| config_error(&config, errors))?;
+
+        let pty_config = PtyConfig::from(&config);
+        let spawner = PtySpawner::with_config(pty_config);
+
         // Spawn the process
         let handle = spawner.spawn(command, &args_owned).await?;
 
@@ -722,8 +1182,10 @@ impl Session<AsyncPty> {
     ///
     /// Returns an error if the resize ioctl fails.
     pub async fn resize_pty(&mut self, cols: u16, rows: u16) -> Result<()> {
-        let mut transport = self.transport.lock().await;
-        transport.resize(cols, rows)
+        self.transport.lock().await.resize(cols, rows)?;
+        #[cfg(feature = "screen")]
+        self.screen.resize(usize::from(rows), usize::from(cols));
+        Ok(())
     }
 
     /// Send a signal to the child process.
@@ -742,6 +1204,41 @@ impl Session<AsyncPty> {
         }
     }
 
+    /// Send a signal to the terminal's foreground process group.
+    ///
+    /// Unlike [`signal`](Self::signal), which targets only the spawned process,
+    /// this queries the PTY's current foreground process group (`tcgetpgrp`)
+    /// and signals it directly. This reaches whatever job is currently running
+    /// in a shell without depending on the line discipline translating control
+    /// characters, which may be disabled by the terminal settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the foreground process group cannot be determined
+    /// or sending the signal fails.
+    pub fn send_signal_to_foreground(&self, signal: i32) -> Result<()> {
+        if let Ok(transport) = self.transport.try_lock() {
+            transport.signal_foreground(signal)
+        } else {
+            Err(ExpectError::io_context(
+                "sending signal to foreground process group",
+                std::io::Error::new(std::io::ErrorKind::WouldBlock, "transport is locked"),
+            ))
+        }
+    }
+
+    /// Interrupt the command currently running in the terminal.
+    ///
+    /// Sends `SIGINT` to the foreground process group. See
+    /// [`send_signal_to_foreground`](Self::send_signal_to_foreground).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if sending the signal fails.
+    pub fn interrupt(&self) -> Result<()> {
+        self.send_signal_to_foreground(libc::SIGINT)
+    }
+
     /// Kill the child process.
     ///
     /// # Errors
@@ -759,6 +1256,66 @@ impl Session<AsyncPty> {
     }
 }
 
+// SSH spawn implementation
+#[cfg(feature = "ssh")]
+impl Session<SshChannelStream> {
+    /// Connect to an SSH server and start an interactive shell.
+    ///
+    /// The shell runs on a PTY, so the full expect/send/dialog API works
+    /// the same as for a local process. The returned session owns the
+    /// connection and closes it when dropped.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rust_expect::Session;
+    /// use rust_expect::backend::ssh::{SshConfig, SshCredentials};
+    ///
+    /// let config = SshConfig::new("example.com")
+    ///     .credentials(SshCredentials::new("user").with_password("pass"));
+    /// let mut session = Session::spawn_ssh(config).await?;
+    /// session.expect("$ ").await?;
+    /// session.send_line("uname -a").await?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if connecting, authenticating, or starting the
+    /// shell fails.
+    pub async fn spawn_ssh(ssh: SshConfig) -> Result<Self> {
+        Self::spawn_ssh_with_config(ssh, SessionConfig::default()).await
+    }
+
+    /// Connect over SSH and start a shell with custom session configuration.
+    ///
+    /// The PTY takes its size from `config.dimensions` and its terminal
+    /// type from a `TERM` entry in `config.env`, if present.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if connecting, authenticating, or starting the
+    /// shell fails.
+    pub async fn spawn_ssh_with_config(ssh: SshConfig, config: SessionConfig) -> Result<Self> {
+        let mut connection = SshSession::new(ssh);
+        connection.connect_async().await?;
+
+        let (cols, rows) = config.dimensions;
+        let mut channel_config = ChannelConfig::default().dimensions(cols, rows);
+        if let Some(term) = config.env.get("TERM") {
+            channel_config = channel_config.term(term);
+        }
+        let stream = connection
+            .shell_with_config(channel_config)
+            .await?
+            .owning(connection);
+
+        let mut session = Self::new(stream, config);
+        session.state = SessionState::Running;
+
+        Ok(session)
+    }
+}
+
 // Windows-specific spawn implementation
 #[cfg(windows)]
 impl Session<WindowsAsyncPty> {
@@ -796,18 +1353,25 @@ impl Session<WindowsAsyncPty> {
     ///
     /// # Errors
     ///
-    /// Returns an error if spawning fails.
+    /// Returns [`SpawnError::WorkingDirMissing`] if the working directory
+    /// does not exist and nothing else is wrong, [`SpawnError::InvalidConfig`]
+    /// listing every problem if the configuration otherwise fails
+    /// [`validate_session_config`], or an error if spawning fails.
     pub async fn spawn_with_config(
         command: &str,
         args: &[&str],
-        config: SessionConfig,
+        mut config: SessionConfig,
     ) -> Result<Self> {
-        let pty_config = PtyConfig::from(&config);
-        let spawner = PtySpawner::with_config(pty_config);
-
         // Convert &[&str] to Vec<String> for the spawner
         let args_owned: Vec<String> = args.iter().map(|s| s.to_string()).collect();
 
+        config.command = command.to_string();
+        config.args.clone_from(&args_owned);
+        validate_session_config(&config).map_err(|errors| config_error(&config, errors))?;
+
+        let pty_config = PtyConfig::from(&config);
+        let spawner = PtySpawner::with_config(pty_config);
+
         // Spawn the process
         let handle = spawner.spawn(command, &args_owned).await?;
 
@@ -837,8 +1401,10 @@ impl Session<WindowsAsyncPty> {
     ///
     /// Returns an error if the resize operation fails.
     pub async fn resize_pty(&mut self, cols: u16, rows: u16) -> Result<()> {
-        let mut transport = self.transport.lock().await;
-        transport.resize(cols, rows)
+        self.transport.lock().await.resize(cols, rows)?;
+        #[cfg(feature = "screen")]
+        self.screen.resize(usize::from(rows), usize::from(cols));
+        Ok(())
     }
 
     /// Check if the child process is still running.
@@ -851,6 +1417,25 @@ impl Session<WindowsAsyncPty> {
         }
     }
 
+    /// Interrupt the command currently running in the console.
+    ///
+    /// Generates a Ctrl+C console event rather than writing the `0x03` byte,
+    /// so it does not depend on the console's input processing mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the console event cannot be generated.
+    pub fn interrupt(&self) -> Result<()> {
+        if let Ok(transport) = self.transport.try_lock() {
+            transport.interrupt()
+        } else {
+            Err(ExpectError::io_context(
+                "interrupting process",
+                std::io::Error::new(std::io::ErrorKind::WouldBlock, "transport is locked"),
+            ))
+        }
+    }
+
     /// Kill the child process.
     ///
     /// # Errors
@@ -884,6 +1469,31 @@ pub trait SessionExt {
     ) -> impl std::future::Future<Output = Result<()>> + Send;
 }
 
+/// The spawn error for a configuration that failed validation.
+///
+/// A missing working directory on its own is still reported as
+/// [`SpawnError::WorkingDirMissing`], as it was before validation ran.
+fn config_error(config: &SessionConfig, errors: Vec<ValidationError>) -> SpawnError {
+    if let ([only], Some(dir)) = (errors.as_slice(), &config.working_dir)
+        && only.field == "working_dir"
+        && !dir.exists()
+    {
+        return SpawnError::working_dir_missing(dir.display().to_string());
+    }
+    SpawnError::invalid_config(errors)
+}
+
+/// The instant `timeout` from now.
+///
+/// A timeout too large to represent, such as [`Duration::MAX`], gives a
+/// deadline about 30 years out instead of overflowing.
+fn deadline_after(timeout: Duration) -> tokio::time::Instant {
+    const FAR_FUTURE: Duration = Duration::from_secs(86_400 * 365 * 30);
+
+    let now = tokio::time::Instant::now();
+    now.checked_add(timeout).unwrap_or_else(|| now + FAR_FUTURE)
+}
+
 /// Check if an I/O error indicates PTY EOF.
 ///
 /// On Linux, reading from the PTY master returns EIO when the slave side
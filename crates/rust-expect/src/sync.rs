@@ -2,31 +2,93 @@
 //!
 //! This module provides a blocking API for users who prefer or require
 //! synchronous operations instead of async/await.
+//!
+//! A [`SyncSession`] drives its session on a runtime it owns, or on one
+//! it was handed with [`SyncSession::spawn_on`]. Its methods may also be
+//! called from inside a multi-threaded Tokio runtime: they block the
+//! calling worker with [`tokio::task::block_in_place`], so the other
+//! workers keep running. Calling them from a current-thread runtime
+//! panics, since blocking its only thread would stall every task on it.
 
 use std::time::Duration;
 
-use tokio::runtime::{Builder, Runtime};
+use tokio::runtime::{Builder, Handle, Runtime, RuntimeFlavor};
 
 #[cfg(unix)]
 use crate::backend::AsyncPty;
 #[cfg(windows)]
 use crate::backend::WindowsAsyncPty;
 use crate::config::SessionConfig;
+use crate::dialog::{Dialog, DialogResult};
 use crate::error::Result;
-use crate::expect::Pattern;
+use crate::expect::{Pattern, PatternSet};
 use crate::session::Session;
 use crate::types::{ControlChar, Match};
 
+/// The runtime a [`SyncSession`] blocks on.
+enum Driver {
+    /// A current-thread runtime created for the session.
+    ///
+    /// Only taken when the driver is dropped.
+    Owned(Option<Runtime>),
+    /// A runtime owned by the caller.
+    Borrowed(Handle),
+}
+
+impl Driver {
+    /// Create a current-thread runtime for a session.
+    fn owned() -> Result<Self> {
+        Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map(|runtime| Self::Owned(Some(runtime)))
+            .map_err(|e| crate::error::ExpectError::io_context("creating tokio runtime", e))
+    }
+
+    /// Block on `future`, stepping out of any runtime the caller is on.
+    fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
+        let run = || match self {
+            Self::Owned(Some(runtime)) => runtime.block_on(future),
+            Self::Owned(None) => unreachable!("the runtime is only taken on drop"),
+            Self::Borrowed(handle) => handle.block_on(future),
+        };
+        match Handle::try_current() {
+            Err(_) => run(),
+            Ok(current) if current.runtime_flavor() == RuntimeFlavor::CurrentThread => panic!(
+                "SyncSession cannot block inside a current-thread Tokio runtime; \
+                 use the async Session or a multi-threaded runtime"
+            ),
+            Ok(_) => tokio::task::block_in_place(run),
+        }
+    }
+}
+
+impl Drop for Driver {
+    fn drop(&mut self) {
+        // Outside a runtime, an owned runtime waits for its tasks to stop
+        // when dropped. That wait is forbidden inside another runtime, so
+        // there it is shut down in the background.
+        if let Self::Owned(runtime) = self
+            && Handle::try_current().is_ok()
+            && let Some(runtime) = runtime.take()
+        {
+            runtime.shutdown_background();
+        }
+    }
+}
+
 /// A synchronous session wrapper.
 ///
 /// This wraps an async session and provides blocking methods for
 /// use in synchronous contexts.
 #[cfg(unix)]
 pub struct SyncSession {
-    /// The tokio runtime.
-    runtime: Runtime,
     /// The inner async session.
+    ///
+    /// Declared first so it is dropped while the runtime is still alive.
     inner: Session<AsyncPty>,
+    /// The tokio runtime.
+    runtime: Driver,
 }
 
 /// A synchronous session wrapper (Windows).
@@ -35,10 +97,12 @@ pub struct SyncSession {
 /// use in synchronous contexts using Windows ConPTY.
 #[cfg(windows)]
 pub struct SyncSession {
-    /// The tokio runtime.
-    runtime: Runtime,
     /// The inner async session.
+    ///
+    /// Declared first so it is dropped while the runtime is still alive.
     inner: Session<WindowsAsyncPty>,
+    /// The tokio runtime.
+    runtime: Driver,
 }
 
 #[cfg(unix)]
@@ -49,14 +113,11 @@ impl SyncSession {
     ///
     /// Returns an error if spawning fails.
     pub fn spawn(command: &str, args: &[&str]) -> Result<Self> {
-        let runtime = Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .map_err(|e| crate::error::ExpectError::io_context("creating tokio runtime", e))?;
+        let runtime = Driver::owned()?;
 
         let inner = runtime.block_on(Session::spawn(command, args))?;
 
-        Ok(Self { runtime, inner })
+        Ok(Self { inner, runtime })
     }
 
     /// Spawn with custom configuration.
@@ -65,14 +126,11 @@ impl SyncSession {
     ///
     /// Returns an error if spawning fails.
     pub fn spawn_with_config(command: &str, args: &[&str], config: SessionConfig) -> Result<Self> {
-        let runtime = Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .map_err(|e| crate::error::ExpectError::io_context("creating tokio runtime", e))?;
+        let runtime = Driver::owned()?;
 
         let inner = runtime.block_on(Session::spawn_with_config(command, args, config))?;
 
-        Ok(Self { runtime, inner })
+        Ok(Self { inner, runtime })
     }
 
     /// Get the session configuration.
@@ -129,6 +187,15 @@ impl SyncSession {
         self.runtime.block_on(self.inner.send_control(ctrl))
     }
 
+    /// Send a chord of control characters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write fails.
+    pub fn send_chord(&mut self, chord: &[ControlChar]) -> Result<()> {
+        self.runtime.block_on(self.inner.send_chord(chord))
+    }
+
     /// Expect a pattern in the output.
     ///
     /// # Errors
@@ -191,6 +258,10 @@ impl SyncSession {
     }
 
     /// Run an async operation synchronously.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from a current-thread Tokio runtime.
     pub fn block_on<F, T>(&self, future: F) -> T
     where
         F: std::future::Future<Output = T>,
@@ -207,14 +278,11 @@ impl SyncSession {
     ///
     /// Returns an error if spawning fails.
     pub fn spawn(command: &str, args: &[&str]) -> Result<Self> {
-        let runtime = Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .map_err(|e| crate::error::ExpectError::io_context("creating tokio runtime", e))?;
+        let runtime = Driver::owned()?;
 
         let inner = runtime.block_on(Session::spawn(command, args))?;
 
-        Ok(Self { runtime, inner })
+        Ok(Self { inner, runtime })
     }
 
     /// Spawn with custom configuration.
@@ -223,14 +291,11 @@ impl SyncSession {
     ///
     /// Returns an error if spawning fails.
     pub fn spawn_with_config(command: &str, args: &[&str], config: SessionConfig) -> Result<Self> {
-        let runtime = Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .map_err(|e| crate::error::ExpectError::io_context("creating tokio runtime", e))?;
+        let runtime = Driver::owned()?;
 
         let inner = runtime.block_on(Session::spawn_with_config(command, args, config))?;
 
-        Ok(Self { runtime, inner })
+        Ok(Self { inner, runtime })
     }
 
     /// Get the session configuration.
@@ -287,6 +352,15 @@ impl SyncSession {
         self.runtime.block_on(self.inner.send_control(ctrl))
     }
 
+    /// Send a chord of control characters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write fails.
+    pub fn send_chord(&mut self, chord: &[ControlChar]) -> Result<()> {
+        self.runtime.block_on(self.inner.send_chord(chord))
+    }
+
     /// Expect a pattern in the output.
     ///
     /// # Errors
@@ -346,6 +420,10 @@ impl SyncSession {
     }
 
     /// Run an async operation synchronously.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from a current-thread Tokio runtime.
     pub fn block_on<F, T>(&self, future: F) -> T
     where
         F: std::future::Future<Output = T>,
@@ -354,6 +432,85 @@ impl SyncSession {
     }
 }
 
+impl SyncSession {
+    /// Spawn `config.command` with `config.args` on a runtime of its own.
+    ///
+    /// The session creates and keeps a current-thread runtime, so a plain
+    /// `fn main` can automate a process without any Tokio setup. The
+    /// runtime is shut down when the session is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runtime cannot be created or spawning fails.
+    pub fn spawn_blocking(config: SessionConfig) -> Result<Self> {
+        let runtime = Driver::owned()?;
+        let command = config.command.clone();
+        let args = config.args.clone();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let inner = runtime.block_on(Session::spawn_with_config(&command, &args, config))?;
+
+        Ok(Self { inner, runtime })
+    }
+
+    /// Spawn a command on a runtime owned by the caller.
+    ///
+    /// The session's I/O is driven by `handle`, so the runtime must keep
+    /// running while the session is in use; a multi-threaded runtime does
+    /// this on its own.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if spawning fails.
+    pub fn spawn_on(
+        handle: Handle,
+        command: &str,
+        args: &[&str],
+        config: SessionConfig,
+    ) -> Result<Self> {
+        let runtime = Driver::Borrowed(handle);
+        let inner = runtime.block_on(Session::spawn_with_config(command, args, config))?;
+
+        Ok(Self { inner, runtime })
+    }
+
+    /// Expect any of a set of patterns, returning the first to match.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on timeout or EOF, unless the set handles them.
+    pub fn expect_any(&mut self, patterns: &PatternSet) -> Result<Match> {
+        self.runtime.block_on(self.inner.expect_any(patterns))
+    }
+
+    /// Wait for the process to close its output, returning what was left.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on timeout.
+    pub fn expect_eof(&mut self) -> Result<String> {
+        self.runtime.block_on(self.inner.expect_eof())
+    }
+
+    /// Wait for end-of-file with a specific timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on timeout.
+    pub fn expect_eof_timeout(&mut self, timeout: Duration) -> Result<String> {
+        self.runtime
+            .block_on(self.inner.expect_eof_timeout(timeout))
+    }
+
+    /// Run a dialog to completion.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an I/O error stops the dialog.
+    pub fn run_dialog(&mut self, dialog: &Dialog) -> Result<DialogResult> {
+        self.runtime.block_on(self.inner.run_dialog(dialog))
+    }
+}
+
 impl std::fmt::Debug for SyncSession {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SyncSession").finish_non_exhaustive()
@@ -445,4 +602,80 @@ mod tests {
         let m = session.expect("hello").expect("Failed to expect hello");
         assert!(m.matched.contains("hello"));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn sync_session_echo_interaction() {
+        let mut session = SyncSession::spawn("/bin/cat", &[]).expect("Failed to spawn cat");
+
+        session.send_line("ping").expect("Failed to send");
+        session.expect("ping").expect("Failed to expect ping");
+
+        let mut patterns = PatternSet::new();
+        patterns
+            .add(Pattern::literal("never"))
+            .add(Pattern::literal("pong"));
+        session.send_line("pong").expect("Failed to send");
+        let m = session
+            .expect_any(&patterns)
+            .expect("Failed to expect pong");
+        assert_eq!(m.pattern_index, 1);
+
+        session
+            .send_control(ControlChar::CtrlD)
+            .expect("Failed to send EOF");
+        session.expect_eof().expect("cat did not exit");
+        assert!(!session.is_active());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sync_session_run_dialog() {
+        let mut session = SyncSession::spawn("/bin/cat", &[]).expect("Failed to spawn cat");
+
+        let dialog = Dialog::new()
+            .step(crate::dialog::DialogStep::send_line("hello"))
+            .step(crate::dialog::DialogStep::expect("hello"));
+        let result = session.run_dialog(&dialog).expect("Dialog failed");
+        assert!(result.success, "{:?}", result.error);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sync_session_inside_multi_thread_runtime() {
+        let mut session = SyncSession::spawn("/bin/echo", &["inside"]).expect("Failed to spawn");
+        session.expect("inside").expect("Failed to expect");
+
+        let mut session = SyncSession::spawn_on(
+            Handle::current(),
+            "/bin/echo",
+            &["borrowed"],
+            SessionConfig::default(),
+        )
+        .expect("Failed to spawn");
+        session.expect("borrowed").expect("Failed to expect");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[should_panic(expected = "current-thread")]
+    async fn sync_session_inside_current_thread_runtime_panics() {
+        let runtime = Driver::owned().expect("Failed to build runtime");
+        runtime.block_on(async {});
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sync_session_spawn_blocking_without_runtime() {
+        let config = SessionConfig::new("/bin/sh").args(["-c", "read name; echo hi $name"]);
+        let mut session = SyncSession::spawn_blocking(config).expect("Failed to spawn sh");
+        assert!(Handle::try_current().is_err());
+
+        session.send_line("there").expect("Failed to send");
+        session
+            .expect("hi there")
+            .expect("Failed to expect greeting");
+        session.expect_eof().expect("sh did not exit");
+        drop(session);
+    }
 }
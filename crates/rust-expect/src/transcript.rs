@@ -1,14 +1,17 @@
 //! Session transcripts and recording.
 //!
 //! This module provides functionality for recording and playing back
-//! terminal sessions, with support for the asciicast v2 format.
+//! terminal sessions, with support for the asciicast v2 format and the
+//! classic `script(1)` typescript format.
 
 pub mod asciicast;
 pub mod format;
 pub mod player;
 pub mod recorder;
+pub mod script;
 
 pub use asciicast::{AsciicastHeader, read_asciicast, write_asciicast};
-pub use format::{EventType, Transcript, TranscriptEvent, TranscriptMetadata};
+pub use format::{EventType, Transcript, TranscriptEvent, TranscriptMetadata, TranscriptTheme};
 pub use player::{PlaybackOptions, PlaybackSpeed, Player, PlayerState, play_to_stdout};
-pub use recorder::{Recorder, RecorderBuilder};
+pub use recorder::{DEFAULT_FLUSH_INTERVAL, Recorder, RecorderBuilder, StreamingRecorder};
+pub use script::{read_typescript, write_typescript};
@@ -4,7 +4,9 @@ use std::fmt::Write as FmtWrite;
 use std::io::{BufRead, Write};
 use std::time::Duration;
 
-use super::format::{EventType, Transcript, TranscriptEvent, TranscriptMetadata};
+use serde_json::Value;
+
+use super::format::{EventType, Transcript, TranscriptEvent, TranscriptMetadata, TranscriptTheme};
 use crate::error::{ExpectError, Result};
 
 /// Asciicast v2 header.
@@ -28,6 +30,8 @@ pub struct AsciicastHeader {
     pub title: Option<String>,
     /// Environment.
     pub env: std::collections::HashMap<String, String>,
+    /// Terminal color theme.
+    pub theme: Option<TranscriptTheme>,
 }
 
 impl Default for AsciicastHeader {
@@ -42,6 +46,7 @@ impl Default for AsciicastHeader {
             command: None,
             title: None,
             env: std::collections::HashMap::new(),
+            theme: None,
         }
     }
 }
@@ -89,6 +94,14 @@ impl AsciicastHeader {
                 .collect();
             parts.push(format!("\"env\": {{{}}}", env_parts.join(", ")));
         }
+        if let Some(ref theme) = self.theme {
+            parts.push(format!(
+                "\"theme\": {{\"fg\": \"{}\", \"bg\": \"{}\", \"palette\": \"{}\"}}",
+                escape_json(&theme.fg),
+                escape_json(&theme.bg),
+                escape_json(&theme.palette.join(":"))
+            ));
+        }
 
         format!("{{{}}}", parts.join(", "))
     }
@@ -96,55 +109,67 @@ impl AsciicastHeader {
 
 /// Write a transcript in asciicast v2 format.
 pub fn write_asciicast<W: Write>(writer: &mut W, transcript: &Transcript) -> Result<()> {
+    write_header(writer, &transcript.metadata)?;
+
+    for event in &transcript.events {
+        write_event(writer, event)?;
+    }
+
+    Ok(())
+}
+
+/// Write the asciicast header line for `metadata`.
+pub(crate) fn write_header<W: Write>(writer: &mut W, metadata: &TranscriptMetadata) -> Result<()> {
     let header = AsciicastHeader {
-        width: transcript.metadata.width,
-        height: transcript.metadata.height,
-        timestamp: transcript.metadata.timestamp,
-        duration: transcript.metadata.duration.map(|d| d.as_secs_f64()),
-        command: transcript.metadata.command.clone(),
-        title: transcript.metadata.title.clone(),
-        env: transcript.metadata.env.clone(),
+        width: metadata.width,
+        height: metadata.height,
+        timestamp: metadata.timestamp,
+        duration: metadata.duration.map(|d| d.as_secs_f64()),
+        command: metadata.command.clone(),
+        title: metadata.title.clone(),
+        env: metadata.env.clone(),
+        theme: metadata.theme.clone(),
         ..Default::default()
     };
 
-    // Write header
     writeln!(writer, "{}", header.to_json())
-        .map_err(|e| ExpectError::io_context("writing asciicast header", e))?;
-
-    // Write events
-    for event in &transcript.events {
-        let time = event.timestamp.as_secs_f64();
-        let event_type = match event.event_type {
-            EventType::Output => "o",
-            EventType::Input => "i",
-            EventType::Resize => "r",
-            EventType::Marker => "m",
-        };
-        let data = String::from_utf8_lossy(&event.data);
-        writeln!(
-            writer,
-            "[{:.6}, \"{}\", \"{}\"]",
-            time,
-            event_type,
-            escape_json(&data)
-        )
-        .map_err(|e| ExpectError::io_context("writing asciicast event", e))?;
-    }
+        .map_err(|e| ExpectError::io_context("writing asciicast header", e))
+}
 
-    Ok(())
+/// Write a single asciicast event line.
+pub(crate) fn write_event<W: Write>(writer: &mut W, event: &TranscriptEvent) -> Result<()> {
+    let time = event.timestamp.as_secs_f64();
+    let event_type = match event.event_type {
+        EventType::Output => "o",
+        EventType::Input => "i",
+        EventType::Resize => "r",
+        EventType::Marker => "m",
+        EventType::Exit => "x",
+    };
+    let data = String::from_utf8_lossy(&event.data);
+    writeln!(
+        writer,
+        "[{:.6}, \"{}\", \"{}\"]",
+        time,
+        event_type,
+        escape_json(&data)
+    )
+    .map_err(|e| ExpectError::io_context("writing asciicast event", e))
 }
 
 /// Read a transcript from asciicast v2 format.
-pub fn read_asciicast<R: BufRead>(reader: R) -> Result<Transcript> {
-    let mut lines = reader.lines();
+pub fn read_asciicast<R: BufRead>(mut reader: R) -> Result<Transcript> {
+    let mut line = String::new();
 
     // Parse header
-    let header_line = lines
-        .next()
-        .ok_or_else(|| ExpectError::config("Empty asciicast file"))?
+    let read = reader
+        .read_line(&mut line)
         .map_err(|e| ExpectError::io_context("reading asciicast header line", e))?;
+    if read == 0 {
+        return Err(ExpectError::config("Empty asciicast file"));
+    }
 
-    let header = parse_header(&header_line);
+    let header = parse_header(strip_line_ending(&line));
 
     let metadata = TranscriptMetadata {
         width: header.width,
@@ -152,215 +177,141 @@ pub fn read_asciicast<R: BufRead>(reader: R) -> Result<Transcript> {
         command: header.command,
         title: header.title,
         timestamp: header.timestamp,
-        duration: header.duration.map(Duration::from_secs_f64),
+        duration: header
+            .duration
+            .and_then(|d| Duration::try_from_secs_f64(d).ok()),
         env: header.env,
+        theme: header.theme,
     };
 
     let mut transcript = Transcript::new(metadata);
 
     // Parse events
-    for line in lines {
-        let line = line.map_err(|e| ExpectError::io_context("reading asciicast event line", e))?;
-        if line.trim().is_empty() {
+    loop {
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| ExpectError::io_context("reading asciicast event line", e))?;
+        if read == 0 {
+            break;
+        }
+        let event_line = strip_line_ending(&line);
+        if event_line.trim().is_empty() {
             continue;
         }
-        if let Some(event) = parse_event(&line)? {
-            transcript.push(event);
+        match parse_event(event_line) {
+            Ok(Some(event)) => transcript.push(event),
+            Ok(None) => {}
+            // A recording cut off mid-write ends without a newline
+            Err(_) if !line.ends_with('\n') => break,
+            Err(e) => return Err(e),
         }
     }
 
     Ok(transcript)
 }
 
+/// Strip a trailing `\n` or `\r\n` from a line.
+fn strip_line_ending(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+/// Parse the header line, falling back to defaults if it is not a JSON
+/// object.
 fn parse_header(line: &str) -> AsciicastHeader {
-    // Parse numeric fields
-    let mut header = AsciicastHeader {
-        width: parse_json_number(line, "width").unwrap_or(80) as u16,
-        height: parse_json_number(line, "height").unwrap_or(24) as u16,
-        version: parse_json_number(line, "version").unwrap_or(2) as u8,
-        ..Default::default()
+    let Ok(Value::Object(fields)) = serde_json::from_str(line) else {
+        return AsciicastHeader::default();
     };
 
-    if let Some(ts) = parse_json_number(line, "timestamp") {
-        header.timestamp = Some(ts as u64);
-    }
+    let number = |name: &str| fields.get(name).and_then(Value::as_u64);
+    let float = |name: &str| fields.get(name).and_then(Value::as_f64);
+    let string = |name: &str| fields.get(name).and_then(Value::as_str).map(str::to_string);
 
-    if let Some(dur) = parse_json_float(line, "duration") {
-        header.duration = Some(dur);
-    }
+    let mut header = AsciicastHeader {
+        version: number("version")
+            .and_then(|v| u8::try_from(v).ok())
+            .unwrap_or(2),
+        width: number("width")
+            .and_then(|v| u16::try_from(v).ok())
+            .unwrap_or(80),
+        height: number("height")
+            .and_then(|v| u16::try_from(v).ok())
+            .unwrap_or(24),
+        timestamp: number("timestamp"),
+        duration: float("duration"),
+        idle_time_limit: float("idle_time_limit"),
+        command: string("command"),
+        title: string("title"),
+        ..Default::default()
+    };
 
-    if let Some(limit) = parse_json_float(line, "idle_time_limit") {
-        header.idle_time_limit = Some(limit);
+    if let Some(Value::Object(env)) = fields.get("env") {
+        header.env = env
+            .iter()
+            .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+            .collect();
     }
 
-    // Parse string fields
-    header.command = parse_json_string(line, "command");
-    header.title = parse_json_string(line, "title");
-
-    // Parse env object (simplified - handles flat env objects)
-    if let Some(env) = parse_json_object(line, "env") {
-        header.env = env;
+    if let Some(Value::Object(theme)) = fields.get("theme") {
+        let color = |name: &str| {
+            theme
+                .get(name)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        header.theme = Some(TranscriptTheme {
+            fg: color("fg"),
+            bg: color("bg"),
+            palette: theme
+                .get("palette")
+                .and_then(Value::as_str)
+                .map(|p| p.split(':').map(str::to_string).collect())
+                .unwrap_or_default(),
+        });
     }
 
     header
 }
 
-/// Parse a numeric JSON field.
-fn parse_json_number(json: &str, field: &str) -> Option<i64> {
-    let pattern = format!("\"{field}\":");
-    let start = json.find(&pattern)?;
-    let rest = &json[start + pattern.len()..];
-    let rest = rest.trim_start();
-
-    // Find the end of the number
-    let end = rest
-        .find(|c: char| !c.is_ascii_digit() && c != '-')
-        .unwrap_or(rest.len());
-
-    rest[..end].trim().parse().ok()
-}
-
-/// Parse a floating-point JSON field.
-fn parse_json_float(json: &str, field: &str) -> Option<f64> {
-    let pattern = format!("\"{field}\":");
-    let start = json.find(&pattern)?;
-    let rest = &json[start + pattern.len()..];
-    let rest = rest.trim_start();
-
-    // Find the end of the number (including decimal point and exponent)
-    let end = rest
-        .find(|c: char| {
-            !c.is_ascii_digit() && c != '.' && c != '-' && c != 'e' && c != 'E' && c != '+'
-        })
-        .unwrap_or(rest.len());
-
-    rest[..end].trim().parse().ok()
-}
-
-/// Parse a string JSON field.
-fn parse_json_string(json: &str, field: &str) -> Option<String> {
-    let pattern = format!("\"{field}\":");
-    let start = json.find(&pattern)?;
-    let rest = &json[start + pattern.len()..];
-    let rest = rest.trim_start();
-
-    // Must start with a quote
-    if !rest.starts_with('"') {
-        return None;
-    }
-
-    // Find the closing quote (handling escapes)
-    let content = &rest[1..];
-    let mut end = 0;
-    let mut escaped = false;
-
-    for (i, c) in content.char_indices() {
-        if escaped {
-            escaped = false;
-            continue;
-        }
-        if c == '\\' {
-            escaped = true;
-            continue;
-        }
-        if c == '"' {
-            end = i;
-            break;
-        }
-    }
-
-    if end == 0 && !content.is_empty() && !content.starts_with('"') {
-        // No closing quote found, check if string is at end
-        end = content.len();
-    }
-
-    Some(unescape_json(&content[..end]))
-}
-
-/// Parse a JSON object field (simplified, handles flat string-value objects).
-fn parse_json_object(json: &str, field: &str) -> Option<std::collections::HashMap<String, String>> {
-    let pattern = format!("\"{field}\":");
-    let start = json.find(&pattern)?;
-    let rest = &json[start + pattern.len()..];
-    let rest = rest.trim_start();
-
-    // Must start with {
-    if !rest.starts_with('{') {
-        return None;
-    }
-
-    // Find matching closing brace
-    let mut depth = 0;
-    let mut end = 0;
-
-    for (i, c) in rest.char_indices() {
-        match c {
-            '{' => depth += 1,
-            '}' => {
-                depth -= 1;
-                if depth == 0 {
-                    end = i + 1;
-                    break;
-                }
-            }
-            _ => {}
-        }
-    }
-
-    if end == 0 {
-        return None;
-    }
-
-    let obj_str = &rest[1..end - 1]; // Content inside braces
-    let mut result = std::collections::HashMap::new();
-
-    // Parse key-value pairs
-    for pair in obj_str.split(',') {
-        let pair = pair.trim();
-        if let Some(colon) = pair.find(':') {
-            let key = pair[..colon].trim().trim_matches('"');
-            let value = pair[colon + 1..].trim().trim_matches('"');
-            if !key.is_empty() {
-                result.insert(key.to_string(), unescape_json(value));
-            }
-        }
-    }
-
-    Some(result)
-}
-
 fn parse_event(line: &str) -> Result<Option<TranscriptEvent>> {
     let line = line.trim();
-    if !line.starts_with('[') || !line.ends_with(']') {
+    if !line.starts_with('[') {
         return Ok(None);
     }
 
-    let inner = &line[1..line.len() - 1];
-    let parts: Vec<&str> = inner.splitn(3, ',').collect();
-    if parts.len() < 3 {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|e| ExpectError::config(format!("Invalid asciicast event: {e}")))?;
+    let Some([time, event_type, data]) = value.as_array().map(Vec::as_slice) else {
         return Ok(None);
-    }
-
-    let time: f64 = parts[0]
-        .trim()
-        .parse()
-        .map_err(|_| ExpectError::config("Invalid timestamp"))?;
+    };
 
-    let event_type = parts[1].trim().trim_matches('"');
-    let data = parts[2].trim().trim_matches('"');
+    let timestamp = time
+        .as_f64()
+        .and_then(|t| Duration::try_from_secs_f64(t).ok())
+        .ok_or_else(|| ExpectError::config(format!("Invalid timestamp: {time}")))?;
+
+    let event_type = match event_type.as_str() {
+        Some("o") => EventType::Output,
+        Some("i") => EventType::Input,
+        Some("r") => EventType::Resize,
+        Some("m") => EventType::Marker,
+        Some("x") => EventType::Exit,
+        _ => return Ok(None),
+    };
 
-    let event_type = match event_type {
-        "o" => EventType::Output,
-        "i" => EventType::Input,
-        "r" => EventType::Resize,
-        "m" => EventType::Marker,
+    // Exit codes may be written as a bare number
+    let data = match data {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
         _ => return Ok(None),
     };
 
     Ok(Some(TranscriptEvent {
-        timestamp: Duration::from_secs_f64(time),
+        timestamp,
         event_type,
-        data: unescape_json(data).into_bytes(),
+        data: data.into_bytes(),
     }))
 }
 
@@ -382,56 +333,6 @@ fn escape_json(s: &str) -> String {
     result
 }
 
-fn unescape_json(s: &str) -> String {
-    let mut result = String::with_capacity(s.len());
-    let mut chars = s.chars().peekable();
-    while let Some(c) = chars.next() {
-        if c == '\\' {
-            match chars.next() {
-                Some('n') => result.push('\n'),
-                Some('r') => result.push('\r'),
-                Some('t') => result.push('\t'),
-                Some('b') => result.push('\u{0008}'), // backspace
-                Some('f') => result.push('\u{000C}'), // form feed
-                Some('"') => result.push('"'),
-                Some('\\') => result.push('\\'),
-                Some('/') => result.push('/'),
-                Some('u') => {
-                    // Parse \uXXXX unicode escape
-                    let mut hex = String::with_capacity(4);
-                    for _ in 0..4 {
-                        if let Some(&c) = chars.peek() {
-                            if c.is_ascii_hexdigit() {
-                                hex.push(chars.next().unwrap());
-                            } else {
-                                break;
-                            }
-                        }
-                    }
-                    if hex.len() == 4
-                        && let Ok(code) = u32::from_str_radix(&hex, 16)
-                        && let Some(ch) = char::from_u32(code)
-                    {
-                        result.push(ch);
-                        continue;
-                    }
-                    // Invalid escape, keep as-is
-                    result.push_str("\\u");
-                    result.push_str(&hex);
-                }
-                Some(c) => {
-                    result.push('\\');
-                    result.push(c);
-                }
-                None => result.push('\\'),
-            }
-        } else {
-            result.push(c);
-        }
-    }
-    result
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -465,75 +366,73 @@ mod tests {
         assert_eq!(parsed.events.len(), 1);
     }
 
-    #[test]
-    fn parse_json_number_basic() {
-        let json = r#"{"version": 2, "width": 120, "height": 40}"#;
-        assert_eq!(parse_json_number(json, "version"), Some(2));
-        assert_eq!(parse_json_number(json, "width"), Some(120));
-        assert_eq!(parse_json_number(json, "height"), Some(40));
-        assert_eq!(parse_json_number(json, "nonexistent"), None);
-    }
-
-    #[test]
-    fn parse_json_number_negative() {
-        let json = r#"{"offset": -100}"#;
-        assert_eq!(parse_json_number(json, "offset"), Some(-100));
+    /// Parse a single output event whose data is the JSON string body `escaped`.
+    fn event_data(escaped: &str) -> Result<String> {
+        let line = format!(r#"[0.0, "o", "{escaped}"]"#);
+        let event = parse_event(&line)?.unwrap();
+        Ok(String::from_utf8(event.data).unwrap())
     }
 
     #[test]
-    fn parse_json_float_basic() {
-        let json = r#"{"duration": 123.456789, "idle_time_limit": 2.5}"#;
-        assert!((parse_json_float(json, "duration").unwrap() - 123.456_789).abs() < 0.000_001);
-        assert!((parse_json_float(json, "idle_time_limit").unwrap() - 2.5).abs() < 0.000_001);
-        assert_eq!(parse_json_float(json, "nonexistent"), None);
+    fn parse_header_numbers() {
+        let header =
+            parse_header(r#"{"version": 2, "width": 120, "height": 40, "duration": 1.5e1}"#);
+        assert_eq!(header.version, 2);
+        assert_eq!(header.width, 120);
+        assert_eq!(header.height, 40);
+        assert!((header.duration.unwrap() - 15.0).abs() < 0.000_001);
+        assert_eq!(header.idle_time_limit, None);
     }
 
     #[test]
-    fn parse_json_float_scientific() {
-        let json = r#"{"value": 1.5e10}"#;
-        assert!((parse_json_float(json, "value").unwrap() - 1.5e10).abs() < 1.0);
+    fn parse_header_escaped_strings() {
+        let header = parse_header(
+            r#"{"version": 2, "width": 80, "height": 24, "command": "C:\\Users\\test", "title": "say \"hello\""}"#,
+        );
+        assert_eq!(header.command, Some("C:\\Users\\test".to_string()));
+        assert_eq!(header.title, Some("say \"hello\"".to_string()));
     }
 
     #[test]
-    fn parse_json_string_basic() {
-        let json = r#"{"command": "/bin/bash", "title": "My Recording"}"#;
-        assert_eq!(
-            parse_json_string(json, "command"),
-            Some("/bin/bash".to_string())
-        );
-        assert_eq!(
-            parse_json_string(json, "title"),
-            Some("My Recording".to_string())
+    fn parse_header_commas_and_colons_in_values() {
+        let header = parse_header(
+            r#"{"version": 2, "width": 80, "height": 24, "command": "sh -c \"a,b\"", "title": "x: {y}, z", "env": {"PS1": "a,b: c", "TERM": "xterm"}}"#,
         );
-        assert_eq!(parse_json_string(json, "nonexistent"), None);
+        assert_eq!(header.command, Some("sh -c \"a,b\"".to_string()));
+        assert_eq!(header.title, Some("x: {y}, z".to_string()));
+        assert_eq!(header.env.len(), 2);
+        assert_eq!(header.env.get("PS1"), Some(&"a,b: c".to_string()));
+        assert_eq!(header.env.get("TERM"), Some(&"xterm".to_string()));
+        assert_eq!(header.width, 80);
     }
 
     #[test]
-    fn parse_json_string_escaped() {
-        let json = r#"{"path": "C:\\Users\\test", "msg": "say \"hello\""}"#;
-        assert_eq!(
-            parse_json_string(json, "path"),
-            Some("C:\\Users\\test".to_string())
+    fn parse_header_nested_looking_strings() {
+        // Field names inside string values must not be picked up.
+        let header = parse_header(
+            r#"{"title": "\"width\": 999, \"env\": {}", "version": 2, "width": 100, "height": 30}"#,
         );
+        assert_eq!(header.width, 100);
+        assert_eq!(header.height, 30);
         assert_eq!(
-            parse_json_string(json, "msg"),
-            Some("say \"hello\"".to_string())
+            header.title,
+            Some("\"width\": 999, \"env\": {}".to_string())
         );
+        assert!(header.env.is_empty());
     }
 
     #[test]
-    fn parse_json_object_basic() {
-        let json = r#"{"env": {"SHELL": "/bin/bash", "TERM": "xterm-256color"}}"#;
-        let env = parse_json_object(json, "env").unwrap();
-        assert_eq!(env.get("SHELL"), Some(&"/bin/bash".to_string()));
-        assert_eq!(env.get("TERM"), Some(&"xterm-256color".to_string()));
+    fn parse_header_empty_env() {
+        let header = parse_header(r#"{"version": 2, "width": 80, "height": 24, "env": {}}"#);
+        assert!(header.env.is_empty());
     }
 
     #[test]
-    fn parse_json_object_empty() {
-        let json = r#"{"env": {}}"#;
-        let env = parse_json_object(json, "env").unwrap();
-        assert!(env.is_empty());
+    fn parse_header_invalid() {
+        for line in ["not json", "[1, 2]"] {
+            let header = parse_header(line);
+            assert_eq!((header.version, header.width, header.height), (2, 80, 24));
+        }
     }
 
     #[test]
@@ -567,68 +466,75 @@ mod tests {
     }
 
     #[test]
-    fn unescape_json_sequences() {
-        assert_eq!(unescape_json("hello\\nworld"), "hello\nworld");
-        assert_eq!(unescape_json("tab\\there"), "tab\there");
-        assert_eq!(unescape_json("quote\\\"here"), "quote\"here");
-        assert_eq!(unescape_json("back\\\\slash"), "back\\slash");
-        assert_eq!(unescape_json("return\\rhere"), "return\rhere");
+    fn parse_event_data_with_commas() {
+        let event = parse_event(r#"[1.5, "o", "a, b, \"c\", d"]"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.data, b"a, b, \"c\", d");
+        assert_eq!(event.timestamp, Duration::from_millis(1500));
     }
 
     #[test]
-    fn unescape_json_backspace_formfeed() {
-        assert_eq!(unescape_json("back\\bspace"), "back\u{0008}space");
-        assert_eq!(unescape_json("form\\ffeed"), "form\u{000C}feed");
+    fn parse_event_exit_code_number() {
+        let event = parse_event(r#"[2.0, "x", 1]"#).unwrap().unwrap();
+        assert_eq!(event.exit_code(), Some(1));
     }
 
     #[test]
-    fn unescape_json_forward_slash() {
-        // Forward slash can be escaped but doesn't need to be
-        assert_eq!(unescape_json("path\\/to\\/file"), "path/to/file");
-        assert_eq!(unescape_json("path/to/file"), "path/to/file");
+    fn parse_event_skips_unknown() {
+        assert!(parse_event(r#"[1.0, "z", "data"]"#).unwrap().is_none());
+        assert!(parse_event("").unwrap().is_none());
+        assert!(parse_event(r#"[1.0, "o"]"#).unwrap().is_none());
+        assert!(parse_event(r#"[1.0, "o", "unterminated]"#).is_err());
+        assert!(parse_event(r#"["soon", "o", "x"]"#).is_err());
     }
 
     #[test]
-    fn unescape_json_unicode() {
-        // Basic ASCII via unicode escape
-        assert_eq!(unescape_json("\\u0041"), "A");
-        assert_eq!(unescape_json("\\u0048\\u0069"), "Hi");
+    fn parse_event_rejects_unrepresentable_timestamps() {
+        assert!(parse_event(r#"[1e300, "o", "x"]"#).is_err());
+        assert!(parse_event(r#"[-1.0, "o", "x"]"#).is_err());
+    }
 
-        // Control characters
-        assert_eq!(unescape_json("\\u001b"), "\u{001b}"); // ESC
-        assert_eq!(unescape_json("\\u0000"), "\u{0000}"); // NULL
+    #[test]
+    fn read_tolerates_truncated_final_line() {
+        let data = "{\"version\": 2, \"width\": 80, \"height\": 24}\n\
+                    [0.5, \"o\", \"hi\"]\n\
+                    [1.0, \"o\", \"cut of";
+        let transcript = read_asciicast(data.as_bytes()).unwrap();
+        assert_eq!(transcript.events.len(), 1);
 
-        // Non-ASCII unicode
-        assert_eq!(unescape_json("\\u00e9"), "é");
-        assert_eq!(unescape_json("\\u4e2d\\u6587"), "中文");
+        let data = "{\"version\": 2}\n[1.0, \"o\", \"cut of\n[2.0, \"o\", \"later\"]\n";
+        assert!(read_asciicast(data.as_bytes()).is_err());
 
-        // Mixed content
-        assert_eq!(unescape_json("hello\\u0020world"), "hello world");
-        assert_eq!(unescape_json("\\u0041\\u0042\\u0043"), "ABC");
+        // A complete final line is not truncated, so a bad one is an error
+        let data = "{\"version\": 2}\n[0.5, \"o\", \"hi\"]\n[1.0, \"o\", \"cut of\n";
+        assert!(read_asciicast(data.as_bytes()).is_err());
+        let data = "{\"version\": 2}\r\n[0.5, \"o\", \"hi\"]\r\n";
+        assert_eq!(read_asciicast(data.as_bytes()).unwrap().events.len(), 1);
     }
 
     #[test]
-    fn unescape_json_unicode_invalid() {
-        // Invalid: not enough hex digits
-        assert_eq!(unescape_json("\\u00"), "\\u00");
-        assert_eq!(unescape_json("\\u0"), "\\u0");
-        assert_eq!(unescape_json("\\u"), "\\u");
-
-        // Invalid: non-hex characters
-        assert_eq!(unescape_json("\\u00GH"), "\\u00GH");
+    fn event_data_escape_sequences() {
+        assert_eq!(event_data("hello\\nworld").unwrap(), "hello\nworld");
+        assert_eq!(event_data("tab\\there").unwrap(), "tab\there");
+        assert_eq!(event_data("quote\\\"here").unwrap(), "quote\"here");
+        assert_eq!(event_data("back\\\\slash").unwrap(), "back\\slash");
+        assert_eq!(event_data("return\\rhere").unwrap(), "return\rhere");
+        assert_eq!(event_data("back\\bspace").unwrap(), "back\u{0008}space");
+        assert_eq!(event_data("form\\ffeed").unwrap(), "form\u{000C}feed");
+        assert_eq!(event_data("path\\/to\\/file").unwrap(), "path/to/file");
     }
 
     #[test]
-    fn unescape_json_mixed_escapes() {
-        // Combine various escape types
-        assert_eq!(
-            unescape_json("line1\\nline2\\ttab\\u0021"),
-            "line1\nline2\ttab!"
-        );
-        assert_eq!(
-            unescape_json("\\\"quoted\\\" and \\u003Ctag\\u003E"),
-            "\"quoted\" and <tag>"
-        );
+    fn event_data_unicode_escapes() {
+        assert_eq!(event_data("\\u0048\\u0069").unwrap(), "Hi");
+        assert_eq!(event_data("\\u001b[0m").unwrap(), "\u{001b}[0m");
+        assert_eq!(event_data("\\u4e2d\\u6587").unwrap(), "中文");
+        // Surrogate pair
+        assert_eq!(event_data("\\ud83d\\ude00").unwrap(), "\u{1F600}");
+        // Invalid escapes are rejected rather than passed through
+        assert!(event_data("\\u00").is_err());
+        assert!(event_data("\\u00GH").is_err());
     }
 
     #[test]
@@ -669,4 +575,53 @@ mod tests {
         assert_eq!(parsed.metadata.timestamp, Some(1_704_067_200));
         assert_eq!(parsed.events.len(), 3);
     }
+
+    #[test]
+    fn read_theme_and_exit_event() {
+        let file = concat!(
+            r##"{"version": 2, "width": 80, "height": 24, "theme": {"fg": "#d0d0d0", "bg": "#212121", "palette": "#151515:#ac4142:#7e8e50:#e5b567:#6c99bb:#9f4e85:#7dd6cf:#d0d0d0"}}"##,
+            "\n",
+            r#"[0.5, "o", "bye\r\n"]"#,
+            "\n",
+            r#"[1.25, "x", "3"]"#,
+            "\n",
+        );
+
+        let transcript = read_asciicast(file.as_bytes()).unwrap();
+        let theme = transcript.metadata.theme.as_ref().unwrap();
+        assert_eq!(theme.fg, "#d0d0d0");
+        assert_eq!(theme.bg, "#212121");
+        assert_eq!(theme.palette.len(), 8);
+        assert_eq!(theme.palette[1], "#ac4142");
+
+        assert_eq!(transcript.events.len(), 2);
+        let exit = &transcript.events[1];
+        assert_eq!(exit.event_type, EventType::Exit);
+        assert_eq!(exit.exit_code(), Some(3));
+        assert_eq!(exit.timestamp, Duration::from_millis(1250));
+    }
+
+    #[test]
+    fn roundtrip_theme_and_exit_event() {
+        let mut metadata = TranscriptMetadata::new(80, 24);
+        metadata.theme = Some(TranscriptTheme {
+            fg: "#ffffff".to_string(),
+            bg: "#000000".to_string(),
+            palette: vec!["#000000".to_string(); 16],
+        });
+
+        let mut transcript = Transcript::new(metadata.clone());
+        transcript.push(TranscriptEvent::output(Duration::from_millis(100), b"done"));
+        transcript.push(TranscriptEvent::exit(Duration::from_millis(200), 0));
+
+        let mut buf = Vec::new();
+        write_asciicast(&mut buf, &transcript).unwrap();
+        let text = String::from_utf8(buf.clone()).unwrap();
+        assert!(text.contains(r#""x", "0""#));
+
+        let parsed = read_asciicast(buf.as_slice()).unwrap();
+        assert_eq!(parsed.metadata.theme, metadata.theme);
+        assert_eq!(parsed.events[1].exit_code(), Some(0));
+        assert_eq!(parsed.events[0].exit_code(), None);
+    }
 }
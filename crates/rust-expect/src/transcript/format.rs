@@ -24,6 +24,8 @@ pub enum EventType {
     Resize,
     /// Marker/annotation.
     Marker,
+    /// Process exit; the data holds the exit code.
+    Exit,
 }
 
 impl TranscriptEvent {
@@ -66,6 +68,36 @@ impl TranscriptEvent {
             data: label.as_bytes().to_vec(),
         }
     }
+
+    /// Create an exit event with the process exit code.
+    #[must_use]
+    pub fn exit(timestamp: Duration, code: i32) -> Self {
+        Self {
+            timestamp,
+            event_type: EventType::Exit,
+            data: code.to_string().into_bytes(),
+        }
+    }
+
+    /// Get the exit code of an exit event.
+    #[must_use]
+    pub fn exit_code(&self) -> Option<i32> {
+        if self.event_type != EventType::Exit {
+            return None;
+        }
+        std::str::from_utf8(&self.data).ok()?.trim().parse().ok()
+    }
+}
+
+/// Terminal color theme recorded with a transcript.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranscriptTheme {
+    /// Default foreground color (e.g., `#d0d0d0`).
+    pub fg: String,
+    /// Default background color.
+    pub bg: String,
+    /// Palette colors, 8 or 16 entries.
+    pub palette: Vec<String>,
 }
 
 /// Transcript metadata.
@@ -85,6 +117,8 @@ pub struct TranscriptMetadata {
     pub timestamp: Option<u64>,
     /// Total duration.
     pub duration: Option<Duration>,
+    /// Terminal color theme.
+    pub theme: Option<TranscriptTheme>,
 }
 
 impl TranscriptMetadata {
@@ -120,6 +120,25 @@ impl<'a> Player<'a> {
         self
     }
 
+    /// Set a playback speed multiplier, like `asciinema play --speed`.
+    ///
+    /// `2.0` plays twice as fast, `0.5` at half speed. The multiplier
+    /// should be positive.
+    #[must_use]
+    pub const fn with_speed(mut self, multiplier: f64) -> Self {
+        self.options.speed = PlaybackSpeed::Speed(multiplier);
+        self
+    }
+
+    /// Cap idle gaps between events, like `asciinema play --idle-time-limit`.
+    ///
+    /// The limit applies to recorded time, before the speed multiplier.
+    #[must_use]
+    pub const fn with_idle_limit(mut self, limit: Duration) -> Self {
+        self.options.max_idle = limit;
+        self
+    }
+
     /// Get current state.
     #[must_use]
     pub const fn state(&self) -> PlayerState {
@@ -198,30 +217,71 @@ impl<'a> Player<'a> {
         }
 
         let next_time = self.transcript.events[self.index].timestamp;
-        let delay = next_time.saturating_sub(self.last_event_time);
+        self.playback_delay(next_time.saturating_sub(self.last_event_time))
+    }
 
-        // Apply speed
-        let delay = match self.options.speed {
-            PlaybackSpeed::Instant => Duration::ZERO,
-            PlaybackSpeed::Realtime => delay,
-            PlaybackSpeed::Speed(mult) => Duration::from_secs_f64(delay.as_secs_f64() / mult),
-        };
+    /// Total wall-clock time to play the remaining events.
+    ///
+    /// Accounts for the idle limit and speed, so it matches how long
+    /// [`play_to`](Self::play_to) will sleep.
+    #[must_use]
+    pub fn playback_duration(&self) -> Duration {
+        let mut last = self.last_event_time;
+        self.transcript.events[self.index..]
+            .iter()
+            .map(|event| {
+                let gap = event.timestamp.saturating_sub(last);
+                last = event.timestamp;
+                self.playback_delay(gap)
+            })
+            .sum()
+    }
 
-        // Apply max idle
-        delay.min(self.options.max_idle)
+    /// Convert a recorded gap into a playback delay.
+    ///
+    /// The idle limit clamps the recorded gap first, then the speed scales
+    /// it, matching asciinema.
+    fn playback_delay(&self, gap: Duration) -> Duration {
+        let gap = gap.min(self.options.max_idle);
+        match self.options.speed {
+            PlaybackSpeed::Instant => Duration::ZERO,
+            PlaybackSpeed::Realtime => gap,
+            PlaybackSpeed::Speed(mult) => {
+                Duration::try_from_secs_f64(gap.as_secs_f64() / mult).unwrap_or(Duration::ZERO)
+            }
+        }
     }
 
     /// Play to a writer (blocking).
+    ///
+    /// Each event is written after sleeping for the gap since the previous
+    /// one. Marker labels are not shown; use
+    /// [`play_to_with_markers`](Self::play_to_with_markers) for that.
     pub fn play_to<W: Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.play_to_with_markers(writer, &mut std::io::sink())
+    }
+
+    /// Play to a writer (blocking), writing marker labels to `markers`.
+    ///
+    /// Each marker is written to `markers` as a `[marker] <label>` line
+    /// when it is reached. Pass a separate sink, such as stderr, so labels
+    /// do not interleave with the replayed terminal output.
+    pub fn play_to_with_markers<W: Write, M: Write>(
+        &mut self,
+        writer: &mut W,
+        markers: &mut M,
+    ) -> std::io::Result<()> {
         self.play();
 
-        while let Some(event) = self.next_event() {
+        loop {
+            let delay = self.delay_to_next();
+            let Some(event) = self.next_event() else {
+                break;
+            };
             // Clone what we need from event to release the borrow
             let event_type = event.event_type;
             let event_data = event.data.clone();
 
-            // Now we can use self again
-            let delay = self.delay_to_next();
             if delay > Duration::ZERO {
                 std::thread::sleep(delay);
             }
@@ -237,10 +297,14 @@ impl<'a> Player<'a> {
                     writer.write_all(&event_data)?;
                     writer.flush()?;
                 }
-                EventType::Marker if self.options.pause_at_markers => {
-                    self.pause();
-                    // User would need to call play() to resume
-                    break;
+                EventType::Marker => {
+                    writeln!(markers, "[marker] {}", String::from_utf8_lossy(&event_data))?;
+                    markers.flush()?;
+                    if self.options.pause_at_markers {
+                        self.pause();
+                        // User would need to call play() to resume
+                        break;
+                    }
                 }
                 _ => {}
             }
@@ -299,4 +363,59 @@ mod tests {
 
         assert_eq!(player.delay_to_next(), Duration::ZERO);
     }
+
+    fn transcript_with_idle_gap() -> Transcript {
+        let mut transcript = Transcript::new(TranscriptMetadata::new(80, 24));
+        transcript.push(TranscriptEvent::output(Duration::ZERO, b"start "));
+        transcript.push(TranscriptEvent::output(
+            Duration::from_millis(200),
+            b"busy ",
+        ));
+        transcript.push(TranscriptEvent::output(Duration::from_secs(30), b"done"));
+        transcript
+    }
+
+    #[test]
+    fn player_idle_limit_clamps_gaps() {
+        let transcript = transcript_with_idle_gap();
+        let player = Player::new(&transcript).with_idle_limit(Duration::from_secs(1));
+
+        // 200ms, then the 29.8s gap clamped to 1s.
+        assert_eq!(player.playback_duration(), Duration::from_millis(1200));
+    }
+
+    #[test]
+    fn player_speed_scales_after_idle_limit() {
+        let transcript = transcript_with_idle_gap();
+        let player = Player::new(&transcript)
+            .with_speed(2.0)
+            .with_idle_limit(Duration::from_secs(1));
+
+        assert_eq!(player.playback_duration(), Duration::from_millis(600));
+
+        let unlimited = Player::new(&transcript)
+            .with_speed(2.0)
+            .with_idle_limit(Duration::MAX);
+        assert_eq!(unlimited.playback_duration(), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn player_play_to_sleeps_before_each_event() {
+        let transcript = transcript_with_idle_gap();
+        let mut player = Player::new(&transcript)
+            .with_speed(4.0)
+            .with_idle_limit(Duration::from_millis(200));
+        let expected = player.playback_duration();
+        assert_eq!(expected, Duration::from_millis(100));
+
+        let mut out = Vec::new();
+        let started = Instant::now();
+        player.play_to(&mut out).unwrap();
+        let elapsed = started.elapsed();
+
+        assert_eq!(out, b"start busy done");
+        assert!(elapsed >= expected);
+        assert!(elapsed < Duration::from_secs(2));
+        assert_eq!(player.state(), PlayerState::Finished);
+    }
 }
@@ -1,9 +1,20 @@
 //! Session recording.
+//!
+//! [`Recorder`] keeps every event in memory. For long sessions,
+//! [`Recorder::to_writer`] turns it into a [`StreamingRecorder`] that writes
+//! each event to an asciicast stream as it arrives.
 
+use std::fmt;
+use std::io::{BufWriter, Write};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use super::asciicast::{write_event, write_header};
 use super::format::{Transcript, TranscriptEvent, TranscriptMetadata};
+use crate::error::{ExpectError, Result};
+
+/// Default interval between flushes of a [`StreamingRecorder`].
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
 
 /// A session recorder.
 #[derive(Debug)]
@@ -101,6 +112,14 @@ impl Recorder {
         self.push_event(TranscriptEvent::marker(self.elapsed(), label));
     }
 
+    /// Mark the current point in the recording with `label`, for example
+    /// to split a long session into chapters.
+    ///
+    /// Same as [`add_marker`](Self::add_marker).
+    pub fn mark(&self, label: &str) {
+        self.add_marker(label);
+    }
+
     /// Check if we should still record.
     fn should_record(&self) -> bool {
         if !self.recording {
@@ -150,6 +169,235 @@ impl Recorder {
     pub fn event_count(&self) -> usize {
         self.transcript.lock().map(|t| t.events.len()).unwrap_or(0)
     }
+
+    /// Switch to streaming events to `writer` in asciicast v2 format.
+    ///
+    /// The header is written from this recorder's metadata, followed by any
+    /// events already recorded. Later events are written as they arrive
+    /// instead of being kept in memory, so memory use stays flat for long
+    /// sessions. Timestamps stay relative to this recorder's start time and
+    /// the duration and event limits carry over.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing the header or existing events fails.
+    pub fn to_writer<W: Write>(self, writer: W) -> Result<StreamingRecorder<W>> {
+        let start = self.start;
+        let recording = self.recording;
+        let max_duration = self.max_duration;
+        let max_events = self.max_events;
+        let transcript = self.into_transcript();
+
+        let mut writer = BufWriter::new(writer);
+        write_header(&mut writer, &transcript.metadata)?;
+        for event in &transcript.events {
+            write_event(&mut writer, event)?;
+        }
+
+        Ok(StreamingRecorder {
+            start,
+            state: Mutex::new(StreamState {
+                writer,
+                events: transcript.events.len(),
+                last_flush: Instant::now(),
+                error: None,
+            }),
+            recording,
+            max_duration,
+            max_events,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+        })
+    }
+}
+
+/// A recorder that writes events to an asciicast stream as they arrive.
+///
+/// Created with [`Recorder::to_writer`]. Output is buffered and flushed at
+/// most every [`flush_interval`](Self::with_flush_interval). Write errors
+/// stop recording and are reported by [`flush`](Self::flush) and
+/// [`finish`](Self::finish).
+pub struct StreamingRecorder<W: Write> {
+    /// Start time.
+    start: Instant,
+    /// Writer and bookkeeping.
+    state: Mutex<StreamState<W>>,
+    /// Whether recording is active.
+    recording: bool,
+    /// Maximum recording duration.
+    max_duration: Option<Duration>,
+    /// Maximum events to record.
+    max_events: Option<usize>,
+    /// Maximum time between flushes.
+    flush_interval: Duration,
+}
+
+/// Mutable state of a [`StreamingRecorder`].
+struct StreamState<W: Write> {
+    writer: BufWriter<W>,
+    events: usize,
+    last_flush: Instant,
+    error: Option<ExpectError>,
+}
+
+impl<W: Write> fmt::Debug for StreamingRecorder<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamingRecorder")
+            .field("events", &self.event_count())
+            .field("recording", &self.recording)
+            .field("max_duration", &self.max_duration)
+            .field("max_events", &self.max_events)
+            .field("flush_interval", &self.flush_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: Write> StreamingRecorder<W> {
+    /// Set the maximum time between flushes.
+    ///
+    /// A zero interval flushes after every event.
+    #[must_use]
+    pub const fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Get elapsed time since start.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Check if recording is active.
+    #[must_use]
+    pub const fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Stop recording and flush buffered events.
+    pub fn stop(&mut self) {
+        self.recording = false;
+        let _ = self.flush();
+    }
+
+    /// Record an output event.
+    pub fn record_output(&self, data: &[u8]) {
+        if !self.should_record() {
+            return;
+        }
+        self.write(&TranscriptEvent::output(self.elapsed(), data));
+    }
+
+    /// Record an input event.
+    pub fn record_input(&self, data: &[u8]) {
+        if !self.should_record() {
+            return;
+        }
+        self.write(&TranscriptEvent::input(self.elapsed(), data));
+    }
+
+    /// Record a resize event.
+    pub fn record_resize(&self, cols: u16, rows: u16) {
+        if !self.should_record() {
+            return;
+        }
+        self.write(&TranscriptEvent::resize(self.elapsed(), cols, rows));
+    }
+
+    /// Add a marker.
+    pub fn add_marker(&self, label: &str) {
+        if !self.should_record() {
+            return;
+        }
+        self.write(&TranscriptEvent::marker(self.elapsed(), label));
+    }
+
+    /// Get the number of events written.
+    #[must_use]
+    pub fn event_count(&self) -> usize {
+        self.state.lock().map(|s| s.events).unwrap_or(0)
+    }
+
+    /// Flush buffered events to the writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an earlier write failed or flushing fails.
+    pub fn flush(&self) -> Result<()> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| ExpectError::config("Recorder state poisoned"))?;
+        if let Some(e) = state.error.take() {
+            return Err(e);
+        }
+        state.last_flush = Instant::now();
+        state
+            .writer
+            .flush()
+            .map_err(|e| ExpectError::io_context("flushing asciicast stream", e))
+    }
+
+    /// Flush remaining events and return the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an earlier write failed or flushing fails.
+    pub fn finish(self) -> Result<W> {
+        self.flush()?;
+        let state = self
+            .state
+            .into_inner()
+            .map_err(|_| ExpectError::config("Recorder state poisoned"))?;
+        state
+            .writer
+            .into_inner()
+            .map_err(|e| ExpectError::io_context("flushing asciicast stream", e.into_error()))
+    }
+
+    /// Check if we should still record.
+    fn should_record(&self) -> bool {
+        if !self.recording {
+            return false;
+        }
+
+        if let Some(max_dur) = self.max_duration
+            && self.elapsed() > max_dur
+        {
+            return false;
+        }
+
+        if let Some(max_events) = self.max_events
+            && self.event_count() >= max_events
+        {
+            return false;
+        }
+
+        true
+    }
+
+    /// Write an event, flushing if the interval has passed.
+    fn write(&self, event: &TranscriptEvent) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if state.error.is_some() {
+            return;
+        }
+
+        let mut result = write_event(&mut state.writer, event);
+        if result.is_ok() && state.last_flush.elapsed() >= self.flush_interval {
+            state.last_flush = Instant::now();
+            result = state
+                .writer
+                .flush()
+                .map_err(|e| ExpectError::io_context("flushing asciicast stream", e));
+        }
+
+        match result {
+            Ok(()) => state.events += 1,
+            Err(e) => state.error = Some(e),
+        }
+    }
 }
 
 /// Builder for creating recorders.
@@ -254,6 +502,75 @@ mod tests {
         assert_eq!(recorder.event_count(), 1);
     }
 
+    /// A writer whose contents can be inspected while it is in use.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn streaming_recorder_writes_incrementally() {
+        let sink = SharedBuf::default();
+        let recorder = RecorderBuilder::new().size(100, 30).title("stream").build();
+        recorder.record_output(b"buffered before streaming\r\n");
+        let recorder = recorder
+            .to_writer(sink.clone())
+            .unwrap()
+            .with_flush_interval(Duration::from_secs(3600));
+
+        for i in 0..20_000 {
+            recorder.record_output(format!("line {i}\r\n").as_bytes());
+            if i % 1000 == 0 {
+                recorder.record_input(b"x");
+            }
+        }
+
+        // Only the writer's internal buffer is held back, not the events.
+        let written = sink.0.lock().unwrap().len();
+        let sink = recorder.finish().unwrap();
+        let total = sink.0.lock().unwrap().len();
+        assert!(total - written <= 8 * 1024);
+
+        let data = sink.0.lock().unwrap().clone();
+        let transcript = crate::transcript::read_asciicast(data.as_slice()).unwrap();
+        assert_eq!(transcript.metadata.width, 100);
+        assert_eq!(transcript.metadata.title.as_deref(), Some("stream"));
+        assert_eq!(transcript.events.len(), 1 + 20_000 + 20);
+        assert!(
+            transcript
+                .events
+                .windows(2)
+                .all(|w| w[0].timestamp <= w[1].timestamp)
+        );
+        assert_eq!(transcript.events[1].data, b"line 0\r\n");
+    }
+
+    #[test]
+    fn streaming_recorder_respects_limits() {
+        let recorder = RecorderBuilder::new()
+            .max_events(3)
+            .build()
+            .to_writer(Vec::new())
+            .unwrap()
+            .with_flush_interval(Duration::ZERO);
+        for _ in 0..10 {
+            recorder.record_output(b"x");
+        }
+        assert_eq!(recorder.event_count(), 3);
+
+        let data = recorder.finish().unwrap();
+        assert_eq!(String::from_utf8(data).unwrap().lines().count(), 4);
+    }
+
     #[test]
     fn recorder_builder() {
         let recorder = RecorderBuilder::new()
@@ -0,0 +1,290 @@
+//! Classic `script(1)` typescript format support.
+//!
+//! A typescript is a pair of files: the raw output stream, and a timing
+//! file with one `delay bytecount` line per chunk of output, where `delay`
+//! is the number of seconds since the previous chunk. This is the format
+//! written by `script -t` and read by `scriptreplay`:
+//!
+//! ```text
+//! scriptreplay --timing session.timing session.typescript
+//! ```
+//!
+//! Only output events are stored. Input, resize and marker events have no
+//! representation in the classic format and are skipped. An exit event is
+//! written as the `COMMAND_EXIT_CODE` in the trailing "Script done" line.
+
+use std::io::{BufRead, Read, Write};
+use std::time::Duration;
+
+use super::format::{EventType, Transcript, TranscriptEvent, TranscriptMetadata};
+use crate::error::{ExpectError, Result};
+
+/// Write a transcript as a typescript data file and timing file.
+pub fn write_typescript<D: Write, T: Write>(
+    data: &mut D,
+    timing: &mut T,
+    transcript: &Transcript,
+) -> Result<()> {
+    let metadata = &transcript.metadata;
+
+    let mut attrs = Vec::new();
+    if let Some(ref cmd) = metadata.command {
+        attrs.push(format!("COMMAND=\"{cmd}\""));
+    }
+    attrs.push(format!("COLUMNS=\"{}\"", metadata.width));
+    attrs.push(format!("LINES=\"{}\"", metadata.height));
+    writeln!(
+        data,
+        "Script started{} [{}]",
+        date_suffix(metadata.timestamp, Duration::ZERO),
+        attrs.join(" ")
+    )
+    .map_err(|e| ExpectError::io_context("writing typescript header", e))?;
+
+    let mut last = Duration::ZERO;
+    let mut exit_code = None;
+    for event in &transcript.events {
+        match event.event_type {
+            EventType::Output if !event.data.is_empty() => {
+                let delay = event.timestamp.saturating_sub(last);
+                last = event.timestamp;
+                data.write_all(&event.data)
+                    .map_err(|e| ExpectError::io_context("writing typescript data", e))?;
+                writeln!(timing, "{:.6} {}", delay.as_secs_f64(), event.data.len())
+                    .map_err(|e| ExpectError::io_context("writing typescript timing", e))?;
+            }
+            EventType::Exit => exit_code = event.exit_code(),
+            _ => {}
+        }
+    }
+
+    if let Some(code) = exit_code {
+        writeln!(
+            data,
+            "\nScript done{} [COMMAND_EXIT_CODE=\"{code}\"]",
+            date_suffix(metadata.timestamp, transcript.duration())
+        )
+        .map_err(|e| ExpectError::io_context("writing typescript footer", e))?;
+    }
+
+    Ok(())
+}
+
+/// Read a transcript from a typescript data file and timing file.
+///
+/// The header line supplies the command and terminal size when present.
+/// A trailing "Script done" line with a `COMMAND_EXIT_CODE` becomes an
+/// exit event.
+pub fn read_typescript<D: Read, T: BufRead>(mut data: D, timing: T) -> Result<Transcript> {
+    let mut bytes = Vec::new();
+    data.read_to_end(&mut bytes)
+        .map_err(|e| ExpectError::io_context("reading typescript data", e))?;
+
+    // The first line is the "Script started" header
+    let body_start = bytes
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |p| p + 1);
+    let header = String::from_utf8_lossy(&bytes[..body_start]);
+
+    let mut metadata = TranscriptMetadata::new(80, 24);
+    metadata.command = header_attr(&header, "COMMAND");
+    if let Some(cols) = header_attr(&header, "COLUMNS").and_then(|v| v.parse().ok()) {
+        metadata.width = cols;
+    }
+    if let Some(lines) = header_attr(&header, "LINES").and_then(|v| v.parse().ok()) {
+        metadata.height = lines;
+    }
+
+    let mut transcript = Transcript::new(metadata);
+    let mut offset = body_start;
+    let mut time = Duration::ZERO;
+
+    for line in timing.lines() {
+        let line = line.map_err(|e| ExpectError::io_context("reading typescript timing", e))?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (delay, count) = line
+            .split_once(' ')
+            .ok_or_else(|| ExpectError::config(format!("Invalid timing line: {line}")))?;
+        let delay = delay
+            .parse::<f64>()
+            .ok()
+            .and_then(|d| Duration::try_from_secs_f64(d).ok())
+            .ok_or_else(|| ExpectError::config(format!("Invalid timing delay: {line}")))?;
+        let count: usize = count
+            .trim()
+            .parse()
+            .map_err(|_| ExpectError::config(format!("Invalid timing byte count: {line}")))?;
+
+        let end = offset
+            .checked_add(count)
+            .filter(|&end| end <= bytes.len())
+            .ok_or_else(|| ExpectError::config("Timing file extends past typescript data"))?;
+
+        time = time
+            .checked_add(delay)
+            .ok_or_else(|| ExpectError::config(format!("Timing overflows at line: {line}")))?;
+        transcript.push(TranscriptEvent::output(time, &bytes[offset..end]));
+        offset = end;
+    }
+
+    let trailer = String::from_utf8_lossy(&bytes[offset..]);
+    if let Some(code) = header_attr(&trailer, "COMMAND_EXIT_CODE").and_then(|v| v.parse().ok()) {
+        transcript.push(TranscriptEvent::exit(time, code));
+    }
+
+    Ok(transcript)
+}
+
+/// Extract `NAME="value"` from a `script` header or footer line.
+fn header_attr(line: &str, name: &str) -> Option<String> {
+    let pattern = format!("{name}=\"");
+    let mut search = line;
+    loop {
+        let start = search.find(&pattern)?;
+        // Make sure we matched the whole name, not a suffix of another one
+        let preceded_ok = start == 0 || matches!(search.as_bytes()[start - 1], b' ' | b'[');
+        let rest = &search[start + pattern.len()..];
+        if preceded_ok {
+            // The value ends at the quote that closes the attribute
+            let end = rest
+                .match_indices('"')
+                .map(|(i, _)| i)
+                .find(|&i| matches!(rest.as_bytes().get(i + 1), Some(b' ' | b']')))?;
+            return Some(rest[..end].to_string());
+        }
+        search = rest;
+    }
+}
+
+/// Format ` on <date>` for a Unix timestamp plus an offset, if known.
+fn date_suffix(timestamp: Option<u64>, offset: Duration) -> String {
+    timestamp.map_or_else(String::new, |ts| {
+        format!(" on {}", format_utc(ts.saturating_add(offset.as_secs())))
+    })
+}
+
+/// Format a Unix timestamp as `YYYY-MM-DD HH:MM:SS+00:00`.
+fn format_utc(timestamp: u64) -> String {
+    let days = timestamp / 86_400;
+    let secs = timestamp % 86_400;
+
+    // Civil-from-days, from Howard Hinnant's date algorithms
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}+00:00",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Transcript {
+        let mut metadata = TranscriptMetadata::new(100, 30);
+        metadata.command = Some("bash -c \"echo hi\"".to_string());
+        metadata.timestamp = Some(1_704_067_200);
+
+        let mut transcript = Transcript::new(metadata);
+        transcript.push(TranscriptEvent::output(Duration::from_millis(10), b"$ "));
+        transcript.push(TranscriptEvent::input(Duration::from_millis(20), b"ls\n"));
+        transcript.push(TranscriptEvent::output(
+            Duration::from_millis(250),
+            b"file1\r\nfile2\r\n",
+        ));
+        transcript.push(TranscriptEvent::exit(Duration::from_millis(300), 2));
+        transcript
+    }
+
+    #[test]
+    fn timing_file_format() {
+        let mut data = Vec::new();
+        let mut timing = Vec::new();
+        write_typescript(&mut data, &mut timing, &sample()).unwrap();
+
+        let timing = String::from_utf8(timing).unwrap();
+        assert_eq!(timing, "0.010000 2\n0.240000 14\n");
+
+        let data = String::from_utf8(data).unwrap();
+        assert!(data.starts_with(
+            "Script started on 2024-01-01 00:00:00+00:00 [COMMAND=\"bash -c \"echo hi\"\" COLUMNS=\"100\" LINES=\"30\"]\n$ file1"
+        ));
+        assert!(
+            data.ends_with(
+                "\nScript done on 2024-01-01 00:00:00+00:00 [COMMAND_EXIT_CODE=\"2\"]\n"
+            )
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let original = sample();
+        let mut data = Vec::new();
+        let mut timing = Vec::new();
+        write_typescript(&mut data, &mut timing, &original).unwrap();
+
+        let parsed = read_typescript(data.as_slice(), timing.as_slice()).unwrap();
+        assert_eq!(parsed.metadata.width, 100);
+        assert_eq!(parsed.metadata.height, 30);
+        assert_eq!(
+            parsed.metadata.command.as_deref(),
+            Some("bash -c \"echo hi\"")
+        );
+        assert_eq!(parsed.output_text(), original.output_text());
+
+        assert_eq!(parsed.events.len(), 3);
+        assert_eq!(parsed.events[0].timestamp, Duration::from_millis(10));
+        assert_eq!(parsed.events[1].timestamp, Duration::from_millis(250));
+        assert_eq!(parsed.events[2].exit_code(), Some(2));
+    }
+
+    #[test]
+    fn read_script_output() {
+        // As written by util-linux `script -t`
+        let data = "Script started on 2026-01-01 10:00:00+00:00 [COMMAND=\"echo hi\" <not executed on terminal>]\nhi\r\nthere\r\n\nScript done on 2026-01-01 10:00:00+00:00 [COMMAND_EXIT_CODE=\"0\"]\n";
+        let timing = "0.010182 4\n0.192267 7\n";
+
+        let transcript = read_typescript(data.as_bytes(), timing.as_bytes()).unwrap();
+        assert_eq!(transcript.metadata.command.as_deref(), Some("echo hi"));
+        assert_eq!(transcript.metadata.width, 80);
+        assert_eq!(transcript.output_text(), "hi\r\nthere\r\n");
+        assert_eq!(transcript.events.last().unwrap().exit_code(), Some(0));
+    }
+
+    #[test]
+    fn read_rejects_bad_timing() {
+        let data = "Script started\nabc";
+        assert!(read_typescript(data.as_bytes(), &b"0.1 10\n"[..]).is_err());
+        assert!(read_typescript(data.as_bytes(), &b"soon 1\n"[..]).is_err());
+        assert!(read_typescript(data.as_bytes(), &b"0.1\n"[..]).is_err());
+        assert!(read_typescript(data.as_bytes(), &b"1e19 1\n1e19 1\n"[..]).is_err());
+    }
+
+    #[test]
+    fn date_suffix_saturates() {
+        assert!(date_suffix(Some(u64::MAX), Duration::MAX).starts_with(" on "));
+    }
+
+    #[test]
+    fn format_utc_dates() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00+00:00");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00+00:00");
+        assert_eq!(format_utc(1_704_067_199), "2023-12-31 23:59:59+00:00");
+    }
+}
@@ -3,10 +3,17 @@
 //! This module defines core types used throughout the library including
 //! patterns, matches, and session state.
 
+use std::collections::HashMap;
 use std::fmt;
 use std::time::Duration;
 
 /// A match result from an expect operation.
+///
+/// A successful expect consumes everything up to the end of the match from
+/// the session buffer: [`before`](Self::before) holds the text preceding the
+/// match and [`matched`](Self::matched) the match itself, like pexpect's
+/// `before` and `after`. Output following the match stays buffered for the
+/// next expect, and [`after`](Self::after) is a snapshot of it.
 #[derive(Debug, Clone)]
 pub struct Match {
     /// The index of the pattern that matched (for multi-pattern expects).
@@ -18,10 +25,15 @@ pub struct Match {
     /// Capture groups from regex patterns.
     pub captures: Vec<String>,
 
-    /// Text before the match.
+    /// Numbered and named capture groups from regex patterns.
+    pub groups: CaptureGroups,
+
+    /// Text before the match, consumed from the buffer.
+    ///
+    /// For an EOF match this is all output left in the buffer.
     pub before: String,
 
-    /// Text after the match (remaining in buffer).
+    /// Text after the match, which remains in the buffer.
     pub after: String,
 }
 
@@ -38,6 +50,7 @@ impl Match {
             pattern_index,
             matched: matched.into(),
             captures: Vec::new(),
+            groups: CaptureGroups::default(),
             before: before.into(),
             after: after.into(),
         }
@@ -50,12 +63,37 @@ impl Match {
         self
     }
 
+    /// Create a match with numbered and named capture groups.
+    #[must_use]
+    pub fn with_groups(mut self, groups: CaptureGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+
     /// Get a capture group by index.
     #[must_use]
     pub fn capture(&self, index: usize) -> Option<&str> {
         self.captures.get(index).map(String::as_str)
     }
 
+    /// Get a regex capture group by number, where 0 is the whole match.
+    ///
+    /// Returns `None` if the group did not participate in the match or the
+    /// pattern was not a regex.
+    #[must_use]
+    pub fn group(&self, index: usize) -> Option<&str> {
+        self.groups.get(index)
+    }
+
+    /// Get a named regex capture group, such as `(?P<version>...)`.
+    ///
+    /// Returns `None` if there is no such group, it did not participate in
+    /// the match, or the pattern was not a regex.
+    #[must_use]
+    pub fn named(&self, name: &str) -> Option<&str> {
+        self.groups.name(name)
+    }
+
     /// Get the full matched text.
     #[must_use]
     pub fn as_str(&self) -> &str {
@@ -63,6 +101,50 @@ impl Match {
     }
 }
 
+/// Capture groups of a regex match.
+///
+/// Unlike [`Match::captures`], groups keep their position when an optional
+/// group does not participate in the match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaptureGroups {
+    /// Group text by number; group 0 is the whole match.
+    groups: Vec<Option<String>>,
+    /// Group number by name.
+    names: HashMap<String, usize>,
+}
+
+impl CaptureGroups {
+    /// Create capture groups from numbered group text and a name index.
+    #[must_use]
+    pub const fn new(groups: Vec<Option<String>>, names: HashMap<String, usize>) -> Self {
+        Self { groups, names }
+    }
+
+    /// Get a group by number.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.groups.get(index)?.as_deref()
+    }
+
+    /// Get a group by name.
+    #[must_use]
+    pub fn name(&self, name: &str) -> Option<&str> {
+        self.get(*self.names.get(name)?)
+    }
+
+    /// Get the number of groups, including group 0.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Check if there are no groups, as for non-regex patterns.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
 impl fmt::Display for Match {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.matched)
@@ -118,6 +200,26 @@ impl ExpectResult {
         }
     }
 
+    /// Get the text preceding the match.
+    ///
+    /// For EOF or timeout this is the whole buffer, since nothing matched.
+    #[must_use]
+    pub fn before(&self) -> &str {
+        match self {
+            Self::Matched(m) => &m.before,
+            Self::Eof { buffer } | Self::Timeout { buffer, .. } => buffer,
+        }
+    }
+
+    /// Get the matched text if this is a successful match.
+    #[must_use]
+    pub fn matched(&self) -> Option<&str> {
+        match self {
+            Self::Matched(m) => Some(&m.matched),
+            _ => None,
+        }
+    }
+
     /// Get the buffer contents (for EOF or timeout).
     #[must_use]
     pub fn buffer(&self) -> Option<&str> {
@@ -513,6 +615,37 @@ mod tests {
         assert_eq!(m.capture(1), None);
     }
 
+    #[test]
+    fn match_groups_by_number_and_name() {
+        let names = HashMap::from([("major".to_string(), 1), ("minor".to_string(), 2)]);
+        let groups = CaptureGroups::new(
+            vec![Some("v1.2".to_string()), Some("1".to_string()), None],
+            names,
+        );
+        let m = Match::new(0, "v1.2", "", "").with_groups(groups);
+
+        assert_eq!(m.group(0), Some("v1.2"));
+        assert_eq!(m.group(1), Some("1"));
+        assert_eq!(m.group(2), None);
+        assert_eq!(m.named("major"), Some("1"));
+        assert_eq!(m.named("minor"), None);
+        assert_eq!(m.named("patch"), None);
+        assert!(Match::new(0, "x", "", "").groups.is_empty());
+    }
+
+    #[test]
+    fn expect_result_before_and_matched() {
+        let result = ExpectResult::Matched(Match::new(0, "$ ", "output\n", ""));
+        assert_eq!(result.before(), "output\n");
+        assert_eq!(result.matched(), Some("$ "));
+
+        let result = ExpectResult::Eof {
+            buffer: "tail".to_string(),
+        };
+        assert_eq!(result.before(), "tail");
+        assert_eq!(result.matched(), None);
+    }
+
     #[test]
     fn session_state_checks() {
         assert!(SessionState::Running.is_usable());
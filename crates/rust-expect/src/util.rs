@@ -1,12 +1,13 @@
 //! Utility modules for expect operations.
 //!
-//! This module provides various utilities for timeout handling,
+//! This module provides various utilities for timeout handling, retries,
 //! byte manipulation, backpressure management, memory-efficient buffers,
 //! and zero-copy I/O operations.
 
 pub mod backpressure;
 pub mod buffer;
 pub mod bytes;
+pub mod retry;
 pub mod timeout;
 pub mod zerocopy;
 
@@ -19,5 +20,6 @@ pub use bytes::{
     EscapedBytes, escape_bytes, find_all_patterns, find_pattern, hexdump, replace_pattern,
     strip_ansi, to_visible_string, unescape_bytes,
 };
+pub use retry::{RetryConfig, RetryError, retry};
 pub use timeout::{Deadline, TimeoutConfig, TimeoutExt};
 pub use zerocopy::{BorrowedView, BytesBuffer, ReadPool, VecWriter, ZeroCopySource};
@@ -165,6 +165,9 @@ pub fn replace_pattern(haystack: &[u8], needle: &[u8], replacement: &[u8]) -> Ve
 }
 
 /// Strip ANSI escape sequences from bytes.
+///
+/// Removes CSI, OSC, DCS, SOS, PM and APC sequences as well as
+/// character-set designations and single-character escapes.
 #[must_use]
 pub fn strip_ansi(data: &[u8]) -> Vec<u8> {
     let mut result = Vec::with_capacity(data.len());
@@ -172,20 +175,7 @@ pub fn strip_ansi(data: &[u8]) -> Vec<u8> {
 
     while i < data.len() {
         if data[i] == 0x1b {
-            // Skip escape sequence
-            if i + 1 < data.len() && data[i + 1] == b'[' {
-                // CSI sequence
-                i += 2;
-                while i < data.len() && !data[i].is_ascii_alphabetic() && data[i] != b'@' {
-                    i += 1;
-                }
-                if i < data.len() {
-                    i += 1; // Skip final character
-                }
-            } else {
-                // Simple escape
-                i += 2;
-            }
+            i += escape_sequence_len(&data[i..]);
         } else {
             result.push(data[i]);
             i += 1;
@@ -195,6 +185,65 @@ pub fn strip_ansi(data: &[u8]) -> Vec<u8> {
     result
 }
 
+/// Length of the escape sequence at the start of `data`, which must begin
+/// with ESC.
+///
+/// Follows the ECMA-48 structure. An unterminated sequence runs to the end
+/// of `data`. A sequence only ends on an ASCII byte or at the end of the
+/// input, so slicing text at the returned length keeps UTF-8 intact.
+pub(crate) fn escape_sequence_len(data: &[u8]) -> usize {
+    debug_assert_eq!(data.first(), Some(&0x1b));
+    match data.get(1) {
+        None => 1,
+        // CSI: parameter and intermediate bytes, then a final byte
+        Some(b'[') => {
+            let end = 2 + data[2..]
+                .iter()
+                .take_while(|b| (0x20..=0x3f).contains(*b))
+                .count();
+            match data.get(end) {
+                Some(0x40..=0x7e) => end + 1,
+                _ => end,
+            }
+        }
+        // OSC, which xterm also lets BEL terminate
+        Some(b']') => control_string_len(data, true),
+        // DCS, SOS, PM and APC, terminated by ST
+        Some(b'P' | b'X' | b'^' | b'_') => control_string_len(data, false),
+        // nF: intermediate bytes then a final byte, e.g. `ESC ( B`
+        Some(0x20..=0x2f) => {
+            let end = 1 + data[1..]
+                .iter()
+                .take_while(|b| (0x20..=0x2f).contains(*b))
+                .count();
+            match data.get(end) {
+                Some(0x30..=0x7e) => end + 1,
+                _ => end,
+            }
+        }
+        // Single-character escapes, e.g. `ESC 7` or `ESC M`
+        Some(0x30..=0x7e) => 2,
+        Some(_) => 1,
+    }
+}
+
+/// Length of a control string whose two-byte introducer starts `data`.
+///
+/// The string ends at ST (`ESC \`), or at BEL when `bel` is set. Any other
+/// ESC aborts it so the next sequence can be parsed.
+fn control_string_len(data: &[u8], bel: bool) -> usize {
+    let mut i = 2;
+    while i < data.len() {
+        match data[i] {
+            0x07 if bel => return i + 1,
+            0x1b if data.get(i + 1) == Some(&b'\\') => return i + 2,
+            0x1b => return i,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
 /// A wrapper for bytes that implements Display with escaping.
 pub struct EscapedBytes<'a>(pub &'a [u8]);
 
@@ -273,6 +322,19 @@ mod tests {
         assert_eq!(stripped, b"Hello");
     }
 
+    #[test]
+    fn test_strip_ansi_mixed_sequences() {
+        let data = b"\x1b]0;title\x07\x1b7\x1b[?25l\x1b[2~dir\x1b8 \x1bPq#0\x1b\\\x1b(Bfile\x1b=";
+        assert_eq!(strip_ansi(data), b"dir file");
+    }
+
+    #[test]
+    fn test_strip_ansi_unterminated() {
+        assert_eq!(strip_ansi(b"ok\x1b]0;never ends"), b"ok");
+        assert_eq!(strip_ansi(b"ok\x1b[12"), b"ok");
+        assert_eq!(strip_ansi(b"ok\x1b"), b"ok");
+    }
+
     #[test]
     fn test_visible_string() {
         let data = b"Hello\x03World";
@@ -0,0 +1,375 @@
+//! Retry with backoff.
+//!
+//! [`retry`] re-runs a fallible async operation, such as a flaky `expect`
+//! or `connect`, sleeping between attempts according to a [`RetryConfig`].
+//! A config with a [`Deadline`] bounds the whole run, including attempts
+//! still in flight.
+
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+use thiserror::Error;
+
+use super::timeout::Deadline;
+
+/// How [`retry`] spaces and bounds its attempts.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Maximum number of attempts, including the first. Zero is treated
+    /// as one.
+    pub max_attempts: u32,
+    /// Delay after the first failed attempt.
+    pub base_delay: Duration,
+    /// Factor the delay grows by after each further failure. Values below
+    /// 1.0 are treated as 1.0, as is NaN.
+    pub multiplier: f64,
+    /// Fraction of each delay, from 0.0 to 1.0, that is randomly shaved
+    /// off so concurrent retries spread out. Values outside that range
+    /// are clamped.
+    pub jitter: f64,
+    /// Deadline for the whole run.
+    pub deadline: Option<Deadline>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            multiplier: 2.0,
+            jitter: 0.0,
+            deadline: None,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Create a config with the given attempt limit and default backoff.
+    #[must_use]
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// Set the delay after the first failed attempt.
+    #[must_use]
+    pub const fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Set the backoff multiplier, clamped to at least 1.0.
+    #[must_use]
+    pub const fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = clamp_multiplier(multiplier);
+        self
+    }
+
+    /// Set the jitter fraction, clamped to 0.0..=1.0.
+    #[must_use]
+    pub const fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set a deadline for the whole run.
+    #[must_use]
+    pub const fn deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// The delay after failed attempt `attempt` (starting at 1), before
+    /// jitter.
+    ///
+    /// A delay too long to represent saturates at [`Duration::MAX`].
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        if self.base_delay.is_zero() {
+            return Duration::ZERO;
+        }
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let multiplier = clamp_multiplier(self.multiplier);
+        let secs = self.base_delay.as_secs_f64() * multiplier.powi(exponent);
+        // With a multiplier of at least 1.0, `secs` only fails to convert
+        // by overflowing.
+        Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+    }
+
+    /// The backoff for `attempt` with jitter applied.
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.backoff(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter > 0.0 {
+            delay.mul_f64(1.0 - rand::rng().random_range(0.0..jitter))
+        } else {
+            delay
+        }
+    }
+}
+
+/// Clamp a backoff multiplier to at least 1.0, mapping NaN to 1.0.
+const fn clamp_multiplier(multiplier: f64) -> f64 {
+    if multiplier >= 1.0 { multiplier } else { 1.0 }
+}
+
+/// Why [`retry`] gave up.
+#[derive(Debug, Error)]
+pub enum RetryError<E> {
+    /// Every attempt failed.
+    #[error("gave up after {attempts} attempts: {last}")]
+    Exhausted {
+        /// The number of attempts made.
+        attempts: u32,
+        /// The error from the last attempt.
+        last: E,
+    },
+
+    /// The deadline passed before an attempt succeeded.
+    #[error("deadline exceeded after {attempts} attempts")]
+    DeadlineExceeded {
+        /// The number of attempts started.
+        attempts: u32,
+        /// The error from the last attempt that finished, if any.
+        last: Option<E>,
+    },
+}
+
+impl<E> RetryError<E> {
+    /// The error from the last attempt that finished, if any.
+    pub fn into_last(self) -> Option<E> {
+        match self {
+            Self::Exhausted { last, .. } => Some(last),
+            Self::DeadlineExceeded { last, .. } => last,
+        }
+    }
+}
+
+/// Run `op` until it succeeds, retrying with backoff under `config`.
+///
+/// When the config has a deadline, an attempt still running when it
+/// passes is cancelled, and no retry is started that would have to wait
+/// past it.
+///
+/// # Errors
+///
+/// Returns [`RetryError::Exhausted`] once `max_attempts` attempts have
+/// failed, or [`RetryError::DeadlineExceeded`] if the deadline passes
+/// first.
+pub async fn retry<T, E, F, Fut>(mut op: F, config: &RetryConfig) -> Result<T, RetryError<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let max_attempts = config.max_attempts.max(1);
+    let mut last = None;
+
+    for attempt in 1..=max_attempts {
+        let result = match &config.deadline {
+            Some(deadline) => match deadline.apply(op()).await {
+                Ok(result) => result,
+                Err(_) => {
+                    return Err(RetryError::DeadlineExceeded {
+                        attempts: attempt,
+                        last,
+                    });
+                }
+            },
+            None => op().await,
+        };
+        match result {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt == max_attempts => {
+                return Err(RetryError::Exhausted {
+                    attempts: attempt,
+                    last: e,
+                });
+            }
+            Err(e) => last = Some(e),
+        }
+
+        let delay = config.delay(attempt);
+        if config
+            .deadline
+            .as_ref()
+            .is_some_and(|deadline| deadline.remaining() <= delay)
+        {
+            return Err(RetryError::DeadlineExceeded {
+                attempts: attempt,
+                last,
+            });
+        }
+        tokio::time::sleep(delay).await;
+    }
+    unreachable!("the last attempt always returns")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Instant;
+
+    use super::*;
+
+    fn failing(calls: &AtomicU32) -> std::future::Ready<Result<(), u32>> {
+        std::future::ready(Err(calls.fetch_add(1, Ordering::SeqCst) + 1))
+    }
+
+    #[test]
+    fn backoff_grows_by_multiplier() {
+        let config = RetryConfig::new(5)
+            .base_delay(Duration::from_millis(10))
+            .multiplier(3.0);
+        assert_eq!(config.backoff(1), Duration::from_millis(10));
+        assert_eq!(config.backoff(2), Duration::from_millis(30));
+        assert_eq!(config.backoff(3), Duration::from_millis(90));
+    }
+
+    #[test]
+    fn backoff_saturates() {
+        let config = RetryConfig::new(u32::MAX).multiplier(10.0);
+        assert_eq!(config.backoff(u32::MAX), Duration::MAX);
+    }
+
+    #[test]
+    fn bad_multiplier_keeps_base_delay() {
+        let base = Duration::from_millis(10);
+        let mut config = RetryConfig::new(5).base_delay(base).multiplier(-2.0);
+        assert!((config.multiplier - 1.0).abs() < f64::EPSILON);
+        assert_eq!(config.backoff(3), base);
+
+        for multiplier in [-2.0, 0.5, f64::NAN, f64::NEG_INFINITY] {
+            config.multiplier = multiplier;
+            assert_eq!(config.backoff(1), base);
+            assert_eq!(config.backoff(4), base);
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_with_negative_multiplier_finishes() {
+        let calls = AtomicU32::new(0);
+        let mut config = RetryConfig::new(3).base_delay(Duration::from_millis(1));
+        config.multiplier = -3.0;
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(1), retry(|| failing(&calls), &config)).await;
+        assert!(matches!(
+            result,
+            Ok(Err(RetryError::Exhausted { attempts: 3, .. }))
+        ));
+    }
+
+    #[test]
+    fn jitter_only_shortens_delay() {
+        let config = RetryConfig::new(3)
+            .base_delay(Duration::from_millis(100))
+            .jitter(0.5);
+        for _ in 0..100 {
+            let delay = config.delay(1);
+            assert!(delay > Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn out_of_range_jitter_field_is_clamped() {
+        let mut config = RetryConfig::new(3).base_delay(Duration::from_millis(100));
+        config.jitter = 5.0;
+        for _ in 0..100 {
+            assert!(config.delay(1) <= Duration::from_millis(100));
+        }
+        config.jitter = f64::NAN;
+        assert_eq!(config.delay(1), Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn retry_returns_first_success() {
+        let calls = AtomicU32::new(0);
+        let config = RetryConfig::new(5).base_delay(Duration::from_millis(1));
+        let result = retry(
+            || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err("not yet"),
+                    n => Ok(n),
+                }
+            },
+            &config,
+        )
+        .await;
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_stops_after_max_attempts() {
+        let calls = AtomicU32::new(0);
+        let config = RetryConfig::new(4).base_delay(Duration::from_millis(1));
+        let err = retry(|| failing(&calls), &config).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            RetryError::Exhausted {
+                attempts: 4,
+                last: 4
+            }
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn retry_zero_attempts_runs_once() {
+        let calls = AtomicU32::new(0);
+        let err = retry(|| failing(&calls), &RetryConfig::new(0))
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_last(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn retry_skips_backoff_past_deadline() {
+        let calls = AtomicU32::new(0);
+        let config = RetryConfig::new(10)
+            .base_delay(Duration::from_millis(40))
+            .deadline(Deadline::from_now(Duration::from_millis(100)));
+
+        let start = Instant::now();
+        let err = retry(|| failing(&calls), &config).await.unwrap_err();
+
+        // Attempts at 0ms and 40ms; the 80ms backoff would pass the deadline
+        assert!(start.elapsed() < Duration::from_millis(100));
+        assert!(matches!(
+            err,
+            RetryError::DeadlineExceeded {
+                attempts: 2,
+                last: Some(2)
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn retry_cancels_attempt_at_deadline() {
+        let config = RetryConfig::new(3).deadline(Deadline::from_now(Duration::from_millis(50)));
+
+        let start = Instant::now();
+        let err = retry(
+            || async {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Ok::<_, ()>(())
+            },
+            &config,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(matches!(
+            err,
+            RetryError::DeadlineExceeded {
+                attempts: 1,
+                last: None
+            }
+        ));
+    }
+}
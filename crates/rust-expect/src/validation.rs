@@ -1,8 +1,12 @@
-//! Command and argument validation.
+//! Command, argument and session configuration validation.
 //!
 //! This module provides validation functions for commands and arguments
-//! to prevent security issues such as command injection.
+//! to prevent security issues such as command injection, and
+//! [`validate_session_config`] to catch misconfigurations before spawning.
 
+use thiserror::Error;
+
+use crate::config::SessionConfig;
 use crate::error::{ExpectError, SpawnError};
 
 /// Characters that are potentially dangerous in shell contexts.
@@ -178,6 +182,139 @@ where
     Ok(())
 }
 
+/// A problem found in a [`SessionConfig`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{field}: {problem} ({suggestion})")]
+pub struct ValidationError {
+    /// The offending field, e.g. `dimensions` or `args[2]`.
+    pub field: String,
+    /// What is wrong with it.
+    pub problem: String,
+    /// How to fix it.
+    pub suggestion: String,
+}
+
+impl ValidationError {
+    /// Create a validation error.
+    pub fn new(
+        field: impl Into<String>,
+        problem: impl Into<String>,
+        suggestion: impl Into<String>,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            problem: problem.into(),
+            suggestion: suggestion.into(),
+        }
+    }
+}
+
+/// Validate a session configuration before spawning.
+///
+/// Checks the command and arguments, terminal dimensions, working
+/// directory, environment, timeout and buffer size, and reports every
+/// problem found rather than stopping at the first.
+///
+/// # Errors
+///
+/// Returns all problems found, in field order.
+pub fn validate_session_config(config: &SessionConfig) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+
+    if config.command.trim().is_empty() {
+        errors.push(ValidationError::new(
+            "command",
+            "is empty",
+            "set the program to run, e.g. \"/bin/sh\"",
+        ));
+    } else if contains_null_byte(&config.command) {
+        errors.push(ValidationError::new(
+            "command",
+            "contains a null byte",
+            "remove the \\0 from the command",
+        ));
+    }
+    for (i, arg) in config.args.iter().enumerate() {
+        if contains_null_byte(arg) {
+            errors.push(ValidationError::new(
+                format!("args[{i}]"),
+                "contains a null byte",
+                "remove the \\0 from the argument",
+            ));
+        }
+    }
+
+    for (key, value) in sorted(&config.env) {
+        if key.is_empty() || key.contains('=') || contains_null_byte(key) {
+            errors.push(ValidationError::new(
+                "env",
+                format!("{key:?} is not a valid variable name"),
+                "use a non-empty name without '=' or null bytes",
+            ));
+        } else if contains_null_byte(value) {
+            errors.push(ValidationError::new(
+                format!("env[{key}]"),
+                "contains a null byte",
+                "remove the \\0 from the value",
+            ));
+        }
+    }
+
+    if let Some(dir) = &config.working_dir {
+        if !dir.exists() {
+            errors.push(ValidationError::new(
+                "working_dir",
+                format!("{} does not exist", dir.display()),
+                "create the directory or point working_dir at an existing one",
+            ));
+        } else if !dir.is_dir() {
+            errors.push(ValidationError::new(
+                "working_dir",
+                format!("{} is not a directory", dir.display()),
+                "point working_dir at a directory",
+            ));
+        }
+    }
+
+    let (cols, rows) = config.dimensions;
+    if cols == 0 || rows == 0 {
+        errors.push(ValidationError::new(
+            "dimensions",
+            format!("{cols}x{rows} has a zero width or height"),
+            "use at least 1x1, e.g. the default 80x24",
+        ));
+    }
+
+    if config.timeout.default.is_zero() {
+        errors.push(ValidationError::new(
+            "timeout.default",
+            "is zero, so every expect times out immediately",
+            "use a positive duration, e.g. 30 seconds",
+        ));
+    }
+
+    if config.buffer.max_size == 0 {
+        errors.push(ValidationError::new(
+            "buffer.max_size",
+            "is zero, so no output can be matched",
+            "use a positive size, e.g. the default 100 MB",
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Environment entries in key order, so errors are reported stably.
+fn sorted(env: &std::collections::HashMap<String, String>) -> Vec<(&String, &String)> {
+    let mut entries: Vec<_> = env.iter().collect();
+    entries.sort();
+    entries
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -237,4 +374,73 @@ mod tests {
         assert!(validate_command_with_args("/bin/echo", ["hello", "world"], &opts).is_ok());
         assert!(validate_command_with_args("/bin/echo", ["hello\0world"], &opts).is_err());
     }
+
+    fn valid_config() -> SessionConfig {
+        SessionConfig::new("/bin/sh")
+    }
+
+    #[test]
+    fn test_validate_session_config_ok() {
+        assert_eq!(validate_session_config(&valid_config()), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_session_config_empty_command() {
+        let errors = validate_session_config(&SessionConfig::new("  ")).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "command");
+        assert!(errors[0].suggestion.contains("/bin/sh"));
+    }
+
+    #[test]
+    fn test_validate_session_config_zero_dimensions() {
+        for dimensions in [(0, 24), (80, 0), (0, 0)] {
+            let config = valid_config().dimensions(dimensions.0, dimensions.1);
+            let errors = validate_session_config(&config).unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].field, "dimensions");
+        }
+    }
+
+    #[test]
+    fn test_validate_session_config_missing_working_dir() {
+        let config = valid_config().working_dir("/nonexistent/dir");
+        let errors = validate_session_config(&config).unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "working_dir: /nonexistent/dir does not exist \
+             (create the directory or point working_dir at an existing one)"
+        );
+    }
+
+    #[test]
+    fn test_validate_session_config_working_dir_is_file() {
+        let config = valid_config().working_dir("/etc/passwd");
+        let errors = validate_session_config(&config).unwrap_err();
+        assert!(errors[0].problem.ends_with("is not a directory"));
+    }
+
+    #[test]
+    fn test_validate_session_config_reports_every_problem() {
+        let mut config = SessionConfig::new("")
+            .args(["ok", "bad\0arg"])
+            .env("", "value")
+            .dimensions(0, 0)
+            .working_dir("/nonexistent/dir");
+        config.buffer.max_size = 0;
+
+        let errors = validate_session_config(&config).unwrap_err();
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "command",
+                "args[1]",
+                "env",
+                "working_dir",
+                "dimensions",
+                "buffer.max_size"
+            ]
+        );
+    }
 }
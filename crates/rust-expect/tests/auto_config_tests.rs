@@ -1,8 +1,12 @@
 //! Integration tests for auto-configuration.
 
+use std::time::Duration;
+
 use rust_expect::auto_config::locale::{detect_locale, is_utf8_environment, locale_env};
 use rust_expect::auto_config::shell::{ShellConfig, default_shell, detect_from_path};
-use rust_expect::{LocaleInfo, ShellType, detect_shell};
+use rust_expect::auto_config::{configure_prompt, prepare_shell};
+use rust_expect::{LocaleInfo, Session, SessionConfig, ShellType, detect_shell};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 #[test]
 fn detect_shell_returns_known_type() {
@@ -219,3 +223,160 @@ fn is_utf8_environment_returns_bool() {
     // Should return a boolean without panicking
     let _is_utf8 = is_utf8_environment();
 }
+
+/// The first single-quoted string in `command`.
+fn quoted(command: &str) -> String {
+    command.split('\'').nth(1).unwrap_or_default().to_string()
+}
+
+/// Set up a prompt on a fake shell that echoes each command line and then
+/// draws the prompt `render` derives from it.
+///
+/// Returns the echoed command and the prompt drawn for it after checking
+/// that the detected pattern matches only the prompt.
+async fn configure_fake_shell(shell: ShellType, render: fn(&str) -> String) -> (String, String) {
+    let (client, mut server) = tokio::io::duplex(4096);
+    let mut config = SessionConfig::default();
+    config.timeout.default = Duration::from_secs(5);
+    let mut session = Session::new(client, config);
+
+    let fake = tokio::spawn(async move {
+        server.write_all(b"user@host:~$ ").await.unwrap();
+        let mut line = Vec::new();
+        let mut byte = [0u8; 1];
+        while server.read(&mut byte).await.unwrap() == 1 && byte[0] != b'\n' {
+            line.push(byte[0]);
+        }
+        let command = String::from_utf8(line).unwrap().trim_end().to_string();
+        let prompt = render(&command);
+        let output = format!("{command}\r\n{prompt}");
+        server.write_all(output.as_bytes()).await.unwrap();
+        (server, command, prompt)
+    });
+
+    let pattern = configure_prompt(&mut session, shell).await.unwrap();
+    let (_server, command, prompt) = fake.await.unwrap();
+    assert!(pattern.find(&prompt).is_some(), "{prompt:?}");
+    assert!(pattern.find(&command).is_none(), "{command:?}");
+    assert!(pattern.find("user@host:~$ ").is_none());
+    (command, prompt)
+}
+
+#[tokio::test]
+async fn configure_prompt_bash() {
+    let (command, prompt) =
+        configure_fake_shell(ShellType::Bash, |c| quoted(&c.replace("''", ""))).await;
+    assert!(command.starts_with("PS1="));
+    assert!(prompt.starts_with("__EXPECT_PROMPT_"));
+}
+
+#[tokio::test]
+async fn configure_prompt_zsh() {
+    let (command, _) = configure_fake_shell(ShellType::Zsh, |c| quoted(&c.replace("''", ""))).await;
+    assert!(command.starts_with("PROMPT="));
+}
+
+#[tokio::test]
+async fn configure_prompt_fish() {
+    let (command, _) =
+        configure_fake_shell(ShellType::Fish, |c| quoted(&c.replace("''", ""))).await;
+    assert!(command.starts_with("function fish_prompt"));
+}
+
+#[tokio::test]
+async fn configure_prompt_tcsh() {
+    let (command, _) =
+        configure_fake_shell(ShellType::Tcsh, |c| quoted(&c.replace("''", ""))).await;
+    assert!(command.starts_with("set prompt="));
+}
+
+#[tokio::test]
+async fn configure_prompt_powershell() {
+    let (command, _) =
+        configure_fake_shell(ShellType::PowerShell, |c| quoted(&c.replace("' + '", ""))).await;
+    assert!(command.starts_with("function prompt"));
+}
+
+#[tokio::test]
+async fn configure_prompt_cmd() {
+    let (command, prompt) = configure_fake_shell(ShellType::Cmd, |c| {
+        c.trim_start_matches("prompt ")
+            .replace("$S", " ")
+            .replace("$P", r"C:\Users\test")
+            .replace("$G", ">")
+    })
+    .await;
+    assert!(command.ends_with("$S$P$G"));
+    assert!(prompt.ends_with(r" C:\Users\test>"));
+}
+
+/// The line `prepare_shell` sends for `shell`.
+async fn prepared_line(shell: ShellType) -> String {
+    let (client, mut server) = tokio::io::duplex(4096);
+    let mut session = Session::new(client, SessionConfig::default());
+    prepare_shell(&mut session, shell).await.unwrap();
+    drop(session);
+
+    let mut sent = String::new();
+    server.read_to_string(&mut sent).await.unwrap();
+    sent
+}
+
+#[tokio::test]
+async fn prepare_shell_bash() {
+    let line = prepared_line(ShellType::Bash).await;
+    assert_eq!(
+        line.trim_end(),
+        "stty -echo; set +o vi; bind 'set enable-bracketed-paste off'; \
+         export PAGER=cat GIT_PAGER=cat MANPAGER=cat SYSTEMD_PAGER="
+    );
+}
+
+#[tokio::test]
+async fn prepare_shell_zsh() {
+    let line = prepared_line(ShellType::Zsh).await;
+    assert!(line.starts_with("stty -echo; unsetopt zle; export PAGER=cat"));
+}
+
+#[tokio::test]
+async fn prepare_shell_posix_sh() {
+    for shell in [ShellType::Sh, ShellType::Dash] {
+        let line = prepared_line(shell).await;
+        assert!(line.starts_with("stty -echo; export PAGER=cat"), "{line}");
+        // POSIX sh has no readline options to reset
+        assert!(!line.contains("set +o vi"));
+    }
+}
+
+#[tokio::test]
+async fn prepare_shell_ksh() {
+    let line = prepared_line(ShellType::Ksh).await;
+    assert!(line.starts_with("stty -echo; set +o vi; export PAGER=cat"));
+}
+
+#[tokio::test]
+async fn prepare_shell_fish() {
+    let line = prepared_line(ShellType::Fish).await;
+    assert!(line.contains("set -gx PAGER cat; set -gx GIT_PAGER cat"));
+    assert!(!line.contains("export"));
+}
+
+#[tokio::test]
+async fn prepare_shell_tcsh() {
+    let line = prepared_line(ShellType::Tcsh).await;
+    assert!(line.starts_with("stty -echo; setenv PAGER cat; setenv GIT_PAGER cat"));
+}
+
+#[tokio::test]
+async fn prepare_shell_powershell() {
+    let line = prepared_line(ShellType::PowerShell).await;
+    assert!(line.starts_with("Remove-Module PSReadLine"));
+    assert!(line.contains("$env:GIT_PAGER = 'cat'"));
+    assert!(!line.contains("stty"));
+}
+
+#[tokio::test]
+async fn prepare_shell_cmd() {
+    let line = prepared_line(ShellType::Cmd).await;
+    assert_eq!(line.trim_end(), r#"set "PAGER=cat" & set "GIT_PAGER=cat""#);
+}
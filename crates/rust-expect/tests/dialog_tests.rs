@@ -159,3 +159,404 @@ fn dialog_entry_point() {
 
     assert_eq!(dialog.entry, Some("start".to_string()));
 }
+
+#[test]
+fn dialog_macro_builds_steps() {
+    let dialog = rust_expect::dialog! {
+        timeout Duration::from_secs(5);
+        forbid "Permission denied";
+        expect "login:";
+        sendln "admin";
+        expect_re r"\$\s*$", Duration::from_secs(1)
+    };
+
+    assert_eq!(dialog.len(), 4);
+    assert_eq!(dialog.steps[0].forbidden_patterns(), ["Permission denied"]);
+    assert_eq!(dialog.steps[1].get_timeout(), Some(Duration::from_secs(5)));
+    assert_eq!(dialog.steps[2].send_text(), Some("admin"));
+    assert!(dialog.steps[2].appends_line_ending());
+    assert!(dialog.steps[3].is_regex());
+    assert_eq!(dialog.steps[3].get_timeout(), Some(Duration::from_secs(1)));
+}
+
+async fn dialog_sent_bytes(line_ending: rust_expect::LineEnding) -> Vec<u8> {
+    use rust_expect::Session;
+    use rust_expect::config::SessionConfig;
+    use tokio::io::AsyncReadExt;
+
+    let (client, mut server) = tokio::io::duplex(1024);
+    let config = SessionConfig::default().line_ending(line_ending);
+    let mut session = Session::new(client, config);
+
+    let dialog = Dialog::named("lines")
+        .step(DialogStep::send_line("dir"))
+        .step(DialogStep::send("!"));
+    let result = session.run_dialog(&dialog).await.unwrap();
+    assert!(result.success);
+    drop(session);
+
+    let mut sent = Vec::new();
+    server.read_to_end(&mut sent).await.unwrap();
+    sent
+}
+
+#[tokio::test]
+async fn dialog_send_line_uses_session_line_ending() {
+    use rust_expect::LineEnding;
+
+    assert_eq!(dialog_sent_bytes(LineEnding::CrLf).await, b"dir\r\n!");
+    assert_eq!(dialog_sent_bytes(LineEnding::Lf).await, b"dir\n!");
+}
+
+/// Run a dialog against canned output, returning the dialog result and
+/// what it sent.
+async fn run_canned(
+    dialog: &Dialog,
+    output: &[u8],
+) -> (rust_expect::dialog::DialogResult, Vec<u8>) {
+    use rust_expect::config::SessionConfig;
+    use rust_expect::{LineEnding, Session};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (client, mut server) = tokio::io::duplex(1024);
+    let config = SessionConfig::default().line_ending(LineEnding::Lf);
+    let mut session = Session::new(client, config);
+    server.write_all(output).await.unwrap();
+
+    let result = session.run_dialog(dialog).await.unwrap();
+    drop(session);
+
+    let mut sent = Vec::new();
+    server.read_to_end(&mut sent).await.unwrap();
+    (result, sent)
+}
+
+fn host_key_login() -> Dialog {
+    use rust_expect::DialogBranch;
+
+    Dialog::named("login")
+        .step(
+            DialogStep::if_match([
+                DialogBranch::new(
+                    "(yes/no)?",
+                    [
+                        DialogStep::send_line("yes"),
+                        DialogStep::expect("password:"),
+                    ],
+                ),
+                DialogBranch::new("password:", []),
+            ])
+            .timeout(Duration::from_secs(1)),
+        )
+        .step(DialogStep::send_line("secret"))
+}
+
+#[tokio::test]
+async fn dialog_if_match_runs_matching_branch() {
+    let (result, sent) = run_canned(&host_key_login(), b"Continue (yes/no)? \npassword: ").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"yes\nsecret\n");
+    // The branch step, both branch steps, then the final send
+    assert_eq!(result.steps.len(), 4);
+    assert_eq!(result.steps[0].matched.as_deref(), Some("(yes/no)?"));
+}
+
+#[tokio::test]
+async fn dialog_if_match_takes_other_branch() {
+    let (result, sent) = run_canned(&host_key_login(), b"password: ").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"secret\n");
+    assert_eq!(result.steps.len(), 2);
+}
+
+#[tokio::test]
+async fn dialog_if_match_prefers_earliest_match_over_branch_order() {
+    let dialog = Dialog::new().step(
+        DialogStep::if_match([
+            rust_expect::DialogBranch::new("password:", [DialogStep::send_line("secret")]),
+            rust_expect::DialogBranch::new("(yes/no)?", [DialogStep::send_line("yes")]),
+        ])
+        .timeout(Duration::from_secs(1)),
+    );
+    let (result, sent) = run_canned(&dialog, b"Continue (yes/no)? \npassword: ").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"yes\n");
+    assert_eq!(result.steps[0].matched.as_deref(), Some("(yes/no)?"));
+}
+
+#[tokio::test]
+async fn dialog_if_match_times_out_without_a_match() {
+    let dialog = Dialog::new().step(
+        DialogStep::if_match([
+            rust_expect::DialogBranch::new("yes", []),
+            rust_expect::DialogBranch::new("no", []),
+        ])
+        .timeout(Duration::from_millis(50)),
+    );
+    let (result, sent) = run_canned(&dialog, b"maybe").await;
+    assert!(!result.success);
+    assert!(sent.is_empty());
+    let error = result.steps[0].error.as_deref().unwrap();
+    assert!(error.contains("'yes' or 'no'"), "{error}");
+}
+
+#[tokio::test]
+async fn dialog_macro_if_match_branches() {
+    let dialog = rust_expect::dialog! {
+        timeout Duration::from_secs(1);
+        if_match {
+            "(yes/no)?" => { sendln "yes"; expect "password:" },
+            "password:" => {}
+        }
+        sendln "secret"
+    };
+    assert_eq!(dialog.steps[0].if_match.len(), 2);
+    assert_eq!(
+        dialog.steps[0].if_match[0].steps[1].get_timeout(),
+        Some(Duration::from_secs(1))
+    );
+
+    let (result, sent) = run_canned(&dialog, b"(yes/no)? password: ").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"yes\nsecret\n");
+
+    let (result, sent) = run_canned(&dialog, b"password: ").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"secret\n");
+}
+
+#[tokio::test]
+async fn dialog_capture_reuses_value_in_later_send() {
+    let dialog = DialogBuilder::named("token")
+        .capture("token", r"token: (\w+)")
+        .step(DialogStep::send_line("use ${token}"))
+        .build();
+    let (result, sent) = run_canned(&dialog, b"token: abc123\n").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"use abc123\n");
+    assert_eq!(result.variable("token"), Some("abc123"));
+}
+
+#[tokio::test]
+async fn dialog_capture_overrides_dialog_variable() {
+    let dialog = Dialog::new()
+        .variable("token", "stale")
+        .step(DialogStep::capture("token", r"token: (\w+)").then_send_line("${token}"));
+    let (result, sent) = run_canned(&dialog, b"token: fresh\n").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"fresh\n");
+}
+
+#[tokio::test]
+async fn dialog_capture_fails_when_group_is_missing() {
+    let dialog = Dialog::new()
+        .step(DialogStep::expect_regex(r"token: (\w+)?").with_capture("token", 1))
+        .step(DialogStep::send_line("use ${token}"));
+    let (result, sent) = run_canned(&dialog, b"token: \n").await;
+    assert!(!result.success);
+    assert!(sent.is_empty());
+    assert!(result.variable("token").is_none());
+}
+
+#[tokio::test]
+async fn dialog_macro_capture_and_interpolate() {
+    let dialog = rust_expect::dialog! {
+        timeout Duration::from_secs(1);
+        capture token from_re r"token: (\w+)";
+        sendln "use ${token}"
+    };
+    assert_eq!(dialog.steps[0].capture.as_ref().unwrap().var, "token");
+    assert_eq!(dialog.steps[0].get_timeout(), Some(Duration::from_secs(1)));
+
+    let (result, sent) = run_canned(&dialog, b"token: xyz\n").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"use xyz\n");
+}
+
+/// Serve a password prompt that rejects the first `failures` attempts,
+/// returning the lines sent.
+fn flaky_login(
+    server: tokio::io::DuplexStream,
+    failures: usize,
+) -> tokio::task::JoinHandle<Vec<String>> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    tokio::spawn(async move {
+        let (reader, mut writer) = tokio::io::split(server);
+        let mut lines = BufReader::new(reader).lines();
+        let mut sent = Vec::new();
+        loop {
+            writer.write_all(b"password: ").await.unwrap();
+            let Ok(Some(line)) = lines.next_line().await else {
+                return sent;
+            };
+            sent.push(line);
+            if sent.len() > failures {
+                writer.write_all(b"\nprompt> ").await.unwrap();
+            } else {
+                writer.write_all(b"\nLogin incorrect\n").await.unwrap();
+            }
+        }
+    })
+}
+
+fn retry_login(max_attempts: u32) -> Dialog {
+    DialogBuilder::named("retry")
+        .step(
+            DialogStep::repeat(
+                "prompt>",
+                max_attempts,
+                [
+                    DialogStep::expect("password:"),
+                    DialogStep::send_line("secret"),
+                ],
+            )
+            .timeout(Duration::from_millis(200)),
+        )
+        .build()
+}
+
+#[tokio::test]
+async fn dialog_repeat_retries_until_pattern() {
+    use rust_expect::config::SessionConfig;
+    use rust_expect::{LineEnding, Session};
+
+    let (client, server) = tokio::io::duplex(1024);
+    let server = flaky_login(server, 2);
+    let config = SessionConfig::default().line_ending(LineEnding::Lf);
+    let mut session = Session::new(client, config);
+
+    let result = session.run_dialog(&retry_login(3)).await.unwrap();
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(
+        result.last_step().unwrap().matched.as_deref(),
+        Some("prompt>")
+    );
+    drop(session);
+    assert_eq!(server.await.unwrap(), ["secret"; 3]);
+}
+
+#[tokio::test]
+async fn dialog_repeat_fails_after_max_attempts() {
+    use rust_expect::config::SessionConfig;
+    use rust_expect::{LineEnding, Session};
+
+    let (client, server) = tokio::io::duplex(1024);
+    let server = flaky_login(server, 5);
+    let config = SessionConfig::default().line_ending(LineEnding::Lf);
+    let mut session = Session::new(client, config);
+
+    let result = session.run_dialog(&retry_login(2)).await.unwrap();
+    assert!(!result.success);
+    let error = result.last_step().unwrap().error.as_deref().unwrap();
+    assert!(error.contains("after 2 attempts"), "{error}");
+    drop(session);
+    assert_eq!(server.await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn dialog_macro_repeat_block() {
+    let dialog = rust_expect::dialog! {
+        timeout Duration::from_millis(200);
+        repeat until "prompt>" max 3 {
+            expect "password:";
+            sendln "secret"
+        }
+        sendln "exit"
+    };
+    assert_eq!(dialog.len(), 2);
+    let repeat = dialog.steps[0].repeat.as_ref().unwrap();
+    assert_eq!(repeat.until, "prompt>");
+    assert_eq!(repeat.max_attempts, 3);
+    assert_eq!(repeat.steps.len(), 2);
+    assert_eq!(
+        dialog.steps[0].get_timeout(),
+        Some(Duration::from_millis(200))
+    );
+}
+
+#[tokio::test]
+async fn dialog_on_timeout_runs_fallback() {
+    let dialog = Dialog::new()
+        .step(
+            DialogStep::expect_or(
+                "login:",
+                Duration::from_millis(50),
+                DialogStep::send_line("nudge"),
+            )
+            .then_send_line("admin"),
+        )
+        .step(DialogStep::send_line("after"));
+    let (result, sent) = run_canned(&dialog, b"booting...").await;
+    assert!(result.success, "{:?}", result.error);
+    // The fallback replaces the step's own send
+    assert_eq!(sent, b"nudge\nafter\n");
+    assert!(result.steps[0].matched.is_none());
+}
+
+#[tokio::test]
+async fn dialog_on_timeout_skipped_when_pattern_appears() {
+    let dialog = Dialog::new().step(
+        DialogStep::expect_or(
+            "login:",
+            Duration::from_secs(1),
+            DialogStep::send_line("nudge"),
+        )
+        .then_send_line("admin"),
+    );
+    let (result, sent) = run_canned(&dialog, b"login: ").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"admin\n");
+}
+
+#[tokio::test]
+async fn dialog_on_timeout_fallback_can_fail() {
+    let dialog = Dialog::new().step(DialogStep::expect_or(
+        "login:",
+        Duration::from_millis(50),
+        DialogStep::expect("still waiting").timeout(Duration::from_millis(50)),
+    ));
+    let (result, _) = run_canned(&dialog, b"booting...").await;
+    assert!(!result.success);
+    assert_eq!(result.steps.len(), 2);
+}
+
+#[tokio::test]
+async fn dialog_macro_expect_within_else() {
+    let dialog = rust_expect::dialog! {
+        expect "login:" within 50ms else sendln "";
+        sendln "admin"
+    };
+    assert_eq!(
+        dialog.steps[0].get_timeout(),
+        Some(Duration::from_millis(50))
+    );
+    assert!(dialog.steps[0].on_timeout.is_some());
+
+    let (result, sent) = run_canned(&dialog, b"booting...").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"\nadmin\n");
+
+    let (result, sent) = run_canned(&dialog, b"login: ").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, b"admin\n");
+}
+
+#[tokio::test]
+async fn dialog_macro_ctrl_and_sendbytes() {
+    let dialog = rust_expect::dialog! {
+        expect ">>> ";
+        sendbytes [0x1b, 0x5b];
+        ctrl 'd'
+    };
+    assert_eq!(
+        dialog.steps[2].send_control(),
+        Some(rust_expect::ControlChar::CtrlD)
+    );
+
+    let (result, sent) = run_canned(&dialog, b">>> ").await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(sent, [0x1b, 0x5b, 0x04]);
+    assert_eq!(result.steps[1].send.as_deref(), Some("<1b 5b>"));
+    assert_eq!(result.steps[2].send.as_deref(), Some("<CtrlD>"));
+}
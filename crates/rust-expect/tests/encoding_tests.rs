@@ -2,7 +2,7 @@
 
 use rust_expect::{
     DetectedEncoding, LineEndingStyle, decode_utf8_lossy, detect_encoding_from_env,
-    detect_line_ending, normalize_line_endings, strip_ansi,
+    detect_encoding_from_locale, detect_line_ending, normalize_line_endings, strip_ansi,
 };
 
 #[test]
@@ -109,6 +109,15 @@ fn detect_encoding_from_env_works() {
     assert!(!format!("{encoding:?}").is_empty());
 }
 
+#[test]
+fn detect_encoding_from_locale_with_modifier() {
+    assert_eq!(
+        detect_encoding_from_locale("en_US.UTF-8@euro"),
+        DetectedEncoding::Utf8
+    );
+    assert!(detect_encoding_from_locale("en_US.UTF-8").is_utf8());
+}
+
 #[test]
 fn line_ending_style_as_str() {
     assert_eq!(LineEndingStyle::Lf.as_str(), "\n");
@@ -214,3 +214,140 @@ async fn pattern_specific_timeout() {
     // May or may not timeout depending on timing
     let _ = short_result;
 }
+
+/// Test that a fallback matches instead of a timeout.
+#[tokio::test]
+async fn pattern_set_fallback_on_timeout() {
+    use tokio::io::AsyncWriteExt;
+
+    // The other end stays open, so only the timeout ends the expect
+    let (transport, mut output) = tokio::io::duplex(1024);
+    output.write_all(b"Booting...\n").await.unwrap();
+    let config = config_with_timeout(Duration::from_millis(100));
+    let mut session = Session::new(transport, config);
+
+    let mut patterns = PatternSet::new();
+    patterns.add(Pattern::literal("login:")).add_fallback();
+    let m = session.expect_any(&patterns).await.unwrap();
+    assert_eq!(Some(m.pattern_index), patterns.fallback_index());
+    assert!(m.before.contains("Booting"));
+    // The unmatched output stays in the buffer
+    assert!(session.buffer().contains("Booting"));
+}
+
+/// Test that a fallback matches instead of EOF.
+#[tokio::test]
+async fn pattern_set_fallback_on_eof() {
+    use tokio::io::AsyncWriteExt;
+
+    let (transport, mut output) = tokio::io::duplex(1024);
+    output.write_all(b"Connection closed\n").await.unwrap();
+    drop(output);
+    let config = config_with_timeout(Duration::from_secs(5));
+    let mut session = Session::new(transport, config);
+
+    let mut patterns = PatternSet::new();
+    patterns.add(Pattern::literal("login:")).add_fallback();
+    let m = session.expect_any(&patterns).await.unwrap();
+    assert_eq!(m.pattern_index, 1);
+    assert!(m.before.contains("Connection closed"));
+}
+
+/// Test that every expect is recorded in the session metrics.
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn expect_records_latency_and_timeouts() {
+    use tokio::io::AsyncWriteExt;
+
+    let (transport, mut output) = tokio::io::duplex(1024);
+    output.write_all(b"login: password: ").await.unwrap();
+    let config = config_with_timeout(Duration::from_millis(50));
+    let mut session = Session::new(transport, config);
+    let metrics = std::sync::Arc::clone(session.metrics());
+
+    session.expect("login:").await.unwrap();
+    assert_eq!(metrics.expect_latency().count(), 1);
+    session.expect("password:").await.unwrap();
+    assert_eq!(metrics.expect_latency().count(), 2);
+    assert_eq!(metrics.timeouts().get(), 0);
+
+    assert!(session.expect("$ ").await.is_err());
+    assert_eq!(metrics.expect_latency().count(), 3);
+    assert_eq!(metrics.timeouts().get(), 1);
+    assert!(metrics.expect_latency().sum() >= 0.05);
+
+    assert!(
+        session
+            .expect_eof_timeout(Duration::from_millis(20))
+            .await
+            .is_err()
+    );
+    assert_eq!(metrics.expect_latency().count(), 4);
+    assert_eq!(metrics.timeouts().get(), 2);
+}
+
+/// Test that an unbounded EOF wait does not overflow its deadline.
+#[tokio::test]
+async fn expect_eof_accepts_max_timeout() {
+    let transport = MockBuilder::new().output("bye\n").eof().build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let output = session.expect_eof_timeout(Duration::MAX).await.unwrap();
+    assert_eq!(output, "bye\n");
+}
+
+/// Test that screen expects are recorded in the session metrics.
+#[cfg(all(feature = "metrics", feature = "screen"))]
+#[tokio::test]
+async fn expect_on_screen_records_latency_and_timeouts() {
+    use tokio::io::AsyncWriteExt;
+
+    let (transport, mut output) = tokio::io::duplex(1024);
+    output.write_all(b"menu").await.unwrap();
+    let config = config_with_timeout(Duration::from_millis(50));
+    let mut session = Session::new(transport, config);
+    let metrics = std::sync::Arc::clone(session.metrics());
+
+    session.expect_on_screen("menu").await.unwrap();
+    assert!(session.expect_on_screen("missing").await.is_err());
+    assert_eq!(metrics.expect_latency().count(), 2);
+    assert_eq!(metrics.timeouts().get(), 1);
+}
+
+/// Test that a screen expect accepts an unbounded default timeout.
+#[cfg(feature = "screen")]
+#[tokio::test]
+async fn expect_on_screen_accepts_max_timeout() {
+    let transport = simple_mock("main menu");
+    let config = config_with_timeout(Duration::MAX);
+    let mut session = Session::new(transport, config);
+
+    session.expect_on_screen("menu").await.unwrap();
+}
+
+/// Test that Shift-JIS output split across reads is decoded before matching.
+#[cfg(feature = "legacy-encoding")]
+#[tokio::test]
+async fn expect_decodes_shift_jis_across_reads() {
+    use rust_expect::EncodingConfig;
+    use rust_expect::config::Encoding;
+    use tokio::io::AsyncWriteExt;
+
+    let (transport, mut output) = tokio::io::duplex(1024);
+    let mut config = config_with_timeout(Duration::from_secs(5));
+    config.encoding = EncodingConfig::new(Encoding::ShiftJis);
+    let mut session = Session::new(transport, config);
+
+    // "こんにちは> " with the third character split between writes
+    let writer = tokio::spawn(async move {
+        output.write_all(b"\x82\xb1\x82\xf1\x82").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        output.write_all(b"\xc9\x82\xbf\x82\xcd> ").await.unwrap();
+        output
+    });
+
+    let m = session.expect("> ").await.unwrap();
+    assert_eq!(m.before, "こんにちは");
+    let _output = writer.await.unwrap();
+}
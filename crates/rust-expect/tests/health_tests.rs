@@ -114,3 +114,104 @@ fn health_check_result_degraded() {
     assert_eq!(result.status, HealthStatus::Degraded);
     assert_eq!(result.message, Some("Partially working".to_string()));
 }
+
+#[tokio::test]
+async fn health_probe_healthy_when_marker_appears() {
+    use rust_expect::{Session, SessionConfig};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (transport, mut shell) = tokio::io::duplex(1024);
+    // Answer each line with a fresh prompt
+    tokio::spawn(async move {
+        let mut buf = [0u8; 64];
+        while let Ok(n) = shell.read(&mut buf).await {
+            if n == 0 || shell.write_all(b"$ ").await.is_err() {
+                break;
+            }
+        }
+    });
+    let mut session = Session::new(transport, SessionConfig::default());
+    let mut checker = HealthChecker::new(HealthCheckConfig::new().with_probe_command("true"));
+
+    let status = checker
+        .probe(&mut session, "$ ", Duration::from_secs(2))
+        .await;
+    assert_eq!(status, HealthStatus::Healthy);
+    assert_eq!(checker.status(), HealthStatus::Healthy);
+}
+
+#[tokio::test]
+async fn health_probe_unhealthy_when_session_is_wedged() {
+    use rust_expect::{Session, SessionConfig};
+
+    // Connected, but nothing ever answers
+    let (transport, _shell) = tokio::io::duplex(1024);
+    let mut session = Session::new(transport, SessionConfig::default());
+    let mut checker = HealthChecker::new(HealthCheckConfig::new().with_failure_threshold(1));
+
+    let status = checker
+        .probe(&mut session, "$ ", Duration::from_millis(50))
+        .await;
+    assert_eq!(status, HealthStatus::Unhealthy);
+    assert_eq!(checker.status(), HealthStatus::Unhealthy);
+    let result = checker.last_result().unwrap();
+    assert!(result.message.as_deref().unwrap().contains("probe failed"));
+    assert!(result.duration >= Duration::from_millis(50));
+}
+
+/// A duplex shell that echoes each line it receives, then writes `reply`.
+fn echoing_shell(reply: &'static [u8]) -> tokio::io::DuplexStream {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (transport, mut shell) = tokio::io::duplex(1024);
+    tokio::spawn(async move {
+        let mut buf = [0u8; 64];
+        while let Ok(n) = shell.read(&mut buf).await {
+            if n == 0
+                || shell.write_all(&buf[..n]).await.is_err()
+                || shell.write_all(reply).await.is_err()
+            {
+                break;
+            }
+        }
+    });
+    transport
+}
+
+#[tokio::test]
+async fn health_probe_ignores_marker_in_echoed_command() {
+    use rust_expect::{Session, SessionConfig};
+
+    // The shell echoes the command but never runs it
+    let mut session = Session::new(echoing_shell(b""), SessionConfig::default());
+    let config = HealthCheckConfig::new().with_probe_command("echo alive");
+    let mut checker = HealthChecker::new(config.clone());
+    let status = checker
+        .probe(&mut session, "alive", Duration::from_millis(100))
+        .await;
+    assert_eq!(status, HealthStatus::Unhealthy);
+
+    let mut session = Session::new(echoing_shell(b"alive\r\n$ "), SessionConfig::default());
+    let mut checker = HealthChecker::new(config);
+    let status = checker
+        .probe(&mut session, "alive", Duration::from_secs(2))
+        .await;
+    assert_eq!(status, HealthStatus::Healthy);
+}
+
+#[tokio::test]
+async fn health_probe_discards_unread_transport_output() {
+    use rust_expect::{Session, SessionConfig};
+    use tokio::io::AsyncWriteExt;
+
+    // A prompt the session has not read yet, then silence
+    let (transport, mut shell) = tokio::io::duplex(1024);
+    shell.write_all(b"$ ").await.unwrap();
+    let mut session = Session::new(transport, SessionConfig::default());
+    let mut checker = HealthChecker::new(HealthCheckConfig::new());
+
+    let status = checker
+        .probe(&mut session, "$ ", Duration::from_millis(50))
+        .await;
+    assert_eq!(status, HealthStatus::Unhealthy);
+}
@@ -6,12 +6,17 @@
 
 #![cfg(feature = "mock")]
 
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 use rust_expect::mock::{
     MockBuilder, MockTransport, Scenario, login_mock, shell_mock, simple_mock,
 };
-use rust_expect::{Session, SessionConfig};
+use rust_expect::{
+    BufferConfig, ExpectError, LineEnding, Pattern, PatternSet, SendFileMode, Session,
+    SessionConfig,
+};
 
 fn config_with_timeout(timeout: Duration) -> SessionConfig {
     let mut config = SessionConfig::default();
@@ -163,3 +168,747 @@ async fn scenario_pattern_matching() {
     let result = session.expect("Access granted").await;
     assert!(result.is_ok());
 }
+
+/// Test that a forbidden pattern aborts a dialog.
+#[tokio::test]
+async fn dialog_forbidden_pattern_aborts() {
+    let transport = simple_mock("login: Permission denied\n");
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let dialog = rust_expect::dialog! {
+        forbid "Permission denied";
+        expect "login:";
+        expect "$ "
+    };
+
+    let result = session.run_dialog(&dialog).await.unwrap();
+    assert!(!result.success);
+    let failed = result.last_step().unwrap();
+    assert!(
+        failed
+            .error
+            .as_deref()
+            .is_some_and(|e| e.contains("Permission denied")),
+        "unexpected error: {:?}",
+        failed.error
+    );
+}
+
+/// Test that unnamed dialog steps run in order.
+#[tokio::test]
+async fn dialog_unnamed_steps_run_in_order() {
+    let transport = simple_mock("login: password: $ ");
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport.clone(), config);
+
+    let dialog = rust_expect::dialog! {
+        forbid "Login incorrect";
+        expect "login:";
+        sendln "admin";
+        expect "password:";
+        sendln "secret";
+        expect "$ "
+    };
+
+    let result = session.run_dialog(&dialog).await.unwrap();
+    assert!(result.success, "dialog failed: {:?}", result.error);
+    assert_eq!(result.steps.len(), 6);
+    assert_eq!(transport.take_input_str(), "admin\nsecret\n");
+}
+
+/// Test reading buffered output in bounded chunks.
+#[tokio::test]
+async fn read_available_returns_buffered_chunks() {
+    let transport = MockBuilder::new()
+        .output("prompt> hello world")
+        .eof()
+        .build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    session.expect("prompt> ").await.unwrap();
+
+    // Output left over after the match is returned first, capped at `max`
+    assert_eq!(session.read_available(5).await.unwrap(), b"hello");
+    assert_eq!(session.read_available(64).await.unwrap(), b" world");
+
+    // Drained at EOF
+    assert!(session.read_available(64).await.unwrap().is_empty());
+    assert!(session.read_available(0).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn expect_any_reports_matched_index() {
+    let transport = MockBuilder::new().output("login: ").eof().build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let patterns = PatternSet::from_patterns(vec![
+        Pattern::literal("password:"),
+        Pattern::literal("login:"),
+        Pattern::Eof,
+    ]);
+    assert_eq!(
+        session.expect_any(&patterns).await.unwrap().pattern_index,
+        1
+    );
+    assert_eq!(
+        session.expect_any(&patterns).await.unwrap().pattern_index,
+        2
+    );
+}
+
+#[tokio::test]
+async fn sequential_expects_consume_before_text() {
+    let transport = MockBuilder::new()
+        .output("uname\r\nLinux\r\n$ id -u\r\n1000\r\n$ exit\r\n")
+        .eof()
+        .build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let m = session.expect("$ ").await.unwrap();
+    assert_eq!(m.before, "uname\r\nLinux\r\n");
+    assert_eq!(m.matched, "$ ");
+    assert_eq!(m.after, "id -u\r\n1000\r\n$ exit\r\n");
+
+    // The remainder stays buffered for the next expect
+    let m = session.expect("$ ").await.unwrap();
+    assert_eq!(m.before, "id -u\r\n1000\r\n");
+    assert_eq!(m.matched, "$ ");
+
+    let m = session.expect(Pattern::Eof).await.unwrap();
+    assert_eq!(m.before, "exit\r\n");
+    assert!(m.matched.is_empty());
+    assert!(
+        session
+            .expect(Pattern::Eof)
+            .await
+            .unwrap()
+            .before
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn next_line_splits_lf_output() {
+    let transport = MockBuilder::new()
+        .output("first\nsec")
+        .output("ond\n$ tail")
+        .eof()
+        .build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+    let timeout = Duration::from_secs(1);
+
+    assert_eq!(
+        session.next_line(timeout).await.unwrap().as_deref(),
+        Some("first")
+    );
+    assert_eq!(
+        session.next_line(timeout).await.unwrap().as_deref(),
+        Some("second")
+    );
+
+    // Lines and expects share the buffer
+    session.expect("$ ").await.unwrap();
+    assert_eq!(
+        session.next_line(timeout).await.unwrap().as_deref(),
+        Some("tail")
+    );
+    assert_eq!(session.next_line(timeout).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn next_line_accepts_max_timeout() {
+    let transport = MockBuilder::new().output("only\n").eof().build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    assert_eq!(
+        session.next_line(Duration::MAX).await.unwrap().as_deref(),
+        Some("only")
+    );
+}
+
+#[tokio::test]
+async fn next_line_honors_crlf_line_ending() {
+    let transport = MockBuilder::new().output("a\rb\r\nc\r\n").eof().build();
+    let config = config_with_timeout(Duration::from_secs(1)).line_ending(LineEnding::CrLf);
+    let mut session = Session::new(transport, config);
+    let timeout = Duration::from_secs(1);
+
+    assert_eq!(
+        session.next_line(timeout).await.unwrap().as_deref(),
+        Some("a\rb")
+    );
+    assert_eq!(
+        session.next_line(timeout).await.unwrap().as_deref(),
+        Some("c")
+    );
+    assert_eq!(session.next_line(timeout).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn next_line_times_out_on_partial_line() {
+    let transport = MockBuilder::new().output("partial").build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let err = session
+        .next_line(Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ExpectError::Timeout { .. }));
+
+    // The partial line is still there for expect
+    let m = session.expect("partial").await.unwrap();
+    assert!(m.before.is_empty());
+}
+
+#[tokio::test]
+async fn expect_line_start_prompt_skips_echoed_dollar() {
+    let transport = MockBuilder::new()
+        .output("echo $ HOME\r\n$ HOME\r\n$ ")
+        .eof()
+        .build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let m = session.expect(Pattern::at_line_start("$ ")).await.unwrap();
+    assert_eq!(m.before, "echo $ HOME\r\n");
+}
+
+#[tokio::test]
+async fn expect_eof_returns_trailing_output() {
+    let transport = MockBuilder::new()
+        .output("$ exit\r\n")
+        .output("logout\r\n")
+        .eof()
+        .build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    session.expect("$ ").await.unwrap();
+    assert_eq!(session.expect_eof().await.unwrap(), "exit\r\nlogout\r\n");
+}
+
+#[tokio::test]
+async fn expect_eof_reports_output_past_timeout() {
+    use tokio::io::AsyncWriteExt;
+
+    let (client, mut server) = tokio::io::duplex(1024);
+    let writer = tokio::spawn(async move {
+        while server.write_all(b"tick\n").await.is_ok() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    });
+
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(client, config);
+    let err = session
+        .expect_eof_timeout(Duration::from_millis(200))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ExpectError::UnexpectedData { .. }));
+    assert!(err.buffer().unwrap().contains("tick"));
+
+    drop(session);
+    writer.await.unwrap();
+}
+
+#[tokio::test]
+async fn expect_eof_times_out_after_output_stops() {
+    use tokio::io::AsyncWriteExt;
+
+    let (client, mut server) = tokio::io::duplex(1024);
+    let writer = tokio::spawn(async move {
+        server.write_all(b"done\n").await.unwrap();
+        // Stay open but silent
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    });
+
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(client, config);
+    let err = session
+        .expect_eof_timeout(Duration::from_millis(200))
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout());
+    assert_eq!(err.buffer(), Some("done\n"));
+
+    drop(session);
+    writer.await.unwrap();
+}
+
+#[tokio::test]
+async fn expect_eof_times_out_when_silent() {
+    let transport = MockBuilder::new().output("ready").build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    session.expect("ready").await.unwrap();
+    let err = session
+        .expect_eof_timeout(Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout());
+}
+
+#[tokio::test]
+async fn expect_unless_fails_on_forbidden_banner() {
+    let transport = MockBuilder::new()
+        .output("building...\nFATAL: disk full\nBuild OK\n")
+        .eof()
+        .build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let forbidden = [Pattern::literal("ERROR"), Pattern::literal("FATAL")];
+    let err = session
+        .expect_unless("Build OK", &forbidden)
+        .await
+        .unwrap_err();
+    match err {
+        ExpectError::ForbiddenMatch { pattern, matched } => {
+            assert_eq!(pattern, "FATAL");
+            assert_eq!(matched, "FATAL");
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+
+    // The wanted pattern still matches when it comes first
+    let m = session.expect_unless("Build OK", &forbidden).await.unwrap();
+    assert_eq!(m.matched, "Build OK");
+}
+
+#[tokio::test]
+async fn expect_sequence_returns_matches_in_pattern_order() {
+    let transport = MockBuilder::new()
+        .output("Username: ")
+        .delay_ms(20)
+        .output("Password: ")
+        .delay_ms(20)
+        .output("Welcome!\n$ ")
+        .eof()
+        .build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let patterns = [
+        Pattern::literal("Username: "),
+        Pattern::literal("Password: "),
+        Pattern::regex(r"Welcome!\s+").unwrap(),
+        Pattern::literal("$ "),
+    ];
+    let matches = session.expect_sequence(&patterns).await.unwrap();
+
+    let texts: Vec<_> = matches.iter().map(|m| m.matched.as_str()).collect();
+    assert_eq!(texts, ["Username: ", "Password: ", "Welcome!\n", "$ "]);
+    let indices: Vec<_> = matches.iter().map(|m| m.pattern_index).collect();
+    assert_eq!(indices, [0, 1, 2, 3]);
+    assert!(matches.iter().all(|m| m.before.is_empty()));
+}
+
+#[tokio::test]
+async fn expect_sequence_skips_output_between_patterns() {
+    let transport = MockBuilder::new()
+        .output("step one done\nnoise\nstep two done\nstep three done\n")
+        .eof()
+        .build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let patterns = [Pattern::literal("one"), Pattern::literal("three")];
+    let matches = session.expect_sequence(&patterns).await.unwrap();
+    assert_eq!(matches[0].before, "step ");
+    assert_eq!(matches[1].before, " done\nnoise\nstep two done\nstep ");
+}
+
+#[tokio::test]
+async fn expect_sequence_fails_on_out_of_order_output() {
+    let transport = MockBuilder::new().output("second\nfirst\n").build();
+    let config = config_with_timeout(Duration::from_millis(100));
+    let mut session = Session::new(transport, config);
+
+    // "second" was consumed while matching "first", so it never appears
+    let patterns = [Pattern::literal("first"), Pattern::literal("second")];
+    let err = session.expect_sequence(&patterns).await.unwrap_err();
+    match err {
+        ExpectError::Timeout { pattern, .. } => assert_eq!(pattern, "second"),
+        other => panic!("unexpected error: {other}"),
+    }
+}
+
+#[tokio::test]
+async fn buffer_overflow_callback_fires_before_match() {
+    let transport = MockBuilder::new()
+        .output(&"x".repeat(100))
+        .output(&"y".repeat(100))
+        .build();
+    let discarded = Arc::new(AtomicUsize::new(0));
+    let mut config = config_with_timeout(Duration::from_millis(100));
+    config.buffer = BufferConfig::new(64).on_overflow({
+        let discarded = Arc::clone(&discarded);
+        move |n| {
+            discarded.fetch_add(n, Ordering::SeqCst);
+        }
+    });
+    let mut session = Session::new(transport, config);
+
+    // The ring buffer keeps trimming, so the expect still just times out
+    let err = session.expect("never").await.unwrap_err();
+    assert!(err.is_timeout());
+    assert_eq!(discarded.load(Ordering::SeqCst), 200 - 64);
+}
+
+#[tokio::test]
+async fn buffer_overflow_fails_expect_without_ring_buffer() {
+    let transport = MockBuilder::new()
+        .output(&"x".repeat(100))
+        .output("needle")
+        .build();
+    let mut config = config_with_timeout(Duration::from_secs(5));
+    config.buffer = BufferConfig::new(64).ring_buffer(false);
+    let mut session = Session::new(transport, config);
+
+    let err = session.expect("never").await.unwrap_err();
+    assert!(matches!(err, ExpectError::BufferOverflow { max_size: 64 }));
+
+    // A match in the retained output still wins over the overflow
+    let m = session.expect("needle").await.unwrap();
+    assert_eq!(m.matched, "needle");
+}
+
+/// A file in the temp directory unique to this test process.
+fn temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("rust-expect-{}-{name}", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[tokio::test]
+async fn send_file_paces_lines_on_prompt() {
+    let script = temp_file(
+        "paced.sql",
+        "CREATE TABLE t (id int);\r\nINSERT INTO t VALUES (1);\n",
+    );
+    // Each prompt is held back until its line has been written
+    let transport = MockBuilder::new()
+        .output("db=# ")
+        .expect_input("CREATE TABLE t (id int);\n")
+        .output("CREATE TABLE\ndb=# ")
+        .expect_input("INSERT INTO t VALUES (1);\n")
+        .output("INSERT 0 1\ndb=# ")
+        .build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport.clone(), config);
+
+    session.expect("db=# ").await.unwrap();
+    let mode = SendFileMode::prompt_paced("db=# ", Duration::from_secs(1));
+    session.send_file(&script, mode).await.unwrap();
+    std::fs::remove_file(&script).unwrap();
+
+    transport.verify();
+}
+
+#[tokio::test]
+async fn send_file_fails_when_prompt_never_returns() {
+    let script = temp_file("stalled.sql", "SELECT 1;\nSELECT 2;\n");
+    let transport = MockBuilder::new()
+        .expect_input("SELECT 1;\n")
+        .output("ERROR: server closed\n")
+        .build();
+    let config = config_with_timeout(Duration::from_secs(5));
+    let mut session = Session::new(transport.clone(), config);
+
+    let mode = SendFileMode::prompt_paced("db=# ", Duration::from_millis(100));
+    let err = session.send_file(&script, mode).await.unwrap_err();
+    std::fs::remove_file(&script).unwrap();
+
+    assert!(err.is_timeout());
+    // The second line is never sent
+    assert_eq!(transport.take_input_str(), "SELECT 1;\n");
+}
+
+#[tokio::test]
+async fn send_file_raw_sends_bytes_unchanged() {
+    let script = temp_file("raw.sql", "SELECT 1;\r\nSELECT 2;");
+    let transport = MockBuilder::new().build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport.clone(), config);
+
+    session.send_file(&script, SendFileMode::Raw).await.unwrap();
+    std::fs::remove_file(&script).unwrap();
+
+    assert_eq!(transport.take_input_str(), "SELECT 1;\r\nSELECT 2;");
+}
+
+#[tokio::test]
+async fn send_file_reports_missing_file() {
+    let transport = MockBuilder::new().build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let err = session
+        .send_file("/nonexistent/script.sql", SendFileMode::Raw)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("/nonexistent/script.sql"));
+}
+
+#[tokio::test]
+async fn regex_match_exposes_capture_groups() {
+    let transport = MockBuilder::new()
+        .output("tool version 2.14 (build 7)\n$ ")
+        .eof()
+        .build();
+    let config = config_with_timeout(Duration::from_secs(1));
+    let mut session = Session::new(transport, config);
+
+    let pattern =
+        Pattern::regex(r"version (\d+\.\d+)(?: \(build (?P<build>\d+)\))?(?P<rc>-rc)?").unwrap();
+    let m = session.expect(pattern).await.unwrap();
+    assert_eq!(m.group(0), Some("version 2.14 (build 7)"));
+    assert_eq!(m.group(1), Some("2.14"));
+    assert_eq!(m.named("build"), Some("7"));
+    assert_eq!(m.named("rc"), None);
+    assert_eq!(m.group(4), None);
+
+    let m = session.expect("$ ").await.unwrap();
+    assert_eq!(m.group(0), None);
+}
+
+#[cfg(feature = "screen")]
+#[tokio::test]
+async fn expect_on_screen_matches_rendered_layout() {
+    // A progress display that redraws in place with cursor positioning
+    let transport = MockBuilder::new()
+        .output("\x1b[2J\x1b[1;1HLoading...\x1b[3;5HStatus: busy")
+        .output("\x1b[1;1H\x1b[KReady\x1b[3;13H\x1b[Kdone")
+        .eof()
+        .build();
+    let mut config = config_with_timeout(Duration::from_secs(1));
+    config.dimensions = (40, 5);
+    let mut session = Session::new(transport, config);
+
+    // The raw stream never contains the rendered line
+    let m = session
+        .expect_on_screen(Pattern::regex(r"Status: (done|failed)").unwrap())
+        .await
+        .unwrap();
+    assert_eq!(m.captures, vec!["done"]);
+    assert_eq!(m.before, "Ready\n\n    ");
+
+    let text = session.screen().text();
+    assert!(!text.contains("Loading"));
+    assert_eq!(text.lines().nth(2), Some("    Status: done"));
+}
+
+/// Test that a pattern split mid-word across delayed chunks still matches.
+#[tokio::test]
+async fn chunked_response_split_mid_word() {
+    let scenario = Scenario::new("split_prompt")
+        .initial_output("Welcome\n")
+        .respond_chunked(
+            ["Pass", "word: "],
+            [Duration::ZERO, Duration::from_millis(30)],
+        );
+
+    let transport = MockTransport::from_scenario(&scenario);
+    let config = config_with_timeout(Duration::from_secs(2));
+    let mut session = Session::new(transport, config);
+
+    let start = std::time::Instant::now();
+    let m = session.expect("Password: ").await.unwrap();
+    assert!(m.before.contains("Welcome"));
+    assert!(start.elapsed() >= Duration::from_millis(30));
+}
+
+/// Test that a regex split across several chunks is reassembled.
+#[tokio::test]
+async fn chunked_response_regex_across_reads() {
+    let scenario = Scenario::new("split_value").respond_chunked(
+        ["val", "ue: 4", "2\n"],
+        [
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+        ],
+    );
+
+    let transport = MockTransport::from_scenario(&scenario);
+    let config = config_with_timeout(Duration::from_secs(2));
+    let mut session = Session::new(transport, config);
+
+    let m = session
+        .expect(Pattern::regex(r"value: (\d+)\n").unwrap())
+        .await
+        .unwrap();
+    assert_eq!(m.group(0), Some("value: 42\n"));
+    assert_eq!(m.group(1), Some("42"));
+}
+
+/// Test that a chunk delayed past the timeout is not waited for.
+#[tokio::test]
+async fn chunked_response_delay_exceeds_timeout() {
+    let scenario = Scenario::new("late_chunk")
+        .respond_chunked(["Pass", "word: "], [Duration::ZERO, Duration::from_secs(5)]);
+
+    let transport = MockTransport::from_scenario(&scenario);
+    let config = config_with_timeout(Duration::from_millis(100));
+    let mut session = Session::new(transport, config);
+
+    let result = session.expect("Password: ").await;
+    assert!(matches!(result, Err(ExpectError::Timeout { .. })));
+    assert_eq!(session.buffer(), "Pass");
+}
+
+/// A login script that expects the username and password in turn.
+fn login_script() -> MockTransport {
+    MockBuilder::new()
+        .output("login: ")
+        .expect_input("admin\n")
+        .output("Password: ")
+        .expect_input("secret\n")
+        .output("Welcome\n$ ")
+        .build()
+}
+
+fn lf_config() -> SessionConfig {
+    config_with_timeout(Duration::from_secs(1)).line_ending(LineEnding::Lf)
+}
+
+/// Test that a login sequence sending the expected input verifies.
+#[tokio::test]
+async fn expected_input_login_sequence() {
+    let transport = login_script();
+    let mock = transport.clone();
+    let mut session = Session::new(transport, lf_config());
+
+    session.expect("login: ").await.unwrap();
+    session.send_line("admin").await.unwrap();
+    session.expect("Password: ").await.unwrap();
+    session.send_line("secret").await.unwrap();
+    session.expect("$ ").await.unwrap();
+
+    mock.verify();
+}
+
+/// Test that output after an expected input waits for it.
+#[tokio::test]
+async fn expected_input_holds_back_output() {
+    let transport = login_script();
+    let mut session = Session::new(transport, lf_config());
+
+    session.expect("login: ").await.unwrap();
+    let early = session
+        .expect_timeout("Password: ", Duration::from_millis(100))
+        .await;
+    assert!(matches!(early, Err(ExpectError::Timeout { .. })));
+
+    session.send_line("admin").await.unwrap();
+    session.expect("Password: ").await.unwrap();
+}
+
+/// Test that wrong input fails the send and the verification.
+#[tokio::test]
+#[should_panic(
+    expected = r#"mock expected input "secret\n" but received "hunter2\n" (first difference at byte 0)"#
+)]
+async fn expected_input_mismatch_fails() {
+    let transport = login_script();
+    let mock = transport.clone();
+    let mut session = Session::new(transport, lf_config());
+
+    session.expect("login: ").await.unwrap();
+    session.send_line("admin").await.unwrap();
+    session.expect("Password: ").await.unwrap();
+    let err = session.send_line("hunter2").await.unwrap_err();
+    assert!(err.to_string().contains("but received"), "{err}");
+
+    mock.verify();
+}
+
+/// Test that verify reports input that was never sent.
+#[tokio::test]
+#[should_panic(expected = r#"mock expected input "secret\n" that was never sent"#)]
+async fn expected_input_never_sent_fails_verify() {
+    let transport = login_script();
+    let mock = transport.clone();
+    let mut session = Session::new(transport, lf_config());
+
+    session.expect("login: ").await.unwrap();
+    session.send_line("admin").await.unwrap();
+    session.expect("Password: ").await.unwrap();
+
+    mock.verify();
+}
+
+/// Test that an injected I/O error surfaces from expect.
+#[tokio::test]
+async fn scenario_error_propagates_as_io() {
+    let scenario = Scenario::new("reset")
+        .initial_output("Connecting...\n")
+        .then_error(std::io::ErrorKind::ConnectionReset);
+
+    let transport = MockTransport::from_scenario(&scenario);
+    let mut session = Session::new(transport, config_with_timeout(Duration::from_secs(1)));
+
+    let err = session.expect("$ ").await.unwrap_err();
+    match err {
+        ExpectError::IoWithContext { source, .. } => {
+            assert_eq!(source.kind(), std::io::ErrorKind::ConnectionReset);
+        }
+        other => panic!("expected an I/O error, got {other:?}"),
+    }
+}
+
+/// Test that a premature close surfaces as EOF with the output so far.
+#[tokio::test]
+async fn scenario_eof_propagates_as_eof() {
+    let scenario = Scenario::new("closed")
+        .initial_output("Password: ")
+        .then_eof()
+        .expect_respond("secret", "Welcome\n$ ");
+
+    let transport = MockTransport::from_scenario(&scenario);
+    let mut session = Session::new(transport, config_with_timeout(Duration::from_secs(1)));
+
+    session.expect("Password: ").await.unwrap();
+    let err = session.expect("$ ").await.unwrap_err();
+    assert!(err.is_eof(), "expected EOF, got {err:?}");
+}
+
+/// Test that a broken pipe is treated like the PTY closing.
+#[tokio::test]
+async fn scenario_broken_pipe_is_eof() {
+    let scenario = Scenario::new("broken_pipe")
+        .initial_output("partial")
+        .then_error(std::io::ErrorKind::BrokenPipe);
+
+    let transport = MockTransport::from_scenario(&scenario);
+    let mut session = Session::new(transport, config_with_timeout(Duration::from_secs(1)));
+
+    let err = session.expect("$ ").await.unwrap_err();
+    assert!(err.is_eof(), "expected EOF, got {err:?}");
+}
+
+/// Test that a hung process surfaces as a timeout.
+#[tokio::test]
+async fn scenario_hang_propagates_as_timeout() {
+    let scenario = Scenario::new("hung")
+        .initial_output("Loading")
+        .then_hang()
+        .expect_respond("", "done\n$ ");
+
+    let transport = MockTransport::from_scenario(&scenario);
+    let mut session = Session::new(transport, config_with_timeout(Duration::from_millis(100)));
+
+    let err = session.expect("$ ").await.unwrap_err();
+    match err {
+        ExpectError::Timeout { buffer, .. } => assert_eq!(buffer, "Loading"),
+        other => panic!("expected a timeout, got {other:?}"),
+    }
+}
@@ -308,3 +308,96 @@ async fn multi_session_manager_session_ids() {
     assert!(ids.contains(&id1));
     assert!(ids.contains(&id2));
 }
+
+#[tokio::test]
+async fn multi_session_expect_any_of_reports_pattern_index() {
+    use rust_expect::Pattern;
+    use rust_expect::config::SessionConfig;
+    use tokio::io::{AsyncWriteExt, DuplexStream};
+
+    let mut manager: MultiSessionManager<DuplexStream> = MultiSessionManager::new();
+    let (c1, _s1) = tokio::io::duplex(1024);
+    let (c2, mut s2) = tokio::io::duplex(1024);
+
+    manager.add(
+        rust_expect::session::Session::new(c1, SessionConfig::default()),
+        "idle",
+    );
+    let id2 = manager.add(
+        rust_expect::session::Session::new(c2, SessionConfig::default()),
+        "busy",
+    );
+    s2.write_all(b"output: beta\n").await.unwrap();
+
+    let result = manager
+        .expect_any_of(&[Pattern::literal("alpha"), Pattern::literal("beta")])
+        .await
+        .unwrap();
+    assert_eq!(result.session_id, id2);
+    assert_eq!(result.pattern_index, 1);
+    assert_eq!(result.matched.pattern_index, 1);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn manager_spawn_and_expect_any() {
+    use std::time::Duration;
+
+    let mut manager = MultiSessionManager::new().with_timeout(Duration::from_secs(5));
+    let id1 = manager
+        .spawn("/bin/sh", &["-c", "echo server-one; sleep 1"])
+        .await
+        .unwrap();
+    let id2 = manager
+        .spawn("/bin/sh", &["-c", "echo server-two; sleep 1"])
+        .await
+        .unwrap();
+
+    assert_ne!(id1, id2);
+    assert_eq!(manager.len(), 2);
+    assert_eq!(manager.label(id1).await, Some(format!("/bin/sh#{id1}")));
+
+    let first = manager.expect_any("server-").await.unwrap();
+    assert!([id1, id2].contains(&first.session_id));
+
+    let other = if first.session_id == id1 { id2 } else { id1 };
+    let second = manager.expect(other, "server-").await.unwrap();
+    assert_eq!(second.matched, "server-");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn manager_close_kills_process() {
+    use std::time::{Duration, Instant};
+
+    let mut manager = MultiSessionManager::new().with_timeout(Duration::from_secs(5));
+    let id = manager.spawn("/bin/sleep", &["30"]).await.unwrap();
+
+    let started = Instant::now();
+    manager.close(id).await.unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(manager.is_empty());
+    assert!(manager.close(id).await.is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn manager_close_cancels_pending_expect() {
+    use std::time::{Duration, Instant};
+
+    use rust_expect::ExpectError;
+
+    let mut manager = MultiSessionManager::new().with_timeout(Duration::from_secs(5));
+    let id = manager.spawn("/bin/sleep", &["30"]).await.unwrap();
+
+    let started = Instant::now();
+    let (pending, closed) = tokio::join!(manager.expect(id, "never printed"), async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        manager.close(id).await
+    });
+
+    assert!(matches!(pending, Err(ExpectError::SessionClosed)));
+    closed.unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(manager.is_empty());
+}
@@ -216,3 +216,31 @@ fn success_indicator_pattern() {
     // "done" is not included in the pattern
     assert!(success_pat.matches("done").is_none());
 }
+
+#[test]
+fn patterns_macro_builds_set_in_order() {
+    let set = rust_expect::patterns! {
+        "err",
+        named: "error",
+        regex(r"\d+"),
+    };
+    assert_eq!(set.len(), 3);
+    assert_eq!(set.get(1).unwrap().name.as_deref(), Some("named"));
+    assert_eq!(set.fallback_index(), None);
+    // Both literals match at the same place, so the earlier arm wins
+    assert_eq!(set.find_match("error 42").unwrap().0, 0);
+}
+
+#[test]
+fn patterns_macro_maps_arms_to_actions() {
+    let (set, actions) = rust_expect::patterns! {
+        "ok" => "success",
+        "err" => "failure",
+        _ => "no answer",
+    };
+    assert_eq!(set.len(), 2);
+    assert_eq!(set.fallback_index(), Some(2));
+    let (index, _) = set.find_match("status: err").unwrap();
+    assert_eq!(actions[index], "failure");
+    assert_eq!(actions[set.fallback_index().unwrap()], "no answer");
+}
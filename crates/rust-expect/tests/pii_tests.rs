@@ -16,6 +16,8 @@ fn pii_type_variants() {
         PiiType::IpAddress,
         PiiType::AwsKey,
         PiiType::Secret,
+        PiiType::Iban,
+        PiiType::NationalId,
     ];
 
     for pii_type in types {
@@ -163,7 +163,7 @@ fn session_builder_logging() {
 // End-to-end spawn tests (require actual process spawning)
 // =============================================================================
 
-use rust_expect::Session;
+use rust_expect::{ExpectError, Session, SpawnError};
 
 /// Test spawning a simple command and expecting output.
 #[tokio::test]
@@ -256,11 +256,98 @@ async fn spawn_with_custom_config() {
 #[tokio::test]
 async fn spawn_nonexistent_command() {
     let result = Session::spawn("/nonexistent/command", &[]).await;
-    // The spawn should succeed (fork works), but the exec fails
-    // The child process will exit immediately with code 1
-    // This is expected behavior for PTY spawning
-    // We just verify we don't panic
-    assert!(result.is_ok() || result.is_err());
+    // The exec failure is reported back from the child
+    assert!(matches!(
+        result,
+        Err(ExpectError::Spawn(SpawnError::CommandNotFound { .. }))
+    ));
+}
+
+/// Test spawning a file without execute permission.
+#[tokio::test]
+async fn spawn_non_executable_file() {
+    let result = Session::spawn("/etc/passwd", &[]).await;
+    assert!(matches!(
+        result,
+        Err(ExpectError::Spawn(SpawnError::PermissionDenied { path })) if path == "/etc/passwd"
+    ));
+}
+
+/// Test spawning in a working directory that does not exist.
+#[tokio::test]
+async fn spawn_missing_working_dir() {
+    let config = SessionBuilder::new()
+        .working_directory("/nonexistent/dir")
+        .build();
+
+    let result = Session::spawn_with_config("/bin/sh", &[], config).await;
+    assert!(matches!(
+        result,
+        Err(ExpectError::Spawn(SpawnError::WorkingDirMissing { path })) if path == "/nonexistent/dir"
+    ));
+}
+
+/// Test that an empty command is rejected before spawning.
+#[tokio::test]
+async fn spawn_empty_command() {
+    let result = Session::spawn("", &[]).await;
+    assert_eq!(invalid_config_fields(result), ["command"]);
+}
+
+/// Test that a working directory that is a file is rejected before spawning.
+#[tokio::test]
+async fn spawn_working_dir_not_a_directory() {
+    let config = SessionBuilder::new().working_directory("/bin/sh").build();
+
+    let result = Session::spawn_with_config("/bin/sh", &[], config).await;
+    assert_eq!(invalid_config_fields(result), ["working_dir"]);
+}
+
+/// Test that zero terminal dimensions are rejected before spawning.
+#[tokio::test]
+async fn spawn_zero_dimensions() {
+    let config = SessionBuilder::new().dimensions(0, 0).build();
+
+    let result = Session::spawn_with_config("/bin/sh", &[], config).await;
+    assert_eq!(invalid_config_fields(result), ["dimensions"]);
+}
+
+/// Test that every configuration problem is reported in one error.
+#[tokio::test]
+async fn spawn_reports_all_config_problems() {
+    let config = SessionBuilder::new()
+        .dimensions(80, 0)
+        .working_directory("/nonexistent/dir")
+        .build();
+
+    let result = Session::spawn_with_config("", &[], config).await;
+    assert_eq!(
+        invalid_config_fields(result),
+        ["command", "working_dir", "dimensions"]
+    );
+}
+
+fn invalid_config_fields<T>(result: Result<T, ExpectError>) -> Vec<String> {
+    match result {
+        Err(ExpectError::Spawn(SpawnError::InvalidConfig { errors })) => {
+            errors.into_iter().map(|e| e.field).collect()
+        }
+        Err(e) => panic!("expected InvalidConfig, got {e:?}"),
+        Ok(_) => panic!("expected InvalidConfig, but the spawn succeeded"),
+    }
+}
+
+/// Test spawning in a custom working directory.
+#[tokio::test]
+async fn spawn_in_working_dir() {
+    let config = SessionBuilder::new().working_directory("/").build();
+
+    let mut session = Session::spawn_with_config("/bin/sh", &["-c", "pwd; echo done"], config)
+        .await
+        .expect("Failed to spawn in working directory");
+
+    let m = session.expect("done").await.expect("Expected 'done'");
+    assert!(m.before.lines().any(|line| line.trim() == "/"));
 }
 
 /// Test sending control characters.
@@ -320,3 +407,26 @@ async fn spawn_match_contains_text() {
     // The matched field should contain the matched text
     assert!(m.matched.contains("hello"), "Match should contain 'hello'");
 }
+
+/// Test interrupting the foreground job via its process group.
+#[tokio::test]
+async fn spawn_interrupt_foreground_job() {
+    let mut session = Session::spawn("/bin/sh", &[])
+        .await
+        .expect("Failed to spawn shell");
+
+    session.send_line("sleep 30").await.expect("Failed to send");
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    session.interrupt().expect("Failed to interrupt");
+
+    // The arithmetic expansion keeps the echoed command line from matching
+    session
+        .send_line("echo after_$((1 + 1))")
+        .await
+        .expect("Failed to send");
+    session
+        .expect_timeout("after_2", Duration::from_secs(5))
+        .await
+        .expect("Shell should respond once the sleep is interrupted");
+}
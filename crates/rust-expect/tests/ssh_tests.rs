@@ -1,7 +1,9 @@
 //! Integration tests for SSH backend.
 //!
-//! Note: These tests verify API structure only.
-//! Actual SSH connections require a running SSH server.
+//! Most of these tests verify API structure only. The exec, shell, port
+//! forwarding, agent forwarding, jump host, keepalive and SFTP tests
+//! connect to in-process russh servers and need the
+//! `insecure-skip-verify` feature to accept their host key.
 
 #![cfg(feature = "ssh")]
 
@@ -233,3 +235,995 @@ fn ssh_full_config() {
         HostKeyVerification::KnownHosts
     );
 }
+
+// Exec and port forwarding against an in-process server
+
+/// A minimal SSH server that runs exec requests with `/bin/sh -c` and
+/// forwards TCP ports.
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+mod test_server {
+    use std::collections::HashMap;
+    use std::os::unix::fs::FileExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use russh::keys::PrivateKey;
+    use russh::keys::ssh_key::private::Ed25519Keypair;
+    use russh::server::{self, Auth, Msg, Session};
+    use russh::{Channel, ChannelId, CryptoVec};
+    use russh_sftp::protocol::{
+        Attrs, Data, FileAttributes, Handle, OpenFlags, Status, StatusCode, Version,
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::task::JoinHandle;
+
+    /// What a test server saw, shared with the test.
+    #[derive(Clone, Default)]
+    pub struct Observed {
+        /// Number of direct-tcpip channels opened.
+        pub tunnels: Arc<AtomicUsize>,
+        /// Replies to the identities request the server sends over each
+        /// forwarded agent.
+        pub agent_replies: Arc<Mutex<Vec<Vec<u8>>>>,
+        /// Number of connections that have ended.
+        pub closed: Arc<AtomicUsize>,
+    }
+
+    #[derive(Default)]
+    struct TestHandler {
+        /// Listeners for remote forwards, by port.
+        forwards: HashMap<u32, JoinHandle<()>>,
+        observed: Observed,
+        /// Session channels not yet claimed by a subsystem.
+        channels: HashMap<ChannelId, Channel<Msg>>,
+    }
+
+    impl server::Handler for TestHandler {
+        type Error = russh::Error;
+
+        async fn auth_password(
+            &mut self,
+            _user: &str,
+            _password: &str,
+        ) -> Result<Auth, Self::Error> {
+            Ok(Auth::Accept)
+        }
+
+        async fn channel_open_session(
+            &mut self,
+            channel: Channel<Msg>,
+            _session: &mut Session,
+        ) -> Result<bool, Self::Error> {
+            self.channels.insert(channel.id(), channel);
+            Ok(true)
+        }
+
+        async fn shell_request(
+            &mut self,
+            id: ChannelId,
+            session: &mut Session,
+        ) -> Result<(), Self::Error> {
+            let Some(channel) = self.channels.remove(&id) else {
+                session.channel_failure(id)?;
+                return Ok(());
+            };
+            // A plain sh on pipes is enough to run commands; it neither
+            // echoes input nor prints a prompt
+            let mut shell = tokio::process::Command::new("/bin/sh")
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::null())
+                .kill_on_drop(true)
+                .spawn()?;
+            let mut stdin = shell.stdin.take().unwrap();
+            let mut stdout = shell.stdout.take().unwrap();
+            tokio::spawn(async move {
+                let (mut reader, mut writer) = tokio::io::split(channel.into_stream());
+                tokio::select! {
+                    _ = tokio::io::copy(&mut reader, &mut stdin) => {}
+                    _ = tokio::io::copy(&mut stdout, &mut writer) => {}
+                }
+                let _ = shell.kill().await;
+            });
+            Ok(())
+        }
+
+        async fn agent_request(
+            &mut self,
+            _channel: ChannelId,
+            session: &mut Session,
+        ) -> Result<bool, Self::Error> {
+            // Like sshd, open an agent channel back to the client and ask
+            // the agent for its identities
+            let handle = session.handle();
+            let replies = Arc::clone(&self.observed.agent_replies);
+            tokio::spawn(async move {
+                let Ok(agent) = handle.channel_open_agent().await else {
+                    return;
+                };
+                let mut stream = agent.into_stream();
+                // SSH_AGENTC_REQUEST_IDENTITIES
+                stream.write_all(&[0, 0, 0, 1, 11]).await.unwrap();
+                let len = stream.read_u32().await.unwrap();
+                let mut reply = vec![0; len as usize];
+                stream.read_exact(&mut reply).await.unwrap();
+                replies.lock().unwrap().push(reply);
+            });
+            Ok(true)
+        }
+
+        async fn subsystem_request(
+            &mut self,
+            channel: ChannelId,
+            name: &str,
+            session: &mut Session,
+        ) -> Result<(), Self::Error> {
+            match self.channels.remove(&channel) {
+                Some(stream) if name == "sftp" => {
+                    session.channel_success(channel)?;
+                    russh_sftp::server::run(stream.into_stream(), SftpHandler::default()).await;
+                }
+                _ => session.channel_failure(channel)?,
+            }
+            Ok(())
+        }
+
+        async fn exec_request(
+            &mut self,
+            channel: ChannelId,
+            data: &[u8],
+            session: &mut Session,
+        ) -> Result<(), Self::Error> {
+            let command = String::from_utf8_lossy(data).into_owned();
+            let output = std::process::Command::new("/bin/sh")
+                .args(["-c", &command])
+                .output()?;
+            let code = output
+                .status
+                .code()
+                .and_then(|c| u32::try_from(c).ok())
+                .unwrap_or(255);
+
+            // Like OpenSSH, report the exit status after EOF
+            session.data(channel, CryptoVec::from(output.stdout))?;
+            session.eof(channel)?;
+            session.exit_status_request(channel, code)?;
+            session.close(channel)?;
+            Ok(())
+        }
+
+        async fn channel_open_direct_tcpip(
+            &mut self,
+            channel: Channel<Msg>,
+            host_to_connect: &str,
+            port_to_connect: u32,
+            _originator_address: &str,
+            _originator_port: u32,
+            _session: &mut Session,
+        ) -> Result<bool, Self::Error> {
+            let port = u16::try_from(port_to_connect).unwrap();
+            // Refuse the channel like sshd when the target is unreachable
+            let Ok(mut socket) = TcpStream::connect((host_to_connect, port)).await else {
+                return Ok(false);
+            };
+            self.observed.tunnels.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let mut stream = channel.into_stream();
+                let _ = tokio::io::copy_bidirectional(&mut socket, &mut stream).await;
+            });
+            Ok(true)
+        }
+
+        async fn tcpip_forward(
+            &mut self,
+            address: &str,
+            port: &mut u32,
+            session: &mut Session,
+        ) -> Result<bool, Self::Error> {
+            let listener = TcpListener::bind((address, u16::try_from(*port).unwrap())).await?;
+            let bound = u32::from(listener.local_addr()?.port());
+            *port = bound;
+
+            let handle = session.handle();
+            let address = address.to_string();
+            let task = tokio::spawn(async move {
+                while let Ok((mut socket, peer)) = listener.accept().await {
+                    let Ok(channel) = handle
+                        .channel_open_forwarded_tcpip(
+                            address.as_str(),
+                            bound,
+                            peer.ip().to_string(),
+                            u32::from(peer.port()),
+                        )
+                        .await
+                    else {
+                        break;
+                    };
+                    tokio::spawn(async move {
+                        let mut stream = channel.into_stream();
+                        let _ = tokio::io::copy_bidirectional(&mut socket, &mut stream).await;
+                    });
+                }
+            });
+            self.forwards.insert(bound, task);
+            Ok(true)
+        }
+
+        async fn cancel_tcpip_forward(
+            &mut self,
+            _address: &str,
+            port: u32,
+            _session: &mut Session,
+        ) -> Result<bool, Self::Error> {
+            let Some(task) = self.forwards.remove(&port) else {
+                return Ok(false);
+            };
+            task.abort();
+            let _ = task.await;
+            Ok(true)
+        }
+    }
+
+    /// An SFTP server over the local filesystem.
+    #[derive(Default)]
+    struct SftpHandler {
+        files: HashMap<String, std::fs::File>,
+        next_handle: u32,
+    }
+
+    impl SftpHandler {
+        fn file(&self, handle: &str) -> Result<&std::fs::File, StatusCode> {
+            self.files.get(handle).ok_or(StatusCode::Failure)
+        }
+    }
+
+    fn status(id: u32) -> Status {
+        Status {
+            id,
+            status_code: StatusCode::Ok,
+            error_message: "Ok".to_string(),
+            language_tag: "en-US".to_string(),
+        }
+    }
+
+    fn no_such_file(_: std::io::Error) -> StatusCode {
+        StatusCode::NoSuchFile
+    }
+
+    impl russh_sftp::server::Handler for SftpHandler {
+        type Error = StatusCode;
+
+        fn unimplemented(&self) -> Self::Error {
+            StatusCode::OpUnsupported
+        }
+
+        async fn init(
+            &mut self,
+            _version: u32,
+            _extensions: HashMap<String, String>,
+        ) -> Result<Version, Self::Error> {
+            Ok(Version::new())
+        }
+
+        async fn open(
+            &mut self,
+            id: u32,
+            filename: String,
+            pflags: OpenFlags,
+            _attrs: FileAttributes,
+        ) -> Result<Handle, Self::Error> {
+            let file = std::fs::OpenOptions::from(pflags)
+                .open(filename)
+                .map_err(no_such_file)?;
+            self.next_handle += 1;
+            let handle = self.next_handle.to_string();
+            self.files.insert(handle.clone(), file);
+            Ok(Handle { id, handle })
+        }
+
+        async fn close(&mut self, id: u32, handle: String) -> Result<Status, Self::Error> {
+            self.files.remove(&handle).ok_or(StatusCode::Failure)?;
+            Ok(status(id))
+        }
+
+        async fn read(
+            &mut self,
+            id: u32,
+            handle: String,
+            offset: u64,
+            len: u32,
+        ) -> Result<Data, Self::Error> {
+            let mut data = vec![0; len as usize];
+            let n = self
+                .file(&handle)?
+                .read_at(&mut data, offset)
+                .map_err(|_| StatusCode::Failure)?;
+            if n == 0 {
+                return Err(StatusCode::Eof);
+            }
+            data.truncate(n);
+            Ok(Data { id, data })
+        }
+
+        async fn write(
+            &mut self,
+            id: u32,
+            handle: String,
+            offset: u64,
+            data: Vec<u8>,
+        ) -> Result<Status, Self::Error> {
+            self.file(&handle)?
+                .write_all_at(&data, offset)
+                .map_err(|_| StatusCode::Failure)?;
+            Ok(status(id))
+        }
+
+        async fn fstat(&mut self, id: u32, handle: String) -> Result<Attrs, Self::Error> {
+            let metadata = self.file(&handle)?.metadata().map_err(no_such_file)?;
+            Ok(Attrs {
+                id,
+                attrs: FileAttributes::from(&metadata),
+            })
+        }
+
+        async fn stat(&mut self, id: u32, path: String) -> Result<Attrs, Self::Error> {
+            let metadata = std::fs::metadata(path).map_err(no_such_file)?;
+            Ok(Attrs {
+                id,
+                attrs: FileAttributes::from(&metadata),
+            })
+        }
+    }
+
+    /// Start the server on a free local port and return the port.
+    pub async fn start() -> u16 {
+        start_observed().await.0
+    }
+
+    /// Start the server and also return what it observes.
+    pub async fn start_observed() -> (u16, Observed) {
+        let observed = Observed::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let config = Arc::new(server::Config {
+            keys: vec![PrivateKey::from(Ed25519Keypair::from_seed(&[7; 32]))],
+            auth_rejection_time_initial: Some(Duration::ZERO),
+            ..Default::default()
+        });
+
+        let shared = observed.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let config = Arc::clone(&config);
+                let handler = TestHandler {
+                    observed: shared.clone(),
+                    ..TestHandler::default()
+                };
+                let closed = Arc::clone(&shared.closed);
+                tokio::spawn(async move {
+                    if let Ok(session) = server::run_stream(config, socket, handler).await {
+                        let _ = session.await;
+                    }
+                    closed.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+        });
+        (port, observed)
+    }
+
+    /// State of a [`link`] between the client and a server.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Link {
+        /// Traffic flows.
+        Up,
+        /// Traffic is silently dropped but the sockets stay open, like a
+        /// dead NAT entry.
+        Stalled,
+        /// Both sockets are closed.
+        Cut,
+    }
+
+    /// Proxy one connection to `port` through a link the test controls.
+    ///
+    /// Returns the proxy's port and the control for the link.
+    pub async fn link(port: u16) -> (u16, tokio::sync::watch::Sender<Link>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_port = listener.local_addr().unwrap().port();
+        let (control, state) = tokio::sync::watch::channel(Link::Up);
+        tokio::spawn(async move {
+            let (client, _) = listener.accept().await.unwrap();
+            let server = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+            let (client_read, client_write) = client.into_split();
+            let (server_read, server_write) = server.into_split();
+            tokio::join!(
+                pipe(client_read, server_write, state.clone()),
+                pipe(server_read, client_write, state),
+            );
+        });
+        (proxy_port, control)
+    }
+
+    async fn pipe(
+        mut from: tokio::net::tcp::OwnedReadHalf,
+        mut to: tokio::net::tcp::OwnedWriteHalf,
+        mut state: tokio::sync::watch::Receiver<Link>,
+    ) {
+        let mut buf = [0; 4096];
+        loop {
+            tokio::select! {
+                read = from.read(&mut buf) => {
+                    let Ok(n @ 1..) = read else {
+                        return;
+                    };
+                    let link = *state.borrow();
+                    match link {
+                        Link::Up => {
+                            if to.write_all(&buf[..n]).await.is_err() {
+                                return;
+                            }
+                        }
+                        Link::Stalled => {}
+                        Link::Cut => return,
+                    }
+                }
+                changed = state.changed() => {
+                    if changed.is_err() || *state.borrow() == Link::Cut {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Forward connections on a new port to `port`, except the first,
+    /// which is closed before the handshake.
+    ///
+    /// Returns the new port.
+    pub async fn refuse_first(port: u16) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let front = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let mut refused = false;
+            while let Ok((mut client, _)) = listener.accept().await {
+                if !refused {
+                    refused = true;
+                    continue;
+                }
+                tokio::spawn(async move {
+                    let mut server = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+                });
+            }
+        });
+        front
+    }
+
+    /// Start a TCP echo server on a free local port and return the port.
+    pub async fn echo() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let (mut reader, mut writer) = socket.into_split();
+                    let _ = tokio::io::copy(&mut reader, &mut writer).await;
+                });
+            }
+        });
+        port
+    }
+}
+
+/// Config for an in-process test server on `port`.
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+fn test_server_config(port: u16) -> SshConfig {
+    SshConfig::new("127.0.0.1")
+        .port(port)
+        .credentials(SshCredentials::new("ci").with_password("secret"))
+        .host_key_verification(HostKeyVerification::AcceptAll)
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+async fn connect_test_server() -> rust_expect::backend::ssh::SshSession {
+    use rust_expect::backend::ssh::SshSession;
+
+    let port = test_server::start().await;
+    let config = test_server_config(port);
+    let mut session = SshSession::new(config);
+    session.connect_async().await.unwrap();
+    session
+}
+
+// Dropping a connected SshSession disconnects with block_in_place, which
+// needs the multi-threaded runtime.
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_exec_reports_failing_exit_status() {
+    use tokio::io::AsyncReadExt;
+
+    let mut session = connect_test_server().await;
+    let mut exec = session.exec("false").await.unwrap();
+
+    let mut output = Vec::new();
+    exec.read_to_end(&mut output).await.unwrap();
+    assert!(output.is_empty());
+    assert_eq!(exec.wait_exit_status().await.unwrap(), 1);
+    assert_eq!(exec.exit_status(), Some(1));
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_exec_reports_output_and_exit_status() {
+    use tokio::io::AsyncReadExt;
+
+    let mut session = connect_test_server().await;
+    let mut exec = session.exec("echo done; exit 3").await.unwrap();
+
+    let mut output = String::new();
+    exec.read_to_string(&mut output).await.unwrap();
+    assert_eq!(output, "done\n");
+    assert_eq!(exec.wait_exit_status().await.unwrap(), 3);
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+// A current-thread runtime, so dropping the session must not block on it
+#[tokio::test]
+async fn ssh_spawn_ssh_runs_expect_over_shell() {
+    use rust_expect::Session;
+
+    let port = test_server::start().await;
+    let mut session = Session::spawn_ssh(test_server_config(port)).await.unwrap();
+
+    session.send_line("echo hi").await.unwrap();
+    session.expect("hi").await.unwrap();
+
+    // The test server does not echo input, so this only matches output
+    session.send_line("echo $((40 + 2))").await.unwrap();
+    let m = session
+        .expect_timeout("42", Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(m.matched, "42");
+    drop(session);
+}
+
+/// Send a message over `addr` and assert it comes back unchanged.
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+async fn assert_echoes(addr: std::net::SocketAddr, message: &[u8]) -> tokio::net::TcpStream {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut conn = tokio::net::TcpStream::connect(addr).await.unwrap();
+    conn.write_all(message).await.unwrap();
+    let mut echoed = vec![0; message.len()];
+    tokio::time::timeout(Duration::from_secs(5), conn.read_exact(&mut echoed))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(echoed, message);
+    conn
+}
+
+/// Wait until nothing accepts connections on `addr` any more.
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+async fn assert_stops_listening(addr: std::net::SocketAddr) {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while tokio::net::TcpStream::connect(addr).await.is_ok() {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "{addr} still accepts connections"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_local_forward_round_trips_to_echo_server() {
+    use tokio::io::AsyncReadExt;
+
+    let echo_port = test_server::echo().await;
+    let session = connect_test_server().await;
+    let forward = session
+        .forward_local("127.0.0.1:0", "127.0.0.1", echo_port)
+        .await
+        .unwrap();
+    let addr = forward.local_addr();
+
+    let mut first = assert_echoes(addr, b"through the tunnel").await;
+    let _second = assert_echoes(addr, b"and another connection").await;
+
+    drop(forward);
+    assert_stops_listening(addr).await;
+    // Open connections are closed with the forward
+    let mut rest = Vec::new();
+    let read = tokio::time::timeout(Duration::from_secs(5), first.read_to_end(&mut rest)).await;
+    assert!(matches!(read, Ok(Ok(0) | Err(_))));
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_remote_forward_round_trips_to_echo_server() {
+    let echo_port = test_server::echo().await;
+    let session = connect_test_server().await;
+    let forward = session
+        .forward_remote("127.0.0.1", 0, "127.0.0.1", echo_port)
+        .await
+        .unwrap();
+    assert_eq!(forward.remote_address(), "127.0.0.1");
+    assert_ne!(forward.remote_port(), 0);
+
+    // The server runs in-process, so its listener is local too
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], forward.remote_port()));
+    assert_echoes(addr, b"back from the server").await;
+
+    drop(forward);
+    assert_stops_listening(addr).await;
+}
+
+#[tokio::test]
+async fn ssh_forward_requires_connection() {
+    use rust_expect::backend::ssh::SshSession;
+
+    let session = SshSession::new(SshConfig::new("example.com"));
+    assert!(
+        session
+            .forward_local("127.0.0.1:0", "db", 5432)
+            .await
+            .is_err()
+    );
+    assert!(
+        session
+            .forward_remote("127.0.0.1", 0, "localhost", 80)
+            .await
+            .is_err()
+    );
+}
+
+#[test]
+fn ssh_config_proxy_jump() {
+    let config = SshConfig::new("internal.example.com")
+        .proxy_jump(vec![SshConfig::new("bastion.example.com").port(2222)]);
+
+    assert_eq!(config.proxy_jump.len(), 1);
+    assert_eq!(config.proxy_jump[0].address(), "bastion.example.com:2222");
+    assert!(SshConfig::default().proxy_jump.is_empty());
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_proxy_jump_reaches_target_through_jump_host() {
+    use rust_expect::backend::ssh::{SshSession, SshSessionState};
+    use std::sync::atomic::Ordering;
+    use tokio::io::AsyncReadExt;
+
+    let (jump_port, jump) = test_server::start_observed().await;
+    let (target_port, target) = test_server::start_observed().await;
+    let config = test_server_config(target_port).proxy_jump(vec![test_server_config(jump_port)]);
+
+    let mut session = SshSession::new(config);
+    session.connect_async().await.unwrap();
+    assert_eq!(session.state(), SshSessionState::Connected);
+    assert_eq!(jump.tunnels.load(Ordering::SeqCst), 1);
+    assert_eq!(target.tunnels.load(Ordering::SeqCst), 0);
+
+    let mut exec = session.exec("echo behind the bastion").await.unwrap();
+    let mut output = String::new();
+    exec.read_to_string(&mut output).await.unwrap();
+    assert_eq!(output, "behind the bastion\n");
+    assert_eq!(exec.wait_exit_status().await.unwrap(), 0);
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_disconnect_async_closes_target_and_jump_host() {
+    use rust_expect::backend::ssh::{SshSession, SshSessionState};
+    use std::sync::atomic::Ordering;
+
+    let (jump_port, jump) = test_server::start_observed().await;
+    let (target_port, target) = test_server::start_observed().await;
+    let config = test_server_config(target_port).proxy_jump(vec![test_server_config(jump_port)]);
+
+    let mut session = SshSession::new(config);
+    session.connect_async().await.unwrap();
+    session.disconnect_async().await;
+    assert_eq!(session.state(), SshSessionState::Disconnected);
+    assert!(session.handle().is_none());
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while target.closed.load(Ordering::SeqCst) == 0 || jump.closed.load(Ordering::SeqCst) == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("servers did not see the disconnect");
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_proxy_jump_reports_unreachable_target() {
+    use rust_expect::ExpectError;
+    use rust_expect::backend::ssh::{SshSession, SshSessionState};
+    use rust_expect::error::SshError;
+
+    let jump_port = test_server::start().await;
+    // Nothing listens on a port whose listener has been dropped
+    let closed_port = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+    let config = test_server_config(closed_port).proxy_jump(vec![test_server_config(jump_port)]);
+
+    let mut session = SshSession::new(config);
+    let err = session.connect_async().await.unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            ExpectError::Ssh(SshError::Connection { port, .. }) if *port == closed_port
+        ),
+        "unexpected error: {err}"
+    );
+    assert_eq!(session.state(), SshSessionState::Error);
+}
+
+#[test]
+fn ssh_config_keepalive() {
+    use rust_expect::backend::ssh::KeepaliveConfig;
+
+    let config = SshConfig::new("host").keepalive(KeepaliveConfig::aggressive());
+    assert_eq!(config.keepalive.interval, Duration::from_secs(15));
+    assert!(SshConfig::default().keepalive.enabled);
+}
+
+/// Connect to a fresh test server through a controllable link.
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+async fn connect_over_link(
+    keepalive: rust_expect::backend::ssh::KeepaliveConfig,
+) -> (
+    rust_expect::backend::ssh::SshSession,
+    tokio::sync::watch::Sender<test_server::Link>,
+) {
+    use rust_expect::backend::ssh::SshSession;
+
+    let (port, link) = test_server::link(test_server::start().await).await;
+    let mut session = SshSession::new(test_server_config(port).keepalive(keepalive));
+    session.connect_async().await.unwrap();
+    (session, link)
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_keepalive_detects_silent_drop() {
+    use rust_expect::backend::ssh::{KeepaliveConfig, SshSessionState};
+
+    let keepalive = KeepaliveConfig::new()
+        .interval(Duration::from_millis(100))
+        .max_missed(2);
+    let (session, link) = connect_over_link(keepalive).await;
+
+    // Answered keepalives keep an idle session up
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(session.is_alive());
+    assert_eq!(session.state(), SshSessionState::Connected);
+
+    link.send(test_server::Link::Stalled).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), session.closed())
+        .await
+        .expect("keepalive timeout was not detected");
+    assert!(!session.is_alive());
+    assert!(!session.is_connected());
+    assert_eq!(session.state(), SshSessionState::Error);
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_server_disconnect_flips_state() {
+    use rust_expect::backend::ssh::{KeepaliveConfig, SshSessionState};
+
+    let (mut session, link) = connect_over_link(KeepaliveConfig::disabled()).await;
+    assert!(session.is_alive());
+
+    link.send(test_server::Link::Cut).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), session.closed())
+        .await
+        .expect("disconnect was not detected");
+    assert_eq!(session.state(), SshSessionState::Error);
+    assert!(session.exec("true").await.is_err());
+
+    session.disconnect();
+    assert_eq!(session.state(), SshSessionState::Disconnected);
+    assert!(!session.is_alive());
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+// A current-thread runtime, so replacing the session must not block on it
+#[tokio::test]
+async fn ssh_health_check_reconnects_dropped_session() {
+    use rust_expect::health::{HealthCheckConfig, HealthChecker};
+    use rust_expect::util::RetryConfig;
+    use rust_expect::{Dialog, DialogStep, HealthStatus, Session};
+
+    let port = test_server::start().await;
+    let (link_port, link) = test_server::link(port).await;
+    let mut session = Session::spawn_ssh(test_server_config(link_port))
+        .await
+        .unwrap();
+
+    // Reconnects go through a front that drops the first connection
+    let front = test_server::refuse_first(port).await;
+    let login = Dialog::new()
+        .step(DialogStep::send_line("echo ready"))
+        .step(DialogStep::expect("ready").timeout(Duration::from_secs(5)));
+    let mut checker = HealthChecker::new(HealthCheckConfig::new().with_probe_command("echo alive"))
+        .with_reconnect(
+            test_server_config(front),
+            login,
+            RetryConfig::new(3)
+                .base_delay(Duration::from_millis(50))
+                .multiplier(1.0),
+        );
+
+    let status = checker
+        .check_ssh(&mut session, "alive", Duration::from_secs(5))
+        .await;
+    assert_eq!(status, HealthStatus::Healthy);
+    assert_eq!(checker.reconnect_attempts(), 0);
+
+    link.send(test_server::Link::Cut).unwrap();
+    let status = checker
+        .check_ssh(&mut session, "alive", Duration::from_secs(1))
+        .await;
+    assert_eq!(status, HealthStatus::Healthy);
+    assert_eq!(checker.reconnect_attempts(), 2);
+    let log = checker.reconnect_log();
+    assert_eq!((log[0].attempt, log[1].attempt), (1, 2));
+    assert!(log[0].error.is_some());
+    assert!(log[1].error.is_none());
+
+    // The replacement session is live
+    session.send_line("echo again").await.unwrap();
+    session.expect("again").await.unwrap();
+}
+
+/// A path in the temp directory unique to this test process.
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("rust-expect-{}-{name}", std::process::id()))
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_sftp_upload_and_download_round_trip() {
+    let session = connect_test_server().await;
+    let contents = b"line one\nline two\n".repeat(1000);
+    let local = temp_path("upload-src");
+    let remote = temp_path("upload-dst");
+    let back = temp_path("upload-back");
+    std::fs::write(&local, &contents).unwrap();
+
+    let remote_path = remote.to_str().unwrap();
+    let sent = session.upload(&local, remote_path).await.unwrap();
+    assert_eq!(sent, contents.len() as u64);
+    assert_eq!(std::fs::read(&remote).unwrap(), contents);
+
+    let received = session.download(remote_path, &back).await.unwrap();
+    assert_eq!(received, contents.len() as u64);
+    assert_eq!(std::fs::read(&back).unwrap(), contents);
+
+    for path in [local, remote, back] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_sftp_client_reads_writes_and_stats() {
+    use rust_expect::ExpectError;
+    use rust_expect::error::SshError;
+
+    let session = connect_test_server().await;
+    let sftp = session.sftp().await.unwrap();
+    let path = temp_path("sftp-client");
+    let remote = path.to_str().unwrap();
+
+    sftp.write(remote, b"hello").await.unwrap();
+    assert_eq!(sftp.read(remote).await.unwrap(), b"hello");
+    assert_eq!(sftp.stat(remote).await.unwrap().size, Some(5));
+
+    let missing = temp_path("sftp-missing");
+    let err = sftp.stat(missing.to_str().unwrap()).await.unwrap_err();
+    assert!(matches!(err, ExpectError::Ssh(SshError::Sftp { .. })));
+
+    sftp.close().await.unwrap();
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn ssh_config_identity_agent() {
+    let config = SshConfig::new("host").identity_agent("/run/agent.sock");
+    assert_eq!(
+        config.identity_agent.as_deref(),
+        Some(std::path::Path::new("/run/agent.sock"))
+    );
+    assert!(SshConfig::default().identity_agent.is_none());
+}
+
+/// Serve one agent connection that answers an identities request with no
+/// keys, returning the request it received.
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+fn fake_agent(path: &std::path::Path) -> tokio::task::JoinHandle<Vec<u8>> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let _ = std::fs::remove_file(path);
+    let listener = tokio::net::UnixListener::bind(path).unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let len = socket.read_u32().await.unwrap();
+        let mut request = vec![0; len as usize];
+        socket.read_exact(&mut request).await.unwrap();
+        // SSH_AGENT_IDENTITIES_ANSWER with zero keys
+        socket
+            .write_all(&[0, 0, 0, 5, 12, 0, 0, 0, 0])
+            .await
+            .unwrap();
+        request
+    })
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_forward_agent_proxies_agent_channel() {
+    use rust_expect::backend::ssh::{ChannelConfig, SshSession};
+    use tokio::io::AsyncReadExt;
+
+    let socket = temp_path("agent.sock");
+    let agent = fake_agent(&socket);
+
+    let (port, observed) = test_server::start_observed().await;
+    let mut session = SshSession::new(test_server_config(port).identity_agent(&socket));
+    session.connect_async().await.unwrap();
+
+    let config = ChannelConfig::default().no_pty().forward_agent(true);
+    let mut exec = session.exec_with_config("true", config).await.unwrap();
+    let mut output = Vec::new();
+    exec.read_to_end(&mut output).await.unwrap();
+
+    let request = tokio::time::timeout(Duration::from_secs(5), agent)
+        .await
+        .expect("agent channel was not proxied")
+        .unwrap();
+    // SSH_AGENTC_REQUEST_IDENTITIES, as the server sent it
+    assert_eq!(request, [11]);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while observed.agent_replies.lock().unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("server did not get the agent's reply");
+    assert_eq!(
+        observed.agent_replies.lock().unwrap().as_slice(),
+        [vec![12, 0, 0, 0, 0]]
+    );
+
+    let _ = std::fs::remove_file(socket);
+}
+
+#[cfg(all(unix, feature = "insecure-skip-verify"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ssh_agent_not_forwarded_by_default() {
+    use rust_expect::backend::ssh::SshSession;
+    use tokio::io::AsyncReadExt;
+
+    let (port, observed) = test_server::start_observed().await;
+    let mut session = SshSession::new(test_server_config(port));
+    session.connect_async().await.unwrap();
+
+    let mut exec = session.exec("true").await.unwrap();
+    let mut output = Vec::new();
+    exec.read_to_end(&mut output).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(observed.agent_replies.lock().unwrap().is_empty());
+}
@@ -3,7 +3,8 @@
 use std::time::Duration;
 
 use rust_expect::transcript::{
-    EventType, PlaybackOptions, PlaybackSpeed, PlayerState, TranscriptMetadata,
+    EventType, PlaybackOptions, PlaybackSpeed, PlayerState, TranscriptMetadata, read_asciicast,
+    write_asciicast,
 };
 use rust_expect::{Player, Recorder, Transcript, TranscriptEvent};
 
@@ -181,6 +182,52 @@ fn recorder_elapsed() {
     assert!(recorder.elapsed() >= Duration::from_millis(10));
 }
 
+#[test]
+fn recorder_markers_survive_asciicast_roundtrip() {
+    let recorder = Recorder::new(80, 24);
+    recorder.mark("setup");
+    recorder.record_output(b"installing\r\n");
+    recorder.mark("build");
+    recorder.record_output(b"compiling\r\n");
+    recorder.add_marker("done: 2 steps, ok");
+
+    let mut buf = Vec::new();
+    write_asciicast(&mut buf, &recorder.into_transcript()).unwrap();
+    let transcript = read_asciicast(buf.as_slice()).unwrap();
+
+    let markers: Vec<_> = transcript
+        .filter(EventType::Marker)
+        .into_iter()
+        .map(|e| String::from_utf8(e.data.clone()).unwrap())
+        .collect();
+    assert_eq!(markers, ["setup", "build", "done: 2 steps, ok"]);
+    assert!(
+        transcript
+            .events
+            .windows(2)
+            .all(|w| w[0].timestamp <= w[1].timestamp)
+    );
+    assert_eq!(transcript.events[2].event_type, EventType::Marker);
+}
+
+#[test]
+fn player_replays_past_markers() {
+    let mut transcript = Transcript::new(TranscriptMetadata::new(80, 24));
+    transcript.push(TranscriptEvent::output(Duration::ZERO, b"one "));
+    transcript.push(TranscriptEvent::marker(Duration::ZERO, "chapter 2"));
+    transcript.push(TranscriptEvent::output(Duration::ZERO, b"two"));
+
+    let mut player = Player::new(&transcript)
+        .with_options(PlaybackOptions::new().with_speed(PlaybackSpeed::Instant));
+    let mut out = Vec::new();
+    let mut markers = Vec::new();
+    player.play_to_with_markers(&mut out, &mut markers).unwrap();
+
+    assert_eq!(out, b"one two");
+    assert_eq!(markers, b"[marker] chapter 2\n");
+    assert_eq!(player.state(), PlayerState::Finished);
+}
+
 #[test]
 fn recorder_into_transcript() {
     let recorder = Recorder::new(80, 24);
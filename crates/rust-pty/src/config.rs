@@ -8,6 +8,8 @@ use std::ffi::OsString;
 use std::path::PathBuf;
 use std::time::Duration;
 
+use crate::error::{PtyError, Result};
+
 // Unix-specific imports for signal constants
 #[cfg(unix)]
 use libc;
@@ -40,16 +42,40 @@ pub struct PtyConfig {
     /// Environment variables to remove from inherited environment.
     pub env_remove: Vec<OsString>,
 
+    /// Whether to start from an empty environment instead of inheriting the
+    /// parent's. Only `env_add` is passed to the child. Ignored when `env`
+    /// is set.
+    pub clear_env: bool,
+
     /// Initial window size (columns, rows).
     pub window_size: (u16, u16),
 
     /// Whether to create a new session (Unix setsid).
+    ///
+    /// A controlling terminal can only be acquired by a session leader, so
+    /// a new session is also created whenever `controlling_terminal` is set.
     pub new_session: bool,
 
     /// Timeout for spawn operation.
     pub spawn_timeout: Option<Duration>,
 
-    /// Whether to use a controlling terminal (Unix).
+    /// Where the child's standard error is sent.
+    ///
+    /// Only [`StdioTarget::Pty`] is supported on Windows.
+    pub stderr: StdioTarget,
+
+    /// Terminal line discipline flags applied to the slave before exec.
+    /// Ignored on Windows.
+    pub termios: TermiosConfig,
+
+    /// Whether to make the PTY the child's controlling terminal (Unix).
+    ///
+    /// This implies a new session. With `new_session` set and this flag
+    /// cleared, the child leads a new session without a controlling
+    /// terminal: it still talks to the PTY through its standard streams but
+    /// cannot open `/dev/tty` and receives no terminal signals such as
+    /// `SIGINT` from Ctrl+C or `SIGWINCH`. With both cleared, the child
+    /// stays in the parent's session.
     #[cfg(unix)]
     pub controlling_terminal: bool,
 
@@ -65,9 +91,12 @@ impl Default for PtyConfig {
             env: None,
             env_add: HashMap::new(),
             env_remove: Vec::new(),
+            clear_env: false,
             window_size: (80, 24),
             new_session: true,
             spawn_timeout: None,
+            stderr: StdioTarget::Pty,
+            termios: TermiosConfig::default(),
             #[cfg(unix)]
             controlling_terminal: true,
             #[cfg(windows)]
@@ -91,14 +120,17 @@ impl PtyConfig {
 
     /// Get the effective environment for the child process.
     ///
-    /// This merges the base environment (inherited or explicit), adds
+    /// This merges the base environment (inherited, empty or explicit), adds
     /// variables from `env_add`, and removes variables from `env_remove`.
     #[must_use]
     pub fn effective_env(&self) -> HashMap<OsString, OsString> {
-        let mut env = self
-            .env
-            .clone()
-            .unwrap_or_else(|| std::env::vars_os().collect());
+        let mut env = self.env.clone().unwrap_or_else(|| {
+            if self.clear_env {
+                HashMap::new()
+            } else {
+                std::env::vars_os().collect()
+            }
+        });
 
         // Add additional variables
         env.extend(self.env_add.clone());
@@ -132,12 +164,13 @@ impl PtyConfigBuilder {
         self
     }
 
-    /// Set the complete environment for the child process.
+    /// Start the child with an empty environment.
     ///
-    /// This replaces the inherited environment entirely.
+    /// Nothing is inherited from the parent; only variables added with
+    /// [`env`](Self::env) are passed to the child.
     #[must_use]
-    pub fn env_clear(mut self) -> Self {
-        self.config.env = Some(HashMap::new());
+    pub const fn env_clear(mut self) -> Self {
+        self.config.clear_env = true;
         self
     }
 
@@ -163,6 +196,9 @@ impl PtyConfigBuilder {
     }
 
     /// Set whether to create a new session.
+    ///
+    /// See [`PtyConfig::new_session`] for how this interacts with the
+    /// controlling terminal setting on Unix.
     #[must_use]
     pub const fn new_session(mut self, value: bool) -> Self {
         self.config.new_session = value;
@@ -176,7 +212,27 @@ impl PtyConfigBuilder {
         self
     }
 
+    /// Set where the child's standard error is sent.
+    ///
+    /// On Windows any target other than [`StdioTarget::Pty`] makes spawning
+    /// fail with [`PtyError::Unsupported`](crate::PtyError::Unsupported).
+    #[must_use]
+    pub const fn stderr(mut self, target: StdioTarget) -> Self {
+        self.config.stderr = target;
+        self
+    }
+
+    /// Set the terminal line discipline flags applied before exec.
+    #[must_use]
+    pub const fn termios(mut self, termios: TermiosConfig) -> Self {
+        self.config.termios = termios;
+        self
+    }
+
     /// Set whether to use a controlling terminal (Unix only).
+    ///
+    /// Pass `false` to detach the child from the PTY as a terminal while
+    /// keeping it attached through its standard streams.
     #[cfg(unix)]
     #[must_use]
     pub const fn controlling_terminal(mut self, value: bool) -> Self {
@@ -199,10 +255,89 @@ impl PtyConfigBuilder {
     }
 }
 
+/// Line discipline flags for the PTY slave.
+///
+/// On Unix these are applied to the slave terminal before the child is
+/// executed, so they are in effect before it writes its first prompt. They
+/// have no effect on Windows, where `ConPTY` manages console modes itself.
+///
+/// The defaults match a freshly allocated terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TermiosConfig {
+    /// Echo input characters back through the master (`ECHO`).
+    pub echo: bool,
+    /// Canonical (line-buffered) input mode (`ICANON`).
+    pub canonical: bool,
+    /// Generate signals for interrupt, quit and suspend keys (`ISIG`).
+    pub isig: bool,
+}
+
+impl Default for TermiosConfig {
+    fn default() -> Self {
+        Self {
+            echo: true,
+            canonical: true,
+            isig: true,
+        }
+    }
+}
+
+impl TermiosConfig {
+    /// Set whether input is echoed.
+    #[must_use]
+    pub const fn echo(mut self, value: bool) -> Self {
+        self.echo = value;
+        self
+    }
+
+    /// Set whether canonical (line-buffered) input is used.
+    #[must_use]
+    pub const fn canonical(mut self, value: bool) -> Self {
+        self.canonical = value;
+        self
+    }
+
+    /// Set whether signal keys generate signals.
+    #[must_use]
+    pub const fn isig(mut self, value: bool) -> Self {
+        self.isig = value;
+        self
+    }
+}
+
+/// Destination for one of the child's standard streams.
+///
+/// `ConPTY` always owns the console streams, so only [`StdioTarget::Pty`] is
+/// supported on Windows; other targets make spawning fail with
+/// [`PtyError::Unsupported`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StdioTarget {
+    /// Connect the stream to the PTY, interleaved with the terminal output.
+    #[default]
+    Pty,
+    /// Inherit the stream from the parent process.
+    Inherit,
+    /// Connect the stream to a separate pipe.
+    ///
+    /// On Unix the read end is available from
+    /// [`UnixPtyChild::take_stderr`](crate::unix::UnixPtyChild::take_stderr).
+    Separate,
+}
+
 /// Cross-platform signal representation.
 ///
 /// This enum provides a unified interface for signals across Unix and Windows.
 /// On Windows, signals are emulated using console events or process control.
+///
+/// # Portability
+///
+/// [`Interrupt`](Self::Interrupt), [`Quit`](Self::Quit),
+/// [`Terminate`](Self::Terminate), [`Kill`](Self::Kill) and
+/// [`Hangup`](Self::Hangup) can be sent on every platform (see
+/// [`is_portable`](Self::is_portable)). The remaining signals are Unix-only;
+/// sending [`WindowChange`](Self::WindowChange) on Windows returns
+/// [`PtyError::Unsupported`], as the console is resized through the master.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum PtySignal {
@@ -213,7 +348,7 @@ pub enum PtySignal {
 
     /// Quit signal (Ctrl+\).
     /// - Unix: SIGQUIT (3)
-    /// - Windows: Not directly supported
+    /// - Windows: `CTRL_BREAK_EVENT`
     Quit,
 
     /// Terminate signal.
@@ -228,12 +363,12 @@ pub enum PtySignal {
 
     /// Hangup signal (terminal closed).
     /// - Unix: SIGHUP (1)
-    /// - Windows: `CTRL_CLOSE_EVENT`
+    /// - Windows: `TerminateProcess`
     Hangup,
 
     /// Window size change.
     /// - Unix: SIGWINCH (28)
-    /// - Windows: Handled via `ConPTY` resize
+    /// - Windows: Unsupported, use `PtyMaster::resize`
     WindowChange,
 
     /// Stop signal (Ctrl+Z).
@@ -262,6 +397,15 @@ pub enum PtySignal {
 }
 
 impl PtySignal {
+    /// Check whether the signal can be sent on every supported platform.
+    #[must_use]
+    pub const fn is_portable(self) -> bool {
+        matches!(
+            self,
+            Self::Interrupt | Self::Quit | Self::Terminate | Self::Kill | Self::Hangup
+        )
+    }
+
     /// Get the Unix signal number, if applicable.
     #[cfg(unix)]
     #[must_use]
@@ -316,6 +460,21 @@ impl WindowSize {
             ypixel,
         }
     }
+
+    /// Check that both dimensions are non-zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PtyError::InvalidWindowSize`] if `cols` or `rows` is zero.
+    pub const fn validate(&self) -> Result<()> {
+        if self.cols == 0 || self.rows == 0 {
+            return Err(PtyError::InvalidWindowSize {
+                width: self.cols,
+                height: self.rows,
+            });
+        }
+        Ok(())
+    }
 }
 
 impl Default for WindowSize {
@@ -347,6 +506,41 @@ mod tests {
         assert!(config.env_add.contains_key(&OsString::from("FOO")));
     }
 
+    #[test]
+    fn cleared_env_keeps_only_added_vars() {
+        let config = PtyConfig::builder()
+            .env_clear()
+            .env("PATH", "/usr/bin:/bin")
+            .build();
+
+        let env = config.effective_env();
+        assert_eq!(env.len(), 1);
+        assert_eq!(
+            env[&OsString::from("PATH")],
+            OsString::from("/usr/bin:/bin")
+        );
+    }
+
+    #[test]
+    fn portable_signals() {
+        assert!(PtySignal::Interrupt.is_portable());
+        assert!(PtySignal::Kill.is_portable());
+        assert!(!PtySignal::WindowChange.is_portable());
+    }
+
+    #[test]
+    fn window_size_validate() {
+        assert!(WindowSize::new(80, 24).validate().is_ok());
+        assert!(matches!(
+            WindowSize::new(0, 24).validate(),
+            Err(PtyError::InvalidWindowSize {
+                width: 0,
+                height: 24
+            })
+        ));
+        assert!(WindowSize::new(80, 0).validate().is_err());
+    }
+
     #[test]
     fn window_size_default() {
         let size = WindowSize::default();
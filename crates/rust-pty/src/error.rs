@@ -65,6 +65,13 @@ pub enum PtyError {
         height: u16,
     },
 
+    /// The operation is not supported on this platform.
+    #[error("operation not supported on this platform: {operation}")]
+    Unsupported {
+        /// Description of the unsupported operation.
+        operation: String,
+    },
+
     /// The operation timed out.
     #[error("operation timed out")]
     Timeout,
@@ -42,6 +42,7 @@
 
 pub mod config;
 pub mod error;
+pub mod termios;
 pub mod traits;
 
 #[cfg(unix)]
@@ -51,7 +52,7 @@ pub mod unix;
 pub mod windows;
 
 // Re-export primary types
-pub use config::{PtyConfig, PtyConfigBuilder, PtySignal, WindowSize};
+pub use config::{PtyConfig, PtyConfigBuilder, PtySignal, StdioTarget, TermiosConfig, WindowSize};
 pub use error::{PtyError, Result};
 pub use traits::{ExitStatus, PtyChild, PtyMaster, PtySystem};
 // Platform-specific re-exports
@@ -84,6 +85,19 @@ pub async fn spawn_shell() -> Result<(WindowsPtyMaster, WindowsPtyChild)> {
     WindowsPtySystem::spawn_shell(&PtyConfig::default()).await
 }
 
+/// The instant `timeout` from now.
+///
+/// A timeout too large to represent, such as [`Duration::MAX`], gives a
+/// deadline about 30 years out instead of overflowing.
+///
+/// [`Duration::MAX`]: std::time::Duration::MAX
+pub(crate) fn deadline_after(timeout: std::time::Duration) -> tokio::time::Instant {
+    const FAR_FUTURE: std::time::Duration = std::time::Duration::from_secs(86_400 * 365 * 30);
+
+    let now = tokio::time::Instant::now();
+    now.checked_add(timeout).unwrap_or_else(|| now + FAR_FUTURE)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,306 @@
+//! Terminal mode control for the local terminal.
+//!
+//! This module provides [`RawModeGuard`], which switches a terminal into raw
+//! or cbreak mode and restores the original settings when dropped. This is
+//! what an interactive session needs before forwarding keystrokes from a
+//! real TTY to a PTY child.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rust_pty::termios::RawModeGuard;
+//!
+//! let guard = RawModeGuard::new()?;
+//! guard.set_raw()?;
+//! // ... forward input to the child ...
+//! drop(guard); // original mode restored
+//! ```
+
+#[cfg(unix)]
+pub use unix::RawModeGuard;
+#[cfg(windows)]
+pub use windows::RawModeGuard;
+
+#[cfg(unix)]
+mod unix {
+    use std::io;
+    use std::os::unix::io::RawFd;
+
+    use crate::error::{PtyError, Result};
+
+    /// Guard that restores a terminal's original termios settings on drop.
+    ///
+    /// Creating the guard only captures the current settings; call
+    /// [`set_raw`](Self::set_raw) or [`set_cbreak`](Self::set_cbreak) to
+    /// change the mode.
+    pub struct RawModeGuard {
+        /// The terminal file descriptor.
+        fd: RawFd,
+        /// Settings captured when the guard was created.
+        original: libc::termios,
+    }
+
+    impl std::fmt::Debug for RawModeGuard {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("RawModeGuard")
+                .field("fd", &self.fd)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl RawModeGuard {
+        /// Capture the current settings of standard input.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`PtyError::GetAttributes`] if standard input is not a terminal.
+        pub fn new() -> Result<Self> {
+            Self::from_fd(libc::STDIN_FILENO)
+        }
+
+        /// Capture the current settings of the terminal behind `fd`.
+        ///
+        /// The descriptor is not owned by the guard and must stay open until
+        /// the guard is dropped.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`PtyError::GetAttributes`] if `fd` is not a terminal.
+        #[allow(unsafe_code)]
+        pub fn from_fd(fd: RawFd) -> Result<Self> {
+            // SAFETY: termios is plain data, and tcgetattr fully initializes it
+            // on success. An invalid fd only makes tcgetattr fail.
+            let mut original: libc::termios = unsafe { std::mem::zeroed() };
+            if unsafe { libc::tcgetattr(fd, &raw mut original) } == -1 {
+                return Err(PtyError::GetAttributes(io::Error::last_os_error()));
+            }
+
+            Ok(Self { fd, original })
+        }
+
+        /// Switch to raw mode: no echo, no line buffering, no signal keys and
+        /// no input or output processing.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`PtyError::SetAttributes`] if the settings cannot be applied.
+        #[allow(unsafe_code)]
+        pub fn set_raw(&self) -> Result<()> {
+            let mut termios = self.original;
+            // SAFETY: termios is a valid, initialized struct.
+            unsafe { libc::cfmakeraw(&raw mut termios) };
+            self.apply(&termios)
+        }
+
+        /// Switch to cbreak mode: input is available per keystroke without
+        /// echo, while signal keys such as Ctrl+C keep working.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`PtyError::SetAttributes`] if the settings cannot be applied.
+        pub fn set_cbreak(&self) -> Result<()> {
+            let mut termios = self.original;
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            self.apply(&termios)
+        }
+
+        /// Restore the settings captured when the guard was created.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`PtyError::SetAttributes`] if the settings cannot be applied.
+        pub fn restore(&self) -> Result<()> {
+            self.apply(&self.original)
+        }
+
+        #[allow(unsafe_code)]
+        fn apply(&self, termios: &libc::termios) -> Result<()> {
+            // SAFETY: termios points to a valid struct for the duration of the call.
+            if unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, termios) } == -1 {
+                return Err(PtyError::SetAttributes(io::Error::last_os_error()));
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for RawModeGuard {
+        fn drop(&mut self) {
+            let _ = self.restore();
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::io;
+
+    use windows_sys::Win32::Foundation::{HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Console::{
+        CONSOLE_MODE, ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT,
+        ENABLE_VIRTUAL_TERMINAL_INPUT, ENABLE_VIRTUAL_TERMINAL_PROCESSING, GetConsoleMode,
+        GetStdHandle, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE, SetConsoleMode,
+    };
+
+    use crate::error::{PtyError, Result};
+
+    /// Guard that restores the console's original input and output modes on drop.
+    ///
+    /// Creating the guard only captures the current modes; call
+    /// [`set_raw`](Self::set_raw) or [`set_cbreak`](Self::set_cbreak) to
+    /// change them.
+    pub struct RawModeGuard {
+        /// Console input handle.
+        input: HANDLE,
+        /// Console output handle.
+        output: HANDLE,
+        /// Input mode captured when the guard was created.
+        input_mode: CONSOLE_MODE,
+        /// Output mode captured when the guard was created.
+        output_mode: CONSOLE_MODE,
+    }
+
+    impl std::fmt::Debug for RawModeGuard {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("RawModeGuard")
+                .field("input_mode", &self.input_mode)
+                .field("output_mode", &self.output_mode)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl RawModeGuard {
+        /// Capture the current modes of the console's standard handles.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`PtyError::GetAttributes`] if the process has no console.
+        #[allow(unsafe_code)]
+        pub fn new() -> Result<Self> {
+            // SAFETY: GetStdHandle has no preconditions.
+            let input = unsafe { GetStdHandle(STD_INPUT_HANDLE) };
+            let output = unsafe { GetStdHandle(STD_OUTPUT_HANDLE) };
+
+            Ok(Self {
+                input,
+                output,
+                input_mode: console_mode(input)?,
+                output_mode: console_mode(output)?,
+            })
+        }
+
+        /// Switch to raw mode: no echo, no line buffering and no Ctrl+C
+        /// processing, with virtual terminal sequences enabled.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`PtyError::SetAttributes`] if the modes cannot be applied.
+        pub fn set_raw(&self) -> Result<()> {
+            let input = (self.input_mode
+                & !(ENABLE_ECHO_INPUT | ENABLE_LINE_INPUT | ENABLE_PROCESSED_INPUT))
+                | ENABLE_VIRTUAL_TERMINAL_INPUT;
+            self.apply(input, self.output_mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING)
+        }
+
+        /// Switch to cbreak mode: input is available per keystroke without
+        /// echo, while Ctrl+C keeps working.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`PtyError::SetAttributes`] if the modes cannot be applied.
+        pub fn set_cbreak(&self) -> Result<()> {
+            let input = self.input_mode & !(ENABLE_ECHO_INPUT | ENABLE_LINE_INPUT);
+            self.apply(input, self.output_mode)
+        }
+
+        /// Restore the modes captured when the guard was created.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`PtyError::SetAttributes`] if the modes cannot be applied.
+        pub fn restore(&self) -> Result<()> {
+            self.apply(self.input_mode, self.output_mode)
+        }
+
+        #[allow(unsafe_code)]
+        fn apply(&self, input: CONSOLE_MODE, output: CONSOLE_MODE) -> Result<()> {
+            // SAFETY: Both handles were returned by GetStdHandle and validated
+            // with GetConsoleMode.
+            if unsafe { SetConsoleMode(self.input, input) } == 0
+                || unsafe { SetConsoleMode(self.output, output) } == 0
+            {
+                return Err(PtyError::SetAttributes(io::Error::last_os_error()));
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for RawModeGuard {
+        fn drop(&mut self) {
+            let _ = self.restore();
+        }
+    }
+
+    /// Read the console mode of `handle`.
+    #[allow(unsafe_code)]
+    fn console_mode(handle: HANDLE) -> Result<CONSOLE_MODE> {
+        if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+            return Err(PtyError::GetAttributes(io::Error::last_os_error()));
+        }
+
+        let mut mode: CONSOLE_MODE = 0;
+        // SAFETY: handle is a valid standard handle and mode is a valid pointer.
+        if unsafe { GetConsoleMode(handle, &mut mode) } == 0 {
+            return Err(PtyError::GetAttributes(io::Error::last_os_error()));
+        }
+        Ok(mode)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::io::AsRawFd;
+
+    use super::*;
+    use crate::unix::{UnixPtyMaster, open_slave};
+
+    #[allow(unsafe_code)]
+    fn local_flags(fd: i32) -> libc::tcflag_t {
+        // SAFETY: termios is plain data, filled in by tcgetattr.
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            assert_eq!(libc::tcgetattr(fd, &raw mut termios), 0);
+            termios.c_lflag
+        }
+    }
+
+    #[tokio::test]
+    async fn raw_mode_restored_on_drop() {
+        let (_master, slave_path) = UnixPtyMaster::open().unwrap();
+        let slave = open_slave(&slave_path).unwrap();
+        let fd = slave.as_raw_fd();
+        assert_ne!(local_flags(fd) & libc::ICANON, 0);
+
+        let guard = RawModeGuard::from_fd(fd).unwrap();
+        guard.set_raw().unwrap();
+        assert_eq!(local_flags(fd) & (libc::ICANON | libc::ISIG), 0);
+
+        guard.set_cbreak().unwrap();
+        let flags = local_flags(fd);
+        assert_eq!(flags & (libc::ICANON | libc::ECHO), 0);
+        assert_ne!(flags & libc::ISIG, 0);
+
+        drop(guard);
+        assert_ne!(local_flags(fd) & libc::ICANON, 0);
+    }
+
+    #[test]
+    fn non_terminal_is_rejected() {
+        let file = std::fs::File::open("/dev/null").unwrap();
+        assert!(matches!(
+            RawModeGuard::from_fd(file.as_raw_fd()),
+            Err(crate::error::PtyError::GetAttributes(_))
+        ));
+    }
+}
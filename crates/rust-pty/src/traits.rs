@@ -28,10 +28,19 @@ pub trait PtyMaster: AsyncRead + AsyncWrite + Send + Sync + Unpin {
     /// Resize the PTY to the given window size.
     ///
     /// This sends a window size change notification to the child process
-    /// (SIGWINCH on Unix, `ConPTY` resize on Windows).
+    /// (`TIOCSWINSZ`/SIGWINCH on Unix, `ResizePseudoConsole` on Windows).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PtyError::InvalidWindowSize`](crate::PtyError::InvalidWindowSize)
+    /// if either dimension is zero, or [`PtyError::Resize`](crate::PtyError::Resize)
+    /// if the platform call fails.
     fn resize(&self, size: WindowSize) -> Result<()>;
 
     /// Get the current window size.
+    ///
+    /// On Unix this queries the terminal with `TIOCGWINSZ`; on Windows it
+    /// returns the size last applied through [`resize`](Self::resize).
     fn window_size(&self) -> Result<WindowSize>;
 
     /// Close the master side of the PTY.
@@ -80,6 +89,10 @@ pub trait PtyChild: Send + Sync {
     fn try_wait(&mut self) -> Result<Option<ExitStatus>>;
 
     /// Send a signal to the child process.
+    ///
+    /// Only [portable](PtySignal::is_portable) signals are guaranteed to be
+    /// deliverable everywhere; others may return
+    /// [`PtyError::Unsupported`](crate::PtyError::Unsupported).
     fn signal(&self, signal: PtySignal) -> Result<()>;
 
     /// Kill the child process.
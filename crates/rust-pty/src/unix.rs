@@ -33,7 +33,7 @@ use std::ffi::OsStr;
 
 pub use buffer::PtyBuffer;
 pub use child::{UnixPtyChild, spawn_child};
-pub use pty::{UnixPtyMaster, open_slave};
+pub use pty::{READ_CHUNK_SIZE, UnixPtyMaster, open_slave};
 pub use signals::{
     PtySignalEvent, SignalHandle, is_sigchld, is_sigwinch, on_window_change, sigchld, sigwinch,
     start_signal_handler,
@@ -86,6 +86,8 @@ pub type NativePtySystem = UnixPtySystem;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::PtySignal;
+    use crate::traits::ExitStatus;
 
     #[tokio::test]
     async fn spawn_shell() {
@@ -116,4 +118,196 @@ mod tests {
             master.close().ok();
         }
     }
+
+    #[tokio::test]
+    async fn read_into_bytes_mut_and_vectored() {
+        use std::io::IoSliceMut;
+
+        let config = PtyConfig::default();
+        let result = UnixPtySystem::spawn("printf", ["abcdefgh"], &config).await;
+
+        if let Ok((master, mut child)) = result {
+            child.wait().await.ok();
+
+            let mut head = [0u8; 3];
+            let mut tail = [0u8; 2];
+            let n = master
+                .read_vectored(&mut [IoSliceMut::new(&mut head), IoSliceMut::new(&mut tail)])
+                .await
+                .unwrap();
+            assert_eq!(n, 5);
+            assert_eq!(&head, b"abc");
+            assert_eq!(&tail, b"de");
+
+            let mut buf = bytes::BytesMut::new();
+            let n = std::future::poll_fn(|cx| master.poll_read_buf(cx, &mut buf))
+                .await
+                .unwrap();
+            assert_eq!(n, 3);
+            assert_eq!(&buf[..], b"fgh");
+        }
+    }
+
+    #[tokio::test]
+    async fn separate_stderr_pipe() {
+        use tokio::io::AsyncReadExt;
+
+        let config = PtyConfig::builder()
+            .stderr(crate::config::StdioTarget::Separate)
+            .build();
+        let result =
+            UnixPtySystem::spawn("sh", ["-c", "echo to_stdout; echo to_stderr >&2"], &config).await;
+
+        if let Ok((mut master, mut child)) = result {
+            let mut stderr = child.take_stderr().expect("stderr should be piped");
+            assert!(child.take_stderr().is_none());
+
+            let mut errors = String::new();
+            stderr.read_to_string(&mut errors).await.unwrap();
+            assert_eq!(errors, "to_stderr\n");
+
+            let mut output = vec![0u8; 256];
+            let n = master.read(&mut output).await.unwrap();
+            let output = String::from_utf8_lossy(&output[..n]);
+            assert!(output.contains("to_stdout"));
+            assert!(!output.contains("to_stderr"));
+
+            child.wait().await.ok();
+            master.close().ok();
+        }
+    }
+
+    /// Write a line to `cat` and count how often it comes back within 300ms.
+    async fn cat_round_trip(config: &PtyConfig) -> Option<usize> {
+        use std::time::Duration;
+
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (mut master, mut child) =
+            UnixPtySystem::spawn("cat", std::iter::empty::<&str>(), config)
+                .await
+                .ok()?;
+        master.write_all(b"marker\n").await.unwrap();
+
+        let mut output = Vec::new();
+        let mut buf = [0u8; 256];
+        while let Ok(Ok(n)) =
+            tokio::time::timeout(Duration::from_millis(300), master.read(&mut buf)).await
+        {
+            if n == 0 {
+                break;
+            }
+            output.extend_from_slice(&buf[..n]);
+        }
+
+        child.kill().ok();
+        master.close().ok();
+        Some(String::from_utf8_lossy(&output).matches("marker").count())
+    }
+
+    #[tokio::test]
+    async fn termios_echo_disabled_at_spawn() {
+        // Echoed input plus cat's own output
+        if let Some(count) = cat_round_trip(&PtyConfig::default()).await {
+            assert_eq!(count, 2);
+        }
+
+        // Only cat's output
+        let config = PtyConfig::builder()
+            .termios(crate::config::TermiosConfig::default().echo(false))
+            .build();
+        if let Some(count) = cat_round_trip(&config).await {
+            assert_eq!(count, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn spawn_with_cleared_env() {
+        let config = PtyConfig::builder()
+            .env_clear()
+            .env("PATH", "/usr/bin:/bin")
+            .build();
+        let result = UnixPtySystem::spawn("env", std::iter::empty::<&str>(), &config).await;
+
+        if let Ok((mut master, mut child)) = result {
+            child.wait().await.ok();
+            let output = master
+                .close_with_drain(std::time::Duration::from_secs(5))
+                .await
+                .unwrap();
+            let output = String::from_utf8_lossy(&output);
+            let vars: Vec<&str> = output.lines().map(str::trim_end).collect();
+            assert_eq!(vars, ["PATH=/usr/bin:/bin"]);
+        }
+    }
+
+    /// Report whether the child can open its controlling terminal.
+    async fn has_controlling_terminal(config: &PtyConfig) -> Option<bool> {
+        let script = "if (: < /dev/tty) 2>/dev/null; then echo has_tty; else echo no_tty; fi";
+        let (mut master, mut child) = UnixPtySystem::spawn("sh", ["-c", script], config)
+            .await
+            .ok()?;
+        child.wait().await.ok();
+
+        let output = master
+            .close_with_drain(std::time::Duration::from_secs(5))
+            .await
+            .unwrap();
+        Some(String::from_utf8_lossy(&output).contains("has_tty"))
+    }
+
+    #[tokio::test]
+    async fn new_session_without_controlling_terminal() {
+        if let Some(has_tty) = has_controlling_terminal(&PtyConfig::default()).await {
+            assert!(has_tty);
+        }
+
+        let config = PtyConfig::builder()
+            .new_session(true)
+            .controlling_terminal(false)
+            .build();
+        if let Some(has_tty) = has_controlling_terminal(&config).await {
+            assert!(!has_tty);
+        }
+    }
+
+    #[tokio::test]
+    async fn signal_terminates_child() {
+        let config = PtyConfig::default();
+        let result = UnixPtySystem::spawn("sleep", ["30"], &config).await;
+
+        if let Ok((mut master, mut child)) = result {
+            child.signal(PtySignal::Terminate).unwrap();
+            assert_eq!(
+                child.wait().await.unwrap(),
+                ExitStatus::Signaled(libc::SIGTERM)
+            );
+
+            master.close().ok();
+        }
+    }
+
+    #[tokio::test]
+    async fn try_wait_polls_without_blocking() {
+        let config = PtyConfig::default();
+        let result = UnixPtySystem::spawn("sh", ["-c", "sleep 0.2; exit 3"], &config).await;
+
+        if let Ok((mut master, mut child)) = result {
+            assert_eq!(child.try_wait().unwrap(), None);
+
+            let status = loop {
+                if let Some(status) = child.try_wait().unwrap() {
+                    break status;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            };
+            assert_eq!(status, ExitStatus::Exited(3));
+            assert!(!child.is_running());
+
+            // The reaped status stays available to `wait`
+            assert_eq!(child.wait().await.unwrap(), status);
+
+            master.close().ok();
+        }
+    }
 }
@@ -13,10 +13,10 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use rustix::process::{Pid, Signal, WaitStatus, kill_process};
-use tokio::process::Child as TokioChild;
+use tokio::process::{Child as TokioChild, ChildStderr};
 use tokio::sync::Mutex;
 
-use crate::config::{PtyConfig, PtySignal};
+use crate::config::{PtyConfig, PtySignal, StdioTarget, TermiosConfig};
 use crate::error::{PtyError, Result};
 use crate::traits::{ExitStatus, PtyChild};
 
@@ -33,6 +33,8 @@ pub struct UnixPtyChild {
     running: Arc<AtomicBool>,
     /// Cached exit status.
     exit_status: Arc<Mutex<Option<ExitStatus>>>,
+    /// Read end of the stderr pipe, when stderr is separate from the PTY.
+    stderr: Option<ChildStderr>,
 }
 
 impl std::fmt::Debug for UnixPtyChild {
@@ -46,14 +48,19 @@ impl std::fmt::Debug for UnixPtyChild {
 
 impl UnixPtyChild {
     /// Create a new child process handle.
+    ///
+    /// A piped stderr is taken from `child` and exposed through
+    /// [`take_stderr`](Self::take_stderr).
     #[must_use]
-    pub fn new(child: TokioChild) -> Self {
+    pub fn new(mut child: TokioChild) -> Self {
         let pid = child.id().expect("child should have pid");
+        let stderr = child.stderr.take();
         Self {
             child: Arc::new(Mutex::new(Some(child))),
             pid,
             running: Arc::new(AtomicBool::new(true)),
             exit_status: Arc::new(Mutex::new(None)),
+            stderr,
         }
     }
 
@@ -65,9 +72,18 @@ impl UnixPtyChild {
             pid,
             running: Arc::new(AtomicBool::new(true)),
             exit_status: Arc::new(Mutex::new(None)),
+            stderr: None,
         }
     }
 
+    /// Take the read end of the child's stderr pipe.
+    ///
+    /// Returns `Some` once when the child was spawned with
+    /// [`StdioTarget::Separate`], and `None` otherwise.
+    pub const fn take_stderr(&mut self) -> Option<ChildStderr> {
+        self.stderr.take()
+    }
+
     /// Get the process ID.
     #[must_use]
     pub const fn pid(&self) -> u32 {
@@ -143,6 +159,11 @@ impl UnixPtyChild {
     }
 
     /// Try to get the exit status without blocking.
+    ///
+    /// Returns `Ok(None)` if the process is still running. Like
+    /// [`std::process::Child::try_wait`], this reaps the process once it
+    /// has exited; the status is cached so later calls to `wait` or
+    /// `try_wait` return it again.
     pub fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
         use rustix::process::{WaitOptions, waitpid};
 
@@ -153,6 +174,21 @@ impl UnixPtyChild {
             return Ok(Some(s));
         }
 
+        // A tokio-owned child must be polled through tokio, otherwise reaping it
+        // here would make a later `wait` fail with ECHILD
+        if let Ok(mut child_guard) = self.child.try_lock()
+            && let Some(ref mut child) = *child_guard
+        {
+            return match child.try_wait().map_err(PtyError::Wait)? {
+                Some(status) => {
+                    let exit_status = convert_exit_status(status);
+                    self.cache_exit_status(exit_status);
+                    Ok(Some(exit_status))
+                }
+                None => Ok(None),
+            };
+        }
+
         let pid = Pid::from_raw(self.pid as i32).ok_or_else(|| {
             PtyError::Wait(io::Error::new(io::ErrorKind::InvalidInput, "invalid pid"))
         })?;
@@ -160,12 +196,7 @@ impl UnixPtyChild {
         match waitpid(Some(pid), WaitOptions::NOHANG) {
             Ok(Some((_pid, wait_status))) => {
                 let exit_status = convert_wait_status(wait_status);
-
-                self.running.store(false, Ordering::SeqCst);
-                if let Ok(mut guard) = self.exit_status.try_lock() {
-                    *guard = Some(exit_status);
-                }
-
+                self.cache_exit_status(exit_status);
                 Ok(Some(exit_status))
             }
             Ok(None) => Ok(None), // Still running
@@ -175,18 +206,25 @@ impl UnixPtyChild {
         }
     }
 
+    /// Record the exit status without blocking.
+    fn cache_exit_status(&self, exit_status: ExitStatus) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Ok(mut guard) = self.exit_status.try_lock() {
+            *guard = Some(exit_status);
+        }
+    }
+
     /// Send a signal to the child process.
     pub fn signal(&self, signal: PtySignal) -> Result<()> {
         if !self.is_running() {
             return Err(PtyError::ProcessExited(0));
         }
 
-        let sig_num = signal.as_unix_signal().ok_or_else(|| {
-            PtyError::Signal(io::Error::new(
-                io::ErrorKind::Unsupported,
-                "unsupported signal",
-            ))
-        })?;
+        let sig_num = signal
+            .as_unix_signal()
+            .ok_or_else(|| PtyError::Unsupported {
+                operation: format!("sending {signal:?}"),
+            })?;
 
         let pid = Pid::from_raw(self.pid as i32).ok_or_else(|| {
             PtyError::Signal(io::Error::new(io::ErrorKind::InvalidInput, "invalid pid"))
@@ -271,6 +309,21 @@ fn convert_exit_status(status: StdExitStatus) -> ExitStatus {
     }
 }
 
+/// Apply the configured line discipline flags to the slave terminal.
+fn configure_termios(slave_fd: &OwnedFd, termios: TermiosConfig) -> Result<()> {
+    use rustix::termios::{LocalModes, OptionalActions, tcgetattr, tcsetattr};
+
+    let mut attrs = tcgetattr(slave_fd)
+        .map_err(|e| PtyError::GetAttributes(io::Error::from_raw_os_error(e.raw_os_error())))?;
+
+    attrs.local_modes.set(LocalModes::ECHO, termios.echo);
+    attrs.local_modes.set(LocalModes::ICANON, termios.canonical);
+    attrs.local_modes.set(LocalModes::ISIG, termios.isig);
+
+    tcsetattr(slave_fd, OptionalActions::Now, &attrs)
+        .map_err(|e| PtyError::SetAttributes(io::Error::from_raw_os_error(e.raw_os_error())))
+}
+
 /// Spawn a child process in a PTY.
 ///
 /// This sets up the child's stdin/stdout/stderr to use the slave PTY
@@ -291,6 +344,9 @@ where
 
     use tokio::process::Command;
 
+    // Apply line discipline flags before the child can write anything
+    configure_termios(&slave_fd, config.termios)?;
+
     // Convert to raw fd for dup2
     let slave_raw = slave_fd.as_raw_fd();
 
@@ -312,17 +368,19 @@ where
     unsafe {
         cmd.stdin(Stdio::from_raw_fd(libc::dup(slave_raw)));
         cmd.stdout(Stdio::from_raw_fd(libc::dup(slave_raw)));
-        cmd.stderr(Stdio::from_raw_fd(libc::dup(slave_raw)));
+        cmd.stderr(match config.stderr {
+            StdioTarget::Inherit => Stdio::inherit(),
+            StdioTarget::Separate => Stdio::piped(),
+            _ => Stdio::from_raw_fd(libc::dup(slave_raw)),
+        });
     }
 
-    // Configure process
-    if config.new_session {
-        cmd.process_group(0);
-    }
-
-    // Pre-exec hook to set up controlling terminal
-    #[cfg(unix)]
-    if config.controlling_terminal {
+    // Pre-exec hook to set up the session and controlling terminal.
+    // setsid() also makes the child a process group leader, so it must not be
+    // combined with process_group(0): setsid() fails with EPERM for a process
+    // that already leads a group. A controlling terminal requires a session.
+    let controlling_terminal = config.controlling_terminal;
+    if config.new_session || controlling_terminal {
         // SAFETY: These are async-signal-safe operations
         unsafe {
             cmd.pre_exec(move || {
@@ -333,7 +391,9 @@ where
 
                 // Set controlling terminal
                 // Cast TIOCSCTTY to c_ulong for macOS compatibility (u32 -> u64)
-                if libc::ioctl(slave_raw, libc::c_ulong::from(libc::TIOCSCTTY), 0) == -1 {
+                if controlling_terminal
+                    && libc::ioctl(slave_raw, libc::c_ulong::from(libc::TIOCSCTTY), 0) == -1
+                {
                     return Err(io::Error::last_os_error());
                 }
 
@@ -357,4 +417,25 @@ mod tests {
         assert_eq!(child.pid(), 1234);
         assert!(child.is_running());
     }
+
+    #[test]
+    #[allow(clippy::zombie_processes)] // Reaped through UnixPtyChild instead
+    fn try_wait_caches_reaped_status() {
+        let process = std::process::Command::new("sh")
+            .args(["-c", "exit 7"])
+            .spawn()
+            .unwrap();
+        let mut child = UnixPtyChild::from_pid(process.id());
+
+        let status = loop {
+            if let Some(status) = child.try_wait().unwrap() {
+                break status;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert_eq!(status, ExitStatus::Exited(7));
+
+        // The pid has been reaped; the cached status is returned instead of ECHILD
+        assert_eq!(child.try_wait().unwrap(), Some(status));
+    }
 }
@@ -35,7 +35,7 @@ pub use child::{WindowsPtyChild, spawn_child};
 pub use conpty::{ConPty, is_conpty_available};
 pub use pipes::{PipePair, create_input_pipe, create_output_pipe, set_inheritable};
 
-use crate::config::{PtyConfig, WindowSize};
+use crate::config::{PtyConfig, StdioTarget, WindowSize};
 use crate::error::{PtyError, Result};
 use crate::traits::PtySystem;
 
@@ -65,6 +65,13 @@ impl PtySystem for WindowsPtySystem {
                 return Err(PtyError::ConPtyNotAvailable);
             }
 
+            // ConPTY owns the child's console streams
+            if config.stderr != StdioTarget::Pty {
+                return Err(PtyError::Unsupported {
+                    operation: format!("redirecting stderr to {:?} under ConPTY", config.stderr),
+                });
+            }
+
             // Create pipes
             let input_pipe = create_input_pipe()?;
             let output_pipe = create_output_pipe()?;
@@ -123,4 +130,14 @@ mod tests {
         // Just check that this doesn't panic
         let _ = is_conpty_available();
     }
+
+    #[tokio::test]
+    async fn separate_stderr_is_unsupported() {
+        let config = PtyConfig::builder().stderr(StdioTarget::Separate).build();
+        let result = WindowsPtySystem::spawn("cmd.exe", ["/c", "echo"], &config).await;
+        assert!(matches!(
+            result,
+            Err(PtyError::Unsupported { .. } | PtyError::ConPtyNotAvailable)
+        ));
+    }
 }
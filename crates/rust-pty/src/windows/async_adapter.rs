@@ -47,8 +47,8 @@ pub struct WindowsPtyMaster {
     resize_fn: Option<Box<dyn Fn(WindowSize) -> Result<()> + Send + Sync>>,
     /// Whether the PTY is open.
     open: Arc<AtomicBool>,
-    /// Current window size.
-    window_size: WindowSize,
+    /// Current window size, updated on successful resize.
+    window_size: Mutex<WindowSize>,
     /// Pending read state (protected by mutex for Sync).
     pending_read: Arc<Mutex<PendingReadState>>,
 }
@@ -57,7 +57,10 @@ impl std::fmt::Debug for WindowsPtyMaster {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("WindowsPtyMaster")
             .field("open", &self.open.load(Ordering::SeqCst))
-            .field("window_size", &self.window_size)
+            .field(
+                "window_size",
+                &self.window_size.lock().map(|size| *size).ok(),
+            )
             .finish()
     }
 }
@@ -75,11 +78,42 @@ impl WindowsPtyMaster {
             output: Arc::new(output),
             resize_fn: Some(Box::new(resize_fn)),
             open: Arc::new(AtomicBool::new(true)),
-            window_size: initial_size,
+            window_size: Mutex::new(initial_size),
             pending_read: Arc::new(Mutex::new(PendingReadState::Idle)),
         }
     }
 
+    /// Drain remaining output, then close the PTY master.
+    ///
+    /// Reads until the output pipe reaches EOF or `timeout` elapses, so the
+    /// last lines written by a child that is just exiting are not lost.
+    /// Whatever was drained is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails for a reason other than EOF. The
+    /// master is closed in either case.
+    pub async fn close_with_drain(&mut self, timeout: std::time::Duration) -> Result<Vec<u8>> {
+        use tokio::io::AsyncReadExt;
+
+        let deadline = crate::deadline_after(timeout);
+        let mut drained = Vec::new();
+        let mut buf = [0u8; 8192];
+
+        let result = loop {
+            match tokio::time::timeout_at(deadline, self.read(&mut buf)).await {
+                Ok(Ok(0)) | Err(_) => break Ok(()),
+                Ok(Ok(n)) => drained.extend_from_slice(&buf[..n]),
+                // The pipe breaks once ConPTY has closed its end
+                Ok(Err(e)) if e.kind() == io::ErrorKind::BrokenPipe => break Ok(()),
+                Ok(Err(e)) => break Err(PtyError::Io(e)),
+            }
+        };
+
+        self.close()?;
+        result.map(|()| drained)
+    }
+
     /// Create without resize support.
     pub fn without_resize(input: OwnedHandle, output: OwnedHandle) -> Self {
         Self {
@@ -87,7 +121,7 @@ impl WindowsPtyMaster {
             output: Arc::new(output),
             resize_fn: None,
             open: Arc::new(AtomicBool::new(true)),
-            window_size: WindowSize::default(),
+            window_size: Mutex::new(WindowSize::default()),
             pending_read: Arc::new(Mutex::new(PendingReadState::Idle)),
         }
     }
@@ -246,8 +280,13 @@ impl AsyncWrite for WindowsPtyMaster {
 
 impl PtyMaster for WindowsPtyMaster {
     fn resize(&self, size: WindowSize) -> Result<()> {
+        size.validate()?;
         if let Some(ref resize_fn) = self.resize_fn {
-            resize_fn(size)
+            resize_fn(size)?;
+            if let Ok(mut current) = self.window_size.lock() {
+                *current = size;
+            }
+            Ok(())
         } else {
             Err(PtyError::Resize(io::Error::new(
                 io::ErrorKind::Unsupported,
@@ -257,7 +296,10 @@ impl PtyMaster for WindowsPtyMaster {
     }
 
     fn window_size(&self) -> Result<WindowSize> {
-        Ok(self.window_size)
+        self.window_size
+            .lock()
+            .map(|size| *size)
+            .map_err(|_| PtyError::Io(io::Error::other("window size lock poisoned")))
     }
 
     fn close(&mut self) -> Result<()> {
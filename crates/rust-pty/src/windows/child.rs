@@ -34,6 +34,8 @@ const FALSE: BOOL = 0;
 const TRUE: BOOL = 1;
 /// Wait result when object is signaled (value 0)
 const WAIT_OBJECT_0: u32 = 0;
+/// Wait result when the wait itself failed
+const WAIT_FAILED: u32 = 0xFFFF_FFFF;
 
 use crate::config::{PtyConfig, PtySignal};
 use crate::error::{PtyError, Result};
@@ -116,6 +118,9 @@ impl WindowsPtyChild {
     }
 
     /// Try to get the exit status without blocking.
+    ///
+    /// Returns `Ok(None)` while the process is running. Once it has exited
+    /// the status is cached, so repeated calls keep returning it.
     pub fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
         if let Some(status) = self.exit_status {
             return Ok(Some(status));
@@ -126,6 +131,10 @@ impl WindowsPtyChild {
         // SAFETY: handle is valid
         let wait_result = unsafe { WaitForSingleObject(handle, 0) };
 
+        if wait_result == WAIT_FAILED {
+            return Err(PtyError::Wait(io::Error::last_os_error()));
+        }
+
         if wait_result == WAIT_OBJECT_0 {
             let mut exit_code: u32 = 0;
             // SAFETY: handle is valid
@@ -145,7 +154,9 @@ impl WindowsPtyChild {
 
     /// Send a signal to the child process.
     ///
-    /// On Windows, most signals are emulated or not supported.
+    /// `Interrupt` and `Quit` are delivered as `CTRL_C_EVENT` and
+    /// `CTRL_BREAK_EVENT`; `Terminate`, `Kill` and `Hangup` terminate the
+    /// process. Any other signal returns [`PtyError::Unsupported`].
     pub fn signal(&self, signal: PtySignal) -> Result<()> {
         use windows_sys::Win32::System::Console::{
             CTRL_BREAK_EVENT, CTRL_C_EVENT, GenerateConsoleCtrlEvent,
@@ -172,10 +183,10 @@ impl WindowsPtyChild {
                 // Terminate the process (Windows equivalent of SIGTERM/SIGKILL)
                 self.terminate_impl()
             }
-            PtySignal::WindowChange => {
-                // Window changes are handled via ConPTY resize
-                Ok(())
-            }
+            // Window changes are handled via ConPTY resize
+            _ => Err(PtyError::Unsupported {
+                operation: format!("sending {signal:?}"),
+            }),
         }
     }
 
@@ -415,6 +426,10 @@ fn build_environment_block(
         block.push(0);
     }
 
+    if block.is_empty() {
+        // An empty block still needs both terminators
+        block.push(0);
+    }
     block.push(0); // Double null terminator
     block
 }